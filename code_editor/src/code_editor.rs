@@ -761,6 +761,15 @@ impl CodeEditor {
                 keyboard_moved_cursor = true;
                 actions.push(CodeEditorAction::TextDidChange);
             }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyS,
+                modifiers: KeyModifiers {control, logo, ..},
+                ..
+            }) => {
+                if control || logo {
+                    actions.push(CodeEditorAction::SaveRequested);
+                }
+            }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::ReturnKey,
                 ..
@@ -1417,6 +1426,7 @@ impl CodeEditor {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    SaveRequested,
     None
 }
 
@@ -0,0 +1,164 @@
+//! Reading of color glyphs, either as layers of outlines in the `COLR`/`CPAL` tables
+//! (version 0), or as bitmaps in the `CBDT`/`sbix` tables, which `ttf_parser` already reads.
+
+use {
+    crate::{
+        makepad_vector::geometry::{Point, Rectangle},
+    },
+    makepad_rustybuzz::ttf_parser::{Face, GlyphId, RasterImageFormat, Tag},
+    makepad_zune_png::PngDecoder,
+};
+
+pub struct ColorLayer {
+    pub glyph_id: usize,
+    // None means the layer is drawn in the text color, which the atlas can't know
+    pub color: Option<[u8; 4]>,
+}
+
+pub struct ColorBitmap {
+    pub width: usize,
+    pub height: usize,
+    // non premultiplied rgba, top row first
+    pub data: Vec<[u8; 4]>,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+pub fn has_color_glyphs(face: &Face) -> bool {
+    face.raw_face().table(Tag::from_bytes(b"COLR")).is_some()
+        || face.tables().bdat.is_some()
+        || face.tables().sbix.is_some()
+}
+
+pub fn colr_layers(face: &Face, glyph_id: usize) -> Option<Vec<ColorLayer>> {
+    let colr = face.raw_face().table(Tag::from_bytes(b"COLR")) ?;
+    let base_count = read_u16(colr, 2) ? as usize;
+    let base_offset = read_u32(colr, 4) ? as usize;
+    let layer_offset = read_u32(colr, 8) ? as usize;
+
+    // the base glyph records are sorted by glyph id
+    let (mut lo, mut hi) = (0, base_count);
+    let (first_layer, layer_count) = loop {
+        if lo >= hi {
+            return None
+        }
+        let mid = (lo + hi) / 2;
+        let record = base_offset + mid * 6;
+        let id = read_u16(colr, record) ? as usize;
+        if id == glyph_id {
+            break (read_u16(colr, record + 2) ? as usize, read_u16(colr, record + 4) ? as usize)
+        }
+        else if id < glyph_id {
+            lo = mid + 1;
+        }
+        else {
+            hi = mid;
+        }
+    };
+
+    let cpal = face.raw_face().table(Tag::from_bytes(b"CPAL"));
+    let mut layers = Vec::new();
+    for i in first_layer..first_layer + layer_count {
+        let record = layer_offset + i * 4;
+        let palette_index = read_u16(colr, record + 2) ?;
+        layers.push(ColorLayer {
+            glyph_id: read_u16(colr, record) ? as usize,
+            color: if palette_index == 0xffff {None} else {cpal.and_then( | cpal | cpal_color(cpal, palette_index as usize))}
+        });
+    }
+    Some(layers)
+}
+
+// colors always come from the first palette
+fn cpal_color(cpal: &[u8], palette_index: usize) -> Option<[u8; 4]> {
+    let entry_count = read_u16(cpal, 2) ? as usize;
+    let records_offset = read_u32(cpal, 8) ? as usize;
+    let first_record = read_u16(cpal, 12) ? as usize;
+    if palette_index >= entry_count {
+        return None
+    }
+    let bgra = cpal.get(records_offset + (first_record + palette_index) * 4..) ?.get(..4) ?;
+    Some([bgra[2], bgra[1], bgra[0], bgra[3]])
+}
+
+// the bounds of a color glyph in font units, which for a layered glyph are those of all its
+// layers together, and for a bitmap those of the image in its strike
+pub fn color_glyph_bounds(face: &Face, glyph_id: usize) -> Option<Rectangle> {
+    if let Some(layers) = colr_layers(face, glyph_id) {
+        let mut bounds: Option<Rectangle> = None;
+        for layer in layers {
+            if let Some(rect) = face.glyph_bounding_box(GlyphId(layer.glyph_id as u16)) {
+                let rect = Rectangle::new(
+                    Point::new(rect.x_min as f64, rect.y_min as f64),
+                    Point::new(rect.x_max as f64, rect.y_max as f64),
+                );
+                bounds = Some(if let Some(bounds) = bounds {
+                    Rectangle::new(
+                        Point::new(bounds.p_min.x.min(rect.p_min.x), bounds.p_min.y.min(rect.p_min.y)),
+                        Point::new(bounds.p_max.x.max(rect.p_max.x), bounds.p_max.y.max(rect.p_max.y)),
+                    )
+                } else {rect});
+            }
+        }
+        return bounds
+    }
+    let image = face.glyph_raster_image(GlyphId(glyph_id as u16), u16::MAX) ?;
+    if image.format != RasterImageFormat::PNG || image.pixels_per_em == 0 {
+        return None
+    }
+    let scale = face.units_per_em() as f64 / image.pixels_per_em as f64;
+    Some(Rectangle::new(
+        Point::new(image.x as f64 * scale, image.y as f64 * scale),
+        Point::new((image.x as f64 + image.width as f64) * scale, (image.y as f64 + image.height as f64) * scale),
+    ))
+}
+
+pub fn color_glyph_bitmap(face: &Face, glyph_id: usize, pixels_per_em: f64) -> Option<ColorBitmap> {
+    let image = face.glyph_raster_image(GlyphId(glyph_id as u16), pixels_per_em.ceil().min(u16::MAX as f64) as u16) ?;
+    if image.format != RasterImageFormat::PNG {
+        return None
+    }
+    let mut decoder = PngDecoder::new(image.data);
+    let pixels = decoder.decode().ok() ?.u8() ?;
+    let (width, height) = decoder.get_dimensions() ?;
+    if width == 0 || height == 0 {
+        return None
+    }
+    let data = match pixels.len() / (width * height) {
+        4 => pixels.chunks_exact(4).map( | p | [p[0], p[1], p[2], p[3]]).collect(),
+        3 => pixels.chunks_exact(3).map( | p | [p[0], p[1], p[2], 255]).collect(),
+        2 => pixels.chunks_exact(2).map( | p | [p[0], p[0], p[0], p[1]]).collect(),
+        1 => pixels.iter().map( | p | [*p, *p, *p, 255]).collect(),
+        _ => return None
+    };
+    Some(ColorBitmap {width, height, data})
+}
+
+impl ColorBitmap {
+    // bilinear sample at (x, y) in pixels, with y going up from the bottom of the image,
+    // returned as premultiplied rgba
+    pub fn sample(&self, x: f64, y: f64) -> [f64; 4] {
+        let x = (x - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (self.height as f64 - y - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+        let mut out = [0.0; 4];
+        for (px, py, w) in [(x0, y0, (1.0 - fx) * (1.0 - fy)), (x1, y0, fx * (1.0 - fy)), (x0, y1, (1.0 - fx) * fy), (x1, y1, fx * fy)] {
+            let p = self.data[py * self.width + px];
+            // blend premultiplied so transparent texels don't bleed their color
+            let a = p[3] as f64 / 255.0;
+            out[0] += p[0] as f64 / 255.0 * a * w;
+            out[1] += p[1] as f64 / 255.0 * a * w;
+            out[2] += p[2] as f64 / 255.0 * a * w;
+            out[3] += a * w;
+        }
+        out
+    }
+}
@@ -19,7 +19,7 @@ use {
         icon_atlas::CxIconAtlasRc,
         font_atlas::CxFontsAtlasRc,
         draw_list_2d::DrawList2d,
-        turtle::{Turtle, TurtleWalk, Walk, AlignEntry},
+        turtle::{Turtle, TurtleWalk, TurtleDefer, Walk, AlignEntry},
     }
 };
 
@@ -40,6 +40,7 @@ pub struct Cx2d<'a> {
     pub draw_list_stack: Vec<DrawListId>,
     pub (crate) turtles: Vec<Turtle>,
     pub (crate) turtle_walks: Vec<TurtleWalk>,
+    pub (crate) turtle_defers: Vec<TurtleDefer>,
     pub (crate) turtle_clips: Vec<(DVec2, DVec2)>,
     pub (crate) align_list: Vec<AlignEntry>,
     pub fonts_atlas_rc: CxFontsAtlasRc,
@@ -88,6 +89,7 @@ impl<'a> Cx2d<'a> {
             draw_list_stack: Vec::new(),
             turtle_clips: Vec::new(),
             turtle_walks: Vec::new(),
+            turtle_defers: Vec::new(),
             turtles: Vec::new(),
             align_list: Vec::new(),
             nav_tree_rc,
@@ -1,4 +1,5 @@
 use sdfer::NDCursor as _;
+use crate::color_glyph;
 
 pub use {
     std::{
@@ -19,7 +20,7 @@ pub use {
         geometry::GeometryQuad2D,
         makepad_vector::font::Glyph,
         makepad_vector::trapezoidator::Trapezoidator,
-        makepad_vector::geometry::{AffineTransformation, Transform, Vector},
+        makepad_vector::geometry::{AffineTransformation, Transform, Vector, Rectangle},
         makepad_vector::internal_iter::ExtendFromInternalIterator,
         makepad_vector::path::PathIterator,
     },
@@ -28,13 +29,18 @@ pub use {
 
 pub(crate) const ATLAS_WIDTH: usize = 4096;
 pub(crate) const ATLAS_HEIGHT: usize = 4096;
+pub(crate) const COLOR_ATLAS_WIDTH: usize = 2048;
+pub(crate) const COLOR_ATLAS_HEIGHT: usize = 2048;
 
 pub struct CxFontsAtlas {
     pub fonts: Vec<Option<CxFont >>,
     pub path_to_font_id: HashMap<String, usize>,
     pub texture: Texture,
+    pub color_texture: Texture,
     pub clear_buffer: bool,
-    pub alloc: CxFontsAtlasAlloc
+    pub alloc: CxFontsAtlasAlloc,
+    pub color_alloc: CxFontsAtlasAlloc,
+    pub shape_cache: ShapeCache,
 }
 
 #[derive(Default)]
@@ -53,12 +59,22 @@ pub struct CxFontsAtlasSdfConfig {
 }
 
 impl CxFontsAtlas {
-    pub fn new(texture: Texture) -> Self {
+    pub fn new(texture: Texture, color_texture: Texture) -> Self {
         Self {
             fonts: Vec::new(),
             path_to_font_id: HashMap::new(),
             texture,
+            color_texture,
             clear_buffer: false,
+            shape_cache: ShapeCache::new(),
+            // color glyphs are copied as they are, so they need no sdf padding
+            color_alloc: CxFontsAtlasAlloc {
+                texture_size: DVec2 {
+                    x: COLOR_ATLAS_WIDTH as f64,
+                    y: COLOR_ATLAS_HEIGHT as f64
+                },
+                ..Default::default()
+            },
             alloc: CxFontsAtlasAlloc {
                 full: false,
                 texture_size: DVec2 {
@@ -83,6 +99,14 @@ impl CxFontsAtlas {
     }
 }
 impl CxFontsAtlasAlloc {
+    pub fn reset(&mut self) {
+        self.todo.clear();
+        self.full = false;
+        self.xpos = 0;
+        self.ypos = 0;
+        self.hmax = 0;
+    }
+    
     pub fn alloc_atlas_glyph(&mut self, w: f64, h: f64, todo: CxFontsAtlasTodo) -> CxFontAtlasGlyph {
         // In SDF mode, leave enough room around each glyph (i.e. padding).
         let pad = self.sdf.as_ref().map_or(0, |sdf| sdf.params.pad);
@@ -196,17 +220,29 @@ impl CxFontsAtlas {
                 cxfont.atlas_pages.clear();
             }
         }
-        self.alloc.todo.clear();
-        self.alloc.full = false;
-        self.alloc.xpos = 0;
-        self.alloc.ypos = 0;
-        self.alloc.hmax = 0;
+        self.alloc.reset();
+        self.color_alloc.reset();
         self.clear_buffer = true;
     }
     
     pub fn get_internal_font_atlas_texture_id(&self) -> Texture {
         self.texture.clone()
     }
+    
+    // The advance of `c` in the first of `font_ids` that has a glyph for it, in the units of the
+    // first font, so measuring can keep using the units per em of the primary font.
+    pub fn get_advance(&mut self, font_ids: &[usize], c: char) -> Option<f64> {
+        let units_per_em = self.fonts[*font_ids.first() ?].as_ref() ?.ttf_font.units_per_em;
+        for font_id in font_ids {
+            if let Some(cxfont) = &mut self.fonts[*font_id] {
+                let font_units_per_em = cxfont.ttf_font.units_per_em;
+                if let Some(glyph) = cxfont.get_glyph(c) {
+                    return Some(glyph.horizontal_metrics.advance_width * units_per_em / font_units_per_em)
+                }
+            }
+        }
+        None
+    }
 }
 
 impl<'a> Cx2d<'a> {
@@ -221,7 +257,13 @@ impl<'a> Cx2d<'a> {
                 unpack_row_length: None
             });
             
-            let fonts_atlas = CxFontsAtlas::new(texture);
+            let color_texture = Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32 {
+                width: COLOR_ATLAS_WIDTH,
+                height: COLOR_ATLAS_HEIGHT,
+                data: vec![],
+            });
+            
+            let fonts_atlas = CxFontsAtlas::new(texture, color_texture);
             cx.set_global(CxFontsAtlasRc(Rc::new(RefCell::new(fonts_atlas))));
         }
     }
@@ -238,7 +280,7 @@ impl<'a> Cx2d<'a> {
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
 
-        if fonts_atlas.alloc.full {
+        if fonts_atlas.alloc.full || fonts_atlas.color_alloc.full {
             fonts_atlas.reset_fonts_atlas();
         }
 
@@ -248,6 +290,9 @@ impl<'a> Cx2d<'a> {
         for todo in std::mem::take(&mut fonts_atlas.alloc.todo) {
             self.swrast_atlas_todo(fonts_atlas, todo, &mut reuse_sdfer_bufs);
         }
+        for todo in std::mem::take(&mut fonts_atlas.color_alloc.todo) {
+            self.swrast_color_atlas_todo(fonts_atlas, todo);
+        }
     }
 
     fn swrast_atlas_todo(
@@ -312,40 +357,7 @@ impl<'a> Cx2d<'a> {
             atlas_alloc_wh.y.ceil() as usize,
         );
 
-        let mut cur = ab_glyph_rasterizer::point(0.0, 0.0);
-        let to_ab = |p: makepad_vector::geometry::Point| ab_glyph_rasterizer::point(p.x as f32, p.y as f32);
-        commands
-        .fold(ab_glyph_rasterizer::Rasterizer::new(
-            glyph_rast.width(),
-            glyph_rast.height()
-        ), |mut rasterizer, cmd| match cmd {
-            makepad_vector::path::PathCommand::MoveTo(p) => {
-                cur = to_ab(p);
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::LineTo(p1) => {
-                let (p0, p1) = (cur, to_ab(p1));
-                rasterizer.draw_line(p0, p1);
-                cur = p1;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::ArcTo(..) => {
-                unreachable!("font glyphs should not use arcs");
-            }
-            makepad_vector::path::PathCommand::QuadraticTo(p1, p2) => {
-                let (p0, p1, p2) = (cur, to_ab(p1), to_ab(p2));
-                rasterizer.draw_quad(p0, p1, p2);
-                cur = p2;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::CubicTo(p1, p2, p3) => {
-                let (p0, p1, p2, p3) = (cur, to_ab(p1), to_ab(p2), to_ab(p3));
-                rasterizer.draw_cubic(p0, p1, p2, p3);
-                cur = p3;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::Close => rasterizer
-        })
+        rasterize_commands(commands, glyph_rast.width(), glyph_rast.height())
         .for_each_pixel_2d(|x, y, a| {
             glyph_rast[(x as usize, y as usize)] = sdfer::Unorm8::encode(a);
         });
@@ -385,18 +397,146 @@ impl<'a> Cx2d<'a> {
         }
         fonts_atlas.texture.swap_vec_u8(self.cx, &mut atlas_data);
     }
+    
+    fn swrast_color_atlas_todo(&mut self, fonts_atlas: &mut CxFontsAtlas, todo: CxFontsAtlasTodo) {
+        let cxfont = fonts_atlas.fonts[todo.font_id].as_mut().unwrap();
+        let units_per_em = cxfont.ttf_font.units_per_em;
+        let bounds = if let Some(bounds) = cxfont.get_color_glyph_bounds(todo.glyph_id) {bounds} else {return};
+        let atlas_page = &cxfont.atlas_pages[todo.atlas_page_id];
+        let glyphtc = *atlas_page.atlas_glyphs.get(&todo.glyph_id).unwrap();
+        let pixels_per_em = atlas_page.font_size * 96.0 / 72.0 * atlas_page.dpi_factor;
+        let font_scale_pixels = pixels_per_em / units_per_em;
+        
+        // the same padding and scaling to the allocated size as `swrast_atlas_todo`
+        let render_pad_dpx = 2.0;
+        let render_wh = dvec2(
+            ((bounds.p_max.x - bounds.p_min.x) * font_scale_pixels).ceil() + render_pad_dpx * 2.0,
+            ((bounds.p_max.y - bounds.p_min.y) * font_scale_pixels).ceil() + render_pad_dpx * 2.0,
+        );
+        let atlas_alloc_wh = dvec2(
+            (glyphtc.t2.x - glyphtc.t1.x) as f64 * fonts_atlas.color_alloc.texture_size.x + 1.0,
+            (glyphtc.t2.y - glyphtc.t1.y) as f64 * fonts_atlas.color_alloc.texture_size.y + 1.0,
+        );
+        let atlas_scaling = atlas_alloc_wh / render_wh;
+        let (width, height) = (atlas_alloc_wh.x.ceil() as usize, atlas_alloc_wh.y.ceil() as usize);
+        
+        // premultiplied rgba, bottom row first like the outline rasterizer
+        let mut glyph_out = vec![[0.0f64; 4]; width * height];
+        
+        let layers = cxfont.owned_font_face.with_ref( | face | color_glyph::colr_layers(face, todo.glyph_id));
+        if let Some(layers) = layers {
+            let transform = AffineTransformation::identity()
+                .translate(Vector::new(-bounds.p_min.x, -bounds.p_min.y))
+                .uniform_scale(font_scale_pixels)
+                .translate(Vector::new(render_pad_dpx, render_pad_dpx))
+                .scale(Vector::new(atlas_scaling.x, atlas_scaling.y));
+            for layer in layers {
+                // the text color isn't known here, so those layers are drawn white
+                let color = layer.color.unwrap_or([255; 4]).map( | c | c as f64 / 255.0);
+                let glyph = if let Ok(glyph) = cxfont.get_glyph_by_id(layer.glyph_id) {glyph} else {continue};
+                let commands = glyph.outline.iter().map( | command | command.transform(&transform));
+                rasterize_commands(commands, width, height).for_each_pixel_2d( | x, y, a | {
+                    let a = a as f64 * color[3];
+                    let dst = &mut glyph_out[y as usize * width + x as usize];
+                    for i in 0..3 {
+                        dst[i] = color[i] * a + dst[i] * (1.0 - a);
+                    }
+                    dst[3] = a + dst[3] * (1.0 - a);
+                });
+            }
+        }
+        else {
+            let bitmap = cxfont.owned_font_face.with_ref( | face | color_glyph::color_glyph_bitmap(face, todo.glyph_id, pixels_per_em));
+            let bitmap = if let Some(bitmap) = bitmap {bitmap} else {return};
+            let bitmap_scale = dvec2(
+                bitmap.width as f64 / ((bounds.p_max.x - bounds.p_min.x) * font_scale_pixels),
+                bitmap.height as f64 / ((bounds.p_max.y - bounds.p_min.y) * font_scale_pixels),
+            );
+            for y in 0..height {
+                for x in 0..width {
+                    let pos = (dvec2(x as f64 + 0.5, y as f64 + 0.5) / atlas_scaling - dvec2(render_pad_dpx, render_pad_dpx)) * bitmap_scale;
+                    if pos.x >= 0.0 && pos.y >= 0.0 && pos.x <= bitmap.width as f64 && pos.y <= bitmap.height as f64 {
+                        glyph_out[y * width + x] = bitmap.sample(pos.x, pos.y);
+                    }
+                }
+            }
+        }
+        
+        let mut atlas_data = vec![];
+        fonts_atlas.color_texture.swap_vec_u32(self.cx, &mut atlas_data);
+        let (atlas_w, atlas_h) = fonts_atlas.color_texture.get_format(self.cx).vec_width_height().unwrap();
+        if atlas_data.is_empty() {
+            atlas_data = vec![0; atlas_w * atlas_h];
+        }
+        let atlas_x0 = (glyphtc.t1.x as f64 * fonts_atlas.color_alloc.texture_size.x) as usize;
+        let atlas_y0 = (glyphtc.t1.y as f64 * fonts_atlas.color_alloc.texture_size.y) as usize;
+        for y in 0..height {
+            let dst = &mut atlas_data[(atlas_h - atlas_y0 - 1 - y) * atlas_w..][..atlas_w][atlas_x0..][..width];
+            for (x, dst) in dst.iter_mut().enumerate() {
+                let [r, g, b, a] = glyph_out[y * width + x];
+                // the atlas holds straight alpha, the shader premultiplies
+                let straight = | c: f64 | if a > 0.0 {((c / a).min(1.0) * 255.0) as u32} else {0};
+                *dst = ((a * 255.0) as u32) << 24 | straight(r) << 16 | straight(g) << 8 | straight(b);
+            }
+        }
+        fonts_atlas.color_texture.swap_vec_u32(self.cx, &mut atlas_data);
+    }
+}
+
+fn rasterize_commands(
+    commands: impl Iterator<Item = makepad_vector::path::PathCommand>,
+    width: usize,
+    height: usize,
+) -> ab_glyph_rasterizer::Rasterizer {
+    let mut cur = ab_glyph_rasterizer::point(0.0, 0.0);
+    let to_ab = |p: makepad_vector::geometry::Point| ab_glyph_rasterizer::point(p.x as f32, p.y as f32);
+    commands.fold(ab_glyph_rasterizer::Rasterizer::new(width, height), |mut rasterizer, cmd| match cmd {
+        makepad_vector::path::PathCommand::MoveTo(p) => {
+            cur = to_ab(p);
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::LineTo(p1) => {
+            let (p0, p1) = (cur, to_ab(p1));
+            rasterizer.draw_line(p0, p1);
+            cur = p1;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::ArcTo(..) => {
+            unreachable!("font glyphs should not use arcs");
+        }
+        makepad_vector::path::PathCommand::QuadraticTo(p1, p2) => {
+            let (p0, p1, p2) = (cur, to_ab(p1), to_ab(p2));
+            rasterizer.draw_quad(p0, p1, p2);
+            cur = p2;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::CubicTo(p1, p2, p3) => {
+            let (p0, p1, p2, p3) = (cur, to_ab(p1), to_ab(p2), to_ab(p3));
+            rasterizer.draw_cubic(p0, p1, p2, p3);
+            cur = p3;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::Close => rasterizer
+    })
 }
 
 pub struct CxFont {
     pub ttf_font: makepad_vector::font::TTFFont,
     pub owned_font_face: crate::owned_font_face::OwnedFace,
     pub atlas_pages: Vec<CxFontAtlasPage>,
-    pub shape_cache: ShapeCache,
+    // None for fonts without color tables, otherwise the bounds of each color glyph seen so far
+    pub color_glyphs: Option<HashMap<usize, Option<Rectangle>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapedGlyph {
+    pub font_id: usize,
+    pub glyph_id: usize,
 }
 
 pub struct ShapeCache {
-    pub keys: VecDeque<(Direction, Rc<str>)>,
-    pub glyph_ids: HashMap<(Direction, Rc<str>), Vec<usize>>,
+    pub keys: VecDeque<(Direction, Rc<[usize]>, Rc<str>)>,
+    pub glyph_ids: HashMap<(Direction, Rc<[usize]>, Rc<str>), Vec<ShapedGlyph>>,
 }
 
 impl ShapeCache {
@@ -411,24 +551,24 @@ impl ShapeCache {
     }
 
     // If there is an entry for the given key in the cache, returns the corresponding list of
-    // glyph indices for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
-    // compute the list of glyph indices for the key, inserts that in the cache and then returns
-    // the corresponding list.
+    // glyphs for that key. Otherwise, uses the given UnicodeBuffer and fonts to compute the list
+    // of glyphs for the key, inserts that in the cache and then returns the corresponding list.
+    //
+    // The key holds the ids of the font chain: the text is shaped with the first font, and every
+    // run of glyph clusters that font has no glyphs for is shaped again with the rest of the
+    // chain. Clusters that no font in the chain covers keep the glyphs of the first font.
     //
     // This method takes a UnicodeBuffer by value, and then returns the same buffer by value. This
     // is necessary because rustybuzz::shape consumes the UnicodeBuffer and then returns a
     // GlyphBuffer that reuses the same storage. Once we are done with the GlyphBuffer, we consume
     // it and then return yet another UnicodeBuffer that reuses the same storage. This allows us to
     // avoid unnecessary heap allocations.
-    //
-    // Note that owned_font_face should be the same as the CxFont to which this cache belongs,
-    // otherwise you will not get correct results.
     pub fn get_or_compute_glyph_ids(
         &mut self, 
-        key: (Direction, &str),
+        key: (Direction, &[usize], &str),
         mut rustybuzz_buffer: UnicodeBuffer,
-        owned_font_face: &crate::owned_font_face::OwnedFace
-    ) -> (&[usize], UnicodeBuffer) {
+        fonts: &[Option<CxFont>]
+    ) -> (&[ShapedGlyph], UnicodeBuffer) {
         if !self.glyph_ids.contains_key(&key as &dyn ShapeCacheKey) {
             if self.keys.len() == Self::MAX_SIZE {
                 for run in self.keys.drain(..Self::MAX_SIZE / 2) {
@@ -436,43 +576,90 @@ impl ShapeCache {
                 }
             }
 
-            let (direction, string) = key;
-            rustybuzz_buffer.set_direction(direction);
-            rustybuzz_buffer.push_str(string);
-            let glyph_buffer = owned_font_face.with_ref( | face | makepad_rustybuzz::shape(face, &[], rustybuzz_buffer));
-            let glyph_ids: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | glyph.glyph_id as usize).collect();
-            rustybuzz_buffer = glyph_buffer.clear();
+            let (direction, font_ids, string) = key;
+            let mut glyph_ids = Vec::new();
+            rustybuzz_buffer = Self::shape_with_fallbacks(&mut glyph_ids, direction, font_ids, string, rustybuzz_buffer, fonts);
 
+            let owned_font_ids: Rc<[usize]> = font_ids.into();
             let owned_string: Rc<str> = string.into();
-            self.keys.push_back((direction, owned_string.clone()));
-            self.glyph_ids.insert((direction, owned_string), glyph_ids);
+            self.keys.push_back((direction, owned_font_ids.clone(), owned_string.clone()));
+            self.glyph_ids.insert((direction, owned_font_ids, owned_string), glyph_ids);
         }
         (&self.glyph_ids[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
     }
+    
+    fn shape_with_fallbacks(
+        glyph_ids: &mut Vec<ShapedGlyph>,
+        direction: Direction,
+        font_ids: &[usize],
+        string: &str,
+        mut rustybuzz_buffer: UnicodeBuffer,
+        fonts: &[Option<CxFont>]
+    ) -> UnicodeBuffer {
+        let (font_id, fallbacks) = if let Some((first, rest)) = font_ids.split_first() {(*first, rest)} else {return rustybuzz_buffer};
+        let cxfont = if let Some(cxfont) = &fonts[font_id] {cxfont} else {
+            return Self::shape_with_fallbacks(glyph_ids, direction, fallbacks, string, rustybuzz_buffer, fonts)
+        };
+        
+        rustybuzz_buffer.set_direction(direction);
+        rustybuzz_buffer.push_str(string);
+        let glyph_buffer = cxfont.owned_font_face.with_ref( | face | makepad_rustybuzz::shape(face, &[], rustybuzz_buffer));
+        let shaped: Vec<(usize, usize)> = glyph_buffer.glyph_infos().iter().map( | glyph | (glyph.glyph_id as usize, glyph.cluster as usize)).collect();
+        rustybuzz_buffer = glyph_buffer.clear();
+        
+        let mut i = 0;
+        while i < shaped.len() {
+            if shaped[i].0 != 0 || fallbacks.is_empty() {
+                glyph_ids.push(ShapedGlyph {font_id, glyph_id: shaped[i].0});
+                i += 1;
+                continue;
+            }
+            // a run of missing glyphs covers the text from its first cluster up to the next
+            // cluster after its last one, in either direction
+            let mut end = i;
+            while end < shaped.len() && shaped[end].0 == 0 {
+                end += 1;
+            }
+            let start_byte = shaped[i..end].iter().map( | g | g.1).min().unwrap();
+            let last_byte = shaped[i..end].iter().map( | g | g.1).max().unwrap();
+            let end_byte = shaped.iter().map( | g | g.1).filter( | c | *c > last_byte).min().unwrap_or(string.len());
+            
+            let len = glyph_ids.len();
+            rustybuzz_buffer = Self::shape_with_fallbacks(glyph_ids, direction, fallbacks, &string[start_byte..end_byte], rustybuzz_buffer, fonts);
+            // if the fallbacks had nothing either, keep the tofu of the first font
+            if glyph_ids[len..].iter().all( | g | g.glyph_id == 0) {
+                glyph_ids.truncate(len);
+                glyph_ids.extend(shaped[i..end].iter().map( | g | ShapedGlyph {font_id, glyph_id: g.0}));
+            }
+            i = end;
+        }
+        rustybuzz_buffer
+    }
 }
 
-// When doing inserts on the shape cache, we want to use (Direction, Rc<str>) as our key type. When
-// doing lookups on the shape cache, we want to use (Direction, &str) as our key type.
-// Unfortunately, Rust does not allow this, since (Direction, Rc<str>) can only be borrowed as
-// &(Direction, Rc<str>). So we'd have to create a temporary key, and then borrow from that.
+// When doing inserts on the shape cache, we want to use (Direction, Rc<[usize]>, Rc<str>) as our key type. When
+// doing lookups on the shape cache, we want to use (Direction, &[usize], &str) as our key type.
+// Unfortunately, Rust does not allow this, since (Direction, Rc<[usize]>, Rc<str>) can only be borrowed as
+// &(Direction, Rc<[usize]>, Rc<str>). So we'd have to create a temporary key, and then borrow from that.
 //
 // This is unacceptable, because creating a temporary key requires us to do a heap allocation every
 // time we want to do a lookup on the shape cache, which is on a very hot path. Instead, we resort
 // to a bit of trickery, inspired by the following post on Stackoverflow:
 // https://stackoverflow.com/questions/45786717/how-to-implement-hashmap-with-two-keys/46044391#46044391
 //
-// The idea is that we cannot borrow (Direction, Rc<str>) as a (Direction, &str). But what we *can* do is
-// define a trait ShapeCacheKey to represent our key, with methods to access both the direction and the
-// string, implement that for both (Direction, Rc<str>) and (Direction, &str), and then borrow
-// (Direction, Rc<str>) as &dyn ShapeCacheKey (that is, a reference to a trait object). We can turn a
-// (Direction, &str) into a &dyn ShapeCacheKey without creating a temporary key or doing any heap
+// The idea is that we cannot borrow (Direction, Rc<[usize]>, Rc<str>) as a (Direction, &[usize], &str). But what we *can* do is
+// define a trait ShapeCacheKey to represent our key, with methods to access the direction, the font
+// ids and the string, implement that for both (Direction, Rc<[usize]>, Rc<str>) and (Direction, &[usize], &str), and then borrow
+// (Direction, Rc<[usize]>, Rc<str>) as &dyn ShapeCacheKey (that is, a reference to a trait object). We can turn a
+// (Direction, &[usize], &str) into a &dyn ShapeCacheKey without creating a temporary key or doing any heap
 // allocations, so this allows us to do what we want.
 pub trait ShapeCacheKey {
     fn direction(&self) -> Direction;
+    fn font_ids(&self) -> &[usize];
     fn string(&self) -> &str;
 }
 
-impl<'a> Borrow<dyn ShapeCacheKey + 'a> for (Direction, Rc<str>) {
+impl<'a> Borrow<dyn ShapeCacheKey + 'a> for (Direction, Rc<[usize]>, Rc<str>) {
     fn borrow(&self) -> &(dyn ShapeCacheKey + 'a) {
         self
     }
@@ -483,6 +670,7 @@ impl Eq for dyn ShapeCacheKey + '_ {}
 impl Hash for dyn ShapeCacheKey + '_ {
     fn hash<H: Hasher>(&self, hasher: &mut H) {
         self.direction().hash(hasher);
+        self.font_ids().hash(hasher);
         self.string().hash(hasher);
     }
 }
@@ -492,6 +680,9 @@ impl PartialEq for dyn ShapeCacheKey + '_ {
         if self.direction() != other.direction() {
             return false;
         }
+        if self.font_ids() != other.font_ids() {
+            return false;
+        }
         if self.string() != other.string() {
             return false;
         }
@@ -499,24 +690,32 @@ impl PartialEq for dyn ShapeCacheKey + '_ {
     }
 }
 
-impl ShapeCacheKey for (Direction, &str) {
+impl ShapeCacheKey for (Direction, &[usize], &str) {
     fn direction(&self) -> Direction {
         self.0
     }
 
-    fn string(&self) -> &str {
+    fn font_ids(&self) -> &[usize] {
         self.1
     }
+
+    fn string(&self) -> &str {
+        self.2
+    }
 }
 
-impl ShapeCacheKey for (Direction, Rc<str>) {
+impl ShapeCacheKey for (Direction, Rc<[usize]>, Rc<str>) {
     fn direction(&self) -> Direction {
         self.0
     }
 
-    fn string(&self) -> &str {
+    fn font_ids(&self) -> &[usize] {
         &self.1
     }
+
+    fn string(&self) -> &str {
+        &self.2
+    }
 }
 
 #[derive(Clone)]
@@ -543,11 +742,12 @@ impl CxFont {
     pub fn load_from_ttf_bytes(bytes: Rc<Vec<u8>>) -> Result<Self, crate::owned_font_face::FaceParsingError> {
         let owned_font_face = crate::owned_font_face::OwnedFace::parse(bytes, 0)?;
         let ttf_font = owned_font_face.with_ref(|face| makepad_vector::ttf_parser::from_ttf_parser_face(face));
+        let color_glyphs = owned_font_face.with_ref(|face| color_glyph::has_color_glyphs(face)).then(HashMap::new);
         Ok(Self {
             ttf_font,
             owned_font_face,
             atlas_pages: Vec::new(),
-            shape_cache: ShapeCache::new(),
+            color_glyphs,
         })
     }
     
@@ -567,11 +767,15 @@ impl CxFont {
     }
 
     pub fn get_glyph(&mut self, c:char)->Option<&Glyph>{
-        if c < '\u{10000}' {
-            Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
-        } else {
-            None
-        }
+        Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
+    }
+    
+    // The bounds of the glyph if the font draws it in color, in which case it goes in the color atlas.
+    pub fn get_color_glyph_bounds(&mut self, id: usize) -> Option<Rectangle> {
+        let color_glyphs = self.color_glyphs.as_mut()?;
+        *color_glyphs.entry(id).or_insert_with(|| {
+            self.owned_font_face.with_ref(|face| color_glyph::color_glyph_bounds(face, id))
+        })
     }
 
     pub fn get_glyph_by_id(&mut self, id: usize) -> makepad_vector::ttf_parser::Result<&Glyph> {
@@ -13,6 +13,7 @@ pub mod geometry;
 pub mod nav;
 pub mod icon_atlas;
 mod owned_font_face;
+mod color_glyph;
  
 pub use crate::{
     match_event::MatchEvent, 
@@ -23,9 +24,12 @@ pub use crate::{
         Align,
         Padding,
         Flow,
+        LayoutDirection,
         Size,
         TurtleAlignRange,
-        DeferWalk
+        DeferWalk,
+        GridTracks,
+        GridCell
     },
     overlay::{
         Overlay
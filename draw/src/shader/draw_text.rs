@@ -2,7 +2,7 @@ use {
     crate::{
         makepad_platform::*,
         turtle::{Walk, Size, Align},
-        font_atlas::{CxFontsAtlasTodo, CxFont, CxFontsAtlas, Font},
+        font_atlas::{CxFontsAtlasTodo, CxFontsAtlas, Font, ShapedGlyph},
         draw_list_2d::ManyInstances,
         geometry::GeometryQuad2D,
         cx_2d::Cx2d
@@ -22,6 +22,7 @@ live_design!{
         uniform sdf_cutoff: float
         
         texture tex: texture2d
+        texture color_tex: texture2d
         
         varying tex_coord1: vec2
         varying tex_coord2: vec2
@@ -75,7 +76,15 @@ live_design!{
             return self.blend_color(vec4(s * col.rgb * self.brightness * col.a, s * col.a));
         }
         
+        fn sample_color_glyph(self, pos:vec2)->vec4{
+            let c = sample2d(self.color_tex, pos);
+            return self.blend_color(vec4(c.rgb * c.a, c.a) * self.get_color().a);
+        }
+        
         fn pixel(self) -> vec4 {
+            if self.color_glyph > 0.5 {
+                return self.sample_color_glyph(self.tex_coord1.xy);
+            }
             let texel_coords = self.tex_coord1.xy;
             let dxt = length(dFdx(texel_coords));
             let dyt = length(dFdy(texel_coords));
@@ -142,6 +151,7 @@ const _: () = assert!(crate::font_atlas::ATLAS_HEIGHT == 4096);
 #[live_ignore]
 pub struct TextStyle {
     #[live()] pub font: Font,
+    #[live] pub font_fallbacks: Vec<Font>,
     #[live(9.0)] pub font_size: f64,
     #[live(1.0)] pub brightness: f32,
     #[live(0.5)] pub curve: f32,
@@ -150,6 +160,13 @@ pub struct TextStyle {
     #[live(1.3)] pub height_factor: f64,
 }
 
+impl TextStyle {
+    // the font followed by its fallbacks, in the order glyphs are looked up in
+    pub fn font_ids(&self) -> Vec<usize> {
+        self.font.font_id.into_iter().chain(self.font_fallbacks.iter().filter_map( | font | font.font_id)).collect()
+    }
+}
+
 #[derive(Clone, Live, LiveHook)]
 #[live_ignore]
 pub enum TextWrap {
@@ -207,20 +224,20 @@ impl<'a> WordIterator<'a> {
         };
     }
     
-    fn next_word(&mut self, font: &mut CxFont) -> Option<WordItem> {
+    fn next_word(&mut self, fonts_atlas: &mut CxFontsAtlas, font_ids: &[usize]) -> Option<WordItem> {
         if self.last_char == '\0'{
             return None
         }
         else if self.last_char == '\n'{ // return newline
             self.next_char();
             if self.ignore_newlines{
-                return self.next_word(font);
+                return self.next_word(fonts_atlas, font_ids);
             }
             return Some(WordItem::Newline);
         }
         else if self.last_char == ' '{
-            let adv = if let Some(glyph) = font.get_glyph(' ') {
-                glyph.horizontal_metrics.advance_width * self.font_size_total
+            let adv = if let Some(advance) = fonts_atlas.get_advance(font_ids, ' ') {
+                advance * self.font_size_total
             }else {0.0};
             let start = self.last_index;
             let mut width = 0.0;
@@ -253,8 +270,8 @@ impl<'a> WordIterator<'a> {
             let start = self.last_index;
             let mut width = 0.0;
             while self.last_char != ' ' && self.last_char != '\0' && self.last_char != '\n' {
-                let adv = if let Some(glyph) = font.get_glyph(self.last_char) {
-                    glyph.horizontal_metrics.advance_width * self.font_size_total
+                let adv = if let Some(advance) = fonts_atlas.get_advance(font_ids, self.last_char) {
+                    advance * self.font_size_total
                 }else {0.0};
                 if width + adv >= self.eval_width{
                     if start == self.last_index{// advance atleast one char
@@ -312,6 +329,7 @@ pub struct DrawText {
     #[calc] pub delta: Vec2,
     #[calc] pub shader_font_size: f32,
     #[calc] pub advance: f32,
+    #[calc] pub color_glyph: f32,
 }
 
 impl LiveHook for DrawText {
@@ -371,6 +389,7 @@ impl DrawText {
     
     pub fn update_draw_call_vars(&mut self, font_atlas: &CxFontsAtlas) {
         self.draw_vars.texture_slots[0] = Some(font_atlas.texture.clone());
+        self.draw_vars.texture_slots[1] = Some(font_atlas.color_texture.clone());
         self.draw_vars.user_uniforms[0] = self.text_style.brightness;
         self.draw_vars.user_uniforms[1] = self.text_style.curve;
         let (sdf_radius, sdf_cutoff) = font_atlas.alloc.sdf.as_ref()
@@ -404,18 +423,8 @@ impl DrawText {
             self.begin_many_instances_internal(cx, fonts_atlas);
         }
         
-        let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
         let dpi_factor = cx.current_dpi_factor();
-        
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
-        
-        let font = &mut cxfont.ttf_font;
-        let owned_font_face = &cxfont.owned_font_face;
-        
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
-        
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
+        let font_ids = self.text_style.font_ids();
         
         let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
         let zbias_step = 0.00001;
@@ -442,7 +451,7 @@ impl DrawText {
             for (run_level, run_range) in runs_with_level_and_range {
                 // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
                 // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
+                let (glyph_ids, new_rustybuzz_buffer) = fonts_atlas
                     .shape_cache
                     .get_or_compute_glyph_ids(
                     (
@@ -451,14 +460,24 @@ impl DrawText {
                             } else {
                                 makepad_rustybuzz::Direction::LeftToRight
                             },
+                            &font_ids,
                             &bidi_info.text[run_range]
                         ),
                         rustybuzz_buffer,
-                        owned_font_face
+                        &fonts_atlas.fonts
                     );
                 rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
-                    let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
+                for &ShapedGlyph {font_id, glyph_id} in glyph_ids {
+                    let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
+                    let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
+                    
+                    let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * cxfont.ttf_font.units_per_em);
+                    let font_size_pixels = font_size_logical * dpi_factor;
+                    
+                    // color glyphs take their bounds from their layers or bitmap, not the outline
+                    let color_bounds = cxfont.get_color_glyph_bounds(glyph_id);
+                    let glyph = cxfont.get_glyph_by_id(glyph_id).unwrap();
+                    let bounds = color_bounds.unwrap_or(glyph.bounds);
                     
                     let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
                     
@@ -467,14 +486,16 @@ impl DrawText {
                     // edges of the raster area, while the SDF padding exists for
                     // e.g. bilinear sampling to have excess texels to sample.
                     let pad_dpx = 2.0;
-                    let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                    let w_dpx = ((bounds.p_max.x - bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                    let h_dpx = ((bounds.p_max.y - bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
                     let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
-                                        
+                    
+                    let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
                     let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
                         // see if we can fit it
                         // allocate slot
-                        fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
+                        let alloc = if color_bounds.is_some() {&mut fonts_atlas.color_alloc} else {&mut fonts_atlas.alloc};
+                        alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
                             font_id,
                             atlas_page_id,
                             glyph_id,
@@ -485,8 +506,8 @@ impl DrawText {
                     let w = w_dpx * self.font_scale / dpi_factor;
                     let h = h_dpx * self.font_scale / dpi_factor;
                     
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
-                    let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
+                    let delta_x = font_size_logical * self.font_scale * bounds.p_min.x - pad;
+                    let delta_y = -(font_size_logical * self.font_scale * bounds.p_min.y - pad)
                         + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
                     // give the callback a chance to do things
                     //et scaled_min_pos_x = walk_x + delta_x;
@@ -500,6 +521,7 @@ impl DrawText {
                     self.delta.y = delta_y as f32;
                     self.shader_font_size = self.text_style.font_size as f32;
                     self.advance = advance as f32; //char_offset as f32;
+                    self.color_glyph = if color_bounds.is_some() {1.0} else {0.0};
                     char_depth += zbias_step;
                     mi.instances.extend_from_slice(self.draw_vars.as_slice());
                     walk_x += advance;
@@ -519,16 +541,17 @@ impl DrawText {
         if fonts_atlas.fonts[font_id].is_none() {
             return None
         }
+        let font_ids = self.text_style.font_ids();
         
         let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
-        let eval_width = cx.turtle().eval_width(walk.width, walk.margin, cx.turtle().layout().flow);
-        let eval_height = cx.turtle().eval_height(walk.height, walk.margin, cx.turtle().layout().flow);
+        let eval_size = cx.turtle().eval_walk(&walk);
+        let (eval_width, eval_height) = (eval_size.x, eval_size.y);
         
         match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
             TextWrap::Ellipsis => {
-                let ellip_width = if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph('.') {
-                    glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale
+                let ellip_width = if let Some(advance) = fonts_atlas.get_advance(&font_ids, '.') {
+                    advance * font_size_logical * self.font_scale
                 }
                 else {
                     0.0
@@ -541,8 +564,8 @@ impl DrawText {
                     if measured_width + ellip_width * 3.0 < eval_width {
                         ellip_pt = Some((i, measured_width, 3));
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(advance) = fonts_atlas.get_advance(&font_ids, c) {
+                        let adv = advance * font_size_logical * self.font_scale;
                         // ok so now what.
                         if measured_width + adv >= eval_width { // we have to drop back to ellip_pt
                             // if we don't have an ellip_pt, set it to 0
@@ -584,7 +607,7 @@ impl DrawText {
                     self.ignore_newlines,
                     self.combine_spaces,
                 );
-                while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
                     match word{
                         WordItem::Newline=>{
                             measured_height += line_height * self.text_style.line_spacing;
@@ -620,8 +643,8 @@ impl DrawText {
                     if c == '\n' {
                         measured_height += line_height * self.text_style.line_spacing;
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(advance) = fonts_atlas.get_advance(&font_ids, c) {
+                        let adv = advance * font_size_logical * self.font_scale;
                         measured_width += adv;
                     }
                     if measured_width > max_width {
@@ -670,8 +693,9 @@ impl DrawText {
             self.ignore_newlines,
             self.combine_spaces,
         );
+        let font_ids = self.text_style.font_ids();
         let mut last_rect = None;
-        while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+        while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
             match word{
                 WordItem::Newline=>{
                     cx.turtle_new_line();
@@ -681,7 +705,8 @@ impl DrawText {
                         abs_pos: None,
                         margin: Margin::default(),
                         width: Size::Fixed(width),
-                        height: Size::Fixed(line_drop)
+                        height: Size::Fixed(line_drop),
+                        ..Walk::default()
                     });
                     if last_rect.is_none(){
                         last_rect = Some(walk_rect)
@@ -747,7 +772,8 @@ impl DrawText {
                             abs_pos: walk.abs_pos,
                             margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
-                            height: Size::Fixed(height)
+                            height: Size::Fixed(height),
+                            ..walk
                         });
                         
                         // Ensure the chunk before the ellipsis is aligned down to a char boundary
@@ -775,7 +801,8 @@ impl DrawText {
                                 } else {
                                     geom.eval_height
                                 }
-                            )
+                            ),
+                            ..walk
                         });
                         let x_align = (geom.eval_width - geom.measured_width) * align.x;
                         self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, fonts_atlas);
@@ -786,7 +813,8 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(geom.measured_height)
+                        height: Size::Fixed(geom.measured_height),
+                        ..walk
                     });
                     let mut pos = dvec2(0.0, 0.0);
                     
@@ -797,7 +825,8 @@ impl DrawText {
                         self.ignore_newlines,
                         self.combine_spaces,    
                     );
-                    while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                    let font_ids = self.text_style.font_ids();
+                    while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
                         match word{
                             WordItem::Newline=>{
                                 pos.y += line_height * self.text_style.line_spacing;
@@ -820,7 +849,8 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.measured_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..walk
                     });
                     // lets do our y alignment
                     let mut ypos = 0.0;
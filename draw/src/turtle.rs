@@ -15,7 +15,8 @@ pub struct Layout {
     #[live] pub align: Align,
     #[live] pub flow: Flow,
     #[live] pub spacing: f64,
-    #[live] pub line_spacing: f64
+    #[live] pub line_spacing: f64,
+    #[live] pub direction: LayoutDirection
 }
 
 impl Default for Layout{
@@ -28,7 +29,8 @@ impl Default for Layout{
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
             spacing: 0.0,
-            line_spacing: 0.0
+            line_spacing: 0.0,
+            direction: LayoutDirection::Inherit
         }
     }
 }
@@ -40,6 +42,13 @@ pub struct Walk {
     #[live] pub margin: Margin,
     #[live] pub width: Size,
     #[live] pub height: Size,
+    #[live] pub min_width: Option<f64>,
+    #[live] pub max_width: Option<f64>,
+    #[live] pub min_height: Option<f64>,
+    #[live] pub max_height: Option<f64>,
+    // the number of columns and rows taken in a Flow::Grid, 0 counts as 1
+    #[live] pub column_span: usize,
+    #[live] pub row_span: usize,
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook, LiveRegister)]
@@ -71,6 +80,16 @@ impl Default for Axis2 {
     }
 }
 
+// Right to left mirrors the layout: flows run from the right, and the left of padding,
+// margins and align.x is read as the start and the right as the end
+#[derive(Copy, Clone, Debug, Default, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum LayoutDirection {
+    #[pick] #[default] Inherit,
+    Ltr,
+    Rtl
+}
+
 #[derive(Copy, Clone, Debug, Live, LiveHook)]
 #[live_ignore]
 pub enum Flow {
@@ -79,7 +98,12 @@ pub enum Flow {
     //Left,
     //Up,
     Overlay, 
-    RightWrap
+    RightWrap,
+    // the gap between columns is the spacing, the gap between rows the line_spacing
+    #[live(columns: GridTracks::default(), rows: GridTracks::default())] Grid{
+        columns: GridTracks,
+        rows: GridTracks
+    }
 }
 
 #[derive(Copy, Clone, Debug, Live)]
@@ -88,15 +112,61 @@ pub enum Size {
     #[pick] Fill,
     #[live(200.0)] Fixed(f64),
     Fit,
-    All
+    All,
+    // fills like Fill, sharing the space with its fill siblings in the ratio of the weights
+    #[live(1.0)] FillWeight(f64)
+}
+
+pub const GRID_MAX_TRACKS: usize = 16;
+
+// the track sizes of one axis of a Flow::Grid, packed so Layout stays small and Copy.
+// In live_design they are an array of sizes, columns: [200, Fill, FillWeight(2.0), Fit],
+// or a number of equally sized fill tracks, columns: 3
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GridTracks {
+    len: usize,
+    // >= 0 is a fixed size, < 0 a fill weight and NaN is fit
+    tracks: [f32; GRID_MAX_TRACKS]
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    pub column_span: usize,
+    pub row_span: usize
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct TurtleGrid {
+    column_gap: f64,
+    row_gap: f64,
+    // the x offset and width of every column, relative to the padded origin
+    columns: Vec<(f64, f64)>,
+    // the height of every row of the template, NaN for the ones that fit their content
+    template_rows: Vec<f64>,
+    // the height of every row the placement reached so far
+    rows: Vec<f64>,
+    // a bit for every occupied column, per row
+    occupied: Vec<u64>,
+    cursor: (usize, usize),
+    // the turtle walk index and cell of everything walked, to align it in its cell at the end
+    walks: Vec<(usize, GridCell)>,
+    deferred: Vec<GridCell>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TurtleDefer {
+    weight: f64,
+    min: Option<f64>,
+    max: Option<f64>
 }
 
 #[derive(Clone, Debug)]
 pub enum DeferWalk{
     Unresolved{
         defer_index: usize,
-        margin: Margin,
-        other_axis: Size,
+        walk: Walk,
         pos: DVec2
     },
     Resolved(Walk)
@@ -117,6 +187,7 @@ pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
     rect: Rect,
+    margin: Margin,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -126,6 +197,9 @@ pub struct Turtle {
     align_start: usize,
     turtle_walks_start: usize,
     defer_count: usize,
+    defer_start: usize,
+    grid: Option<Box<TurtleGrid>>,
+    rtl: bool,
     shift: DVec2,
     pos: DVec2,
     origin: DVec2,
@@ -156,10 +230,7 @@ impl<'a> Cx2d<'a> {
         let turtle = self.turtles.last_mut().unwrap();
         let defer_index = turtle.defer_count;
         let pos = turtle.pos;
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         let margin_size = walk.margin.size();
         match turtle.layout.flow {
             Flow::Right if walk.width.is_fill() => {
@@ -168,10 +239,14 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
                 turtle.defer_count += 1;
+                self.turtle_defers.push(TurtleDefer{
+                    weight: walk.width.fill_weight(),
+                    min: walk.min_width,
+                    max: walk.max_width
+                });
                 Some(DeferWalk::Unresolved{
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -181,13 +256,33 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
                 turtle.defer_count += 1;
+                self.turtle_defers.push(TurtleDefer{
+                    weight: walk.height.fill_weight(),
+                    min: walk.min_height,
+                    max: walk.max_height
+                });
                 Some(DeferWalk::Unresolved {
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
                     pos: pos + spacing
                 })
             },
+            // a fill height in a row that fits its content is only known once the row is done
+            Flow::Grid{..} if walk.height.is_fill() => {
+                let grid = turtle.grid.as_mut().unwrap();
+                let cell = grid.find_cell(walk.column_span, walk.row_span);
+                if !grid.cell_size(cell, false).y.is_nan() {
+                    return None
+                }
+                let cell = grid.place(walk.column_span, walk.row_span);
+                let defer_index = grid.deferred.len();
+                grid.deferred.push(cell);
+                Some(DeferWalk::Unresolved {
+                    defer_index,
+                    walk,
+                    pos: dvec2(0.0, 0.0)
+                })
+            },
             Flow::RightWrap if walk.width.is_fill() => {
                 error!("flow RightWrap does not support fill childnodes");
                 None
@@ -206,12 +301,15 @@ impl<'a> Cx2d<'a> {
     pub fn begin_pass_sized_turtle(&mut self, layout: Layout) {
         let pass_size = self.current_pass_size();
         self.align_list.push(AlignEntry::BeginTurtle(dvec2(0.0,0.0),pass_size));
-        let turtle = Turtle {
+        let mut turtle = Turtle {
             walk: Walk::fill(),
             layout,
             align_start: self.align_list.len() - 1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_start: self.turtle_defers.len(),
+            grid: None,
+            rtl: layout.direction.is_rtl(self.is_rtl()),
             pos: DVec2 {
                 x: layout.padding.left,
                 y: layout.padding.top
@@ -224,6 +322,7 @@ impl<'a> Cx2d<'a> {
             height_used: layout.padding.top,
             guard_area: Area::Empty,
         };
+        turtle.init_grid();
         self.turtles.push(turtle);
     }
     
@@ -234,6 +333,7 @@ impl<'a> Cx2d<'a> {
         //log!("{:?}", self.align_list[turtle.align_start]);
         self.align_list[turtle.align_start] = AlignEntry::SkipTurtle{skip:self.align_list.len()};
         self.turtle_walks.truncate(turtle.turtle_walks_start);
+        self.turtle_defers.truncate(turtle.defer_start);
     }
     
     pub fn end_pass_sized_turtle(&mut self){
@@ -245,6 +345,7 @@ impl<'a> Cx2d<'a> {
         //log!("{:?}", self.align_list[turtle.align_start]);
         self.align_list[turtle.align_start] = AlignEntry::SkipTurtle{skip:self.align_list.len()};
         self.turtle_walks.truncate(turtle.turtle_walks_start);
+        self.turtle_defers.truncate(turtle.defer_start);
     }
     
     pub fn end_pass_sized_turtle_with_shift(&mut self, area:Area, shift:DVec2){
@@ -260,17 +361,21 @@ impl<'a> Cx2d<'a> {
             skip: self.align_list.len()
         };
         self.turtle_walks.truncate(turtle.turtle_walks_start);
+        self.turtle_defers.truncate(turtle.defer_start);
     }
     
     pub fn begin_turtle_with_guard(&mut self, walk: Walk, layout: Layout, guard_area: Area) {
+        let rtl = layout.direction.is_rtl(self.turtles.last().map_or(self.is_rtl(), | parent | parent.rtl));
         let (origin, width, height, draw_clip) = if let Some(parent) = self.turtles.last() {
             
-            let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
+            let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else if let Some(cell) = parent.grid_peek(&walk) {
+                cell.pos
+            } else {
                 parent.pos + parent.child_spacing(self.turtle_walks.len())
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
+            let size = parent.eval_walk(&walk);
+            let (w, h) = (size.x, size.y);
             
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
@@ -296,18 +401,21 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
-            let w = walk.width.fixed_or_nan();
-            let h = walk.height.fixed_or_nan();
+            let w = walk.clamp_width(walk.width.fixed_or_nan());
+            let h = walk.clamp_height(walk.height.fixed_or_nan());
             
             (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
         };
         self.align_list.push(AlignEntry::BeginTurtle(draw_clip.0,draw_clip.1));
-        let turtle = Turtle {
+        let mut turtle = Turtle {
             walk,
             layout,
             align_start: self.align_list.len()-1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_start: self.turtle_defers.len(),
+            grid: None,
+            rtl,
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
                 y: origin.y + layout.padding.top
@@ -320,7 +428,7 @@ impl<'a> Cx2d<'a> {
             height_used: layout.padding.top,
             guard_area,
         };
-        
+        turtle.init_grid();
         self.turtles.push(turtle);
     }
     
@@ -338,38 +446,63 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        if let Some(grid) = &turtle.grid {
+            let size = grid.size();
+            let padded_origin = turtle.origin + turtle.layout.padding.left_top();
+            turtle.update_width_max(padded_origin.x, size.x);
+            turtle.update_height_max(padded_origin.y, size.y);
+        }
+        
         // computed width / height
         let w = if turtle.width.is_nan() {
-            Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
+            Size::Fixed(turtle.walk.clamp_width(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x))
         }
         else {
             Size::Fixed(turtle.width)
         };
         
         let h = if turtle.height.is_nan() {
-            Size::Fixed(turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y)
+            Size::Fixed(turtle.walk.clamp_height(turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y))
         }
         else {
             Size::Fixed(turtle.height)
         };
         
+        // right to left is laid out left to right and then every walk gets mirrored,
+        // which flips the alignment shifts below as well
+        let dir = if turtle.rtl {
+            let mirror = 2.0 * (turtle.origin.x + turtle.layout.scroll.x) + w.fixed_or_zero();
+            for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                // the margins swap sides as well
+                let TurtleWalk {rect, margin, align_start, ..} = self.turtle_walks[i];
+                let align_end = self.get_turtle_walk_align_end(i);
+                let dx = mirror - 2.0 * rect.pos.x - rect.size.x + margin.right - margin.left;
+                self.move_align_list(dx, 0.0, align_start, align_end, false, dvec2(0.0, 0.0));
+            }
+            -1.0
+        }
+        else {
+            1.0
+        };
+        
         match turtle.layout.flow {
             Flow::Right => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                    let sizes = self.turtle_defer_sizes(&turtle, turtle.width_left());
+                    // min/max constraints on the fills can leave space over, which aligns as usual
+                    let left = turtle.width_left() - sizes.iter().sum::<f64>();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
+                        let shift_x = sizes[..walk.defer_index].iter().sum::<f64>() + turtle.layout.align.x * left;
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
                 else {
@@ -379,25 +512,46 @@ impl<'a> Cx2d<'a> {
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
             },
             Flow::RightWrap=>{
                 if turtle.defer_count > 0{panic!()}
-                // for now we only support align:0,0
+                // align.x shifts every wrapped line on its own, align.y is not supported
+                if turtle.layout.align.x != 0.0 && !turtle.width.is_nan(){
+                    let right = turtle.origin.x + turtle.width - turtle.layout.padding.right;
+                    let mut line_start = turtle.turtle_walks_start;
+                    while line_start < self.turtle_walks.len(){
+                        let line_y = self.turtle_walks[line_start].rect.pos.y;
+                        let mut line_end = line_start;
+                        let mut line_right = f64::MIN;
+                        while line_end < self.turtle_walks.len() && self.turtle_walks[line_end].rect.pos.y == line_y{
+                            let rect = self.turtle_walks[line_end].rect;
+                            line_right = line_right.max(rect.pos.x + rect.size.x);
+                            line_end += 1;
+                        }
+                        let shift_x = turtle.layout.align.x * (right - line_right);
+                        if shift_x > 0.0{
+                            let align_start = self.turtle_walks[line_start].align_start;
+                            let align_end = self.get_turtle_walk_align_end(line_end - 1);
+                            self.move_align_list(dir * shift_x, 0.0, align_start, align_end, false, turtle.shift);
+                        }
+                        line_start = line_end;
+                    }
+                }
             }
             Flow::Down => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                    let sizes = self.turtle_defer_sizes(&turtle, turtle.height_left());
+                    let left = turtle.height_left() - sizes.iter().sum::<f64>();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
+                        let shift_y = sizes[..walk.defer_index].iter().sum::<f64>() + turtle.layout.align.y * left;
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
                 else {
@@ -407,7 +561,7 @@ impl<'a> Cx2d<'a> {
                         let shift_y = turtle.layout.align.y * turtle.height_left();
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
             },
@@ -418,12 +572,34 @@ impl<'a> Cx2d<'a> {
                     let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                     let align_start = walk.align_start;
                     let align_end = self.get_turtle_walk_align_end(i);
-                    self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                    self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                }
+            }
+            Flow::Grid{..} => {
+                // everything is aligned within its own cell
+                let grid = turtle.grid.as_ref().unwrap();
+                let mut cells = grid.walks.iter().peekable();
+                for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                    let walk = &self.turtle_walks[i];
+                    let (shift_x, shift_y) = if let Some((_, cell)) = cells.next_if( | (index, _) | *index == i) {
+                        let cell_size = grid.cell_size(*cell, true);
+                        (
+                            turtle.layout.align.x * (cell_size.x - walk.rect.size.x).max(0.0),
+                            turtle.layout.align.y * (cell_size.y - walk.rect.size.y).max(0.0)
+                        )
+                    }
+                    else {
+                        (0.0, 0.0)
+                    };
+                    let align_start = walk.align_start;
+                    let align_end = self.get_turtle_walk_align_end(i);
+                    self.move_align_list(dir * shift_x, shift_y, align_start, align_end, false, turtle.shift);
                 }
             }
         }
 
         self.turtle_walks.truncate(turtle.turtle_walks_start);
+        self.turtle_defers.truncate(turtle.defer_start);
         self.align_list.push(AlignEntry::EndTurtle);
         if self.turtles.len() == 0 {
             return Rect {
@@ -474,6 +650,9 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let turtle = self.turtles.last().unwrap();
+            if let Some(cell) = turtle.grid_peek(&walk) {
+                return cell.pos + walk.margin.left_top()
+            }
             turtle.pos + walk.margin.left_top()
         }
     }
@@ -481,16 +660,14 @@ impl<'a> Cx2d<'a> {
      fn walk_turtle_move(&mut self, walk: Walk, align_start: usize) -> Rect {
         
         let turtle = self.turtles.last_mut().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: 0,
-                rect: Rect {pos, size: size + walk.margin.size()}
+                rect: Rect {pos, size: size + walk.margin.size()},
+                margin: walk.margin
             });
             
             match turtle.layout.flow {
@@ -500,6 +677,10 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(pos.x, size.x);
                     turtle.update_height_max(pos.y,size.y);
                 }
+                Flow::Grid{..} => {
+                    turtle.update_width_max(pos.x, size.x + walk.margin.size().x);
+                    turtle.update_height_max(pos.y, size.y + walk.margin.size().y);
+                }
                 Flow::RightWrap=>{
                     panic!("Cannot use abs_pos in a flow::Rightwrap");
                 }
@@ -567,12 +748,25 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::Grid{..} => {
+                    let padded_origin = turtle.origin + turtle.layout.padding.left_top();
+                    let grid = turtle.grid.as_mut().unwrap();
+                    let cell = grid.place(walk.column_span, walk.row_span);
+                    if cell.row_span == 1 {
+                        grid.grow_row(cell.row, size.y + margin_size.y);
+                    }
+                    grid.walks.push((self.turtle_walks.len(), cell));
+                    pos = padded_origin + grid.cell_pos(cell);
+                    turtle.update_width_max(pos.x, size.x + margin_size.x);
+                    turtle.update_height_max(pos.y, size.y + margin_size.y);
+                }
             };
             
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index,
-                rect: Rect {pos, size: size + margin_size}
+                rect: Rect {pos: pos + spacing, size: size + margin_size},
+                margin: walk.margin
             });
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
         }
@@ -583,14 +777,14 @@ impl<'a> Cx2d<'a> {
             return Rect::default()
         }
         let turtle = self.turtles.last().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             Rect {pos: pos + walk.margin.left_top(), size}
         }
+        else if let Some(cell) = turtle.grid_peek(&walk) {
+            Rect {pos: cell.pos + walk.margin.left_top(), size}
+        }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let pos = turtle.pos;
@@ -600,6 +794,10 @@ impl<'a> Cx2d<'a> {
     
     pub fn turtle_new_line(&mut self){
         let turtle = self.turtles.last_mut().unwrap();
+        if let Some(grid) = &mut turtle.grid {
+            grid.new_row();
+            return
+        }
         turtle.pos.x = turtle.origin.x + turtle.layout.padding.left;
         let next_y = turtle.height_used + turtle.origin.y + turtle.layout.line_spacing;
         if turtle.pos.y == next_y{
@@ -787,6 +985,10 @@ impl Turtle {
         self.height_used = self.height_used.min((pos + dy) - self.origin.y);
     }
     
+    pub fn is_rtl(&self) -> bool {
+        self.rtl
+    }
+    
     pub fn set_shift(&mut self, shift: DVec2) {
         self.shift = shift;
     }
@@ -832,6 +1034,9 @@ impl Turtle {
                 Flow::RightWrap=>{
                     dvec2(self.layout.spacing, 0.0)
                 }
+                Flow::Grid{..} => {
+                    dvec2(0.0, 0.0)
+                }
             }
         }
         else {
@@ -875,7 +1080,7 @@ impl Turtle {
         return match width {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill | Size::FillWeight(_) => {
                 match flow {
                     Flow::RightWrap=> {
                         max_zero_keep_nan(self.width - (self.pos.x - self.origin.x) - margin.width() -self.layout.padding.right)
@@ -883,7 +1088,7 @@ impl Turtle {
                     Flow::Right => {
                         max_zero_keep_nan(self.width_left() - margin.width())
                     },
-                    Flow::Down | Flow::Overlay => {
+                    Flow::Down | Flow::Overlay | Flow::Grid{..} => {
                         let r = max_zero_keep_nan(self.width - self.layout.padding.width() - margin.width());
                         if r.is_nan() {
                             return self.width_used - margin.width() - self.layout.padding.right
@@ -900,9 +1105,9 @@ impl Turtle {
         return match height {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill | Size::FillWeight(_) => {
                 match flow {
-                    Flow::RightWrap | Flow::Right | Flow::Overlay => {
+                    Flow::RightWrap | Flow::Right | Flow::Overlay | Flow::Grid{..} => {
                         let r = max_zero_keep_nan(self.height - self.layout.padding.height() - margin.height());
                         if r.is_nan() {
                             return self.height_used - margin.height() - self.layout.padding.bottom
@@ -918,6 +1123,50 @@ impl Turtle {
         }
     }
     
+    // the size of a walk in this turtle, within its min/max constraints
+    pub fn eval_walk(&self, walk: &Walk) -> DVec2 {
+        let size = if let Some(cell) = self.grid_peek(walk) {
+            dvec2(
+                eval_in_cell(walk.width, walk.margin.width(), cell.size.x, self.width),
+                eval_in_cell(walk.height, walk.margin.height(), cell.size.y, self.height)
+            )
+        }
+        else {
+            dvec2(
+                self.eval_width(walk.width, walk.margin, self.layout.flow),
+                self.eval_height(walk.height, walk.margin, self.layout.flow)
+            )
+        };
+        dvec2(walk.clamp_width(size.x), walk.clamp_height(size.y))
+    }
+    
+    // the cell the walk would be placed in when this is a grid, its height is NaN while
+    // one of its rows fits its content
+    pub fn grid_peek(&self, walk: &Walk) -> Option<Rect> {
+        if walk.abs_pos.is_some() {
+            return None
+        }
+        let grid = self.grid.as_ref()?;
+        let cell = grid.find_cell(walk.column_span, walk.row_span);
+        Some(Rect {
+            pos: self.origin + self.layout.padding.left_top() + grid.cell_pos(cell),
+            size: grid.cell_size(cell, false)
+        })
+    }
+    
+    fn init_grid(&mut self) {
+        if let Flow::Grid{columns, rows} = &self.layout.flow {
+            self.grid = Some(Box::new(TurtleGrid::new(
+                columns,
+                rows,
+                self.width - self.layout.padding.width(),
+                self.height - self.layout.padding.height(),
+                self.layout.spacing,
+                self.layout.line_spacing
+            )));
+        }
+    }
+    
     pub fn rect(&self) -> Rect {
         Rect {
             pos: self.origin,
@@ -990,30 +1239,44 @@ impl DeferWalk {
     pub fn resolve(&mut self, cx: &Cx2d) -> Walk {
         match self{
             Self::Resolved(walk)=>{*walk},
-            Self::Unresolved{pos, defer_index, margin, other_axis}=>{
+            Self::Unresolved{pos, defer_index, walk}=>{
                 let turtle = cx.turtles.last().unwrap();
                 let walk = match turtle.layout.flow {
                     Flow::Right => {
-                        let left = turtle.width_left();
-                        let part = left / turtle.defer_count as f64;
+                        let sizes = cx.turtle_defer_sizes(turtle, turtle.width_left());
+                        let offset = sizes[..*defer_index].iter().sum::<f64>();
                         Walk {
-                            abs_pos: Some(*pos + dvec2(part * *defer_index as f64, 0.)),
-                            margin: *margin,
-                            width: Size::Fixed(part),
-                            height: *other_axis
+                            abs_pos: Some(*pos + dvec2(offset, 0.)),
+                            width: Size::Fixed(sizes[*defer_index]),
+                            ..*walk
                         }
                     },
                     Flow::RightWrap => {
                         panic!()
                     }
                     Flow::Down => { 
-                        let left = turtle.height_left();
-                        let part = left / turtle.defer_count as f64;
+                        let sizes = cx.turtle_defer_sizes(turtle, turtle.height_left());
+                        let offset = sizes[..*defer_index].iter().sum::<f64>();
                         Walk {
-                            abs_pos: Some(*pos + dvec2(0., part * *defer_index as f64)),
-                            margin: *margin,
-                            height: Size::Fixed(part),
-                            width: *other_axis
+                            abs_pos: Some(*pos + dvec2(0., offset)),
+                            height: Size::Fixed(sizes[*defer_index]),
+                            ..*walk
+                        }
+                    }
+                    Flow::Grid{..} => {
+                        let grid = turtle.grid.as_ref().unwrap();
+                        let cell = grid.deferred[*defer_index];
+                        let cell_size = grid.cell_size(cell, true);
+                        Walk {
+                            abs_pos: Some(turtle.origin + turtle.layout.padding.left_top() + grid.cell_pos(cell)),
+                            width: if walk.width.is_fill() {
+                                Size::Fixed(max_zero_keep_nan(cell_size.x - walk.margin.width()))
+                            }
+                            else {
+                                walk.width
+                            },
+                            height: Size::Fixed(max_zero_keep_nan(cell_size.y - walk.margin.height())),
+                            ..*walk
                         }
                     }
                     Flow::Overlay => panic!()
@@ -1026,6 +1289,41 @@ impl DeferWalk {
     
 }
 
+impl<'a> Cx2d<'a> {
+    // the sizes of the deferred fill walks of a turtle, sharing the space by weight
+    // within their min/max constraints
+    fn turtle_defer_sizes(&self, turtle: &Turtle, space: f64) -> Vec<f64> {
+        let defers = &self.turtle_defers[turtle.defer_start..turtle.defer_start + turtle.defer_count];
+        let mut sizes = vec![f64::NAN; defers.len()];
+        if space.is_nan() {
+            return sizes
+        }
+        let mut fixed = vec![false; defers.len()];
+        loop {
+            // the ones pushed outside their constraints get clamped, the others share again
+            let mut left = space;
+            let mut weights = 0.0;
+            for (i, defer) in defers.iter().enumerate() {
+                if fixed[i] {left -= sizes[i]} else {weights += defer.weight}
+            }
+            let mut clamped = false;
+            for (i, defer) in defers.iter().enumerate() {
+                if !fixed[i] {
+                    let part = if weights > 0.0 {left.max(0.0) * defer.weight / weights} else {0.0};
+                    sizes[i] = clamp_keep_nan(part, defer.min, defer.max);
+                    if sizes[i] != part {
+                        fixed[i] = true;
+                        clamped = true;
+                    }
+                }
+            }
+            if !clamped {
+                return sizes
+            }
+        }
+    }
+}
+
 impl Layout {
     pub fn flow_right() -> Self {
         Self {
@@ -1107,6 +1405,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Self::default()
         }
     }
     
@@ -1116,6 +1415,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Self::default()
         }
     }
 
@@ -1125,6 +1425,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(rect.size.x),
             height: Size::Fixed(rect.size.y),
+            ..Self::default()
         }
     }
     
@@ -1134,6 +1435,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(w),
             height: Size::Fixed(h),
+            ..Self::default()
         }
     }
         
@@ -1143,6 +1445,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Self::default()
         }
     }
     
@@ -1152,6 +1455,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1161,6 +1465,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fill,
+            ..Self::default()
         }
     }
     
@@ -1170,6 +1475,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1214,6 +1520,40 @@ impl Walk {
         self.margin.bottom += v.bottom;
         self
     }
+    
+    pub fn with_min_width(mut self, v: f64) -> Self {
+        self.min_width = Some(v);
+        self
+    }
+    
+    pub fn with_max_width(mut self, v: f64) -> Self {
+        self.max_width = Some(v);
+        self
+    }
+    
+    pub fn with_min_height(mut self, v: f64) -> Self {
+        self.min_height = Some(v);
+        self
+    }
+    
+    pub fn with_max_height(mut self, v: f64) -> Self {
+        self.max_height = Some(v);
+        self
+    }
+    
+    pub fn with_span(mut self, column_span: usize, row_span: usize) -> Self {
+        self.column_span = column_span;
+        self.row_span = row_span;
+        self
+    }
+    
+    pub fn clamp_width(&self, v: f64) -> f64 {
+        clamp_keep_nan(v, self.min_width, self.max_width)
+    }
+    
+    pub fn clamp_height(&self, v: f64) -> f64 {
+        clamp_keep_nan(v, self.min_height, self.max_height)
+    }
 }
 
 impl Padding {
@@ -1246,6 +1586,16 @@ impl LiveHook for Padding {
     }
 }
 
+impl LayoutDirection {
+    pub fn is_rtl(&self, inherited: bool) -> bool {
+        match self {
+            Self::Inherit => inherited,
+            Self::Ltr => false,
+            Self::Rtl => true
+        }
+    }
+}
+
 impl Default for Flow {
     fn default() -> Self {Self::Down}
 }
@@ -1356,10 +1706,274 @@ impl Size {
     
     pub fn is_fill(&self) -> bool {
         match self {
-            Self::Fill => true,
+            Self::Fill | Self::FillWeight(_) => true,
             _ => false
         }
     }
+    
+    pub fn fill_weight(&self) -> f64 {
+        match self {
+            Self::Fill => 1.0,
+            Self::FillWeight(v) => v.max(0.0),
+            _ => 0.0
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[Size]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks {
+            ret.push(*track);
+        }
+        ret
+    }
+    
+    pub fn fill(count: usize) -> Self {
+        let mut ret = Self::default();
+        for _ in 0..count {
+            ret.push(Size::Fill);
+        }
+        ret
+    }
+    
+    pub fn push(&mut self, track: Size) -> bool {
+        if self.len == GRID_MAX_TRACKS {
+            return false
+        }
+        self.tracks[self.len] = match track {
+            Size::Fixed(v) => v.max(0.0) as f32,
+            Size::Fill | Size::All => -1.0,
+            Size::FillWeight(v) => -(v.max(0.0) as f32),
+            Size::Fit => f32::NAN
+        };
+        self.len += 1;
+        true
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn get(&self, index: usize) -> Option<Size> {
+        if index >= self.len {
+            return None
+        }
+        let v = self.tracks[index];
+        Some(if v.is_nan() {
+            Size::Fit
+        }
+        else if v == -1.0 {
+            Size::Fill
+        }
+        else if v < 0.0 {
+            Size::FillWeight(-v as f64)
+        }
+        else {
+            Size::Fixed(v as f64)
+        })
+    }
+}
+
+impl LiveHook for GridTracks {}
+impl LiveApply for GridTracks {
+    fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        match &nodes[index].value {
+            LiveValue::Int64(v) => {
+                *self = Self::fill((*v).max(0) as usize);
+                if *v as usize > GRID_MAX_TRACKS {
+                    cx.apply_error(live_error_origin!(), index, nodes, format!("a grid has at most {} tracks per axis", GRID_MAX_TRACKS));
+                }
+                index + 1
+            }
+            LiveValue::Array => {
+                *self = Self::default();
+                let mut index = index + 1;
+                while !nodes[index].is_close() {
+                    let mut track = Size::Fill;
+                    let next = track.apply(cx, apply, index, nodes);
+                    if !self.push(track) {
+                        cx.apply_error(live_error_origin!(), index, nodes, format!("a grid has at most {} tracks per axis", GRID_MAX_TRACKS));
+                    }
+                    index = next;
+                }
+                index + 1
+            }
+            _ => {
+                cx.apply_error_wrong_value_type_for_primitive(live_error_origin!(), index, nodes, "GridTracks");
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+impl LiveNew for GridTracks {
+    fn new(_cx: &mut Cx) -> Self {
+        Self::default()
+    }
+    
+    fn live_type_info(_cx: &mut Cx) -> LiveTypeInfo {
+        LiveTypeInfo {
+            module_id: LiveModuleId::from_str(&module_path!()).unwrap(),
+            live_type: LiveType::of::<Self>(),
+            fields: Vec::new(),
+            live_ignore: true,
+            type_name: id_lut!(GridTracks)
+        }
+    }
+}
+
+impl TurtleGrid {
+    fn new(columns: &GridTracks, rows: &GridTracks, width: f64, height: f64, column_gap: f64, row_gap: f64) -> Self {
+        // columns are sized up front so a cell's width is known when it is walked,
+        // which is why fit columns fill instead. Without a template there is one column
+        let column_count = columns.len().clamp(1, 64);
+        let mut x = 0.0;
+        let columns = grid_track_sizes(columns, column_count, width, column_gap, true).into_iter().map( | w | {
+            let w = if w.is_nan() {0.0} else {w};
+            let column = (x, w);
+            x += w + column_gap;
+            column
+        }).collect();
+        Self {
+            column_gap,
+            row_gap,
+            columns,
+            template_rows: grid_track_sizes(rows, rows.len(), height, row_gap, false),
+            ..Self::default()
+        }
+    }
+    
+    // finds the first free cell from the cursor on, in reading order
+    pub fn find_cell(&self, column_span: usize, row_span: usize) -> GridCell {
+        let column_span = column_span.clamp(1, self.columns.len());
+        let row_span = row_span.max(1);
+        let (mut row, mut column) = self.cursor;
+        loop {
+            if column + column_span > self.columns.len() {
+                row += 1;
+                column = 0;
+                continue;
+            }
+            let mask = (u64::MAX >> (64 - column_span)) << column;
+            if (row..row + row_span).all( | row | self.occupied.get(row).map_or(true, | occupied | occupied & mask == 0)) {
+                return GridCell {column, row, column_span, row_span}
+            }
+            column += 1;
+        }
+    }
+    
+    pub fn place(&mut self, column_span: usize, row_span: usize) -> GridCell {
+        let cell = self.find_cell(column_span, row_span);
+        let mask = (u64::MAX >> (64 - cell.column_span)) << cell.column;
+        for row in cell.row..cell.row + cell.row_span {
+            if self.occupied.len() <= row {
+                self.occupied.resize(row + 1, 0);
+            }
+            self.occupied[row] |= mask;
+        }
+        // rows before the cursor are done, so their heights are final from here on
+        while self.rows.len() <= cell.row {
+            let row = self.rows.len();
+            self.rows.push(self.template_row(row).unwrap_or(0.0));
+        }
+        self.cursor = (cell.row, cell.column + cell.column_span);
+        cell
+    }
+    
+    pub fn new_row(&mut self) {
+        if self.cursor.1 > 0 {
+            self.cursor = (self.cursor.0 + 1, 0);
+        }
+    }
+    
+    // grows a row that fits its content
+    fn grow_row(&mut self, row: usize, height: f64) {
+        if self.template_row(row).is_none() && !height.is_nan() {
+            self.rows[row] = self.rows[row].max(height);
+        }
+    }
+    
+    // the height of a row of the template, None if it fits its content
+    fn template_row(&self, row: usize) -> Option<f64> {
+        self.template_rows.get(row).copied().filter( | h | !h.is_nan())
+    }
+    
+    fn row_height(&self, row: usize) -> f64 {
+        if let Some(h) = self.rows.get(row) {*h} else {self.template_row(row).unwrap_or(0.0)}
+    }
+    
+    pub fn cell_pos(&self, cell: GridCell) -> DVec2 {
+        let y = (0..cell.row).map( | row | self.row_height(row) + self.row_gap).sum();
+        dvec2(self.columns[cell.column].0, y)
+    }
+    
+    // the size of a cell, with a NaN height while one of its rows still fits its content
+    // unless `measured` asks for the height of the content so far
+    pub fn cell_size(&self, cell: GridCell, measured: bool) -> DVec2 {
+        let columns = &self.columns[cell.column..cell.column + cell.column_span];
+        let width = columns.iter().map( | (_, w) | w).sum::<f64>() + self.column_gap * (cell.column_span - 1) as f64;
+        let rows = cell.row..cell.row + cell.row_span;
+        let height = if !measured && rows.clone().any( | row | self.template_row(row).is_none()) {
+            f64::NAN
+        }
+        else {
+            rows.map( | row | self.row_height(row)).sum::<f64>() + self.row_gap * (cell.row_span - 1) as f64
+        };
+        dvec2(width, height)
+    }
+    
+    // the size of all the columns and every row of the template or in use
+    fn size(&self) -> DVec2 {
+        let (x, w) = self.columns.last().unwrap();
+        let rows = self.rows.len().max(self.template_rows.len()).max(self.occupied.len());
+        let height = (0..rows).map( | row | self.row_height(row)).sum::<f64>() + self.row_gap * rows.saturating_sub(1) as f64;
+        dvec2(x + w, height)
+    }
+}
+
+fn clamp_keep_nan(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = if let Some(max) = max {v.min(max)} else {v};
+    if let Some(min) = min {v.max(min)} else {v}
+}
+
+fn eval_in_cell(size: Size, margin: f64, cell: f64, all: f64) -> f64 {
+    match size {
+        Size::Fit => std::f64::NAN,
+        Size::Fixed(v) => max_zero_keep_nan(v),
+        Size::Fill | Size::FillWeight(_) => max_zero_keep_nan(cell - margin),
+        Size::All => all
+    }
+}
+
+// sizes the tracks of one grid axis: fixed tracks keep their size and fill tracks share
+// what is left by weight. Fit tracks are NaN unless `fit_fills`, which makes them fill
+fn grid_track_sizes(tracks: &GridTracks, count: usize, space: f64, gap: f64, fit_fills: bool) -> Vec<f64> {
+    let tracks: Vec<Size> = (0..count).map( | i | match tracks.get(i) {
+        Some(Size::Fit) if fit_fills => Size::Fill,
+        Some(track) => track,
+        None => Size::Fill
+    }).collect();
+    let mut left = space - gap * count.saturating_sub(1) as f64;
+    let mut weights = 0.0;
+    for track in &tracks {
+        match track {
+            Size::Fixed(v) => left -= v,
+            track => weights += track.fill_weight()
+        }
+    }
+    tracks.iter().map( | track | match track {
+        Size::Fixed(v) => *v,
+        Size::Fit => f64::NAN,
+        track => if weights > 0.0 {max_zero_keep_nan(left) * track.fill_weight() / weights} else {0.0}
+    }).collect()
 }
 
 fn max_zero_keep_nan(v: f64) -> f64 {
@@ -0,0 +1,144 @@
+use {
+    std::{cell::RefCell, rc::Rc},
+    makepad_draw::*,
+    makepad_draw::makepad_shader_compiler::{ShaderBackend, ShaderBackendError, generate_glsl},
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_draw::shader::draw_quad::DrawQuad;
+
+    Valid = <DrawQuad> {
+        texture image: texture2d
+        fn pixel(self) -> vec4 {
+            return sample2d(self.image, self.pos);
+        }
+    }
+
+    Textures = <DrawQuad> {
+        texture cube: textureCube
+        texture volume: texture3d
+        texture layers: texture2dArray
+        fn pixel(self) -> vec4 {
+            return sampleCube(self.cube, vec3(self.pos, 1.0))
+                + sample3d(self.volume, vec3(self.pos, 0.5))
+                + sample2dArray(self.layers, self.pos, 2.0);
+        }
+    }
+
+    // GLSL ES 1.00 has no unsigned types, so GLSL emulates them
+    Unsigned = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let steps: uint = uint(self.pos.x * 4.0);
+            let cells: uvec2 = uvec2(self.pos * 16.0) / uint(4);
+            if steps < uint(2) && all(lessThan(cells, uvec2(2))) {
+                return vec4(0.0);
+            }
+            return vec4(float(steps / uint(3)), vec2(cells) / 4.0, 1.0);
+        }
+    }
+
+    // external textures only exist on Android, so only GLSL can generate this
+    ExternalTexture = <DrawQuad> {
+        texture video: textureOES
+        fn pixel(self) -> vec4 {
+            return sample2dOES(self.video, self.pos);
+        }
+    }
+}
+
+/// Creates an app that creates the given draw shaders at startup.
+fn headless_app(names: &[LiveId]) -> (HeadlessApp, Rc<RefCell<Vec<DrawQuad >>>) {
+    let quads = Rc::new(RefCell::new(Vec::new()));
+    let names = names.to_vec();
+    let mut cx = Cx::new(Box::new({
+        let quads = quads.clone();
+        move | cx, event | {
+            if let Event::Startup = event {
+                let module_id = LiveModuleId::from_str(module_path!()).unwrap();
+                for name in &names {
+                    quads.borrow_mut().push(DrawQuad::new_from_module(cx, module_id, *name).unwrap());
+                }
+            }
+        }
+    }));
+    makepad_draw::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.init_cx_os();
+    (HeadlessApp::new(cx), quads)
+}
+
+/// Runs the shader check like `cargo makepad check` does. Returns the failure count
+/// and the backend errors of each draw shader.
+fn check_shaders(names: &[LiveId]) -> (usize, Vec<Vec<ShaderBackendError >>) {
+    let (mut app, quads) = headless_app(names);
+    let failed = app.check_shaders(ShaderBackend::ALL.to_vec());
+
+    let cx = app.cx.borrow();
+    let errors = quads.borrow().iter().map( | quad | {
+        let draw_shader_ptr = quad.draw_vars.draw_shader.as_ref().unwrap().draw_shader_ptr;
+        cx.draw_shaders.backend_errors.iter()
+            .filter( | (ptr, _) | *ptr == draw_shader_ptr)
+            .map( | (_, error) | error.clone())
+            .collect()
+    }).collect();
+    (failed, errors)
+}
+
+fn backends(errors: &[ShaderBackendError]) -> Vec<ShaderBackend> {
+    errors.iter().map( | error | error.backend).collect()
+}
+
+#[test]
+fn valid_shader_has_no_backend_errors() {
+    let (failed, errors) = check_shaders(&[live_id!(Valid)]);
+    assert!(errors[0].is_empty());
+    assert_eq!(failed, 0);
+}
+
+#[test]
+fn generates_cube_3d_and_array_textures_everywhere() {
+    let (failed, errors) = check_shaders(&[live_id!(Textures)]);
+    assert!(errors[0].is_empty(), "{:?}", errors[0]);
+    assert_eq!(failed, 0);
+}
+
+#[test]
+fn generates_unsigned_types_everywhere() {
+    let (failed, errors) = check_shaders(&[live_id!(Unsigned)]);
+    assert!(errors[0].is_empty(), "{:?}", errors[0]);
+    assert_eq!(failed, 0);
+}
+
+#[test]
+fn emulates_unsigned_types_on_glsl() {
+    let (mut app, quads) = headless_app(&[live_id!(Unsigned), live_id!(Valid)]);
+    app.check_shaders(vec![ShaderBackend::Glsl]);
+    let cx = app.cx.borrow();
+    let pixel_shaders: Vec<String> = quads.borrow().iter().map( | quad | {
+        let draw_shader_ptr = quad.draw_vars.draw_shader.as_ref().unwrap().draw_shader_ptr;
+        let item = cx.draw_shaders.ptr_to_item.get(&draw_shader_ptr).unwrap();
+        generate_glsl::generate_pixel_shader(
+            cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap(),
+            &cx.draw_shaders.shaders[item.draw_shader_id].mapping.const_table,
+            &cx.shader_registry
+        ).unwrap()
+    }).collect();
+    assert!(!pixel_shaders[0].contains("uint") && !pixel_shaders[0].contains("uvec"), "{}", pixel_shaders[0]);
+    for helper in ["mpsc_udiv(", "mpsc_ult(", "mpsc_ulessThan(", "mpsc_utof(", "mpsc_ftou("] {
+        assert!(pixel_shaders[0].contains(helper), "{} missing in {}", helper, pixel_shaders[0]);
+    }
+    // shaders without unsigned types don't get the helpers
+    assert!(!pixel_shaders[1].contains("mpsc_u"), "{}", pixel_shaders[1]);
+}
+
+#[test]
+fn reports_errors_per_backend() {
+    let (failed, errors) = check_shaders(&[live_id!(Valid), live_id!(ExternalTexture)]);
+    assert!(errors[0].is_empty());
+    assert_eq!(backends(&errors[1]), vec![ShaderBackend::Metal, ShaderBackend::Hlsl, ShaderBackend::Wgsl]);
+    for error in &errors[1] {
+        assert!(error.error.message.contains("textureOES"), "{:?}", error);
+    }
+    assert_eq!(failed, 3);
+}
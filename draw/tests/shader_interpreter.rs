@@ -0,0 +1,216 @@
+use makepad_draw::*;
+use makepad_draw::makepad_shader_compiler::{DrawShaderInterpreter, ShaderTextures, interpret::Value};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_draw::shader::draw_quad::DrawQuad;
+
+    SdfCircle = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.circle(50.0, 50.0, 25.0);
+            sdf.fill(#f00);
+            return sdf.result;
+        }
+    }
+
+    SdfBox = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.clear(#00f);
+            sdf.box(20.0, 20.0, 60.0, 40.0, 4.0);
+            sdf.stroke(#0f0, 2.0);
+            return sdf.result;
+        }
+    }
+
+    PassPos = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            return vec4(self.pos, 0.0, 1.0);
+        }
+    }
+
+    Inverse = <DrawQuad> {
+        uniform scale: 2.0
+        fn pixel(self) -> vec4 {
+            return inverse(mat4(self.scale)) * vec4(1.0, 2.0, 3.0, 1.0);
+        }
+    }
+
+    Unsigned = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let steps: uint = uint(self.pos.x * 4.0);
+            let wrapped: uint = steps - uint(3);
+            return vec4(float(steps) / 4.0, float(wrapped) / 4294967296.0, 0.0, 1.0);
+        }
+    }
+
+    Textures = <DrawQuad> {
+        texture cube: textureCube
+        texture volume: texture3d
+        texture layers: texture2dArray
+        fn pixel(self) -> vec4 {
+            return vec4(
+                sampleCube(self.cube, vec3(0.0, 0.0, 1.0)).x,
+                sample3d(self.volume, vec3(self.pos, 0.25)).z,
+                sample2dArray(self.layers, self.pos, 2.0).w,
+                1.0
+            );
+        }
+    }
+}
+
+const SIZE: f32 = 100.0;
+
+fn cx() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+fn draw_quad(cx: &mut Cx, name: LiveId) -> DrawQuad {
+    let module_id = LiveModuleId::from_str(module_path!()).unwrap();
+    DrawQuad::new_from_module(cx, module_id, name).unwrap()
+}
+
+fn interpreter<'a>(cx: &'a Cx, quad: &DrawQuad) -> DrawShaderInterpreter<'a> {
+    let draw_shader_ptr = quad.draw_vars.draw_shader.as_ref().unwrap().draw_shader_ptr;
+    DrawShaderInterpreter::new(&cx.shader_registry, cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap())
+}
+
+fn vec2_value(x: f32, y: f32) -> Value {
+    Value::Vec(2, [x, y, 0.0, 0.0])
+}
+
+fn identity() -> Value {
+    let mut m = [0.0; 16];
+    for i in 0..4 {
+        m[i * 5] = 1.0;
+    }
+    Value::Mat(4, m)
+}
+
+/// Shades the pixel at `(x, y)` of a `SIZE` square quad, with derivatives so the
+/// antialiasing of `Sdf2d` works like it does on the GPU.
+fn shade(interp: &DrawShaderInterpreter, x: f32, y: f32) -> Vec4 {
+    let varyings = interp.pack_varyings(&[
+        (live_id!(pos), vec2_value(x / SIZE, y / SIZE)),
+        (live_id!(rect_size), vec2_value(SIZE, SIZE)),
+    ]);
+    let ddx = interp.pack_varyings(&[(live_id!(pos), vec2_value(1.0 / SIZE, 0.0))]);
+    let ddy = interp.pack_varyings(&[(live_id!(pos), vec2_value(0.0, 1.0 / SIZE))]);
+    interp.pixel_with_derivatives(&varyings, &ddx, &ddy, &()).unwrap()
+}
+
+fn assert_color(color: Vec4, r: f32, g: f32, b: f32, a: f32) {
+    let close = | x: f32, y: f32 | (x - y).abs() < 0.01;
+    assert!(
+        close(color.x, r) && close(color.y, g) && close(color.z, b) && close(color.w, a),
+        "{:?} is not ({}, {}, {}, {})", color, r, g, b, a
+    );
+}
+
+#[test]
+fn fills_sdf_circle() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(SdfCircle));
+    let interp = interpreter(&cx, &quad);
+    assert_color(shade(&interp, 50.0, 50.0), 1.0, 0.0, 0.0, 1.0);
+    assert_color(shade(&interp, 50.0, 30.0), 1.0, 0.0, 0.0, 1.0);
+    assert_color(shade(&interp, 5.0, 5.0), 0.0, 0.0, 0.0, 0.0);
+    assert_color(shade(&interp, 90.0, 50.0), 0.0, 0.0, 0.0, 0.0);
+    // the antialiasing width is the pixel diagonal, so half a pixel inside the edge is covered for 0.5 / sqrt(2)
+    let edge = 0.5 / 2f32.sqrt();
+    assert_color(shade(&interp, 74.5, 50.0), edge, 0.0, 0.0, edge);
+}
+
+#[test]
+fn strokes_sdf_box_over_clear_color() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(SdfBox));
+    let interp = interpreter(&cx, &quad);
+    // the stroke is 2 wide on both sides of the box outline
+    assert_color(shade(&interp, 50.0, 20.0), 0.0, 1.0, 0.0, 1.0);
+    assert_color(shade(&interp, 80.0, 40.0), 0.0, 1.0, 0.0, 1.0);
+    assert_color(shade(&interp, 50.0, 40.0), 0.0, 0.0, 1.0, 1.0);
+    assert_color(shade(&interp, 5.0, 90.0), 0.0, 0.0, 1.0, 1.0);
+}
+
+#[test]
+fn runs_vertex_then_pixel() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(PassPos));
+    let mut interp = interpreter(&cx, &quad);
+    for id in [live_id!(camera_projection), live_id!(camera_view), live_id!(view_transform)] {
+        assert!(interp.set_uniform(id, identity()));
+    }
+    assert!(interp.set_uniform(live_id!(draw_zbias), Value::Float(0.25)));
+
+    let geometry = interp.pack_geometry(&[(live_id!(geom_pos), vec2_value(1.0, 1.0))]);
+    let instance = interp.pack_instance(&[
+        (live_id!(rect_pos), vec2_value(10.0, 20.0)),
+        (live_id!(rect_size), vec2_value(30.0, 40.0)),
+        (live_id!(draw_clip), Value::Vec(4, [0.0, 0.0, 25.0, 1000.0])),
+        (live_id!(draw_depth), Value::Float(1.0)),
+    ]);
+    let out = interp.vertex(&geometry, &instance, &()).unwrap();
+    // the corner is clipped at x 25, halfway the quad
+    assert_color(out.position, 25.0, 60.0, 1.25, 1.0);
+    assert_eq!(out.varyings.len(), interp.varying_slots());
+    assert_color(interp.pixel(&out.varyings, &()).unwrap(), 0.5, 1.0, 0.0, 1.0);
+}
+
+#[test]
+fn inverts_matrices_with_nan() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(Inverse));
+    let mut interp = interpreter(&cx, &quad);
+    assert!(interp.set_uniform(live_id!(scale), Value::Float(2.0)));
+    assert_color(interp.pixel(&[], &()).unwrap(), 0.5, 1.0, 1.5, 0.5);
+
+    interp.set_uniform(live_id!(scale), Value::Float(f32::NAN));
+    assert!(interp.pixel(&[], &()).is_ok());
+}
+
+#[test]
+fn wraps_unsigned_integers() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(Unsigned));
+    let interp = interpreter(&cx, &quad);
+    let varyings = interp.pack_varyings(&[(live_id!(pos), vec2_value(0.6, 0.0))]);
+    // 2 - 3 wraps around to u32::MAX
+    assert_color(interp.pixel(&varyings, &()).unwrap(), 0.5, 1.0, 0.0, 1.0);
+}
+
+/// Returns the texture index and the coordinates it was sampled at.
+struct EchoTextures;
+
+impl ShaderTextures for EchoTextures {
+    fn sample_2d(&self, index: usize, uv: Vec2) -> Vec4 {
+        vec4(index as f32, uv.x, uv.y, 0.0)
+    }
+
+    fn sample_cube(&self, index: usize, dir: Vec3) -> Vec4 {
+        vec4(index as f32 + dir.z, dir.x, dir.y, 0.0)
+    }
+
+    fn sample_3d(&self, index: usize, uvw: Vec3) -> Vec4 {
+        vec4(index as f32, uvw.x, uvw.y + uvw.z, 0.0)
+    }
+
+    fn sample_2d_array(&self, index: usize, uv: Vec2, layer: f32) -> Vec4 {
+        vec4(index as f32, uv.x, uv.y, layer)
+    }
+}
+
+#[test]
+fn samples_cube_3d_and_array_textures() {
+    let mut cx = cx();
+    let quad = draw_quad(&mut cx, live_id!(Textures));
+    let interp = interpreter(&cx, &quad);
+    let varyings = interp.pack_varyings(&[(live_id!(pos), vec2_value(0.25, 0.5))]);
+    // the cube is texture 0, the layer of the array is passed along
+    assert_color(interp.pixel(&varyings, &EchoTextures).unwrap(), 1.0, 0.75, 2.0, 1.0);
+}
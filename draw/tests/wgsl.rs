@@ -0,0 +1,103 @@
+// validates the generated WGSL with naga, run with `cargo test -p makepad-draw --features wgsl-validation`
+#![cfg(feature = "wgsl-validation")]
+
+use makepad_draw::*;
+use makepad_draw::makepad_shader_compiler::generate_wgsl;
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_draw::shader::draw_quad::DrawQuad;
+
+    Sdf = <DrawQuad> {
+        instance hover: 0.0
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.clear(#00f);
+            sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, 3.0);
+            sdf.fill_keep(mix(#f00, #0f0, self.hover));
+            sdf.stroke(#fff, 1.0);
+            sdf.rotate(self.hover, 10.0, 10.0);
+            sdf.move_to(0.0, 0.0);
+            sdf.line_to(10.0, 10.0);
+            sdf.circle(10.0, 10.0, 5.0);
+            sdf.glow(#ff0, 2.0);
+            return sdf.result;
+        }
+    }
+
+    Shadow = <DrawQuad> {
+        uniform sigma: 2.0
+        fn pixel(self) -> vec4 {
+            let shadow = GaussShadow::box_shadow(vec2(10.0), self.rect_size - 10.0, self.pos * self.rect_size, self.sigma);
+            return Pal::hsv2rgb(vec4(self.pos.x, 1.0, 1.0, shadow));
+        }
+    }
+
+    Matrices = <DrawQuad> {
+        uniform scale: 2.0
+        fn pixel(self) -> vec4 {
+            return inverse(mat4(self.scale)) * vec4(self.pos, 0.0, 1.0);
+        }
+    }
+
+    Textures = <DrawQuad> {
+        texture image: texture2d
+        texture cube: textureCube
+        texture volume: texture3d
+        texture layers: texture2dArray
+        fn pixel(self) -> vec4 {
+            return sample2d(self.image, self.pos)
+                + sampleCube(self.cube, vec3(self.pos, 1.0))
+                + sample3d(self.volume, vec3(self.pos, 0.5))
+                + sample2dArray(self.layers, self.pos, 2.0);
+        }
+    }
+
+    Unsigned = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let steps: uint = uint(self.pos.x * 4.0);
+            let cells: uvec2 = uvec2(self.pos * 16.0) / uint(4);
+            if steps < uint(2) && all(lessThan(cells, uvec2(2))) {
+                return vec4(0.0);
+            }
+            return vec4(float(steps / uint(3)), vec2(cells) / 4.0, 1.0);
+        }
+    }
+}
+
+#[test]
+fn generated_wgsl_validates() {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.live_expand();
+    // the draw shaders compile when the draw structs are created from their live_design
+    let module_id = LiveModuleId::from_str(module_path!()).unwrap();
+    let _quads: Vec<DrawQuad> = [live_id!(Sdf), live_id!(Shadow), live_id!(Matrices), live_id!(Textures), live_id!(Unsigned)].iter().map( | name | {
+        DrawQuad::new_from_module(&mut cx, module_id, *name).unwrap()
+    }).collect();
+    let _color = DrawColor::new_local(&mut cx);
+    let _text = DrawText::new_local(&mut cx);
+    let _icon = DrawIcon::new_local(&mut cx);
+    let _line = DrawLine::new_local(&mut cx);
+
+    let mut failures = Vec::new();
+    for (draw_shader_ptr, item) in cx.draw_shaders.ptr_to_item.iter() {
+        let Some(draw_shader_def) = cx.shader_registry.draw_shader_defs.get(draw_shader_ptr) else {continue};
+        let const_table = &cx.draw_shaders.shaders[item.draw_shader_id].mapping.const_table;
+        let wgsl = generate_wgsl::generate_shader(draw_shader_def, const_table, &cx.shader_registry).unwrap().wgsl;
+        let module = match naga::front::wgsl::parse_str(&wgsl) {
+            Ok(module) => module,
+            Err(err) => {
+                failures.push(err.emit_to_string(&wgsl));
+                continue
+            }
+        };
+        let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+        if let Err(err) = validator.validate(&module) {
+            failures.push(err.emit_to_string(&wgsl));
+        }
+    }
+    assert!(cx.draw_shaders.ptr_to_item.len() >= 9);
+    assert!(failures.is_empty(), "{} of {} shaders failed:\n{}", failures.len(), cx.draw_shaders.ptr_to_item.len(), failures.join("\n"));
+}
@@ -0,0 +1,510 @@
+// A small CSS engine for styling parsed html: it parses rules from `<style>` blocks and `style`
+// attributes, matches type, class, id, descendant and child selectors and cascades the
+// declarations by importance, specificity and source order.
+// Only a handful of properties are understood, everything else is skipped.
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CssColor{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CssLength{
+    Px(f64),
+    Em(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CssFontSize{
+    /// Relative to the font size of the parent element (em, %, smaller, larger)
+    Scale(f64),
+    /// Relative to the base font size (px, pt, rem and the size keywords). 16px is a scale of 1.0
+    AbsScale(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CssTextAlign{
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CssDisplay{
+    Inline,
+    Block,
+    None,
+}
+
+/// The properties declared for an element after the cascade.
+/// Properties that aren't declared stay None, inheritance is left to the renderer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CssStyle{
+    pub color: Option<CssColor>,
+    pub font_size: Option<CssFontSize>,
+    pub bold: Option<bool>,
+    pub margin_top: Option<CssLength>,
+    pub margin_right: Option<CssLength>,
+    pub margin_bottom: Option<CssLength>,
+    pub margin_left: Option<CssLength>,
+    pub text_align: Option<CssTextAlign>,
+    pub display: Option<CssDisplay>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Combinator{
+    Descendant,
+    Child,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Compound{
+    tag: Option<LiveId>,
+    id: Option<LiveId>,
+    classes: Vec<LiveId>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Selector{
+    // right most compound last, the combinator joins it to the previous one
+    parts: Vec<(Combinator, Compound)>,
+    specificity: (u32, u32, u32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Declaration{
+    name: String,
+    value: String,
+    important: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Rule{
+    selectors: Vec<Selector>,
+    declarations: Vec<Declaration>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CssStyleSheet{
+    rules: Vec<Rule>,
+}
+
+struct Element{
+    lc: LiveId,
+    id: Option<LiveId>,
+    classes: Vec<LiveId>,
+}
+
+fn strip_comments(text:&str)->String{
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("/*"){
+        out.push_str(&rest[..start]);
+        rest = match rest[start+2..].find("*/"){
+            Some(end)=>&rest[start + 2 + end + 2..],
+            None=>""
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse_declarations(text:&str)->Vec<Declaration>{
+    let mut declarations = Vec::new();
+    for declaration in text.split(';'){
+        let Some((name, value)) = declaration.split_once(':') else {continue};
+        let name = name.trim().to_ascii_lowercase();
+        let mut value = value.trim();
+        let mut important = false;
+        if let Some(pos) = value.to_ascii_lowercase().rfind("!important"){
+            value = value[..pos].trim();
+            important = true;
+        }
+        if !name.is_empty() && !value.is_empty(){
+            declarations.push(Declaration{name, value:value.into(), important});
+        }
+    }
+    declarations
+}
+
+fn parse_compound(text:&str)->Option<Compound>{
+    fn finish(specifier:Option<char>, name:&str, compound:&mut Compound)->Option<()>{
+        if name.is_empty(){
+            return if specifier.is_none(){Some(())}else{None}
+        }
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '*'){
+            return None
+        }
+        match specifier{
+            None if name == "*"=>(),
+            None=>compound.tag = Some(LiveId::from_str_lc(name)),
+            Some('.')=>compound.classes.push(LiveId::from_str(name)),
+            Some('#')=>compound.id = Some(LiveId::from_str(name)),
+            _=>return None
+        }
+        Some(())
+    }
+    let mut compound = Compound::default();
+    let mut specifier = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices(){
+        if c == '.' || c == '#'{
+            finish(specifier, &text[start..i], &mut compound)?;
+            specifier = Some(c);
+            start = i + 1;
+        }
+        else if c == ':' || c == '[' || c == '+' || c == '~'{
+            // pseudo classes, attribute selectors and sibling combinators never match
+            return None
+        }
+    }
+    finish(specifier, &text[start..], &mut compound)?;
+    Some(compound)
+}
+
+fn parse_selector(text:&str)->Option<Selector>{
+    let mut parts = Vec::new();
+    let mut combinator = Combinator::Descendant;
+    for word in text.replace('>', " > ").split_whitespace(){
+        if word == ">"{
+            combinator = Combinator::Child;
+            continue
+        }
+        parts.push((combinator, parse_compound(word)?));
+        combinator = Combinator::Descendant;
+    }
+    if parts.is_empty() || combinator == Combinator::Child{
+        return None
+    }
+    let mut specificity = (0, 0, 0);
+    for (_, compound) in &parts{
+        specificity.0 += compound.id.is_some() as u32;
+        specificity.1 += compound.classes.len() as u32;
+        specificity.2 += compound.tag.is_some() as u32;
+    }
+    Some(Selector{parts, specificity})
+}
+
+/// Parses a style sheet. Like browsers do, anything that can't be parsed is skipped.
+/// At-rules such as `@media` are ignored entirely.
+pub fn parse_css(text:&str)->CssStyleSheet{
+    let text = strip_comments(text);
+    let mut rules = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('{'){
+        let prelude = rest[..open].trim();
+        // find the matching close brace, at-rules can nest blocks
+        let mut depth = 0;
+        let mut close = rest.len();
+        for (i, c) in rest[open..].char_indices(){
+            match c{
+                '{'=>depth += 1,
+                '}'=>{
+                    depth -= 1;
+                    if depth == 0{
+                        close = open + i;
+                        break
+                    }
+                }
+                _=>()
+            }
+        }
+        let body = &rest[(open + 1).min(close)..close];
+        rest = &rest[(close + 1).min(rest.len())..];
+        // statement at-rules like @import end with a ; before the next rule
+        let prelude = match prelude.rfind(';'){
+            Some(pos) if prelude.starts_with('@')=>prelude[pos+1..].trim(),
+            _=>prelude
+        };
+        if prelude.starts_with('@'){
+            continue
+        }
+        let selectors:Vec<Selector> = prelude.split(',').filter_map(parse_selector).collect();
+        if !selectors.is_empty(){
+            rules.push(Rule{selectors, declarations:parse_declarations(body)});
+        }
+    }
+    CssStyleSheet{rules}
+}
+
+fn parse_length(value:&str)->Option<CssLength>{
+    let value = value.trim().to_ascii_lowercase();
+    if value == "0" || value == "auto"{
+        return Some(CssLength::Px(0.0))
+    }
+    if let Some(v) = value.strip_suffix("px"){
+        return v.parse().ok().map(CssLength::Px)
+    }
+    if let Some(v) = value.strip_suffix("pt"){
+        return v.parse::<f64>().ok().map(|v| CssLength::Px(v * 4.0 / 3.0))
+    }
+    if let Some(v) = value.strip_suffix("rem").or_else(|| value.strip_suffix("em")){
+        return v.parse().ok().map(CssLength::Em)
+    }
+    None
+}
+
+fn parse_font_size(value:&str)->Option<CssFontSize>{
+    let value = value.trim().to_ascii_lowercase();
+    Some(match value.as_str(){
+        "xx-small"=>CssFontSize::AbsScale(0.6),
+        "x-small"=>CssFontSize::AbsScale(0.75),
+        "small"=>CssFontSize::AbsScale(0.89),
+        "medium"=>CssFontSize::AbsScale(1.0),
+        "large"=>CssFontSize::AbsScale(1.2),
+        "x-large"=>CssFontSize::AbsScale(1.5),
+        "xx-large"=>CssFontSize::AbsScale(2.0),
+        "smaller"=>CssFontSize::Scale(0.83),
+        "larger"=>CssFontSize::Scale(1.2),
+        _=>{
+            if let Some(v) = value.strip_suffix('%'){
+                return v.parse::<f64>().ok().map(|v| CssFontSize::Scale(v / 100.0))
+            }
+            if let Some(v) = value.strip_suffix("rem"){
+                return v.parse().ok().map(CssFontSize::AbsScale)
+            }
+            match parse_length(&value)?{
+                CssLength::Px(px)=>CssFontSize::AbsScale(px / 16.0),
+                CssLength::Em(em)=>CssFontSize::Scale(em),
+            }
+        }
+    })
+}
+
+/// Parses a css color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()` or a basic color name.
+pub fn parse_css_color(value:&str)->Option<CssColor>{
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#'){
+        let digits:Vec<u32> = hex.chars().map(|c| c.to_digit(16)).collect::<Option<_>>()?;
+        let channels:Vec<f32> = match digits.len(){
+            3 | 4=>digits.iter().map(|d| (d * 17) as f32 / 255.0).collect(),
+            6 | 8=>digits.chunks(2).map(|d| (d[0] * 16 + d[1]) as f32 / 255.0).collect(),
+            _=>return None
+        };
+        return Some(CssColor{r:channels[0], g:channels[1], b:channels[2], a:*channels.get(3).unwrap_or(&1.0)})
+    }
+    if let Some(args) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")){
+        let args = args.strip_suffix(')')?;
+        let mut channels = Vec::new();
+        for (i, arg) in args.split([',', '/', ' ']).filter(|a| !a.is_empty()).enumerate(){
+            let channel = match arg.strip_suffix('%'){
+                Some(p)=>p.parse::<f32>().ok()? / 100.0,
+                None if i == 3=>arg.parse::<f32>().ok()?,
+                None=>arg.parse::<f32>().ok()? / 255.0
+            };
+            channels.push(channel.clamp(0.0, 1.0));
+        }
+        if channels.len() < 3{
+            return None
+        }
+        return Some(CssColor{r:channels[0], g:channels[1], b:channels[2], a:*channels.get(3).unwrap_or(&1.0)})
+    }
+    let rgb = match value.as_str(){
+        "transparent"=>return Some(CssColor{r:0.0, g:0.0, b:0.0, a:0.0}),
+        "black"=>0x000000,
+        "silver"=>0xc0c0c0,
+        "gray" | "grey"=>0x808080,
+        "white"=>0xffffff,
+        "maroon"=>0x800000,
+        "red"=>0xff0000,
+        "purple"=>0x800080,
+        "fuchsia" | "magenta"=>0xff00ff,
+        "green"=>0x008000,
+        "lime"=>0x00ff00,
+        "olive"=>0x808000,
+        "yellow"=>0xffff00,
+        "navy"=>0x000080,
+        "blue"=>0x0000ff,
+        "teal"=>0x008080,
+        "aqua" | "cyan"=>0x00ffff,
+        "orange"=>0xffa500,
+        "brown"=>0xa52a2a,
+        "pink"=>0xffc0cb,
+        "gold"=>0xffd700,
+        "darkgray" | "darkgrey"=>0xa9a9a9,
+        "lightgray" | "lightgrey"=>0xd3d3d3,
+        _=>return None
+    };
+    Some(CssColor{
+        r: ((rgb >> 16) & 0xff) as f32 / 255.0,
+        g: ((rgb >> 8) & 0xff) as f32 / 255.0,
+        b: (rgb & 0xff) as f32 / 255.0,
+        a: 1.0
+    })
+}
+
+impl CssStyle{
+    /// Applies a single declaration, invalid values are ignored
+    pub fn apply_declaration(&mut self, name:&str, value:&str){
+        let keyword = value.trim().to_ascii_lowercase();
+        match name{
+            "color"=>if let Some(color) = parse_css_color(value){
+                self.color = Some(color)
+            }
+            "font-size"=>if let Some(size) = parse_font_size(value){
+                self.font_size = Some(size)
+            }
+            "font-weight"=>match keyword.as_str(){
+                "bold" | "bolder"=>self.bold = Some(true),
+                "normal" | "lighter"=>self.bold = Some(false),
+                weight=>if let Ok(weight) = weight.parse::<u32>(){
+                    self.bold = Some(weight >= 600)
+                }
+            }
+            "margin"=>{
+                let values:Option<Vec<CssLength>> = value.split_whitespace().map(parse_length).collect();
+                let Some(values) = values else {return};
+                let (top, right, bottom, left) = match values.len(){
+                    1=>(values[0], values[0], values[0], values[0]),
+                    2=>(values[0], values[1], values[0], values[1]),
+                    3=>(values[0], values[1], values[2], values[1]),
+                    4=>(values[0], values[1], values[2], values[3]),
+                    _=>return
+                };
+                self.margin_top = Some(top);
+                self.margin_right = Some(right);
+                self.margin_bottom = Some(bottom);
+                self.margin_left = Some(left);
+            }
+            "margin-top"=>if let Some(v) = parse_length(value){self.margin_top = Some(v)}
+            "margin-right"=>if let Some(v) = parse_length(value){self.margin_right = Some(v)}
+            "margin-bottom"=>if let Some(v) = parse_length(value){self.margin_bottom = Some(v)}
+            "margin-left"=>if let Some(v) = parse_length(value){self.margin_left = Some(v)}
+            "text-align"=>match keyword.as_str(){
+                "left" | "start" | "justify"=>self.text_align = Some(CssTextAlign::Left),
+                "center"=>self.text_align = Some(CssTextAlign::Center),
+                "right" | "end"=>self.text_align = Some(CssTextAlign::Right),
+                _=>()
+            }
+            "display"=>match keyword.as_str(){
+                "none"=>self.display = Some(CssDisplay::None),
+                "inline" | "inline-block"=>self.display = Some(CssDisplay::Inline),
+                "block" | "list-item" | "flex" | "grid" | "table"=>self.display = Some(CssDisplay::Block),
+                _=>()
+            }
+            _=>()
+        }
+    }
+}
+
+impl Compound{
+    fn matches(&self, element:&Element)->bool{
+        self.tag.is_none_or(|tag| tag == element.lc) &&
+        self.id.is_none_or(|id| Some(id) == element.id) &&
+        self.classes.iter().all(|class| element.classes.contains(class))
+    }
+}
+
+impl Selector{
+    // elements is the ancestor chain with the element to match last
+    fn matches(&self, elements:&[Element])->bool{
+        fn matches_at(parts:&[(Combinator, Compound)], elements:&[Element])->bool{
+            let Some(((combinator, compound), parts)) = parts.split_last() else {return true};
+            let Some((element, ancestors)) = elements.split_last() else {return false};
+            if !compound.matches(element){
+                return false
+            }
+            if parts.is_empty(){
+                return true
+            }
+            match combinator{
+                Combinator::Child=>matches_at(parts, ancestors),
+                Combinator::Descendant=>(0..ancestors.len()).rev().any(|i| matches_at(parts, &ancestors[..=i]))
+            }
+        }
+        matches_at(&self.parts, elements)
+    }
+}
+
+impl CssStyleSheet{
+    /// Appends the rules of another sheet, which then win over ours at equal specificity
+    pub fn append(&mut self, other:CssStyleSheet){
+        self.rules.extend(other.rules);
+    }
+
+    /// Computes the cascaded style of every element in a balanced node stream
+    /// (see `parse_html_fragment`), including the declarations of `style` attributes.
+    /// The result is indexed like `doc.nodes` and only has entries for OpenTag nodes
+    /// that have any recognised declarations.
+    pub fn compute_styles(&self, doc:&HtmlDoc)->Vec<Option<CssStyle>>{
+        let mut styles = vec![None; doc.nodes.len()];
+        let mut elements:Vec<Element> = Vec::new();
+        for (index, node) in doc.nodes.iter().enumerate(){
+            match node{
+                HtmlNode::OpenTag{lc, ..}=>{
+                    let mut element = Element{lc:*lc, id:None, classes:Vec::new()};
+                    let mut inline = None;
+                    let mut walker = doc.new_walker_with_index(index + 1);
+                    while let Some((lc, value)) = walker.while_attr_lc(){
+                        match lc{
+                            live_id!(id)=>element.id = Some(LiveId::from_str(value.trim())),
+                            live_id!(class)=>element.classes = value.split_whitespace().map(LiveId::from_str).collect(),
+                            live_id!(style)=>inline = Some(value),
+                            _=>()
+                        }
+                    }
+                    elements.push(element);
+                    // collect matching declarations with their cascade order
+                    let mut matched = Vec::new();
+                    let mut order = 0;
+                    for rule in &self.rules{
+                        let specificity = rule.selectors.iter()
+                            .filter(|s| s.matches(&elements))
+                            .map(|s| s.specificity).max();
+                        if let Some(specificity) = specificity{
+                            for declaration in &rule.declarations{
+                                matched.push(((declaration.important, false, specificity, order), declaration.clone()));
+                                order += 1;
+                            }
+                        }
+                    }
+                    if let Some(inline) = inline{
+                        for declaration in parse_declarations(inline){
+                            matched.push(((declaration.important, true, (0, 0, 0), order), declaration));
+                            order += 1;
+                        }
+                    }
+                    if matched.is_empty(){
+                        continue
+                    }
+                    matched.sort_by_key(|m| m.0);
+                    let mut style = CssStyle::default();
+                    for (_, declaration) in &matched{
+                        style.apply_declaration(&declaration.name, &declaration.value);
+                    }
+                    if style != CssStyle::default(){
+                        styles[index] = Some(style);
+                    }
+                }
+                HtmlNode::CloseTag{..}=>{
+                    elements.pop();
+                }
+                _=>()
+            }
+        }
+        styles
+    }
+}
+
+impl HtmlDoc{
+    /// Collects the rules of all `<style>` elements in the document
+    pub fn style_sheet(&self)->CssStyleSheet{
+        let mut sheet = CssStyleSheet::default();
+        let mut in_style = false;
+        for node in &self.nodes{
+            match node{
+                HtmlNode::OpenTag{lc:live_id!(style), ..}=>in_style = true,
+                HtmlNode::CloseTag{lc:live_id!(style), ..}=>in_style = false,
+                HtmlNode::Text{start, end, ..} if in_style=>{
+                    sheet.append(parse_css(&self.decoded[*start..*end]));
+                }
+                _=>()
+            }
+        }
+        sheet
+    }
+}
@@ -1,5 +1,10 @@
 use makepad_live_id::*;
 
+mod tree;
+mod css;
+pub use tree::*;
+pub use css::*;
+
 #[derive(Debug)]
 pub struct HtmlError{
     pub message:String,
@@ -319,10 +324,31 @@ impl<'a> HtmlWalker<'a>{
          }
      }
      
+     // script, style and friends contain raw text up to their close tag, title and textarea
+     // (rcdata) do decode entities
+     fn raw_text_element(lc:LiveId)->Option<(LiveId, bool)>{
+         match lc{
+             live_id!(script) | live_id!(style) | live_id!(xmp) | live_id!(iframe) |
+             live_id!(noembed) | live_id!(noframes)=>Some((lc, false)),
+             live_id!(title) | live_id!(textarea)=>Some((lc, true)),
+             _=>None
+         }
+     }
+     
+     fn is_close_tag_of(rest:&str, lc:LiveId)->bool{
+         if let Some(rest) = rest.strip_prefix('/'){
+             let end = rest.find(|c:char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(rest.len());
+             return LiveId::from_str_lc(&rest[..end]) == lc
+         }
+         false
+     }
+     
      let mut nodes = Vec::new();
      let mut state = State::Text(0, 0, 0);
      let mut decoded = String::new();
      let mut in_entity = None;
+     // the raw text element we are in, from its open tag up to the < of its close tag
+     let mut raw_text:Option<(LiveId, bool)> = None;
      
      for (i, c) in body.char_indices(){
          state = match state{
@@ -351,11 +377,32 @@ impl<'a> HtmlWalker<'a>{
                  }
              }
              State::Text(start, dec_start, last_non_whitespace)=>{ 
-                 if c == '<'{
-                    if let Some(start) = in_entity{
+                 let raw = raw_text;
+                 let next = body[i+1..].chars().next();
+                 if c == '<' && raw.is_some() && !is_close_tag_of(&body[i+1..], raw.unwrap().0) ||
+                    c == '<' && raw.is_none() && next.is_none_or(|n| n.is_whitespace()){
+                     // a < that doesn't start a tag is text
+                     if raw.is_none(){
+                         if let Some(errors) = errors{errors.push(HtmlError{message:"Found whitespace at beginning of tag".into(), position:i})};
+                     }
+                     let mut last_non_whitespace = last_non_whitespace;
+                     process_entity(c, body, &mut in_entity, i, &mut decoded, errors, &mut last_non_whitespace);
+                     State::Text(start, dec_start, last_non_whitespace)
+                 }
+                 else if c == '&' && matches!(raw, Some((_, false))){
+                     // raw text elements don't decode entities
+                     decoded.push(c);
+                     State::Text(start, dec_start, decoded.len())
+                 }
+                 else if c == '<'{
+                    let mut last_non_whitespace = last_non_whitespace;
+                    if let Some(start) = in_entity.take(){
                         if let Some(errors) = errors{errors.push(HtmlError{message:"Unterminated entity".into(), position:start})};
+                        decoded.push_str(&body[start-1..i]);
+                        last_non_whitespace = decoded.len();
                     }
                     nodes.push(HtmlNode::Text{start:dec_start, end:decoded.len(), all_ws:dec_start == last_non_whitespace});
+                    raw_text = None;
                     State::ElementName(i+1)
                  }
                  else{
@@ -380,16 +427,22 @@ impl<'a> HtmlWalker<'a>{
                          State::Text(i+1, decoded.len(), decoded.len())
                      }
                      else{
-                        nodes.push(HtmlNode::OpenTag{lc:LiveId::from_str_lc(&body[start..i]),nc:LiveId::from_str(&body[start..i])});
+                        let lc = LiveId::from_str_lc(&body[start..i]);
+                        raw_text = raw_text_element(lc);
+                        nodes.push(HtmlNode::OpenTag{lc,nc:LiveId::from_str(&body[start..i])});
                         State::ElementAttrs
                     }
                 }
                  else if c == '/'{
-                     nodes.push(HtmlNode::OpenTag{lc:LiveId::from_str_lc(&body[start..i]),nc:LiveId::from_str(&body[start..i])});
+                     let lc = LiveId::from_str_lc(&body[start..i]);
+                     raw_text = raw_text_element(lc);
+                     nodes.push(HtmlNode::OpenTag{lc,nc:LiveId::from_str(&body[start..i])});
                      State::ElementSelfClose
                  }
                  else if c == '>'{
-                     nodes.push(HtmlNode::OpenTag{lc:LiveId::from_str_lc(&body[start..i]),nc:LiveId::from_str(&body[start..i])});
+                     let lc = LiveId::from_str_lc(&body[start..i]);
+                     raw_text = raw_text_element(lc);
+                     nodes.push(HtmlNode::OpenTag{lc,nc:LiveId::from_str(&body[start..i])});
                      State::Text(i+1, decoded.len(), decoded.len())
                  }
                  else{
@@ -428,6 +481,7 @@ impl<'a> HtmlWalker<'a>{
                  // look backwards to the OpenTag
                  let begin = nodes.iter().rev().find_map(|v| if let HtmlNode::OpenTag{lc,nc} = v{Some((lc,nc))}else{None}).unwrap();
                  nodes.push(HtmlNode::CloseTag{lc:*begin.0,nc:*begin.1});
+                 raw_text = None;
                  State::Text(i+1, decoded.len(), decoded.len())
              }
              State::ElementAttrs=>{
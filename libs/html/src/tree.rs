@@ -0,0 +1,1359 @@
+// HTML5 tree construction on top of the flat token stream of parse_html.
+// Follows the insertion modes of the WHATWG parsing spec (implied end tags, void elements,
+// implicit html/head/body/tbody/tr, active formatting elements with the adoption agency
+// algorithm and foster parenting for tables) and serialises the resulting tree
+// back into a balanced HtmlNode stream. Foreign content (svg/math), templates
+// and framesets are treated as ordinary elements.
+use crate::*;
+
+#[derive(Clone, PartialEq)]
+struct Attr{
+    lc: LiveId,
+    nc: LiveId,
+    value: String,
+}
+
+enum Token{
+    Start{lc:LiveId, nc:LiveId, attrs:Vec<Attr>, self_closing:bool},
+    End{lc:LiveId, nc:LiveId},
+    Text(String),
+    Eof,
+}
+
+enum Kind{
+    Document,
+    Element{lc:LiveId, nc:LiveId, attrs:Vec<Attr>},
+    Text(String),
+}
+
+struct Node{
+    kind: Kind,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode{
+    Initial,
+    BeforeHead,
+    InHead,
+    AfterHead,
+    InBody,
+    Text,
+    InTable,
+    InTableText,
+    InCaption,
+    InColumnGroup,
+    InTableBody,
+    InRow,
+    InCell,
+    InSelect,
+    InSelectInTable,
+    AfterBody,
+    AfterAfterBody,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scope{
+    Default,
+    ListItem,
+    Button,
+    Table,
+    Select,
+}
+
+fn is_void(lc:LiveId)->bool{
+    matches!(lc, live_id!(area) | live_id!(base) | live_id!(basefont) | live_id!(bgsound) | live_id!(br) |
+        live_id!(col) | live_id!(embed) | live_id!(frame) | live_id!(hr) | live_id!(img) | live_id!(input) |
+        live_id!(keygen) | live_id!(link) | live_id!(meta) | live_id!(param) | live_id!(source) |
+        live_id!(track) | live_id!(wbr))
+}
+
+fn is_special(lc:LiveId)->bool{
+    matches!(lc, live_id!(address) | live_id!(applet) | live_id!(area) | live_id!(article) | live_id!(aside) |
+        live_id!(base) | live_id!(basefont) | live_id!(bgsound) | live_id!(blockquote) | live_id!(body) |
+        live_id!(br) | live_id!(button) | live_id!(caption) | live_id!(center) | live_id!(col) |
+        live_id!(colgroup) | live_id!(dd) | live_id!(details) | live_id!(dir) | live_id!(div) | live_id!(dl) |
+        live_id!(dt) | live_id!(embed) | live_id!(fieldset) | live_id!(figcaption) | live_id!(figure) |
+        live_id!(footer) | live_id!(form) | live_id!(frame) | live_id!(frameset) | live_id!(h1) |
+        live_id!(h2) | live_id!(h3) | live_id!(h4) | live_id!(h5) | live_id!(h6) | live_id!(head) |
+        live_id!(header) | live_id!(hgroup) | live_id!(hr) | live_id!(html) | live_id!(iframe) |
+        live_id!(img) | live_id!(input) | live_id!(keygen) | live_id!(li) | live_id!(link) |
+        live_id!(listing) | live_id!(main) | live_id!(marquee) | live_id!(menu) | live_id!(meta) |
+        live_id!(nav) | live_id!(noembed) | live_id!(noframes) | live_id!(noscript) | live_id!(object) |
+        live_id!(ol) | live_id!(p) | live_id!(param) | live_id!(plaintext) | live_id!(pre) |
+        live_id!(script) | live_id!(search) | live_id!(section) | live_id!(select) | live_id!(source) |
+        live_id!(style) | live_id!(summary) | live_id!(table) | live_id!(tbody) | live_id!(td) |
+        live_id!(template) | live_id!(textarea) | live_id!(tfoot) | live_id!(th) | live_id!(thead) |
+        live_id!(title) | live_id!(tr) | live_id!(track) | live_id!(ul) | live_id!(wbr) | live_id!(xmp))
+}
+
+fn is_formatting(lc:LiveId)->bool{
+    matches!(lc, live_id!(a) | live_id!(b) | live_id!(big) | live_id!(code) | live_id!(em) | live_id!(font) |
+        live_id!(i) | live_id!(nobr) | live_id!(s) | live_id!(small) | live_id!(strike) | live_id!(strong) |
+        live_id!(tt) | live_id!(u))
+}
+
+fn is_heading(lc:LiveId)->bool{
+    matches!(lc, live_id!(h1) | live_id!(h2) | live_id!(h3) | live_id!(h4) | live_id!(h5) | live_id!(h6))
+}
+
+fn is_implied_end(lc:LiveId)->bool{
+    matches!(lc, live_id!(dd) | live_id!(dt) | live_id!(li) | live_id!(optgroup) | live_id!(option) |
+        live_id!(p) | live_id!(rb) | live_id!(rp) | live_id!(rt) | live_id!(rtc))
+}
+
+fn is_table_section(lc:LiveId)->bool{
+    matches!(lc, live_id!(tbody) | live_id!(tfoot) | live_id!(thead))
+}
+
+fn is_table_part(lc:LiveId)->bool{
+    matches!(lc, live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(tbody) | live_id!(td) |
+        live_id!(tfoot) | live_id!(th) | live_id!(thead) | live_id!(tr))
+}
+
+fn is_ws(text:&str)->bool{
+    text.chars().all(|c| matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0c'))
+}
+
+struct TreeBuilder{
+    nodes: Vec<Node>,
+    open: Vec<usize>,
+    // None is a marker
+    formatting: Vec<Option<usize>>,
+    head: Option<usize>,
+    mode: Mode,
+    original_mode: Mode,
+    table_text: String,
+    foster_parenting: bool,
+    skip_newline: bool,
+    context: Option<LiveId>,
+    errors: Vec<String>,
+}
+
+impl TreeBuilder{
+    fn new(context:Option<LiveId>)->Self{
+        let mut builder = Self{
+            nodes: vec![Node{kind:Kind::Document, parent:None, children:Vec::new()}],
+            open: Vec::new(),
+            formatting: Vec::new(),
+            head: None,
+            mode: Mode::Initial,
+            original_mode: Mode::Initial,
+            table_text: String::new(),
+            foster_parenting: false,
+            skip_newline: false,
+            context,
+            errors: Vec::new(),
+        };
+        if context.is_some(){
+            // fragments are parsed into an html root that is dropped on output
+            let html = builder.create(live_id!(html), live_id!(html), Vec::new());
+            builder.append(0, html);
+            builder.open.push(html);
+            builder.reset_mode();
+        }
+        builder
+    }
+
+    fn error(&mut self, message:&str){
+        self.errors.push(message.into());
+    }
+
+    // tree helpers
+
+    fn create(&mut self, lc:LiveId, nc:LiveId, attrs:Vec<Attr>)->usize{
+        self.nodes.push(Node{kind:Kind::Element{lc, nc, attrs}, parent:None, children:Vec::new()});
+        self.nodes.len() - 1
+    }
+
+    fn lc(&self, node:usize)->LiveId{
+        match &self.nodes[node].kind{
+            Kind::Element{lc,..}=>*lc,
+            _=>LiveId(0)
+        }
+    }
+
+    fn detach(&mut self, node:usize){
+        if let Some(parent) = self.nodes[node].parent.take(){
+            self.nodes[parent].children.retain(|c| *c != node);
+        }
+    }
+
+    fn append(&mut self, parent:usize, node:usize){
+        self.detach(node);
+        self.nodes[node].parent = Some(parent);
+        self.nodes[parent].children.push(node);
+    }
+
+    fn insert_at(&mut self, parent:usize, before:Option<usize>, node:usize){
+        self.detach(node);
+        self.nodes[node].parent = Some(parent);
+        let index = before.and_then(|b| self.nodes[parent].children.iter().position(|c| *c == b));
+        match index{
+            Some(index)=>self.nodes[parent].children.insert(index, node),
+            None=>self.nodes[parent].children.push(node)
+        }
+    }
+
+    fn current(&self)->usize{
+        *self.open.last().unwrap_or(&0)
+    }
+
+    fn current_lc(&self)->LiveId{
+        self.lc(self.current())
+    }
+
+    // the appropriate place for inserting a node, taking foster parenting into account
+    fn insertion_place(&self, target:Option<usize>)->(usize, Option<usize>){
+        let target = target.unwrap_or(self.current());
+        if self.foster_parenting && matches!(self.lc(target), live_id!(table) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr)){
+            if let Some(pos) = self.open.iter().rposition(|n| self.lc(*n) == live_id!(table)){
+                let table = self.open[pos];
+                if let Some(parent) = self.nodes[table].parent{
+                    return (parent, Some(table))
+                }
+                if pos > 0{
+                    return (self.open[pos - 1], None)
+                }
+            }
+            return (*self.open.first().unwrap_or(&0), None)
+        }
+        (target, None)
+    }
+
+    fn insert_element(&mut self, lc:LiveId, nc:LiveId, attrs:Vec<Attr>)->usize{
+        let node = self.create(lc, nc, attrs);
+        let (parent, before) = self.insertion_place(None);
+        self.insert_at(parent, before, node);
+        self.open.push(node);
+        node
+    }
+
+    fn insert_text(&mut self, text:&str){
+        if text.is_empty(){
+            return
+        }
+        let (parent, before) = self.insertion_place(None);
+        if parent == 0{ // no text in the document node
+            return
+        }
+        // merge with the preceding text node
+        let children = &self.nodes[parent].children;
+        let prev = match before{
+            Some(before)=>children.iter().position(|c| *c == before).and_then(|i| if i>0{Some(children[i-1])}else{None}),
+            None=>children.last().cloned()
+        };
+        if let Some(prev) = prev{
+            if let Kind::Text(existing) = &mut self.nodes[prev].kind{
+                existing.push_str(text);
+                return
+            }
+        }
+        self.nodes.push(Node{kind:Kind::Text(text.into()), parent:None, children:Vec::new()});
+        let node = self.nodes.len() - 1;
+        self.insert_at(parent, before, node);
+    }
+
+    // stack helpers
+
+    fn in_scope_with(&self, scope:Scope, test:impl Fn(LiveId)->bool)->bool{
+        for node in self.open.iter().rev(){
+            let lc = self.lc(*node);
+            if test(lc){
+                return true
+            }
+            let boundary = match scope{
+                Scope::Select=>!matches!(lc, live_id!(optgroup) | live_id!(option)),
+                Scope::Table=>matches!(lc, live_id!(html) | live_id!(table) | live_id!(template)),
+                _=>{
+                    matches!(lc, live_id!(applet) | live_id!(caption) | live_id!(html) | live_id!(table) |
+                        live_id!(td) | live_id!(th) | live_id!(marquee) | live_id!(object) | live_id!(template)) ||
+                    scope == Scope::ListItem && matches!(lc, live_id!(ol) | live_id!(ul)) ||
+                    scope == Scope::Button && lc == live_id!(button)
+                }
+            };
+            if boundary{
+                return false
+            }
+        }
+        false
+    }
+
+    fn in_scope(&self, scope:Scope, tag:LiveId)->bool{
+        self.in_scope_with(scope, |lc| lc == tag)
+    }
+
+    fn pop_until(&mut self, test:impl Fn(LiveId)->bool){
+        while let Some(node) = self.open.pop(){
+            if test(self.lc(node)){
+                break
+            }
+        }
+    }
+
+    fn pop_until_tag(&mut self, tag:LiveId){
+        self.pop_until(|lc| lc == tag)
+    }
+
+    fn generate_implied_end_tags(&mut self, except:Option<LiveId>){
+        while is_implied_end(self.current_lc()) && Some(self.current_lc()) != except{
+            self.open.pop();
+        }
+    }
+
+    fn close_p(&mut self){
+        self.generate_implied_end_tags(Some(live_id!(p)));
+        if self.current_lc() != live_id!(p){
+            self.error("unexpected element while closing p");
+        }
+        self.pop_until_tag(live_id!(p));
+    }
+
+    fn close_p_in_button_scope(&mut self){
+        if self.in_scope(Scope::Button, live_id!(p)){
+            self.close_p();
+        }
+    }
+
+    fn clear_to_context(&mut self, test:impl Fn(LiveId)->bool){
+        while !test(self.current_lc()) && !matches!(self.current_lc(), live_id!(html) | live_id!(template)) && !self.open.is_empty(){
+            self.open.pop();
+        }
+    }
+
+    fn reset_mode(&mut self){
+        for (i, node) in self.open.iter().enumerate().rev(){
+            let last = i == 0;
+            let lc = if last{self.context.unwrap_or(self.lc(*node))}else{self.lc(*node)};
+            let mode = match lc{
+                live_id!(select)=>{
+                    if self.open[..i].iter().any(|n| self.lc(*n) == live_id!(table)){
+                        Mode::InSelectInTable
+                    }
+                    else{
+                        Mode::InSelect
+                    }
+                }
+                live_id!(td) | live_id!(th) if !last=>Mode::InCell,
+                live_id!(tr)=>Mode::InRow,
+                live_id!(tbody) | live_id!(thead) | live_id!(tfoot)=>Mode::InTableBody,
+                live_id!(caption)=>Mode::InCaption,
+                live_id!(colgroup)=>Mode::InColumnGroup,
+                live_id!(table)=>Mode::InTable,
+                live_id!(head) if !last=>Mode::InHead,
+                live_id!(body)=>Mode::InBody,
+                live_id!(html)=>{
+                    if self.head.is_none() && self.context.is_none(){Mode::BeforeHead}else{Mode::AfterHead}
+                }
+                _ if last=>Mode::InBody,
+                _=>continue
+            };
+            self.mode = mode;
+            return
+        }
+        self.mode = Mode::InBody;
+    }
+
+    // active formatting elements
+
+    fn push_formatting(&mut self, node:usize){
+        // only 3 identical elements after the last marker are kept
+        let mut same = Vec::new();
+        for (i, entry) in self.formatting.iter().enumerate().rev(){
+            match entry{
+                None=>break,
+                Some(other)=>if self.same_element(*other, node){
+                    same.push(i);
+                }
+            }
+        }
+        if same.len() >= 3{
+            self.formatting.remove(*same.last().unwrap());
+        }
+        self.formatting.push(Some(node));
+    }
+
+    fn same_element(&self, a:usize, b:usize)->bool{
+        match (&self.nodes[a].kind, &self.nodes[b].kind){
+            (Kind::Element{lc:la, attrs:aa, ..}, Kind::Element{lc:lb, attrs:ab, ..})=>{
+                la == lb && aa.len() == ab.len() && aa.iter().all(|a| ab.contains(a))
+            }
+            _=>false
+        }
+    }
+
+    fn clear_formatting_to_marker(&mut self){
+        while let Some(entry) = self.formatting.pop(){
+            if entry.is_none(){
+                break
+            }
+        }
+    }
+
+    fn clone_element(&mut self, node:usize)->usize{
+        let (lc, nc, attrs) = match &self.nodes[node].kind{
+            Kind::Element{lc, nc, attrs}=>(*lc, *nc, attrs.clone()),
+            _=>panic!()
+        };
+        self.create(lc, nc, attrs)
+    }
+
+    fn reconstruct_formatting(&mut self){
+        let Some(Some(last)) = self.formatting.last() else {return};
+        if self.open.contains(last){
+            return
+        }
+        let mut index = self.formatting.len() - 1;
+        // rewind to the entry after the last marker or open element
+        while index > 0{
+            match self.formatting[index - 1]{
+                None=>break,
+                Some(node) if self.open.contains(&node)=>break,
+                _=>index -= 1
+            }
+        }
+        while index < self.formatting.len(){
+            let entry = self.formatting[index].unwrap();
+            let clone = self.clone_element(entry);
+            let (parent, before) = self.insertion_place(None);
+            self.insert_at(parent, before, clone);
+            self.open.push(clone);
+            self.formatting[index] = Some(clone);
+            index += 1;
+        }
+    }
+
+    // returns true when the end tag has to be handled as 'any other end tag'
+    fn adoption_agency(&mut self, subject:LiveId)->bool{
+        let current = self.current();
+        if self.lc(current) == subject && !self.formatting.contains(&Some(current)){
+            self.open.pop();
+            return false
+        }
+        for _ in 0..8{
+            let mut formatting_index = None;
+            for (i, entry) in self.formatting.iter().enumerate().rev(){
+                match entry{
+                    None=>break,
+                    Some(node) if self.lc(*node) == subject=>{
+                        formatting_index = Some(i);
+                        break
+                    }
+                    _=>()
+                }
+            }
+            let Some(formatting_index) = formatting_index else {return true};
+            let formatting_element = self.formatting[formatting_index].unwrap();
+            let Some(stack_index) = self.open.iter().position(|n| *n == formatting_element) else{
+                self.error("formatting element not open");
+                self.formatting.remove(formatting_index);
+                return false
+            };
+            if !self.in_scope_with(Scope::Default, |lc| lc == subject){
+                self.error("formatting element not in scope");
+                return false
+            }
+            let furthest_block = self.open[stack_index+1..].iter().position(|n| is_special(self.lc(*n))).map(|i| i + stack_index + 1);
+            let Some(furthest_index) = furthest_block else{
+                self.open.truncate(stack_index);
+                self.formatting.remove(formatting_index);
+                return false
+            };
+            let furthest_block = self.open[furthest_index];
+            let common_ancestor = self.open[stack_index - 1];
+            let mut bookmark = formatting_index;
+            let mut node_index = furthest_index;
+            let mut last_node = furthest_block;
+            let mut counter = 0;
+            loop{
+                counter += 1;
+                node_index -= 1;
+                let node = self.open[node_index];
+                if node == formatting_element{
+                    break
+                }
+                let mut entry = self.formatting.iter().position(|e| *e == Some(node));
+                if counter > 3{
+                    if let Some(e) = entry{
+                        self.formatting.remove(e);
+                        if e < bookmark{
+                            bookmark -= 1;
+                        }
+                        entry = None;
+                    }
+                }
+                let Some(entry) = entry else{
+                    self.open.remove(node_index);
+                    continue
+                };
+                let clone = self.clone_element(node);
+                self.formatting[entry] = Some(clone);
+                self.open[node_index] = clone;
+                if last_node == furthest_block{
+                    bookmark = entry + 1;
+                }
+                self.append(clone, last_node);
+                last_node = clone;
+            }
+            let (parent, before) = self.insertion_place(Some(common_ancestor));
+            self.insert_at(parent, before, last_node);
+            let clone = self.clone_element(formatting_element);
+            for child in std::mem::take(&mut self.nodes[furthest_block].children){
+                self.nodes[child].parent = None;
+                self.append(clone, child);
+            }
+            self.append(furthest_block, clone);
+            if let Some(index) = self.formatting.iter().position(|e| *e == Some(formatting_element)){
+                self.formatting.remove(index);
+                if index < bookmark{
+                    bookmark -= 1;
+                }
+            }
+            self.formatting.insert(bookmark.min(self.formatting.len()), Some(clone));
+            self.open.retain(|n| *n != formatting_element);
+            let furthest_index = self.open.iter().position(|n| *n == furthest_block).unwrap();
+            self.open.insert(furthest_index + 1, clone);
+        }
+        false
+    }
+
+    // token processing
+
+    fn process(&mut self, token:Token){
+        let mut token = token;
+        // a newline directly after <pre>, <listing> and <textarea> is dropped
+        if self.skip_newline{
+            self.skip_newline = false;
+            if let Token::Text(text) = &mut token{
+                if text.starts_with('\n'){
+                    text.remove(0);
+                }
+            }
+        }
+        self.process_in(self.mode, token);
+    }
+
+    fn process_in(&mut self, mode:Mode, token:Token){
+        match mode{
+            Mode::Initial=>match token{
+                Token::Text(text) if is_ws(&text)=>(),
+                Token::Start{lc:live_id!(html), nc, attrs, ..}=>{
+                    let html = self.create(live_id!(html), nc, attrs);
+                    self.append(0, html);
+                    self.open.push(html);
+                    self.mode = Mode::BeforeHead;
+                }
+                Token::End{lc, ..} if !matches!(lc, live_id!(head) | live_id!(body) | live_id!(html) | live_id!(br))=>{
+                    self.error("unexpected end tag before html");
+                }
+                token=>{
+                    let html = self.create(live_id!(html), live_id!(html), Vec::new());
+                    self.append(0, html);
+                    self.open.push(html);
+                    self.mode = Mode::BeforeHead;
+                    self.process_in(Mode::BeforeHead, token);
+                }
+            }
+            Mode::BeforeHead=>match token{
+                Token::Text(text) if is_ws(&text)=>(),
+                Token::Start{lc:live_id!(html), ..}=>self.process_in(Mode::InBody, token),
+                Token::Start{lc:live_id!(head), nc, attrs, ..}=>{
+                    self.head = Some(self.insert_element(live_id!(head), nc, attrs));
+                    self.mode = Mode::InHead;
+                }
+                Token::End{lc, ..} if !matches!(lc, live_id!(head) | live_id!(body) | live_id!(html) | live_id!(br))=>{
+                    self.error("unexpected end tag before head");
+                }
+                token=>{
+                    self.head = Some(self.insert_element(live_id!(head), live_id!(head), Vec::new()));
+                    self.mode = Mode::InHead;
+                    self.process_in(Mode::InHead, token);
+                }
+            }
+            Mode::InHead=>match token{
+                Token::Text(text) if is_ws(&text)=>self.insert_text(&text),
+                Token::Text(text)=>{
+                    // leading whitespace stays in the head
+                    let split = text.find(|c| !matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0c')).unwrap_or(0);
+                    self.insert_text(&text[..split]);
+                    self.open.pop();
+                    self.mode = Mode::AfterHead;
+                    self.process_in(Mode::AfterHead, Token::Text(text[split..].into()));
+                }
+                Token::Start{lc:live_id!(html), ..}=>self.process_in(Mode::InBody, token),
+                Token::Start{lc:lc@(live_id!(base) | live_id!(basefont) | live_id!(bgsound) | live_id!(link) | live_id!(meta)), nc, attrs, ..}=>{
+                    self.insert_element(lc, nc, attrs);
+                    self.open.pop();
+                }
+                Token::Start{lc:lc@(live_id!(title) | live_id!(noscript) | live_id!(noframes) | live_id!(style) | live_id!(script) | live_id!(template)), nc, attrs, self_closing}=>{
+                    self.insert_element(lc, nc, attrs);
+                    if self_closing{
+                        self.open.pop();
+                    }
+                    else{
+                        self.original_mode = self.mode;
+                        self.mode = Mode::Text;
+                    }
+                }
+                Token::End{lc:live_id!(head), ..}=>{
+                    self.open.pop();
+                    self.mode = Mode::AfterHead;
+                }
+                Token::Start{lc:live_id!(head), ..}=>self.error("unexpected head"),
+                Token::End{lc, ..} if !matches!(lc, live_id!(body) | live_id!(html) | live_id!(br))=>{
+                    self.error("unexpected end tag in head");
+                }
+                token=>{
+                    self.open.pop();
+                    self.mode = Mode::AfterHead;
+                    self.process_in(Mode::AfterHead, token);
+                }
+            }
+            Mode::AfterHead=>match token{
+                Token::Text(text) if is_ws(&text)=>self.insert_text(&text),
+                Token::Start{lc:live_id!(html), ..}=>self.process_in(Mode::InBody, token),
+                Token::Start{lc:live_id!(body), nc, attrs, ..}=>{
+                    self.insert_element(live_id!(body), nc, attrs);
+                    self.mode = Mode::InBody;
+                }
+                Token::Start{lc:live_id!(base) | live_id!(basefont) | live_id!(bgsound) | live_id!(link) | live_id!(meta) |
+                    live_id!(noframes) | live_id!(script) | live_id!(style) | live_id!(template) | live_id!(title), ..}=>{
+                    self.error("head element after head");
+                    let head = self.head.unwrap();
+                    self.open.push(head);
+                    self.process_in(Mode::InHead, token);
+                    self.open.retain(|n| *n != head);
+                }
+                Token::Start{lc:live_id!(head), ..}=>self.error("unexpected head"),
+                Token::End{lc, ..} if !matches!(lc, live_id!(body) | live_id!(html) | live_id!(br))=>{
+                    self.error("unexpected end tag after head");
+                }
+                token=>{
+                    self.insert_element(live_id!(body), live_id!(body), Vec::new());
+                    self.mode = Mode::InBody;
+                    self.process_in(Mode::InBody, token);
+                }
+            }
+            Mode::Text=>match token{
+                Token::Text(text)=>self.insert_text(&text),
+                Token::End{..}=>{
+                    self.open.pop();
+                    self.mode = self.original_mode;
+                }
+                token=>{
+                    self.error("unexpected token in text");
+                    self.open.pop();
+                    self.mode = self.original_mode;
+                    self.process_in(self.mode, token);
+                }
+            }
+            Mode::InBody=>self.process_in_body(token),
+            Mode::InTable=>self.process_in_table(token),
+            Mode::InTableText=>match token{
+                Token::Text(text)=>self.table_text.push_str(&text),
+                token=>{
+                    let text = std::mem::take(&mut self.table_text);
+                    if is_ws(&text){
+                        self.insert_text(&text);
+                    }
+                    else{
+                        self.error("text in table");
+                        self.foster_parenting = true;
+                        self.process_in_body(Token::Text(text));
+                        self.foster_parenting = false;
+                    }
+                    self.mode = self.original_mode;
+                    self.process_in(self.mode, token);
+                }
+            }
+            Mode::InCaption=>match token{
+                Token::End{lc:live_id!(caption), ..}=>{
+                    self.close_caption();
+                }
+                Token::Start{lc, ..} if is_table_part(lc)=>{
+                    if self.close_caption(){
+                        self.process_in(self.mode, token);
+                    }
+                }
+                Token::End{lc:live_id!(table), ..}=>{
+                    if self.close_caption(){
+                        self.process_in(self.mode, token);
+                    }
+                }
+                Token::End{lc, ..} if lc == live_id!(body) || lc == live_id!(html) || is_table_part(lc)=>{
+                    self.error("unexpected end tag in caption");
+                }
+                token=>self.process_in_body(token)
+            }
+            Mode::InColumnGroup=>match token{
+                Token::Text(text) if is_ws(&text)=>self.insert_text(&text),
+                Token::Start{lc:live_id!(html), ..}=>self.process_in_body(token),
+                Token::Start{lc:live_id!(col), nc, attrs, ..}=>{
+                    self.insert_element(live_id!(col), nc, attrs);
+                    self.open.pop();
+                }
+                Token::End{lc:live_id!(colgroup), ..}=>{
+                    if self.current_lc() == live_id!(colgroup){
+                        self.open.pop();
+                        self.mode = Mode::InTable;
+                    }
+                }
+                Token::End{lc:live_id!(col), ..}=>self.error("unexpected col end tag"),
+                Token::Eof=>self.process_in_body(token),
+                token=>{
+                    if self.current_lc() == live_id!(colgroup){
+                        self.open.pop();
+                        self.mode = Mode::InTable;
+                        self.process_in(Mode::InTable, token);
+                    }
+                }
+            }
+            Mode::InTableBody=>match token{
+                Token::Start{lc:live_id!(tr), nc, attrs, ..}=>{
+                    self.clear_to_context(is_table_section);
+                    self.insert_element(live_id!(tr), nc, attrs);
+                    self.mode = Mode::InRow;
+                }
+                Token::Start{lc:live_id!(th) | live_id!(td), ..}=>{
+                    self.error("cell outside of row");
+                    self.clear_to_context(is_table_section);
+                    self.insert_element(live_id!(tr), live_id!(tr), Vec::new());
+                    self.mode = Mode::InRow;
+                    self.process_in(Mode::InRow, token);
+                }
+                Token::End{lc, ..} if is_table_section(lc)=>{
+                    if self.in_scope(Scope::Table, lc){
+                        self.clear_to_context(is_table_section);
+                        self.open.pop();
+                        self.mode = Mode::InTable;
+                    }
+                }
+                Token::Start{lc:live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead), ..} |
+                Token::End{lc:live_id!(table), ..}=>{
+                    if self.in_scope_with(Scope::Table, is_table_section){
+                        self.clear_to_context(is_table_section);
+                        self.open.pop();
+                        self.mode = Mode::InTable;
+                        self.process_in(Mode::InTable, token);
+                    }
+                }
+                Token::End{lc:live_id!(body) | live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(html) | live_id!(td) | live_id!(th) | live_id!(tr), ..}=>{
+                    self.error("unexpected end tag in table body");
+                }
+                token=>self.process_in_table(token)
+            }
+            Mode::InRow=>match token{
+                Token::Start{lc:lc@(live_id!(th) | live_id!(td)), nc, attrs, ..}=>{
+                    self.clear_to_context(|lc| lc == live_id!(tr));
+                    self.insert_element(lc, nc, attrs);
+                    self.mode = Mode::InCell;
+                    self.formatting.push(None);
+                }
+                Token::End{lc:live_id!(tr), ..}=>{
+                    self.close_row();
+                }
+                Token::Start{lc:live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr), ..} |
+                Token::End{lc:live_id!(table), ..}=>{
+                    if self.close_row(){
+                        self.process_in(Mode::InTableBody, token);
+                    }
+                }
+                Token::End{lc, ..} if is_table_section(lc)=>{
+                    if self.in_scope(Scope::Table, lc) && self.close_row(){
+                        self.process_in(Mode::InTableBody, token);
+                    }
+                }
+                Token::End{lc:live_id!(body) | live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(html) | live_id!(td) | live_id!(th), ..}=>{
+                    self.error("unexpected end tag in row");
+                }
+                token=>self.process_in_table(token)
+            }
+            Mode::InCell=>match token{
+                Token::End{lc:lc@(live_id!(td) | live_id!(th)), ..}=>{
+                    if self.in_scope(Scope::Table, lc){
+                        self.generate_implied_end_tags(None);
+                        self.pop_until_tag(lc);
+                        self.clear_formatting_to_marker();
+                        self.mode = Mode::InRow;
+                    }
+                }
+                Token::Start{lc, ..} if is_table_part(lc)=>{
+                    if self.in_scope_with(Scope::Table, |lc| lc == live_id!(td) || lc == live_id!(th)){
+                        self.close_cell();
+                        self.process_in(self.mode, token);
+                    }
+                }
+                Token::End{lc:live_id!(body) | live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(html), ..}=>{
+                    self.error("unexpected end tag in cell");
+                }
+                Token::End{lc:lc@(live_id!(table) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr)), ..}=>{
+                    if self.in_scope(Scope::Table, lc){
+                        self.close_cell();
+                        self.process_in(self.mode, token);
+                    }
+                }
+                token=>self.process_in_body(token)
+            }
+            Mode::InSelect | Mode::InSelectInTable=>match token{
+                Token::Start{lc:live_id!(caption) | live_id!(table) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr) | live_id!(td) | live_id!(th), ..} if mode == Mode::InSelectInTable=>{
+                    self.pop_until_tag(live_id!(select));
+                    self.reset_mode();
+                    self.process_in(self.mode, token);
+                }
+                Token::End{lc:lc@(live_id!(caption) | live_id!(table) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr) | live_id!(td) | live_id!(th)), ..} if mode == Mode::InSelectInTable=>{
+                    if self.in_scope(Scope::Table, lc){
+                        self.pop_until_tag(live_id!(select));
+                        self.reset_mode();
+                        self.process_in(self.mode, token);
+                    }
+                }
+                Token::Text(text)=>self.insert_text(&text),
+                Token::Start{lc:live_id!(html), ..}=>self.process_in_body(token),
+                Token::Start{lc:live_id!(option), nc, attrs, ..}=>{
+                    if self.current_lc() == live_id!(option){
+                        self.open.pop();
+                    }
+                    self.insert_element(live_id!(option), nc, attrs);
+                }
+                Token::Start{lc:live_id!(optgroup), nc, attrs, ..}=>{
+                    if self.current_lc() == live_id!(option){
+                        self.open.pop();
+                    }
+                    if self.current_lc() == live_id!(optgroup){
+                        self.open.pop();
+                    }
+                    self.insert_element(live_id!(optgroup), nc, attrs);
+                }
+                Token::End{lc:live_id!(optgroup), ..}=>{
+                    let len = self.open.len();
+                    if self.current_lc() == live_id!(option) && len > 1 && self.lc(self.open[len - 2]) == live_id!(optgroup){
+                        self.open.pop();
+                    }
+                    if self.current_lc() == live_id!(optgroup){
+                        self.open.pop();
+                    }
+                }
+                Token::End{lc:live_id!(option), ..}=>{
+                    if self.current_lc() == live_id!(option){
+                        self.open.pop();
+                    }
+                }
+                Token::End{lc:live_id!(select), ..} | Token::Start{lc:live_id!(select), ..}=>{
+                    if self.in_scope(Scope::Select, live_id!(select)){
+                        self.pop_until_tag(live_id!(select));
+                        self.reset_mode();
+                    }
+                }
+                Token::Start{lc:live_id!(input) | live_id!(keygen) | live_id!(textarea), ..}=>{
+                    if self.in_scope(Scope::Select, live_id!(select)){
+                        self.pop_until_tag(live_id!(select));
+                        self.reset_mode();
+                        self.process_in(self.mode, token);
+                    }
+                }
+                Token::Start{lc:live_id!(script) | live_id!(template), ..}=>self.process_in(Mode::InHead, token),
+                Token::Eof=>self.process_in_body(token),
+                _=>self.error("unexpected token in select"),
+            }
+            Mode::AfterBody | Mode::AfterAfterBody=>match token{
+                Token::Text(text) if is_ws(&text)=>self.process_in_body(Token::Text(text)),
+                Token::Start{lc:live_id!(html), ..}=>self.process_in_body(token),
+                Token::End{lc:live_id!(html), ..} if mode == Mode::AfterBody=>{
+                    self.mode = Mode::AfterAfterBody;
+                }
+                Token::Eof=>(),
+                token=>{
+                    self.error("content after body");
+                    self.mode = Mode::InBody;
+                    self.process_in_body(token);
+                }
+            }
+        }
+    }
+
+    fn close_caption(&mut self)->bool{
+        if !self.in_scope(Scope::Table, live_id!(caption)){
+            self.error("no caption to close");
+            return false
+        }
+        self.generate_implied_end_tags(None);
+        self.pop_until_tag(live_id!(caption));
+        self.clear_formatting_to_marker();
+        self.mode = Mode::InTable;
+        true
+    }
+
+    fn close_row(&mut self)->bool{
+        if !self.in_scope(Scope::Table, live_id!(tr)){
+            self.error("no row to close");
+            return false
+        }
+        self.clear_to_context(|lc| lc == live_id!(tr));
+        self.open.pop();
+        self.mode = Mode::InTableBody;
+        true
+    }
+
+    fn close_cell(&mut self){
+        self.generate_implied_end_tags(None);
+        self.pop_until(|lc| lc == live_id!(td) || lc == live_id!(th));
+        self.clear_formatting_to_marker();
+        self.mode = Mode::InRow;
+    }
+
+    fn process_in_table(&mut self, token:Token){
+        match token{
+            Token::Text(text) if matches!(self.current_lc(), live_id!(table) | live_id!(tbody) | live_id!(tfoot) | live_id!(thead) | live_id!(tr))=>{
+                self.table_text.clear();
+                self.original_mode = self.mode;
+                self.mode = Mode::InTableText;
+                self.process_in(Mode::InTableText, Token::Text(text));
+            }
+            Token::Start{lc:live_id!(caption), nc, attrs, ..}=>{
+                self.clear_to_context(|lc| lc == live_id!(table));
+                self.formatting.push(None);
+                self.insert_element(live_id!(caption), nc, attrs);
+                self.mode = Mode::InCaption;
+            }
+            Token::Start{lc:live_id!(colgroup), nc, attrs, ..}=>{
+                self.clear_to_context(|lc| lc == live_id!(table));
+                self.insert_element(live_id!(colgroup), nc, attrs);
+                self.mode = Mode::InColumnGroup;
+            }
+            Token::Start{lc:live_id!(col), ..}=>{
+                self.clear_to_context(|lc| lc == live_id!(table));
+                self.insert_element(live_id!(colgroup), live_id!(colgroup), Vec::new());
+                self.mode = Mode::InColumnGroup;
+                self.process_in(Mode::InColumnGroup, token);
+            }
+            Token::Start{lc:lc@(live_id!(tbody) | live_id!(tfoot) | live_id!(thead)), nc, attrs, ..}=>{
+                self.clear_to_context(|lc| lc == live_id!(table));
+                self.insert_element(lc, nc, attrs);
+                self.mode = Mode::InTableBody;
+            }
+            Token::Start{lc:live_id!(td) | live_id!(th) | live_id!(tr), ..}=>{
+                self.clear_to_context(|lc| lc == live_id!(table));
+                self.insert_element(live_id!(tbody), live_id!(tbody), Vec::new());
+                self.mode = Mode::InTableBody;
+                self.process_in(Mode::InTableBody, token);
+            }
+            Token::Start{lc:live_id!(table), ..}=>{
+                self.error("table inside table");
+                if self.in_scope(Scope::Table, live_id!(table)){
+                    self.pop_until_tag(live_id!(table));
+                    self.reset_mode();
+                    self.process_in(self.mode, token);
+                }
+            }
+            Token::End{lc:live_id!(table), ..}=>{
+                if self.in_scope(Scope::Table, live_id!(table)){
+                    self.pop_until_tag(live_id!(table));
+                    self.reset_mode();
+                }
+            }
+            Token::End{lc, ..} if lc == live_id!(body) || lc == live_id!(html) || is_table_part(lc)=>{
+                self.error("unexpected end tag in table");
+            }
+            Token::Start{lc:live_id!(style) | live_id!(script) | live_id!(template), ..}=>{
+                self.process_in(Mode::InHead, token);
+            }
+            Token::Start{lc:live_id!(input), nc, ref attrs, ..} if attrs.iter().any(|a| a.lc == live_id!(type) && a.value.eq_ignore_ascii_case("hidden"))=>{
+                let attrs = attrs.clone();
+                self.insert_element(live_id!(input), nc, attrs);
+                self.open.pop();
+            }
+            Token::Eof=>self.process_in_body(token),
+            token=>{
+                self.error("content in table");
+                self.foster_parenting = true;
+                self.process_in_body(token);
+                self.foster_parenting = false;
+            }
+        }
+    }
+
+    fn process_in_body(&mut self, token:Token){
+        match token{
+            Token::Text(text)=>{
+                self.reconstruct_formatting();
+                self.insert_text(&text);
+            }
+            Token::Eof=>(),
+            Token::Start{lc:live_id!(html), attrs, ..}=>{
+                self.error("unexpected html");
+                if let Some(html) = self.open.first().cloned(){
+                    self.merge_attrs(html, attrs);
+                }
+            }
+            Token::Start{lc:live_id!(base) | live_id!(basefont) | live_id!(bgsound) | live_id!(link) | live_id!(meta) |
+                live_id!(noframes) | live_id!(script) | live_id!(style) | live_id!(template) | live_id!(title), ..}=>{
+                self.process_in(Mode::InHead, token);
+            }
+            Token::Start{lc:live_id!(body), attrs, ..}=>{
+                self.error("unexpected body");
+                if self.open.len() > 1 && self.lc(self.open[1]) == live_id!(body){
+                    let body = self.open[1];
+                    self.merge_attrs(body, attrs);
+                }
+            }
+            Token::Start{lc:lc@(live_id!(address) | live_id!(article) | live_id!(aside) | live_id!(blockquote) |
+                live_id!(center) | live_id!(details) | live_id!(dialog) | live_id!(dir) | live_id!(div) | live_id!(dl) |
+                live_id!(fieldset) | live_id!(figcaption) | live_id!(figure) | live_id!(footer) | live_id!(header) |
+                live_id!(hgroup) | live_id!(main) | live_id!(menu) | live_id!(nav) | live_id!(ol) | live_id!(p) |
+                live_id!(search) | live_id!(section) | live_id!(summary) | live_id!(ul) | live_id!(form) |
+                live_id!(plaintext)), nc, attrs, self_closing}=>{
+                self.close_p_in_button_scope();
+                self.insert_element(lc, nc, attrs);
+                if self_closing{
+                    self.open.pop();
+                }
+            }
+            Token::Start{lc, nc, attrs, self_closing} if is_heading(lc)=>{
+                self.close_p_in_button_scope();
+                if is_heading(self.current_lc()){
+                    self.error("nested heading");
+                    self.open.pop();
+                }
+                self.insert_element(lc, nc, attrs);
+                if self_closing{
+                    self.open.pop();
+                }
+            }
+            Token::Start{lc:lc@(live_id!(pre) | live_id!(listing)), nc, attrs, ..}=>{
+                self.close_p_in_button_scope();
+                self.insert_element(lc, nc, attrs);
+                self.skip_newline = true;
+            }
+            Token::Start{lc:lc@(live_id!(li) | live_id!(dd) | live_id!(dt)), nc, attrs, ..}=>{
+                for node in self.open.clone().into_iter().rev(){
+                    let node_lc = self.lc(node);
+                    let closes = if lc == live_id!(li){
+                        node_lc == live_id!(li)
+                    }
+                    else{
+                        node_lc == live_id!(dd) || node_lc == live_id!(dt)
+                    };
+                    if closes{
+                        self.generate_implied_end_tags(Some(node_lc));
+                        self.pop_until_tag(node_lc);
+                        break
+                    }
+                    if is_special(node_lc) && !matches!(node_lc, live_id!(address) | live_id!(div) | live_id!(p)){
+                        break
+                    }
+                }
+                self.close_p_in_button_scope();
+                self.insert_element(lc, nc, attrs);
+            }
+            Token::Start{lc:live_id!(button), nc, attrs, ..}=>{
+                if self.in_scope(Scope::Default, live_id!(button)){
+                    self.error("nested button");
+                    self.generate_implied_end_tags(None);
+                    self.pop_until_tag(live_id!(button));
+                }
+                self.reconstruct_formatting();
+                self.insert_element(live_id!(button), nc, attrs);
+            }
+            Token::Start{lc:live_id!(a), nc, attrs, self_closing}=>{
+                let open_a = self.formatting.iter().rev()
+                    .take_while(|e| e.is_some())
+                    .find(|e| self.lc(e.unwrap()) == live_id!(a)).map(|e| e.unwrap());
+                if let Some(open_a) = open_a{
+                    self.error("nested a");
+                    self.adoption_agency(live_id!(a));
+                    self.formatting.retain(|e| *e != Some(open_a));
+                    self.open.retain(|n| *n != open_a);
+                }
+                self.reconstruct_formatting();
+                let node = self.insert_element(live_id!(a), nc, attrs);
+                self.push_formatting(node);
+                if self_closing{
+                    self.open.pop();
+                }
+            }
+            Token::Start{lc:live_id!(nobr), nc, attrs, ..}=>{
+                self.reconstruct_formatting();
+                if self.in_scope(Scope::Default, live_id!(nobr)){
+                    self.error("nested nobr");
+                    self.adoption_agency(live_id!(nobr));
+                    self.reconstruct_formatting();
+                }
+                let node = self.insert_element(live_id!(nobr), nc, attrs);
+                self.push_formatting(node);
+            }
+            Token::Start{lc, nc, attrs, self_closing} if is_formatting(lc)=>{
+                self.reconstruct_formatting();
+                let node = self.insert_element(lc, nc, attrs);
+                self.push_formatting(node);
+                if self_closing{
+                    self.open.pop();
+                }
+            }
+            Token::Start{lc:lc@(live_id!(applet) | live_id!(marquee) | live_id!(object)), nc, attrs, ..}=>{
+                self.reconstruct_formatting();
+                self.insert_element(lc, nc, attrs);
+                self.formatting.push(None);
+            }
+            Token::Start{lc:live_id!(table), nc, attrs, ..}=>{
+                self.close_p_in_button_scope();
+                self.insert_element(live_id!(table), nc, attrs);
+                self.mode = Mode::InTable;
+            }
+            Token::Start{lc:live_id!(hr), nc, attrs, ..}=>{
+                self.close_p_in_button_scope();
+                self.insert_element(live_id!(hr), nc, attrs);
+                self.open.pop();
+            }
+            Token::Start{lc:live_id!(image), attrs, ..}=>{
+                self.error("image instead of img");
+                self.reconstruct_formatting();
+                self.insert_element(live_id!(img), live_id!(img), attrs);
+                self.open.pop();
+            }
+            Token::Start{lc:live_id!(textarea), nc, attrs, ..}=>{
+                self.insert_element(live_id!(textarea), nc, attrs);
+                self.skip_newline = true;
+                self.original_mode = self.mode;
+                self.mode = Mode::Text;
+            }
+            Token::Start{lc:live_id!(xmp), nc, attrs, ..}=>{
+                self.close_p_in_button_scope();
+                self.reconstruct_formatting();
+                self.insert_element(live_id!(xmp), nc, attrs);
+                self.original_mode = self.mode;
+                self.mode = Mode::Text;
+            }
+            Token::Start{lc:lc@(live_id!(iframe) | live_id!(noembed)), nc, attrs, ..}=>{
+                self.insert_element(lc, nc, attrs);
+                self.original_mode = self.mode;
+                self.mode = Mode::Text;
+            }
+            Token::Start{lc:live_id!(select), nc, attrs, ..}=>{
+                self.reconstruct_formatting();
+                self.insert_element(live_id!(select), nc, attrs);
+                self.mode = if matches!(self.mode, Mode::InTable | Mode::InCaption | Mode::InTableBody | Mode::InRow | Mode::InCell){
+                    Mode::InSelectInTable
+                }
+                else{
+                    Mode::InSelect
+                };
+            }
+            Token::Start{lc:lc@(live_id!(optgroup) | live_id!(option)), nc, attrs, ..}=>{
+                if self.current_lc() == live_id!(option){
+                    self.open.pop();
+                }
+                self.reconstruct_formatting();
+                self.insert_element(lc, nc, attrs);
+            }
+            Token::Start{lc:lc@(live_id!(rb) | live_id!(rtc) | live_id!(rp) | live_id!(rt)), nc, attrs, ..}=>{
+                if self.in_scope(Scope::Default, live_id!(ruby)){
+                    let except = if matches!(lc, live_id!(rp) | live_id!(rt)){Some(live_id!(rtc))}else{None};
+                    self.generate_implied_end_tags(except);
+                }
+                self.insert_element(lc, nc, attrs);
+            }
+            Token::Start{lc:live_id!(caption) | live_id!(col) | live_id!(colgroup) | live_id!(frame) | live_id!(head) |
+                live_id!(tbody) | live_id!(td) | live_id!(tfoot) | live_id!(th) | live_id!(thead) | live_id!(tr), ..}=>{
+                self.error("table or head element in body");
+            }
+            Token::Start{lc, nc, attrs, self_closing}=>{
+                // any other start tag, including void elements
+                self.reconstruct_formatting();
+                self.insert_element(lc, nc, attrs);
+                if self_closing || is_void(lc){
+                    self.open.pop();
+                }
+            }
+            Token::End{lc:live_id!(body), ..}=>{
+                if self.in_scope(Scope::Default, live_id!(body)){
+                    self.mode = Mode::AfterBody;
+                }
+            }
+            Token::End{lc:live_id!(html), ..}=>{
+                if self.in_scope(Scope::Default, live_id!(body)){
+                    self.mode = Mode::AfterBody;
+                    self.process_in(Mode::AfterBody, token);
+                }
+            }
+            Token::End{lc:lc@(live_id!(address) | live_id!(article) | live_id!(aside) | live_id!(blockquote) |
+                live_id!(button) | live_id!(center) | live_id!(details) | live_id!(dialog) | live_id!(dir) |
+                live_id!(div) | live_id!(dl) | live_id!(fieldset) | live_id!(figcaption) | live_id!(figure) |
+                live_id!(footer) | live_id!(header) | live_id!(hgroup) | live_id!(listing) | live_id!(main) |
+                live_id!(menu) | live_id!(nav) | live_id!(ol) | live_id!(pre) | live_id!(search) | live_id!(section) |
+                live_id!(summary) | live_id!(ul) | live_id!(form)), ..}=>{
+                if self.in_scope(Scope::Default, lc){
+                    self.generate_implied_end_tags(None);
+                    self.pop_until_tag(lc);
+                }
+                else{
+                    self.error("end tag without open element");
+                }
+            }
+            Token::End{lc:live_id!(p), ..}=>{
+                if !self.in_scope(Scope::Button, live_id!(p)){
+                    self.error("p end tag without open p");
+                    self.insert_element(live_id!(p), live_id!(p), Vec::new());
+                }
+                self.close_p();
+            }
+            Token::End{lc:live_id!(li), ..}=>{
+                if self.in_scope(Scope::ListItem, live_id!(li)){
+                    self.generate_implied_end_tags(Some(live_id!(li)));
+                    self.pop_until_tag(live_id!(li));
+                }
+            }
+            Token::End{lc:lc@(live_id!(dd) | live_id!(dt)), ..}=>{
+                if self.in_scope(Scope::Default, lc){
+                    self.generate_implied_end_tags(Some(lc));
+                    self.pop_until_tag(lc);
+                }
+            }
+            Token::End{lc, ..} if is_heading(lc)=>{
+                if self.in_scope_with(Scope::Default, is_heading){
+                    self.generate_implied_end_tags(None);
+                    self.pop_until(is_heading);
+                }
+            }
+            Token::End{lc, nc} if is_formatting(lc)=>{
+                if self.adoption_agency(lc){
+                    self.any_other_end_tag(lc, nc);
+                }
+            }
+            Token::End{lc:lc@(live_id!(applet) | live_id!(marquee) | live_id!(object)), ..}=>{
+                if self.in_scope(Scope::Default, lc){
+                    self.generate_implied_end_tags(None);
+                    self.pop_until_tag(lc);
+                    self.clear_formatting_to_marker();
+                }
+            }
+            Token::End{lc:live_id!(br), ..}=>{
+                self.error("br end tag");
+                self.process_in_body(Token::Start{lc:live_id!(br), nc:live_id!(br), attrs:Vec::new(), self_closing:false});
+            }
+            Token::End{lc, nc}=>self.any_other_end_tag(lc, nc),
+        }
+    }
+
+    fn any_other_end_tag(&mut self, lc:LiveId, _nc:LiveId){
+        for (i, node) in self.open.iter().enumerate().rev(){
+            let node_lc = self.lc(*node);
+            if node_lc == lc{
+                self.generate_implied_end_tags(Some(lc));
+                self.open.truncate(i);
+                return
+            }
+            if is_special(node_lc){
+                self.error("unexpected end tag");
+                return
+            }
+        }
+    }
+
+    fn merge_attrs(&mut self, node:usize, attrs:Vec<Attr>){
+        if let Kind::Element{attrs:existing, ..} = &mut self.nodes[node].kind{
+            for attr in attrs{
+                if !existing.iter().any(|a| a.lc == attr.lc){
+                    existing.push(attr);
+                }
+            }
+        }
+    }
+
+    fn serialize(&self, node:usize, doc:&mut HtmlDoc){
+        match &self.nodes[node].kind{
+            Kind::Document=>(),
+            Kind::Text(text)=>{
+                let start = doc.decoded.len();
+                doc.decoded.push_str(text);
+                doc.nodes.push(HtmlNode::Text{start, end:doc.decoded.len(), all_ws:is_ws(text)});
+                return
+            }
+            Kind::Element{lc, nc, attrs}=>{
+                doc.nodes.push(HtmlNode::OpenTag{lc:*lc, nc:*nc});
+                for attr in attrs{
+                    let start = doc.decoded.len();
+                    doc.decoded.push_str(&attr.value);
+                    doc.nodes.push(HtmlNode::Attribute{lc:attr.lc, nc:attr.nc, start, end:doc.decoded.len()});
+                }
+            }
+        }
+        for child in &self.nodes[node].children{
+            self.serialize(*child, doc);
+        }
+        if let Kind::Element{lc, nc, ..} = &self.nodes[node].kind{
+            doc.nodes.push(HtmlNode::CloseTag{lc:*lc, nc:*nc});
+        }
+    }
+}
+
+fn tokens(doc:&HtmlDoc)->Vec<Token>{
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < doc.nodes.len(){
+        match &doc.nodes[i]{
+            HtmlNode::OpenTag{lc, nc}=>{
+                let mut attrs:Vec<Attr> = Vec::new();
+                while let Some(HtmlNode::Attribute{lc, nc, start, end}) = doc.nodes.get(i + 1){
+                    // the first of a duplicated attribute wins
+                    if !attrs.iter().any(|a| a.lc == *lc){
+                        attrs.push(Attr{lc:*lc, nc:*nc, value:doc.decoded[*start..*end].into()});
+                    }
+                    i += 1;
+                }
+                // parse_html emits a close tag straight after a self closed tag
+                let self_closing = matches!(doc.nodes.get(i + 1), Some(HtmlNode::CloseTag{lc:close,..}) if close == lc);
+                if self_closing{
+                    i += 1;
+                }
+                tokens.push(Token::Start{lc:*lc, nc:*nc, attrs, self_closing});
+            }
+            HtmlNode::CloseTag{lc, nc}=>{
+                tokens.push(Token::End{lc:*lc, nc:*nc});
+            }
+            HtmlNode::Text{start, end, ..}=>{
+                if start != end{
+                    tokens.push(Token::Text(doc.decoded[*start..*end].into()));
+                }
+            }
+            HtmlNode::Attribute{..}=>()
+        }
+        i += 1;
+    }
+    tokens.push(Token::Eof);
+    tokens
+}
+
+fn build_tree(body:&str, context:Option<LiveId>, errors:&mut Option<Vec<HtmlError>>)->HtmlDoc{
+    let flat = parse_html(body, errors);
+    let mut builder = TreeBuilder::new(context);
+    for token in tokens(&flat){
+        builder.process(token);
+    }
+    if let Some(errors) = errors{
+        for message in builder.errors.drain(..){
+            errors.push(HtmlError{message, position:body.len()});
+        }
+    }
+    let mut doc = HtmlDoc::default();
+    match context{
+        // fragments only output the children of the html root
+        Some(_)=>for child in &builder.nodes[builder.open.first().cloned().unwrap_or(0)].children{
+            builder.serialize(*child, &mut doc);
+        }
+        None=>builder.serialize(0, &mut doc)
+    }
+    doc
+}
+
+/// Parses a complete document following the HTML5 tree construction rules.
+/// The node stream is always balanced and contains the implied html, head and body elements.
+pub fn parse_html_document(body:&str, errors:&mut Option<Vec<HtmlError>>)->HtmlDoc{
+    build_tree(body, None, errors)
+}
+
+/// Parses an HTML fragment as if it was the content of the `context` element (lowercase id, e.g. `live_id!(body)`).
+/// The node stream is always balanced but does not contain an html root.
+pub fn parse_html_fragment(body:&str, context:LiveId, errors:&mut Option<Vec<HtmlError>>)->HtmlDoc{
+    build_tree(body, Some(context), errors)
+}
@@ -0,0 +1,42 @@
+use {
+    makepad_html::*,
+    makepad_live_id::*,
+};
+
+// returns the computed style of the first element with the given lowercase tag name
+fn style_of(html:&str, tag:LiveId)->Option<CssStyle>{
+    let doc = parse_html_fragment(html, live_id!(body), &mut None);
+    let styles = doc.style_sheet().compute_styles(&doc);
+    let index = doc.nodes.iter().position(|n| matches!(n, HtmlNode::OpenTag{lc,..} if *lc == tag)).unwrap();
+    styles[index].clone()
+}
+
+#[test]
+fn cascade(){
+    let html = "<style>p{color:red; font-size:2em} .a{color:#00ff00} #x{color:blue !important}</style>\
+        <p class=a id=x style='color:black; margin: 4px 8px'>x</p>";
+    let style = style_of(html, live_id!(p)).unwrap();
+    assert_eq!(style.color, parse_css_color("blue"));
+    assert_eq!(style.font_size, Some(CssFontSize::Scale(2.0)));
+    assert_eq!(style.margin_top, Some(CssLength::Px(4.0)));
+    assert_eq!(style.margin_left, Some(CssLength::Px(8.0)));
+}
+
+#[test]
+fn selectors(){
+    let html = "<style>div span{font-weight:bold} ul > b{display:none} li b{text-align:center} a:hover{color:red}</style>\
+        <div><p><span>x</span></p></div><ul><li><b>y</b></li></ul><a>z</a>";
+    assert_eq!(style_of(html, live_id!(span)).unwrap().bold, Some(true));
+    let b = style_of(html, live_id!(b)).unwrap();
+    assert_eq!(b.display, None);
+    assert_eq!(b.text_align, Some(CssTextAlign::Center));
+    assert_eq!(style_of(html, live_id!(a)), None);
+}
+
+#[test]
+fn colors(){
+    assert_eq!(parse_css_color("#f00"), Some(CssColor{r:1.0, g:0.0, b:0.0, a:1.0}));
+    assert_eq!(parse_css_color("rgba(0, 255, 0, 0.5)"), Some(CssColor{r:0.0, g:1.0, b:0.0, a:0.5}));
+    assert_eq!(parse_css_color("rgb(100%, 0%, 0%)"), parse_css_color("red"));
+    assert_eq!(parse_css_color("#12"), None);
+}
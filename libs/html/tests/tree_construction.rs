@@ -0,0 +1,171 @@
+// Runs tree construction tests in the html5lib-tests `.dat` format against parse_html_document
+// and parse_html_fragment. The files in tests/tree_construction are written by hand, with cases
+// from the spec and from html5lib-tests, and #errors is not checked. Files from
+// html5lib-tests/tree-construction can be copied unchanged next to them and listed at the
+// bottom of this file, with the #data of the cases that don't pass yet in their skip list. Comments and doctypes are not part of the HtmlNode
+// stream, so those lines are skipped in the expected output. We parse with scripting disabled,
+// so #script-on cases are not run.
+use {
+    makepad_html::*,
+    makepad_live_id::*,
+    std::collections::HashMap,
+};
+
+struct Test{
+    data: String,
+    fragment: Option<String>,
+    script_on: bool,
+    document: Vec<String>,
+}
+
+fn parse_dat(string:&str)->Vec<Test>{
+    let mut tests = Vec::new();
+    for chunk in string.split("#data\n").skip(1){
+        let mut section = "data";
+        let mut data = Vec::new();
+        let mut fragment = None;
+        let mut script_on = false;
+        let mut document = Vec::new();
+        for line in chunk.lines(){
+            if line.starts_with('#') && section != "data" || line == "#errors"{
+                script_on |= line == "#script-on";
+                section = line;
+                continue
+            }
+            match section{
+                "data"=>data.push(line),
+                "#document-fragment"=>fragment = Some(line.trim().to_string()),
+                "#document" if !line.is_empty()=>match line.strip_prefix("| "){
+                    Some(line)=>document.push(line.to_string()),
+                    // continuation of a multiline text node
+                    None=>document.last_mut().unwrap().push_str(&format!("\n{}", line)),
+                }
+                _=>()
+            }
+        }
+        document.retain(|line| !line.trim_start().starts_with("<!"));
+        tests.push(Test{data:data.join("\n"), fragment, script_on, document});
+    }
+    tests
+}
+
+fn names(document:&[String])->HashMap<LiveId, String>{
+    let mut names = HashMap::new();
+    for line in document{
+        let line = line.trim_start();
+        let name = if let Some(tag) = line.strip_prefix('<'){
+            tag.trim_end_matches('>')
+        }
+        else if let Some(eq) = line.find("=\""){
+            &line[..eq]
+        }
+        else{
+            continue
+        };
+        names.insert(LiveId::from_str(name), name.to_string());
+    }
+    names
+}
+
+fn dump(doc:&HtmlDoc, names:&HashMap<LiveId, String>)->Vec<String>{
+    let name = |id:&LiveId| names.get(id).cloned().unwrap_or_else(|| format!("{:?}", id));
+    let mut out = Vec::new();
+    let mut depth = 0;
+    let mut attrs = Vec::new();
+    let indent = |depth:usize| "  ".repeat(depth);
+    for node in &doc.nodes{
+        if !matches!(node, HtmlNode::Attribute{..}) && !attrs.is_empty(){
+            attrs.sort();
+            for attr in attrs.drain(..){
+                out.push(format!("{}{}", indent(depth), attr));
+            }
+        }
+        match node{
+            HtmlNode::OpenTag{lc, ..}=>{
+                out.push(format!("{}<{}>", indent(depth), name(lc)));
+                depth += 1;
+            }
+            HtmlNode::CloseTag{..}=>depth -= 1,
+            HtmlNode::Attribute{lc, start, end, ..}=>{
+                attrs.push(format!("{}=\"{}\"", name(lc), &doc.decoded[*start..*end]));
+            }
+            HtmlNode::Text{start, end, ..}=>{
+                out.push(format!("{}\"{}\"", indent(depth), &doc.decoded[*start..*end]));
+            }
+        }
+    }
+    assert_eq!(depth, 0, "unbalanced node stream");
+    out
+}
+
+fn run(string:&str, skip:&[&str]){
+    let mut failures = Vec::new();
+    let mut skipped_passes = Vec::new();
+    for test in parse_dat(string){
+        if test.script_on{
+            continue
+        }
+        let names = names(&test.document);
+        let doc = match &test.fragment{
+            Some(context)=>parse_html_fragment(&test.data, LiveId::from_str_lc(context), &mut None),
+            None=>parse_html_document(&test.data, &mut None),
+        };
+        let actual = dump(&doc, &names);
+        let skipped = skip.contains(&test.data.as_str());
+        if actual != test.document && !skipped{
+            failures.push(format!("#data\n{}\n#expected\n{}\n#actual\n{}\n", test.data, test.document.join("\n"), actual.join("\n")));
+        }
+        else if actual == test.document && skipped{
+            skipped_passes.push(test.data);
+        }
+    }
+    if !failures.is_empty(){
+        panic!("{} tree construction tests failed\n\n{}", failures.len(), failures.join("\n"));
+    }
+    // keep the skip lists honest, cases that pass now have to come off them
+    if !skipped_passes.is_empty(){
+        panic!("skipped tree construction tests pass now, remove them from the skip list: {:?}", skipped_passes);
+    }
+}
+
+macro_rules! tree_construction {
+    ($($name:ident => $file_name:literal, skip: [$($skip:literal),*],)*) => {
+        $(
+            #[test]
+            fn $name() {
+                use std::{fs, path::PathBuf};
+
+                let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+                path.push("tests/tree_construction");
+                path.push($file_name);
+                let string = fs::read_to_string(path).unwrap();
+                run(&string, &[$($skip),*]);
+            }
+        )*
+    }
+}
+
+tree_construction! {
+    implied_end_tags => "implied_end_tags.dat", skip: [
+        // the form element pointer isn't tracked
+        "<form><form>x"
+    ],
+    formatting => "formatting.dat", skip: [],
+    tables => "tables.dat", skip: [
+        // without a doctype we are in quirks mode, where <table> doesn't close a <p>
+        "<p>1<table><tr><td>2</table>3",
+        // a <form> in a table should be inserted and popped right away
+        "<table><form><tr><td>x</table>"
+    ],
+    raw_text => "raw_text.dat", skip: [
+        // the tokenizer has no PLAINTEXT state
+        "<plaintext></plaintext>x"
+    ],
+    fragments => "fragments.dat", skip: [
+        // the tokenizer doesn't start in the state of the context element
+        "<b>&amp;</b>",
+        "<b>x",
+        // an html context doesn't get a head
+        "<p>x"
+    ],
+}
@@ -1,11 +1,28 @@
 //use makepad_live_id::*;
 use std::str::Chars;
 use std::fmt::Write;
+use std::ops::Range;
+
+mod writer;
+pub use writer::*;
 
 #[derive(Default, PartialEq)]
 pub struct MarkdownDoc{
     pub decoded: String,
     pub nodes: Vec<MarkdownNode>,
+    /// Byte range in the source for every entry in `nodes`,
+    /// a Begin node spans up to and including its matching End node
+    pub spans: Vec<Range<usize>>,
+}
+
+impl MarkdownDoc{
+    /// Index of the innermost node whose source span contains `offset`
+    pub fn node_at_offset(&self, offset:usize)->Option<usize>{
+        self.spans.iter().enumerate()
+            .filter(|(_, span)| span.contains(&offset))
+            .min_by_key(|(_, span)| span.len())
+            .map(|(index, _)| index)
+    }
 }
 #[derive(Debug, PartialEq)]
 pub enum MarkdownListLabel{
@@ -14,6 +31,13 @@ pub enum MarkdownListLabel{
     Star,
     Number{digit:usize, start:usize, end:usize},
 }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkdownTableAlign{
+    None,
+    Left,
+    Center,
+    Right,
+}
 #[derive(Debug, PartialEq)]
 pub enum MarkdownNode{
     BeginHead{level:usize},
@@ -29,6 +53,8 @@ pub enum MarkdownNode{
     Separator, 
     BeginUnderline,
     EndUnderline,
+    BeginStrikethrough,
+    EndStrikethrough,
     BeginCode,
     EndCode,
     BeginInlineCode,
@@ -38,7 +64,20 @@ pub enum MarkdownNode{
     BeginItalic,
     EndBold,
     EndItalic,
-    Text{start:usize, end:usize}
+    Text{start:usize, end:usize},
+    BeginTable{columns:usize},
+    EndTable,
+    BeginTableRow{header:bool},
+    EndTableRow,
+    BeginTableCell{align:MarkdownTableAlign},
+    EndTableCell,
+    /// follows a BeginListItem for `- [ ]` and `- [x]` items
+    TaskCheckbox{checked:bool},
+    /// `[^label]`, numbered by order of first reference
+    FootnoteRef{start:usize, end:usize, number:usize},
+    /// footnote definitions are moved to the end of the document, ordered by number
+    BeginFootnote{start:usize, end:usize, number:usize},
+    EndFootnote
 }
 
 #[derive(Clone)]
@@ -46,6 +85,8 @@ struct Cursor<'a>{
     iter: Chars<'a>,
     chars:[char;3],
     last_char: char,
+    // byte offset of chars[0] in the source
+    pos: usize,
 }
 
 impl <'a> Cursor<'a>{
@@ -54,10 +95,12 @@ impl <'a> Cursor<'a>{
             iter: s.chars(),
             chars: ['\0';3],
             last_char: '\0',
+            pos: 0,
         };
         ret.next();
         ret.next();
         ret.next();
+        ret.pos = 0;
         ret
     }
     
@@ -72,6 +115,9 @@ impl <'a> Cursor<'a>{
     }
 
     fn next(&mut self){
+        if self.chars[0] != '\0'{
+            self.pos += self.chars[0].len_utf8();
+        }
         self.last_char = self.chars[0];
         self.chars[0] = self.chars[1];
         self.chars[1] = self.chars[2];
@@ -84,8 +130,110 @@ impl <'a> Cursor<'a>{
     }
 }
 
+// node list that records the source span of every node pushed onto it
+struct Nodes{
+    nodes: Vec<MarkdownNode>,
+    spans: Vec<Range<usize>>,
+    open: usize,
+    pos: usize,
+}
+
+impl Nodes{
+    fn push(&mut self, node:MarkdownNode){
+        self.nodes.push(node);
+        self.spans.push(self.pos..self.pos);
+    }
+    
+    fn pop(&mut self)->Option<MarkdownNode>{
+        self.spans.pop();
+        self.nodes.pop()
+    }
+    
+    fn last(&self)->Option<&MarkdownNode>{
+        self.nodes.last()
+    }
+    
+    fn last_mut(&mut self)->Option<&mut MarkdownNode>{
+        self.nodes.last_mut()
+    }
+    
+    fn iter(&self)->std::slice::Iter<'_, MarkdownNode>{
+        self.nodes.iter()
+    }
+    
+    // closes the spans of the nodes pushed since the last call at the cursor position
+    fn advance(&mut self, pos:usize){
+        let open = self.open.min(self.spans.len());
+        for span in &mut self.spans[open..]{
+            span.end = pos;
+        }
+        // text nodes keep growing as characters are appended to them
+        if let (Some(MarkdownNode::Text{..}), Some(span)) = (self.nodes.last(), self.spans.last_mut()){
+            span.end = pos;
+        }
+        self.open = self.spans.len();
+        self.pos = pos;
+    }
+    
+    // extends every Begin node span over its matching End node
+    fn close_spans(&mut self){
+        let mut stack:Vec<usize> = Vec::new();
+        for i in 0..self.nodes.len(){
+            if self.nodes[i].is_begin(){
+                stack.push(i);
+            }
+            else if let Some(at) = stack.iter().rposition(|begin| self.nodes[*begin].is_closed_by(&self.nodes[i])){
+                let begin = stack.remove(at);
+                self.spans[begin].end = self.spans[begin].end.max(self.spans[i].end);
+            }
+        }
+    }
+}
+
+impl MarkdownNode{
+    // the offsets into decoded this node holds
+    fn decoded_offsets_mut(&mut self)->Vec<&mut usize>{
+        use MarkdownNode::*;
+        match self{
+            BeginListItem{label:MarkdownListLabel::Number{start, end, ..}} | Text{start, end} |
+            FootnoteRef{start, end, ..} | BeginFootnote{start, end, ..} => vec![start, end],
+            Link{start, url_start, end} | Image{start, url_start, end} => vec![start, url_start, end],
+            _ => Vec::new()
+        }
+    }
+    
+    fn is_begin(&self)->bool{
+        use MarkdownNode::*;
+        matches!(self, BeginHead{..} | BeginListItem{..} | BeginNormal | BeginQuote | BeginUnderline |
+            BeginStrikethrough | BeginCode | BeginInlineCode | BeginBold | BeginItalic | BeginTable{..} |
+            BeginTableRow{..} | BeginTableCell{..} | BeginFootnote{..})
+    }
+    
+    fn is_closed_by(&self, end:&MarkdownNode)->bool{
+        use MarkdownNode::*;
+        matches!((self, end), (BeginHead{..}, EndHead) | (BeginListItem{..}, EndListItem) |
+            (BeginNormal, EndNormal) | (BeginQuote, EndQuote) | (BeginUnderline, EndUnderline) |
+            (BeginStrikethrough, EndStrikethrough) | (BeginCode, EndCode) | (BeginInlineCode, EndInlineCode) |
+            (BeginBold, EndBold) | (BeginItalic, EndItalic) | (BeginTable{..}, EndTable) |
+            (BeginTableRow{..}, EndTableRow) | (BeginTableCell{..}, EndTableCell) | (BeginFootnote{..}, EndFootnote))
+    }
+}
+
+// what can go between < and > to make an autolink, an email address or an url with a scheme
+fn is_autolink(url:&str)->bool{
+    if url.is_empty() || url.contains(|c:char| c.is_whitespace() || c == '<' || c == '>'){
+        return false
+    }
+    if url.contains('@') && !url.contains(':'){
+        return true
+    }
+    url.split_once(':').map_or(false, |(scheme, rest)|{
+        scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-') && !rest.is_empty()
+    })
+}
+
 pub fn parse_markdown(body:&str)->MarkdownDoc{
-    let mut nodes = Vec::new();
+    let mut nodes = Nodes{nodes:Vec::new(), spans:Vec::new(), open:0, pos:0};
     let mut decoded = String::new();
     
     let _last_was_ws = false;
@@ -93,18 +241,23 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
     let mut cursor = Cursor::new(body);
     enum State{
         Root{spaces:usize},
-        Inline{kind:Kind, bold:usize, italic:usize, underline:usize}, // terminates
+        Inline{kind:Kind, bold:usize, italic:usize, strike:usize}, // terminates
     }
     enum Kind{
         Normal,
         Head,
         Quote(usize),
-        List(usize)
+        List(usize),
+        Footnote,
+        Table{aligns:Vec<MarkdownTableAlign>, column:usize, header:bool, cell_open:bool}
     }
+    // footnote labels in order of first reference
+    let mut footnotes:Vec<String> = Vec::new();
+    let defined_footnotes = footnote_definitions(body);
     
     let mut state = State::Root{spaces:0};
     
-    fn push_char(nodes: &mut Vec<MarkdownNode>, decoded:&mut String, c:char){
+    fn push_char(nodes: &mut Nodes, decoded:&mut String, c:char){
         // ok so lets check our last node
         let start = decoded.len();
         decoded.push(c);
@@ -118,7 +271,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
         }
     }
     
-    fn push_optional_char(nodes: &mut Vec<MarkdownNode>, decoded:&mut String, c:char){
+    fn push_optional_char(nodes: &mut Nodes, decoded:&mut String, c:char){
         // ok so lets check our last node
         if let Some(last) = nodes.last_mut(){
             if let MarkdownNode::Text{end,..} = last{
@@ -128,7 +281,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
         }
     }
     
-    fn code_on_one_line(nodes: &mut Vec<MarkdownNode>, decoded:&mut String, cursor:&mut Cursor){
+    fn code_on_one_line(nodes: &mut Nodes, decoded:&mut String, cursor:&mut Cursor){
         // alright we have to check if we are in a code block already
         let already_in_code = if let Some(MarkdownNode::EndCode) = nodes.last(){
             nodes.pop();
@@ -153,29 +306,213 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
         nodes.push(MarkdownNode::Text{start, end:decoded.len()});
         nodes.push(MarkdownNode::EndCode);
     }
-    
+
+    fn end_styles(nodes: &mut Nodes, bold:&mut usize, italic:&mut usize, strike:&mut usize){
+        for _ in 0..*bold{
+            nodes.push(MarkdownNode::EndBold);
+        }
+        for _ in 0..*italic{
+            nodes.push(MarkdownNode::EndItalic);
+        }
+        for _ in 0..*strike{
+            nodes.push(MarkdownNode::EndStrikethrough);
+        }
+        *bold = 0;
+        *italic = 0;
+        *strike = 0;
+    }
+
+    fn trim_trailing_space(nodes: &mut Nodes, decoded:&mut String){
+        if let Some(MarkdownNode::Text{start, end}) = nodes.last_mut(){
+            if *end == decoded.len() && decoded.ends_with(' '){
+                decoded.pop();
+                *end -= 1;
+                if *start == *end{
+                    nodes.pop();
+                }
+            }
+        }
+    }
+
+    fn table_cells(line:&str)->Vec<&str>{
+        let line = line.trim();
+        let line = line.strip_prefix('|').unwrap_or(line);
+        let line = if line.ends_with('|') && !line.ends_with("\\|"){&line[..line.len()-1]}else{line};
+        let mut cells = Vec::new();
+        let mut cell_start = 0;
+        let mut last = '\0';
+        for (i, c) in line.char_indices(){
+            if c == '|' && last != '\\'{
+                cells.push(&line[cell_start..i]);
+                cell_start = i + 1;
+            }
+            last = c;
+        }
+        cells.push(&line[cell_start..]);
+        cells
+    }
+
+    // a table starts with a header row containing a pipe, followed by a delimiter row like |---|:--:|
+    fn scan_table_header(cursor:&Cursor)->Option<Vec<MarkdownTableAlign>>{
+        let mut scan = cursor.clone();
+        let mut lines = [String::new(), String::new()];
+        for line in &mut lines{
+            while scan.chars[0] != '\n' && !scan.at_end(){
+                line.push(scan.chars[0]);
+                scan.next();
+            }
+            scan.next();
+        }
+        if !lines[0].contains('|'){
+            return None
+        }
+        let header = table_cells(&lines[0]);
+        let delimiters = table_cells(&lines[1]);
+        if header.len() != delimiters.len(){
+            return None
+        }
+        delimiters.iter().map(|cell|{
+            let cell = cell.trim();
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || dashes.chars().any(|c| c != '-'){
+                return None
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')){
+                (true, true) => MarkdownTableAlign::Center,
+                (true, false) => MarkdownTableAlign::Left,
+                (false, true) => MarkdownTableAlign::Right,
+                (false, false) => MarkdownTableAlign::None,
+            })
+        }).collect()
+    }
+
+    fn begin_table_row(nodes: &mut Nodes, cursor:&mut Cursor, aligns:&[MarkdownTableAlign], header:bool){
+        while cursor.chars[0] == ' '{
+            cursor.next();
+        }
+        if cursor.chars[0] == '|'{
+            cursor.next();
+        }
+        while cursor.chars[0] == ' '{
+            cursor.next();
+        }
+        nodes.push(MarkdownNode::BeginTableRow{header});
+        nodes.push(MarkdownNode::BeginTableCell{align:aligns[0]});
+    }
+
+    fn end_table_row(nodes: &mut Nodes, decoded:&mut String, aligns:&[MarkdownTableAlign], column:&mut usize, cell_open:&mut bool){
+        if *cell_open{
+            trim_trailing_space(nodes, decoded);
+            nodes.push(MarkdownNode::EndTableCell);
+            *column += 1;
+            *cell_open = false;
+        }
+        // rows with too few cells are padded with empty ones
+        while *column < aligns.len(){
+            nodes.push(MarkdownNode::BeginTableCell{align:aligns[*column]});
+            nodes.push(MarkdownNode::EndTableCell);
+            *column += 1;
+        }
+        nodes.push(MarkdownNode::EndTableRow);
+    }
+
+    fn task_checkbox(nodes: &mut Nodes, cursor:&mut Cursor){
+        if let Some(MarkdownNode::BeginListItem{..}) = nodes.last(){
+            if let ['[', c, ']'] = cursor.chars{
+                let mut scan = cursor.clone();
+                scan.skip(3);
+                if (c == ' ' || c == 'x' || c == 'X') && scan.chars[0] == ' '{
+                    nodes.push(MarkdownNode::TaskCheckbox{checked: c != ' '});
+                    scan.next();
+                    *cursor = scan;
+                }
+            }
+        }
+    }
+
+    // labels of the `[^label]:` lines, definitions can follow their references
+    fn footnote_definitions(body:&str)->Vec<String>{
+        body.lines().filter_map(|line|{
+            let line = line.strip_prefix("   ").or(line.strip_prefix("  ")).or(line.strip_prefix(' ')).unwrap_or(line);
+            let (label, _) = line.strip_prefix("[^")?.split_once("]:")?;
+            if label.is_empty() || label.contains(char::is_whitespace){
+                return None
+            }
+            Some(label.to_lowercase())
+        }).collect()
+    }
+
+    // bare http://, https:// and www. autolinks, trailing punctuation is not part of the link
+    fn scan_autolink(cursor:&Cursor)->Option<String>{
+        let mut scan = cursor.clone();
+        let mut url = String::new();
+        while !scan.at_end() && !scan.chars[0].is_whitespace() && scan.chars[0] != '<'{
+            url.push(scan.chars[0]);
+            scan.next();
+        }
+        loop{
+            if url.ends_with(['.', ',', ':', ';', '!', '?', '"', '\'', '*', '_', '~']){
+                url.pop();
+            }
+            else if url.ends_with(')') && url.matches(')').count() > url.matches('(').count(){
+                url.pop();
+            }
+            else{
+                break
+            }
+        }
+        for prefix in ["http://", "https://", "www."]{
+            if url.len() > prefix.len() && url.starts_with(prefix){
+                return Some(url)
+            }
+        }
+        None
+    }
+
     loop{
+        nodes.advance(cursor.pos);
         match &mut state{
-            State::Inline{kind, bold, italic, underline}=> match cursor.chars{
+            State::Inline{kind, bold, italic, strike}=> match cursor.chars{
                 [' ',' ','\n']=>{
                     nodes.push(MarkdownNode::NewLine);
                     cursor.skip(2);
                 }
                 ['\n',_,_] | ['\0',_,_]=>{
-                    for _ in 0..*bold{
-                        nodes.push(MarkdownNode::EndBold);
-                    }
-                    for _ in 0..*italic{
-                        nodes.push(MarkdownNode::EndItalic);
-                    }
-                    for _ in 0..*underline{
-                        nodes.push(MarkdownNode::EndUnderline);
+                    // trailing space of a cell goes before its styles end, so it stays out of them
+                    if let Kind::Table{cell_open:true, ..} = kind{
+                        trim_trailing_space(&mut nodes, &mut decoded);
                     }
-                    *bold = 0;
-                    *italic = 0;
-                    *underline = 0;
-                    
+                    end_styles(&mut nodes, bold, italic, strike);
+
                     match kind{
+                        Kind::Table{aligns, column, header, cell_open}=>{
+                            end_table_row(&mut nodes, &mut decoded, aligns, column, cell_open);
+                            cursor.next();
+                            if *header{ // skip the delimiter row
+                                while cursor.chars[0] != '\n' && !cursor.at_end(){
+                                    cursor.next();
+                                }
+                                cursor.next();
+                            }
+                            // the table continues as long as lines contain a pipe
+                            let mut scan = cursor.clone();
+                            let mut has_pipe = false;
+                            while scan.chars[0] != '\n' && !scan.at_end(){
+                                has_pipe |= scan.chars[0] == '|';
+                                scan.next();
+                            }
+                            if has_pipe{
+                                nodes.advance(cursor.pos);
+                                begin_table_row(&mut nodes, &mut cursor, aligns, false);
+                                *column = 0;
+                                *header = false;
+                                *cell_open = true;
+                            }
+                            else{
+                                nodes.push(MarkdownNode::EndTable);
+                                state = State::Root{spaces:0};
+                            }
+                        }
                         Kind::Head=>{
                             cursor.next();
                             nodes.push(MarkdownNode::EndHead);
@@ -211,7 +548,8 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 state = State::Root{spaces};
                             }
                         }
-                        Kind::Normal=>{
+                        Kind::Normal | Kind::Footnote=>{
+                            let end = if let Kind::Footnote = kind{MarkdownNode::EndFootnote}else{MarkdownNode::EndNormal};
                             let last_is_space = cursor.last_char == ' ';
                             cursor.next();
                             let mut spaces = 0;
@@ -219,14 +557,14 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 cursor.next();
                                 spaces += 1;
                             }
-                            if cursor.chars[0] == '#'{
+                            if cursor.chars[0] == '#' || cursor.chars[0] == '[' && cursor.chars[1] == '^' && spaces == 0{
                                 state = State::Root{spaces};
-                                nodes.push(MarkdownNode::EndNormal);
+                                nodes.push(end);
                             }
                             else if cursor.chars[0] == '\n' || cursor.chars[0] == '\0'{
                                 cursor.next();
                                 state = State::Root{spaces:0};
-                                nodes.push(MarkdownNode::EndNormal);
+                                nodes.push(end);
                             }
                             else if !last_is_space{
                                 push_char(&mut nodes, &mut decoded, ' ');
@@ -266,6 +604,32 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     }
                     
                 }
+                ['\\','|',_] if matches!(kind, Kind::Table{..})=>{
+                    push_char(&mut nodes, &mut decoded, '|');
+                    cursor.skip(2);
+                }
+                ['|',_,_] if matches!(kind, Kind::Table{..})=>{ // next table cell
+                    let Kind::Table{aligns, column, cell_open, ..} = kind else{unreachable!()};
+                    trim_trailing_space(&mut nodes, &mut decoded);
+                    end_styles(&mut nodes, bold, italic, strike);
+                    nodes.push(MarkdownNode::EndTableCell);
+                    *cell_open = false;
+                    *column += 1;
+                    cursor.next();
+                    nodes.advance(cursor.pos);
+                    while cursor.chars[0] == ' '{
+                        cursor.next();
+                    }
+                    if *column >= aligns.len(){ // cells beyond the header are dropped
+                        while cursor.chars[0] != '\n' && !cursor.at_end(){
+                            cursor.next();
+                        }
+                    }
+                    else if cursor.chars[0] != '\n' && !cursor.at_end(){
+                        nodes.push(MarkdownNode::BeginTableCell{align:aligns[*column]});
+                        *cell_open = true;
+                    }
+                }
                 ['*','*',w] | ['_','_',w] if w != ' ' && w != '\n'=>{ // alright so have have 2 *'s
                     // this is the start of a bold block
                     nodes.push(MarkdownNode::BeginBold);
@@ -275,33 +639,42 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                 [w,'*','*'] | [w,'_','_'] if w != ' '&& w != '\n'=>{
                     // end of a bold block
                     push_char(&mut nodes, &mut decoded, w);
+                    cursor.next();
                     if *bold > 0{
+                        nodes.advance(cursor.pos);
                         *bold -= 1;
-                        cursor.skip(3);
+                        cursor.skip(2);
                         nodes.push(MarkdownNode::EndBold);
                     }
-                    else{
-                        cursor.next();
-                    }
                 }
-                ['~','~',w] if w != ' ' && w != '\n'=>{ // alright so have have 2 *'s
-                    // this is the start of a bold block
-                    nodes.push(MarkdownNode::BeginUnderline);
-                    *underline += 1;
+                ['*','*',_] | ['_','_',_] if *bold > 0 && cursor.last_char != ' '=>{
+                    // end of a bold block right after another style ends, like **~~a~~**
+                    cursor.skip(2);
+                    *bold -= 1;
+                    nodes.push(MarkdownNode::EndBold);
+                }
+                ['~','~',w] if w != ' ' && w != '\n'=>{
+                    // this is the start of a strikethrough block
+                    nodes.push(MarkdownNode::BeginStrikethrough);
+                    *strike += 1;
                     cursor.skip(2);
                 }
                 [w,'~','~'] if w != ' '&& w != '\n'=>{
-                    // end of a bold block
+                    // end of a strikethrough block
                     push_char(&mut nodes, &mut decoded, w);
-                    if *underline > 0{
-                        *underline -= 1;
-                        cursor.skip(3);
-                        nodes.push(MarkdownNode::EndUnderline);
-                    }
-                    else{
-                        cursor.next();
+                    cursor.next();
+                    if *strike > 0{
+                        nodes.advance(cursor.pos);
+                        *strike -= 1;
+                        cursor.skip(2);
+                        nodes.push(MarkdownNode::EndStrikethrough);
                     }
                 }
+                ['~','~',_] if *strike > 0 && cursor.last_char != ' '=>{
+                    cursor.skip(2);
+                    *strike -= 1;
+                    nodes.push(MarkdownNode::EndStrikethrough);
+                }
                 ['*',w,_] | ['_',w,_] if w != ' ' && w != '\n'=>{
                     *italic += 1;
                     nodes.push(MarkdownNode::BeginItalic);
@@ -309,7 +682,9 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                 }
                 [w,'*',_] | [w,'_',_] if w != ' ' && w != '\n' =>{
                     push_char(&mut nodes, &mut decoded, w);
-                    cursor.skip(2);
+                    cursor.next();
+                    nodes.advance(cursor.pos);
+                    cursor.next();
                     if *italic > 0{
                         *italic -= 1;
                         nodes.push(MarkdownNode::EndItalic);
@@ -359,6 +734,13 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 nodes.push(MarkdownNode::EndListItem);
                             }
                         }
+                        Kind::Footnote => {
+                            nodes.push(MarkdownNode::EndFootnote)
+                        }
+                        Kind::Table{aligns, column, cell_open, ..} => {
+                            end_table_row(&mut nodes, &mut decoded, aligns, column, cell_open);
+                            nodes.push(MarkdownNode::EndTable);
+                        }
                     }
                     state = State::Root{spaces:0};
                 }
@@ -389,7 +771,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                 ['!', '[', _]=>{
                     // alright lets do it
                     let mut scan = cursor.clone();
-                    scan.skip(1);
+                    scan.skip(2);
                     
                     let start = decoded.len();
                     // lets first patternmatch it
@@ -423,6 +805,78 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     // parse inline image
                     cursor.skip(2);
                 }
+                ['[','^',_]=>{ // possible footnote reference
+                    let mut scan = cursor.clone();
+                    scan.skip(2);
+                    let mut text = String::new();
+                    while scan.chars[0] != ']' && !scan.at_end() && !scan.chars[0].is_whitespace(){
+                        text.push(scan.chars[0]);
+                        scan.next();
+                    }
+                    let label = text.to_lowercase();
+                    // without a matching definition it stays plain text
+                    if scan.chars[0] == ']' && defined_footnotes.contains(&label){
+                        scan.next();
+                        let start = decoded.len();
+                        decoded.push_str(&text);
+                        let number = if let Some(index) = footnotes.iter().position(|l| *l == label){
+                            index + 1
+                        }
+                        else{
+                            footnotes.push(label);
+                            footnotes.len()
+                        };
+                        nodes.push(MarkdownNode::FootnoteRef{start, end:decoded.len(), number});
+                        cursor = scan;
+                    }
+                    else{
+                        push_char(&mut nodes, &mut decoded, '[');
+                        cursor.next();
+                    }
+                }
+                ['<',_,_]=>{ // possible autolink
+                    let mut scan = cursor.clone();
+                    scan.skip(1);
+                    let mut url = String::new();
+                    while scan.chars[0] != '>' && !scan.at_end() && !scan.chars[0].is_whitespace() && scan.chars[0] != '<'{
+                        url.push(scan.chars[0]);
+                        scan.next();
+                    }
+                    if scan.chars[0] == '>' && is_autolink(&url){
+                        let is_email = !url.contains(':');
+                        scan.next();
+                        let start = decoded.len();
+                        decoded.push_str(&url);
+                        let url_start = decoded.len();
+                        if is_email{
+                            decoded.push_str("mailto:");
+                        }
+                        decoded.push_str(&url);
+                        nodes.push(MarkdownNode::Link{start, url_start, end:decoded.len()});
+                        cursor = scan;
+                    }
+                    else{
+                        push_char(&mut nodes, &mut decoded, '<');
+                        cursor.next();
+                    }
+                }
+                ['h','t','t'] | ['w','w','w'] if matches!(cursor.last_char, '\0' | ' ' | '\n' | '(' | '*' | '_' | '~')=>{
+                    if let Some(url) = scan_autolink(&cursor){
+                        let start = decoded.len();
+                        decoded.push_str(&url);
+                        let url_start = decoded.len();
+                        if url.starts_with("www."){
+                            decoded.push_str("http://");
+                        }
+                        decoded.push_str(&url);
+                        nodes.push(MarkdownNode::Link{start, url_start, end:decoded.len()});
+                        cursor.skip(url.chars().count());
+                    }
+                    else{
+                        push_char(&mut nodes, &mut decoded, cursor.chars[0]);
+                        cursor.next();
+                    }
+                }
                 ['[',_,_]=>{ // possible named link
                     let mut scan = cursor.clone();
                     scan.skip(1);
@@ -455,7 +909,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         }
                     }
                     decoded.truncate(start);
-                    decoded.push(cursor.chars[0]);
+                    push_char(&mut nodes, &mut decoded, '[');
                     cursor.next();
                 }
                 [' ',_,_]=>{
@@ -504,7 +958,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         }
                         push_optional_char(&mut nodes, &mut decoded, ' ');
                         // alright now we know how deep in the block stack we need to be
-                        state = State::Inline{kind:Kind::Quote(blocks), bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Quote(blocks), bold:0, italic:0, strike:0};
                     }
                 }
                 ['#',_,_]=>{
@@ -526,13 +980,13 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         else{
                             nodes.push(MarkdownNode::Text{start, end:decoded.len()});
                         }
-                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
                     }
                     else {
                         cursor.next();
                         decoded.truncate(start);
                         nodes.push(MarkdownNode::BeginHead{level});
-                        state = State::Inline{kind:Kind::Head, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Head, bold:0, italic:0, strike:0};
                     }
                 }
                 ['-','-','-']=>{ // separator
@@ -545,7 +999,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         if cursor.chars[2] != '\n'{
                             nodes.push(MarkdownNode::BeginNormal);
                             push_char(&mut nodes, &mut decoded, '-');
-                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
                         }
                         else{
                             cursor.skip(3);
@@ -593,7 +1047,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         }
                         else{ // its normal 
                             nodes.push(MarkdownNode::BeginNormal);
-                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
                         }
                     }
                     else{
@@ -610,16 +1064,48 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                             _=>panic!()
                         }});
                         
-                        state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, underline:0}
+                        state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, strike:0}
                     }
                     cursor.skip(2);
+                    task_checkbox(&mut nodes, &mut cursor);
                     //push_optional_char(&mut nodes, &mut decoded, ' ');
                 }
                 ['\n',_,_]=>{ // skip it
                     cursor.skip(1);
                     state = State::Root{spaces:0};
                 }
+                ['[','^',_] if *spaces < 4 =>{ // possible footnote definition
+                    let mut scan = cursor.clone();
+                    scan.skip(2);
+                    let start = decoded.len();
+                    while scan.chars[0] != ']' && !scan.at_end() && !scan.chars[0].is_whitespace(){
+                        decoded.push(scan.chars[0]);
+                        scan.next();
+                    }
+                    if scan.chars[0] == ']' && scan.chars[1] == ':' && start != decoded.len(){
+                        scan.skip(2);
+                        while scan.chars[0] == ' '{
+                            scan.next();
+                        }
+                        cursor = scan;
+                        nodes.push(MarkdownNode::BeginFootnote{start, end:decoded.len(), number:0});
+                        state = State::Inline{kind:Kind::Footnote, bold:0, italic:0, strike:0};
+                    }
+                    else{
+                        decoded.truncate(start);
+                        nodes.push(MarkdownNode::BeginNormal);
+                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
+                    }
+                }
                 [a,_b,_c]=>{
+                    if *spaces < 4{
+                        if let Some(aligns) = scan_table_header(&cursor){
+                            nodes.push(MarkdownNode::BeginTable{columns:aligns.len()});
+                            begin_table_row(&mut nodes, &mut cursor, &aligns, true);
+                            state = State::Inline{kind:Kind::Table{aligns, column:0, header:true, cell_open:true}, bold:0, italic:0, strike:0};
+                            continue
+                        }
+                    }
                     let mut is_list_digit = None;
                     if a.is_ascii_digit(){
                         let mut scan = cursor.clone();
@@ -653,7 +1139,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                             }
                             else{ // its normal 
                                 nodes.push(MarkdownNode::BeginNormal);
-                                state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                                state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
                             }
                         }
                         else{ 
@@ -696,8 +1182,9 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 start,
                                 end
                             }});
+                            task_checkbox(&mut nodes, &mut cursor);
                                                     
-                            state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, underline:0}
+                            state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, strike:0}
                         }
                     }
                     else if *spaces>=4{ // its code
@@ -706,14 +1193,61 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     }
                     else{
                         nodes.push(MarkdownNode::BeginNormal);
-                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strike:0};
                     }
                 }
             }
         }
     }
+    // footnote definitions go to the end of the document in order of first reference,
+    // definitions that are never referenced are dropped
+    let mut body = Vec::new();
+    let mut definitions:Vec<(usize, Vec<(MarkdownNode, Range<usize>)>)> = Vec::new();
+    nodes.advance(cursor.pos);
+    nodes.close_spans();
+    let mut iter = nodes.nodes.into_iter().zip(nodes.spans);
+    while let Some((node, span)) = iter.next(){
+        if let MarkdownNode::BeginFootnote{start, end, ..} = node{
+            let label = decoded[start..end].to_lowercase();
+            let number = footnotes.iter().position(|l| *l == label).map(|index| index + 1);
+            let mut definition = vec![(MarkdownNode::BeginFootnote{start, end, number:number.unwrap_or(0)}, span)];
+            for (node, span) in iter.by_ref(){
+                let is_end = node == MarkdownNode::EndFootnote;
+                definition.push((node, span));
+                if is_end{
+                    break
+                }
+            }
+            if let Some(number) = number{
+                if !definitions.iter().any(|(n,_)| *n == number){
+                    definitions.push((number, definition));
+                }
+            }
+        }
+        else{
+            body.push((node, span));
+        }
+    }
+    definitions.sort_by_key(|(number,_)| *number);
+    body.extend(definitions.into_iter().flat_map(|(_,definition)| definition));
+    // decoded is rebuilt in node order, so moved definitions keep their text in order
+    // and dropped ones leave nothing behind
+    let mut compact = String::with_capacity(decoded.len());
+    for (node, _) in &mut body{
+        let offsets = node.decoded_offsets_mut();
+        if let (Some(from), Some(to)) = (offsets.first().map(|o| **o), offsets.last().map(|o| **o)){
+            let start = compact.len();
+            compact.push_str(&decoded[from..to]);
+            for offset in offsets{
+                *offset = *offset - from + start;
+            }
+        }
+    }
+    let (nodes, spans) = body.into_iter().unzip();
+    
     MarkdownDoc{
         nodes,
-        decoded,
+        spans,
+        decoded:compact,
     }
 }
\ No newline at end of file
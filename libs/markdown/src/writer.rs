@@ -0,0 +1,206 @@
+use crate::*;
+use std::fmt::Write;
+
+/// Writes a `MarkdownDoc` back to Markdown text.
+/// The output is normalised (one line per paragraph, fenced code blocks, `-` style rules)
+/// but parses back into the same nodes and decoded text. Text is written as is, since the
+/// parser has no backslash escapes outside of table cells, so text that reads as markup
+/// itself (a lone `*` inside bold) does not survive the round trip.
+pub fn write_markdown(doc:&MarkdownDoc)->String{
+    let mut out = String::new();
+    let mut list_depth = 0;
+    let mut quote_depth = 0;
+    let mut table_aligns = Vec::new();
+    let mut in_table_cell = false;
+    let mut in_header_row = false;
+    let mut in_code = false;
+
+    // blocks are separated by an empty line
+    fn begin_block(out:&mut String){
+        while !out.is_empty() && !out.ends_with("\n\n"){
+            out.push('\n');
+        }
+    }
+
+    for node in &doc.nodes{
+        match node{
+            MarkdownNode::BeginHead{level}=>{
+                begin_block(&mut out);
+                for _ in 0..*level{
+                    out.push('#');
+                }
+                out.push(' ');
+            }
+            MarkdownNode::EndHead=>{
+                out.push('\n');
+            }
+            MarkdownNode::BeginListItem{label}=>{
+                if list_depth == 0{
+                    begin_block(&mut out);
+                }
+                else if !out.ends_with('\n'){
+                    out.push('\n');
+                }
+                for _ in 0..list_depth{
+                    out.push_str("  ");
+                }
+                match label{
+                    MarkdownListLabel::Plus=>out.push_str("+ "),
+                    MarkdownListLabel::Minus=>out.push_str("- "),
+                    MarkdownListLabel::Star=>out.push_str("* "),
+                    MarkdownListLabel::Number{digit,..}=>{
+                        write!(&mut out, "{}. ", digit).unwrap();
+                    }
+                }
+                list_depth += 1;
+            }
+            MarkdownNode::EndListItem=>{
+                list_depth -= 1;
+                if list_depth == 0{
+                    out.push('\n');
+                }
+            }
+            MarkdownNode::TaskCheckbox{checked}=>{
+                out.push_str(if *checked{"[x] "}else{"[ ] "});
+            }
+            MarkdownNode::BeginNormal=>{
+                begin_block(&mut out);
+            }
+            MarkdownNode::EndNormal=>{
+                out.push('\n');
+            }
+            MarkdownNode::Link{start, url_start, end}=>{
+                let name = &doc.decoded[*start..*url_start];
+                let url = &doc.decoded[*url_start..*end];
+                // only when it parses back as the same autolink, emails get mailto: added
+                let is_email = !name.contains(':');
+                if is_autolink(name) && (if is_email{url.strip_prefix("mailto:") == Some(name)}else{url == name}){
+                    write!(&mut out, "<{}>", name).unwrap();
+                }
+                else if name.starts_with("www.") && url.strip_prefix("http://") == Some(name){
+                    out.push_str(name);
+                }
+                else{
+                    write!(&mut out, "[{}]({})", name, url).unwrap();
+                }
+            }
+            MarkdownNode::Image{start, url_start, end}=>{
+                write!(&mut out, "![{}]({})", &doc.decoded[*start..*url_start], &doc.decoded[*url_start..*end]).unwrap();
+            }
+            MarkdownNode::BeginQuote=>{
+                if quote_depth == 0{
+                    begin_block(&mut out);
+                }
+                else if !out.ends_with("> "){
+                    // nested quote starting after text of the outer one
+                    out.push('\n');
+                    for _ in 0..quote_depth{
+                        out.push_str("> ");
+                    }
+                }
+                quote_depth += 1;
+                out.push_str("> ");
+            }
+            MarkdownNode::EndQuote=>{
+                quote_depth -= 1;
+                if quote_depth == 0{
+                    out.push('\n');
+                }
+            }
+            MarkdownNode::Separator=>{
+                begin_block(&mut out);
+                out.push_str("---\n");
+            }
+            // underline has no markdown syntax, the text is kept as is
+            MarkdownNode::BeginUnderline | MarkdownNode::EndUnderline=>(),
+            MarkdownNode::BeginStrikethrough | MarkdownNode::EndStrikethrough=>{
+                out.push_str("~~");
+            }
+            MarkdownNode::BeginCode=>{
+                begin_block(&mut out);
+                out.push_str("```");
+                in_code = true;
+            }
+            MarkdownNode::EndCode=>{
+                out.push_str("\n```\n");
+                in_code = false;
+            }
+            MarkdownNode::BeginInlineCode | MarkdownNode::EndInlineCode=>{
+                out.push('`');
+            }
+            MarkdownNode::NewLine=>{
+                if in_code{
+                    out.push('\n');
+                }
+                else{
+                    // hard line break
+                    out.push_str("  \n");
+                    for _ in 0..quote_depth{
+                        out.push_str("> ");
+                    }
+                }
+            }
+            MarkdownNode::BeginBold | MarkdownNode::EndBold=>{
+                out.push_str("**");
+            }
+            MarkdownNode::BeginItalic | MarkdownNode::EndItalic=>{
+                out.push('*');
+            }
+            MarkdownNode::Text{start, end}=>{
+                let text = &doc.decoded[*start..*end];
+                if in_table_cell{
+                    out.push_str(&text.replace('|', "\\|"));
+                }
+                else{
+                    out.push_str(text);
+                }
+            }
+            MarkdownNode::BeginTable{..}=>{
+                begin_block(&mut out);
+                table_aligns.clear();
+            }
+            MarkdownNode::EndTable=>(),
+            MarkdownNode::BeginTableRow{header}=>{
+                in_header_row = *header;
+                out.push('|');
+            }
+            MarkdownNode::EndTableRow=>{
+                out.push('\n');
+                if in_header_row{
+                    out.push('|');
+                    for align in &table_aligns{
+                        out.push_str(match align{
+                            MarkdownTableAlign::None=>" --- |",
+                            MarkdownTableAlign::Left=>" :-- |",
+                            MarkdownTableAlign::Center=>" :-: |",
+                            MarkdownTableAlign::Right=>" --: |",
+                        });
+                    }
+                    out.push('\n');
+                }
+            }
+            MarkdownNode::BeginTableCell{align}=>{
+                if in_header_row{
+                    table_aligns.push(*align);
+                }
+                in_table_cell = true;
+                out.push(' ');
+            }
+            MarkdownNode::EndTableCell=>{
+                in_table_cell = false;
+                out.push_str(" |");
+            }
+            MarkdownNode::FootnoteRef{start, end, ..}=>{
+                write!(&mut out, "[^{}]", &doc.decoded[*start..*end]).unwrap();
+            }
+            MarkdownNode::BeginFootnote{start, end, ..}=>{
+                begin_block(&mut out);
+                write!(&mut out, "[^{}]: ", &doc.decoded[*start..*end]).unwrap();
+            }
+            MarkdownNode::EndFootnote=>{
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
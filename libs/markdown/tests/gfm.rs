@@ -0,0 +1,113 @@
+use makepad_markdown::*;
+use MarkdownNode::*;
+
+fn text(doc:&MarkdownDoc, node:&MarkdownNode)->String{
+    match node{
+        Text{start, end} | FootnoteRef{start, end, ..} | BeginFootnote{start, end, ..} => doc.decoded[*start..*end].to_string(),
+        Link{start, url_start, end} => format!("{} -> {}", &doc.decoded[*start..*url_start], &doc.decoded[*url_start..*end]),
+        _ => panic!("{:?} has no text", node)
+    }
+}
+
+fn texts(doc:&MarkdownDoc)->Vec<String>{
+    doc.nodes.iter().filter(|node| matches!(node, Text{..} | FootnoteRef{..} | BeginFootnote{..} | Link{..})).map(|node| text(doc, node)).collect()
+}
+
+#[test]
+fn table(){
+    let doc = parse_markdown("| a | b \\| c |\n|:--|--:|\n| 1 | 2 |\n| 3 |\n\ntext\n");
+    assert_eq!(doc.nodes, vec![
+        BeginTable{columns:2},
+        BeginTableRow{header:true},
+        BeginTableCell{align:MarkdownTableAlign::Left}, Text{start:0, end:1}, EndTableCell,
+        BeginTableCell{align:MarkdownTableAlign::Right}, Text{start:1, end:6}, EndTableCell,
+        EndTableRow,
+        BeginTableRow{header:false},
+        BeginTableCell{align:MarkdownTableAlign::Left}, Text{start:6, end:7}, EndTableCell,
+        BeginTableCell{align:MarkdownTableAlign::Right}, Text{start:7, end:8}, EndTableCell,
+        EndTableRow,
+        // missing cells are padded
+        BeginTableRow{header:false},
+        BeginTableCell{align:MarkdownTableAlign::Left}, Text{start:8, end:9}, EndTableCell,
+        BeginTableCell{align:MarkdownTableAlign::Right}, EndTableCell,
+        EndTableRow,
+        EndTable,
+        BeginNormal, Text{start:9, end:13}, EndNormal,
+    ]);
+    assert_eq!(texts(&doc), ["a", "b | c", "1", "2", "3", "text"]);
+}
+
+#[test]
+fn table_needs_a_delimiter_row(){
+    let doc = parse_markdown("| a | b |\n| 1 | 2 |\n");
+    assert!(!doc.nodes.iter().any(|node| matches!(node, BeginTable{..})));
+    // and the delimiter row needs a cell for every header cell
+    let doc = parse_markdown("| a | b |\n|---|\n");
+    assert!(!doc.nodes.iter().any(|node| matches!(node, BeginTable{..})));
+}
+
+#[test]
+fn strikethrough(){
+    let doc = parse_markdown("~~gone~~ and **~~both~~** ~~**again**~~\n");
+    assert_eq!(doc.nodes, vec![
+        BeginNormal,
+        BeginStrikethrough, Text{start:0, end:4}, EndStrikethrough,
+        Text{start:4, end:9},
+        BeginBold, BeginStrikethrough, Text{start:9, end:13}, EndStrikethrough, EndBold,
+        Text{start:13, end:14},
+        BeginStrikethrough, BeginBold, Text{start:14, end:19}, EndBold, EndStrikethrough,
+        EndNormal,
+    ]);
+    assert_eq!(texts(&doc), ["gone", " and ", "both", " ", "again"]);
+}
+
+#[test]
+fn task_list(){
+    let doc = parse_markdown("- [ ] todo\n- [x] done\n- [X] also done\n- [y] not a task\n");
+    let checkboxes:Vec<_> = doc.nodes.iter().filter_map(|node| match node{
+        TaskCheckbox{checked} => Some(*checked),
+        _ => None
+    }).collect();
+    assert_eq!(checkboxes, [false, true, true]);
+    assert_eq!(texts(&doc), ["todo", "done", "also done", "[y] not a task"]);
+}
+
+#[test]
+fn footnotes(){
+    let doc = parse_markdown("b[^Second] a[^first] again[^second]\n\n[^first]: one\n\n[^second]: two\n");
+    assert_eq!(doc.nodes, vec![
+        BeginNormal,
+        Text{start:0, end:1}, FootnoteRef{start:1, end:7, number:1},
+        Text{start:7, end:9}, FootnoteRef{start:9, end:14, number:2},
+        Text{start:14, end:20}, FootnoteRef{start:20, end:26, number:1},
+        EndNormal,
+        // definitions are moved to the end in order of first reference
+        BeginFootnote{start:26, end:32, number:1}, Text{start:32, end:35}, EndFootnote,
+        BeginFootnote{start:35, end:40, number:2}, Text{start:40, end:43}, EndFootnote,
+    ]);
+    assert_eq!(texts(&doc), ["b", "Second", " a", "first", " again", "second", "second", "two", "first", "one"]);
+}
+
+#[test]
+fn undefined_footnote_is_text(){
+    let doc = parse_markdown("a[^1] b[^none]\n\n[^1]: note\n");
+    assert_eq!(texts(&doc), ["a", "1", " b[^none]", "1", "note"]);
+    let doc = parse_markdown("a [^1] b\n");
+    assert_eq!(doc.nodes, vec![BeginNormal, Text{start:0, end:8}, EndNormal]);
+    assert_eq!(doc.decoded, "a [^1] b");
+}
+
+#[test]
+fn autolinks(){
+    let doc = parse_markdown("<https://x.org> www.x.org, https://y.org/a_(b). <me@x.org> <not a link>\n");
+    assert_eq!(texts(&doc), [
+        "https://x.org -> https://x.org",
+        " ",
+        "www.x.org -> http://www.x.org",
+        ", ",
+        "https://y.org/a_(b) -> https://y.org/a_(b)",
+        ". ",
+        "me@x.org -> mailto:me@x.org",
+        " <not a link>",
+    ]);
+}
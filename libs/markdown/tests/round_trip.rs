@@ -0,0 +1,65 @@
+use makepad_markdown::*;
+
+const CORPUS:&[&str] = &[
+    "plain text\n",
+    "two\nlines joined\n\nand a second paragraph\n",
+    "# head\n\n## second *level*\n\ntext\n",
+    "hard  \nbreak\n",
+    "*italic* **bold** ***both*** ~~strike~~ **~~nested~~**\n",
+    "`inline code` and ``tick ` inside``\n",
+    "```\nfenced\n  code\n```\n",
+    "```rust\nfn main(){}\n```\n",
+    "    indented code\n",
+    "> quote\n> more\n\n> > nested quote\n",
+    "- one\n- two\n  - nested\n  - nested **bold**\n- three\n",
+    "1. first\n2. second\n3. third\n",
+    "- [ ] todo\n- [x] done\n",
+    "a [link](https://x.org) and ![image](img.png)\n",
+    "<https://x.org> www.x.org <me@x.org>\n",
+    "escaped \\* star and \\_ underscore\n",
+    "text\n\n---\n\nafter the rule\n",
+    "| a | b \\| c |\n|:--|--:|\n| 1 | 2 |\n| 3 |\n\ntext\n",
+    "| left | center | right |\n|:--|:-:|--:|\n| *x* | `y` | [z](z) |\n",
+    "| a |\n|---|\n| b **c |\n",
+    "| a |\n|---|\n| ~~b *c |\n",
+    "b[^Second] a[^first] again[^second]\n\n[^first]: one\n\n[^second]: two\n",
+    "[^a]: unreferenced\n",
+    "text[^a]\n\n[^b]: unreferenced\n\n[^a]: used\n",
+    "a [^1] b[^none]\n",
+    "5. five\n6. six\n",
+    "> - quoted list\n> - more\n",
+    "*a **b** c*\n",
+    "**unclosed bold\n",
+    "[link **bold**](u)\n",
+    "| `a|b` |\n|---|\n| x |\n",
+    "| a |\n|---|\n| [z](z) | <y.org> |\n",
+];
+
+#[test]
+fn written_markdown_parses_back_the_same(){
+    for sample in CORPUS{
+        let doc = parse_markdown(sample);
+        let written = write_markdown(&doc);
+        let again = parse_markdown(&written);
+        assert_eq!(again.nodes, doc.nodes, "{:?} was written as {:?}", sample, written);
+        assert_eq!(again.decoded, doc.decoded, "{:?} was written as {:?}", sample, written);
+        // and writing is stable after that
+        assert_eq!(write_markdown(&again), written, "{:?}", sample);
+    }
+}
+
+#[test]
+fn unreferenced_footnotes_leave_nothing_behind(){
+    let doc = parse_markdown("[^a]: unreferenced\n");
+    assert!(doc.nodes.is_empty());
+    assert_eq!(doc.decoded, "");
+    let doc = parse_markdown("text[^a]\n\n[^b]: unreferenced\n\n[^a]: used\n");
+    assert_eq!(doc.decoded, "textaaused");
+    assert!(!doc.decoded.contains("unreferenced"));
+}
+
+#[test]
+fn styles_in_table_cells_end_before_trailing_space(){
+    let doc = parse_markdown("| a |\n|---|\n| b **c |\n");
+    assert_eq!(write_markdown(&doc), "| a |\n| --- |\n| b **c** |\n");
+}
@@ -0,0 +1,16 @@
+use std::env;
+
+// Decides whether threaded code is executed in trampoline mode (see `next_instr` in
+// src/exec.rs). This is decided when the crate is built, so that executing the next instruction
+// doesn't have to check for it.
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(stitch_trampoline)");
+    let trampoline = env::var_os("CARGO_FEATURE_TRAMPOLINE").is_some()
+        // We have not found a way to get LLVM to optimise sibling calls on 32-bit platforms.
+        || env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap() != "64"
+        // LLVM only optimises sibling calls from opt-level 2 up.
+        || matches!(env::var("OPT_LEVEL").unwrap().as_str(), "0" | "1");
+    if trampoline {
+        println!("cargo:rustc-cfg=stitch_trampoline");
+    }
+}
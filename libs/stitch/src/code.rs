@@ -20,6 +20,8 @@ pub(crate) enum Code {
 pub(crate) struct UncompiledCode {
     pub(crate) locals: Box<[ValType]>,
     pub(crate) expr: Arc<[u8]>,
+    /// The offset of `expr` in the module.
+    pub(crate) expr_offset: usize,
 }
 
 impl Decode for UncompiledCode {
@@ -39,6 +41,7 @@ impl Decode for UncompiledCode {
                 }
                 locals.into()
             },
+            expr_offset: code_decoder.offset(),
             expr: code_decoder.read_bytes_until_end().into(),
         })
     }
@@ -49,9 +52,34 @@ pub(crate) struct CompiledCode {
     pub(crate) max_stack_height: usize,
     pub(crate) local_count: usize,
     pub(crate) code: AliasableBox<[InstrSlot]>,
+    /// For each Wasm instruction that emitted code, the index of the first slot of that code,
+    /// together with the offset of the instruction in the module, ordered by index. The first
+    /// entry always has index 0, and covers the code that is emitted before the first instruction.
+    pub(crate) instr_offsets: Box<[(u32, u32)]>,
 }
 
-pub(crate) type InstrSlot = usize;
+impl CompiledCode {
+    /// Returns the offset in the module of the Wasm instruction that emitted the code at the given
+    /// instruction pointer, or `None` if the instruction pointer does not point into this
+    /// [`CompiledCode`].
+    pub(crate) fn instr_offset(&self, ip: *const InstrSlot) -> Option<usize> {
+        let range = self.code.as_ptr_range();
+        if !range.contains(&ip) {
+            return None;
+        }
+        let idx = unsafe { ip.offset_from(range.start) } as u32;
+        let pos = self
+            .instr_offsets
+            .partition_point(|&(first_idx, _)| first_idx <= idx);
+        Some(self.instr_offsets[pos - 1].1 as usize)
+    }
+}
+
+/// A slot in the threaded code for a function.
+///
+/// Every instruction and immediate occupies one slot, so a slot must be large enough to hold a
+/// 64-bit immediate, even on 32-bit platforms.
+pub(crate) type InstrSlot = u64;
 
 pub(crate) trait InstrVisitor {
     type Error;
@@ -74,6 +102,12 @@ pub(crate) trait InstrVisitor {
     fn visit_return(&mut self) -> Result<(), Self::Error>;
     fn visit_call(&mut self, func_idx: u32) -> Result<(), Self::Error>;
     fn visit_call_indirect(&mut self, table_idx: u32, type_idx: u32) -> Result<(), Self::Error>;
+    fn visit_return_call(&mut self, func_idx: u32) -> Result<(), Self::Error>;
+    fn visit_return_call_indirect(
+        &mut self,
+        table_idx: u32,
+        type_idx: u32,
+    ) -> Result<(), Self::Error>;
 
     // Reference instructions
     fn visit_ref_null(&mut self, type_: RefType) -> Result<(), Self::Error>;
@@ -108,11 +142,11 @@ pub(crate) trait InstrVisitor {
     // Memory instructions
     fn visit_load(&mut self, arg: MemArg, info: LoadInfo) -> Result<(), Self::Error>;
     fn visit_store(&mut self, arg: MemArg, info: StoreInfo) -> Result<(), Self::Error>;
-    fn visit_memory_size(&mut self) -> Result<(), Self::Error>;
-    fn visit_memory_grow(&mut self) -> Result<(), Self::Error>;
-    fn visit_memory_fill(&mut self) -> Result<(), Self::Error>;
-    fn visit_memory_copy(&mut self) -> Result<(), Self::Error>;
-    fn visit_memory_init(&mut self, data_idx: u32) -> Result<(), Self::Error>;
+    fn visit_memory_size(&mut self, mem_idx: u32) -> Result<(), Self::Error>;
+    fn visit_memory_grow(&mut self, mem_idx: u32) -> Result<(), Self::Error>;
+    fn visit_memory_fill(&mut self, mem_idx: u32) -> Result<(), Self::Error>;
+    fn visit_memory_copy(&mut self, dst_mem_idx: u32, src_mem_idx: u32) -> Result<(), Self::Error>;
+    fn visit_memory_init(&mut self, mem_idx: u32, data_idx: u32) -> Result<(), Self::Error>;
     fn visit_data_drop(&mut self, data_idx: u32) -> Result<(), Self::Error>;
 
     // Numeric instructions
@@ -122,6 +156,10 @@ pub(crate) trait InstrVisitor {
     fn visit_f64_const(&mut self, val: f64) -> Result<(), Self::Error>;
     fn visit_un_op(&mut self, info: UnOpInfo) -> Result<(), Self::Error>;
     fn visit_bin_op(&mut self, info: BinOpInfo) -> Result<(), Self::Error>;
+
+    // Vector instructions
+    fn visit_v128_const(&mut self, val: u128) -> Result<(), Self::Error>;
+    fn visit_simd_op(&mut self, info: SimdOpInfo) -> Result<(), Self::Error>;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -158,6 +196,7 @@ impl Decode for BlockType {
             0x7E => Ok(BlockType::ValType(Some(ValType::I64))),
             0x7D => Ok(BlockType::ValType(Some(ValType::F32))),
             0x7C => Ok(BlockType::ValType(Some(ValType::F64))),
+            0x7B => Ok(BlockType::ValType(Some(ValType::V128))),
             0x70 => Ok(BlockType::ValType(Some(ValType::FuncRef))),
             0x6F => Ok(BlockType::ValType(Some(ValType::ExternRef))),
             byte => {
@@ -179,13 +218,21 @@ impl Decode for BlockType {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct MemArg {
     pub(crate) align: u32,
-    pub(crate) offset: u32,
+    pub(crate) mem_idx: u32,
+    pub(crate) offset: u64,
 }
 
 impl Decode for MemArg {
     fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        // Bit 6 of the alignment field indicates that an explicit memory index follows.
+        let flags: u32 = decoder.decode()?;
         Ok(Self {
-            align: decoder.decode()?,
+            align: flags & !0x40,
+            mem_idx: if flags & 0x40 != 0 {
+                decoder.decode()?
+            } else {
+                0
+            },
             offset: decoder.decode()?,
         })
     }
@@ -230,6 +277,36 @@ pub(crate) struct BinOpInfo {
     pub(crate) instr_rr: Option<ThreadedInstr>,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SimdOpInfo {
+    pub(crate) _name: &'static str,
+    pub(crate) input_types: &'static [ValType],
+    pub(crate) output_type: Option<ValType>,
+    pub(crate) imm: SimdImm,
+    pub(crate) instr: ThreadedInstr,
+}
+
+/// The immediate operands of a SIMD instruction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SimdImm {
+    None,
+    Lane {
+        lane_idx: u8,
+        lane_count: u8,
+    },
+    Shuffle([u8; 16]),
+    MemArg {
+        max_align: u32,
+        arg: MemArg,
+    },
+    MemArgLane {
+        max_align: u32,
+        arg: MemArg,
+        lane_idx: u8,
+        lane_count: u8,
+    },
+}
+
 pub(crate) fn decode_instr<V>(
     decoder: &mut Decoder<'_>,
     label_idxs: &mut Vec<u32>,
@@ -264,6 +341,12 @@ where
             let table_idx = decoder.decode()?;
             visitor.visit_call_indirect(table_idx, type_idx)
         }
+        0x12 => visitor.visit_return_call(decoder.decode()?),
+        0x13 => {
+            let type_idx = decoder.decode()?;
+            let table_idx = decoder.decode()?;
+            visitor.visit_return_call_indirect(table_idx, type_idx)
+        }
         0x1A => visitor.visit_drop(),
         0x1B => visitor.visit_select(None),
         0x1C => {
@@ -665,18 +748,8 @@ where
                 },
             },
         ),
-        0x3F => {
-            if decoder.read_byte()? != 0x00 {
-                return Err(DecodeError::new("expected zero byte"))?;
-            }
-            visitor.visit_memory_size()
-        }
-        0x40 => {
-            if decoder.read_byte()? != 0x00 {
-                return Err(DecodeError::new("expected zero byte"))?;
-            }
-            visitor.visit_memory_grow()
-        }
+        0x3F => visitor.visit_memory_size(decoder.decode()?),
+        0x40 => visitor.visit_memory_grow(decoder.decode()?),
         0x41 => visitor.visit_i32_const(decoder.decode()?),
         0x42 => visitor.visit_i64_const(decoder.decode()?),
         0x43 => visitor.visit_f32_const(decoder.decode()?),
@@ -2307,27 +2380,12 @@ where
             }),
             8 => {
                 let data_idx = decoder.decode()?;
-                if decoder.read_byte()? != 0x00 {
-                    return Err(DecodeError::new("expected zero byte"))?;
-                }
-                visitor.visit_memory_init(data_idx)
+                let mem_idx = decoder.decode()?;
+                visitor.visit_memory_init(mem_idx, data_idx)
             }
             9 => visitor.visit_data_drop(decoder.decode()?),
-            10 => {
-                if decoder.read_byte()? != 0x00 {
-                    return Err(DecodeError::new("expected zero byte"))?;
-                }
-                if decoder.read_byte()? != 0x00 {
-                    return Err(DecodeError::new("expected zero byte"))?;
-                }
-                visitor.visit_memory_copy()
-            }
-            11 => {
-                if decoder.read_byte()? != 0x00 {
-                    return Err(DecodeError::new("expected zero byte"))?;
-                }
-                visitor.visit_memory_fill()
-            }
+            10 => visitor.visit_memory_copy(decoder.decode()?, decoder.decode()?),
+            11 => visitor.visit_memory_fill(decoder.decode()?),
             12 => {
                 let elem_idx = decoder.decode()?;
                 let table_idx = decoder.decode()?;
@@ -2340,6 +2398,1779 @@ where
             17 => visitor.visit_table_fill(decoder.decode()?),
             _ => Err(DecodeError::new("illegal opcode"))?,
         },
+        0xFD => match decoder.decode::<u32>()? {
+            0x00 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 4,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load,
+            }),
+            0x01 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load8x8_s",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load8x8_s,
+            }),
+            0x02 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load8x8_u",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load8x8_u,
+            }),
+            0x03 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load16x4_s",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load16x4_s,
+            }),
+            0x04 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load16x4_u",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load16x4_u,
+            }),
+            0x05 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load32x2_s",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load32x2_s,
+            }),
+            0x06 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load32x2_u",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load32x2_u,
+            }),
+            0x07 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load8_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 0,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load8_splat,
+            }),
+            0x08 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load16_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 1,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load16_splat,
+            }),
+            0x09 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load32_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 2,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load32_splat,
+            }),
+            0x0A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load64_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load64_splat,
+            }),
+            0x0B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_store",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: None,
+                imm: SimdImm::MemArg {
+                    max_align: 4,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_store,
+            }),
+            0x0C => visitor.visit_v128_const(decoder.decode()?),
+            0x0D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_shuffle",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Shuffle(decoder.decode::<u128>()?.to_le_bytes()),
+                instr: exec::i8x16_shuffle,
+            }),
+            0x0E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_swizzle",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_swizzle,
+            }),
+            0x0F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_splat,
+            }),
+            0x10 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_splat,
+            }),
+            0x11 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_splat",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_splat,
+            }),
+            0x12 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_splat",
+                input_types: &[ValType::I64],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_splat,
+            }),
+            0x13 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_splat",
+                input_types: &[ValType::F32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_splat,
+            }),
+            0x14 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_splat",
+                input_types: &[ValType::F64],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_splat,
+            }),
+            0x15 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_extract_lane_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 16,
+                },
+                instr: exec::i8x16_extract_lane_s,
+            }),
+            0x16 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_extract_lane_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 16,
+                },
+                instr: exec::i8x16_extract_lane_u,
+            }),
+            0x17 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_replace_lane",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 16,
+                },
+                instr: exec::i8x16_replace_lane,
+            }),
+            0x18 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extract_lane_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 8,
+                },
+                instr: exec::i16x8_extract_lane_s,
+            }),
+            0x19 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extract_lane_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 8,
+                },
+                instr: exec::i16x8_extract_lane_u,
+            }),
+            0x1A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_replace_lane",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 8,
+                },
+                instr: exec::i16x8_replace_lane,
+            }),
+            0x1B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extract_lane",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::i32x4_extract_lane,
+            }),
+            0x1C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_replace_lane",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::i32x4_replace_lane,
+            }),
+            0x1D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extract_lane",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I64),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::i64x2_extract_lane,
+            }),
+            0x1E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_replace_lane",
+                input_types: &[ValType::V128, ValType::I64],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::i64x2_replace_lane,
+            }),
+            0x1F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_extract_lane",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::F32),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::f32x4_extract_lane,
+            }),
+            0x20 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_replace_lane",
+                input_types: &[ValType::V128, ValType::F32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::f32x4_replace_lane,
+            }),
+            0x21 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_extract_lane",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::F64),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::f64x2_extract_lane,
+            }),
+            0x22 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_replace_lane",
+                input_types: &[ValType::V128, ValType::F64],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::Lane {
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::f64x2_replace_lane,
+            }),
+            0x23 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_eq,
+            }),
+            0x24 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_ne,
+            }),
+            0x25 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_lt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_lt_s,
+            }),
+            0x26 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_lt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_lt_u,
+            }),
+            0x27 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_gt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_gt_s,
+            }),
+            0x28 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_gt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_gt_u,
+            }),
+            0x29 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_le_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_le_s,
+            }),
+            0x2A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_le_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_le_u,
+            }),
+            0x2B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_ge_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_ge_s,
+            }),
+            0x2C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_ge_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_ge_u,
+            }),
+            0x2D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_eq,
+            }),
+            0x2E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_ne,
+            }),
+            0x2F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_lt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_lt_s,
+            }),
+            0x30 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_lt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_lt_u,
+            }),
+            0x31 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_gt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_gt_s,
+            }),
+            0x32 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_gt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_gt_u,
+            }),
+            0x33 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_le_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_le_s,
+            }),
+            0x34 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_le_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_le_u,
+            }),
+            0x35 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_ge_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_ge_s,
+            }),
+            0x36 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_ge_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_ge_u,
+            }),
+            0x37 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_eq,
+            }),
+            0x38 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_ne,
+            }),
+            0x39 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_lt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_lt_s,
+            }),
+            0x3A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_lt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_lt_u,
+            }),
+            0x3B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_gt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_gt_s,
+            }),
+            0x3C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_gt_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_gt_u,
+            }),
+            0x3D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_le_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_le_s,
+            }),
+            0x3E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_le_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_le_u,
+            }),
+            0x3F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_ge_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_ge_s,
+            }),
+            0x40 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_ge_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_ge_u,
+            }),
+            0x41 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_eq,
+            }),
+            0x42 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_ne,
+            }),
+            0x43 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_lt",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_lt,
+            }),
+            0x44 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_gt",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_gt,
+            }),
+            0x45 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_le",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_le,
+            }),
+            0x46 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_ge",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_ge,
+            }),
+            0x47 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_eq,
+            }),
+            0x48 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_ne,
+            }),
+            0x49 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_lt",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_lt,
+            }),
+            0x4A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_gt",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_gt,
+            }),
+            0x4B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_le",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_le,
+            }),
+            0x4C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_ge",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_ge,
+            }),
+            0x4D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_not",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_not,
+            }),
+            0x4E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_and",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_and,
+            }),
+            0x4F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_andnot",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_andnot,
+            }),
+            0x50 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_or",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_or,
+            }),
+            0x51 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_xor",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_xor,
+            }),
+            0x52 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_bitselect",
+                input_types: &[ValType::V128, ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::v128_bitselect,
+            }),
+            0x53 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_any_true",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::v128_any_true,
+            }),
+            0x54 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load8_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArgLane {
+                    max_align: 0,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 16,
+                },
+                instr: exec::v128_load8_lane,
+            }),
+            0x55 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load16_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArgLane {
+                    max_align: 1,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 8,
+                },
+                instr: exec::v128_load16_lane,
+            }),
+            0x56 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load32_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArgLane {
+                    max_align: 2,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::v128_load32_lane,
+            }),
+            0x57 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load64_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArgLane {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::v128_load64_lane,
+            }),
+            0x58 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_store8_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: None,
+                imm: SimdImm::MemArgLane {
+                    max_align: 0,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 16,
+                },
+                instr: exec::v128_store8_lane,
+            }),
+            0x59 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_store16_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: None,
+                imm: SimdImm::MemArgLane {
+                    max_align: 1,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 8,
+                },
+                instr: exec::v128_store16_lane,
+            }),
+            0x5A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_store32_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: None,
+                imm: SimdImm::MemArgLane {
+                    max_align: 2,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 4,
+                },
+                instr: exec::v128_store32_lane,
+            }),
+            0x5B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_store64_lane",
+                input_types: &[ValType::I32, ValType::V128],
+                output_type: None,
+                imm: SimdImm::MemArgLane {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                    lane_idx: decoder.read_byte()?,
+                    lane_count: 2,
+                },
+                instr: exec::v128_store64_lane,
+            }),
+            0x5C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load32_zero",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 2,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load32_zero,
+            }),
+            0x5D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "v128_load64_zero",
+                input_types: &[ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::MemArg {
+                    max_align: 3,
+                    arg: decoder.decode()?,
+                },
+                instr: exec::v128_load64_zero,
+            }),
+            0x5E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_demote_f64x2_zero",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_demote_f64x2_zero,
+            }),
+            0x5F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_promote_low_f32x4",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_promote_low_f32x4,
+            }),
+            0x60 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_abs,
+            }),
+            0x61 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_neg,
+            }),
+            0x62 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_popcnt",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_popcnt,
+            }),
+            0x63 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_all_true",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i8x16_all_true,
+            }),
+            0x64 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_bitmask",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i8x16_bitmask,
+            }),
+            0x65 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_narrow_i16x8_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_narrow_i16x8_s,
+            }),
+            0x66 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_narrow_i16x8_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_narrow_i16x8_u,
+            }),
+            0x67 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_ceil",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_ceil,
+            }),
+            0x68 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_floor",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_floor,
+            }),
+            0x69 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_trunc",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_trunc,
+            }),
+            0x6A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_nearest",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_nearest,
+            }),
+            0x6B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_shl",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_shl,
+            }),
+            0x6C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_shr_s",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_shr_s,
+            }),
+            0x6D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_shr_u",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_shr_u,
+            }),
+            0x6E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_add,
+            }),
+            0x6F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_add_sat_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_add_sat_s,
+            }),
+            0x70 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_add_sat_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_add_sat_u,
+            }),
+            0x71 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_sub,
+            }),
+            0x72 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_sub_sat_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_sub_sat_s,
+            }),
+            0x73 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_sub_sat_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_sub_sat_u,
+            }),
+            0x74 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_ceil",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_ceil,
+            }),
+            0x75 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_floor",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_floor,
+            }),
+            0x76 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_min_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_min_s,
+            }),
+            0x77 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_min_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_min_u,
+            }),
+            0x78 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_max_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_max_s,
+            }),
+            0x79 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_max_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_max_u,
+            }),
+            0x7A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_trunc",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_trunc,
+            }),
+            0x7B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i8x16_avgr_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i8x16_avgr_u,
+            }),
+            0x7C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extadd_pairwise_i8x16_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extadd_pairwise_i8x16_s,
+            }),
+            0x7D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extadd_pairwise_i8x16_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extadd_pairwise_i8x16_u,
+            }),
+            0x7E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extadd_pairwise_i16x8_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extadd_pairwise_i16x8_s,
+            }),
+            0x7F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extadd_pairwise_i16x8_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extadd_pairwise_i16x8_u,
+            }),
+            0x80 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_abs,
+            }),
+            0x81 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_neg,
+            }),
+            0x82 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_q15mulr_sat_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_q15mulr_sat_s,
+            }),
+            0x83 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_all_true",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i16x8_all_true,
+            }),
+            0x84 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_bitmask",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i16x8_bitmask,
+            }),
+            0x85 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_narrow_i32x4_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_narrow_i32x4_s,
+            }),
+            0x86 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_narrow_i32x4_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_narrow_i32x4_u,
+            }),
+            0x87 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extend_low_i8x16_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extend_low_i8x16_s,
+            }),
+            0x88 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extend_high_i8x16_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extend_high_i8x16_s,
+            }),
+            0x89 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extend_low_i8x16_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extend_low_i8x16_u,
+            }),
+            0x8A => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extend_high_i8x16_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extend_high_i8x16_u,
+            }),
+            0x8B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_shl",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_shl,
+            }),
+            0x8C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_shr_s",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_shr_s,
+            }),
+            0x8D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_shr_u",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_shr_u,
+            }),
+            0x8E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_add,
+            }),
+            0x8F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_add_sat_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_add_sat_s,
+            }),
+            0x90 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_add_sat_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_add_sat_u,
+            }),
+            0x91 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_sub,
+            }),
+            0x92 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_sub_sat_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_sub_sat_s,
+            }),
+            0x93 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_sub_sat_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_sub_sat_u,
+            }),
+            0x94 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_nearest",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_nearest,
+            }),
+            0x95 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_mul",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_mul,
+            }),
+            0x96 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_min_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_min_s,
+            }),
+            0x97 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_min_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_min_u,
+            }),
+            0x98 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_max_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_max_s,
+            }),
+            0x99 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_max_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_max_u,
+            }),
+            0x9B => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_avgr_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_avgr_u,
+            }),
+            0x9C => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extmul_low_i8x16_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extmul_low_i8x16_s,
+            }),
+            0x9D => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extmul_high_i8x16_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extmul_high_i8x16_s,
+            }),
+            0x9E => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extmul_low_i8x16_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extmul_low_i8x16_u,
+            }),
+            0x9F => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i16x8_extmul_high_i8x16_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i16x8_extmul_high_i8x16_u,
+            }),
+            0xA0 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_abs,
+            }),
+            0xA1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_neg,
+            }),
+            0xA3 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_all_true",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i32x4_all_true,
+            }),
+            0xA4 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_bitmask",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i32x4_bitmask,
+            }),
+            0xA7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extend_low_i16x8_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extend_low_i16x8_s,
+            }),
+            0xA8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extend_high_i16x8_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extend_high_i16x8_s,
+            }),
+            0xA9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extend_low_i16x8_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extend_low_i16x8_u,
+            }),
+            0xAA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extend_high_i16x8_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extend_high_i16x8_u,
+            }),
+            0xAB => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_shl",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_shl,
+            }),
+            0xAC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_shr_s",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_shr_s,
+            }),
+            0xAD => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_shr_u",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_shr_u,
+            }),
+            0xAE => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_add,
+            }),
+            0xB1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_sub,
+            }),
+            0xB5 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_mul",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_mul,
+            }),
+            0xB6 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_min_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_min_s,
+            }),
+            0xB7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_min_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_min_u,
+            }),
+            0xB8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_max_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_max_s,
+            }),
+            0xB9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_max_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_max_u,
+            }),
+            0xBA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_dot_i16x8_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_dot_i16x8_s,
+            }),
+            0xBC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extmul_low_i16x8_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extmul_low_i16x8_s,
+            }),
+            0xBD => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extmul_high_i16x8_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extmul_high_i16x8_s,
+            }),
+            0xBE => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extmul_low_i16x8_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extmul_low_i16x8_u,
+            }),
+            0xBF => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_extmul_high_i16x8_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_extmul_high_i16x8_u,
+            }),
+            0xC0 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_abs,
+            }),
+            0xC1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_neg,
+            }),
+            0xC3 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_all_true",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i64x2_all_true,
+            }),
+            0xC4 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_bitmask",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::I32),
+                imm: SimdImm::None,
+                instr: exec::i64x2_bitmask,
+            }),
+            0xC7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extend_low_i32x4_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extend_low_i32x4_s,
+            }),
+            0xC8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extend_high_i32x4_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extend_high_i32x4_s,
+            }),
+            0xC9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extend_low_i32x4_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extend_low_i32x4_u,
+            }),
+            0xCA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extend_high_i32x4_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extend_high_i32x4_u,
+            }),
+            0xCB => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_shl",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_shl,
+            }),
+            0xCC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_shr_s",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_shr_s,
+            }),
+            0xCD => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_shr_u",
+                input_types: &[ValType::V128, ValType::I32],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_shr_u,
+            }),
+            0xCE => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_add,
+            }),
+            0xD1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_sub,
+            }),
+            0xD5 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_mul",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_mul,
+            }),
+            0xD6 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_eq",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_eq,
+            }),
+            0xD7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_ne",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_ne,
+            }),
+            0xD8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_lt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_lt_s,
+            }),
+            0xD9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_gt_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_gt_s,
+            }),
+            0xDA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_le_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_le_s,
+            }),
+            0xDB => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_ge_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_ge_s,
+            }),
+            0xDC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extmul_low_i32x4_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extmul_low_i32x4_s,
+            }),
+            0xDD => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extmul_high_i32x4_s",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extmul_high_i32x4_s,
+            }),
+            0xDE => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extmul_low_i32x4_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extmul_low_i32x4_u,
+            }),
+            0xDF => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i64x2_extmul_high_i32x4_u",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i64x2_extmul_high_i32x4_u,
+            }),
+            0xE0 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_abs,
+            }),
+            0xE1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_neg,
+            }),
+            0xE3 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_sqrt",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_sqrt,
+            }),
+            0xE4 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_add,
+            }),
+            0xE5 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_sub,
+            }),
+            0xE6 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_mul",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_mul,
+            }),
+            0xE7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_div",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_div,
+            }),
+            0xE8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_min",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_min,
+            }),
+            0xE9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_max",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_max,
+            }),
+            0xEA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_pmin",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_pmin,
+            }),
+            0xEB => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_pmax",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_pmax,
+            }),
+            0xEC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_abs",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_abs,
+            }),
+            0xED => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_neg",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_neg,
+            }),
+            0xEF => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_sqrt",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_sqrt,
+            }),
+            0xF0 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_add",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_add,
+            }),
+            0xF1 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_sub",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_sub,
+            }),
+            0xF2 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_mul",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_mul,
+            }),
+            0xF3 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_div",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_div,
+            }),
+            0xF4 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_min",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_min,
+            }),
+            0xF5 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_max",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_max,
+            }),
+            0xF6 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_pmin",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_pmin,
+            }),
+            0xF7 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_pmax",
+                input_types: &[ValType::V128, ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_pmax,
+            }),
+            0xF8 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_trunc_sat_f32x4_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_trunc_sat_f32x4_s,
+            }),
+            0xF9 => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_trunc_sat_f32x4_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_trunc_sat_f32x4_u,
+            }),
+            0xFA => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_convert_i32x4_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_convert_i32x4_s,
+            }),
+            0xFB => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f32x4_convert_i32x4_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f32x4_convert_i32x4_u,
+            }),
+            0xFC => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_trunc_sat_f64x2_s_zero",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_trunc_sat_f64x2_s_zero,
+            }),
+            0xFD => visitor.visit_simd_op(SimdOpInfo {
+                _name: "i32x4_trunc_sat_f64x2_u_zero",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::i32x4_trunc_sat_f64x2_u_zero,
+            }),
+            0xFE => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_convert_low_i32x4_s",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_convert_low_i32x4_s,
+            }),
+            0xFF => visitor.visit_simd_op(SimdOpInfo {
+                _name: "f64x2_convert_low_i32x4_u",
+                input_types: &[ValType::V128],
+                output_type: Some(ValType::V128),
+                imm: SimdImm::None,
+                instr: exec::f64x2_convert_low_i32x4_u,
+            }),
+            _ => Err(DecodeError::new("illegal opcode"))?,
+        },
         _ => Err(DecodeError::new("illegal opcode"))?,
     }
 }
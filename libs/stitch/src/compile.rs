@@ -3,8 +3,8 @@ use {
         aliasable_box::AliasableBox,
         code,
         code::{
-            BinOpInfo, BlockType, CompiledCode, InstrSlot, InstrVisitor, LoadInfo, MemArg,
-            StoreInfo, UnOpInfo, UncompiledCode,
+            BinOpInfo, BlockType, CompiledCode, InstrSlot, InstrVisitor, LoadInfo, MemArg, SimdImm,
+            SimdOpInfo, StoreInfo, UnOpInfo, UncompiledCode,
         },
         decode::DecodeError,
         exec,
@@ -13,6 +13,7 @@ use {
         func::{Func, FuncEntity, FuncType},
         func_ref::FuncRef,
         instance::Instance,
+        mem::MemType,
         ref_::RefType,
         stack::StackSlot,
         store::Store,
@@ -82,6 +83,9 @@ impl Compiler {
             first_temp_stack_idx: local_count,
             max_stack_height: local_count,
             regs: [None; 2],
+            fuel_cost_idx: None,
+            fuel_cost: 0,
+            is_profiling: store.engine().profile_interval().is_some(),
             code: Vec::new(),
         };
         compile.push_block(
@@ -97,11 +101,24 @@ impl Compiler {
                 .mem(0)
                 .map(|mem| mem.to_unguarded(store.id())),
         );
+        if compile.is_profiling {
+            compile.emit(exec::count_call as ThreadedInstr);
+            compile.emit(func.to_unguarded(store.id()));
+        }
+        compile.emit_consume_fuel();
 
-        let mut decoder = Decoder::new(&code.expr);
+        let mut instr_offsets = vec![(0, code.expr_offset as u32)];
+        let mut decoder = Decoder::with_offset(&code.expr, code.expr_offset);
         while !compile.blocks.is_empty() {
+            let first_idx = compile.code.len() as u32;
+            let instr_offset = decoder.offset() as u32;
             code::decode_instr(&mut decoder, &mut self.label_idxs, &mut compile).unwrap();
+            compile.fuel_cost += 1;
+            if compile.code.len() as u32 > first_idx {
+                instr_offsets.push((first_idx, instr_offset));
+            }
         }
+        compile.patch_fuel_cost();
 
         for (result_idx, result_type) in type_.clone().results().iter().copied().enumerate().rev() {
             compile.emit(select_copy_stack(result_type));
@@ -112,13 +129,14 @@ impl Compiler {
 
         let mut code: AliasableBox<[InstrSlot]> = AliasableBox::from_box(Box::from(compile.code));
         for fixup_idx in compile.fixup_idxs.drain(..) {
-            code[fixup_idx] += code.as_ptr() as usize;
+            code[fixup_idx] += code.as_ptr() as InstrSlot;
         }
 
         CompiledCode {
             max_stack_height: compile.max_stack_height,
             local_count,
             code,
+            instr_offsets: instr_offsets.into(),
         }
     }
 }
@@ -142,6 +160,9 @@ struct Compile<'a> {
     first_temp_stack_idx: usize,
     max_stack_height: usize,
     regs: [Option<usize>; 2],
+    fuel_cost_idx: Option<usize>,
+    fuel_cost: usize,
+    is_profiling: bool,
     code: Vec<InstrSlot>,
 }
 
@@ -230,21 +251,24 @@ impl<'a> Compile<'a> {
 
     /// Pushes the hole with the given index onto the block with the given index.
     fn push_hole(&mut self, block_idx: usize, hole_idx: usize) {
-        // We use the hole itself to store the index of the next hole. The value `usize::MAX` is
-        // used to indicate the absence of a next hole.
-        self.code[hole_idx] = self.block(block_idx).first_hole_idx.unwrap_or(usize::MAX);
+        // We use the hole itself to store the index of the next hole. The value `InstrSlot::MAX`
+        // is used to indicate the absence of a next hole.
+        self.code[hole_idx] = self
+            .block(block_idx)
+            .first_hole_idx
+            .map_or(InstrSlot::MAX, |first_hole_idx| first_hole_idx as InstrSlot);
         self.block_mut(block_idx).first_hole_idx = Some(hole_idx);
     }
 
     /// Pops a hole from the block with the given index.
     fn pop_hole(&mut self, block_idx: usize) -> Option<usize> {
         if let Some(hole_idx) = self.block(block_idx).first_hole_idx {
-            // We use the hole itself to store the index of the next hole. The value `usize::MAX` is
-            // used to indicate the absence of a next hole.
-            self.block_mut(block_idx).first_hole_idx = if self.code[hole_idx] == usize::MAX {
+            // We use the hole itself to store the index of the next hole. The value
+            // `InstrSlot::MAX` is used to indicate the absence of a next hole.
+            self.block_mut(block_idx).first_hole_idx = if self.code[hole_idx] == InstrSlot::MAX {
                 None
             } else {
-                Some(self.code[hole_idx])
+                Some(self.code[hole_idx] as usize)
             };
             Some(hole_idx)
         } else {
@@ -495,6 +519,12 @@ impl<'a> Compile<'a> {
             UnguardedVal::I64(val) => self.emit(val),
             UnguardedVal::F32(val) => self.emit(val),
             UnguardedVal::F64(val) => self.emit(val),
+            UnguardedVal::V128(val) => {
+                // A v128 value does not fit in a single instruction slot, so we emit it as two
+                // halves, starting with the low half.
+                self.emit(val as u64);
+                self.emit((val >> 64) as u64);
+            }
             UnguardedVal::FuncRef(val) => self.emit(val),
             UnguardedVal::ExternRef(val) => self.emit(val),
         }
@@ -534,7 +564,32 @@ impl<'a> Compile<'a> {
     /// Patches the hole with the given index with the offset of the current instruction.
     fn patch_hole(&mut self, hole_idx: usize) {
         self.fixup_idxs.push(hole_idx);
-        self.code[hole_idx] = self.code.len() * mem::size_of::<usize>();
+        self.code[hole_idx] = (self.code.len() * mem::size_of::<InstrSlot>()) as InstrSlot;
+    }
+
+    /// Emits a `consume_fuel` instruction, which starts a new block for the purpose of fuel
+    /// metering. If profiling is enabled, the block is also recorded in the profile.
+    ///
+    /// We don't yet know how many instructions the block contains, so we emit a hole for the cost
+    /// of the block instead, which is patched when the next block starts.
+    fn emit_consume_fuel(&mut self) {
+        self.patch_fuel_cost();
+        if self.is_profiling {
+            self.emit(exec::consume_fuel_profiled as ThreadedInstr);
+        } else {
+            self.emit(exec::consume_fuel as ThreadedInstr);
+        }
+        self.fuel_cost_idx = Some(self.code.len());
+        self.emit(0usize);
+    }
+
+    /// Patches the hole for the cost of the current block with the number of instructions that
+    /// were compiled since the block started.
+    fn patch_fuel_cost(&mut self) {
+        if let Some(fuel_cost_idx) = self.fuel_cost_idx.take() {
+            self.code[fuel_cost_idx] = self.fuel_cost as InstrSlot;
+        }
+        self.fuel_cost = 0;
     }
 
     /// Emits the offset of the instruction with the given index.
@@ -542,6 +597,76 @@ impl<'a> Compile<'a> {
         self.fixup_idxs.push(self.code.len());
         self.emit(instr_idx * mem::size_of::<InstrSlot>());
     }
+
+    /// Emits the operands for a tail call to a function with the given type, and pops its
+    /// arguments from the stack.
+    ///
+    /// The arguments are moved over the parameters of the current function, after which the call
+    /// frame of the callee starts where the parameters of the current function start.
+    fn emit_tail_call_opds(&mut self, type_: &FuncType) {
+        let first_callee_stack_idx = self.temp_stack_idx(self.opds.len() - type_.params().len());
+        for _ in 0..type_.params().len() {
+            self.pop_opd();
+        }
+        self.emit_stack_offset(first_callee_stack_idx);
+        self.emit(type_.params().len());
+        self.emit_stack_offset(self.first_param_result_stack_idx);
+        self.emit_stack_offset(
+            self.first_param_result_stack_idx + type_.call_frame_size() as isize,
+        );
+    }
+
+    // Methods for memory accesses.
+
+    /// Prepares a memory access with the given [`MemArg`], the address of which is the operand at
+    /// the given depth.
+    ///
+    /// Load and store instructions always access the memory cached in the memory registers, which
+    /// is memory 0, so for any other memory we emit a `use_mem` instruction first. The memory
+    /// registers are restored by [`Self::finish_mem_access`].
+    ///
+    /// Load and store instructions also always take a 32-bit address. For 64-bit memories, we
+    /// therefore add the static offset to the address in advance, and wrap the result to a
+    /// 32-bit address. An address that does not fit in 32 bits is out of bounds, so the wrapping
+    /// traps in that case, which is why an immediate address is only wrapped in advance if it fits.
+    ///
+    /// Returns the static offset to emit for the memory access.
+    fn prepare_mem_access(&mut self, arg: MemArg, addr_depth: usize) -> u32 {
+        let mem = self.instance.mem(arg.mem_idx).unwrap();
+        if arg.mem_idx != 0 {
+            self.emit(exec::use_mem as ThreadedInstr);
+            self.emit(mem.to_unguarded(self.store.id()));
+        }
+        if !mem.type_(self.store).is_64 {
+            return arg.offset as u32;
+        }
+        let imm_addr = match self.opd(addr_depth).val {
+            Some(UnguardedVal::I64(addr)) => exec::wrap_addr64_val(addr as u64, arg.offset),
+            _ => None,
+        };
+        if let Some(addr) = imm_addr {
+            self.opd_mut(addr_depth).val = Some(UnguardedVal::I32(addr as i32));
+        } else {
+            self.ensure_opd_not_imm(addr_depth);
+            self.ensure_opd_not_local(addr_depth);
+            self.ensure_opd_not_reg(addr_depth);
+            self.emit(exec::wrap_addr64 as ThreadedInstr);
+            self.emit_stack_offset(self.opd_stack_idx(addr_depth));
+            self.emit(arg.offset);
+            self.emit_stack_offset(self.opd_stack_idx(addr_depth));
+        }
+        self.opd_mut(addr_depth).type_ = ValType::I32;
+        0
+    }
+
+    /// Finishes a memory access with the given [`MemArg`], by restoring the memory registers to
+    /// memory 0 if necessary.
+    fn finish_mem_access(&mut self, arg: MemArg) {
+        if arg.mem_idx != 0 {
+            self.emit(exec::use_mem as ThreadedInstr);
+            self.emit(self.instance.mem(0).unwrap().to_unguarded(self.store.id()));
+        }
+    }
 }
 
 impl<'a> InstrVisitor for Compile<'a> {
@@ -611,8 +736,15 @@ impl<'a> InstrVisitor for Compile<'a> {
             }
         }
 
+        let is_reachable = !self.block(0).is_unreachable;
+
         self.push_block(BlockKind::Loop, type_);
 
+        // The start of a loop is a branch target, so a new block starts here.
+        if is_reachable {
+            self.emit_consume_fuel();
+        }
+
         Ok(())
     }
 
@@ -680,6 +812,11 @@ impl<'a> InstrVisitor for Compile<'a> {
         // the start of the `else` block.
         self.block_mut(0).else_hole_idx = else_hole_idx;
 
+        // The start of an `if` block is only executed conditionally, so a new block starts here.
+        if else_hole_idx.is_some() {
+            self.emit_consume_fuel();
+        }
+
         Ok(())
     }
 
@@ -715,7 +852,8 @@ impl<'a> InstrVisitor for Compile<'a> {
         // Even if the rest of the `if` block is unreachable, we still need to patch the hole for
         // the the `else` block, because that could have been emitted before rest of the `if` block
         // became unreachable.
-        if let Some(else_hole_idx) = self.block_mut(0).else_hole_idx.take() {
+        let else_hole_idx = self.block_mut(0).else_hole_idx.take();
+        if let Some(else_hole_idx) = else_hole_idx {
             self.patch_hole(else_hole_idx);
         }
 
@@ -725,6 +863,11 @@ impl<'a> InstrVisitor for Compile<'a> {
         // Push the `else` block on the stack.
         self.push_block(BlockKind::Block, block.type_);
 
+        // The start of the `else` block is a branch target, so a new block starts here.
+        if else_hole_idx.is_some() {
+            self.emit_consume_fuel();
+        }
+
         // Copy the list of holes for the `if` block to that of the `else` block, so that they will
         // be patched when we reach the first instruction after the end of the `else` block.
         self.block_mut(0).first_hole_idx = block.first_hole_idx;
@@ -768,6 +911,12 @@ impl<'a> InstrVisitor for Compile<'a> {
             self.push_opd(result_type);
         }
 
+        // The first instruction after the end of a block is a branch target, so a new block starts
+        // here, unless this was the end of the function.
+        if !self.blocks.is_empty() && !self.block(0).is_unreachable {
+            self.emit_consume_fuel();
+        }
+
         Ok(())
     }
 
@@ -1057,6 +1206,96 @@ impl<'a> InstrVisitor for Compile<'a> {
         Ok(())
     }
 
+    /// Compiles a `return_call` instruction.
+    fn visit_return_call(&mut self, func_idx: u32) -> Result<(), Self::Error> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Obtain the [`Func`] for this instruction.
+        let func = self.instance.func(func_idx).unwrap();
+
+        // Host functions can't reuse our call frame, so we compile tail calls to them as a regular
+        // call followed by a return.
+        if let FuncEntity::Host(_) = func.0.as_ref(&self.store) {
+            self.visit_call(func_idx)?;
+            return self.visit_return();
+        }
+
+        // Obtain the type of the [`Func`].
+        let type_ = func.type_(&self.store).clone();
+
+        // The arguments are moved over our own parameters, so they need to be stored in
+        // temporaries.
+        for opd_depth in 0..type_.params().len() {
+            self.ensure_opd_not_imm(opd_depth);
+            self.ensure_opd_not_local(opd_depth);
+        }
+        self.preserve_all_regs();
+
+        self.emit(exec::compile_return_call as ThreadedInstr);
+        self.emit(func.0.to_unguarded(self.store.id()));
+        self.emit_tail_call_opds(&type_);
+
+        self.set_unreachable();
+
+        Ok(())
+    }
+
+    /// Compiles a `return_call_indirect` instruction.
+    fn visit_return_call_indirect(
+        &mut self,
+        table_idx: u32,
+        type_idx: u32,
+    ) -> Result<(), Self::Error> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Obtain the [`Table`] for this instruction.
+        let table = self.instance.table(table_idx).unwrap();
+
+        // Obtain the (interned) type of the elements in the [`Table`].
+        let interned_type = self.instance.type_(type_idx).unwrap();
+        let type_ = self.store.resolve_type(interned_type).clone();
+
+        self.ensure_opd_not_imm(0);
+
+        // The arguments are moved over our own parameters, so they need to be stored in
+        // temporaries.
+        for opd_depth in 1..type_.params().len() + 1 {
+            self.ensure_opd_not_imm(opd_depth);
+            self.ensure_opd_not_local(opd_depth);
+        }
+        self.preserve_all_regs();
+
+        self.emit(exec::return_call_indirect as ThreadedInstr);
+        self.emit_stack_offset(self.opd_stack_idx(0));
+        self.pop_opd();
+        self.emit(table.0.to_unguarded(self.store.id()));
+        self.emit(interned_type.to_unguarded(self.store.id()));
+        self.emit_tail_call_opds(&type_);
+
+        // If the callee turns out to be a host function, it is called with a call frame on top of
+        // our own instead.
+        let first_callee_stack_idx = self.first_temp_stack_idx + self.opds.len();
+        let last_callee_stack_idx = first_callee_stack_idx + type_.call_frame_size();
+        self.max_stack_height = self.max_stack_height.max(last_callee_stack_idx);
+        self.emit_stack_offset(last_callee_stack_idx as isize);
+        self.emit(type_.results().len());
+        self.emit(
+            self.instance
+                .mem(0)
+                .map(|mem| mem.0.to_unguarded(self.store.id())),
+        );
+
+        self.set_unreachable();
+
+        Ok(())
+    }
+
     // Reference instructions
 
     /// Compiles a `ref.null` instruction.
@@ -1151,6 +1390,35 @@ impl<'a> InstrVisitor for Compile<'a> {
 
         let type_ = type_.unwrap_or_else(|| self.opd(1).type_);
 
+        // v128 values are never stored in a register, so we only implement a select_v128_sss
+        // instruction, which writes its output to the stack.
+        if type_ == ValType::V128 {
+            for opd_depth in 0..3 {
+                self.ensure_opd_not_imm(opd_depth);
+                self.ensure_opd_not_reg(opd_depth);
+            }
+
+            // Emit the instruction.
+            self.emit(select_select(
+                type_,
+                self.opd(2).kind(),
+                self.opd(1).kind(),
+                self.opd(0).kind(),
+            ));
+
+            // Emit the inputs and pop them from the stack.
+            for _ in 0..3 {
+                self.emit_opd(0);
+                self.pop_opd();
+            }
+
+            // Push the output onto the stack and emit its stack offset.
+            self.push_opd(type_);
+            self.emit_stack_offset(self.opd_stack_idx(0));
+
+            return Ok(());
+        }
+
         // The `select` instruction does not have any _{sri}{sri}i variants.
         //
         // For instance, the following sequence of instructions:
@@ -1321,6 +1589,12 @@ impl<'a> InstrVisitor for Compile<'a> {
         // Obtain the type of the [`Global`].
         let val_type = global.type_(&self.store).val;
 
+        // There is no global_set_v128_i instruction, so we need to ensure that v128 operands are
+        // not immediate operands.
+        if val_type == ValType::V128 {
+            self.ensure_opd_not_imm(0);
+        }
+
         // Emit the instruction.
         self.emit(select_global_set(val_type, self.opd(0).kind()));
 
@@ -1614,13 +1888,17 @@ impl<'a> InstrVisitor for Compile<'a> {
             return Ok(());
         }
 
+        let static_offset = self.prepare_mem_access(arg, 0);
+
         // We compile load instructions by delegating to the code for compiling unary operations.
         // This works because load instructions are essentially unary operations with an extra
         // immediate operand.
         self.visit_un_op(info.op)?;
 
         // Emit the static offset.
-        self.emit(arg.offset);
+        self.emit(static_offset);
+
+        self.finish_mem_access(arg);
 
         Ok(())
     }
@@ -1632,52 +1910,59 @@ impl<'a> InstrVisitor for Compile<'a> {
             return Ok(());
         }
 
+        let static_offset = self.prepare_mem_access(arg, 1);
+
         // We compile store instructions by delegating to the code for compiling binary operations.
         // This works because store instructions are essentially binary operations with an extra
         // immediate operand.
         self.visit_bin_op(info.op)?;
 
         // Emit the static offset.
-        self.emit(arg.offset);
+        self.emit(static_offset);
+
+        self.finish_mem_access(arg);
 
         Ok(())
     }
 
-    /// Compiles a `memory.fill` instruction.
-    fn visit_memory_size(&mut self) -> Result<(), Self::Error> {
+    /// Compiles a `memory.size` instruction.
+    fn visit_memory_size(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
         // Skip this instruction if it is unreachable.
         if self.block(0).is_unreachable {
             return Ok(());
         }
 
         // Obtain the [`Mem`] for this instruction.
-        let mem = self.instance.mem(0).unwrap();
+        let mem = self.instance.mem(mem_idx).unwrap();
+
+        // Obtain the type of the [`Mem`].
+        let type_ = mem.type_(&self.store);
 
         // Emit the instruction.
-        //
-        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
-        // function item instead of a function pointer.
-        self.emit(exec::memory_size as ThreadedInstr);
+        self.emit(select_memory_size(type_));
 
         // Emit an unguarded handle to the [`Mem`].
         self.emit(mem.to_unguarded(self.store.id()));
 
         // Push the output onto the stack and emit its stack offset.
-        self.push_opd(ValType::I32);
+        self.push_opd(addr_type(type_));
         self.emit_stack_offset(self.opd_stack_idx(0));
 
         Ok(())
     }
 
     /// Compiles a `memory.grow` instruction.
-    fn visit_memory_grow(&mut self) -> Result<(), Self::Error> {
+    fn visit_memory_grow(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
         // Skip this instruction if it is unreachable.
         if self.block(0).is_unreachable {
             return Ok(());
         }
 
         // Obtain the [`Mem`] for this instruction.
-        let mem = self.instance.mem(0).unwrap();
+        let mem = self.instance.mem(mem_idx).unwrap();
+
+        // Obtain the type of the [`Mem`].
+        let type_ = mem.type_(&self.store);
 
         // This instruction has only one variant, which reads all its operands from the stack, so we
         // need to ensure that all operands are neither constant nor register operands.
@@ -1685,10 +1970,7 @@ impl<'a> InstrVisitor for Compile<'a> {
         self.ensure_opd_not_reg(0);
 
         // Emit the instruction.
-        //
-        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
-        // function item instead of a function pointer.
-        self.emit(exec::memory_grow as ThreadedInstr);
+        self.emit(select_memory_grow(type_));
 
         // Emit the input and pop it from the stack.
         self.emit_and_pop_opd();
@@ -1697,21 +1979,21 @@ impl<'a> InstrVisitor for Compile<'a> {
         self.emit(mem.to_unguarded(self.store.id()));
 
         // Push the output onto the stack and emit its stack offset.
-        self.push_opd(ValType::I32);
+        self.push_opd(addr_type(type_));
         self.emit_stack_offset(self.opd_stack_idx(0));
 
         Ok(())
     }
 
     /// Compiles a `memory.fill` instruction.
-    fn visit_memory_fill(&mut self) -> Result<(), Self::Error> {
+    fn visit_memory_fill(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
         // Skip this instruction if it is unreachable.
         if self.block(0).is_unreachable {
             return Ok(());
         }
 
         // Obtain the [`Mem`] for this instruction.
-        let mem = self.instance.mem(0).unwrap();
+        let mem = self.instance.mem(mem_idx).unwrap();
 
         // This instruction has only one variant, which reads all its operands from the stack, so we
         // need to ensure that all operands are neither constant nor register operands.
@@ -1721,10 +2003,7 @@ impl<'a> InstrVisitor for Compile<'a> {
         }
 
         // Emit the instruction.
-        //
-        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
-        // function item instead of a function pointer.
-        self.emit(exec::memory_fill as ThreadedInstr);
+        self.emit(select_memory_fill(mem.type_(&self.store)));
 
         // Emit the inputs and pop them from the stack.
         for _ in 0..3 {
@@ -1739,14 +2018,15 @@ impl<'a> InstrVisitor for Compile<'a> {
     }
 
     /// Compiles a `memory.copy` instruction.
-    fn visit_memory_copy(&mut self) -> Result<(), Self::Error> {
+    fn visit_memory_copy(&mut self, dst_mem_idx: u32, src_mem_idx: u32) -> Result<(), Self::Error> {
         // Skip this instruction if it is unreachable.
         if self.block(0).is_unreachable {
             return Ok(());
         }
 
-        // Obtain the [`Mem`] for this instruction.
-        let mem = self.instance.mem(0).unwrap();
+        // Obtain the destination and source [`Mem`] for this instruction.
+        let dst_mem = self.instance.mem(dst_mem_idx).unwrap();
+        let src_mem = self.instance.mem(src_mem_idx).unwrap();
 
         // This instruction has only one variant, which reads all its operands from the stack, so we
         // need to ensure that all operands are neither constant nor register operands.
@@ -1756,10 +2036,10 @@ impl<'a> InstrVisitor for Compile<'a> {
         }
 
         // Emit the instruction.
-        //
-        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
-        // function item instead of a function pointer.
-        self.emit(exec::memory_copy as ThreadedInstr);
+        self.emit(select_memory_copy(
+            dst_mem.type_(&self.store),
+            src_mem.type_(&self.store),
+        ));
 
         // Emit the inputs and pop them from the stack.
         for _ in 0..3 {
@@ -1767,21 +2047,22 @@ impl<'a> InstrVisitor for Compile<'a> {
             self.pop_opd();
         }
 
-        // Emit an unguarded handle to the [`Mem`].
-        self.emit(mem.to_unguarded(self.store.id()));
+        // Emit unguarded handles to the destination and source [`Mem`].
+        self.emit(dst_mem.to_unguarded(self.store.id()));
+        self.emit(src_mem.to_unguarded(self.store.id()));
 
         Ok(())
     }
 
     /// Compiles a `memory.init` instruction.
-    fn visit_memory_init(&mut self, data_idx: u32) -> Result<(), Self::Error> {
+    fn visit_memory_init(&mut self, mem_idx: u32, data_idx: u32) -> Result<(), Self::Error> {
         // Skip this instruction if it is unreachable.
         if self.block(0).is_unreachable {
             return Ok(());
         }
 
         // Obtain the destination [`Mem`] and source [`Data`] for this instruction.
-        let dst_mem = self.instance.mem(0).unwrap();
+        let dst_mem = self.instance.mem(mem_idx).unwrap();
         let src_data = self.instance.data(data_idx).unwrap();
 
         // This instruction has only one variant, which reads all its operands from the stack, so we
@@ -1792,10 +2073,7 @@ impl<'a> InstrVisitor for Compile<'a> {
         }
 
         // Emit the instruction.
-        //
-        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
-        // function item instead of a function pointer.
-        self.emit(exec::memory_init as ThreadedInstr);
+        self.emit(select_memory_init(dst_mem.type_(&self.store)));
 
         // Emit the inputs and pop them from the stack.
         for _ in 0..3 {
@@ -2049,6 +2327,88 @@ impl<'a> InstrVisitor for Compile<'a> {
 
         Ok(())
     }
+
+    // Vector instructions
+
+    /// Compiles a `v128.const` instruction.
+    fn visit_v128_const(&mut self, val: u128) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Push the output onto the stack and set its value.
+        //
+        // Setting its value will mark the operand as a immediate operand.
+        self.push_opd(ValType::V128);
+        self.opd_mut(0).val = Some(UnguardedVal::V128(val));
+
+        Ok(())
+    }
+
+    /// Compiles a SIMD operation.
+    fn visit_simd_op(&mut self, info: SimdOpInfo) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // The first input of a SIMD memory instruction is its address.
+        let mem_arg = match info.imm {
+            SimdImm::MemArg { arg, .. } | SimdImm::MemArgLane { arg, .. } => Some(arg),
+            _ => None,
+        };
+        let static_offset =
+            mem_arg.map(|arg| self.prepare_mem_access(arg, info.input_types.len() - 1));
+
+        // SIMD operations only have a single variant, which reads all its inputs from the stack
+        // and writes its output to the stack. This keeps the number of instructions manageable,
+        // and v128 values can't be stored in a register anyway.
+        //
+        // We therefore ensure that none of the inputs are immediate or register operands.
+        for opd_depth in 0..info.input_types.len() {
+            self.ensure_opd_not_imm(opd_depth);
+            self.ensure_opd_not_reg(opd_depth);
+        }
+
+        // Emit the instruction.
+        self.emit(info.instr);
+
+        // Emit the inputs and pop them from the stack.
+        for _ in 0..info.input_types.len() {
+            self.emit_opd(0);
+            self.pop_opd();
+        }
+
+        // Emit the immediates.
+        match info.imm {
+            SimdImm::None => {}
+            SimdImm::Lane { lane_idx, .. } => self.emit(lane_idx),
+            SimdImm::Shuffle(lane_idxs) => {
+                let lane_idxs = u128::from_le_bytes(lane_idxs);
+                self.emit(lane_idxs as u64);
+                self.emit((lane_idxs >> 64) as u64);
+            }
+            SimdImm::MemArg { .. } => self.emit(static_offset.unwrap()),
+            SimdImm::MemArgLane { lane_idx, .. } => {
+                self.emit(static_offset.unwrap());
+                self.emit(lane_idx);
+            }
+        }
+
+        // If the operation has an output, push the output onto the stack and emit its stack
+        // offset.
+        if let Some(output_type) = info.output_type {
+            self.push_opd(output_type);
+            self.emit_stack_offset(self.opd_stack_idx(0));
+        }
+
+        if let Some(arg) = mem_arg {
+            self.finish_mem_access(arg);
+        }
+
+        Ok(())
+    }
 }
 
 /// A local on the stack.
@@ -2226,6 +2586,15 @@ enum OpdKind {
     Imm,
 }
 
+/// Returns the type of the addresses of a memory with the given [`MemType`].
+fn addr_type(type_: MemType) -> ValType {
+    if type_.is_64 {
+        ValType::I64
+    } else {
+        ValType::I32
+    }
+}
+
 // Instruction selection
 //
 // Most instructions come in multiple variants, depending on the types of their operands, and
@@ -2283,6 +2652,8 @@ fn select_select(
     kind_2: OpdKind,
 ) -> ThreadedInstr {
     match (type_, kind_0, kind_1, kind_2) {
+        (ValType::V128, OpdKind::Stack, OpdKind::Stack, OpdKind::Stack) => exec::select_v128_sss,
+        (ValType::V128, _, _, _) => panic!("no suitable instruction found"),
         (ValType::I32, OpdKind::Stack, OpdKind::Stack, OpdKind::Stack) => exec::select_i32_sss,
         (ValType::I32, OpdKind::Reg, OpdKind::Stack, OpdKind::Stack) => exec::select_i32_rss,
         (ValType::I32, OpdKind::Imm, OpdKind::Stack, OpdKind::Stack) => exec::select_i32_iss,
@@ -2434,6 +2805,7 @@ fn select_global_get(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::global_get_i64,
         ValType::F32 => exec::global_get_f32,
         ValType::F64 => exec::global_get_f64,
+        ValType::V128 => exec::global_get_v128,
         ValType::FuncRef => exec::global_get_func_ref,
         ValType::ExternRef => exec::global_get_extern_ref,
     }
@@ -2453,6 +2825,8 @@ fn select_global_set(type_: ValType, kind: OpdKind) -> ThreadedInstr {
         (ValType::F64, OpdKind::Stack) => exec::global_set_f64_s,
         (ValType::F64, OpdKind::Reg) => exec::global_set_f64_r,
         (ValType::F64, OpdKind::Imm) => exec::global_set_f64_i,
+        (ValType::V128, OpdKind::Stack) => exec::global_set_v128_s,
+        (ValType::V128, OpdKind::Reg | OpdKind::Imm) => panic!("no suitable instruction found"),
         (ValType::FuncRef, OpdKind::Stack) => exec::global_set_func_ref_s,
         (ValType::FuncRef, OpdKind::Reg) => exec::global_set_func_ref_r,
         (ValType::FuncRef, OpdKind::Imm) => exec::global_set_func_ref_i,
@@ -2545,6 +2919,47 @@ fn select_elem_drop(type_: RefType) -> ThreadedInstr {
     }
 }
 
+fn select_memory_size(type_: MemType) -> ThreadedInstr {
+    if type_.is_64 {
+        exec::memory_size_i64
+    } else {
+        exec::memory_size_i32
+    }
+}
+
+fn select_memory_grow(type_: MemType) -> ThreadedInstr {
+    if type_.is_64 {
+        exec::memory_grow_i64
+    } else {
+        exec::memory_grow_i32
+    }
+}
+
+fn select_memory_fill(type_: MemType) -> ThreadedInstr {
+    if type_.is_64 {
+        exec::memory_fill_i64
+    } else {
+        exec::memory_fill_i32
+    }
+}
+
+fn select_memory_copy(dst_type: MemType, src_type: MemType) -> ThreadedInstr {
+    match (dst_type.is_64, src_type.is_64) {
+        (false, false) => exec::memory_copy_i32_i32,
+        (false, true) => exec::memory_copy_i32_i64,
+        (true, false) => exec::memory_copy_i64_i32,
+        (true, true) => exec::memory_copy_i64_i64,
+    }
+}
+
+fn select_memory_init(type_: MemType) -> ThreadedInstr {
+    if type_.is_64 {
+        exec::memory_init_i64
+    } else {
+        exec::memory_init_i32
+    }
+}
+
 fn select_un_op(info: UnOpInfo, kind: OpdKind) -> ThreadedInstr {
     match kind {
         OpdKind::Stack => Some(info.instr_s),
@@ -2583,6 +2998,7 @@ fn select_copy_imm_to_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_imm_to_stack_i64,
         ValType::F32 => exec::copy_imm_to_stack_f32,
         ValType::F64 => exec::copy_imm_to_stack_f64,
+        ValType::V128 => exec::copy_imm_to_stack_v128,
         ValType::FuncRef => exec::copy_imm_to_stack_func_ref,
         ValType::ExternRef => exec::copy_imm_to_stack_extern_ref,
     }
@@ -2594,6 +3010,7 @@ fn select_copy_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_stack_i64,
         ValType::F32 => exec::copy_stack_f32,
         ValType::F64 => exec::copy_stack_f64,
+        ValType::V128 => exec::copy_stack_v128,
         ValType::FuncRef => exec::copy_stack_func_ref,
         ValType::ExternRef => exec::copy_stack_extern_ref,
     }
@@ -2605,6 +3022,7 @@ fn select_copy_reg_to_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_reg_to_stack_i64,
         ValType::F32 => exec::copy_reg_to_stack_f32,
         ValType::F64 => exec::copy_reg_to_stack_f64,
+        ValType::V128 => panic!("no suitable instruction found"),
         ValType::FuncRef => exec::copy_reg_to_stack_func_ref,
         ValType::ExternRef => exec::copy_reg_to_stack_extern_ref,
     }
@@ -4,7 +4,7 @@ pub(crate) const MAX_FUNC_COUNT: usize = 1_000_000;
 pub(crate) const MAX_FUNC_PARAM_COUNT: usize = 1_000;
 pub(crate) const MAX_FUNC_RESULT_COUNT: usize = 1_000;
 pub(crate) const MAX_TABLE_COUNT: usize = 100;
-pub(crate) const MAX_MEMORY_COUNT: usize = 1;
+pub(crate) const MAX_MEMORY_COUNT: usize = 100;
 pub(crate) const MAX_GLOBAL_COUNT: usize = 1_000_000;
 pub(crate) const MAX_EXPORT_COUNT: usize = 100_000;
 pub(crate) const MAX_ELEM_COUNT: usize = 100_000;
@@ -17,6 +17,15 @@ impl Data {
         self.0.as_mut(store).drop_bytes();
     }
 
+    pub(crate) fn is_dropped(self, store: &Store) -> bool {
+        self.0.as_ref(store).is_dropped
+    }
+
+    /// Marks this [`Data`] as dropped or not dropped, restoring its bytes in the latter case.
+    pub(crate) fn set_dropped(self, store: &mut Store, is_dropped: bool) {
+        self.0.as_mut(store).is_dropped = is_dropped;
+    }
+
     pub(crate) unsafe fn from_unguarded(data: UnguardedData, store_id: StoreId) -> Self {
         Self(Handle::from_unguarded(data, store_id))
     }
@@ -32,19 +41,29 @@ pub(crate) type UnguardedData = UnguardedHandle<DataEntity>;
 /// The representation of a [`Data`] in the store.
 #[derive(Debug)]
 pub(crate) struct DataEntity {
-    bytes: Option<Arc<[u8]>>,
+    // The bytes are kept alive after the segment is dropped, so that a snapshot from before the
+    // drop can be restored.
+    bytes: Arc<[u8]>,
+    is_dropped: bool,
 }
 
 impl DataEntity {
     fn new(bytes: Arc<[u8]>) -> Self {
-        Self { bytes: Some(bytes) }
+        Self {
+            bytes,
+            is_dropped: false,
+        }
     }
 
     pub(crate) fn bytes(&self) -> &[u8] {
-        self.bytes.as_ref().map_or(&[], |bytes| &bytes)
+        if self.is_dropped {
+            &[]
+        } else {
+            &self.bytes
+        }
     }
 
     pub(crate) fn drop_bytes(&mut self) {
-        self.bytes = None;
+        self.is_dropped = true;
     }
 }
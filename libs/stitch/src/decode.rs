@@ -3,12 +3,29 @@ use std::{error::Error, fmt, marker::PhantomData, str, sync::Arc};
 #[derive(Clone, Debug)]
 pub(crate) struct Decoder<'a> {
     bytes: &'a [u8],
+    base_offset: usize,
     position: usize,
 }
 
 impl<'a> Decoder<'a> {
     pub(crate) fn new(bytes: &'a [u8]) -> Self {
-        Self { bytes, position: 0 }
+        Self::with_offset(bytes, 0)
+    }
+
+    /// Creates a new [`Decoder`] for the given bytes, which start at the given offset in the
+    /// module they were taken from.
+    pub(crate) fn with_offset(bytes: &'a [u8], base_offset: usize) -> Self {
+        Self {
+            bytes,
+            base_offset,
+            position: 0,
+        }
+    }
+
+    /// Returns the offset of the current position of this [`Decoder`] in the module.
+    #[inline]
+    pub(crate) fn offset(&self) -> usize {
+        self.base_offset + self.position
     }
 
     #[inline]
@@ -71,7 +88,11 @@ impl<'a> Decoder<'a> {
 
     pub(crate) fn decode_decoder(&mut self) -> Result<Decoder<'a>, DecodeError> {
         let count: u32 = self.decode()?;
-        Ok(Decoder::new(self.read_bytes(count as usize)?))
+        let base_offset = self.offset();
+        Ok(Decoder::with_offset(
+            self.read_bytes(count as usize)?,
+            base_offset,
+        ))
     }
 
     pub(crate) fn decode<T>(&mut self) -> Result<T, DecodeError>
@@ -175,6 +196,35 @@ impl Decode for u32 {
     }
 }
 
+impl Decode for u64 {
+    #[inline]
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        fn decode_u64_tail(decoder: &mut Decoder<'_>, mut val: u64) -> Result<u64, DecodeError> {
+            let mut shift = 7;
+            loop {
+                let byte = decoder.read_byte()?;
+                if shift >= 57 && byte >> (64 - shift) != 0 {
+                    return Err(DecodeError::new("malformed u64"));
+                }
+                val |= ((byte & 0x7F) as u64) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+            }
+            Ok(val)
+        }
+
+        let byte = decoder.read_byte()?;
+        let val = (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            Ok(val)
+        } else {
+            decode_u64_tail(decoder, val)
+        }
+    }
+}
+
 impl Decode for i64 {
     #[inline]
     fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
@@ -233,6 +283,15 @@ impl Decode for f64 {
     }
 }
 
+impl Decode for u128 {
+    #[inline]
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        Ok(Self::from_le_bytes(
+            decoder.read_bytes(16)?.try_into().unwrap(),
+        ))
+    }
+}
+
 impl Decode for Arc<[u8]> {
     fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
         Ok(decoder.decode_bytes()?.into())
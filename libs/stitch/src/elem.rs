@@ -16,9 +16,11 @@ impl Elem {
         Self(store.insert_elem(match elems {
             UnguardedElems::FuncRef(elems) => ElemEntity::FuncRef(ElemEntityT {
                 elems: elems.into(),
+                is_dropped: false,
             }),
             UnguardedElems::ExternRef(elems) => ElemEntity::ExternRef(ElemEntityT {
                 elems: elems.into(),
+                is_dropped: false,
             }),
         }))
     }
@@ -37,6 +39,21 @@ impl Elem {
         }
     }
 
+    pub(crate) fn is_dropped(self, store: &Store) -> bool {
+        match self.0.as_ref(store) {
+            ElemEntity::FuncRef(elem) => elem.is_dropped,
+            ElemEntity::ExternRef(elem) => elem.is_dropped,
+        }
+    }
+
+    /// Marks this [`Elem`] as dropped or not dropped, restoring its elements in the latter case.
+    pub(crate) fn set_dropped(self, store: &mut Store, is_dropped: bool) {
+        match self.0.as_mut(store) {
+            ElemEntity::FuncRef(elem) => elem.is_dropped = is_dropped,
+            ElemEntity::ExternRef(elem) => elem.is_dropped = is_dropped,
+        }
+    }
+
     pub(crate) unsafe fn from_unguarded(elem: UnguardedElem, store_id: StoreId) -> Self {
         Self(Handle::from_unguarded(elem, store_id))
     }
@@ -78,16 +95,23 @@ impl ElemEntity {
 
 #[derive(Debug)]
 pub(crate) struct ElemEntityT<T> {
+    // The elements are kept alive after the segment is dropped, so that a snapshot from before the
+    // drop can be restored.
     elems: Box<[T]>,
+    is_dropped: bool,
 }
 
 impl<T> ElemEntityT<T> {
     pub(crate) fn elems(&self) -> &[T] {
-        &self.elems
+        if self.is_dropped {
+            &[]
+        } else {
+            &self.elems
+        }
     }
 
     pub(crate) fn drop_elems(&mut self) {
-        self.elems = Box::new([]);
+        self.is_dropped = true;
     }
 }
 
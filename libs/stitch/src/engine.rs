@@ -21,14 +21,41 @@ pub struct Engine {
 impl Engine {
     /// Creates a new [`Engine`].
     pub fn new() -> Engine {
+        Engine::new_with_profile_interval(None)
+    }
+
+    /// Creates a new [`Engine`] with profiling enabled.
+    ///
+    /// Every [`Store`] that uses this [`Engine`] records a [`Profile`](crate::Profile) of the Wasm
+    /// functions it executes, which samples the call stack every `sample_interval` Wasm
+    /// instructions. A `sample_interval` of 1 samples the call stack at the start of every block of
+    /// instructions. Functions are compiled with extra instrumentation, which makes them
+    /// significantly slower.
+    ///
+    /// # Panics
+    ///
+    /// If `sample_interval` is 0.
+    pub fn with_profiling(sample_interval: u64) -> Engine {
+        assert!(sample_interval > 0, "sample interval must be non-zero");
+        Engine::new_with_profile_interval(Some(sample_interval))
+    }
+
+    fn new_with_profile_interval(profile_interval: Option<u64>) -> Engine {
         Engine {
             inner: Arc::new(EngineInner {
                 validators: Mutex::new(Pool::new()),
                 compilers: Mutex::new(Pool::new()),
+                profile_interval,
             }),
         }
     }
 
+    /// Returns the interval at which the call stack is sampled if profiling is enabled for this
+    /// [`Engine`], or `None` otherwise.
+    pub(crate) fn profile_interval(&self) -> Option<u64> {
+        self.inner.profile_interval
+    }
+
     pub(crate) fn validate(
         &self,
         type_: &FuncType,
@@ -65,6 +92,7 @@ impl Default for Engine {
 struct EngineInner {
     validators: Mutex<Pool<Validator>>,
     compilers: Mutex<Pool<Compiler>>,
+    profile_interval: Option<u64>,
 }
 
 #[derive(Debug)]
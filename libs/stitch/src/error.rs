@@ -1,7 +1,12 @@
 use {
     crate::{
-        decode::DecodeError, func::FuncError, global::GlobalError, linker::InstantiateError,
-        mem::MemError, table::TableError, trap::Trap,
+        decode::DecodeError,
+        func::FuncError,
+        global::GlobalError,
+        linker::InstantiateError,
+        mem::MemError,
+        table::TableError,
+        trap::{Backtrace, Trap},
     },
     std::{error, fmt},
 };
@@ -15,7 +20,10 @@ pub enum Error {
     Table(TableError),
     Memory(MemError),
     Global(GlobalError),
-    Trap(Trap),
+    /// A trap, together with a backtrace of the Wasm functions that were executing when it
+    /// occurred. The backtrace is empty if the trap did not occur while executing Wasm code.
+    Trap(Trap, Backtrace),
+    Exit(i32),
 }
 
 impl error::Error for Error {
@@ -27,7 +35,8 @@ impl error::Error for Error {
             Error::Table(error) => Some(error),
             Error::Memory(error) => Some(error),
             Error::Global(error) => Some(error),
-            Error::Trap(error) => Some(error),
+            Error::Trap(error, _) => Some(error),
+            Error::Exit(_) => None,
         }
     }
 }
@@ -41,7 +50,8 @@ impl fmt::Display for Error {
             Error::Table(_) => write!(f, "table error"),
             Error::Memory(_) => write!(f, "memory error"),
             Error::Global(_) => write!(f, "global error"),
-            Error::Trap(_) => write!(f, "trap"),
+            Error::Trap(..) => write!(f, "trap"),
+            Error::Exit(code) => write!(f, "exit with code {}", code),
         }
     }
 }
@@ -84,6 +94,6 @@ impl From<GlobalError> for Error {
 
 impl From<Trap> for Error {
     fn from(trap: Trap) -> Self {
-        Error::Trap(trap)
+        Error::Trap(trap, Backtrace::default())
     }
 }
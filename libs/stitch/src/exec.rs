@@ -16,7 +16,7 @@ use {
         stack::{Stack, StackGuard, StackSlot},
         store::{Handle, Store, UnguardedInternedFuncType},
         table::UnguardedTable,
-        trap::Trap,
+        trap::{Backtrace, Trap},
         val::{UnguardedVal, Val},
     },
     std::{hint, mem, ptr},
@@ -40,6 +40,11 @@ use {
 /// On 64-bit Windows platforms, the "C" ABI corresponds to the "win64" ABI. This ABI allows only
 /// the first 4 arguments to be passed in hardware registers, regardless of their type. This is
 /// insufficient for our needs, so on Windows platforms, we use the "sysv64" ABI instead.
+///
+/// Where sibling call optimisation is not available, such as on 32-bit platforms, in unoptimised
+/// builds, or when running under Miri, `ThreadedInstr`s are executed in trampoline mode instead
+/// (see [`next_instr`]). Trampoline mode can also be enabled explicitly with the `trampoline`
+/// feature.
 
 #[cfg(not(windows))]
 pub(crate) type ThreadedInstr = unsafe extern "C" fn(
@@ -76,8 +81,10 @@ pub(crate) type Sp = *mut StackSlot;
 /// The memory data register (`Md`) stores a pointer to the start of the current [`Memory`].
 pub(crate) type Md = *mut u8;
 
-/// The memory size register (`Ms`) stores the size of the current [`Memory`].
-pub(crate) type Ms = u32;
+/// The memory size register (`Ms`) stores the size of the current [`Memory`] in bytes.
+///
+/// A memory can be exactly 4 GiB, so this does not fit in 32 bits.
+pub(crate) type Ms = u64;
 
 /// The integer register (`Ix`) stores temporary values of integral type.
 pub(crate) type Ix = u64;
@@ -110,6 +117,9 @@ pub(crate) struct Context<'a> {
     pub(crate) store: &'a mut Store,
     // A scoped lock to the stack for the current thread.
     pub(crate) stack: Option<StackGuard>,
+    // The start of the call frame of the function that was called from the host, where walking
+    // the call frames on the stack stops.
+    pub(crate) ptr: Sp,
     // Used to store out-of-band error data.
     pub(crate) error: Option<Error>,
 }
@@ -117,6 +127,7 @@ pub(crate) struct Context<'a> {
 /// Used to tell the interpreter what to do next.
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum ControlFlow {
+    Continue,
     Stop,
     Trap(Trap),
     Error,
@@ -127,6 +138,8 @@ impl ControlFlow {
     pub(crate) fn from_bits(bits: usize) -> Option<Self> {
         if bits == 0 {
             Some(Self::Stop)
+        } else if bits == 1 {
+            Some(Self::Continue)
         } else if bits & 0x03 == 2 {
             Trap::from_usize(bits >> 2).map(Self::Trap)
         } else if bits & 0x03 == 3 {
@@ -140,6 +153,7 @@ impl ControlFlow {
     pub(crate) fn to_bits(self) -> ControlFlowBits {
         match self {
             Self::Stop => 0,
+            Self::Continue => 1,
             Self::Trap(trap) => trap.to_usize() << 2 | 2,
             Self::Error => 3,
         }
@@ -149,6 +163,35 @@ impl ControlFlow {
 /// The raw bit representation of a `ControlFlow`.
 pub(crate) type ControlFlowBits = usize;
 
+/// The state of a function call that was suspended because it ran out of fuel or was interrupted.
+#[derive(Debug)]
+pub(crate) struct ExecState {
+    func: Func,
+    // Virtual registers
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    // The start of the call frame for the function.
+    ptr: *mut StackSlot,
+    // The trampoline that the call frame for the function returns to.
+    trampoline: Box<[InstrSlot; 4]>,
+    // The locked stack for the current thread.
+    stack: StackGuard,
+    // A pointer to the data of each memory in the store at the time of suspension.
+    mem_datas: Vec<*mut u8>,
+}
+
+impl ExecState {
+    /// Returns the [`Func`] for this [`ExecState`].
+    pub(crate) fn func(&self) -> Func {
+        self.func
+    }
+}
+
 /// Executes the given [`Func`] with the given arguments.
 ///
 /// The results are written to the `results` slice.
@@ -158,6 +201,9 @@ pub(crate) fn exec(
     args: &[Val],
     results: &mut [Val],
 ) -> Result<(), Error> {
+    // Cancel any suspended call, so that the stack for the current thread is unlocked.
+    store.cancel_suspended();
+
     // Lock the stack for the current thread.
     let mut stack = Stack::lock();
 
@@ -186,91 +232,227 @@ pub(crate) fn exec(
     // Store the start of the call frame so we can reset the stack to it later.
     let ptr = stack.ptr();
 
+    // If the function is a host function, it is not called from Wasm, so there is no caller
+    // memory.
+    store.set_caller_mem(None);
+
     match func.0.as_mut(store) {
-        FuncEntity::Wasm(func) => {
+        FuncEntity::Wasm(wasm_func) => {
             // Obtain the compiled code for this function.
-            let Code::Compiled(code) = func.code_mut() else {
+            let Code::Compiled(code) = wasm_func.code_mut() else {
                 unreachable!();
             };
 
-            // Create a trampoline for the [`WasmFuncEntity`].
-            let mut trampoline = [
-                call as InstrSlot,
+            // Create a trampoline for the [`WasmFuncEntity`]. The trampoline is boxed, because the
+            // call frame for the function refers to it, and the call might be suspended.
+            let mut trampoline = Box::new([
+                call as ThreadedInstr as usize as InstrSlot,
                 code.code.as_mut_ptr() as InstrSlot,
-                type_.call_frame_size() * mem::size_of::<StackSlot>(),
-                stop as InstrSlot,
-            ];
-
-            // Create an execution context.
-            let mut context = Context {
-                ip: trampoline.as_mut_ptr(),
-                sp: stack.ptr(),
-                md: ptr::null_mut(),
-                ms: 0,
-                ix: 0,
-                sx: 0.0,
-                dx: 0.0,
+                (type_.call_frame_size() * mem::size_of::<StackSlot>()) as InstrSlot,
+                stop as ThreadedInstr as usize as InstrSlot,
+            ]);
+
+            run(
                 store,
-                stack: Some(stack),
-                error: None,
-            };
+                ExecState {
+                    func,
+                    ip: trampoline.as_mut_ptr(),
+                    sp: ptr,
+                    md: ptr::null_mut(),
+                    ms: 0,
+                    ix: 0,
+                    sx: 0.0,
+                    dx: 0.0,
+                    ptr,
+                    trampoline,
+                    stack,
+                    mem_datas: Vec::new(),
+                },
+                results,
+            )
+        }
+        FuncEntity::Host(func) => {
+            // Set the stack pointer to the end of the call frame, and store the registers of the
+            // (non-existent) caller, so the call frames can be walked if the host function grows a
+            // memory.
+            unsafe {
+                let new_ptr = ptr.add(type_.call_frame_size());
+                *new_ptr.offset(-4).cast::<Ip>() = ptr::null_mut();
+                *new_ptr.offset(-3).cast::<Sp>() = ptr;
+                *new_ptr.offset(-2).cast::<Md>() = ptr::null_mut();
+                *new_ptr.offset(-1).cast::<Ms>() = 0;
+                stack.set_ptr(new_ptr);
+            }
 
-            // Main interpreter loop
-            loop {
-                match ControlFlow::from_bits(unsafe {
-                    next_instr(
-                        context.ip,
-                        context.sp,
-                        context.md,
-                        context.ms,
-                        context.ix,
-                        context.sx,
-                        context.dx,
-                        &mut context as *mut _,
-                    )
-                })
-                .unwrap()
-                {
-                    ControlFlow::Stop => {
-                        stack = context.stack.take().unwrap();
+            // Call the [`HostTrampoline`] of the [`HostFuncEntity`].
+            stack = func.trampoline().clone().call(store, stack)?;
+
+            // Reset the stack to the start of the call frame.
+            stack.set_ptr(ptr);
 
-                        // Reset the stack to the start of the call frame.
-                        stack.set_ptr(ptr);
+            copy_results(store, &mut stack, results);
+            Ok(())
+        }
+    }
+}
+
+/// Resumes the given suspended function call.
+///
+/// The results are written to the `results` slice.
+pub(crate) fn resume(
+    store: &mut Store,
+    mut state: ExecState,
+    results: &mut [Val],
+) -> Result<(), Error> {
+    // The memories in the store might have been grown while the call was suspended, so update the
+    // memory registers of the suspended call and its call frames to match.
+    for (idx, old_data) in mem::take(&mut state.mem_datas).into_iter().enumerate() {
+        let (new_data, new_size) = store.mem_data(idx);
+        if state.md == old_data {
+            state.md = new_data;
+            state.ms = new_size;
+        }
+        let mut sp = state.sp;
+        while sp != state.ptr {
+            unsafe {
+                if *sp.offset(-2).cast::<*mut u8>() == old_data {
+                    *sp.offset(-2).cast() = new_data;
+                    *sp.offset(-1).cast() = new_size;
+                }
+                sp = *sp.offset(-3).cast();
+            }
+        }
+    }
+    run(store, state, results)
+}
 
-                        break;
-                    }
-                    ControlFlow::Trap(trap) => {
-                        stack = context.stack.take().unwrap();
+/// Runs the main interpreter loop for the given function call, until it either returns, traps, or
+/// is suspended.
+///
+/// The results are written to the `results` slice.
+fn run(store: &mut Store, state: ExecState, results: &mut [Val]) -> Result<(), Error> {
+    let ExecState {
+        func,
+        ptr,
+        trampoline,
+        ..
+    } = state;
+
+    // Make sure that the sibling calls between threaded instructions don't overflow the stack.
+    check_sibling_call_optimisation();
+
+    // Create an execution context.
+    let mut context = Context {
+        ip: state.ip,
+        sp: state.sp,
+        md: state.md,
+        ms: state.ms,
+        ix: state.ix,
+        sx: state.sx,
+        dx: state.dx,
+        store,
+        stack: Some(state.stack),
+        ptr,
+        error: None,
+    };
 
-                        // Reset the stack to the start of the call frame.
-                        stack.set_ptr(ptr);
+    // Main interpreter loop
+    let mut stack = loop {
+        match ControlFlow::from_bits(unsafe {
+            exec_instr(
+                context.ip,
+                context.sp,
+                context.md,
+                context.ms,
+                context.ix,
+                context.sx,
+                context.dx,
+                &mut context as *mut _,
+            )
+        })
+        .unwrap()
+        {
+            ControlFlow::Continue => {}
+            ControlFlow::Stop => {
+                let mut stack = context.stack.take().unwrap();
+
+                // Reset the stack to the start of the call frame.
+                stack.set_ptr(ptr);
+
+                break stack;
+            }
+            ControlFlow::Trap(trap @ (Trap::OutOfFuel | Trap::Interrupted)) => {
+                // The instruction pointer points to the start of the instruction that trapped, so
+                // that execution can be resumed from it.
+                let backtrace = unsafe { backtrace(context.store, context.ip, context.sp, ptr) };
+
+                // Keep the stack locked, and store the state of the call in the store so that it
+                // can be resumed later.
+                let mem_datas = context.store.mem_datas();
+                context.store.suspend(ExecState {
+                    func,
+                    ip: context.ip,
+                    sp: context.sp,
+                    md: context.md,
+                    ms: context.ms,
+                    ix: context.ix,
+                    sx: context.sx,
+                    dx: context.dx,
+                    ptr,
+                    trampoline,
+                    stack: context.stack.take().unwrap(),
+                    mem_datas,
+                });
+                return Err(Error::Trap(trap, backtrace));
+            }
+            ControlFlow::Trap(trap) => {
+                // The instruction pointer points past the start of the instruction that trapped.
+                let backtrace =
+                    unsafe { backtrace(context.store, context.ip.offset(-1), context.sp, ptr) };
 
-                        return Err(trap)?;
-                    }
-                    ControlFlow::Error => {
-                        stack = context.stack.take().unwrap();
+                let mut stack = context.stack.take().unwrap();
 
-                        // Reset the stack to the start of the call frame.
-                        stack.set_ptr(ptr);
+                // Reset the stack to the start of the call frame.
+                stack.set_ptr(ptr);
 
-                        return Err(context.error.take().unwrap());
-                    }
-                }
+                return Err(Error::Trap(trap, backtrace));
             }
-        }
-        FuncEntity::Host(func) => {
-            // Set the stack pointer to the end of the call frame.
-            stack.set_ptr(unsafe { ptr.add(type_.call_frame_size()) });
+            ControlFlow::Error => {
+                // If the error came from a host function, the stack was unlocked by the host
+                // function, so we need to lock it again.
+                let mut stack = context.stack.take().unwrap_or_else(Stack::lock);
 
-            // Call the [`HostTrampoline`] of the [`HostFuncEntity`].
-            stack = func.trampoline().clone().call(store, stack)?;
+                // Reset the stack to the start of the call frame.
+                stack.set_ptr(ptr);
 
-            // Reset the stack to the start of the call frame.
-            stack.set_ptr(ptr);
+                return Err(context.error.take().unwrap());
+            }
         }
+    };
+
+    copy_results(context.store, &mut stack, results);
+    Ok(())
+}
+
+/// Creates a [`Backtrace`] for a trap that occurred at the given instruction pointer, with the
+/// given stack pointer, by walking the call frames on the stack until the one that starts at
+/// `ptr`.
+unsafe fn backtrace(store: &Store, ip: Ip, sp: Sp, ptr: Sp) -> Backtrace {
+    let mut frames = Vec::new();
+    let mut ip = ip;
+    let mut sp = sp;
+    while sp != ptr {
+        frames.extend(store.backtrace_frame(ip));
+
+        // The instruction pointer in a call frame points past the start of the call instruction.
+        ip = (*sp.offset(-4).cast::<Ip>()).offset(-1);
+        sp = *sp.offset(-3).cast();
     }
+    Backtrace::new(frames)
+}
 
-    // Copy the results from the stack.
+/// Copies the results of a function call from the stack.
+fn copy_results(store: &Store, stack: &mut Stack, results: &mut [Val]) {
     let mut ptr = stack.ptr();
     for result in results.iter_mut() {
         unsafe {
@@ -281,8 +463,6 @@ pub(crate) fn exec(
             ptr = ptr.add(1);
         }
     }
-
-    Ok(())
 }
 
 // Helper macros
@@ -339,10 +519,10 @@ macro_rules! threaded_instr {
 
 /// A helper macro for unwrapping a result or propagating its trap.
 macro_rules! r#try {
-    ($expr:expr) => {
+    ($ip:expr, $sp:expr, $cx:expr, $expr:expr) => {
         match $expr {
             Ok(val) => val,
-            Err(trap) => return ControlFlow::Trap(trap).to_bits(),
+            Err(trap) => return trap_at($ip, $sp, $cx, trap),
         }
     };
 }
@@ -350,16 +530,16 @@ macro_rules! r#try {
 // Control instructions
 
 threaded_instr!(unreachable(
-    _ip: Ip,
-    _sp: Sp,
+    ip: Ip,
+    sp: Sp,
     _md: Md,
     _ms: Ms,
     _ix: Ix,
     _sx: Sx,
     _dx: Dx,
-    _cx: Cx,
+    cx: Cx,
 ) -> ControlFlowBits {
-    ControlFlow::Trap(Trap::Unreachable).to_bits()
+    trap_at(ip, sp, cx, Trap::Unreachable)
 });
 
 threaded_instr!(br(
@@ -563,8 +743,8 @@ threaded_instr!(call(
 threaded_instr!(call_host(
     ip: Ip,
     sp: Sp,
-    _md: Md,
-    _ms: Ms,
+    md: Md,
+    ms: Ms,
     ix: Ix,
     sx: Sx,
     dx: Dx,
@@ -575,11 +755,19 @@ threaded_instr!(call_host(
     let (offset, ip) = read_imm(ip);
     let (mem, ip): (Option<UnguardedMem>, _) = read_imm(ip);
 
+    // Store call frame on stack, so it can be walked if the host function grows a memory.
+    let new_sp: Sp = sp.cast::<u8>().add(offset).cast();
+    *new_sp.offset(-4).cast() = ip;
+    *new_sp.offset(-3).cast() = sp;
+    *new_sp.offset(-2).cast() = md;
+    *new_sp.offset(-1).cast() = ms;
+
     let mut stack = (*cx).stack.take().unwrap_unchecked();
-    stack.set_ptr(sp.cast::<u8>().add(offset).cast());
+    stack.set_ptr(new_sp);
     let FuncEntity::Host(func) = func.as_ref() else {
         hint::unreachable_unchecked();
     };
+    (*(*cx).store).set_caller_mem(mem);
     let stack = match func.trampoline().clone().call((*cx).store, stack) {
         Ok(stack) => stack,
         Err(error) => {
@@ -595,7 +783,7 @@ threaded_instr!(call_host(
     if let Some(mut mem) = mem {
         let data = mem.as_mut().bytes_mut();
         md = data.as_mut_ptr();
-        ms = data.len() as u32;
+        ms = data.len() as u64;
     } else {
         md = ptr::null_mut();
         ms = 0;
@@ -622,20 +810,20 @@ threaded_instr!(call_indirect(
     let (stack_offset, ip) = read_imm(ip);
     let (mem, ip): (Option<UnguardedMem>, _) = read_imm(ip);
 
-    let func = r#try!(table
+    let func = r#try!(ip, sp, cx, table
         .as_ref()
         .downcast_ref::<UnguardedFuncRef>()
         .unwrap_unchecked()
         .get(table_offset)
         .ok_or(Trap::TableAccessOutOfBounds));
-    let mut func = r#try!(func.ok_or(Trap::ElemUninited));
+    let mut func = r#try!(ip, sp, cx, func.ok_or(Trap::ElemUninited));
     if func
         .as_ref()
         .type_()
         .to_unguarded((*(*cx).store).id())
         != type_
     {
-        return ControlFlow::Trap(Trap::TypeMismatch).to_bits();
+        return trap_at(ip, sp, cx, Trap::TypeMismatch);
     }
     Func(Handle::from_unguarded(func, (*(*cx).store).id())).compile(&mut *(*cx).store);
     match func.as_mut() {
@@ -658,8 +846,15 @@ threaded_instr!(call_indirect(
             next_instr(ip, sp, md, ms, ix, sx, dx, cx)
         }
         FuncEntity::Host(func) => {
+            let new_sp: Sp = sp.cast::<u8>().add(stack_offset).cast();
+            *new_sp.offset(-4).cast() = ip;
+            *new_sp.offset(-3).cast() = sp;
+            *new_sp.offset(-2).cast() = md;
+            *new_sp.offset(-1).cast() = ms;
+
             let mut stack = (*cx).stack.take().unwrap_unchecked();
-            stack.set_ptr(sp.cast::<u8>().add(stack_offset).cast());
+            stack.set_ptr(new_sp);
+            (*(*cx).store).set_caller_mem(mem);
             let stack = match func.trampoline().clone().call((*cx).store, stack) {
                 Ok(stack) => stack,
                 Err(error) => {
@@ -674,7 +869,7 @@ threaded_instr!(call_indirect(
             if let Some(mut mem) = mem {
                 let data = mem.as_mut().bytes_mut();
                 md = data.as_mut_ptr();
-                ms = data.len() as u32;
+                ms = data.len() as u64;
             } else {
                 md = ptr::null_mut();
                 ms = 0;
@@ -686,6 +881,159 @@ threaded_instr!(call_indirect(
     }
 });
 
+threaded_instr!(return_call(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands.
+    let (target, ip) = read_imm(ip);
+    let (src_offset, ip): (isize, _) = read_imm(ip);
+    let (count, ip): (usize, _) = read_imm(ip);
+    let (dst_offset, ip): (isize, _) = read_imm(ip);
+    let (new_sp_offset, _ip): (isize, _) = read_imm(ip);
+
+    // Replace our call frame with the one for the callee.
+    let sp = replace_call_frame(sp, src_offset, count, dst_offset, new_sp_offset);
+
+    // Branch to target.
+    let ip = target;
+
+    // Execute next instruction.
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(return_call_indirect(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (table_offset, ip): (u32, _) = read_stack(ip, sp);
+    let (table, ip): (UnguardedTable, _) = read_imm(ip);
+    let (type_, ip): (UnguardedInternedFuncType, _) = read_imm(ip);
+    let (src_offset, ip): (isize, _) = read_imm(ip);
+    let (count, ip): (usize, _) = read_imm(ip);
+    let (dst_offset, ip): (isize, _) = read_imm(ip);
+    let (new_sp_offset, ip): (isize, _) = read_imm(ip);
+    let (stack_offset, ip) = read_imm(ip);
+    let (result_count, ip): (usize, _) = read_imm(ip);
+    let (mem, ip): (Option<UnguardedMem>, _) = read_imm(ip);
+
+    let func = r#try!(ip, sp, cx, table
+        .as_ref()
+        .downcast_ref::<UnguardedFuncRef>()
+        .unwrap_unchecked()
+        .get(table_offset)
+        .ok_or(Trap::TableAccessOutOfBounds));
+    let mut func = r#try!(ip, sp, cx, func.ok_or(Trap::ElemUninited));
+    if func
+        .as_ref()
+        .type_()
+        .to_unguarded((*(*cx).store).id())
+        != type_
+    {
+        return trap_at(ip, sp, cx, Trap::TypeMismatch);
+    }
+    Func(Handle::from_unguarded(func, (*(*cx).store).id())).compile(&mut *(*cx).store);
+    match func.as_mut() {
+        FuncEntity::Wasm(func) => {
+            let Code::Compiled(code) = func.code_mut() else {
+                hint::unreachable_unchecked();
+            };
+            let target = code.code.as_mut_ptr();
+
+            let sp = replace_call_frame(sp, src_offset, count, dst_offset, new_sp_offset);
+
+            let ip = target;
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        }
+        FuncEntity::Host(func) => {
+            // Host functions can't reuse our call frame, so we call them as usual, and then return
+            // their results as our own.
+            let new_sp: Sp = sp.cast::<u8>().add(stack_offset).cast();
+            *new_sp.offset(-4).cast() = ip;
+            *new_sp.offset(-3).cast() = sp;
+            *new_sp.offset(-2).cast() = md;
+            *new_sp.offset(-1).cast() = ms;
+
+            let mut stack = (*cx).stack.take().unwrap_unchecked();
+            stack.set_ptr(new_sp);
+            (*(*cx).store).set_caller_mem(mem);
+            let stack = match func.trampoline().clone().call((*cx).store, stack) {
+                Ok(stack) => stack,
+                Err(error) => {
+                    (*cx).error = Some(error);
+                    return ControlFlow::Error.to_bits();
+                }
+            };
+            (*cx).stack = Some(stack);
+
+            ptr::copy(
+                sp.cast::<u8>().offset(src_offset).cast::<StackSlot>(),
+                sp.cast::<u8>().offset(dst_offset).cast::<StackSlot>(),
+                result_count,
+            );
+
+            // Restore call frame from stack.
+            let old_sp = sp;
+            let ip = *old_sp.offset(-4).cast();
+            let sp = *old_sp.offset(-3).cast();
+            let md = *old_sp.offset(-2).cast();
+            let ms = *old_sp.offset(-1).cast();
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        }
+    }
+});
+
+/// Replaces the call frame of the current function with the one for the callee of a tail call.
+///
+/// The arguments for the callee are moved from the given source offset to the given destination
+/// offset, which is where the parameters of the current function start, after which the saved
+/// registers of the caller are moved to just below the new stack pointer.
+///
+/// Returns the new stack pointer.
+unsafe fn replace_call_frame(
+    sp: Sp,
+    src_offset: isize,
+    count: usize,
+    dst_offset: isize,
+    new_sp_offset: isize,
+) -> Sp {
+    // The arguments may overwrite the saved registers, so we need to read them first.
+    let ip: Ip = *sp.offset(-4).cast();
+    let caller_sp: Sp = *sp.offset(-3).cast();
+    let md: Md = *sp.offset(-2).cast();
+    let ms: Ms = *sp.offset(-1).cast();
+
+    ptr::copy(
+        sp.cast::<u8>().offset(src_offset).cast::<StackSlot>(),
+        sp.cast::<u8>().offset(dst_offset).cast::<StackSlot>(),
+        count,
+    );
+
+    let new_sp: Sp = sp.cast::<u8>().offset(new_sp_offset).cast();
+    *new_sp.offset(-4).cast() = ip;
+    *new_sp.offset(-3).cast() = caller_sp;
+    *new_sp.offset(-2).cast() = md;
+    *new_sp.offset(-1).cast() = ms;
+    new_sp
+}
+
 // Reference instructions
 
 macro_rules! ref_is_null {
@@ -1306,6 +1654,33 @@ select!(
     UnguardedExternRef
 );
 
+// v128 values are never stored in a register, so we only implement a select_v128_sss instruction,
+// which writes its output to the stack.
+threaded_instr!(select_v128_sss(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (cond, ip): (u32, _) = read_stack(ip, sp);
+    let (x1, ip): (u128, _) = read_stack(ip, sp);
+    let (x0, ip): (u128, _) = read_stack(ip, sp);
+
+    // Perform operation
+    let y = if cond != 0 { x0 } else { x1 };
+
+    // Write result
+    let ip = write_stack(ip, sp, y);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 // Variable instructions
 
 macro_rules! global_get {
@@ -1343,6 +1718,7 @@ global_get!(global_get_i32, i32);
 global_get!(global_get_i64, i64);
 global_get!(global_get_f32, f32);
 global_get!(global_get_f64, f64);
+global_get!(global_get_v128, u128);
 global_get!(global_get_func_ref, UnguardedFuncRef);
 global_get!(global_get_extern_ref, UnguardedExternRef);
 
@@ -1442,6 +1818,31 @@ global_set!(
     UnguardedExternRef
 );
 
+threaded_instr!(global_set_v128_s(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (val, ip): (u128, _) = read_stack(ip, sp);
+    let (mut global, ip): (UnguardedGlobal, _) = read_imm(ip);
+
+    // Perform operation
+    global
+        .as_mut()
+        .downcast_mut::<u128>()
+        .unwrap_unchecked()
+        .set(val);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 // Table instructions
 
 macro_rules! table_get {
@@ -1461,7 +1862,7 @@ macro_rules! table_get {
             let (table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            let val = r#try!(table
+            let val = r#try!(ip, sp, cx, table
                 .as_ref()
                 .downcast_ref::<$T>()
                 .unwrap_unchecked()
@@ -1490,7 +1891,7 @@ macro_rules! table_get {
             let (table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            let val = r#try!(table
+            let val = r#try!(ip, sp, cx, table
                 .as_ref()
                 .downcast_ref::<$T>()
                 .unwrap_unchecked()
@@ -1519,7 +1920,7 @@ macro_rules! table_get {
             let (table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            let val = r#try!(table
+            let val = r#try!(ip, sp, cx, table
                 .as_ref()
                 .downcast_ref::<$T>()
                 .unwrap_unchecked()
@@ -1576,7 +1977,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1603,7 +2004,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1630,7 +2031,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1657,7 +2058,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1684,7 +2085,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1711,7 +2112,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1738,7 +2139,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1765,7 +2166,7 @@ macro_rules! table_set {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1893,7 +2294,7 @@ macro_rules! table_fill {
             let (mut table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(table
+            r#try!(ip, sp, cx, table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -1928,7 +2329,7 @@ macro_rules! table_copy {
             let (src_table, ip): (UnguardedTable, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(if dst_table == src_table {
+            r#try!(ip, sp, cx, if dst_table == src_table {
                 dst_table
                     .as_mut()
                     .downcast_mut::<$T>()
@@ -1976,7 +2377,7 @@ macro_rules! table_init {
             let (src_elem, ip): (UnguardedElem, _) = read_imm(ip);
 
             // Perform operation
-            r#try!(dst_table
+            r#try!(ip, sp, cx, dst_table
                 .as_mut()
                 .downcast_mut::<$T>()
                 .unwrap_unchecked()
@@ -2044,8 +2445,8 @@ macro_rules! load {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$T>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let mut bytes = [0u8; mem::size_of::<$T>()];
             ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
@@ -2074,8 +2475,8 @@ macro_rules! load {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$T>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let mut bytes = [0u8; mem::size_of::<$T>()];
             ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
@@ -2104,8 +2505,8 @@ macro_rules! load {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$T>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let mut bytes = [0u8; mem::size_of::<$T>()];
             ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
@@ -2150,8 +2551,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2177,8 +2578,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2204,8 +2605,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2231,8 +2632,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2258,8 +2659,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2285,8 +2686,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2312,8 +2713,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2339,8 +2740,8 @@ macro_rules! store {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2387,8 +2788,8 @@ macro_rules! store_float {
 
             // Perform operation
             let offset = dyn_offset as u64 + static_offset as u64;
-            if offset + mem::size_of::<$U>() as u64 > ms as u64 {
-                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            if offset + mem::size_of::<$U>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
             }
             let bytes = (x as $U).to_le_bytes();
             ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
@@ -2524,30 +2925,7 @@ store!(
     u32
 );
 
-threaded_instr!(memory_size(
-    ip: Ip,
-    sp: Sp,
-    md: Md,
-    ms: Ms,
-    ix: Ix,
-    sx: Sx,
-    dx: Dx,
-    cx: Cx,
-) -> ControlFlowBits {
-    // Read operands
-    let (mem, ip): (UnguardedMem, _) = read_imm(ip);
-
-    // Perform operation
-    let size = mem.as_ref().size();
-
-    // Write result
-    let ip = write_stack(ip, sp, size);
-
-    // Execute next instruction
-    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
-});
-
-threaded_instr!(memory_grow(
+threaded_instr!(use_mem(
     ip: Ip,
     sp: Sp,
     _md: Md,
@@ -2558,27 +2936,18 @@ threaded_instr!(memory_grow(
     cx: Cx,
 ) -> ControlFlowBits {
     // Read operands
-    let (count, ip): (u32, _) = read_stack(ip, sp);
     let (mut mem, ip): (UnguardedMem, _) = read_imm(ip);
 
     // Perform operation
-    (*cx).stack.as_mut().unwrap_unchecked().set_ptr(sp);
-    let old_size = mem
-        .as_mut()
-        .grow_with_stack(count, (*cx).stack.as_mut().unwrap_unchecked())
-        .unwrap_or(u32::MAX);
     let bytes = mem.as_mut().bytes_mut();
     let md = bytes.as_mut_ptr();
-    let ms = bytes.len() as u32;
-
-    // Write result
-    let ip = write_stack(ip, sp, old_size);
+    let ms = bytes.len() as u64;
 
     // Execute next instruction
     next_instr(ip, sp, md, ms, ix, sx, dx, cx)
 });
 
-threaded_instr!(memory_fill(
+threaded_instr!(wrap_addr64(
     ip: Ip,
     sp: Sp,
     md: Md,
@@ -2589,66 +2958,215 @@ threaded_instr!(memory_fill(
     cx: Cx,
 ) -> ControlFlowBits {
     // Read operands
-    let (count, ip) = read_stack(ip, sp);
-    let (val, ip): (u32, _) = read_stack(ip, sp);
-    let (idx, ip) = read_stack(ip, sp);
-    let (mut mem, ip): (UnguardedMem, _) = read_imm(ip);
+    let (addr, ip): (u64, _) = read_stack(ip, sp);
+    let (static_offset, ip): (u64, _) = read_imm(ip);
 
     // Perform operation
-    r#try!(mem.as_mut().fill(idx, val as u8, count));
+    let Some(addr) = wrap_addr64_val(addr, static_offset) else {
+        return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
+    };
+
+    // Write result
+    let ip = write_stack(ip, sp, addr);
 
     // Execute next instruction
     next_instr(ip, sp, md, ms, ix, sx, dx, cx)
 });
 
-threaded_instr!(memory_copy(
-    ip: Ip,
-    sp: Sp,
-    md: Md,
-    ms: Ms,
-    ix: Ix,
-    sx: Sx,
-    dx: Dx,
-    cx: Cx,
-) -> ControlFlowBits {
-    // Read operands
-    let (count, ip): (u32, _) = read_stack(ip, sp);
-    let (src_idx, ip): (u32, _) = read_stack(ip, sp);
-    let (dst_idx, ip): (u32, _) = read_stack(ip, sp);
-    let (mut mem, ip): (UnguardedMem, _) = read_imm(ip);
+/// Adds the given static offset to the given 64-bit address, and wraps the result to a 32-bit
+/// address.
+///
+/// Memories are never larger than 4 GiB, so if the result does not fit in 32 bits, the access is
+/// out of bounds. In that case, we return `None`. There is no 32-bit address we could return
+/// instead, since every one of them is in bounds for a memory of exactly 4 GiB.
+pub(crate) fn wrap_addr64_val(addr: u64, static_offset: u64) -> Option<u32> {
+    addr.checked_add(static_offset)
+        .and_then(|addr| u32::try_from(addr).ok())
+}
 
-    // Perform operation
-    r#try!(mem.as_mut().copy_within(dst_idx, src_idx, count));
+macro_rules! memory_size {
+    ($memory_size:ident, $T:ty) => {
+        threaded_instr!($memory_size(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (mem, ip): (UnguardedMem, _) = read_imm(ip);
 
-    // Execute next instruction
-    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
-});
+            // Perform operation
+            let size = <$T>::from(mem.as_ref().size());
 
-threaded_instr!(memory_init(
-    ip: Ip,
-    sp: Sp,
-    md: Md,
-    ms: Ms,
-    ix: Ix,
-    sx: Sx,
-    dx: Dx,
-    cx: Cx,
-) -> ControlFlowBits {
-    // Read operands
-    let (count, ip): (u32, _) = read_stack(ip, sp);
-    let (src_idx, ip): (u32, _) = read_stack(ip, sp);
-    let (dst_idx, ip): (u32, _) = read_stack(ip, sp);
-    let (mut dst_mem, ip): (UnguardedMem, _) = read_imm(ip);
-    let (src_data, ip): (UnguardedData, _) = read_imm(ip);
+            // Write result
+            let ip = write_stack(ip, sp, size);
 
-    // Perform operation
-    r#try!(dst_mem
-        .as_mut()
-        .init(dst_idx, src_data.as_ref(), src_idx, count));
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
 
-    // Execute next instruction
-    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
-});
+memory_size!(memory_size_i32, u32);
+memory_size!(memory_size_i64, u64);
+
+macro_rules! memory_grow {
+    ($memory_grow:ident, $T:ty) => {
+        threaded_instr!($memory_grow(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (count, ip): ($T, _) = read_stack(ip, sp);
+            let (mut mem, ip): (UnguardedMem, _) = read_imm(ip);
+
+            // Perform operation
+            let old_data = mem.as_mut().bytes_mut().as_mut_ptr();
+            (*cx).stack.as_mut().unwrap_unchecked().set_ptr(sp);
+            let old_size = match u32::try_from(count) {
+                Ok(count) => mem
+                    .as_mut()
+                    .grow_with_stack(count, (*cx).stack.as_mut().unwrap_unchecked())
+                    .map_or(<$T>::MAX, <$T>::from),
+                Err(_) => <$T>::MAX,
+            };
+
+            // The memory registers only need to be updated if they refer to the memory that grew.
+            let (md, ms) = if md == old_data {
+                let bytes = mem.as_mut().bytes_mut();
+                (bytes.as_mut_ptr(), bytes.len() as u64)
+            } else {
+                (md, ms)
+            };
+
+            // Write result
+            let ip = write_stack(ip, sp, old_size);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+memory_grow!(memory_grow_i32, u32);
+memory_grow!(memory_grow_i64, u64);
+
+macro_rules! memory_fill {
+    ($memory_fill:ident, $T:ty) => {
+        threaded_instr!($memory_fill(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (count, ip): ($T, _) = read_stack(ip, sp);
+            let (val, ip): (u32, _) = read_stack(ip, sp);
+            let (idx, ip): ($T, _) = read_stack(ip, sp);
+            let (mut mem, ip): (UnguardedMem, _) = read_imm(ip);
+
+            // Perform operation
+            r#try!(ip, sp, cx, mem.as_mut().fill(idx.into(), val as u8, count.into()));
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+memory_fill!(memory_fill_i32, u32);
+memory_fill!(memory_fill_i64, u64);
+
+macro_rules! memory_copy {
+    ($memory_copy:ident, $D:ty, $S:ty, $N:ty) => {
+        threaded_instr!($memory_copy(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (count, ip): ($N, _) = read_stack(ip, sp);
+            let (src_idx, ip): ($S, _) = read_stack(ip, sp);
+            let (dst_idx, ip): ($D, _) = read_stack(ip, sp);
+            let (mut dst_mem, ip): (UnguardedMem, _) = read_imm(ip);
+            let (src_mem, ip): (UnguardedMem, _) = read_imm(ip);
+
+            // Perform operation
+            if dst_mem == src_mem {
+                r#try!(ip, sp, cx, dst_mem
+                    .as_mut()
+                    .copy_within(dst_idx.into(), src_idx.into(), count.into()));
+            } else {
+                r#try!(ip, sp, cx, dst_mem.as_mut().copy(
+                    dst_idx.into(),
+                    src_mem.as_ref(),
+                    src_idx.into(),
+                    count.into()
+                ));
+            }
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+memory_copy!(memory_copy_i32_i32, u32, u32, u32);
+memory_copy!(memory_copy_i32_i64, u32, u64, u32);
+memory_copy!(memory_copy_i64_i32, u64, u32, u32);
+memory_copy!(memory_copy_i64_i64, u64, u64, u64);
+
+macro_rules! memory_init {
+    ($memory_init:ident, $T:ty) => {
+        threaded_instr!($memory_init(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (count, ip): (u32, _) = read_stack(ip, sp);
+            let (src_idx, ip): (u32, _) = read_stack(ip, sp);
+            let (dst_idx, ip): ($T, _) = read_stack(ip, sp);
+            let (mut dst_mem, ip): (UnguardedMem, _) = read_imm(ip);
+            let (src_data, ip): (UnguardedData, _) = read_imm(ip);
+
+            // Perform operation
+            r#try!(ip, sp, cx, dst_mem
+                .as_mut()
+                .init(dst_idx.into(), src_data.as_ref(), src_idx, count));
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+memory_init!(memory_init_i32, u32);
+memory_init!(memory_init_i64, u64);
 
 threaded_instr!(data_drop(
     ip: Ip,
@@ -2688,7 +3206,7 @@ macro_rules! un_op {
             let (x, ip) = read_stack(ip, sp);
 
             // Perform operation
-            let y = r#try!($f(x));
+            let y = r#try!(ip, sp, cx, $f(x));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2711,7 +3229,7 @@ macro_rules! un_op {
             let x = read_reg(ix, sx, dx);
 
             // Perform operation
-            let y = r#try!($f(x));
+            let y = r#try!(ip, sp, cx, $f(x));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2745,7 +3263,7 @@ macro_rules! bin_op {
             let (x0, ip) = read_stack(ip, sp);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2769,7 +3287,7 @@ macro_rules! bin_op {
             let x0 = read_reg(ix, sx, dx);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2793,7 +3311,7 @@ macro_rules! bin_op {
             let (x0, ip) = read_imm(ip);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2817,7 +3335,7 @@ macro_rules! bin_op {
             let (x0, ip) = read_imm(ip);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2856,7 +3374,7 @@ macro_rules! bin_op_noncommutative {
             let (x0, ip) = read_stack(ip, sp);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2880,7 +3398,7 @@ macro_rules! bin_op_noncommutative {
             let (x0, ip) = read_stack(ip, sp);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -2904,7 +3422,7 @@ macro_rules! bin_op_noncommutative {
             let x0 = read_reg(ix, sx, dx);
 
             // Perform operation
-            let y = r#try!($f(x0, x1));
+            let y = r#try!(ip, sp, cx, $f(x0, x1));
 
             // Write result
             let (ix, sx, dx) = write_reg(ix, sx, dx, y);
@@ -3817,6 +4335,1300 @@ un_op!(
     <u64 as Trunc<f64>>::trunc_sat
 );
 
+// SIMD instructions
+//
+// Unlike the scalar instructions, SIMD instructions only come in a single variant, which reads its
+// inputs from the stack and writes its output to the stack.
+
+macro_rules! simd_un_op {
+    ($simd_un_op:ident, $T:ty, $f:expr) => {
+        threaded_instr!($simd_un_op(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip): ($T, _) = read_stack(ip, sp);
+
+            // Perform operation
+            let y = $f(x);
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_bin_op {
+    ($simd_bin_op:ident, $T0:ty, $T1:ty, $f:expr) => {
+        threaded_instr!($simd_bin_op(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x1, ip): ($T1, _) = read_stack(ip, sp);
+            let (x0, ip): ($T0, _) = read_stack(ip, sp);
+
+            // Perform operation
+            let y = $f(x0, x1);
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_extract_lane {
+    ($simd_extract_lane:ident, $f:expr) => {
+        threaded_instr!($simd_extract_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip): (u128, _) = read_stack(ip, sp);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let y = $f(x, lane_idx);
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_replace_lane {
+    ($simd_replace_lane:ident, $T:ty, $f:expr) => {
+        threaded_instr!($simd_replace_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x1, ip): ($T, _) = read_stack(ip, sp);
+            let (x0, ip): (u128, _) = read_stack(ip, sp);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let y: u128 = $f(x0, lane_idx, x1);
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_load {
+    ($simd_load:ident, $N:expr, $f:expr) => {
+        threaded_instr!($simd_load(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + $N > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
+            }
+            let mut bytes = [0u8; $N];
+            ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
+            let y: u128 = $f(bytes);
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_load_lane {
+    ($simd_load_lane:ident, $T:ty, $N:expr) => {
+        threaded_instr!($simd_load_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip): (u128, _) = read_stack(ip, sp);
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + mem::size_of::<$T>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
+            }
+            let mut bytes = [0u8; mem::size_of::<$T>()];
+            ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
+            let y = replace_lane::<$T, $N>(x, lane_idx, <$T>::from_le_bytes(bytes));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_store_lane {
+    ($simd_store_lane:ident, $T:ty, $N:expr) => {
+        threaded_instr!($simd_store_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip): (u128, _) = read_stack(ip, sp);
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + mem::size_of::<$T>() as u64 > ms {
+                return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
+            }
+            let bytes = extract_lane::<$T, $N>(x, lane_idx).to_le_bytes();
+            ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+threaded_instr!(v128_store(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (x, ip): (u128, _) = read_stack(ip, sp);
+    let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+    let (static_offset, ip): (u32, _) = read_imm(ip);
+
+    // Perform operation
+    let offset = dyn_offset as u64 + static_offset as u64;
+    if offset + mem::size_of::<u128>() as u64 > ms {
+        return trap_at(ip, sp, cx, Trap::MemAccessOutOfBounds);
+    }
+    let bytes = x.to_le_bytes();
+    ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(i8x16_shuffle(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (x1, ip): (u128, _) = read_stack(ip, sp);
+    let (x0, ip): (u128, _) = read_stack(ip, sp);
+    let (lane_idxs_lo, ip): (u64, _) = read_imm(ip);
+    let (lane_idxs_hi, ip): (u64, _) = read_imm(ip);
+
+    // Perform operation
+    let lane_idxs = (lane_idxs_hi as u128) << 64 | lane_idxs_lo as u128;
+    let y = shuffle(x0, x1, lane_idxs.to_le_bytes());
+
+    // Write result
+    let ip = write_stack(ip, sp, y);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(v128_bitselect(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (x2, ip): (u128, _) = read_stack(ip, sp);
+    let (x1, ip): (u128, _) = read_stack(ip, sp);
+    let (x0, ip): (u128, _) = read_stack(ip, sp);
+
+    // Perform operation
+    let y = x0 & x2 | x1 & !x2;
+
+    // Write result
+    let ip = write_stack(ip, sp, y);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+simd_load!(v128_load, 16, u128::from_le_bytes);
+simd_load!(v128_load8x8_s, 8, |bytes| extend::<i8, i16, 16, 8>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load8x8_u, 8, |bytes| extend::<u8, u16, 16, 8>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load16x4_s, 8, |bytes| extend::<i16, i32, 8, 4>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load16x4_u, 8, |bytes| extend::<u16, u32, 8, 4>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load32x2_s, 8, |bytes| extend::<i32, i64, 4, 2>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load32x2_u, 8, |bytes| extend::<u32, u64, 4, 2>(
+    u64::from_le_bytes(bytes) as u128,
+    false
+));
+simd_load!(v128_load8_splat, 1, |bytes| splat::<u8, 16>(
+    u8::from_le_bytes(bytes)
+));
+simd_load!(v128_load16_splat, 2, |bytes| splat::<u16, 8>(
+    u16::from_le_bytes(bytes)
+));
+simd_load!(v128_load32_splat, 4, |bytes| splat::<u32, 4>(
+    u32::from_le_bytes(bytes)
+));
+simd_load!(v128_load64_splat, 8, |bytes| splat::<u64, 2>(
+    u64::from_le_bytes(bytes)
+));
+simd_bin_op!(i8x16_swizzle, u128, u128, swizzle);
+simd_un_op!(i8x16_splat, i32, |x| splat::<i8, 16>(x as i8));
+simd_un_op!(i16x8_splat, i32, |x| splat::<i16, 8>(x as i16));
+simd_un_op!(i32x4_splat, i32, splat::<i32, 4>);
+simd_un_op!(i64x2_splat, i64, splat::<i64, 2>);
+simd_un_op!(f32x4_splat, f32, splat::<f32, 4>);
+simd_un_op!(f64x2_splat, f64, splat::<f64, 2>);
+simd_extract_lane!(
+    i8x16_extract_lane_s,
+    |x, lane_idx| extract_lane::<i8, 16>(x, lane_idx) as i32
+);
+simd_extract_lane!(
+    i8x16_extract_lane_u,
+    |x, lane_idx| extract_lane::<u8, 16>(x, lane_idx) as i32
+);
+simd_replace_lane!(i8x16_replace_lane, i32, |x, lane_idx, y| replace_lane::<
+    i8,
+    16,
+>(
+    x, lane_idx, y as i8
+));
+simd_extract_lane!(
+    i16x8_extract_lane_s,
+    |x, lane_idx| extract_lane::<i16, 8>(x, lane_idx) as i32
+);
+simd_extract_lane!(
+    i16x8_extract_lane_u,
+    |x, lane_idx| extract_lane::<u16, 8>(x, lane_idx) as i32
+);
+simd_replace_lane!(i16x8_replace_lane, i32, |x, lane_idx, y| replace_lane::<
+    i16,
+    8,
+>(
+    x, lane_idx, y as i16
+));
+simd_extract_lane!(i32x4_extract_lane, extract_lane::<i32, 4>);
+simd_replace_lane!(i32x4_replace_lane, i32, replace_lane::<i32, 4>);
+simd_extract_lane!(i64x2_extract_lane, extract_lane::<i64, 2>);
+simd_replace_lane!(i64x2_replace_lane, i64, replace_lane::<i64, 2>);
+simd_extract_lane!(f32x4_extract_lane, extract_lane::<f32, 4>);
+simd_replace_lane!(f32x4_replace_lane, f32, replace_lane::<f32, 4>);
+simd_extract_lane!(f64x2_extract_lane, extract_lane::<f64, 2>);
+simd_replace_lane!(f64x2_replace_lane, f64, replace_lane::<f64, 2>);
+simd_bin_op!(i8x16_eq, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(i8x16_ne, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(i8x16_lt_s, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i8x16_lt_u, u128, u128, |x, y| lanewise_rel_op::<u8, 16>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i8x16_gt_s, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i8x16_gt_u, u128, u128, |x, y| lanewise_rel_op::<u8, 16>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i8x16_le_s, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i8x16_le_u, u128, u128, |x, y| lanewise_rel_op::<u8, 16>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i8x16_ge_s, u128, u128, |x, y| lanewise_rel_op::<i8, 16>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i8x16_ge_u, u128, u128, |x, y| lanewise_rel_op::<u8, 16>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i16x8_eq, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(i16x8_ne, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(i16x8_lt_s, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i16x8_lt_u, u128, u128, |x, y| lanewise_rel_op::<u16, 8>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i16x8_gt_s, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i16x8_gt_u, u128, u128, |x, y| lanewise_rel_op::<u16, 8>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i16x8_le_s, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i16x8_le_u, u128, u128, |x, y| lanewise_rel_op::<u16, 8>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i16x8_ge_s, u128, u128, |x, y| lanewise_rel_op::<i16, 8>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i16x8_ge_u, u128, u128, |x, y| lanewise_rel_op::<u16, 8>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i32x4_eq, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(i32x4_ne, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(i32x4_lt_s, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i32x4_lt_u, u128, u128, |x, y| lanewise_rel_op::<u32, 4>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i32x4_gt_s, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i32x4_gt_u, u128, u128, |x, y| lanewise_rel_op::<u32, 4>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i32x4_le_s, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i32x4_le_u, u128, u128, |x, y| lanewise_rel_op::<u32, 4>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i32x4_ge_s, u128, u128, |x, y| lanewise_rel_op::<i32, 4>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i32x4_ge_u, u128, u128, |x, y| lanewise_rel_op::<u32, 4>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(f32x4_eq, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(f32x4_ne, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(f32x4_lt, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(f32x4_gt, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(f32x4_le, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(f32x4_ge, u128, u128, |x, y| lanewise_rel_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(f64x2_eq, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(f64x2_ne, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(f64x2_lt, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(f64x2_gt, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(f64x2_le, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(f64x2_ge, u128, u128, |x, y| lanewise_rel_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_un_op!(v128_not, u128, |x: u128| !x);
+simd_bin_op!(v128_and, u128, u128, |x, y| x & y);
+simd_bin_op!(v128_andnot, u128, u128, |x: u128, y: u128| x & !y);
+simd_bin_op!(v128_or, u128, u128, |x, y| x | y);
+simd_bin_op!(v128_xor, u128, u128, |x, y| x ^ y);
+simd_un_op!(v128_any_true, u128, |x| (x != 0) as i32);
+simd_load_lane!(v128_load8_lane, u8, 16);
+simd_load_lane!(v128_load16_lane, u16, 8);
+simd_load_lane!(v128_load32_lane, u32, 4);
+simd_load_lane!(v128_load64_lane, u64, 2);
+simd_store_lane!(v128_store8_lane, u8, 16);
+simd_store_lane!(v128_store16_lane, u16, 8);
+simd_store_lane!(v128_store32_lane, u32, 4);
+simd_store_lane!(v128_store64_lane, u64, 2);
+simd_load!(v128_load32_zero, 4, |bytes| u32::from_le_bytes(bytes)
+    as u128);
+simd_load!(v128_load64_zero, 8, |bytes| u64::from_le_bytes(bytes)
+    as u128);
+simd_un_op!(f32x4_demote_f64x2_zero, u128, |x| {
+    let x = to_lanes::<f64, 2>(x);
+    from_lanes::<f32, 4>([x[0] as f32, x[1] as f32, 0.0, 0.0])
+});
+simd_un_op!(f64x2_promote_low_f32x4, u128, |x| {
+    let x = to_lanes::<f32, 4>(x);
+    from_lanes::<f64, 2>([x[0] as f64, x[1] as f64])
+});
+simd_un_op!(i8x16_abs, u128, |x| lanewise_un_op::<i8, 16>(
+    x,
+    i8::wrapping_abs
+));
+simd_un_op!(i8x16_neg, u128, |x| lanewise_un_op::<i8, 16>(
+    x,
+    i8::wrapping_neg
+));
+simd_un_op!(i8x16_popcnt, u128, |x| lanewise_un_op::<u8, 16>(
+    x,
+    |x| x.count_ones() as u8
+));
+simd_un_op!(i8x16_all_true, u128, all_true::<i8, 16>);
+simd_un_op!(i8x16_bitmask, u128, bitmask::<i8, 16>);
+simd_bin_op!(i8x16_narrow_i16x8_s, u128, u128, narrow::<i16, i8, 8, 16>);
+simd_bin_op!(i8x16_narrow_i16x8_u, u128, u128, narrow::<i16, u8, 8, 16>);
+simd_un_op!(f32x4_ceil, u128, |x| lanewise_un_op::<f32, 4>(x, |x| {
+    FloatOps::ceil(x).unwrap()
+}));
+simd_un_op!(f32x4_floor, u128, |x| lanewise_un_op::<f32, 4>(x, |x| {
+    FloatOps::floor(x).unwrap()
+}));
+simd_un_op!(f32x4_trunc, u128, |x| lanewise_un_op::<f32, 4>(x, |x| {
+    FloatOps::trunc(x).unwrap()
+}));
+simd_un_op!(f32x4_nearest, u128, |x| lanewise_un_op::<f32, 4>(x, |x| {
+    FloatOps::nearest(x).unwrap()
+}));
+simd_bin_op!(i8x16_shl, u128, u32, |x, y| lanewise_shift_op::<i8, 16>(
+    x,
+    y,
+    i8::wrapping_shl
+));
+simd_bin_op!(i8x16_shr_s, u128, u32, |x, y| lanewise_shift_op::<i8, 16>(
+    x,
+    y,
+    i8::wrapping_shr
+));
+simd_bin_op!(i8x16_shr_u, u128, u32, |x, y| lanewise_shift_op::<u8, 16>(
+    x,
+    y,
+    u8::wrapping_shr
+));
+simd_bin_op!(i8x16_add, u128, u128, |x, y| lanewise_bin_op::<i8, 16>(
+    x,
+    y,
+    i8::wrapping_add
+));
+simd_bin_op!(
+    i8x16_add_sat_s,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<i8, 16>(x, y, i8::saturating_add)
+);
+simd_bin_op!(
+    i8x16_add_sat_u,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<u8, 16>(x, y, u8::saturating_add)
+);
+simd_bin_op!(i8x16_sub, u128, u128, |x, y| lanewise_bin_op::<i8, 16>(
+    x,
+    y,
+    i8::wrapping_sub
+));
+simd_bin_op!(
+    i8x16_sub_sat_s,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<i8, 16>(x, y, i8::saturating_sub)
+);
+simd_bin_op!(
+    i8x16_sub_sat_u,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<u8, 16>(x, y, u8::saturating_sub)
+);
+simd_un_op!(f64x2_ceil, u128, |x| lanewise_un_op::<f64, 2>(x, |x| {
+    FloatOps::ceil(x).unwrap()
+}));
+simd_un_op!(f64x2_floor, u128, |x| lanewise_un_op::<f64, 2>(x, |x| {
+    FloatOps::floor(x).unwrap()
+}));
+simd_bin_op!(i8x16_min_s, u128, u128, |x, y| lanewise_bin_op::<i8, 16>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i8x16_min_u, u128, u128, |x, y| lanewise_bin_op::<u8, 16>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i8x16_max_s, u128, u128, |x, y| lanewise_bin_op::<i8, 16>(
+    x,
+    y,
+    Ord::max
+));
+simd_bin_op!(i8x16_max_u, u128, u128, |x, y| lanewise_bin_op::<u8, 16>(
+    x,
+    y,
+    Ord::max
+));
+simd_un_op!(f64x2_trunc, u128, |x| lanewise_un_op::<f64, 2>(x, |x| {
+    FloatOps::trunc(x).unwrap()
+}));
+simd_bin_op!(i8x16_avgr_u, u128, u128, |x, y| lanewise_bin_op::<u8, 16>(
+    x,
+    y,
+    |x, y| (x as u16 + y as u16).div_ceil(2) as u8
+));
+simd_un_op!(i16x8_extadd_pairwise_i8x16_s, u128, |x| extadd_pairwise::<
+    i8,
+    i16,
+    16,
+    8,
+>(
+    x,
+    i16::wrapping_add
+));
+simd_un_op!(i16x8_extadd_pairwise_i8x16_u, u128, |x| extadd_pairwise::<
+    u8,
+    u16,
+    16,
+    8,
+>(
+    x,
+    u16::wrapping_add
+));
+simd_un_op!(i32x4_extadd_pairwise_i16x8_s, u128, |x| extadd_pairwise::<
+    i16,
+    i32,
+    8,
+    4,
+>(
+    x,
+    i32::wrapping_add
+));
+simd_un_op!(i32x4_extadd_pairwise_i16x8_u, u128, |x| extadd_pairwise::<
+    u16,
+    u32,
+    8,
+    4,
+>(
+    x,
+    u32::wrapping_add
+));
+simd_un_op!(i16x8_abs, u128, |x| lanewise_un_op::<i16, 8>(
+    x,
+    i16::wrapping_abs
+));
+simd_un_op!(i16x8_neg, u128, |x| lanewise_un_op::<i16, 8>(
+    x,
+    i16::wrapping_neg
+));
+simd_bin_op!(i16x8_q15mulr_sat_s, u128, u128, q15mulr_sat_s);
+simd_un_op!(i16x8_all_true, u128, all_true::<i16, 8>);
+simd_un_op!(i16x8_bitmask, u128, bitmask::<i16, 8>);
+simd_bin_op!(i16x8_narrow_i32x4_s, u128, u128, narrow::<i32, i16, 4, 8>);
+simd_bin_op!(i16x8_narrow_i32x4_u, u128, u128, narrow::<i32, u16, 4, 8>);
+simd_un_op!(
+    i16x8_extend_low_i8x16_s,
+    u128,
+    |x| extend::<i8, i16, 16, 8>(x, false)
+);
+simd_un_op!(
+    i16x8_extend_high_i8x16_s,
+    u128,
+    |x| extend::<i8, i16, 16, 8>(x, true)
+);
+simd_un_op!(
+    i16x8_extend_low_i8x16_u,
+    u128,
+    |x| extend::<u8, u16, 16, 8>(x, false)
+);
+simd_un_op!(
+    i16x8_extend_high_i8x16_u,
+    u128,
+    |x| extend::<u8, u16, 16, 8>(x, true)
+);
+simd_bin_op!(i16x8_shl, u128, u32, |x, y| lanewise_shift_op::<i16, 8>(
+    x,
+    y,
+    i16::wrapping_shl
+));
+simd_bin_op!(i16x8_shr_s, u128, u32, |x, y| lanewise_shift_op::<i16, 8>(
+    x,
+    y,
+    i16::wrapping_shr
+));
+simd_bin_op!(i16x8_shr_u, u128, u32, |x, y| lanewise_shift_op::<u16, 8>(
+    x,
+    y,
+    u16::wrapping_shr
+));
+simd_bin_op!(i16x8_add, u128, u128, |x, y| lanewise_bin_op::<i16, 8>(
+    x,
+    y,
+    i16::wrapping_add
+));
+simd_bin_op!(
+    i16x8_add_sat_s,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<i16, 8>(x, y, i16::saturating_add)
+);
+simd_bin_op!(
+    i16x8_add_sat_u,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<u16, 8>(x, y, u16::saturating_add)
+);
+simd_bin_op!(i16x8_sub, u128, u128, |x, y| lanewise_bin_op::<i16, 8>(
+    x,
+    y,
+    i16::wrapping_sub
+));
+simd_bin_op!(
+    i16x8_sub_sat_s,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<i16, 8>(x, y, i16::saturating_sub)
+);
+simd_bin_op!(
+    i16x8_sub_sat_u,
+    u128,
+    u128,
+    |x, y| lanewise_bin_op::<u16, 8>(x, y, u16::saturating_sub)
+);
+simd_un_op!(f64x2_nearest, u128, |x| lanewise_un_op::<f64, 2>(x, |x| {
+    FloatOps::nearest(x).unwrap()
+}));
+simd_bin_op!(i16x8_mul, u128, u128, |x, y| lanewise_bin_op::<i16, 8>(
+    x,
+    y,
+    i16::wrapping_mul
+));
+simd_bin_op!(i16x8_min_s, u128, u128, |x, y| lanewise_bin_op::<i16, 8>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i16x8_min_u, u128, u128, |x, y| lanewise_bin_op::<u16, 8>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i16x8_max_s, u128, u128, |x, y| lanewise_bin_op::<i16, 8>(
+    x,
+    y,
+    Ord::max
+));
+simd_bin_op!(i16x8_max_u, u128, u128, |x, y| lanewise_bin_op::<u16, 8>(
+    x,
+    y,
+    Ord::max
+));
+simd_bin_op!(i16x8_avgr_u, u128, u128, |x, y| lanewise_bin_op::<u16, 8>(
+    x,
+    y,
+    |x, y| (x as u32 + y as u32).div_ceil(2) as u16
+));
+simd_bin_op!(i16x8_extmul_low_i8x16_s, u128, u128, |x, y| extmul::<
+    i8,
+    i16,
+    16,
+    8,
+>(
+    x,
+    y,
+    false,
+    i16::wrapping_mul
+));
+simd_bin_op!(i16x8_extmul_high_i8x16_s, u128, u128, |x, y| extmul::<
+    i8,
+    i16,
+    16,
+    8,
+>(
+    x,
+    y,
+    true,
+    i16::wrapping_mul
+));
+simd_bin_op!(i16x8_extmul_low_i8x16_u, u128, u128, |x, y| extmul::<
+    u8,
+    u16,
+    16,
+    8,
+>(
+    x,
+    y,
+    false,
+    u16::wrapping_mul
+));
+simd_bin_op!(i16x8_extmul_high_i8x16_u, u128, u128, |x, y| extmul::<
+    u8,
+    u16,
+    16,
+    8,
+>(
+    x,
+    y,
+    true,
+    u16::wrapping_mul
+));
+simd_un_op!(i32x4_abs, u128, |x| lanewise_un_op::<i32, 4>(
+    x,
+    i32::wrapping_abs
+));
+simd_un_op!(i32x4_neg, u128, |x| lanewise_un_op::<i32, 4>(
+    x,
+    i32::wrapping_neg
+));
+simd_un_op!(i32x4_all_true, u128, all_true::<i32, 4>);
+simd_un_op!(i32x4_bitmask, u128, bitmask::<i32, 4>);
+simd_un_op!(
+    i32x4_extend_low_i16x8_s,
+    u128,
+    |x| extend::<i16, i32, 8, 4>(x, false)
+);
+simd_un_op!(
+    i32x4_extend_high_i16x8_s,
+    u128,
+    |x| extend::<i16, i32, 8, 4>(x, true)
+);
+simd_un_op!(
+    i32x4_extend_low_i16x8_u,
+    u128,
+    |x| extend::<u16, u32, 8, 4>(x, false)
+);
+simd_un_op!(
+    i32x4_extend_high_i16x8_u,
+    u128,
+    |x| extend::<u16, u32, 8, 4>(x, true)
+);
+simd_bin_op!(i32x4_shl, u128, u32, |x, y| lanewise_shift_op::<i32, 4>(
+    x,
+    y,
+    i32::wrapping_shl
+));
+simd_bin_op!(i32x4_shr_s, u128, u32, |x, y| lanewise_shift_op::<i32, 4>(
+    x,
+    y,
+    i32::wrapping_shr
+));
+simd_bin_op!(i32x4_shr_u, u128, u32, |x, y| lanewise_shift_op::<u32, 4>(
+    x,
+    y,
+    u32::wrapping_shr
+));
+simd_bin_op!(i32x4_add, u128, u128, |x, y| lanewise_bin_op::<i32, 4>(
+    x,
+    y,
+    i32::wrapping_add
+));
+simd_bin_op!(i32x4_sub, u128, u128, |x, y| lanewise_bin_op::<i32, 4>(
+    x,
+    y,
+    i32::wrapping_sub
+));
+simd_bin_op!(i32x4_mul, u128, u128, |x, y| lanewise_bin_op::<i32, 4>(
+    x,
+    y,
+    i32::wrapping_mul
+));
+simd_bin_op!(i32x4_min_s, u128, u128, |x, y| lanewise_bin_op::<i32, 4>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i32x4_min_u, u128, u128, |x, y| lanewise_bin_op::<u32, 4>(
+    x,
+    y,
+    Ord::min
+));
+simd_bin_op!(i32x4_max_s, u128, u128, |x, y| lanewise_bin_op::<i32, 4>(
+    x,
+    y,
+    Ord::max
+));
+simd_bin_op!(i32x4_max_u, u128, u128, |x, y| lanewise_bin_op::<u32, 4>(
+    x,
+    y,
+    Ord::max
+));
+simd_bin_op!(i32x4_dot_i16x8_s, u128, u128, dot_i16x8_s);
+simd_bin_op!(i32x4_extmul_low_i16x8_s, u128, u128, |x, y| extmul::<
+    i16,
+    i32,
+    8,
+    4,
+>(
+    x,
+    y,
+    false,
+    i32::wrapping_mul
+));
+simd_bin_op!(i32x4_extmul_high_i16x8_s, u128, u128, |x, y| extmul::<
+    i16,
+    i32,
+    8,
+    4,
+>(
+    x,
+    y,
+    true,
+    i32::wrapping_mul
+));
+simd_bin_op!(i32x4_extmul_low_i16x8_u, u128, u128, |x, y| extmul::<
+    u16,
+    u32,
+    8,
+    4,
+>(
+    x,
+    y,
+    false,
+    u32::wrapping_mul
+));
+simd_bin_op!(i32x4_extmul_high_i16x8_u, u128, u128, |x, y| extmul::<
+    u16,
+    u32,
+    8,
+    4,
+>(
+    x,
+    y,
+    true,
+    u32::wrapping_mul
+));
+simd_un_op!(i64x2_abs, u128, |x| lanewise_un_op::<i64, 2>(
+    x,
+    i64::wrapping_abs
+));
+simd_un_op!(i64x2_neg, u128, |x| lanewise_un_op::<i64, 2>(
+    x,
+    i64::wrapping_neg
+));
+simd_un_op!(i64x2_all_true, u128, all_true::<i64, 2>);
+simd_un_op!(i64x2_bitmask, u128, bitmask::<i64, 2>);
+simd_un_op!(
+    i64x2_extend_low_i32x4_s,
+    u128,
+    |x| extend::<i32, i64, 4, 2>(x, false)
+);
+simd_un_op!(
+    i64x2_extend_high_i32x4_s,
+    u128,
+    |x| extend::<i32, i64, 4, 2>(x, true)
+);
+simd_un_op!(
+    i64x2_extend_low_i32x4_u,
+    u128,
+    |x| extend::<u32, u64, 4, 2>(x, false)
+);
+simd_un_op!(
+    i64x2_extend_high_i32x4_u,
+    u128,
+    |x| extend::<u32, u64, 4, 2>(x, true)
+);
+simd_bin_op!(i64x2_shl, u128, u32, |x, y| lanewise_shift_op::<i64, 2>(
+    x,
+    y,
+    i64::wrapping_shl
+));
+simd_bin_op!(i64x2_shr_s, u128, u32, |x, y| lanewise_shift_op::<i64, 2>(
+    x,
+    y,
+    i64::wrapping_shr
+));
+simd_bin_op!(i64x2_shr_u, u128, u32, |x, y| lanewise_shift_op::<u64, 2>(
+    x,
+    y,
+    u64::wrapping_shr
+));
+simd_bin_op!(i64x2_add, u128, u128, |x, y| lanewise_bin_op::<i64, 2>(
+    x,
+    y,
+    i64::wrapping_add
+));
+simd_bin_op!(i64x2_sub, u128, u128, |x, y| lanewise_bin_op::<i64, 2>(
+    x,
+    y,
+    i64::wrapping_sub
+));
+simd_bin_op!(i64x2_mul, u128, u128, |x, y| lanewise_bin_op::<i64, 2>(
+    x,
+    y,
+    i64::wrapping_mul
+));
+simd_bin_op!(i64x2_eq, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x == y
+));
+simd_bin_op!(i64x2_ne, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x != y
+));
+simd_bin_op!(i64x2_lt_s, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x < y
+));
+simd_bin_op!(i64x2_gt_s, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x > y
+));
+simd_bin_op!(i64x2_le_s, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x <= y
+));
+simd_bin_op!(i64x2_ge_s, u128, u128, |x, y| lanewise_rel_op::<i64, 2>(
+    x,
+    y,
+    |x, y| x >= y
+));
+simd_bin_op!(i64x2_extmul_low_i32x4_s, u128, u128, |x, y| extmul::<
+    i32,
+    i64,
+    4,
+    2,
+>(
+    x,
+    y,
+    false,
+    i64::wrapping_mul
+));
+simd_bin_op!(i64x2_extmul_high_i32x4_s, u128, u128, |x, y| extmul::<
+    i32,
+    i64,
+    4,
+    2,
+>(
+    x,
+    y,
+    true,
+    i64::wrapping_mul
+));
+simd_bin_op!(i64x2_extmul_low_i32x4_u, u128, u128, |x, y| extmul::<
+    u32,
+    u64,
+    4,
+    2,
+>(
+    x,
+    y,
+    false,
+    u64::wrapping_mul
+));
+simd_bin_op!(i64x2_extmul_high_i32x4_u, u128, u128, |x, y| extmul::<
+    u32,
+    u64,
+    4,
+    2,
+>(
+    x,
+    y,
+    true,
+    u64::wrapping_mul
+));
+simd_un_op!(f32x4_abs, u128, |x| lanewise_un_op::<f32, 4>(x, f32::abs));
+simd_un_op!(f32x4_neg, u128, |x| lanewise_un_op::<f32, 4>(x, |x| -x));
+simd_un_op!(f32x4_sqrt, u128, |x| lanewise_un_op::<f32, 4>(x, f32::sqrt));
+simd_bin_op!(f32x4_add, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x + y
+));
+simd_bin_op!(f32x4_sub, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x - y
+));
+simd_bin_op!(f32x4_mul, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x * y
+));
+simd_bin_op!(f32x4_div, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| x / y
+));
+simd_bin_op!(f32x4_min, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| FloatOps::min(x, y).unwrap()
+));
+simd_bin_op!(f32x4_max, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| FloatOps::max(x, y).unwrap()
+));
+simd_bin_op!(f32x4_pmin, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| if y < x { y } else { x }
+));
+simd_bin_op!(f32x4_pmax, u128, u128, |x, y| lanewise_bin_op::<f32, 4>(
+    x,
+    y,
+    |x, y| if x < y { y } else { x }
+));
+simd_un_op!(f64x2_abs, u128, |x| lanewise_un_op::<f64, 2>(x, f64::abs));
+simd_un_op!(f64x2_neg, u128, |x| lanewise_un_op::<f64, 2>(x, |x| -x));
+simd_un_op!(f64x2_sqrt, u128, |x| lanewise_un_op::<f64, 2>(x, f64::sqrt));
+simd_bin_op!(f64x2_add, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x + y
+));
+simd_bin_op!(f64x2_sub, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x - y
+));
+simd_bin_op!(f64x2_mul, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x * y
+));
+simd_bin_op!(f64x2_div, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| x / y
+));
+simd_bin_op!(f64x2_min, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| FloatOps::min(x, y).unwrap()
+));
+simd_bin_op!(f64x2_max, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| FloatOps::max(x, y).unwrap()
+));
+simd_bin_op!(f64x2_pmin, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| if y < x { y } else { x }
+));
+simd_bin_op!(f64x2_pmax, u128, u128, |x, y| lanewise_bin_op::<f64, 2>(
+    x,
+    y,
+    |x, y| if x < y { y } else { x }
+));
+simd_un_op!(i32x4_trunc_sat_f32x4_s, u128, |x| from_lanes::<i32, 4>(
+    to_lanes::<f32, 4>(x).map(|x| x as i32)
+));
+simd_un_op!(i32x4_trunc_sat_f32x4_u, u128, |x| from_lanes::<u32, 4>(
+    to_lanes::<f32, 4>(x).map(|x| x as u32)
+));
+simd_un_op!(f32x4_convert_i32x4_s, u128, |x| from_lanes::<f32, 4>(
+    to_lanes::<i32, 4>(x).map(|x| x as f32)
+));
+simd_un_op!(f32x4_convert_i32x4_u, u128, |x| from_lanes::<f32, 4>(
+    to_lanes::<u32, 4>(x).map(|x| x as f32)
+));
+simd_un_op!(i32x4_trunc_sat_f64x2_s_zero, u128, |x| {
+    let x = to_lanes::<f64, 2>(x);
+    from_lanes::<i32, 4>([x[0] as i32, x[1] as i32, 0, 0])
+});
+simd_un_op!(i32x4_trunc_sat_f64x2_u_zero, u128, |x| {
+    let x = to_lanes::<f64, 2>(x);
+    from_lanes::<u32, 4>([x[0] as u32, x[1] as u32, 0, 0])
+});
+simd_un_op!(f64x2_convert_low_i32x4_s, u128, |x| {
+    let x = to_lanes::<i32, 4>(x);
+    from_lanes::<f64, 2>([x[0] as f64, x[1] as f64])
+});
+simd_un_op!(f64x2_convert_low_i32x4_u, u128, |x| {
+    let x = to_lanes::<u32, 4>(x);
+    from_lanes::<f64, 2>([x[0] as f64, x[1] as f64])
+});
+
 // Miscellaneous instructions
 
 macro_rules! copy_imm_to_stack {
@@ -3850,6 +5662,30 @@ copy_imm_to_stack!(copy_imm_to_stack_f64, f64);
 copy_imm_to_stack!(copy_imm_to_stack_func_ref, UnguardedFuncRef);
 copy_imm_to_stack!(copy_imm_to_stack_extern_ref, UnguardedExternRef);
 
+// A v128 immediate value does not fit in a single instruction slot, so it is split into two halves,
+// starting with the low half.
+threaded_instr!(copy_imm_to_stack_v128(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read immediate value
+    let (x_lo, ip): (u64, _) = read_imm(ip);
+    let (x_hi, ip): (u64, _) = read_imm(ip);
+    let x = (x_hi as u128) << 64 | x_lo as u128;
+
+    // Write value to stack
+    let ip = write_stack(ip, sp, x);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 macro_rules! copy_stack {
     ($copy_stack_t:ident, $T:ty) => {
         threaded_instr!($copy_stack_t(
@@ -3878,6 +5714,7 @@ copy_stack!(copy_stack_i32, i32);
 copy_stack!(copy_stack_i64, i64);
 copy_stack!(copy_stack_f32, f32);
 copy_stack!(copy_stack_f64, f64);
+copy_stack!(copy_stack_v128, u128);
 copy_stack!(copy_stack_func_ref, UnguardedFuncRef);
 copy_stack!(copy_stack_extern_ref, UnguardedExternRef);
 
@@ -3949,6 +5786,110 @@ threaded_instr!(compile(
     next_instr(ip, sp, md, ms, ix, sx, dx, cx)
 });
 
+threaded_instr!(compile_return_call(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    let mut func: UnguardedFunc = *ip.cast();
+    Func(Handle::from_unguarded(func, (*(*cx).store).id())).compile((*cx).store);
+    let FuncEntity::Wasm(func) = func.as_mut() else {
+        hint::unreachable_unchecked();
+    };
+    let Code::Compiled(state) = func.code_mut() else {
+        hint::unreachable_unchecked();
+    };
+    *ip.cast() = state.code.as_mut_ptr();
+    let ip = ip.offset(-1);
+    *ip.cast() = return_call as ThreadedInstr;
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(consume_fuel(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    let instr_ip = ip.offset(-1);
+    let (cost, ip): (usize, _) = read_imm(ip);
+
+    if let Err(trap) = (*(*cx).store).consume_fuel(cost as u64) {
+        // Save the virtual registers so that execution can be resumed from this instruction.
+        (*cx).ip = instr_ip;
+        (*cx).sp = sp;
+        (*cx).md = md;
+        (*cx).ms = ms;
+        (*cx).ix = ix;
+        (*cx).sx = sx;
+        (*cx).dx = dx;
+        return ControlFlow::Trap(trap).to_bits();
+    }
+
+    // Execute the next instruction.
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(consume_fuel_profiled(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    let instr_ip = ip.offset(-1);
+    let (cost, ip): (usize, _) = read_imm(ip);
+
+    if let Err(trap) = (*(*cx).store).consume_fuel(cost as u64) {
+        // Save the virtual registers so that execution can be resumed from this instruction.
+        (*cx).ip = instr_ip;
+        (*cx).sp = sp;
+        (*cx).md = md;
+        (*cx).ms = ms;
+        (*cx).ix = ix;
+        (*cx).sx = sx;
+        (*cx).dx = dx;
+        return ControlFlow::Trap(trap).to_bits();
+    }
+
+    // Only record the instructions once we know that the block will be executed, so that they are
+    // not recorded twice if execution is suspended and then resumed.
+    (*(*cx).store).profile_instrs(instr_ip, sp, (*cx).ptr, cost as u64);
+
+    // Execute the next instruction.
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(count_call(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    let (func, ip): (UnguardedFunc, _) = read_imm(ip);
+
+    (*(*cx).store).profile_call(func);
+
+    // Execute the next instruction.
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 threaded_instr!(enter(
     ip: Ip,
     sp: Sp,
@@ -3971,7 +5912,7 @@ threaded_instr!(enter(
     // Check that the stack has enough space.
     let stack_height = sp.offset_from((*cx).stack.as_mut().unwrap_unchecked().base_ptr()) as usize;
     if code.max_stack_height > Stack::SIZE - stack_height {
-        return ControlFlow::Trap(Trap::StackOverflow).to_bits();
+        return trap_at(ip, sp, cx, Trap::StackOverflow);
     }
 
     // Initialize the locals for this function to their default values.
@@ -3982,7 +5923,7 @@ threaded_instr!(enter(
     if let Some(mut mem) = mem {
         let data = mem.as_mut().bytes_mut();
         md = data.as_mut_ptr();
-        ms = data.len() as u32;
+        ms = data.len() as u64;
     } else {
         md = ptr::null_mut();
         ms = 0;
@@ -3995,6 +5936,30 @@ threaded_instr!(enter(
 // Helper functions
 
 /// Executes the next instruction.
+///
+/// This performs a sibling call to the next instruction, which LLVM optimises into a jump.
+#[cfg(not(any(stitch_trampoline, miri)))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn next_instr(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    exec_instr(ip, sp, md, ms, ix, sx, dx, cx)
+}
+
+/// Executes the next instruction in trampoline mode.
+///
+/// This stores the virtual registers in the [`Context`] and returns to the main interpreter loop,
+/// which then executes the next instruction. This is much slower than a sibling call, but does not
+/// rely on sibling call optimisation to avoid overflowing the native stack.
+#[cfg(any(stitch_trampoline, miri))]
+#[allow(clippy::too_many_arguments)]
 pub(crate) unsafe fn next_instr(
     ip: Ip,
     sp: Sp,
@@ -4004,11 +5969,111 @@ pub(crate) unsafe fn next_instr(
     sx: Sx,
     dx: Dx,
     cx: Cx,
+) -> ControlFlowBits {
+    (*cx).ip = ip;
+    (*cx).sp = sp;
+    (*cx).md = md;
+    (*cx).ms = ms;
+    (*cx).ix = ix;
+    (*cx).sx = sx;
+    (*cx).dx = dx;
+    ControlFlow::Continue.to_bits()
+}
+
+/// Executes the instruction at the given instruction pointer.
+#[allow(clippy::too_many_arguments)]
+unsafe fn exec_instr(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
 ) -> ControlFlowBits {
     let (instr, ip): (ThreadedInstr, _) = read_imm(ip);
     (instr)(ip, sp, md, ms, ix, sx, dx, cx)
 }
 
+/// Checks that sibling calls between `ThreadedInstr`s are optimised.
+#[cfg(any(stitch_trampoline, miri))]
+fn check_sibling_call_optimisation() {}
+
+/// Checks that sibling calls between `ThreadedInstr`s are optimised.
+///
+/// LLVM does not guarantee that it optimises sibling calls, so rather than overflowing the native
+/// stack if it doesn't, we check once whether it did, and panic otherwise.
+#[cfg(not(any(stitch_trampoline, miri)))]
+fn check_sibling_call_optimisation() {
+    use std::sync::Once;
+
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        assert!(
+            has_sibling_call_optimisation(),
+            "sibling calls between threaded instructions are not optimised on this target, \
+             enable the `trampoline` feature of makepad-stitch"
+        );
+    });
+}
+
+/// Returns `true` if LLVM optimised the sibling calls between `ThreadedInstr`s.
+///
+/// We check this by executing a probe instruction that calls itself once. If the sibling call was
+/// optimised, the second call reuses the call frame of the first, so both run at the same depth on
+/// the native stack.
+#[cfg(not(any(stitch_trampoline, miri)))]
+fn has_sibling_call_optimisation() -> bool {
+    #[inline(never)]
+    fn stack_depth() -> u64 {
+        let marker = 0u8;
+        hint::black_box(&marker) as *const u8 as u64
+    }
+
+    threaded_instr!(probe(
+        ip: Ip,
+        sp: Sp,
+        md: Md,
+        ms: Ms,
+        ix: Ix,
+        sx: Sx,
+        dx: Dx,
+        cx: Cx,
+    ) -> ControlFlowBits {
+        let depth = stack_depth();
+        if ix == 0 {
+            exec_instr(ip, sp, md, ms, depth, sx, dx, cx)
+        } else {
+            (ix == depth) as ControlFlowBits
+        }
+    });
+
+    let mut code = [probe as ThreadedInstr as usize as InstrSlot];
+    unsafe {
+        probe(
+            code.as_mut_ptr(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            0,
+            0,
+            0.0,
+            0.0,
+            ptr::null_mut(),
+        ) == 1
+    }
+}
+
+/// Saves the instruction and stack pointer in the [`Context`], so that a [`Backtrace`] can be
+/// created for the given [`Trap`], and returns the corresponding [`ControlFlowBits`].
+#[cold]
+unsafe fn trap_at(ip: Ip, sp: Sp, cx: Cx, trap: Trap) -> ControlFlowBits {
+    (*cx).ip = ip;
+    (*cx).sp = sp;
+    ControlFlow::Trap(trap).to_bits()
+}
+
 /// Reads an immediate value.
 unsafe fn read_imm<T>(ip: Ip) -> (T, Ip)
 where
@@ -1,13 +1,15 @@
 use {
     crate::{
-        code::{Code, UncompiledCode},
+        code::{Code, InstrSlot, UncompiledCode},
         decode::{Decode, DecodeError, Decoder},
         error::Error,
         exec,
         instance::Instance,
         into_host_func::IntoHostFunc,
+        module::ModuleNames,
         stack::StackGuard,
         store::{Handle, InternedFuncType, Store, StoreId, UnguardedHandle},
+        trap::BacktraceFrame,
         val::{Val, ValType},
     },
     std::{error, fmt, mem, sync::Arc},
@@ -62,9 +64,13 @@ impl Func {
         store: &mut Store,
         type_: InternedFuncType,
         instance: Instance,
+        idx: u32,
+        names: Arc<ModuleNames>,
         code: UncompiledCode,
     ) -> Self {
-        Self(store.insert_func(FuncEntity::Wasm(WasmFuncEntity::new(type_, instance, code))))
+        Self(store.insert_func(FuncEntity::Wasm(WasmFuncEntity::new(
+            type_, instance, idx, names, code,
+        ))))
     }
 
     /// Converts the given [`UnguardedFunc`] to a [`Func`].
@@ -145,12 +151,13 @@ impl FuncType {
     /// [`FuncType`] [] -> [`ValType`?].
     pub(crate) fn from_val_type(type_: Option<ValType>) -> FuncType {
         thread_local! {
-            static TYPES: [FuncType; 7] = [
+            static TYPES: [FuncType; 8] = [
                 FuncType::new(vec![], vec![]),
                 FuncType::new(vec![], vec![ValType::I32]),
                 FuncType::new(vec![], vec![ValType::I64]),
                 FuncType::new(vec![], vec![ValType::F32]),
                 FuncType::new(vec![], vec![ValType::F64]),
+                FuncType::new(vec![], vec![ValType::V128]),
                 FuncType::new(vec![], vec![ValType::FuncRef]),
                 FuncType::new(vec![], vec![ValType::ExternRef]),
             ];
@@ -162,8 +169,9 @@ impl FuncType {
             Some(ValType::I64) => types[2].clone(),
             Some(ValType::F32) => types[3].clone(),
             Some(ValType::F64) => types[4].clone(),
-            Some(ValType::FuncRef) => types[5].clone(),
-            Some(ValType::ExternRef) => types[6].clone(),
+            Some(ValType::V128) => types[5].clone(),
+            Some(ValType::FuncRef) => types[6].clone(),
+            Some(ValType::ExternRef) => types[7].clone(),
         })
     }
 
@@ -199,6 +207,7 @@ pub enum FuncError {
     ParamCountMismatch,
     ParamTypeMismatch,
     ResultCountMismatch,
+    NotSuspended,
 }
 
 impl fmt::Display for FuncError {
@@ -207,6 +216,7 @@ impl fmt::Display for FuncError {
             Self::ParamCountMismatch => write!(f, "function parameter count mismatch"),
             Self::ParamTypeMismatch => write!(f, "function parameter type mismatch"),
             Self::ResultCountMismatch => write!(f, "function result count mismatch"),
+            Self::NotSuspended => write!(f, "no suspended function call to resume"),
         }
     }
 }
@@ -234,15 +244,25 @@ impl FuncEntity {
 pub(crate) struct WasmFuncEntity {
     type_: InternedFuncType,
     instance: Instance,
+    idx: u32,
+    names: Arc<ModuleNames>,
     code: Code,
 }
 
 impl WasmFuncEntity {
     /// Creates a new [`WasmFuncEntity`] from its raw parts.
-    fn new(type_: InternedFuncType, instance: Instance, code: UncompiledCode) -> WasmFuncEntity {
+    fn new(
+        type_: InternedFuncType,
+        instance: Instance,
+        idx: u32,
+        names: Arc<ModuleNames>,
+        code: UncompiledCode,
+    ) -> WasmFuncEntity {
         WasmFuncEntity {
             type_,
             instance,
+            idx,
+            names,
             code: Code::Uncompiled(code),
         }
     }
@@ -257,6 +277,16 @@ impl WasmFuncEntity {
         &self.instance
     }
 
+    /// Returns the index of this [`WasmFuncEntity`] in its module.
+    pub(crate) fn idx(&self) -> u32 {
+        self.idx
+    }
+
+    /// Returns the names from the name section of the module of this [`WasmFuncEntity`].
+    pub(crate) fn names(&self) -> &ModuleNames {
+        &self.names
+    }
+
     /// Returns a reference to the [`Code`] of this [`WasmFuncEntity`].
     pub(crate) fn code(&self) -> &Code {
         &self.code
@@ -266,6 +296,21 @@ impl WasmFuncEntity {
     pub(crate) fn code_mut(&mut self) -> &mut Code {
         &mut self.code
     }
+
+    /// Returns a [`BacktraceFrame`] for the given instruction pointer, or `None` if the
+    /// instruction pointer does not point into the compiled code of this [`WasmFuncEntity`].
+    pub(crate) fn backtrace_frame(&self, ip: *const InstrSlot) -> Option<BacktraceFrame> {
+        let Code::Compiled(code) = &self.code else {
+            return None;
+        };
+        let instr_offset = code.instr_offset(ip)?;
+        Some(BacktraceFrame::new(
+            self.names.module.clone(),
+            self.idx,
+            self.names.funcs.get(&self.idx).cloned(),
+            instr_offset,
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -82,6 +82,7 @@ impl Instance {
                 elems: initer.elems.into(),
                 datas: initer.datas.into(),
                 exports: initer.exports,
+                module_fingerprint: initer.module_fingerprint,
             })
             .expect("instance already initialized");
     }
@@ -160,6 +161,12 @@ impl Instance {
         self.inner().datas.get(idx as usize).copied()
     }
 
+    /// Returns the fingerprint of the [`Module`](crate::Module) this [`Instance`] was
+    /// instantiated from.
+    pub(crate) fn module_fingerprint(&self) -> u64 {
+        self.inner().module_fingerprint
+    }
+
     fn inner(&self) -> &InstanceInner {
         self.inner.get().expect("instance not yet initialized")
     }
@@ -204,6 +211,7 @@ struct InstanceInner {
     elems: Box<[UnguardedElem]>,
     datas: Box<[UnguardedData]>,
     exports: HashMap<Arc<str>, UnguardedExternVal>,
+    module_fingerprint: u64,
 }
 
 /// An initializer for an [`Instance`].
@@ -218,11 +226,12 @@ pub(crate) struct InstanceIniter {
     elems: Vec<UnguardedElem>,
     datas: Vec<UnguardedData>,
     exports: HashMap<Arc<str>, UnguardedExternVal>,
+    module_fingerprint: u64,
 }
 
 impl InstanceIniter {
     /// Creates a new [`InstanceIniter`].
-    pub(crate) fn new(store_id: StoreId) -> InstanceIniter {
+    pub(crate) fn new(store_id: StoreId, module_fingerprint: u64) -> InstanceIniter {
         InstanceIniter {
             types: Vec::new(),
             funcs: Vec::new(),
@@ -232,6 +241,7 @@ impl InstanceIniter {
             elems: Vec::new(),
             datas: Vec::new(),
             exports: HashMap::new(),
+            module_fingerprint,
             store_id,
         }
     }
@@ -23,13 +23,16 @@ mod linker;
 mod mem;
 mod module;
 mod ops;
+mod profile;
 mod ref_;
+mod snapshot;
 mod stack;
 mod store;
 mod table;
 mod trap;
 mod val;
 mod validate;
+mod wasi;
 
 pub use self::{
     decode::DecodeError,
@@ -45,8 +48,12 @@ pub use self::{
     linker::{InstantiateError, Linker},
     mem::{Mem, MemError, MemType},
     module::{Module, ModuleExports, ModuleImports},
+    profile::{FuncProfile, Profile, ProfileFuncs},
     ref_::{Ref, RefType},
-    store::Store,
+    snapshot::{Snapshot, SnapshotError},
+    store::{InterruptHandle, Store},
     table::{Table, TableError, TableType},
+    trap::{Backtrace, BacktraceFrame, Trap},
     val::{Val, ValType},
+    wasi::{MemDir, Wasi},
 };
@@ -3,8 +3,8 @@ use crate::decode::{Decode, DecodeError, Decoder};
 /// A size range.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Limits {
-    pub min: u32,
-    pub max: Option<u32>,
+    pub min: u64,
+    pub max: Option<u64>,
 }
 
 impl Limits {
@@ -13,7 +13,7 @@ impl Limits {
     /// A [`Limits`] is valid within the range `0..=limit` if its minimum is not greater than
     /// `limit` and its maximum, if it exists, is neither less than its minimum nor greater than
     /// `limit`.
-    pub fn is_valid(self, limit: u32) -> bool {
+    pub fn is_valid(self, limit: u64) -> bool {
         if self.min > limit {
             return false;
         }
@@ -47,12 +47,12 @@ impl Decode for Limits {
     fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
         match decoder.read_byte()? {
             0x00 => Ok(Limits {
-                min: decoder.decode()?,
+                min: decoder.decode::<u32>()?.into(),
                 max: None,
             }),
             0x01 => Ok(Limits {
-                min: decoder.decode()?,
-                max: Some(decoder.decode()?),
+                min: decoder.decode::<u32>()?.into(),
+                max: Some(decoder.decode::<u32>()?.into()),
             }),
             _ => Err(DecodeError::new("invalid limits")),
         }
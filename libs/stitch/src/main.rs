@@ -1,19 +1,41 @@
 use {
-    makepad_stitch::{Engine, Linker, Module, Store, Val, ValType},
-    std::{env, fs},
+    makepad_stitch::{Engine, Error, Linker, Module, Store, Val, ValType, Wasi},
+    std::{env, fs, process},
 };
 
+/// The number of Wasm instructions between samples of the call stack when profiling.
+const PROFILE_SAMPLE_INTERVAL: u64 = 1000;
+
 fn main() {
-    let mut args = env::args();
+    let mut args = env::args().peekable();
     args.next().unwrap();
+    // With `--profile <profile_file>`, a profile of the Wasm functions that were executed is
+    // written to the given file, as folded stacks.
+    let profile_file = if args.peek().map(String::as_str) == Some("--profile") {
+        args.next();
+        Some(args.next().unwrap())
+    } else {
+        None
+    };
     let wasm_file = args.next().unwrap();
-    let func_name = args.next().unwrap();
+    let func_name = args.next().unwrap_or_else(|| "_start".to_string());
     let args: Vec<_> = args.collect();
-    let engine = Engine::new();
+    let engine = if profile_file.is_some() {
+        Engine::with_profiling(PROFILE_SAMPLE_INTERVAL)
+    } else {
+        Engine::new()
+    };
     let mut store = Store::new(engine);
-    let bytes = fs::read(wasm_file).unwrap();
+    let bytes = fs::read(&wasm_file).unwrap();
     let module = Module::new(store.engine(), &bytes).unwrap();
-    let linker = Linker::new();
+    let mut linker = Linker::new();
+    // WASI binaries get the remaining arguments as their command line arguments, and can access
+    // the current directory.
+    Wasi::new()
+        .args([wasm_file.clone()])
+        .args(args.iter().cloned())
+        .preopen_dir(".", ".")
+        .define(&mut store, &mut linker);
     let instance = linker.instantiate(&mut store, &module).unwrap();
     let func = instance.exported_func(&func_name).unwrap();
     let args: Vec<_> = func
@@ -29,18 +51,48 @@ fn main() {
         .iter()
         .map(|type_| Val::default(*type_))
         .collect();
-    func.call(&mut store, &args, &mut results).unwrap();
+    let result = func.call(&mut store, &args, &mut results);
+    if let Some(profile_file) = &profile_file {
+        write_profile(&store, profile_file);
+    }
+    match &result {
+        Err(Error::Exit(code)) => process::exit(*code),
+        Err(Error::Trap(trap, backtrace)) => {
+            eprintln!("error: {}", trap);
+            eprint!("wasm backtrace:\n{}", backtrace);
+            process::exit(1);
+        }
+        _ => {}
+    }
+    result.unwrap();
     for result in results {
         print_val(result);
     }
 }
 
+fn write_profile(store: &Store, profile_file: &str) {
+    let profile = store.profile().unwrap();
+    fs::write(profile_file, profile.folded_stacks()).unwrap();
+    let mut funcs: Vec<_> = profile.funcs().collect();
+    funcs.sort_by_key(|func| std::cmp::Reverse(func.instr_count()));
+    eprintln!("{:>16} {:>12}  function", "instructions", "calls");
+    for func in funcs.iter().take(20) {
+        eprintln!(
+            "{:>16} {:>12}  {}",
+            func.instr_count(),
+            func.call_count(),
+            func
+        );
+    }
+}
+
 fn parse_val(type_: ValType, string: &str) -> Val {
     match type_ {
         ValType::I32 => string.parse::<i32>().unwrap().into(),
         ValType::I64 => string.parse::<i64>().unwrap().into(),
         ValType::F32 => string.parse::<f32>().unwrap().into(),
         ValType::F64 => string.parse::<f64>().unwrap().into(),
+        ValType::V128 => string.parse::<u128>().unwrap().into(),
         ValType::FuncRef => unimplemented!(),
         ValType::ExternRef => unimplemented!(),
     }
@@ -52,6 +104,7 @@ fn print_val(val: Val) {
         Val::I64(val) => println!("{}", val),
         Val::F32(val) => println!("{}", val),
         Val::F64(val) => println!("{}", val),
+        Val::V128(val) => println!("{:#034x}", val),
         Val::FuncRef(_) => unimplemented!(),
         Val::ExternRef(_) => unimplemented!(),
     }
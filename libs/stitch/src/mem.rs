@@ -3,7 +3,7 @@ use {
         data::{Data, DataEntity},
         decode::{Decode, DecodeError, Decoder},
         limits::Limits,
-        stack::Stack,
+        stack::{Stack, StackSlot},
         store::{Handle, HandlePair, Store, StoreId, UnguardedHandle},
         trap::Trap,
     },
@@ -28,8 +28,10 @@ impl Mem {
 
     /// Returns the [`MemType`] of this [`Mem`].
     pub fn type_(self, store: &Store) -> MemType {
+        let mem = self.0.as_ref(store);
         MemType {
-            limits: self.0.as_ref(store).limits(),
+            limits: mem.limits(),
+            is_64: mem.is_64,
         }
     }
 
@@ -56,13 +58,18 @@ impl Mem {
     ///
     /// If this [`Mem`] failed to grow.
     pub fn grow(self, store: &mut Store, count: u32) -> Result<u32, MemError> {
+        if store.is_suspended() {
+            // The stack is locked by the suspended call, which updates its call frames to match
+            // when it is resumed.
+            return self.0.as_mut(store).grow_without_stack(count);
+        }
         self.0.as_mut(store).grow(count)
     }
 
     pub(crate) fn init(
         self,
         store: &mut Store,
-        dst_offset: u32,
+        dst_offset: u64,
         src_data: Data,
         src_offset: u32,
         count: u32,
@@ -98,29 +105,59 @@ pub(crate) type UnguardedMem = UnguardedHandle<MemEntity>;
 pub struct MemType {
     /// The [`Limits`] of this [`Mem`].
     pub limits: Limits,
+    /// Whether this [`Mem`] is indexed with 64-bit addresses.
+    pub is_64: bool,
 }
 
 impl MemType {
     /// Returns `true` if this [`MemType`] is valid.
     ///
     /// A [`MemType`] is valid if its [`Limits`] are valid within range 65_536.
+    ///
+    /// The memory64 proposal allows up to 2^48 pages for memories indexed with 64-bit addresses,
+    /// but we do not support memories larger than 4 GiB, so those are rejected as well.
     pub fn is_valid(&self) -> bool {
-        self.limits.is_valid(65_536)
+        self.limits.is_valid(MAX_PAGE_COUNT)
     }
 
     /// Returns `true` if this [`MemType`] is a subtype of the given [`MemType`].
     ///
     /// A [`MemType`] is a subtype of another [`MemType`] if its [`Limits`] are a sublimit of the
-    /// other's.
+    /// other's, and both are indexed with addresses of the same width.
     pub fn is_subtype_of(self, other: Self) -> bool {
-        self.limits.is_sublimit_of(other.limits)
+        if !self.limits.is_sublimit_of(other.limits) {
+            return false;
+        }
+        if self.is_64 != other.is_64 {
+            return false;
+        }
+        true
     }
 }
 
 impl Decode for MemType {
     fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let flags = decoder.read_byte()?;
+        if flags & !0x05 != 0 {
+            return Err(DecodeError::new("malformed limits flags"));
+        }
+        let is_64 = flags & 0x04 != 0;
+        let mut decode_bound = || -> Result<u64, DecodeError> {
+            if is_64 {
+                decoder.decode()
+            } else {
+                Ok(decoder.decode::<u32>()?.into())
+            }
+        };
+        let min = decode_bound()?;
+        let max = if flags & 0x01 != 0 {
+            Some(decode_bound()?)
+        } else {
+            None
+        };
         Ok(Self {
-            limits: Limits::decode(decoder)?,
+            limits: Limits { min, max },
+            is_64,
         })
     }
 }
@@ -145,23 +182,33 @@ impl Error for MemError {}
 /// The representation of a [`Mem`] in a [`Store`].
 #[derive(Debug)]
 pub(crate) struct MemEntity {
-    max: Option<u32>,
+    max: Option<u64>,
+    is_64: bool,
     bytes: Vec<u8>,
 }
 
 impl MemEntity {
     /// Creates a new [`MemEntity`] with the given [`MemType`].
     fn new(type_: MemType) -> Self {
+        let len = usize::try_from(type_.limits.min)
+            .ok()
+            .and_then(|min| min.checked_mul(PAGE_SIZE))
+            .unwrap();
+        // Compiled code finds the call frames that cache a memory by the address of its bytes, so
+        // every memory needs its own allocation, even if it is empty.
+        let mut bytes = vec![0; len.max(1)];
+        bytes.truncate(len);
         Self {
             max: type_.limits.max,
-            bytes: vec![0; (type_.limits.min as usize).checked_mul(PAGE_SIZE).unwrap()],
+            is_64: type_.is_64,
+            bytes,
         }
     }
 
     /// Returns the [`Limits`] of this [`MemEntity`].
     fn limits(&self) -> Limits {
         Limits {
-            min: self.size(),
+            min: self.size().into(),
             max: self.max,
         }
     }
@@ -198,29 +245,47 @@ impl MemEntity {
         count: u32,
         stack: &mut Stack,
     ) -> Result<u32, MemError> {
-        if count > self.max.unwrap_or(65_536) - self.size() {
-            return Err(MemError::FailedToGrow);
-        }
         let old_data = self.bytes.as_mut_ptr();
-        let old_size = self.size();
-        let new_size = self.size() + count;
-        self.bytes
-            .resize((new_size as usize).checked_mul(PAGE_SIZE).unwrap(), 0);
+        let old_size = self.grow_without_stack(count)?;
+        let new_len = self.bytes.len() as u64;
         let new_data = self.bytes.as_mut_ptr();
-        let mut ptr = stack.ptr();
-        while ptr != stack.base_ptr() {
-            ptr = *ptr.offset(-3).cast();
+        // Every call frame on the stack stores the memory registers of its caller, including the
+        // one for the current function, so update all of them that refer to this memory.
+        let base_ptr = stack.base_ptr();
+        let mut ptr: *mut StackSlot = stack.ptr();
+        while ptr != base_ptr {
             if *ptr.offset(-2).cast::<*mut u8>() == old_data {
                 *ptr.offset(-2).cast() = new_data;
-                *ptr.offset(-1).cast() = new_size;
+                *ptr.offset(-1).cast() = new_len;
             }
+            ptr = *ptr.offset(-3).cast();
         }
         Ok(old_size)
     }
 
-    pub(crate) fn fill(&mut self, idx: u32, val: u8, count: u32) -> Result<(), Trap> {
-        let idx = idx as usize;
-        let count = count as usize;
+    /// Grows this [`MemEntity`] by the given number of pages, without updating the call frames on
+    /// the stack.
+    ///
+    /// Returns the previous size of this [`MemEntity`] in number of pages.
+    ///
+    /// # Errors
+    ///
+    /// If this [`MemEntity`] failed to grow.
+    pub(crate) fn grow_without_stack(&mut self, count: u32) -> Result<u32, MemError> {
+        let max = self.max.unwrap_or(u64::MAX).min(MAX_PAGE_COUNT);
+        if u64::from(count) > max.saturating_sub(self.size().into()) {
+            return Err(MemError::FailedToGrow);
+        }
+        let old_size = self.size();
+        let new_size = self.size() + count;
+        self.bytes
+            .resize((new_size as usize).checked_mul(PAGE_SIZE).unwrap(), 0);
+        Ok(old_size)
+    }
+
+    pub(crate) fn fill(&mut self, idx: u64, val: u8, count: u64) -> Result<(), Trap> {
+        let idx = to_usize(idx)?;
+        let count = to_usize(count)?;
         let bytes = self
             .bytes
             .get_mut(idx..)
@@ -232,13 +297,13 @@ impl MemEntity {
 
     pub(crate) fn copy_within(
         &mut self,
-        dst_idx: u32,
-        src_idx: u32,
-        count: u32,
+        dst_idx: u64,
+        src_idx: u64,
+        count: u64,
     ) -> Result<(), Trap> {
-        let dst_idx = dst_idx as usize;
-        let src_idx = src_idx as usize;
-        let count = count as usize;
+        let dst_idx = to_usize(dst_idx)?;
+        let src_idx = to_usize(src_idx)?;
+        let count = to_usize(count)?;
         if count > self.bytes.len()
             || dst_idx > self.bytes.len() - count
             || src_idx > self.bytes.len() - count
@@ -249,14 +314,38 @@ impl MemEntity {
         Ok(())
     }
 
+    pub(crate) fn copy(
+        &mut self,
+        dst_idx: u64,
+        src_mem: &MemEntity,
+        src_idx: u64,
+        count: u64,
+    ) -> Result<(), Trap> {
+        let dst_idx = to_usize(dst_idx)?;
+        let src_idx = to_usize(src_idx)?;
+        let count = to_usize(count)?;
+        let dst_bytes = self
+            .bytes
+            .get_mut(dst_idx..)
+            .and_then(|bytes| bytes.get_mut(..count))
+            .ok_or(Trap::MemAccessOutOfBounds)?;
+        let src_bytes = src_mem
+            .bytes
+            .get(src_idx..)
+            .and_then(|bytes| bytes.get(..count))
+            .ok_or(Trap::MemAccessOutOfBounds)?;
+        dst_bytes.copy_from_slice(src_bytes);
+        Ok(())
+    }
+
     pub(crate) fn init(
         &mut self,
-        dst_idx: u32,
+        dst_idx: u64,
         src_data: &DataEntity,
         src_idx: u32,
         count: u32,
     ) -> Result<(), Trap> {
-        let dst_idx = dst_idx as usize;
+        let dst_idx = to_usize(dst_idx)?;
         let src_idx = src_idx as usize;
         let count = count as usize;
         let dst_bytes = self
@@ -274,4 +363,15 @@ impl MemEntity {
     }
 }
 
-const PAGE_SIZE: usize = 65_536;
+/// Converts the given index or count to a `usize`, trapping if it does not fit.
+fn to_usize(val: u64) -> Result<usize, Trap> {
+    usize::try_from(val).map_err(|_| Trap::MemAccessOutOfBounds)
+}
+
+pub(crate) const PAGE_SIZE: usize = 65_536;
+
+/// The maximum number of pages a [`Mem`] can have, regardless of its [`MemType`].
+///
+/// Compiled code wraps the addresses of memories indexed with 64-bit addresses to 32 bits, so
+/// memories are limited to 4 GiB, even if they are indexed with 64-bit addresses.
+const MAX_PAGE_COUNT: u64 = 65_536;
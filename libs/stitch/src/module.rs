@@ -18,7 +18,7 @@ use {
         store::Store,
         table::{Table, TableType},
         trap::Trap,
-        val::ValType,
+        val::{Val, ValType},
     },
     std::{
         collections::{hash_map, HashMap, HashSet},
@@ -46,6 +46,8 @@ pub struct Module {
     codes: Box<[UncompiledCode]>,
     elems: Box<[ElemDef]>,
     datas: Box<[DataDef]>,
+    names: Arc<ModuleNames>,
+    fingerprint: u64,
 }
 
 impl Module {
@@ -84,7 +86,14 @@ impl Module {
             match section_id {
                 // Custom section
                 0 => {
-                    section_decoder.decode_string()?;
+                    let name = section_decoder.decode_string()?;
+                    if name == "name" {
+                        // The name section is only used for debugging, so if it is malformed, we
+                        // ignore it rather than rejecting the module.
+                        if let Ok(names) = section_decoder.decode() {
+                            builder.set_names(names);
+                        }
+                    }
                     section_decoder.read_bytes_until_end();
                 }
                 // Type section
@@ -162,7 +171,18 @@ impl Module {
                 return Err(DecodeError::new(""))?;
             }
         }
-        builder.finish(engine)
+        builder.finish(engine, fingerprint(bytes))
+    }
+
+    /// Returns the name of this [`Module`], if it has one in its name section.
+    pub fn name(&self) -> Option<&str> {
+        self.names.module.as_deref()
+    }
+
+    /// Returns the name of the function with the given index in this [`Module`], if it has one in
+    /// its name section.
+    pub fn func_name(&self, idx: u32) -> Option<&str> {
+        self.names.funcs.get(&idx).map(|name| &**name)
     }
 
     /// Returns an iterator over the imports in this [`Module`].
@@ -195,7 +215,7 @@ impl Module {
         linker: &Linker,
     ) -> Result<Instance, Error> {
         let instance = Instance::uninited(store.id());
-        let mut initer = InstanceIniter::new(store.id());
+        let mut initer = InstanceIniter::new(store.id(), self.fingerprint);
         for type_ in self.types.iter() {
             initer.push_type(store.get_or_intern_type(type_));
         }
@@ -226,9 +246,16 @@ impl Module {
                 _ => {}
             }
         }
-        for (type_, code) in self.internal_funcs() {
+        for ((type_, code), idx) in self.internal_funcs().zip(self.imported_func_count as u32..) {
             let type_ = store.get_or_intern_type(type_);
-            initer.push_func(Func::new_wasm(store, type_, instance.clone(), code.clone()));
+            initer.push_func(Func::new_wasm(
+                store,
+                type_,
+                instance.clone(),
+                idx,
+                self.names.clone(),
+                code.clone(),
+            ));
         }
         let global_init_vals: Vec<_> = self
             .internal_globals()
@@ -345,12 +372,14 @@ impl Module {
             else {
                 continue;
             };
+            let offset = match offset.evaluate(store, &instance) {
+                Val::I32(offset) => offset as u32 as u64,
+                Val::I64(offset) => offset as u64,
+                _ => return Err(Trap::Unreachable)?,
+            };
             instance.mem(mem_idx).unwrap().init(
                 store,
-                offset
-                    .evaluate(store, &instance)
-                    .to_i32()
-                    .ok_or(Trap::Unreachable)? as u32,
+                offset,
                 instance.data(data_idx).unwrap(),
                 0,
                 data.bytes.len().try_into().unwrap(),
@@ -486,6 +515,7 @@ pub(crate) struct ModuleBuilder {
     datas: Vec<DataDef>,
     data_count: Option<u32>,
     refs: HashSet<u32>,
+    names: ModuleNames,
 }
 
 impl ModuleBuilder {
@@ -509,6 +539,7 @@ impl ModuleBuilder {
             datas: Vec::new(),
             data_count: None,
             refs: HashSet::new(),
+            names: ModuleNames::default(),
         }
     }
 
@@ -789,8 +820,12 @@ impl ModuleBuilder {
             ref offset,
         } = data.kind
         {
-            self.memory(mem_idx)?;
-            if offset.validate(self)? != ValType::I32 {
+            let addr_type = if self.memory(mem_idx)?.is_64 {
+                ValType::I64
+            } else {
+                ValType::I32
+            };
+            if offset.validate(self)? != addr_type {
                 return Err(DecodeError::new("type mismatch"));
             }
         }
@@ -802,7 +837,11 @@ impl ModuleBuilder {
         self.data_count = Some(data_count);
     }
 
-    fn finish(self, engine: &Engine) -> Result<Module, DecodeError> {
+    fn set_names(&mut self, names: ModuleNames) {
+        self.names = names;
+    }
+
+    fn finish(self, engine: &Engine, fingerprint: u64) -> Result<Module, DecodeError> {
         if self.func_types.len() - self.imported_func_count > self.codes.len() {
             return Err(DecodeError::new(
                 "function and code section have inconsistent sizes",
@@ -838,10 +877,53 @@ impl ModuleBuilder {
             codes: self.codes.into(),
             elems: self.elems.into(),
             datas: self.datas.into(),
+            names: Arc::new(self.names),
+            fingerprint,
         })
     }
 }
 
+/// Computes a fingerprint of the given module bytes, using the 64-bit FNV-1a hash.
+fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+/// The names of a [`Module`] and its functions, as defined by its name section.
+#[derive(Debug, Default)]
+pub(crate) struct ModuleNames {
+    pub(crate) module: Option<Arc<str>>,
+    pub(crate) funcs: HashMap<u32, Arc<str>>,
+}
+
+impl Decode for ModuleNames {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        let mut names = ModuleNames::default();
+        while !decoder.is_at_end() {
+            let subsection_id = decoder.read_byte()?;
+            let mut subsection_decoder = decoder.decode_decoder()?;
+            match subsection_id {
+                // Module name subsection
+                0 => {
+                    names.module = Some(subsection_decoder.decode()?);
+                }
+                // Function name subsection
+                1 => {
+                    for _ in 0u32..subsection_decoder.decode()? {
+                        let idx = subsection_decoder.decode()?;
+                        let name = subsection_decoder.decode()?;
+                        names.funcs.insert(idx, name);
+                    }
+                }
+                // Other subsections (local names, etc.) are not used.
+                _ => {}
+            }
+        }
+        Ok(names)
+    }
+}
+
 /// The kind of an import
 #[derive(Clone, Copy, Debug)]
 enum ImportKind {
@@ -178,18 +178,30 @@ macro_rules! impl_float_ops {
             }
 
             fn ceil(self) -> Result<Self, Trap> {
+                if self.is_nan() {
+                    return Ok(self + self);
+                }
                 Ok(self.ceil())
             }
 
             fn floor(self) -> Result<Self, Trap> {
+                if self.is_nan() {
+                    return Ok(self + self);
+                }
                 Ok(self.floor())
             }
 
             fn trunc(self) -> Result<Self, Trap> {
+                if self.is_nan() {
+                    return Ok(self + self);
+                }
                 Ok(self.trunc())
             }
 
             fn nearest(self) -> Result<Self, Trap> {
+                if self.is_nan() {
+                    return Ok(self + self);
+                }
                 let round = self.round();
                 if self.fract().abs() != 0.5 {
                     Ok(round)
@@ -449,3 +461,254 @@ impl_extend_n!(i8, i64);
 impl_extend_n!(i16, i32);
 impl_extend_n!(i16, i64);
 impl_extend_n!(i32, i64);
+
+// SIMD operations
+
+/// A type that can be used as a lane of a `v128`.
+pub(crate) trait Lane: Copy {
+    /// Reads a lane from the start of the given little-endian bytes.
+    fn read_le(bytes: &[u8]) -> Self;
+
+    /// Writes this lane to the start of the given bytes in little-endian order.
+    fn write_le(self, bytes: &mut [u8]);
+
+    /// Returns a lane with all bits set if `cond` is `true`, or with all bits cleared otherwise.
+    fn mask(cond: bool) -> Self;
+}
+
+macro_rules! impl_lane {
+    ($T:ty, $Bits:ty) => {
+        impl Lane for $T {
+            fn read_le(bytes: &[u8]) -> Self {
+                Self::from_le_bytes(bytes[..mem::size_of::<Self>()].try_into().unwrap())
+            }
+
+            fn write_le(self, bytes: &mut [u8]) {
+                bytes[..mem::size_of::<Self>()].copy_from_slice(&self.to_le_bytes());
+            }
+
+            fn mask(cond: bool) -> Self {
+                Self::from_le_bytes((if cond { <$Bits>::MAX } else { 0 }).to_le_bytes())
+            }
+        }
+    };
+}
+
+impl_lane!(i8, u8);
+impl_lane!(u8, u8);
+impl_lane!(i16, u16);
+impl_lane!(u16, u16);
+impl_lane!(i32, u32);
+impl_lane!(u32, u32);
+impl_lane!(i64, u64);
+impl_lane!(u64, u64);
+impl_lane!(f32, u32);
+impl_lane!(f64, u64);
+
+/// Splits a `v128` into its lanes.
+pub(crate) fn to_lanes<T: Lane, const N: usize>(x: u128) -> [T; N] {
+    let bytes = x.to_le_bytes();
+    let size = bytes.len() / N;
+    std::array::from_fn(|idx| T::read_le(&bytes[idx * size..]))
+}
+
+/// Combines the given lanes into a `v128`.
+pub(crate) fn from_lanes<T: Lane, const N: usize>(lanes: [T; N]) -> u128 {
+    let mut bytes = [0; 16];
+    let size = bytes.len() / N;
+    for (idx, lane) in lanes.into_iter().enumerate() {
+        lane.write_le(&mut bytes[idx * size..]);
+    }
+    u128::from_le_bytes(bytes)
+}
+
+/// Applies a unary operation to each lane of a `v128`.
+pub(crate) fn lanewise_un_op<T: Lane, const N: usize>(x: u128, f: impl Fn(T) -> T) -> u128 {
+    from_lanes(to_lanes::<T, N>(x).map(f))
+}
+
+/// Applies a binary operation to each pair of corresponding lanes of two `v128`s.
+pub(crate) fn lanewise_bin_op<T: Lane, const N: usize>(
+    x: u128,
+    y: u128,
+    f: impl Fn(T, T) -> T,
+) -> u128 {
+    let x = to_lanes::<T, N>(x);
+    let y = to_lanes::<T, N>(y);
+    from_lanes::<T, N>(std::array::from_fn(|idx| f(x[idx], y[idx])))
+}
+
+/// Applies a comparison to each pair of corresponding lanes of two `v128`s.
+///
+/// Each lane of the result has all bits set if the comparison is `true`, or all bits cleared
+/// otherwise.
+pub(crate) fn lanewise_rel_op<T: Lane, const N: usize>(
+    x: u128,
+    y: u128,
+    f: impl Fn(T, T) -> bool,
+) -> u128 {
+    lanewise_bin_op::<T, N>(x, y, |x, y| T::mask(f(x, y)))
+}
+
+/// Applies a shift to each lane of a `v128`.
+pub(crate) fn lanewise_shift_op<T: Lane, const N: usize>(
+    x: u128,
+    y: u32,
+    f: impl Fn(T, u32) -> T,
+) -> u128 {
+    lanewise_un_op::<T, N>(x, |x| f(x, y))
+}
+
+/// Creates a `v128` with all lanes set to the given value.
+pub(crate) fn splat<T: Lane, const N: usize>(x: T) -> u128 {
+    from_lanes::<T, N>([x; N])
+}
+
+/// Returns the lane with the given index of a `v128`.
+pub(crate) fn extract_lane<T: Lane, const N: usize>(x: u128, lane_idx: u8) -> T {
+    to_lanes::<T, N>(x)[lane_idx as usize]
+}
+
+/// Replaces the lane with the given index of a `v128`.
+pub(crate) fn replace_lane<T: Lane, const N: usize>(x: u128, lane_idx: u8, y: T) -> u128 {
+    let mut lanes = to_lanes::<T, N>(x);
+    lanes[lane_idx as usize] = y;
+    from_lanes(lanes)
+}
+
+/// Returns `1` if all lanes of a `v128` are non-zero, or `0` otherwise.
+pub(crate) fn all_true<T: Lane + Default + PartialEq, const N: usize>(x: u128) -> i32 {
+    to_lanes::<T, N>(x)
+        .into_iter()
+        .all(|lane| lane != T::default())
+        .into()
+}
+
+/// Returns a mask with bit `i` set if the high bit of lane `i` of a `v128` is set.
+pub(crate) fn bitmask<T: Lane + Default + PartialOrd, const N: usize>(x: u128) -> i32 {
+    to_lanes::<T, N>(x)
+        .into_iter()
+        .enumerate()
+        .fold(0, |mask, (idx, lane)| {
+            mask | (((lane < T::default()) as i32) << idx)
+        })
+}
+
+/// Narrows the lanes of two `v128`s to lanes of half the width, with saturation.
+pub(crate) fn narrow<T, U, const N: usize, const M: usize>(x: u128, y: u128) -> u128
+where
+    T: Lane,
+    U: Lane + Saturate<T>,
+{
+    let x = to_lanes::<T, N>(x);
+    let y = to_lanes::<T, N>(y);
+    from_lanes::<U, M>(std::array::from_fn(|idx| {
+        U::saturate(if idx < N { x[idx] } else { y[idx - N] })
+    }))
+}
+
+/// Extends the low or high half of the lanes of a `v128` to lanes of twice the width.
+pub(crate) fn extend<T, U, const N: usize, const M: usize>(x: u128, high: bool) -> u128
+where
+    T: Lane + Into<U>,
+    U: Lane,
+{
+    let x = to_lanes::<T, N>(x);
+    let offset = if high { M } else { 0 };
+    from_lanes::<U, M>(std::array::from_fn(|idx| x[offset + idx].into()))
+}
+
+/// Multiplies the low or high half of the lanes of two `v128`s, producing lanes of twice the
+/// width.
+pub(crate) fn extmul<T, U, const N: usize, const M: usize>(
+    x: u128,
+    y: u128,
+    high: bool,
+    mul: impl Fn(U, U) -> U,
+) -> u128
+where
+    T: Lane + Into<U>,
+    U: Lane,
+{
+    let x = to_lanes::<T, N>(x);
+    let y = to_lanes::<T, N>(y);
+    let offset = if high { M } else { 0 };
+    from_lanes::<U, M>(std::array::from_fn(|idx| {
+        mul(x[offset + idx].into(), y[offset + idx].into())
+    }))
+}
+
+/// Adds adjacent pairs of lanes of a `v128`, producing lanes of twice the width.
+pub(crate) fn extadd_pairwise<T, U, const N: usize, const M: usize>(
+    x: u128,
+    add: impl Fn(U, U) -> U,
+) -> u128
+where
+    T: Lane + Into<U>,
+    U: Lane,
+{
+    let x = to_lanes::<T, N>(x);
+    from_lanes::<U, M>(std::array::from_fn(|idx| {
+        add(x[2 * idx].into(), x[2 * idx + 1].into())
+    }))
+}
+
+/// Computes the dot product of the signed 16-bit lanes of two `v128`s.
+pub(crate) fn dot_i16x8_s(x: u128, y: u128) -> u128 {
+    let x = to_lanes::<i16, 8>(x);
+    let y = to_lanes::<i16, 8>(y);
+    from_lanes::<i32, 4>(std::array::from_fn(|idx| {
+        (x[2 * idx] as i32 * y[2 * idx] as i32)
+            .wrapping_add(x[2 * idx + 1] as i32 * y[2 * idx + 1] as i32)
+    }))
+}
+
+/// Performs a saturating, rounding Q15 multiplication of the signed 16-bit lanes of two `v128`s.
+pub(crate) fn q15mulr_sat_s(x: u128, y: u128) -> u128 {
+    lanewise_bin_op::<i16, 8>(x, y, |x, y| {
+        i16::saturate((x as i32 * y as i32 + 0x4000) >> 15)
+    })
+}
+
+/// Selects the lanes of the first `v128` using the indices in the second `v128`.
+///
+/// Indices that are out of range select a zero lane.
+pub(crate) fn swizzle(x: u128, y: u128) -> u128 {
+    let x = to_lanes::<u8, 16>(x);
+    let y = to_lanes::<u8, 16>(y);
+    from_lanes::<u8, 16>(y.map(|idx| x.get(idx as usize).copied().unwrap_or(0)))
+}
+
+/// Selects the lanes of the concatenation of two `v128`s using the given indices.
+pub(crate) fn shuffle(x: u128, y: u128, lane_idxs: [u8; 16]) -> u128 {
+    let x = to_lanes::<u8, 16>(x);
+    let y = to_lanes::<u8, 16>(y);
+    from_lanes::<u8, 16>(lane_idxs.map(|idx| {
+        let idx = idx as usize;
+        if idx < 16 {
+            x[idx]
+        } else {
+            y[idx - 16]
+        }
+    }))
+}
+
+/// Converts a value to a narrower integer type, with saturation.
+pub(crate) trait Saturate<T>: Sized {
+    fn saturate(val: T) -> Self;
+}
+
+macro_rules! impl_saturate {
+    ($T:ty, $U:ty) => {
+        impl Saturate<$T> for $U {
+            fn saturate(val: $T) -> Self {
+                val.clamp(<$U>::MIN as $T, <$U>::MAX as $T) as $U
+            }
+        }
+    };
+}
+
+impl_saturate!(i16, i8);
+impl_saturate!(i16, u8);
+impl_saturate!(i32, i16);
+impl_saturate!(i32, u16);
@@ -0,0 +1,259 @@
+use {
+    crate::{
+        aliasable_box::AliasableBox,
+        code::{Code, InstrSlot},
+        exec::{Ip, Sp},
+        func::{FuncEntity, UnguardedFunc},
+    },
+    std::{
+        collections::{hash_map, HashMap},
+        fmt,
+        fmt::Write,
+        sync::Arc,
+    },
+};
+
+/// A profile of the Wasm functions that were executed in a [`Store`](crate::Store).
+///
+/// A [`Profile`] is only recorded if the [`Store`](crate::Store) was created with an
+/// [`Engine`](crate::Engine) that has profiling enabled. For each Wasm function, it records how
+/// often the function was called, and exactly how many Wasm instructions were executed in it. In
+/// addition, it periodically samples the call stack, so that the instructions can be attributed
+/// to the stacks they were executed in.
+#[derive(Debug)]
+pub struct Profile {
+    sample_interval: u64,
+    unsampled_instr_count: u64,
+    funcs: HashMap<UnguardedFunc, FuncProfile>,
+    stacks: HashMap<Box<[UnguardedFunc]>, u64>,
+    // The range of compiled code for each compiled Wasm function, ordered by start address.
+    code_ranges: Vec<(*const InstrSlot, *const InstrSlot, UnguardedFunc)>,
+    frames: Vec<UnguardedFunc>,
+}
+
+impl Profile {
+    /// Creates a new, empty [`Profile`] that samples the call stack every `sample_interval`
+    /// instructions.
+    pub(crate) fn new(sample_interval: u64) -> Self {
+        Self {
+            sample_interval,
+            unsampled_instr_count: 0,
+            funcs: HashMap::new(),
+            stacks: HashMap::new(),
+            code_ranges: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the profiles of the Wasm functions that were called, in no
+    /// particular order.
+    pub fn funcs(&self) -> ProfileFuncs<'_> {
+        ProfileFuncs {
+            iter: self.funcs.values(),
+        }
+    }
+
+    /// Returns the sampled call stacks in this [`Profile`] in the folded stack format, which is
+    /// understood by most flame graph tools.
+    ///
+    /// Each line consists of the names of the functions in a call stack, starting with the
+    /// outermost one and separated by semicolons, followed by a space and the number of
+    /// instructions that were executed in that stack.
+    pub fn folded_stacks(&self) -> String {
+        let mut lines: Vec<_> = self
+            .stacks
+            .iter()
+            .map(|(frames, &instr_count)| {
+                let mut line = String::new();
+                for (idx, func) in frames.iter().enumerate() {
+                    if idx > 0 {
+                        line.push(';');
+                    }
+                    write!(line, "{}", self.funcs[func]).unwrap();
+                }
+                write!(line, " {}", instr_count).unwrap();
+                line
+            })
+            .collect();
+        lines.sort();
+        lines.into_iter().fold(String::new(), |mut string, line| {
+            string.push_str(&line);
+            string.push('\n');
+            string
+        })
+    }
+
+    /// Records a call to the given Wasm function.
+    pub(crate) fn record_call(&mut self, func: UnguardedFunc) {
+        self.func_profile(func).call_count += 1;
+    }
+
+    /// Records that the given number of instructions were executed at the given instruction
+    /// pointer, with the given stack pointer.
+    ///
+    /// # Safety
+    ///
+    /// The call frames on the stack must form a chain from the given stack pointer to the given
+    /// pointer to the start of the call frame of the function that was called from the host.
+    pub(crate) unsafe fn record_instrs(
+        &mut self,
+        funcs: &[AliasableBox<FuncEntity>],
+        ip: Ip,
+        sp: Sp,
+        ptr: Sp,
+        instr_count: u64,
+    ) {
+        let Some(func) = self.func_at(funcs, ip) else {
+            return;
+        };
+        self.func_profile(func).instr_count += instr_count;
+        self.unsampled_instr_count += instr_count;
+        if self.unsampled_instr_count < self.sample_interval {
+            return;
+        }
+
+        // Walk the call frames on the stack, in the same way as when creating a backtrace.
+        let mut frames = std::mem::take(&mut self.frames);
+        frames.clear();
+        let mut ip = ip;
+        let mut sp = sp;
+        while sp != ptr {
+            frames.extend(self.func_at(funcs, ip));
+
+            // The instruction pointer in a call frame points past the start of the call
+            // instruction.
+            ip = (*sp.offset(-4).cast::<Ip>()).offset(-1);
+            sp = *sp.offset(-3).cast();
+        }
+        frames.reverse();
+        for &func in frames.iter() {
+            self.func_profile(func);
+        }
+        match self.stacks.get_mut(&*frames) {
+            Some(count) => *count += self.unsampled_instr_count,
+            None => {
+                self.stacks
+                    .insert(frames.as_slice().into(), self.unsampled_instr_count);
+            }
+        }
+        self.unsampled_instr_count = 0;
+        self.frames = frames;
+    }
+
+    /// Returns the [`FuncProfile`] for the given Wasm function, creating it if it does not exist.
+    fn func_profile(&mut self, func: UnguardedFunc) -> &mut FuncProfile {
+        self.funcs.entry(func).or_insert_with(|| {
+            let FuncEntity::Wasm(func) = (unsafe { func.as_ref() }) else {
+                unreachable!();
+            };
+            FuncProfile {
+                module_name: func.names().module.clone(),
+                func_idx: func.idx(),
+                func_name: func.names().funcs.get(&func.idx()).cloned(),
+                call_count: 0,
+                instr_count: 0,
+            }
+        })
+    }
+
+    /// Returns the Wasm function whose compiled code contains the given instruction pointer, if
+    /// any.
+    fn func_at(&mut self, funcs: &[AliasableBox<FuncEntity>], ip: Ip) -> Option<UnguardedFunc> {
+        let ip = ip as *const InstrSlot;
+        if let Some(func) = self.lookup_code_range(ip) {
+            return Some(func);
+        }
+
+        // Functions are compiled lazily, so the code range might not be known yet.
+        self.code_ranges = funcs
+            .iter()
+            .filter_map(|func| match &**func {
+                FuncEntity::Wasm(wasm_func) => match wasm_func.code() {
+                    Code::Compiled(code) => {
+                        let range = code.code.as_ptr_range();
+                        Some((range.start, range.end, func.as_raw()))
+                    }
+                    _ => None,
+                },
+                FuncEntity::Host(_) => None,
+            })
+            .collect();
+        self.code_ranges.sort_by_key(|&(start, _, _)| start);
+        self.lookup_code_range(ip)
+    }
+
+    fn lookup_code_range(&self, ip: *const InstrSlot) -> Option<UnguardedFunc> {
+        let pos = self
+            .code_ranges
+            .partition_point(|&(start, _, _)| start <= ip);
+        let &(_, end, func) = self.code_ranges.get(pos.checked_sub(1)?)?;
+        if ip >= end {
+            return None;
+        }
+        Some(func)
+    }
+}
+
+/// An iterator over the [`FuncProfile`]s in a [`Profile`].
+#[derive(Clone, Debug)]
+pub struct ProfileFuncs<'a> {
+    iter: hash_map::Values<'a, UnguardedFunc, FuncProfile>,
+}
+
+impl<'a> Iterator for ProfileFuncs<'a> {
+    type Item = &'a FuncProfile;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+/// The profile of a single Wasm function in a [`Profile`].
+#[derive(Clone, Debug)]
+pub struct FuncProfile {
+    module_name: Option<Arc<str>>,
+    func_idx: u32,
+    func_name: Option<Arc<str>>,
+    call_count: u64,
+    instr_count: u64,
+}
+
+impl FuncProfile {
+    /// Returns the name of the module for this [`FuncProfile`], if it has one in its name
+    /// section.
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name.as_deref()
+    }
+
+    /// Returns the index of the function for this [`FuncProfile`] in its module.
+    pub fn func_idx(&self) -> u32 {
+        self.func_idx
+    }
+
+    /// Returns the name of the function for this [`FuncProfile`], if it has one in the name
+    /// section of its module.
+    pub fn func_name(&self) -> Option<&str> {
+        self.func_name.as_deref()
+    }
+
+    /// Returns the number of times the function was called.
+    pub fn call_count(&self) -> u64 {
+        self.call_count
+    }
+
+    /// Returns the number of Wasm instructions that were executed in the function itself,
+    /// excluding the functions it called.
+    pub fn instr_count(&self) -> u64 {
+        self.instr_count
+    }
+}
+
+impl fmt::Display for FuncProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}!", self.module_name.as_deref().unwrap_or("<unknown>"))?;
+        match &self.func_name {
+            Some(func_name) => write!(f, "{}", func_name),
+            None => write!(f, "<wasm function {}>", self.func_idx),
+        }
+    }
+}
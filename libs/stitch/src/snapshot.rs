@@ -0,0 +1,494 @@
+use {
+    crate::{
+        decode::{DecodeError, Decoder},
+        extern_ref::ExternRef,
+        func::Func,
+        func_ref::FuncRef,
+        global::Mut,
+        instance::Instance,
+        mem::PAGE_SIZE,
+        ref_::{Ref, RefType},
+        store::Store,
+        val::{Val, ValType},
+    },
+    std::{collections::HashMap, error::Error, fmt},
+};
+
+const MAGIC: [u8; 4] = *b"stsn";
+const VERSION: u32 = 1;
+
+/// A snapshot of the state of an [`Instance`] in a [`Store`].
+///
+/// A [`Snapshot`] captures the contents of the memories, globals and tables of an [`Instance`],
+/// as well as which of its element and data segments have been dropped. It can be serialized to
+/// bytes, and restored into any [`Instance`] of the same [`Module`](crate::Module), including one
+/// in a different [`Store`].
+///
+/// References to functions are captured as indices into the function index space of the
+/// [`Instance`], so they are restored as references to the corresponding functions in the target
+/// [`Instance`]. Non-null external references cannot be captured.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    module_fingerprint: u64,
+    mems: Vec<Box<[u8]>>,
+    globals: Vec<SnapshotVal>,
+    tables: Vec<SnapshotTable>,
+    dropped_elems: Vec<bool>,
+    dropped_datas: Vec<bool>,
+}
+
+impl Snapshot {
+    /// Captures a [`Snapshot`] of the given [`Instance`] in the given [`Store`].
+    ///
+    /// # Errors
+    ///
+    /// - If a function call in the [`Store`] is suspended.
+    /// - If a global or table contains a reference that cannot be captured.
+    pub(crate) fn capture(store: &Store, instance: &Instance) -> Result<Self, SnapshotError> {
+        if store.is_suspended() {
+            return Err(SnapshotError::Suspended);
+        }
+        let func_idxs: HashMap<Func, u32> = (0..)
+            .map_while(|idx| instance.func(idx).map(|func| (func, idx)))
+            .collect();
+        let capture_ref = |ref_: Ref| -> Result<Option<u32>, SnapshotError> {
+            match ref_ {
+                Ref::FuncRef(func_ref) => match func_ref.get() {
+                    Some(func) => func_idxs
+                        .get(&func)
+                        .copied()
+                        .map(Some)
+                        .ok_or(SnapshotError::UnsupportedRef),
+                    None => Ok(None),
+                },
+                Ref::ExternRef(extern_ref) if extern_ref.is_null() => Ok(None),
+                Ref::ExternRef(_) => Err(SnapshotError::UnsupportedRef),
+            }
+        };
+        let mems = (0..)
+            .map_while(|idx| instance.mem(idx))
+            .map(|mem| mem.bytes(store).into())
+            .collect();
+        let globals = (0..)
+            .map_while(|idx| instance.global(idx))
+            .map(|global| {
+                Ok(match global.get(store) {
+                    Val::I32(val) => SnapshotVal::I32(val),
+                    Val::I64(val) => SnapshotVal::I64(val),
+                    Val::F32(val) => SnapshotVal::F32(val),
+                    Val::F64(val) => SnapshotVal::F64(val),
+                    Val::V128(val) => SnapshotVal::V128(val),
+                    Val::FuncRef(val) => SnapshotVal::FuncRef(capture_ref(val.into())?),
+                    Val::ExternRef(val) => {
+                        capture_ref(val.into())?;
+                        SnapshotVal::ExternRef
+                    }
+                })
+            })
+            .collect::<Result<_, SnapshotError>>()?;
+        let tables = (0..)
+            .map_while(|idx| instance.table(idx))
+            .map(|table| {
+                Ok(SnapshotTable {
+                    type_: table.type_(store).elem,
+                    elems: (0..table.size(store))
+                        .map(|idx| capture_ref(table.get(store, idx).unwrap()))
+                        .collect::<Result<_, SnapshotError>>()?,
+                })
+            })
+            .collect::<Result<_, SnapshotError>>()?;
+        let dropped_elems = (0..)
+            .map_while(|idx| instance.elem(idx))
+            .map(|elem| elem.is_dropped(store))
+            .collect();
+        let dropped_datas = (0..)
+            .map_while(|idx| instance.data(idx))
+            .map(|data| data.is_dropped(store))
+            .collect();
+        Ok(Self {
+            module_fingerprint: instance.module_fingerprint(),
+            mems,
+            globals,
+            tables,
+            dropped_elems,
+            dropped_datas,
+        })
+    }
+
+    /// Restores this [`Snapshot`] into the given [`Instance`] in the given [`Store`].
+    ///
+    /// Memories and tables that are smaller than in this [`Snapshot`] are grown to match.
+    /// Immutable globals are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// - If a function call in the [`Store`] is suspended.
+    /// - If the [`Instance`] was not instantiated from the same [`Module`](crate::Module) as the
+    ///   one this [`Snapshot`] was captured from.
+    /// - If the state of the [`Instance`] cannot be made to match this [`Snapshot`], for instance
+    ///   because a memory or table is larger than in this [`Snapshot`].
+    /// - If a memory or table failed to grow.
+    pub(crate) fn restore(
+        &self,
+        store: &mut Store,
+        instance: &Instance,
+    ) -> Result<(), SnapshotError> {
+        if store.is_suspended() {
+            return Err(SnapshotError::Suspended);
+        }
+        if instance.module_fingerprint() != self.module_fingerprint {
+            return Err(SnapshotError::ModuleMismatch);
+        }
+
+        // Check that the instance can be made to match this snapshot before changing anything.
+        let mems: Vec<_> = (0..).map_while(|idx| instance.mem(idx)).collect();
+        let globals: Vec<_> = (0..).map_while(|idx| instance.global(idx)).collect();
+        let tables: Vec<_> = (0..).map_while(|idx| instance.table(idx)).collect();
+        let elems: Vec<_> = (0..).map_while(|idx| instance.elem(idx)).collect();
+        let datas: Vec<_> = (0..).map_while(|idx| instance.data(idx)).collect();
+        let funcs: Vec<_> = (0..).map_while(|idx| instance.func(idx)).collect();
+        if mems.len() != self.mems.len()
+            || globals.len() != self.globals.len()
+            || tables.len() != self.tables.len()
+            || elems.len() != self.dropped_elems.len()
+            || datas.len() != self.dropped_datas.len()
+        {
+            return Err(SnapshotError::StateMismatch);
+        }
+        for (mem, bytes) in mems.iter().zip(self.mems.iter()) {
+            let max = mem.type_(store).limits.max.unwrap_or(u64::MAX);
+            let size = bytes.len() / PAGE_SIZE;
+            if mem.bytes(store).len() > bytes.len() || size as u64 > max {
+                return Err(SnapshotError::StateMismatch);
+            }
+        }
+        for (global, val) in globals.iter().zip(self.globals.iter()) {
+            if global.type_(store).val != val.type_() {
+                return Err(SnapshotError::StateMismatch);
+            }
+        }
+        for (table, snapshot) in tables.iter().zip(self.tables.iter()) {
+            let type_ = table.type_(store);
+            let size = u32::try_from(snapshot.elems.len()).unwrap();
+            if type_.elem != snapshot.type_
+                || table.size(store) > size
+                || type_.limits.max.is_some_and(|max| u64::from(size) > max)
+            {
+                return Err(SnapshotError::StateMismatch);
+            }
+        }
+        let refs = self
+            .globals
+            .iter()
+            .filter_map(|val| match *val {
+                SnapshotVal::FuncRef(idx) => Some(idx),
+                _ => None,
+            })
+            .chain(
+                self.tables
+                    .iter()
+                    .flat_map(|table| table.elems.iter().copied()),
+            );
+        for idx in refs.flatten() {
+            if idx as usize >= funcs.len() {
+                return Err(SnapshotError::StateMismatch);
+            }
+        }
+
+        let restore_ref = |type_: RefType, idx: Option<u32>| -> Ref {
+            match type_ {
+                RefType::FuncRef => FuncRef::new(idx.map(|idx| funcs[idx as usize])).into(),
+                RefType::ExternRef => ExternRef::null().into(),
+            }
+        };
+        for (mem, bytes) in mems.iter().zip(self.mems.iter()) {
+            let count = (bytes.len() - mem.bytes(store).len()) / PAGE_SIZE;
+            if count > 0 {
+                mem.grow(store, u32::try_from(count).unwrap())
+                    .map_err(|_| SnapshotError::FailedToGrow)?;
+            }
+            mem.bytes_mut(store).copy_from_slice(bytes);
+        }
+        for (global, val) in globals.iter().zip(self.globals.iter()) {
+            if global.type_(store).mut_ != Mut::Var {
+                continue;
+            }
+            let val = match *val {
+                SnapshotVal::I32(val) => Val::I32(val),
+                SnapshotVal::I64(val) => Val::I64(val),
+                SnapshotVal::F32(val) => Val::F32(val),
+                SnapshotVal::F64(val) => Val::F64(val),
+                SnapshotVal::V128(val) => Val::V128(val),
+                SnapshotVal::FuncRef(idx) => restore_ref(RefType::FuncRef, idx).into(),
+                SnapshotVal::ExternRef => restore_ref(RefType::ExternRef, None).into(),
+            };
+            global.set(store, val).unwrap();
+        }
+        for (table, snapshot) in tables.iter().zip(self.tables.iter()) {
+            let size = u32::try_from(snapshot.elems.len()).unwrap();
+            let count = size - table.size(store);
+            if count > 0 {
+                table
+                    .grow(store, Ref::null(snapshot.type_), count)
+                    .map_err(|_| SnapshotError::FailedToGrow)?;
+            }
+            for (idx, &elem) in (0u32..).zip(snapshot.elems.iter()) {
+                table
+                    .set(store, idx, restore_ref(snapshot.type_, elem))
+                    .unwrap();
+            }
+        }
+        for (elem, &is_dropped) in elems.iter().zip(self.dropped_elems.iter()) {
+            elem.set_dropped(store, is_dropped);
+        }
+        for (data, &is_dropped) in datas.iter().zip(self.dropped_datas.iter()) {
+            data.set_dropped(store, is_dropped);
+        }
+        Ok(())
+    }
+
+    /// Serializes this [`Snapshot`] to bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        write_u64(&mut bytes, VERSION.into());
+        bytes.extend_from_slice(&self.module_fingerprint.to_le_bytes());
+        write_u64(&mut bytes, self.mems.len() as u64);
+        for mem in self.mems.iter() {
+            write_u64(&mut bytes, mem.len() as u64);
+            bytes.extend_from_slice(mem);
+        }
+        write_u64(&mut bytes, self.globals.len() as u64);
+        for val in self.globals.iter() {
+            match *val {
+                SnapshotVal::I32(val) => {
+                    bytes.push(0x7F);
+                    bytes.extend_from_slice(&val.to_le_bytes());
+                }
+                SnapshotVal::I64(val) => {
+                    bytes.push(0x7E);
+                    bytes.extend_from_slice(&val.to_le_bytes());
+                }
+                SnapshotVal::F32(val) => {
+                    bytes.push(0x7D);
+                    bytes.extend_from_slice(&val.to_le_bytes());
+                }
+                SnapshotVal::F64(val) => {
+                    bytes.push(0x7C);
+                    bytes.extend_from_slice(&val.to_le_bytes());
+                }
+                SnapshotVal::V128(val) => {
+                    bytes.push(0x7B);
+                    bytes.extend_from_slice(&val.to_le_bytes());
+                }
+                SnapshotVal::FuncRef(idx) => {
+                    bytes.push(0x70);
+                    write_ref(&mut bytes, idx);
+                }
+                SnapshotVal::ExternRef => {
+                    bytes.push(0x6F);
+                }
+            }
+        }
+        write_u64(&mut bytes, self.tables.len() as u64);
+        for table in self.tables.iter() {
+            bytes.push(match table.type_ {
+                RefType::FuncRef => 0x70,
+                RefType::ExternRef => 0x6F,
+            });
+            write_u64(&mut bytes, table.elems.len() as u64);
+            for &idx in table.elems.iter() {
+                write_ref(&mut bytes, idx);
+            }
+        }
+        for dropped in [&self.dropped_elems, &self.dropped_datas] {
+            write_u64(&mut bytes, dropped.len() as u64);
+            for &is_dropped in dropped.iter() {
+                bytes.push(is_dropped.into());
+            }
+        }
+        bytes
+    }
+
+    /// Deserializes a [`Snapshot`] from the given bytes.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes are not a snapshot, or were produced by an incompatible version of this
+    ///   crate.
+    /// - If the bytes are malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut decoder = Decoder::new(bytes);
+        if decoder.read_bytes(4)? != MAGIC {
+            return Err(SnapshotError::Malformed);
+        }
+        if decoder.decode::<u32>()? != VERSION {
+            return Err(SnapshotError::VersionMismatch);
+        }
+        let module_fingerprint = u64::from_le_bytes(decoder.read_bytes(8)?.try_into().unwrap());
+        let mut mems = Vec::new();
+        for _ in 0..decoder.decode::<u32>()? {
+            let len = usize::try_from(decoder.decode::<u64>()?)
+                .ok()
+                .filter(|&len| len % PAGE_SIZE == 0 && len / PAGE_SIZE <= 65_536)
+                .ok_or(SnapshotError::Malformed)?;
+            mems.push(decoder.read_bytes(len)?.into());
+        }
+        let mut globals = Vec::new();
+        for _ in 0..decoder.decode::<u32>()? {
+            globals.push(match decoder.read_byte()? {
+                0x7F => SnapshotVal::I32(i32::from_le_bytes(
+                    decoder.read_bytes(4)?.try_into().unwrap(),
+                )),
+                0x7E => SnapshotVal::I64(i64::from_le_bytes(
+                    decoder.read_bytes(8)?.try_into().unwrap(),
+                )),
+                0x7D => SnapshotVal::F32(decoder.decode()?),
+                0x7C => SnapshotVal::F64(decoder.decode()?),
+                0x7B => SnapshotVal::V128(decoder.decode()?),
+                0x70 => SnapshotVal::FuncRef(read_ref(&mut decoder)?),
+                0x6F => SnapshotVal::ExternRef,
+                _ => return Err(SnapshotError::Malformed),
+            });
+        }
+        let mut tables = Vec::new();
+        for _ in 0..decoder.decode::<u32>()? {
+            let type_ = match decoder.read_byte()? {
+                0x70 => RefType::FuncRef,
+                0x6F => RefType::ExternRef,
+                _ => return Err(SnapshotError::Malformed),
+            };
+            let mut elems = Vec::new();
+            for _ in 0..decoder.decode::<u32>()? {
+                let idx = read_ref(&mut decoder)?;
+                if type_ == RefType::ExternRef && idx.is_some() {
+                    return Err(SnapshotError::Malformed);
+                }
+                elems.push(idx);
+            }
+            tables.push(SnapshotTable { type_, elems });
+        }
+        let mut dropped_elems = Vec::new();
+        for _ in 0..decoder.decode::<u32>()? {
+            dropped_elems.push(read_bool(&mut decoder)?);
+        }
+        let mut dropped_datas = Vec::new();
+        for _ in 0..decoder.decode::<u32>()? {
+            dropped_datas.push(read_bool(&mut decoder)?);
+        }
+        if !decoder.is_at_end() {
+            return Err(SnapshotError::Malformed);
+        }
+        Ok(Self {
+            module_fingerprint,
+            mems,
+            globals,
+            tables,
+            dropped_elems,
+            dropped_datas,
+        })
+    }
+}
+
+/// An error that can occur when capturing, restoring, or deserializing a [`Snapshot`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    Malformed,
+    VersionMismatch,
+    ModuleMismatch,
+    StateMismatch,
+    UnsupportedRef,
+    Suspended,
+    FailedToGrow,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed snapshot"),
+            Self::VersionMismatch => write!(f, "snapshot version mismatch"),
+            Self::ModuleMismatch => write!(f, "snapshot was captured from a different module"),
+            Self::StateMismatch => write!(f, "snapshot does not match the instance"),
+            Self::UnsupportedRef => write!(f, "snapshot cannot capture host references"),
+            Self::Suspended => write!(f, "store has a suspended function call"),
+            Self::FailedToGrow => write!(f, "failed to grow memory or table"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<DecodeError> for SnapshotError {
+    fn from(_: DecodeError) -> Self {
+        SnapshotError::Malformed
+    }
+}
+
+/// A value of a global in a [`Snapshot`].
+#[derive(Clone, Copy, Debug)]
+enum SnapshotVal {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    V128(u128),
+    FuncRef(Option<u32>),
+    ExternRef,
+}
+
+impl SnapshotVal {
+    fn type_(self) -> ValType {
+        match self {
+            Self::I32(_) => ValType::I32,
+            Self::I64(_) => ValType::I64,
+            Self::F32(_) => ValType::F32,
+            Self::F64(_) => ValType::F64,
+            Self::V128(_) => ValType::V128,
+            Self::FuncRef(_) => ValType::FuncRef,
+            Self::ExternRef => ValType::ExternRef,
+        }
+    }
+}
+
+/// The elements of a table in a [`Snapshot`].
+#[derive(Clone, Debug)]
+struct SnapshotTable {
+    type_: RefType,
+    elems: Vec<Option<u32>>,
+}
+
+/// Writes the given value as an unsigned LEB128 integer.
+fn write_u64(bytes: &mut Vec<u8>, mut val: u64) {
+    loop {
+        let byte = (val & 0x7F) as u8;
+        val >>= 7;
+        if val == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Writes the given function index, or `None` for a null reference.
+fn write_ref(bytes: &mut Vec<u8>, idx: Option<u32>) {
+    write_u64(bytes, idx.map_or(0, |idx| u64::from(idx) + 1));
+}
+
+/// Reads a function index written by [`write_ref`].
+fn read_ref(decoder: &mut Decoder<'_>) -> Result<Option<u32>, SnapshotError> {
+    Ok(match decoder.decode::<u64>()? {
+        0 => None,
+        idx => Some(u32::try_from(idx - 1).map_err(|_| SnapshotError::Malformed)?),
+    })
+}
+
+/// Reads a boolean written as a single byte.
+fn read_bool(decoder: &mut Decoder<'_>) -> Result<bool, SnapshotError> {
+    match decoder.read_byte()? {
+        0x00 => Ok(false),
+        0x01 => Ok(true),
+        _ => Err(SnapshotError::Malformed),
+    }
+}
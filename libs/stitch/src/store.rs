@@ -1,20 +1,34 @@
 use {
     crate::{
         aliasable_box::AliasableBox,
+        code::InstrSlot,
         data::DataEntity,
         elem::ElemEntity,
         engine::Engine,
+        error::Error,
+        exec,
+        exec::{ExecState, Ip, Sp},
         extern_::ExternEntity,
-        func::{FuncEntity, FuncType},
+        func::UnguardedFunc,
+        func::{FuncEntity, FuncError, FuncType},
         global::GlobalEntity,
-        mem::MemEntity,
+        instance::Instance,
+        mem::{Mem, MemEntity, UnguardedMem},
+        profile::Profile,
+        snapshot::{Snapshot, SnapshotError},
         table::TableEntity,
+        trap::{BacktraceFrame, Trap},
+        val::Val,
     },
     std::{
         collections::HashMap,
         fmt,
         hash::{Hash, Hasher},
         ptr::NonNull,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
     },
 };
 
@@ -31,12 +45,18 @@ pub struct Store {
     elems: Vec<AliasableBox<ElemEntity>>,
     datas: Vec<AliasableBox<DataEntity>>,
     externs: Vec<AliasableBox<ExternEntity>>,
+    fuel: Option<u64>,
+    interrupt: Arc<AtomicBool>,
+    suspended: Option<ExecState>,
+    caller_mem: Option<UnguardedMem>,
+    profile: Option<Profile>,
 }
 
 impl Store {
     pub fn new(engine: Engine) -> Self {
         let id = StoreId::new();
         Self {
+            profile: engine.profile_interval().map(Profile::new),
             engine,
             id,
             types: FuncTypeInterner::new(id),
@@ -47,6 +67,10 @@ impl Store {
             elems: Vec::new(),
             datas: Vec::new(),
             externs: Vec::new(),
+            fuel: None,
+            interrupt: Arc::new(AtomicBool::new(false)),
+            suspended: None,
+            caller_mem: None,
         }
     }
 
@@ -54,6 +78,154 @@ impl Store {
         &self.engine
     }
 
+    /// Returns the amount of fuel left in this [`Store`], or `None` if fuel metering is disabled.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Sets the amount of fuel in this [`Store`].
+    ///
+    /// Every block of Wasm code that is executed consumes fuel. When there is not enough fuel left
+    /// to execute a block, execution is suspended with a [`Trap::OutOfFuel`] trap, and can be
+    /// continued with [`Store::resume`] after adding more fuel.
+    ///
+    /// Passing `None` disables fuel metering.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// Adds the given amount of fuel to this [`Store`].
+    ///
+    /// Does nothing if fuel metering is disabled.
+    pub fn add_fuel(&mut self, fuel: u64) {
+        if let Some(old_fuel) = &mut self.fuel {
+            *old_fuel = old_fuel.saturating_add(fuel);
+        }
+    }
+
+    /// Returns an [`InterruptHandle`] that can be used to interrupt execution in this [`Store`]
+    /// from another thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupt: self.interrupt.clone(),
+        }
+    }
+
+    /// Returns `true` if a function call in this [`Store`] was suspended because it ran out of
+    /// fuel or was interrupted.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.is_some()
+    }
+
+    /// Resumes the function call that was suspended because it ran out of fuel or was
+    /// interrupted.
+    ///
+    /// The results of the original call are written to the `results` slice.
+    ///
+    /// A suspended call keeps the stack of the current thread locked, so it has to be resumed or
+    /// cancelled before a function in another [`Store`] can be called on the same thread. Calling
+    /// a function in this [`Store`] cancels the suspended call.
+    ///
+    /// # Errors
+    ///
+    /// - If there is no suspended function call.
+    /// - If the actual result count does not match the expected result count.
+    /// - If the call traps again, or is suspended again.
+    pub fn resume(&mut self, results: &mut [Val]) -> Result<(), Error> {
+        let Some(state) = self.suspended.take() else {
+            return Err(FuncError::NotSuspended)?;
+        };
+        if results.len() != state.func().type_(self).results().len() {
+            self.suspended = Some(state);
+            return Err(FuncError::ResultCountMismatch)?;
+        }
+        exec::resume(self, state, results)
+    }
+
+    /// Cancels the function call that was suspended because it ran out of fuel or was
+    /// interrupted, if any.
+    pub fn cancel_suspended(&mut self) {
+        self.suspended = None;
+    }
+
+    /// Captures a [`Snapshot`] of the memories, globals, and tables of the given [`Instance`].
+    ///
+    /// # Errors
+    ///
+    /// - If a function call in this [`Store`] is suspended.
+    /// - If a global or table of the [`Instance`] contains a non-null [`ExternRef`], or a
+    ///   reference to a function outside the [`Instance`].
+    ///
+    /// # Panics
+    ///
+    /// If the given [`Instance`] is not owned by this [`Store`].
+    ///
+    /// [`ExternRef`]: crate::ExternRef
+    pub fn snapshot(&self, instance: &Instance) -> Result<Snapshot, SnapshotError> {
+        Snapshot::capture(self, instance)
+    }
+
+    /// Restores the given [`Snapshot`] into the given [`Instance`].
+    ///
+    /// The [`Instance`] must have been instantiated from the same [`Module`](crate::Module) as the
+    /// one the [`Snapshot`] was captured from, but can belong to a different [`Store`].
+    ///
+    /// # Errors
+    ///
+    /// - If a function call in this [`Store`] is suspended.
+    /// - If the [`Instance`] was instantiated from a different [`Module`](crate::Module).
+    /// - If a memory or table of the [`Instance`] is larger than in the [`Snapshot`], or failed to
+    ///   grow to its size.
+    ///
+    /// # Panics
+    ///
+    /// If the given [`Instance`] is not owned by this [`Store`].
+    pub fn restore(
+        &mut self,
+        instance: &Instance,
+        snapshot: &Snapshot,
+    ) -> Result<(), SnapshotError> {
+        snapshot.restore(self, instance)
+    }
+
+    /// Returns the default [`Mem`] of the [`Instance`](crate::Instance) that called the host
+    /// function that is currently executing, if any.
+    ///
+    /// This allows host functions to access the memory of their caller.
+    pub fn caller_mem(&self) -> Option<Mem> {
+        self.caller_mem
+            .map(|mem| unsafe { Mem::from_unguarded(mem, self.id) })
+    }
+
+    /// Returns the [`Profile`] recorded by this [`Store`], or `None` if profiling is not enabled
+    /// for its [`Engine`].
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Records a call to the given Wasm function in the [`Profile`] of this [`Store`], if any.
+    pub(crate) fn profile_call(&mut self, func: UnguardedFunc) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_call(func);
+        }
+    }
+
+    /// Records that the given number of instructions were executed at the given instruction
+    /// pointer in the [`Profile`] of this [`Store`], if any.
+    ///
+    /// # Safety
+    ///
+    /// See [`Profile::record_instrs`].
+    pub(crate) unsafe fn profile_instrs(&mut self, ip: Ip, sp: Sp, ptr: Sp, instr_count: u64) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_instrs(&self.funcs, ip, sp, ptr, instr_count);
+        }
+    }
+
+    pub(crate) fn set_caller_mem(&mut self, mem: Option<UnguardedMem>) {
+        self.caller_mem = mem;
+    }
+
     pub(crate) fn id(&self) -> StoreId {
         self.id
     }
@@ -66,6 +238,46 @@ impl Store {
         self.types.get_or_intern(type_)
     }
 
+    /// Consumes the given amount of fuel, and clears the interrupt flag if it is set.
+    ///
+    /// Fails if execution was interrupted, or there is not enough fuel left.
+    pub(crate) fn consume_fuel(&mut self, cost: u64) -> Result<(), Trap> {
+        if self.interrupt.load(Ordering::Relaxed) && self.interrupt.swap(false, Ordering::Relaxed) {
+            return Err(Trap::Interrupted);
+        }
+        if let Some(fuel) = &mut self.fuel {
+            *fuel = fuel.checked_sub(cost).ok_or(Trap::OutOfFuel)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn suspend(&mut self, state: ExecState) {
+        self.suspended = Some(state);
+    }
+
+    /// Returns a pointer to the data of each [`MemEntity`] in this [`Store`].
+    pub(crate) fn mem_datas(&mut self) -> Vec<*mut u8> {
+        self.mems
+            .iter_mut()
+            .map(|mem| mem.bytes_mut().as_mut_ptr())
+            .collect()
+    }
+
+    /// Returns the data and size of the [`MemEntity`] with the given index in this [`Store`].
+    pub(crate) fn mem_data(&mut self, idx: usize) -> (*mut u8, u64) {
+        let bytes = self.mems[idx].bytes_mut();
+        (bytes.as_mut_ptr(), bytes.len() as u64)
+    }
+
+    /// Returns a [`BacktraceFrame`] for the Wasm function whose compiled code contains the given
+    /// instruction pointer, if any.
+    pub(crate) fn backtrace_frame(&self, ip: *const InstrSlot) -> Option<BacktraceFrame> {
+        self.funcs.iter().find_map(|func| match &**func {
+            FuncEntity::Wasm(func) => func.backtrace_frame(ip),
+            FuncEntity::Host(_) => None,
+        })
+    }
+
     /// Inserts the given [`FuncEntity`] into this [`Store`].
     ///
     /// Returns a [`Handle`] to the inserted [`FuncEntity`].
@@ -138,6 +350,23 @@ impl Store {
     }
 }
 
+/// A handle that can be used to interrupt execution in a [`Store`] from another thread.
+///
+/// Interrupting a [`Store`] suspends the function call that is currently executing in it with a
+/// [`Trap::Interrupted`] trap, the next time it enters a block. The call can be continued with
+/// [`Store::resume`].
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    interrupt: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    /// Interrupts execution in the [`Store`] for this [`InterruptHandle`].
+    pub fn interrupt(&self) {
+        self.interrupt.store(true, Ordering::Relaxed);
+    }
+}
+
 /// A unique identifier for a [`Store`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct StoreId(usize);
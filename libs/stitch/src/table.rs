@@ -210,7 +210,7 @@ impl TableType {
     ///
     /// A [`TableType`] is valid if its [`Limits`] are valid within range `u32::MAX`.
     pub fn is_valid(self) -> bool {
-        if !self.limits.is_valid(u32::MAX) {
+        if !self.limits.is_valid(u32::MAX.into()) {
             return false;
         }
         true
@@ -300,9 +300,9 @@ where
 {
     /// Creates a new [`TableEntityT`] with the given [`Limits`] and initialization value.
     fn new(limits: Limits, val: T) -> Self {
-        let min = limits.min as usize;
+        let min = usize::try_from(limits.min).unwrap();
         Self {
-            max: limits.max,
+            max: limits.max.map(|max| u32::try_from(max).unwrap()),
             elems: vec![val; min],
         }
     }
@@ -310,8 +310,8 @@ where
     /// Returns the [`Limits`] of this [`TableEntity`].
     fn limits(&self) -> Limits {
         Limits {
-            min: u32::try_from(self.elems.len()).unwrap(),
-            max: self.max,
+            min: u64::try_from(self.elems.len()).unwrap(),
+            max: self.max.map(Into::into),
         }
     }
 
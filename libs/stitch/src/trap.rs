@@ -1,4 +1,4 @@
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, sync::Arc};
 
 #[derive(Clone, Copy, Debug)]
 pub enum Trap {
@@ -11,6 +11,8 @@ pub enum Trap {
     TableAccessOutOfBounds,
     MemAccessOutOfBounds,
     StackOverflow,
+    OutOfFuel,
+    Interrupted,
 }
 
 impl Trap {
@@ -25,6 +27,8 @@ impl Trap {
             6 => Some(Self::TableAccessOutOfBounds),
             7 => Some(Self::MemAccessOutOfBounds),
             8 => Some(Self::StackOverflow),
+            9 => Some(Self::OutOfFuel),
+            10 => Some(Self::Interrupted),
             _ => None,
         }
     }
@@ -46,8 +50,103 @@ impl fmt::Display for Trap {
             Self::TableAccessOutOfBounds => write!(f, "table access out of bounds"),
             Self::MemAccessOutOfBounds => write!(f, "memory access out of bounds"),
             Self::StackOverflow => write!(f, "stack overflow"),
+            Self::OutOfFuel => write!(f, "out of fuel"),
+            Self::Interrupted => write!(f, "interrupted"),
         }
     }
 }
 
 impl Error for Trap {}
+
+/// A backtrace of the Wasm functions that were executing when a [`Trap`] occurred.
+///
+/// The innermost frame comes first.
+#[derive(Clone, Debug, Default)]
+pub struct Backtrace {
+    frames: Vec<BacktraceFrame>,
+}
+
+impl Backtrace {
+    /// Creates a new [`Backtrace`] from the given frames.
+    pub(crate) fn new(frames: Vec<BacktraceFrame>) -> Self {
+        Self { frames }
+    }
+
+    /// Returns the frames of this [`Backtrace`], starting with the innermost frame.
+    pub fn frames(&self) -> &[BacktraceFrame] {
+        &self.frames
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, frame) in self.frames.iter().enumerate() {
+            writeln!(f, "{:>4}: {}", idx, frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single frame in a [`Backtrace`].
+#[derive(Clone, Debug)]
+pub struct BacktraceFrame {
+    module_name: Option<Arc<str>>,
+    func_idx: u32,
+    func_name: Option<Arc<str>>,
+    instr_offset: usize,
+}
+
+impl BacktraceFrame {
+    /// Creates a new [`BacktraceFrame`] from its raw parts.
+    pub(crate) fn new(
+        module_name: Option<Arc<str>>,
+        func_idx: u32,
+        func_name: Option<Arc<str>>,
+        instr_offset: usize,
+    ) -> Self {
+        Self {
+            module_name,
+            func_idx,
+            func_name,
+            instr_offset,
+        }
+    }
+
+    /// Returns the name of the module for this [`BacktraceFrame`], if it has one in its name
+    /// section.
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name.as_deref()
+    }
+
+    /// Returns the index of the function for this [`BacktraceFrame`] in its module.
+    pub fn func_idx(&self) -> u32 {
+        self.func_idx
+    }
+
+    /// Returns the name of the function for this [`BacktraceFrame`], if it has one in the name
+    /// section of its module.
+    pub fn func_name(&self) -> Option<&str> {
+        self.func_name.as_deref()
+    }
+
+    /// Returns the offset in the module of the instruction that was executing in this
+    /// [`BacktraceFrame`].
+    pub fn instr_offset(&self) -> usize {
+        self.instr_offset
+    }
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:#x} - {}!",
+            self.instr_offset,
+            self.module_name.as_deref().unwrap_or("<unknown>")
+        )?;
+        match &self.func_name {
+            Some(func_name) => write!(f, "{}", func_name),
+            None => write!(f, "<wasm function {}>", self.func_idx),
+        }
+    }
+}
@@ -2,12 +2,13 @@ use {
     crate::{
         code,
         code::{
-            BinOpInfo, BlockType, InstrVisitor, LoadInfo, MemArg, StoreInfo, UnOpInfo,
-            UncompiledCode,
+            BinOpInfo, BlockType, InstrVisitor, LoadInfo, MemArg, SimdImm, SimdOpInfo, StoreInfo,
+            UnOpInfo, UncompiledCode,
         },
         decode::DecodeError,
         func::FuncType,
         global::Mut,
+        mem::MemType,
         module::ModuleBuilder,
         ref_::RefType,
         val::ValType,
@@ -143,6 +144,31 @@ impl<'a> Validation<'a> {
         self.opds.push(type_);
     }
 
+    /// Validates the given [`MemArg`], and returns the type of the addresses of the memory it
+    /// refers to.
+    fn mem_arg(&self, arg: MemArg, max_align: u32) -> Result<ValType, DecodeError> {
+        if arg.align > max_align {
+            return Err(DecodeError::new("alignment too large"));
+        }
+        let type_ = self.module.memory(arg.mem_idx)?;
+        if !type_.is_64 && arg.offset > u32::MAX.into() {
+            return Err(DecodeError::new("offset out of range"));
+        }
+        Ok(addr_type(type_))
+    }
+
+    /// Pops the arguments for a tail call to a function with the given type from the stack.
+    fn pop_tail_call_args(&mut self, type_: &FuncType) -> Result<(), DecodeError> {
+        if type_.results() != self.blocks[0].type_.results() {
+            return Err(DecodeError::new("type mismatch"));
+        }
+        for param_type in type_.params().iter().rev().copied() {
+            self.pop_opd()?.check(param_type)?;
+        }
+        self.set_unreachable();
+        Ok(())
+    }
+
     fn pop_opd(&mut self) -> Result<OpdType, DecodeError> {
         if self.opds.len() == self.block(0).height {
             if !self.block(0).is_unreachable {
@@ -308,6 +334,25 @@ impl<'a> InstrVisitor for Validation<'a> {
         Ok(())
     }
 
+    fn visit_return_call(&mut self, func_idx: u32) -> Result<(), Self::Error> {
+        let type_ = self.module.func(func_idx)?;
+        self.pop_tail_call_args(type_)
+    }
+
+    fn visit_return_call_indirect(
+        &mut self,
+        table_idx: u32,
+        type_idx: u32,
+    ) -> Result<(), Self::Error> {
+        let table_type = self.module.table(table_idx)?;
+        if table_type.elem != RefType::FuncRef {
+            return Err(DecodeError::new("type mismatch"));
+        }
+        let type_ = self.module.type_(type_idx)?;
+        self.pop_opd()?.check(ValType::I32)?;
+        self.pop_tail_call_args(type_)
+    }
+
     // Reference instructions
     fn visit_ref_null(&mut self, type_: RefType) -> Result<(), Self::Error> {
         self.push_opd(type_);
@@ -344,7 +389,9 @@ impl<'a> InstrVisitor for Validation<'a> {
             self.pop_opd()?.check(ValType::I32)?;
             let input_type_1 = self.pop_opd()?;
             let input_type_0 = self.pop_opd()?;
-            if !(input_type_0.is_num() && input_type_1.is_num()) {
+            if !((input_type_0.is_num() || input_type_0.is_vec())
+                && (input_type_1.is_num() || input_type_1.is_vec()))
+            {
                 return Err(DecodeError::new("type mismatch"));
             }
             if let OpdType::ValType(input_type_1) = input_type_1 {
@@ -466,56 +513,62 @@ impl<'a> InstrVisitor for Validation<'a> {
 
     // Memory instructions
     fn visit_load(&mut self, arg: MemArg, info: LoadInfo) -> Result<(), Self::Error> {
-        if arg.align > info.max_align {
-            return Err(DecodeError::new("alignment too large"));
+        let addr_type = self.mem_arg(arg, info.max_align)?;
+        self.pop_opd()?.check(addr_type)?;
+        if let Some(output_type) = info.op.output_type {
+            self.push_opd(output_type);
         }
-        self.module.memory(0)?;
-        self.visit_un_op(info.op)
+        Ok(())
     }
 
     fn visit_store(&mut self, arg: MemArg, info: StoreInfo) -> Result<(), Self::Error> {
-        if arg.align > info.max_align {
-            return Err(DecodeError::new("alignment too large"));
-        }
-        self.module.memory(0)?;
-        self.visit_bin_op(info.op)
+        let addr_type = self.mem_arg(arg, info.max_align)?;
+        self.pop_opd()?.check(info.op.input_type_1)?;
+        self.pop_opd()?.check(addr_type)?;
+        Ok(())
     }
 
-    fn visit_memory_size(&mut self) -> Result<(), Self::Error> {
-        self.module.memory(0)?;
-        self.push_opd(ValType::I32);
+    fn visit_memory_size(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
+        let addr_type = addr_type(self.module.memory(mem_idx)?);
+        self.push_opd(addr_type);
         Ok(())
     }
 
-    fn visit_memory_grow(&mut self) -> Result<(), Self::Error> {
-        self.module.memory(0)?;
-        self.pop_opd()?.check(ValType::I32)?;
-        self.push_opd(ValType::I32);
+    fn visit_memory_grow(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
+        let addr_type = addr_type(self.module.memory(mem_idx)?);
+        self.pop_opd()?.check(addr_type)?;
+        self.push_opd(addr_type);
         Ok(())
     }
 
-    fn visit_memory_fill(&mut self) -> Result<(), Self::Error> {
-        self.module.memory(0)?;
-        self.pop_opd()?.check(ValType::I32)?;
-        self.pop_opd()?.check(ValType::I32)?;
+    fn visit_memory_fill(&mut self, mem_idx: u32) -> Result<(), Self::Error> {
+        let addr_type = addr_type(self.module.memory(mem_idx)?);
+        self.pop_opd()?.check(addr_type)?;
         self.pop_opd()?.check(ValType::I32)?;
+        self.pop_opd()?.check(addr_type)?;
         Ok(())
     }
 
-    fn visit_memory_copy(&mut self) -> Result<(), Self::Error> {
-        self.module.memory(0)?;
-        self.pop_opd()?.check(ValType::I32)?;
-        self.pop_opd()?.check(ValType::I32)?;
-        self.pop_opd()?.check(ValType::I32)?;
+    fn visit_memory_copy(&mut self, dst_mem_idx: u32, src_mem_idx: u32) -> Result<(), Self::Error> {
+        let dst_addr_type = addr_type(self.module.memory(dst_mem_idx)?);
+        let src_addr_type = addr_type(self.module.memory(src_mem_idx)?);
+        let count_type = if dst_addr_type == ValType::I64 && src_addr_type == ValType::I64 {
+            ValType::I64
+        } else {
+            ValType::I32
+        };
+        self.pop_opd()?.check(count_type)?;
+        self.pop_opd()?.check(src_addr_type)?;
+        self.pop_opd()?.check(dst_addr_type)?;
         Ok(())
     }
 
-    fn visit_memory_init(&mut self, data_idx: u32) -> Result<(), Self::Error> {
-        self.module.memory(0)?;
+    fn visit_memory_init(&mut self, mem_idx: u32, data_idx: u32) -> Result<(), Self::Error> {
+        let addr_type = addr_type(self.module.memory(mem_idx)?);
         self.module.data(data_idx)?;
         self.pop_opd()?.check(ValType::I32)?;
         self.pop_opd()?.check(ValType::I32)?;
-        self.pop_opd()?.check(ValType::I32)?;
+        self.pop_opd()?.check(addr_type)?;
         Ok(())
     }
 
@@ -561,6 +614,68 @@ impl<'a> InstrVisitor for Validation<'a> {
         }
         Ok(())
     }
+
+    // Vector instructions
+    fn visit_v128_const(&mut self, _val: u128) -> Result<(), Self::Error> {
+        self.push_opd(ValType::V128);
+        Ok(())
+    }
+
+    fn visit_simd_op(&mut self, info: SimdOpInfo) -> Result<(), Self::Error> {
+        let mut addr_type = None;
+        match info.imm {
+            SimdImm::None => {}
+            SimdImm::Lane {
+                lane_idx,
+                lane_count,
+            } => {
+                if lane_idx >= lane_count {
+                    return Err(DecodeError::new("invalid lane index"));
+                }
+            }
+            SimdImm::Shuffle(lane_idxs) => {
+                if lane_idxs.iter().any(|&lane_idx| lane_idx >= 32) {
+                    return Err(DecodeError::new("invalid lane index"));
+                }
+            }
+            SimdImm::MemArg { max_align, arg } => {
+                addr_type = Some(self.mem_arg(arg, max_align)?);
+            }
+            SimdImm::MemArgLane {
+                max_align,
+                arg,
+                lane_idx,
+                lane_count,
+            } => {
+                addr_type = Some(self.mem_arg(arg, max_align)?);
+                if lane_idx >= lane_count {
+                    return Err(DecodeError::new("invalid lane index"));
+                }
+            }
+        }
+        // The first input of a SIMD memory instruction is its address, the type of which depends
+        // on the memory it refers to.
+        for (input_idx, &input_type) in info.input_types.iter().enumerate().rev() {
+            let input_type = match addr_type {
+                Some(addr_type) if input_idx == 0 => addr_type,
+                _ => input_type,
+            };
+            self.pop_opd()?.check(input_type)?;
+        }
+        if let Some(output_type) = info.output_type {
+            self.push_opd(output_type);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the type of the addresses of a memory with the given [`MemType`].
+fn addr_type(type_: MemType) -> ValType {
+    if type_.is_64 {
+        ValType::I64
+    } else {
+        ValType::I32
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -619,6 +734,13 @@ impl OpdType {
         }
     }
 
+    fn is_vec(self) -> bool {
+        match self {
+            OpdType::ValType(type_) => type_.is_vec(),
+            _ => true,
+        }
+    }
+
     fn is_ref(self) -> bool {
         match self {
             OpdType::ValType(type_) => type_.is_ref(),
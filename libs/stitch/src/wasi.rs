@@ -0,0 +1,1399 @@
+//! An implementation of WASI preview 1 (`wasi_snapshot_preview1`).
+
+use {
+    crate::{error::Error, func::Func, linker::Linker, store::Store},
+    std::{
+        collections::{hash_map::RandomState, BTreeMap},
+        fmt, fs,
+        hash::{BuildHasher, Hasher},
+        io,
+        io::{Read, Seek, SeekFrom, Write},
+        path::{Component, Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// The name of the module from which WASI preview 1 functions are imported.
+const MODULE: &str = "wasi_snapshot_preview1";
+
+/// The default maximum size of a file in a [`MemDir`] that guests can write to.
+const DEFAULT_MAX_MEM_FILE_SIZE: u64 = 1 << 30;
+
+/// A WASI preview 1 environment.
+///
+/// A [`Wasi`] defines the functions of the `wasi_snapshot_preview1` module on a [`Linker`], so
+/// that Wasm modules compiled for `wasm32-wasi` can be instantiated with it.
+///
+/// Guests can only access the file system through preopened directories. A preopened directory is
+/// either a directory on the host, in which case guests cannot access any files outside of it, or
+/// an in-memory [`MemDir`].
+pub struct Wasi {
+    args: Vec<String>,
+    envs: Vec<String>,
+    stdin: Box<dyn Read + Send>,
+    stdout: Box<dyn Write + Send>,
+    stderr: Box<dyn Write + Send>,
+    preopens: Vec<(String, Dir)>,
+    max_mem_file_size: u64,
+}
+
+impl Wasi {
+    /// Creates a new [`Wasi`] environment with no arguments, no environment variables, and no
+    /// preopened directories, that inherits the standard streams of the host.
+    pub fn new() -> Self {
+        Self {
+            args: Vec::new(),
+            envs: Vec::new(),
+            stdin: Box::new(io::stdin()),
+            stdout: Box::new(io::stdout()),
+            stderr: Box::new(io::stderr()),
+            preopens: Vec::new(),
+            max_mem_file_size: DEFAULT_MAX_MEM_FILE_SIZE,
+        }
+    }
+
+    /// Appends the given arguments to the command line arguments for this [`Wasi`] environment.
+    ///
+    /// By convention, the first argument is the name of the program.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(|arg| arg.into()));
+        self
+    }
+
+    /// Adds an environment variable with the given name and value to this [`Wasi`] environment.
+    pub fn env(mut self, name: &str, val: &str) -> Self {
+        self.envs.push(format!("{}={}", name, val));
+        self
+    }
+
+    /// Sets the standard input stream for this [`Wasi`] environment.
+    pub fn stdin(mut self, stdin: impl Read + Send + 'static) -> Self {
+        self.stdin = Box::new(stdin);
+        self
+    }
+
+    /// Sets the standard output stream for this [`Wasi`] environment.
+    pub fn stdout(mut self, stdout: impl Write + Send + 'static) -> Self {
+        self.stdout = Box::new(stdout);
+        self
+    }
+
+    /// Sets the standard error stream for this [`Wasi`] environment.
+    pub fn stderr(mut self, stderr: impl Write + Send + 'static) -> Self {
+        self.stderr = Box::new(stderr);
+        self
+    }
+
+    /// Preopens the given directory on the host, under the given path in the guest.
+    pub fn preopen_dir(mut self, guest_path: &str, host_path: impl Into<PathBuf>) -> Self {
+        self.preopens.push((
+            guest_path.into(),
+            Dir::Host {
+                root: host_path.into(),
+                path: Vec::new(),
+            },
+        ));
+        self
+    }
+
+    /// Preopens the given [`MemDir`], under the given path in the guest.
+    pub fn preopen_mem_dir(mut self, guest_path: &str, dir: MemDir) -> Self {
+        self.preopens.push((
+            guest_path.into(),
+            Dir::Mem {
+                root: dir,
+                path: Vec::new(),
+            },
+        ));
+        self
+    }
+
+    /// Sets the maximum size in bytes that guests can grow a file in a [`MemDir`] to, by writing to
+    /// it or by setting its size. Larger sizes fail with `EFBIG`. The default is 1 GiB.
+    pub fn max_mem_file_size(mut self, size: u64) -> Self {
+        self.max_mem_file_size = size;
+        self
+    }
+
+    /// Defines the functions of the `wasi_snapshot_preview1` module on the given [`Linker`].
+    ///
+    /// The functions access the memory of the instance that calls them, which must therefore
+    /// define or import a memory.
+    pub fn define(self, store: &mut Store, linker: &mut Linker) {
+        let mut fds = vec![
+            Some(FileDesc::Stdin),
+            Some(FileDesc::Stdout),
+            Some(FileDesc::Stderr),
+        ];
+        for (guest_path, dir) in self.preopens {
+            fds.push(Some(FileDesc::Dir {
+                dir,
+                preopen: Some(guest_path),
+            }));
+        }
+        let state = Arc::new(Mutex::new(WasiState {
+            args: self.args,
+            envs: self.envs,
+            stdin: self.stdin,
+            stdout: self.stdout,
+            stderr: self.stderr,
+            fds,
+            max_mem_file_size: self.max_mem_file_size,
+            start: Instant::now(),
+            random: RandomState::new(),
+            random_count: 0,
+        }));
+
+        macro_rules! define {
+            ($name:ident($($arg:ident: $T:ty),*)) => {{
+                let state = state.clone();
+                linker.define(
+                    MODULE,
+                    stringify!($name),
+                    Func::wrap(store, move |store: &mut Store, $($arg: $T),*| -> i32 {
+                        let Some(mem) = store.caller_mem() else {
+                            return errno::FAULT as i32;
+                        };
+                        let mut mem = GuestMem {
+                            bytes: mem.bytes_mut(store),
+                        };
+                        match state.lock().unwrap().$name(&mut mem, $($arg),*) {
+                            Ok(()) => errno::SUCCESS as i32,
+                            Err(errno) => errno as i32,
+                        }
+                    }),
+                );
+            }};
+        }
+
+        define!(args_get(argv: i32, argv_buf: i32));
+        define!(args_sizes_get(argc: i32, argv_buf_size: i32));
+        define!(environ_get(environ: i32, environ_buf: i32));
+        define!(environ_sizes_get(environc: i32, environ_buf_size: i32));
+        define!(clock_res_get(id: i32, resolution: i32));
+        define!(clock_time_get(id: i32, precision: i64, time: i32));
+        define!(random_get(buf: i32, buf_len: i32));
+        define!(fd_advise(fd: i32, offset: i64, len: i64, advice: i32));
+        define!(fd_close(fd: i32));
+        define!(fd_datasync(fd: i32));
+        define!(fd_sync(fd: i32));
+        define!(fd_fdstat_get(fd: i32, stat: i32));
+        define!(fd_fdstat_set_flags(fd: i32, flags: i32));
+        define!(fd_filestat_get(fd: i32, stat: i32));
+        define!(fd_filestat_set_size(fd: i32, size: i64));
+        define!(fd_prestat_get(fd: i32, prestat: i32));
+        define!(fd_prestat_dir_name(fd: i32, path: i32, path_len: i32));
+        define!(fd_read(fd: i32, iovs: i32, iovs_len: i32, nread: i32));
+        define!(fd_write(fd: i32, iovs: i32, iovs_len: i32, nwritten: i32));
+        define!(fd_seek(fd: i32, offset: i64, whence: i32, new_offset: i32));
+        define!(fd_tell(fd: i32, offset: i32));
+        define!(fd_readdir(fd: i32, buf: i32, buf_len: i32, cookie: i64, buf_used: i32));
+        define!(fd_renumber(fd: i32, to: i32));
+        define!(path_create_directory(fd: i32, path: i32, path_len: i32));
+        define!(path_filestat_get(fd: i32, flags: i32, path: i32, path_len: i32, stat: i32));
+        define!(path_open(
+            fd: i32,
+            dir_flags: i32,
+            path: i32,
+            path_len: i32,
+            oflags: i32,
+            rights_base: i64,
+            rights_inheriting: i64,
+            fd_flags: i32,
+            opened_fd: i32
+        ));
+        define!(path_remove_directory(fd: i32, path: i32, path_len: i32));
+        define!(path_unlink_file(fd: i32, path: i32, path_len: i32));
+        define!(poll_oneoff(subscriptions: i32, events: i32, subscription_count: i32, event_count: i32));
+        define!(sched_yield());
+
+        linker.define(
+            MODULE,
+            "proc_exit",
+            Func::wrap(store, |code: i32| -> Result<(), Error> {
+                Err(Error::Exit(code))
+            }),
+        );
+    }
+}
+
+impl Default for Wasi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Wasi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Wasi")
+            .field("args", &self.args)
+            .field("envs", &self.envs)
+            .field("preopens", &self.preopens)
+            .field("max_mem_file_size", &self.max_mem_file_size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An in-memory directory that can be preopened in a [`Wasi`] environment.
+///
+/// Cloning a [`MemDir`] creates a new handle to the same directory, so the host can inspect the
+/// files that a guest created after it has run.
+#[derive(Clone, Debug, Default)]
+pub struct MemDir {
+    entries: Arc<Mutex<BTreeMap<String, MemNode>>>,
+}
+
+impl MemDir {
+    /// Creates a new, empty [`MemDir`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a directory at the given path in this [`MemDir`], including any missing parent
+    /// directories.
+    ///
+    /// # Panics
+    ///
+    /// If a component of the given path is an existing file.
+    pub fn create_dir(&self, path: &str) -> MemDir {
+        let mut dir = self.clone();
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            let child = dir
+                .entries
+                .lock()
+                .unwrap()
+                .entry(name.into())
+                .or_insert_with(|| MemNode::Dir(MemDir::new()))
+                .clone();
+            let MemNode::Dir(child) = child else {
+                panic!("not a directory");
+            };
+            dir = child;
+        }
+        dir
+    }
+
+    /// Creates a file with the given contents at the given path in this [`MemDir`], including any
+    /// missing parent directories. An existing file is replaced.
+    ///
+    /// # Panics
+    ///
+    /// If a component of the given path is an existing file, or the path is an existing directory.
+    pub fn insert_file(&self, path: &str, data: impl Into<Vec<u8>>) {
+        let (dir_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let dir = self.create_dir(dir_path);
+        let mut entries = dir.entries.lock().unwrap();
+        assert!(
+            !matches!(entries.get(name), Some(MemNode::Dir(_))),
+            "is a directory"
+        );
+        entries.insert(
+            name.into(),
+            MemNode::File(MemFile {
+                data: Arc::new(Mutex::new(data.into())),
+            }),
+        );
+    }
+
+    /// Returns the contents of the file at the given path in this [`MemDir`], if it exists.
+    pub fn read_file(&self, path: &str) -> Option<Vec<u8>> {
+        let components: Vec<String> = path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .map(|name| name.into())
+            .collect();
+        match self.lookup(&components).ok()? {
+            MemNode::File(file) => Some(file.data.lock().unwrap().clone()),
+            MemNode::Dir(_) => None,
+        }
+    }
+
+    /// Returns the node at the given path in this [`MemDir`].
+    fn lookup(&self, components: &[String]) -> Result<MemNode, Errno> {
+        let mut node = MemNode::Dir(self.clone());
+        for name in components {
+            let MemNode::Dir(dir) = node else {
+                return Err(errno::NOTDIR);
+            };
+            node = dir
+                .entries
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .ok_or(errno::NOENT)?;
+        }
+        Ok(node)
+    }
+
+    /// Returns the directory at the given path in this [`MemDir`].
+    fn dir(&self, components: &[String]) -> Result<MemDir, Errno> {
+        match self.lookup(components)? {
+            MemNode::Dir(dir) => Ok(dir),
+            MemNode::File(_) => Err(errno::NOTDIR),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum MemNode {
+    File(MemFile),
+    Dir(MemDir),
+}
+
+#[derive(Clone, Debug)]
+struct MemFile {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+/// The state of a [`Wasi`] environment, after its functions have been defined.
+struct WasiState {
+    args: Vec<String>,
+    envs: Vec<String>,
+    stdin: Box<dyn Read + Send>,
+    stdout: Box<dyn Write + Send>,
+    stderr: Box<dyn Write + Send>,
+    fds: Vec<Option<FileDesc>>,
+    max_mem_file_size: u64,
+    start: Instant,
+    random: RandomState,
+    random_count: u64,
+}
+
+impl WasiState {
+    fn args_get(&mut self, mem: &mut GuestMem, argv: i32, argv_buf: i32) -> Result<(), Errno> {
+        write_strings(mem, &self.args, argv as u32, argv_buf as u32)
+    }
+
+    fn args_sizes_get(
+        &mut self,
+        mem: &mut GuestMem,
+        argc: i32,
+        argv_buf_size: i32,
+    ) -> Result<(), Errno> {
+        write_string_sizes(mem, &self.args, argc as u32, argv_buf_size as u32)
+    }
+
+    fn environ_get(
+        &mut self,
+        mem: &mut GuestMem,
+        environ: i32,
+        environ_buf: i32,
+    ) -> Result<(), Errno> {
+        write_strings(mem, &self.envs, environ as u32, environ_buf as u32)
+    }
+
+    fn environ_sizes_get(
+        &mut self,
+        mem: &mut GuestMem,
+        environc: i32,
+        environ_buf_size: i32,
+    ) -> Result<(), Errno> {
+        write_string_sizes(mem, &self.envs, environc as u32, environ_buf_size as u32)
+    }
+
+    fn clock_res_get(&mut self, mem: &mut GuestMem, id: i32, resolution: i32) -> Result<(), Errno> {
+        if !(clock::REALTIME..=clock::THREAD_CPUTIME).contains(&id) {
+            return Err(errno::INVAL);
+        }
+        mem.write_u64(resolution as u32, 1_000)
+    }
+
+    fn clock_time_get(
+        &mut self,
+        mem: &mut GuestMem,
+        id: i32,
+        _precision: i64,
+        time: i32,
+    ) -> Result<(), Errno> {
+        let nanos = match id {
+            clock::REALTIME => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| errno::IO)?
+                .as_nanos(),
+            clock::MONOTONIC | clock::PROCESS_CPUTIME | clock::THREAD_CPUTIME => {
+                self.start.elapsed().as_nanos()
+            }
+            _ => return Err(errno::INVAL),
+        };
+        mem.write_u64(time as u32, nanos as u64)
+    }
+
+    fn random_get(&mut self, mem: &mut GuestMem, buf: i32, buf_len: i32) -> Result<(), Errno> {
+        for chunk in mem.slice_mut(buf as u32, buf_len as u32)?.chunks_mut(8) {
+            let mut hasher = self.random.build_hasher();
+            hasher.write_u64(self.random_count);
+            self.random_count += 1;
+            chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+        }
+        Ok(())
+    }
+
+    fn fd_advise(
+        &mut self,
+        _mem: &mut GuestMem,
+        fd: i32,
+        _offset: i64,
+        _len: i64,
+        _advice: i32,
+    ) -> Result<(), Errno> {
+        self.fd(fd)?;
+        Ok(())
+    }
+
+    fn fd_close(&mut self, _mem: &mut GuestMem, fd: i32) -> Result<(), Errno> {
+        self.fd(fd)?;
+        self.fds[fd as usize] = None;
+        Ok(())
+    }
+
+    fn fd_datasync(&mut self, mem: &mut GuestMem, fd: i32) -> Result<(), Errno> {
+        self.fd_sync(mem, fd)
+    }
+
+    fn fd_sync(&mut self, _mem: &mut GuestMem, fd: i32) -> Result<(), Errno> {
+        match self.fd_mut(fd)? {
+            FileDesc::Stdout => self.stdout.flush().map_err(io_errno),
+            FileDesc::Stderr => self.stderr.flush().map_err(io_errno),
+            FileDesc::File {
+                file: File::Host(file),
+                ..
+            } => file.sync_all().map_err(io_errno),
+            _ => Ok(()),
+        }
+    }
+
+    fn fd_fdstat_get(&mut self, mem: &mut GuestMem, fd: i32, stat: i32) -> Result<(), Errno> {
+        let (filetype, flags) = match self.fd(fd)? {
+            FileDesc::Stdin | FileDesc::Stdout | FileDesc::Stderr => {
+                (filetype::CHARACTER_DEVICE, 0)
+            }
+            FileDesc::File { append, .. } => (
+                filetype::REGULAR_FILE,
+                if *append { fdflags::APPEND } else { 0 },
+            ),
+            FileDesc::Dir { .. } => (filetype::DIRECTORY, 0),
+        };
+        let stat = stat as u32;
+        mem.slice_mut(stat, 24)?.fill(0);
+        mem.write_u8(stat, filetype)?;
+        mem.write_u16(stat + 2, flags)?;
+        mem.write_u64(stat + 8, rights::ALL)?;
+        mem.write_u64(stat + 16, rights::ALL)
+    }
+
+    fn fd_fdstat_set_flags(
+        &mut self,
+        _mem: &mut GuestMem,
+        fd: i32,
+        flags: i32,
+    ) -> Result<(), Errno> {
+        match self.fd_mut(fd)? {
+            FileDesc::File { append, .. } => {
+                *append = flags as u16 & fdflags::APPEND != 0;
+                Ok(())
+            }
+            _ => Err(errno::INVAL),
+        }
+    }
+
+    fn fd_filestat_get(&mut self, mem: &mut GuestMem, fd: i32, stat: i32) -> Result<(), Errno> {
+        let filestat = match self.fd(fd)? {
+            FileDesc::Stdin | FileDesc::Stdout | FileDesc::Stderr => Filestat {
+                filetype: filetype::CHARACTER_DEVICE,
+                size: 0,
+                mtim: 0,
+            },
+            FileDesc::File {
+                file: File::Host(file),
+                ..
+            } => Filestat::from_metadata(&file.metadata().map_err(io_errno)?),
+            FileDesc::File {
+                file: File::Mem { file, .. },
+                ..
+            } => Filestat {
+                filetype: filetype::REGULAR_FILE,
+                size: file.data.lock().unwrap().len() as u64,
+                mtim: 0,
+            },
+            FileDesc::Dir { dir, .. } => dir.filestat(dir.path())?,
+        };
+        filestat.write(mem, stat as u32)
+    }
+
+    fn fd_filestat_set_size(
+        &mut self,
+        _mem: &mut GuestMem,
+        fd: i32,
+        size: i64,
+    ) -> Result<(), Errno> {
+        let max_mem_file_size = self.max_mem_file_size;
+        match self.fd_mut(fd)? {
+            FileDesc::File {
+                file: File::Host(file),
+                ..
+            } => file.set_len(size as u64).map_err(io_errno),
+            FileDesc::File {
+                file: File::Mem { file, .. },
+                ..
+            } => {
+                if size as u64 > max_mem_file_size {
+                    return Err(errno::FBIG);
+                }
+                file.data.lock().unwrap().resize(size as usize, 0);
+                Ok(())
+            }
+            _ => Err(errno::INVAL),
+        }
+    }
+
+    fn fd_prestat_get(&mut self, mem: &mut GuestMem, fd: i32, prestat: i32) -> Result<(), Errno> {
+        let FileDesc::Dir {
+            preopen: Some(name),
+            ..
+        } = self.fd(fd)?
+        else {
+            return Err(errno::BADF);
+        };
+        let len = name.len() as u32;
+        let prestat = prestat as u32;
+        mem.slice_mut(prestat, 8)?.fill(0);
+        mem.write_u32(prestat + 4, len)
+    }
+
+    fn fd_prestat_dir_name(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        path: i32,
+        path_len: i32,
+    ) -> Result<(), Errno> {
+        let FileDesc::Dir {
+            preopen: Some(name),
+            ..
+        } = self.fd(fd)?
+        else {
+            return Err(errno::BADF);
+        };
+        if (path_len as u32 as usize) < name.len() {
+            return Err(errno::NAMETOOLONG);
+        }
+        mem.slice_mut(path as u32, name.len() as u32)?
+            .copy_from_slice(name.as_bytes());
+        Ok(())
+    }
+
+    fn fd_read(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        iovs: i32,
+        iovs_len: i32,
+        nread: i32,
+    ) -> Result<(), Errno> {
+        let mut total = 0;
+        for (ptr, len) in mem.iovecs(iovs as u32, iovs_len as u32)? {
+            let mut buf = vec![0; len as usize];
+            let count = match self.fd_mut(fd)? {
+                FileDesc::Stdin => self.stdin.read(&mut buf).map_err(io_errno)?,
+                FileDesc::File { file, .. } => file.read(&mut buf)?,
+                FileDesc::Stdout | FileDesc::Stderr => return Err(errno::BADF),
+                FileDesc::Dir { .. } => return Err(errno::ISDIR),
+            };
+            mem.slice_mut(ptr, count as u32)?
+                .copy_from_slice(&buf[..count]);
+            total += count as u32;
+            if count < buf.len() {
+                break;
+            }
+        }
+        mem.write_u32(nread as u32, total)
+    }
+
+    fn fd_write(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        iovs: i32,
+        iovs_len: i32,
+        nwritten: i32,
+    ) -> Result<(), Errno> {
+        let max_mem_file_size = self.max_mem_file_size;
+        let mut total = 0;
+        for (ptr, len) in mem.iovecs(iovs as u32, iovs_len as u32)? {
+            let buf = mem.slice(ptr, len)?;
+            match self.fd_mut(fd)? {
+                FileDesc::Stdout => self.stdout.write_all(buf).map_err(io_errno)?,
+                FileDesc::Stderr => self.stderr.write_all(buf).map_err(io_errno)?,
+                FileDesc::File { file, append } => file.write(buf, *append, max_mem_file_size)?,
+                FileDesc::Stdin => return Err(errno::BADF),
+                FileDesc::Dir { .. } => return Err(errno::ISDIR),
+            }
+            total += len;
+        }
+        mem.write_u32(nwritten as u32, total)
+    }
+
+    fn fd_seek(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        offset: i64,
+        whence: i32,
+        new_offset: i32,
+    ) -> Result<(), Errno> {
+        let pos = match whence {
+            whence::SET => SeekFrom::Start(offset as u64),
+            whence::CUR => SeekFrom::Current(offset),
+            whence::END => SeekFrom::End(offset),
+            _ => return Err(errno::INVAL),
+        };
+        let offset = match self.fd_mut(fd)? {
+            FileDesc::File { file, .. } => file.seek(pos)?,
+            FileDesc::Dir { .. } => return Err(errno::ISDIR),
+            _ => return Err(errno::SPIPE),
+        };
+        mem.write_u64(new_offset as u32, offset)
+    }
+
+    fn fd_tell(&mut self, mem: &mut GuestMem, fd: i32, offset: i32) -> Result<(), Errno> {
+        self.fd_seek(mem, fd, 0, whence::CUR, offset)
+    }
+
+    fn fd_readdir(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        buf: i32,
+        buf_len: i32,
+        cookie: i64,
+        buf_used: i32,
+    ) -> Result<(), Errno> {
+        let FileDesc::Dir { dir, .. } = self.fd(fd)? else {
+            return Err(errno::NOTDIR);
+        };
+        let mut bytes = Vec::new();
+        for (idx, (name, filetype)) in dir.entries()?.into_iter().enumerate().skip(cookie as usize)
+        {
+            // The cookie of an entry is the index of the next entry.
+            bytes.extend_from_slice(&(idx as u64 + 1).to_le_bytes());
+            bytes.extend_from_slice(&0u64.to_le_bytes());
+            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&[filetype, 0, 0, 0]);
+            bytes.extend_from_slice(name.as_bytes());
+            if bytes.len() >= buf_len as u32 as usize {
+                break;
+            }
+        }
+        // If the buffer is too small, the last entry is truncated, which signals to the guest that
+        // there are more entries to read.
+        bytes.truncate(buf_len as u32 as usize);
+        mem.slice_mut(buf as u32, bytes.len() as u32)?
+            .copy_from_slice(&bytes);
+        mem.write_u32(buf_used as u32, bytes.len() as u32)
+    }
+
+    fn fd_renumber(&mut self, _mem: &mut GuestMem, fd: i32, to: i32) -> Result<(), Errno> {
+        self.fd(fd)?;
+        self.fd(to)?;
+        self.fds[to as usize] = self.fds[fd as usize].take();
+        Ok(())
+    }
+
+    fn path_create_directory(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        path: i32,
+        path_len: i32,
+    ) -> Result<(), Errno> {
+        let (dir, components) = self.resolve(mem, fd, path, path_len)?;
+        dir.create_dir(&components)
+    }
+
+    fn path_filestat_get(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        _flags: i32,
+        path: i32,
+        path_len: i32,
+        stat: i32,
+    ) -> Result<(), Errno> {
+        let (dir, components) = self.resolve(mem, fd, path, path_len)?;
+        dir.filestat(&components)?.write(mem, stat as u32)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn path_open(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        _dir_flags: i32,
+        path: i32,
+        path_len: i32,
+        oflags: i32,
+        rights_base: i64,
+        _rights_inheriting: i64,
+        fd_flags: i32,
+        opened_fd: i32,
+    ) -> Result<(), Errno> {
+        let (dir, components) = self.resolve(mem, fd, path, path_len)?;
+        let options = OpenOptions {
+            oflags: oflags as u16,
+            read: rights_base as u64 & rights::FD_READ != 0,
+            write: rights_base as u64 & rights::FD_WRITE != 0,
+        };
+        let append = fd_flags as u16 & fdflags::APPEND != 0;
+        let file_desc = match dir.open(&components, options)? {
+            Opened::File(file) => FileDesc::File { file, append },
+            Opened::Dir(dir) => FileDesc::Dir { dir, preopen: None },
+        };
+        let new_fd = match self.fds.iter().position(|fd| fd.is_none()) {
+            Some(new_fd) => {
+                self.fds[new_fd] = Some(file_desc);
+                new_fd
+            }
+            None => {
+                self.fds.push(Some(file_desc));
+                self.fds.len() - 1
+            }
+        };
+        mem.write_u32(opened_fd as u32, new_fd as u32)
+    }
+
+    fn path_remove_directory(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        path: i32,
+        path_len: i32,
+    ) -> Result<(), Errno> {
+        let (dir, components) = self.resolve(mem, fd, path, path_len)?;
+        dir.remove(&components, true)
+    }
+
+    fn path_unlink_file(
+        &mut self,
+        mem: &mut GuestMem,
+        fd: i32,
+        path: i32,
+        path_len: i32,
+    ) -> Result<(), Errno> {
+        let (dir, components) = self.resolve(mem, fd, path, path_len)?;
+        dir.remove(&components, false)
+    }
+
+    fn poll_oneoff(
+        &mut self,
+        _mem: &mut GuestMem,
+        _subscriptions: i32,
+        _events: i32,
+        _subscription_count: i32,
+        _event_count: i32,
+    ) -> Result<(), Errno> {
+        Err(errno::NOSYS)
+    }
+
+    fn sched_yield(&mut self, _mem: &mut GuestMem) -> Result<(), Errno> {
+        Ok(())
+    }
+
+    /// Returns the [`FileDesc`] for the given file descriptor.
+    fn fd(&self, fd: i32) -> Result<&FileDesc, Errno> {
+        self.fds
+            .get(fd as u32 as usize)
+            .and_then(|fd| fd.as_ref())
+            .ok_or(errno::BADF)
+    }
+
+    /// Returns the [`FileDesc`] for the given file descriptor as mutable.
+    fn fd_mut(&mut self, fd: i32) -> Result<&mut FileDesc, Errno> {
+        self.fds
+            .get_mut(fd as u32 as usize)
+            .and_then(|fd| fd.as_mut())
+            .ok_or(errno::BADF)
+    }
+
+    /// Resolves the given path relative to the directory with the given file descriptor.
+    ///
+    /// Returns the root of the directory, and the components of the path relative to the root.
+    fn resolve(
+        &self,
+        mem: &GuestMem,
+        fd: i32,
+        path: i32,
+        path_len: i32,
+    ) -> Result<(Dir, Vec<String>), Errno> {
+        let FileDesc::Dir { dir, .. } = self.fd(fd)? else {
+            return Err(errno::NOTDIR);
+        };
+        let path = mem.read_str(path as u32, path_len as u32)?;
+        if path.starts_with('/') {
+            return Err(errno::NOTCAPABLE);
+        }
+        let mut components = dir.path().to_vec();
+        for name in path.split('/') {
+            match name {
+                "" | "." => {}
+                ".." => {
+                    // Guests cannot access anything outside of the root of a preopened directory.
+                    components.pop().ok_or(errno::NOTCAPABLE)?;
+                }
+                name => {
+                    if !matches!(
+                        Path::new(name).components().collect::<Vec<_>>()[..],
+                        [Component::Normal(_)]
+                    ) {
+                        return Err(errno::NOTCAPABLE);
+                    }
+                    components.push(name.into());
+                }
+            }
+        }
+        Ok((dir.root(), components))
+    }
+}
+
+/// An open file descriptor in a [`Wasi`] environment.
+enum FileDesc {
+    Stdin,
+    Stdout,
+    Stderr,
+    File { file: File, append: bool },
+    Dir { dir: Dir, preopen: Option<String> },
+}
+
+/// An open file, either on the host or in a [`MemDir`].
+enum File {
+    Host(fs::File),
+    Mem { file: MemFile, pos: u64 },
+}
+
+impl File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Errno> {
+        match self {
+            Self::Host(file) => file.read(buf).map_err(io_errno),
+            Self::Mem { file, pos } => {
+                let data = file.data.lock().unwrap();
+                let start = (*pos).min(data.len() as u64) as usize;
+                let count = buf.len().min(data.len() - start);
+                buf[..count].copy_from_slice(&data[start..][..count]);
+                *pos += count as u64;
+                Ok(count)
+            }
+        }
+    }
+
+    /// Writes the given bytes to this [`File`]. A file in a [`MemDir`] cannot grow beyond the
+    /// given maximum size.
+    fn write(&mut self, buf: &[u8], append: bool, max_mem_size: u64) -> Result<(), Errno> {
+        match self {
+            Self::Host(file) => {
+                if append {
+                    file.seek(SeekFrom::End(0)).map_err(io_errno)?;
+                }
+                file.write_all(buf).map_err(io_errno)
+            }
+            Self::Mem { file, pos } => {
+                let mut data = file.data.lock().unwrap();
+                if append {
+                    *pos = data.len() as u64;
+                }
+                // The guest picks the position, so it can be anywhere up to u64::MAX.
+                let end = pos
+                    .checked_add(buf.len() as u64)
+                    .filter(|&end| end <= max_mem_size)
+                    .ok_or(errno::FBIG)?;
+                let (start, end) = (*pos as usize, end as usize);
+                if data.len() < end {
+                    data.resize(end, 0);
+                }
+                data[start..end].copy_from_slice(buf);
+                *pos = end as u64;
+                Ok(())
+            }
+        }
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Errno> {
+        match self {
+            Self::Host(file) => file.seek(pos).map_err(io_errno),
+            Self::Mem { file, pos: old_pos } => {
+                let new_pos = match pos {
+                    SeekFrom::Start(offset) => Some(offset),
+                    SeekFrom::Current(offset) => old_pos.checked_add_signed(offset),
+                    SeekFrom::End(offset) => {
+                        (file.data.lock().unwrap().len() as u64).checked_add_signed(offset)
+                    }
+                };
+                *old_pos = new_pos.ok_or(errno::INVAL)?;
+                Ok(*old_pos)
+            }
+        }
+    }
+}
+
+/// A directory, either on the host or in a [`MemDir`].
+///
+/// A directory consists of the root of the preopened directory it belongs to, and the components
+/// of its path relative to that root.
+#[derive(Clone, Debug)]
+enum Dir {
+    Host { root: PathBuf, path: Vec<String> },
+    Mem { root: MemDir, path: Vec<String> },
+}
+
+impl Dir {
+    /// Returns the components of the path of this [`Dir`], relative to its root.
+    fn path(&self) -> &[String] {
+        match self {
+            Self::Host { path, .. } | Self::Mem { path, .. } => path,
+        }
+    }
+
+    /// Returns the root of this [`Dir`].
+    fn root(&self) -> Dir {
+        match self {
+            Self::Host { root, .. } => Self::Host {
+                root: root.clone(),
+                path: Vec::new(),
+            },
+            Self::Mem { root, .. } => Self::Mem {
+                root: root.clone(),
+                path: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the path on the host for the given components, relative to the root of this
+    /// [`Dir`].
+    fn host_path(root: &Path, components: &[String]) -> Result<PathBuf, Errno> {
+        let path = components
+            .iter()
+            .fold(root.to_path_buf(), |path, name| path.join(name));
+
+        // A symbolic link could point outside of the root, so check that the canonical path of the
+        // nearest existing ancestor is still inside the root.
+        let canonical_root = root.canonicalize().map_err(io_errno)?;
+        let mut ancestor = Some(path.as_path());
+        while let Some(path) = ancestor {
+            if let Ok(canonical_path) = path.canonicalize() {
+                if !canonical_path.starts_with(&canonical_root) {
+                    return Err(errno::NOTCAPABLE);
+                }
+                break;
+            }
+            ancestor = path.parent();
+        }
+        Ok(path)
+    }
+
+    fn open(&self, components: &[String], options: OpenOptions) -> Result<Opened, Errno> {
+        let create = options.oflags & oflags::CREAT != 0;
+        let excl = options.oflags & oflags::EXCL != 0;
+        let trunc = options.oflags & oflags::TRUNC != 0;
+        let directory = options.oflags & oflags::DIRECTORY != 0;
+        match self {
+            Self::Host { root, .. } => {
+                let path = Self::host_path(root, components)?;
+                if path.is_dir() {
+                    if create && excl {
+                        return Err(errno::EXIST);
+                    }
+                    if options.write || trunc {
+                        return Err(errno::ISDIR);
+                    }
+                    return Ok(Opened::Dir(Self::Host {
+                        root: root.clone(),
+                        path: components.to_vec(),
+                    }));
+                }
+                if directory {
+                    return Err(if path.exists() {
+                        errno::NOTDIR
+                    } else {
+                        errno::NOENT
+                    });
+                }
+                let file = fs::OpenOptions::new()
+                    .read(options.read || !options.write)
+                    .write(options.write || create || trunc)
+                    .create(create)
+                    .create_new(create && excl)
+                    .truncate(trunc)
+                    .open(path)
+                    .map_err(io_errno)?;
+                Ok(Opened::File(File::Host(file)))
+            }
+            Self::Mem { root, .. } => {
+                let Some((name, dir_components)) = components.split_last() else {
+                    if options.write || trunc {
+                        return Err(errno::ISDIR);
+                    }
+                    return Ok(Opened::Dir(self.root()));
+                };
+                let dir = root.dir(dir_components)?;
+                let mut entries = dir.entries.lock().unwrap();
+                match entries.get(name) {
+                    Some(_) if create && excl => Err(errno::EXIST),
+                    Some(MemNode::Dir(_)) => {
+                        if options.write || trunc {
+                            return Err(errno::ISDIR);
+                        }
+                        Ok(Opened::Dir(Self::Mem {
+                            root: root.clone(),
+                            path: components.to_vec(),
+                        }))
+                    }
+                    Some(MemNode::File(_)) if directory => Err(errno::NOTDIR),
+                    Some(MemNode::File(file)) => {
+                        if trunc {
+                            file.data.lock().unwrap().clear();
+                        }
+                        Ok(Opened::File(File::Mem {
+                            file: file.clone(),
+                            pos: 0,
+                        }))
+                    }
+                    None if create && !directory => {
+                        let file = MemFile {
+                            data: Arc::new(Mutex::new(Vec::new())),
+                        };
+                        entries.insert(name.clone(), MemNode::File(file.clone()));
+                        Ok(Opened::File(File::Mem { file, pos: 0 }))
+                    }
+                    None => Err(errno::NOENT),
+                }
+            }
+        }
+    }
+
+    fn filestat(&self, components: &[String]) -> Result<Filestat, Errno> {
+        match self {
+            Self::Host { root, .. } => {
+                let path = Self::host_path(root, components)?;
+                Ok(Filestat::from_metadata(
+                    &fs::metadata(path).map_err(io_errno)?,
+                ))
+            }
+            Self::Mem { root, .. } => match root.lookup(components)? {
+                MemNode::File(file) => Ok(Filestat {
+                    filetype: filetype::REGULAR_FILE,
+                    size: file.data.lock().unwrap().len() as u64,
+                    mtim: 0,
+                }),
+                MemNode::Dir(_) => Ok(Filestat {
+                    filetype: filetype::DIRECTORY,
+                    size: 0,
+                    mtim: 0,
+                }),
+            },
+        }
+    }
+
+    /// Returns the name and file type of each entry in this [`Dir`].
+    fn entries(&self) -> Result<Vec<(String, u8)>, Errno> {
+        match self {
+            Self::Host { root, path } => {
+                let mut entries = Vec::new();
+                for entry in fs::read_dir(Self::host_path(root, path)?).map_err(io_errno)? {
+                    let entry = entry.map_err(io_errno)?;
+                    let file_type = entry.file_type().map_err(io_errno)?;
+                    entries.push((
+                        entry.file_name().to_string_lossy().into_owned(),
+                        Filestat::filetype(&file_type),
+                    ));
+                }
+                entries.sort();
+                Ok(entries)
+            }
+            Self::Mem { root, path } => Ok(root
+                .dir(path)?
+                .entries
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(name, node)| {
+                    let filetype = match node {
+                        MemNode::File(_) => filetype::REGULAR_FILE,
+                        MemNode::Dir(_) => filetype::DIRECTORY,
+                    };
+                    (name.clone(), filetype)
+                })
+                .collect()),
+        }
+    }
+
+    fn create_dir(&self, components: &[String]) -> Result<(), Errno> {
+        match self {
+            Self::Host { root, .. } => {
+                fs::create_dir(Self::host_path(root, components)?).map_err(io_errno)
+            }
+            Self::Mem { root, .. } => {
+                let (name, dir_components) = components.split_last().ok_or(errno::EXIST)?;
+                let dir = root.dir(dir_components)?;
+                let mut entries = dir.entries.lock().unwrap();
+                if entries.contains_key(name) {
+                    return Err(errno::EXIST);
+                }
+                entries.insert(name.clone(), MemNode::Dir(MemDir::new()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the file, or the empty directory if `is_dir` is `true`, with the given path.
+    fn remove(&self, components: &[String], is_dir: bool) -> Result<(), Errno> {
+        match self {
+            Self::Host { root, .. } => {
+                let path = Self::host_path(root, components)?;
+                if is_dir {
+                    fs::remove_dir(path).map_err(io_errno)
+                } else if path.is_dir() {
+                    Err(errno::ISDIR)
+                } else {
+                    fs::remove_file(path).map_err(io_errno)
+                }
+            }
+            Self::Mem { root, .. } => {
+                let (name, dir_components) = components.split_last().ok_or(errno::BUSY)?;
+                let dir = root.dir(dir_components)?;
+                let mut entries = dir.entries.lock().unwrap();
+                match (entries.get(name).ok_or(errno::NOENT)?, is_dir) {
+                    (MemNode::Dir(dir), true) => {
+                        if !dir.entries.lock().unwrap().is_empty() {
+                            return Err(errno::NOTEMPTY);
+                        }
+                    }
+                    (MemNode::File(_), false) => {}
+                    (MemNode::File(_), true) => return Err(errno::NOTDIR),
+                    (MemNode::Dir(_), false) => return Err(errno::ISDIR),
+                }
+                entries.remove(name);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OpenOptions {
+    oflags: u16,
+    read: bool,
+    write: bool,
+}
+
+enum Opened {
+    File(File),
+    Dir(Dir),
+}
+
+/// The attributes of a file, as returned by `fd_filestat_get` and `path_filestat_get`.
+#[derive(Clone, Copy, Debug)]
+struct Filestat {
+    filetype: u8,
+    size: u64,
+    mtim: u64,
+}
+
+impl Filestat {
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        Self {
+            filetype: Self::filetype(&metadata.file_type()),
+            size: metadata.len(),
+            mtim: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_nanos() as u64),
+        }
+    }
+
+    fn filetype(file_type: &fs::FileType) -> u8 {
+        if file_type.is_dir() {
+            filetype::DIRECTORY
+        } else if file_type.is_file() {
+            filetype::REGULAR_FILE
+        } else if file_type.is_symlink() {
+            filetype::SYMBOLIC_LINK
+        } else {
+            filetype::UNKNOWN
+        }
+    }
+
+    fn write(&self, mem: &mut GuestMem, ptr: u32) -> Result<(), Errno> {
+        mem.slice_mut(ptr, 64)?.fill(0);
+        mem.write_u8(ptr + 16, self.filetype)?;
+        mem.write_u64(ptr + 24, 1)?;
+        mem.write_u64(ptr + 32, self.size)?;
+        mem.write_u64(ptr + 40, self.mtim)?;
+        mem.write_u64(ptr + 48, self.mtim)?;
+        mem.write_u64(ptr + 56, self.mtim)
+    }
+}
+
+/// The memory of the guest that called a WASI function.
+struct GuestMem<'a> {
+    bytes: &'a mut [u8],
+}
+
+impl GuestMem<'_> {
+    fn slice(&self, ptr: u32, len: u32) -> Result<&[u8], Errno> {
+        self.bytes
+            .get(ptr as usize..)
+            .and_then(|bytes| bytes.get(..len as usize))
+            .ok_or(errno::FAULT)
+    }
+
+    fn slice_mut(&mut self, ptr: u32, len: u32) -> Result<&mut [u8], Errno> {
+        self.bytes
+            .get_mut(ptr as usize..)
+            .and_then(|bytes| bytes.get_mut(..len as usize))
+            .ok_or(errno::FAULT)
+    }
+
+    fn read_u32(&self, ptr: u32) -> Result<u32, Errno> {
+        Ok(u32::from_le_bytes(self.slice(ptr, 4)?.try_into().unwrap()))
+    }
+
+    fn read_str(&self, ptr: u32, len: u32) -> Result<&str, Errno> {
+        std::str::from_utf8(self.slice(ptr, len)?).map_err(|_| errno::ILSEQ)
+    }
+
+    /// Reads a list of `iovec`s, each of which is a pointer and a length.
+    fn iovecs(&self, ptr: u32, len: u32) -> Result<Vec<(u32, u32)>, Errno> {
+        (0..len)
+            .map(|idx| {
+                let ptr = ptr.checked_add(idx * 8).ok_or(errno::FAULT)?;
+                Ok((self.read_u32(ptr)?, self.read_u32(ptr + 4)?))
+            })
+            .collect()
+    }
+
+    fn write_u8(&mut self, ptr: u32, val: u8) -> Result<(), Errno> {
+        self.slice_mut(ptr, 1)?[0] = val;
+        Ok(())
+    }
+
+    fn write_u16(&mut self, ptr: u32, val: u16) -> Result<(), Errno> {
+        self.slice_mut(ptr, 2)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_u32(&mut self, ptr: u32, val: u32) -> Result<(), Errno> {
+        self.slice_mut(ptr, 4)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_u64(&mut self, ptr: u32, val: u64) -> Result<(), Errno> {
+        self.slice_mut(ptr, 8)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+}
+
+/// Writes a list of strings as a list of pointers to null-terminated strings, as expected by
+/// `args_get` and `environ_get`.
+fn write_strings(mem: &mut GuestMem, strings: &[String], ptrs: u32, buf: u32) -> Result<(), Errno> {
+    let mut ptr = buf;
+    for (idx, string) in strings.iter().enumerate() {
+        mem.write_u32(ptrs + idx as u32 * 4, ptr)?;
+        let len = string.len() as u32;
+        mem.slice_mut(ptr, len)?.copy_from_slice(string.as_bytes());
+        mem.write_u8(ptr + len, 0)?;
+        ptr += len + 1;
+    }
+    Ok(())
+}
+
+/// Writes the number of strings in a list, and the size of the buffer needed to store them, as
+/// expected by `args_sizes_get` and `environ_sizes_get`.
+fn write_string_sizes(
+    mem: &mut GuestMem,
+    strings: &[String],
+    count: u32,
+    buf_size: u32,
+) -> Result<(), Errno> {
+    mem.write_u32(count, strings.len() as u32)?;
+    mem.write_u32(
+        buf_size,
+        strings.iter().map(|string| string.len() as u32 + 1).sum(),
+    )
+}
+
+fn io_errno(error: io::Error) -> Errno {
+    match error.kind() {
+        io::ErrorKind::NotFound => errno::NOENT,
+        io::ErrorKind::PermissionDenied => errno::ACCES,
+        io::ErrorKind::AlreadyExists => errno::EXIST,
+        io::ErrorKind::InvalidInput => errno::INVAL,
+        io::ErrorKind::IsADirectory => errno::ISDIR,
+        io::ErrorKind::NotADirectory => errno::NOTDIR,
+        io::ErrorKind::DirectoryNotEmpty => errno::NOTEMPTY,
+        _ => errno::IO,
+    }
+}
+
+type Errno = u16;
+
+mod errno {
+    use super::Errno;
+
+    pub(super) const SUCCESS: Errno = 0;
+    pub(super) const ACCES: Errno = 2;
+    pub(super) const BADF: Errno = 8;
+    pub(super) const BUSY: Errno = 10;
+    pub(super) const EXIST: Errno = 20;
+    pub(super) const FAULT: Errno = 21;
+    pub(super) const FBIG: Errno = 22;
+    pub(super) const ILSEQ: Errno = 25;
+    pub(super) const INVAL: Errno = 28;
+    pub(super) const IO: Errno = 29;
+    pub(super) const ISDIR: Errno = 31;
+    pub(super) const NAMETOOLONG: Errno = 37;
+    pub(super) const NOENT: Errno = 44;
+    pub(super) const NOSYS: Errno = 52;
+    pub(super) const NOTDIR: Errno = 54;
+    pub(super) const NOTEMPTY: Errno = 55;
+    pub(super) const SPIPE: Errno = 70;
+    pub(super) const NOTCAPABLE: Errno = 76;
+}
+
+mod clock {
+    pub(super) const REALTIME: i32 = 0;
+    pub(super) const MONOTONIC: i32 = 1;
+    pub(super) const PROCESS_CPUTIME: i32 = 2;
+    pub(super) const THREAD_CPUTIME: i32 = 3;
+}
+
+mod filetype {
+    pub(super) const UNKNOWN: u8 = 0;
+    pub(super) const CHARACTER_DEVICE: u8 = 2;
+    pub(super) const DIRECTORY: u8 = 3;
+    pub(super) const REGULAR_FILE: u8 = 4;
+    pub(super) const SYMBOLIC_LINK: u8 = 7;
+}
+
+mod fdflags {
+    pub(super) const APPEND: u16 = 1;
+}
+
+mod oflags {
+    pub(super) const CREAT: u16 = 1;
+    pub(super) const DIRECTORY: u16 = 2;
+    pub(super) const EXCL: u16 = 4;
+    pub(super) const TRUNC: u16 = 8;
+}
+
+mod rights {
+    pub(super) const FD_READ: u64 = 1 << 1;
+    pub(super) const FD_WRITE: u64 = 1 << 6;
+    pub(super) const ALL: u64 = (1 << 29) - 1;
+}
+
+mod whence {
+    pub(super) const SET: i32 = 0;
+    pub(super) const CUR: i32 = 1;
+    pub(super) const END: i32 = 2;
+}
@@ -0,0 +1,127 @@
+use {
+    makepad_stitch::{Engine, Error, Func, Linker, Module, Store, Trap, Val},
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+const WAT: &str = r#"
+    (module $plugin
+        (import "env" "log" (func $log (param i32)))
+
+        (memory 1)
+
+        (func $crash
+            unreachable
+        )
+
+        (func $load (param $addr i32) (result i32)
+            (i32.load (local.get $addr))
+        )
+
+        (func (export "run") (param $addr i32) (result i32)
+            (call $log (local.get $addr))
+            (if (i32.eqz (local.get $addr))
+                (then
+                    call $crash
+                )
+            )
+            (call $load (local.get $addr))
+        )
+    )
+"#;
+
+fn encode(wat: &str) -> Vec<u8> {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    wat.encode().unwrap()
+}
+
+fn run(bytes: &[u8], addr: i32) -> Result<i32, Error> {
+    let mut store = Store::new(Engine::new());
+    let module = Module::new(store.engine(), bytes).unwrap();
+    let mut linker = Linker::new();
+    linker.define("env", "log", Func::wrap(&mut store, |_: i32| {}));
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let mut results = [Val::I32(0)];
+    instance
+        .exported_func("run")
+        .unwrap()
+        .call(&mut store, &[Val::I32(addr)], &mut results)?;
+    Ok(results[0].to_i32().unwrap())
+}
+
+#[test]
+fn names() {
+    let store = Store::new(Engine::new());
+    let module = Module::new(store.engine(), &encode(WAT)).unwrap();
+    assert_eq!(module.name(), Some("plugin"));
+    assert_eq!(module.func_name(0), Some("log"));
+    assert_eq!(module.func_name(1), Some("crash"));
+    assert_eq!(module.func_name(2), Some("load"));
+    assert_eq!(module.func_name(3), None);
+}
+
+#[test]
+fn unreachable() {
+    let bytes = encode(WAT);
+    let Err(Error::Trap(Trap::Unreachable, backtrace)) = run(&bytes, 0) else {
+        panic!("expected trap");
+    };
+    let frames = backtrace.frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].module_name(), Some("plugin"));
+    assert_eq!(frames[0].func_idx(), 1);
+    assert_eq!(frames[0].func_name(), Some("crash"));
+    assert_eq!(bytes[frames[0].instr_offset()], 0x00); // unreachable
+    assert_eq!(frames[1].func_idx(), 3);
+    assert_eq!(frames[1].func_name(), None);
+    assert_eq!(bytes[frames[1].instr_offset()], 0x10); // call
+    assert_eq!(bytes[frames[1].instr_offset() + 1], 1);
+    assert_eq!(
+        backtrace.to_string(),
+        format!(
+            "   0: {:#x} - plugin!crash\n   1: {:#x} - plugin!<wasm function 3>\n",
+            frames[0].instr_offset(),
+            frames[1].instr_offset(),
+        )
+    );
+}
+
+#[test]
+fn mem_access_out_of_bounds() {
+    let bytes = encode(WAT);
+    assert_eq!(run(&bytes, 16).unwrap(), 0);
+    let Err(Error::Trap(Trap::MemAccessOutOfBounds, backtrace)) = run(&bytes, 65536) else {
+        panic!("expected trap");
+    };
+    let frames = backtrace.frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].func_name(), Some("load"));
+    assert_eq!(bytes[frames[0].instr_offset()], 0x28); // i32.load
+    assert_eq!(bytes[frames[1].instr_offset()], 0x10); // call
+    assert_eq!(bytes[frames[1].instr_offset() + 1], 2);
+}
+
+#[test]
+fn without_name_section() {
+    let bytes = encode(
+        r#"
+        (module
+            (func (export "run") (param i32) (result i32)
+                (i32.div_u (i32.const 1) (local.get 0))
+            )
+        )
+        "#,
+    );
+    let Err(Error::Trap(Trap::IntDivByZero, backtrace)) = run(&bytes, 0) else {
+        panic!("expected trap");
+    };
+    let frames = backtrace.frames();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].module_name(), None);
+    assert_eq!(frames[0].func_idx(), 0);
+    assert_eq!(frames[0].func_name(), None);
+    assert_eq!(bytes[frames[0].instr_offset()], 0x6E); // i32.div_u
+}
@@ -0,0 +1,138 @@
+use {
+    makepad_stitch::{Engine, Error, Func, FuncError, Instance, Linker, Module, Store, Trap, Val},
+    std::{thread, time::Duration},
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+const WAT: &str = r#"
+    (module
+        (memory (export "mem") 1)
+
+        (func $add (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add
+        )
+
+        (func (export "sum") (param $n i32) (result i32)
+            (local $i i32)
+            (local $sum i32)
+            (block $done
+                (loop $loop
+                    local.get $i
+                    local.get $n
+                    i32.ge_u
+                    br_if $done
+                    (i32.store (i32.const 0) (local.get $i))
+                    local.get $sum
+                    (i32.load (i32.const 0))
+                    call $add
+                    local.set $sum
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    br $loop
+                )
+            )
+            local.get $sum
+        )
+
+        (func (export "spin")
+            (loop $loop
+                br $loop
+            )
+        )
+    )
+"#;
+
+fn instantiate(store: &mut Store) -> Instance {
+    let buf = ParseBuffer::new(WAT).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    let bytes = wat.encode().unwrap();
+    let module = Module::new(store.engine(), &bytes).unwrap();
+    Linker::new().instantiate(store, &module).unwrap()
+}
+
+fn sum(store: &mut Store, func: Func, n: i32) -> Result<i32, Error> {
+    let mut results = [Val::I32(0)];
+    func.call(store, &[Val::I32(n)], &mut results)?;
+    Ok(results[0].to_i32().unwrap())
+}
+
+#[test]
+fn unmetered() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(&mut store);
+    let func = instance.exported_func("sum").unwrap();
+    assert_eq!(sum(&mut store, func, 1000).unwrap(), 499500);
+    assert_eq!(store.fuel(), None);
+}
+
+#[test]
+fn out_of_fuel() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(&mut store);
+    let func = instance.exported_func("spin").unwrap();
+    store.set_fuel(Some(1000));
+    let result = func.call(&mut store, &[], &mut []);
+    assert!(matches!(result, Err(Error::Trap(Trap::OutOfFuel, _))));
+    assert!(store.is_suspended());
+    assert!(store.fuel().unwrap() < 1000);
+
+    // Calling a function cancels the suspended call.
+    store.set_fuel(None);
+    let func = instance.exported_func("sum").unwrap();
+    assert_eq!(sum(&mut store, func, 10).unwrap(), 45);
+    assert!(!store.is_suspended());
+}
+
+#[test]
+fn top_up_and_resume() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(&mut store);
+    let func = instance.exported_func("sum").unwrap();
+    let mem = instance.exported_mem("mem").unwrap();
+    store.set_fuel(Some(100));
+    let mut results = [Val::I32(0)];
+    let mut result = func.call(&mut store, &[Val::I32(1000)], &mut results);
+    let mut suspend_count = 0;
+    while let Err(Error::Trap(Trap::OutOfFuel, _)) = result {
+        suspend_count += 1;
+        // Growing the memory while suspended must not invalidate the suspended call.
+        if suspend_count == 10 {
+            mem.grow(&mut store, 1).unwrap();
+        }
+        store.add_fuel(100);
+        result = store.resume(&mut results);
+    }
+    result.unwrap();
+    assert!(suspend_count > 10);
+    assert_eq!(results[0].to_i32().unwrap(), 499500);
+    assert!(!store.is_suspended());
+}
+
+#[test]
+fn interrupt() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(&mut store);
+    let func = instance.exported_func("spin").unwrap();
+    let handle = store.interrupt_handle();
+    let thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        handle.interrupt();
+    });
+    let result = func.call(&mut store, &[], &mut []);
+    thread.join().unwrap();
+    assert!(matches!(result, Err(Error::Trap(Trap::Interrupted, _))));
+    assert!(store.is_suspended());
+    store.cancel_suspended();
+    assert!(!store.is_suspended());
+}
+
+#[test]
+fn resume_without_suspended_call() {
+    let mut store = Store::new(Engine::new());
+    let result = store.resume(&mut []);
+    assert!(matches!(result, Err(Error::Func(FuncError::NotSuspended))));
+}
@@ -0,0 +1,197 @@
+use {
+    makepad_stitch::{Engine, Error, Func, FuncRef, Instance, Linker, Module, Store, Trap, Val},
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+fn compile(store: &Store, wat: &str) -> Result<Module, Error> {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    let bytes = wat.encode().unwrap();
+    Module::new(store.engine(), &bytes).map_err(Error::from)
+}
+
+fn instantiate(store: &mut Store, wat: &str) -> Instance {
+    let module = compile(store, wat).unwrap();
+    Linker::new().instantiate(store, &module).unwrap()
+}
+
+fn call(store: &mut Store, instance: &Instance, name: &str, args: &[Val]) -> Result<i64, Error> {
+    let func = instance.exported_func(name).unwrap();
+    let mut results = [Val::I64(0)];
+    func.call(store, args, &mut results)?;
+    Ok(results[0].to_i64().unwrap())
+}
+
+fn is_out_of_bounds(result: Result<i64, Error>) -> bool {
+    matches!(result, Err(Error::Trap(Trap::MemAccessOutOfBounds, _)))
+}
+
+#[test]
+fn mems_larger_than_4_gib_are_invalid() {
+    let store = Store::new(Engine::new());
+    assert!(compile(&store, "(module (memory i64 65536))").is_ok());
+    assert!(compile(&store, "(module (memory i64 0 65536))").is_ok());
+    assert!(compile(&store, "(module (memory i64 65537))").is_err());
+    assert!(compile(&store, "(module (memory i64 0 65537))").is_err());
+    assert!(compile(&store, "(module (memory i64 0x1000000))").is_err());
+}
+
+#[test]
+fn mem64_of_4_gib() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        r#"
+        (module
+            (memory i64 65536)
+
+            (func (export "load8") (param i64) (result i64)
+                (i64.load8_u (local.get 0))
+            )
+            (func (export "load8_offset") (param i64) (result i64)
+                (i64.load8_u offset=2 (local.get 0))
+            )
+            (func (export "load32") (param i64) (result i64)
+                (i64.load32_u (local.get 0))
+            )
+            (func (export "store8") (param i64) (result i64)
+                (i64.store8 (local.get 0) (i64.const 42))
+                (i64.const 0)
+            )
+            (func (export "load8_last") (result i64)
+                (i64.load8_u (i64.const 0xffffffff))
+            )
+            (func (export "load8_past_end") (result i64)
+                (i64.load8_u (i64.const 0x100000000))
+            )
+            (func (export "grow") (result i64)
+                (memory.grow (i64.const 1))
+            )
+            (func (export "size") (result i64)
+                (memory.size)
+            )
+        )
+        "#,
+    );
+    let last = Val::I64(0xffff_ffff);
+    assert_eq!(call(&mut store, &instance, "store8", &[last]).unwrap(), 0);
+    assert_eq!(call(&mut store, &instance, "load8", &[last]).unwrap(), 42);
+    assert_eq!(call(&mut store, &instance, "load8_last", &[]).unwrap(), 42);
+    assert_eq!(
+        call(&mut store, &instance, "load8_offset", &[Val::I64(0xffff_fffd)]).unwrap(),
+        42
+    );
+    assert_eq!(
+        call(&mut store, &instance, "load32", &[Val::I64(0xffff_fffc)]).unwrap(),
+        42 << 24
+    );
+
+    // Addresses that do not fit in 32 bits are out of bounds, however they are computed.
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load32", &[Val::I64(0xffff_fffd)])));
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load8", &[Val::I64(0x1_0000_0000)])));
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load8", &[Val::I64(-1)])));
+    assert!(is_out_of_bounds(call(&mut store, &instance, "store8", &[Val::I64(0x1_0000_0000)])));
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load8_offset", &[Val::I64(0xffff_fffe)])));
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load8_past_end", &[])));
+
+    assert_eq!(call(&mut store, &instance, "grow", &[]).unwrap(), -1);
+    assert_eq!(call(&mut store, &instance, "size", &[]).unwrap(), 65536);
+}
+
+#[test]
+fn mem32_of_4_gib() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        r#"
+        (module
+            (memory 65536)
+
+            (func (export "store_load") (param i32) (result i64)
+                (i32.store8 (local.get 0) (i32.const 42))
+                (i64.load8_u (local.get 0))
+            )
+            (func (export "load16") (param i32) (result i64)
+                (i64.load16_u (local.get 0))
+            )
+        )
+        "#,
+    );
+    assert_eq!(
+        call(&mut store, &instance, "store_load", &[Val::I32(-1)]).unwrap(),
+        42
+    );
+    assert!(is_out_of_bounds(call(&mut store, &instance, "load16", &[Val::I32(-1)])));
+}
+
+#[test]
+fn grow_in_callee_updates_caller() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        r#"
+        (module
+            (memory i64 1)
+
+            (func $grow
+                (drop (memory.grow (i64.const 1)))
+            )
+            (func (export "grow_and_load") (result i64)
+                (call $grow)
+                (i64.load (i64.const 65536))
+            )
+        )
+        "#,
+    );
+    assert_eq!(call(&mut store, &instance, "grow_and_load", &[]).unwrap(), 0);
+}
+
+#[test]
+fn grow_in_host_func_updates_callers() {
+    let mut store = Store::new(Engine::new());
+    let mut linker = Linker::new();
+    let grow = Func::wrap(&mut store, |store: &mut Store| {
+        store.caller_mem().unwrap().grow(store, 1).unwrap();
+    });
+    linker.define("host", "grow", grow);
+    let reenter = Func::wrap(&mut store, |store: &mut Store, func: FuncRef| {
+        func.get().unwrap().call(store, &[], &mut []).unwrap();
+    });
+    linker.define("host", "reenter", reenter);
+    let module = compile(
+        &store,
+        r#"
+        (module
+            (import "host" "grow" (func $grow))
+            (import "host" "reenter" (func $reenter (param funcref)))
+            (memory i64 1)
+            (elem declare func $grow_in_wasm)
+
+            (func $grow_in_wasm
+                (drop (memory.grow (i64.const 1)))
+            )
+            (func $grow_in_host (result i64)
+                (call $grow)
+                (i64.load (i64.sub (i64.shl (memory.size) (i64.const 16)) (i64.const 8)))
+            )
+            (func (export "grow_in_host") (result i64)
+                (call $grow_in_host)
+                (i64.load (i64.sub (i64.shl (memory.size) (i64.const 16)) (i64.const 8)))
+                (i64.add)
+            )
+            (func (export "grow_when_reentered") (result i64)
+                (call $reenter (ref.func $grow_in_wasm))
+                (i64.load (i64.sub (i64.shl (memory.size) (i64.const 16)) (i64.const 8)))
+            )
+        )
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    assert_eq!(call(&mut store, &instance, "grow_in_host", &[]).unwrap(), 0);
+    assert_eq!(call(&mut store, &instance, "grow_when_reentered", &[]).unwrap(), 0);
+    assert_eq!(call(&mut store, &instance, "grow_in_host", &[]).unwrap(), 0);
+}
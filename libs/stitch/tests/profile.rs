@@ -0,0 +1,120 @@
+use {
+    makepad_stitch::{Engine, Func, Linker, Module, Store, Val},
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+const WAT: &str = r#"
+    (module $app
+        (import "env" "tick" (func $tick))
+
+        (func $leaf (param i32) (result i32)
+            (i32.add (local.get 0) (i32.const 1))
+        )
+
+        (func $loop (param $n i32) (result i32)
+            (local $acc i32)
+            (block $done
+                (loop $loop
+                    (br_if $done (i32.eqz (local.get $n)))
+                    (local.set $acc (call $leaf (local.get $acc)))
+                    (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                    (br $loop)
+                )
+            )
+            (call $tick)
+            (local.get $acc)
+        )
+
+        (func (export "main") (param i32) (result i32)
+            (call $loop (local.get 0))
+        )
+    )
+"#;
+
+fn encode(wat: &str) -> Vec<u8> {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    wat.encode().unwrap()
+}
+
+fn run(engine: Engine, n: i32) -> Store {
+    let mut store = Store::new(engine);
+    let module = Module::new(store.engine(), &encode(WAT)).unwrap();
+    let mut linker = Linker::new();
+    linker.define("env", "tick", Func::wrap(&mut store, || {}));
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let mut results = [Val::I32(0)];
+    instance
+        .exported_func("main")
+        .unwrap()
+        .call(&mut store, &[Val::I32(n)], &mut results)
+        .unwrap();
+    assert_eq!(results[0].to_i32(), Some(n));
+    store
+}
+
+#[test]
+fn disabled() {
+    let store = run(Engine::new(), 10);
+    assert!(store.profile().is_none());
+}
+
+#[test]
+fn call_and_instr_counts() {
+    let store = run(Engine::with_profiling(1), 10);
+    let profile = store.profile().unwrap();
+    let mut funcs: Vec<_> = profile
+        .funcs()
+        .map(|func| {
+            (
+                func.to_string(),
+                func.func_idx(),
+                func.call_count(),
+                func.instr_count(),
+            )
+        })
+        .collect();
+    funcs.sort_by_key(|&(_, func_idx, _, _)| func_idx);
+    assert_eq!(
+        funcs,
+        [
+            // local.get, i32.const, i32.add, end
+            ("app!leaf".to_string(), 1, 10, 40),
+            // Instructions are counted per block, in the same way as fuel is consumed.
+            ("app!loop".to_string(), 2, 1, 137),
+            ("app!<wasm function 3>".to_string(), 3, 1, 3),
+        ]
+    );
+}
+
+#[test]
+fn folded_stacks() {
+    let store = run(Engine::with_profiling(1), 10);
+    let folded_stacks = store.profile().unwrap().folded_stacks();
+    let lines: Vec<_> = folded_stacks.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "app!<wasm function 3> 3",
+            "app!<wasm function 3>;app!loop 137",
+            "app!<wasm function 3>;app!loop;app!leaf 40",
+        ]
+    );
+}
+
+#[test]
+fn sampled_stacks() {
+    let store = run(Engine::with_profiling(100), 1000);
+    let profile = store.profile().unwrap();
+    let total_instr_count: u64 = profile.funcs().map(|func| func.instr_count()).sum();
+    let sampled_instr_count: u64 = profile
+        .folded_stacks()
+        .lines()
+        .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert!(sampled_instr_count <= total_instr_count);
+    assert!(sampled_instr_count > total_instr_count - 100);
+}
@@ -0,0 +1,185 @@
+use {
+    makepad_stitch::{
+        Engine, ExternRef, Instance, Linker, Module, Snapshot, SnapshotError, Store, Val,
+    },
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+const WAT: &str = r#"
+    (module
+        (memory (export "mem") 1)
+        (global $counter (export "counter") (mut i32) (i32.const 0))
+        (global $handler (mut funcref) (ref.null func))
+        (global $host (export "host") (mut externref) (ref.null extern))
+        (table $table 1 funcref)
+        (elem declare func $double $square)
+        (data $greeting "hello")
+
+        (func $double (param i32) (result i32)
+            (i32.mul (local.get 0) (i32.const 2))
+        )
+
+        (func $square (param i32) (result i32)
+            (i32.mul (local.get 0) (local.get 0))
+        )
+
+        (func (export "step") (result i32)
+            (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+            (i32.store (i32.const 0) (global.get $counter))
+            (if (i32.eq (global.get $counter) (i32.const 1))
+                (then
+                    (global.set $handler (ref.func $double))
+                    (table.set $table (i32.const 0) (ref.func $square))
+                    (memory.init $greeting (i32.const 16) (i32.const 0) (i32.const 5))
+                    (data.drop $greeting)
+                )
+            )
+            (if (i32.eq (global.get $counter) (i32.const 2))
+                (then
+                    (drop (memory.grow (i32.const 1)))
+                    (i32.store (i32.const 65536) (i32.const 42))
+                )
+            )
+            (global.get $counter)
+        )
+
+        (func (export "apply") (param i32) (result i32)
+            (i32.add
+                (call_indirect $table (param i32) (result i32) (local.get 0) (i32.const 0))
+                (i32.load (i32.const 65536))
+            )
+        )
+
+        (func (export "handler_is_null") (result i32)
+            (ref.is_null (global.get $handler))
+        )
+    )
+"#;
+
+fn encode(wat: &str) -> Vec<u8> {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    wat.encode().unwrap()
+}
+
+fn instantiate(bytes: &[u8]) -> (Store, Instance) {
+    let mut store = Store::new(Engine::new());
+    let module = Module::new(store.engine(), bytes).unwrap();
+    let instance = Linker::new().instantiate(&mut store, &module).unwrap();
+    (store, instance)
+}
+
+fn call(store: &mut Store, instance: &Instance, name: &str, params: &[Val]) -> i32 {
+    let mut results = [Val::I32(0)];
+    instance
+        .exported_func(name)
+        .unwrap()
+        .call(store, params, &mut results)
+        .unwrap();
+    results[0].to_i32().unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let bytes = encode(WAT);
+    let (mut store, instance) = instantiate(&bytes);
+    call(&mut store, &instance, "step", &[]);
+    call(&mut store, &instance, "step", &[]);
+    let snapshot = Snapshot::from_bytes(&store.snapshot(&instance).unwrap().to_bytes()).unwrap();
+
+    let (mut restored_store, restored) = instantiate(&bytes);
+    restored_store.restore(&restored, &snapshot).unwrap();
+    let mem = restored.exported_mem("mem").unwrap();
+    assert_eq!(mem.size(&restored_store), 2);
+    assert_eq!(&mem.bytes(&restored_store)[16..21], b"hello");
+    assert_eq!(
+        restored
+            .exported_global("counter")
+            .unwrap()
+            .get(&restored_store)
+            .to_i32(),
+        Some(2)
+    );
+    assert_eq!(
+        call(&mut restored_store, &restored, "apply", &[Val::I32(3)]),
+        51
+    );
+    assert_eq!(
+        call(&mut restored_store, &restored, "handler_is_null", &[]),
+        0
+    );
+
+    // The restored instance carries on from where the original one left off.
+    assert_eq!(call(&mut restored_store, &restored, "step", &[]), 3);
+    assert_eq!(call(&mut store, &instance, "step", &[]), 3);
+    assert_eq!(
+        mem.bytes(&restored_store),
+        instance.exported_mem("mem").unwrap().bytes(&store)
+    );
+}
+
+#[test]
+fn restore_rewinds_state() {
+    let bytes = encode(WAT);
+    let (mut store, instance) = instantiate(&bytes);
+    let snapshot = store.snapshot(&instance).unwrap();
+    call(&mut store, &instance, "step", &[]);
+    store.restore(&instance, &snapshot).unwrap();
+    assert_eq!(call(&mut store, &instance, "step", &[]), 1);
+    assert_eq!(
+        &instance.exported_mem("mem").unwrap().bytes(&store)[16..21],
+        b"hello"
+    );
+
+    // Memories can't shrink, so a snapshot from before the memory grew can no longer be restored.
+    call(&mut store, &instance, "step", &[]);
+    assert!(matches!(
+        store.restore(&instance, &snapshot),
+        Err(SnapshotError::StateMismatch)
+    ));
+}
+
+#[test]
+fn module_mismatch() {
+    let (mut store, instance) = instantiate(&encode(WAT));
+    call(&mut store, &instance, "step", &[]);
+    let snapshot = store.snapshot(&instance).unwrap();
+
+    let (mut other_store, other) = instantiate(&encode(&WAT.replace("hello", "world")));
+    assert!(matches!(
+        other_store.restore(&other, &snapshot),
+        Err(SnapshotError::ModuleMismatch)
+    ));
+}
+
+#[test]
+fn malformed() {
+    let (store, instance) = instantiate(&encode(WAT));
+    let bytes = store.snapshot(&instance).unwrap().to_bytes();
+    assert!(matches!(
+        Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(SnapshotError::Malformed)
+    ));
+    assert!(matches!(
+        Snapshot::from_bytes(b"\0asm\x01\0\0\0"),
+        Err(SnapshotError::Malformed)
+    ));
+}
+
+#[test]
+fn host_refs() {
+    let (mut store, instance) = instantiate(&encode(WAT));
+    let host = ExternRef::new::<i32>(&mut store, 42);
+    instance
+        .exported_global("host")
+        .unwrap()
+        .set(&mut store, host.into())
+        .unwrap();
+    assert!(matches!(
+        store.snapshot(&instance),
+        Err(SnapshotError::UnsupportedRef)
+    ));
+}
@@ -5,9 +5,10 @@ use {
     },
     std::{collections::HashMap, sync::Arc},
     wast::{
-        core::{HeapType, NanPattern, WastArgCore, WastRetCore},
+        core::{HeapType, NanPattern, V128Pattern, WastArgCore, WastRetCore},
         parser,
         parser::ParseBuffer,
+        token::{Float32, Float64},
         QuoteWat, Wast, WastArg, WastDirective, WastExecute, WastInvoke, WastRet, Wat,
     },
 };
@@ -64,6 +65,7 @@ impl WastRunner {
                     min: 1,
                     max: Some(2),
                 },
+                is_64: false,
             },
         );
         let global_i32 = Global::new(
@@ -137,6 +139,16 @@ impl WastRunner {
                     let bytes = wat.encode().unwrap();
                     self.create_instance(None, &bytes).unwrap();
                 }
+                // The multi-memory proposal makes these modules valid, so these assertions no
+                // longer hold.
+                WastDirective::AssertMalformed {
+                    message: "zero byte expected",
+                    ..
+                }
+                | WastDirective::AssertInvalid {
+                    message: "multiple memories",
+                    ..
+                } => {}
                 WastDirective::AssertMalformed {
                     module: QuoteWat::Wat(Wat::Module(mut module)),
                     ..
@@ -253,6 +265,7 @@ impl WastRunner {
                     WastArgCore::I64(arg) => arg.into(),
                     WastArgCore::F32(arg) => f32::from_bits(arg.bits).into(),
                     WastArgCore::F64(arg) => f64::from_bits(arg.bits).into(),
+                    WastArgCore::V128(arg) => u128::from_le_bytes(arg.to_le_bytes()).into(),
                     WastArgCore::RefNull(HeapType::Func) => FuncRef::null().into(),
                     WastArgCore::RefNull(HeapType::Extern) => ExternRef::null().into(),
                     WastArgCore::RefExtern(val) => ExternRef::new(&mut self.store, val).into(),
@@ -295,42 +308,44 @@ fn assert_result(store: &Store, actual: Val, expected: WastRet<'_>) {
             WastRetCore::I64(expected) => {
                 assert_eq!(actual.to_i64().unwrap(), expected)
             }
-            WastRetCore::F32(expected) => match expected {
-                NanPattern::CanonicalNan => {
-                    assert!(
-                        actual.to_f32().unwrap().to_bits() & 0b0_11111111_11111111111111111111111
-                            == 0b0_11111111_10000000000000000000000
-                    );
+            WastRetCore::F32(expected) => assert_f32(actual.to_f32().unwrap(), expected),
+            WastRetCore::F64(expected) => assert_f64(actual.to_f64().unwrap(), expected),
+            WastRetCore::V128(expected) => {
+                let actual = actual.to_v128().unwrap().to_le_bytes();
+                match expected {
+                    V128Pattern::I8x16(expected) => {
+                        assert_eq!(
+                            actual,
+                            u128::from_le_bytes(expected.map(|x| x as u8)).to_le_bytes()
+                        );
+                    }
+                    V128Pattern::I16x8(expected) => {
+                        for (actual, expected) in actual.chunks(2).zip(expected) {
+                            assert_eq!(actual, expected.to_le_bytes());
+                        }
+                    }
+                    V128Pattern::I32x4(expected) => {
+                        for (actual, expected) in actual.chunks(4).zip(expected) {
+                            assert_eq!(actual, expected.to_le_bytes());
+                        }
+                    }
+                    V128Pattern::I64x2(expected) => {
+                        for (actual, expected) in actual.chunks(8).zip(expected) {
+                            assert_eq!(actual, expected.to_le_bytes());
+                        }
+                    }
+                    V128Pattern::F32x4(expected) => {
+                        for (actual, expected) in actual.chunks(4).zip(expected) {
+                            assert_f32(f32::from_le_bytes(actual.try_into().unwrap()), expected);
+                        }
+                    }
+                    V128Pattern::F64x2(expected) => {
+                        for (actual, expected) in actual.chunks(8).zip(expected) {
+                            assert_f64(f64::from_le_bytes(actual.try_into().unwrap()), expected);
+                        }
+                    }
                 }
-                NanPattern::ArithmeticNan => {
-                    assert!(
-                        actual.to_f32().unwrap().to_bits() & 0b0_11111111_11111111111111111111111
-                            >= 0b0_11111111_10000000000000000000000
-                    );
-                }
-                NanPattern::Value(expected_result) => {
-                    assert_eq!(actual.to_f32().unwrap().to_bits(), expected_result.bits)
-                }
-            },
-            WastRetCore::F64(expected) => match expected {
-                NanPattern::CanonicalNan => {
-                    assert!(
-                        actual.to_f64().unwrap().to_bits()
-                            & 0b0_11111111111_1111111111111111111111111111111111111111111111111111
-                            == 0b0_11111111111_1000000000000000000000000000000000000000000000000000
-                    );
-                }
-                NanPattern::ArithmeticNan => {
-                    assert!(
-                        actual.to_f64().unwrap().to_bits()
-                            & 0b0_11111111111_1111111111111111111111111111111111111111111111111111
-                            >= 0b0_11111111111_1000000000000000000000000000000000000000000000000000
-                    );
-                }
-                NanPattern::Value(expected_result) => {
-                    assert_eq!(actual.to_f64().unwrap().to_bits(), expected_result.bits)
-                }
-            },
+            }
             WastRetCore::RefNull(Some(HeapType::Func)) => {
                 assert_eq!(actual, Val::FuncRef(FuncRef::null()));
             }
@@ -353,6 +368,48 @@ fn assert_result(store: &Store, actual: Val, expected: WastRet<'_>) {
     }
 }
 
+fn assert_f32(actual: f32, expected: NanPattern<Float32>) {
+    match expected {
+        NanPattern::CanonicalNan => {
+            assert!(
+                actual.to_bits() & 0b0_11111111_11111111111111111111111
+                    == 0b0_11111111_10000000000000000000000
+            );
+        }
+        NanPattern::ArithmeticNan => {
+            assert!(
+                actual.to_bits() & 0b0_11111111_11111111111111111111111
+                    >= 0b0_11111111_10000000000000000000000
+            );
+        }
+        NanPattern::Value(expected_result) => {
+            assert_eq!(actual.to_bits(), expected_result.bits)
+        }
+    }
+}
+
+fn assert_f64(actual: f64, expected: NanPattern<Float64>) {
+    match expected {
+        NanPattern::CanonicalNan => {
+            assert!(
+                actual.to_bits()
+                    & 0b0_11111111111_1111111111111111111111111111111111111111111111111111
+                    == 0b0_11111111111_1000000000000000000000000000000000000000000000000000
+            );
+        }
+        NanPattern::ArithmeticNan => {
+            assert!(
+                actual.to_bits()
+                    & 0b0_11111111111_1111111111111111111111111111111111111111111111111111
+                    >= 0b0_11111111111_1000000000000000000000000000000000000000000000000000
+            );
+        }
+        NanPattern::Value(expected_result) => {
+            assert_eq!(actual.to_bits(), expected_result.bits)
+        }
+    }
+}
+
 macro_rules! testsuite {
     ($($name:ident => $file_name:literal,)*) => {
         $(
@@ -437,65 +494,63 @@ testsuite! {
     ref_null => "ref_null.wast",
     r#return => "return.wast",
     select => "select.wast",
-    /*
-    simd_address => "simd/address.wast",
-    simd_align => "simd/align.wast",
-    simd_bit_shift => "simd/bit_shift.wast",
-    simd_bitwise => "simd/bitwise.wast",
-    simd_boolean => "simd/boolean.wast",
-    simd_const => "simd/const.wast",
-    simd_conversions => "simd/conversions.wast",
-    simd_f32x4 => "simd/f32x4.wast",
-    simd_f32x4_arith => "simd/f32x4-arith.wast",
-    simd_f32x4_cmp => "simd/f32x4-cmp.wast",
-    simd_f32x4_pmin_pmax => "simd/f32x4-pmin-pmax.wast",
-    simd_f32x4_rounding => "simd/f32x4-rounding.wast",
-    simd_f64x2 => "simd/f64x2.wast",
-    simd_f64x2_arith => "simd/f64x2-arith.wast",
-    simd_f64x2_cmp => "simd/f64x2-cmp.wast",
-    simd_f64x2_pmin_pmax => "simd/f64x2-pmin-pmax.wast",
-    simd_f64x2_rounding => "simd/f64x2-rounding.wast",
-    simd_i16x8_arith => "simd/i16x8-arith.wast",
-    simd_i16x8_arith2 => "simd/i16x8-arith2.wast",
-    simd_i16x8_cmp => "simd/i16x8-cmp.wast",
-    simd_i16x8_extadd_pairwise_i8x16 => "simd/i16x8-extadd-pairwise-i8x16.wast",
-    simd_i16x8_extmul_i8x16 => "simd/i16x8-extmul-i8x16.wast",
-    simd_i16x8_q15mulr_sat_s => "simd/i16x8-q15mulr-sat_s.wast",
-    simd_i16x8_sat_arith => "simd/i16x8-sat-arith.wast",
-    simd_i32x4_arith => "simd/i32x4-arith.wast",
-    simd_i32x4_arith2 => "simd/i32x4-arith2.wast",
-    simd_i32x4_cmp => "simd/i32x4-cmp.wast",
-    simd_i32x4_dot_i16x8 => "simd/i32x4-dot_i16x8.wast",
-    simd_i32x4_extadd_pairwise_i16x8 => "simd/i32x4-extadd-pairwise-i16x8.wast",
-    simd_i32x4_extmul_i16x8 => "simd/i32x4-extmul-i16x8.wast",
-    simd_i32x4_trunc_sat_f32x4 => "simd/i32x4-trunc-sat-f32x4.wast",
-    simd_i32x4_trunc_sat_f64x2 => "simd/i32x4-trunc-sat-f64x2.wast",
-    simd_i64x2_arith => "simd/i64x2-arith.wast",
-    simd_i64x2_arith2 => "simd/i64x2-arith2.wast",
-    simd_i64x2_cmp => "simd/i64x2-cmp.wast",
-    simd_i64x2_extmul_i32x4 => "simd/i64x2-extmul-i32x4.wast",
-    simd_i8x16_arith => "simd/i8x16-arith.wast",
-    simd_i8x16_arith2 => "simd/i8x16-arith2.wast",
-    simd_i8x16_cmp => "simd/i8x16-cmp.wast",
-    simd_i8x16_sat_arith => "simd/i8x16-sat-arith.wast",
-    simd_int_to_int_extend => "simd/int-to-int-extend.wast",
-    simd_lane => "simd/lane.wast",
-    simd_linking => "simd/linking.wast",
-    simd_load => "simd/load.wast",
-    simd_load16_lane => "simd/load16_lane.wast",
-    simd_load32_lane => "simd/load32_lane.wast",
-    simd_load64_lane => "simd/load64_lane.wast",
-    simd_load8_lane => "simd/load8_lane.wast",
-    simd_load_extend => "simd/load_extend.wast",
-    simd_load_splat => "simd/load_splat.wast",
-    simd_load_zero => "simd/load_zero.wast",
-    simd_splat => "simd/splat.wast",
-    simd_store => "simd/store.wast",
-    simd_store16_lane => "simd/store16_lane.wast",
-    simd_store32_lane => "simd/store32_lane.wast",
-    simd_store64_lane => "simd/store64_lane.wast",
-    simd_store8_lane => "simd/store8_lane.wast",
-    */
+    simd_address => "simd_address.wast",
+    simd_align => "simd_align.wast",
+    simd_bit_shift => "simd_bit_shift.wast",
+    simd_bitwise => "simd_bitwise.wast",
+    simd_boolean => "simd_boolean.wast",
+    simd_const => "simd_const.wast",
+    simd_conversions => "simd_conversions.wast",
+    simd_f32x4 => "simd_f32x4.wast",
+    simd_f32x4_arith => "simd_f32x4_arith.wast",
+    simd_f32x4_cmp => "simd_f32x4_cmp.wast",
+    simd_f32x4_pmin_pmax => "simd_f32x4_pmin_pmax.wast",
+    simd_f32x4_rounding => "simd_f32x4_rounding.wast",
+    simd_f64x2 => "simd_f64x2.wast",
+    simd_f64x2_arith => "simd_f64x2_arith.wast",
+    simd_f64x2_cmp => "simd_f64x2_cmp.wast",
+    simd_f64x2_pmin_pmax => "simd_f64x2_pmin_pmax.wast",
+    simd_f64x2_rounding => "simd_f64x2_rounding.wast",
+    simd_i16x8_arith => "simd_i16x8_arith.wast",
+    simd_i16x8_arith2 => "simd_i16x8_arith2.wast",
+    simd_i16x8_cmp => "simd_i16x8_cmp.wast",
+    simd_i16x8_extadd_pairwise_i8x16 => "simd_i16x8_extadd_pairwise_i8x16.wast",
+    simd_i16x8_extmul_i8x16 => "simd_i16x8_extmul_i8x16.wast",
+    simd_i16x8_q15mulr_sat_s => "simd_i16x8_q15mulr_sat_s.wast",
+    simd_i16x8_sat_arith => "simd_i16x8_sat_arith.wast",
+    simd_i32x4_arith => "simd_i32x4_arith.wast",
+    simd_i32x4_arith2 => "simd_i32x4_arith2.wast",
+    simd_i32x4_cmp => "simd_i32x4_cmp.wast",
+    simd_i32x4_dot_i16x8 => "simd_i32x4_dot_i16x8.wast",
+    simd_i32x4_extadd_pairwise_i16x8 => "simd_i32x4_extadd_pairwise_i16x8.wast",
+    simd_i32x4_extmul_i16x8 => "simd_i32x4_extmul_i16x8.wast",
+    simd_i32x4_trunc_sat_f32x4 => "simd_i32x4_trunc_sat_f32x4.wast",
+    simd_i32x4_trunc_sat_f64x2 => "simd_i32x4_trunc_sat_f64x2.wast",
+    simd_i64x2_arith => "simd_i64x2_arith.wast",
+    simd_i64x2_arith2 => "simd_i64x2_arith2.wast",
+    simd_i64x2_cmp => "simd_i64x2_cmp.wast",
+    simd_i64x2_extmul_i32x4 => "simd_i64x2_extmul_i32x4.wast",
+    simd_i8x16_arith => "simd_i8x16_arith.wast",
+    simd_i8x16_arith2 => "simd_i8x16_arith2.wast",
+    simd_i8x16_cmp => "simd_i8x16_cmp.wast",
+    simd_i8x16_sat_arith => "simd_i8x16_sat_arith.wast",
+    simd_int_to_int_extend => "simd_int_to_int_extend.wast",
+    simd_lane => "simd_lane.wast",
+    simd_linking => "simd_linking.wast",
+    simd_load => "simd_load.wast",
+    simd_load16_lane => "simd_load16_lane.wast",
+    simd_load32_lane => "simd_load32_lane.wast",
+    simd_load64_lane => "simd_load64_lane.wast",
+    simd_load8_lane => "simd_load8_lane.wast",
+    simd_load_extend => "simd_load_extend.wast",
+    simd_load_splat => "simd_load_splat.wast",
+    simd_load_zero => "simd_load_zero.wast",
+    simd_splat => "simd_splat.wast",
+    simd_store => "simd_store.wast",
+    simd_store16_lane => "simd_store16_lane.wast",
+    simd_store32_lane => "simd_store32_lane.wast",
+    simd_store64_lane => "simd_store64_lane.wast",
+    simd_store8_lane => "simd_store8_lane.wast",
     skip_stack_guard_page => "skip-stack-guard-page.wast",
     stack => "stack.wast",
     start => "start.wast",
@@ -521,4 +576,67 @@ testsuite! {
     utf8_import_field => "utf8-import-field.wast",
     utf8_import_module => "utf8-import-module.wast",
     utf8_invalid_encoding => "utf8-invalid-encoding.wast",
+    memory64_address => "proposals/memory64/address.wast",
+    memory64_address64 => "proposals/memory64/address64.wast",
+    memory64_align64 => "proposals/memory64/align64.wast",
+    memory64_binary_leb128 => "proposals/memory64/binary-leb128.wast",
+    memory64_binary => "proposals/memory64/binary.wast",
+    memory64_endianness64 => "proposals/memory64/endianness64.wast",
+    memory64_float_memory64 => "proposals/memory64/float_memory64.wast",
+    memory64_load64 => "proposals/memory64/load64.wast",
+    memory64_memory => "proposals/memory64/memory.wast",
+    memory64_memory64 => "proposals/memory64/memory64.wast",
+    memory64_memory_grow64 => "proposals/memory64/memory_grow64.wast",
+    memory64_memory_redundancy64 => "proposals/memory64/memory_redundancy64.wast",
+    memory64_memory_trap64 => "proposals/memory64/memory_trap64.wast",
+    memory64_simd_address => "proposals/memory64/simd_address.wast",
+    multi_memory_address0 => "proposals/multi-memory/address0.wast",
+    multi_memory_address1 => "proposals/multi-memory/address1.wast",
+    multi_memory_align0 => "proposals/multi-memory/align0.wast",
+    multi_memory_binary => "proposals/multi-memory/binary.wast",
+    multi_memory_binary0 => "proposals/multi-memory/binary0.wast",
+    multi_memory_data => "proposals/multi-memory/data.wast",
+    multi_memory_data0 => "proposals/multi-memory/data0.wast",
+    multi_memory_data1 => "proposals/multi-memory/data1.wast",
+    multi_memory_data_drop0 => "proposals/multi-memory/data_drop0.wast",
+    multi_memory_exports0 => "proposals/multi-memory/exports0.wast",
+    multi_memory_float_exprs0 => "proposals/multi-memory/float_exprs0.wast",
+    multi_memory_float_exprs1 => "proposals/multi-memory/float_exprs1.wast",
+    multi_memory_float_memory0 => "proposals/multi-memory/float_memory0.wast",
+    multi_memory_imports => "proposals/multi-memory/imports.wast",
+    multi_memory_imports0 => "proposals/multi-memory/imports0.wast",
+    multi_memory_imports1 => "proposals/multi-memory/imports1.wast",
+    multi_memory_imports2 => "proposals/multi-memory/imports2.wast",
+    multi_memory_imports3 => "proposals/multi-memory/imports3.wast",
+    multi_memory_imports4 => "proposals/multi-memory/imports4.wast",
+    multi_memory_linking0 => "proposals/multi-memory/linking0.wast",
+    multi_memory_linking1 => "proposals/multi-memory/linking1.wast",
+    multi_memory_linking2 => "proposals/multi-memory/linking2.wast",
+    multi_memory_linking3 => "proposals/multi-memory/linking3.wast",
+    multi_memory_load => "proposals/multi-memory/load.wast",
+    multi_memory_load0 => "proposals/multi-memory/load0.wast",
+    multi_memory_load1 => "proposals/multi-memory/load1.wast",
+    multi_memory_load2 => "proposals/multi-memory/load2.wast",
+    multi_memory_memory_multi => "proposals/multi-memory/memory-multi.wast",
+    multi_memory_memory => "proposals/multi-memory/memory.wast",
+    multi_memory_memory_copy0 => "proposals/multi-memory/memory_copy0.wast",
+    multi_memory_memory_copy1 => "proposals/multi-memory/memory_copy1.wast",
+    multi_memory_memory_fill0 => "proposals/multi-memory/memory_fill0.wast",
+    multi_memory_memory_grow => "proposals/multi-memory/memory_grow.wast",
+    multi_memory_memory_init0 => "proposals/multi-memory/memory_init0.wast",
+    multi_memory_memory_size => "proposals/multi-memory/memory_size.wast",
+    multi_memory_memory_size0 => "proposals/multi-memory/memory_size0.wast",
+    multi_memory_memory_size1 => "proposals/multi-memory/memory_size1.wast",
+    multi_memory_memory_size2 => "proposals/multi-memory/memory_size2.wast",
+    multi_memory_memory_size3 => "proposals/multi-memory/memory_size3.wast",
+    multi_memory_memory_trap0 => "proposals/multi-memory/memory_trap0.wast",
+    multi_memory_memory_trap1 => "proposals/multi-memory/memory_trap1.wast",
+    multi_memory_simd_memory_multi => "proposals/multi-memory/simd_memory-multi.wast",
+    multi_memory_start0 => "proposals/multi-memory/start0.wast",
+    multi_memory_store => "proposals/multi-memory/store.wast",
+    multi_memory_store0 => "proposals/multi-memory/store0.wast",
+    multi_memory_store1 => "proposals/multi-memory/store1.wast",
+    multi_memory_traps0 => "proposals/multi-memory/traps0.wast",
+    tail_call_return_call => "proposals/tail-call/return_call.wast",
+    tail_call_return_call_indirect => "proposals/tail-call/return_call_indirect.wast",
 }
@@ -0,0 +1,229 @@
+use {
+    makepad_stitch::{Engine, Error, Instance, Linker, MemDir, Module, Store, Wasi},
+    std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    },
+    wast::{
+        parser::{self, ParseBuffer},
+        Wat,
+    },
+};
+
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn instantiate(store: &mut Store, wasi: Wasi, wat: &str) -> Instance {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    let bytes = wat.encode().unwrap();
+    let module = Module::new(store.engine(), &bytes).unwrap();
+    let mut linker = Linker::new();
+    wasi.define(store, &mut linker);
+    linker.instantiate(store, &module).unwrap()
+}
+
+fn run(store: &mut Store, instance: &Instance) -> Result<(), Error> {
+    instance
+        .exported_func("_start")
+        .unwrap()
+        .call(store, &[], &mut [])
+}
+
+#[test]
+fn hello_world() {
+    let stdout = SharedBuf::default();
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        Wasi::new().stdout(stdout.clone()),
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 16) "Hello, world!\n")
+
+            (func (export "_start")
+                (i32.store (i32.const 0) (i32.const 16))
+                (i32.store (i32.const 4) (i32.const 14))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            )
+        )
+        "#,
+    );
+    run(&mut store, &instance).unwrap();
+    assert_eq!(stdout.contents(), "Hello, world!\n");
+}
+
+#[test]
+fn args_and_exit_code() {
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        Wasi::new().args(["prog", "a", "bc"]),
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "args_sizes_get"
+                (func $args_sizes_get (param i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit"
+                (func $proc_exit (param i32)))
+            (memory (export "memory") 1)
+
+            (func (export "_start")
+                (drop (call $args_sizes_get (i32.const 0) (i32.const 4)))
+                ;; Exit with argc * 100 + the size of the argument buffer.
+                (call $proc_exit
+                    (i32.add
+                        (i32.mul (i32.load (i32.const 0)) (i32.const 100))
+                        (i32.load (i32.const 4))))
+                unreachable
+            )
+        )
+        "#,
+    );
+    let result = run(&mut store, &instance);
+    assert!(matches!(result, Err(Error::Exit(310))));
+}
+
+#[test]
+fn mem_dir() {
+    let dir = MemDir::new();
+    dir.insert_file("in/input.txt", "stitch");
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        Wasi::new().preopen_mem_dir("/sandbox", dir.clone()),
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "path_open"
+                (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_read"
+                (func $fd_read (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_close"
+                (func $fd_close (param i32) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit"
+                (func $proc_exit (param i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 100) "in/input.txt")
+            (data (i32.const 120) "out.txt")
+            (data (i32.const 140) "../escape.txt")
+
+            (func $check (param i32)
+                (if (local.get 0) (then (call $proc_exit (local.get 0))))
+            )
+
+            (func (export "_start")
+                (local $fd i32)
+                ;; Paths outside of the preopened directory are rejected.
+                (if (i32.ne
+                        (call $path_open (i32.const 3) (i32.const 0) (i32.const 140) (i32.const 13)
+                            (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0))
+                        (i32.const 76))
+                    (then (call $proc_exit (i32.const 1))))
+
+                ;; Read the input file into a buffer at offset 200.
+                (call $check
+                    (call $path_open (i32.const 3) (i32.const 0) (i32.const 100) (i32.const 12)
+                        (i32.const 0) (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 0)))
+                (local.set $fd (i32.load (i32.const 0)))
+                (i32.store (i32.const 8) (i32.const 200))
+                (i32.store (i32.const 12) (i32.const 64))
+                (call $check (call $fd_read (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)))
+                (call $check (call $fd_close (local.get $fd)))
+
+                ;; Write it to a newly created output file, twice.
+                (call $check
+                    (call $path_open (i32.const 3) (i32.const 0) (i32.const 120) (i32.const 7)
+                        (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0)))
+                (local.set $fd (i32.load (i32.const 0)))
+                (i32.store (i32.const 12) (i32.load (i32.const 16)))
+                (call $check (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)))
+                (call $check (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)))
+                (call $check (call $fd_close (local.get $fd)))
+            )
+        )
+        "#,
+    );
+    run(&mut store, &instance).unwrap();
+    assert_eq!(dir.read_file("out.txt").unwrap(), b"stitchstitch");
+    assert_eq!(dir.read_file("in/input.txt").unwrap(), b"stitch");
+}
+
+#[test]
+fn mem_file_size_limit() {
+    let dir = MemDir::new();
+    let mut store = Store::new(Engine::new());
+    let instance = instantiate(
+        &mut store,
+        Wasi::new()
+            .preopen_mem_dir("/sandbox", dir.clone())
+            .max_mem_file_size(16),
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "path_open"
+                (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_seek"
+                (func $fd_seek (param i32 i64 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_filestat_set_size"
+                (func $fd_filestat_set_size (param i32 i64) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit"
+                (func $proc_exit (param i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 100) "out.txt")
+            (data (i32.const 120) "0123456789")
+
+            (func $expect (param i32 i32)
+                (if (i32.ne (local.get 0) (local.get 1)) (then (call $proc_exit (local.get 0))))
+            )
+
+            (func (export "_start")
+                (local $fd i32)
+                (call $expect
+                    (call $path_open (i32.const 3) (i32.const 0) (i32.const 100) (i32.const 7)
+                        (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0))
+                    (i32.const 0))
+                (local.set $fd (i32.load (i32.const 0)))
+                (i32.store (i32.const 8) (i32.const 120))
+                (i32.store (i32.const 12) (i32.const 10))
+
+                ;; The first write fits, the second would make the file 20 bytes long.
+                (call $expect (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)) (i32.const 0))
+                (call $expect (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)) (i32.const 22))
+
+                ;; Positions and sizes far beyond the limit fail instead of growing the file.
+                (call $expect (call $fd_seek (local.get $fd) (i64.const 0x4000000000000000) (i32.const 0) (i32.const 16)) (i32.const 0))
+                (call $expect (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)) (i32.const 22))
+                (call $expect (call $fd_seek (local.get $fd) (i64.const -1) (i32.const 0) (i32.const 16)) (i32.const 0))
+                (call $expect (call $fd_write (local.get $fd) (i32.const 8) (i32.const 1) (i32.const 16)) (i32.const 22))
+                (call $expect (call $fd_filestat_set_size (local.get $fd) (i64.const 0x4000000000000000)) (i32.const 22))
+                (call $expect (call $fd_filestat_set_size (local.get $fd) (i64.const 17)) (i32.const 22))
+                (call $expect (call $fd_filestat_set_size (local.get $fd) (i64.const 16)) (i32.const 0))
+            )
+        )
+        "#,
+    );
+    run(&mut store, &instance).unwrap();
+    assert_eq!(dir.read_file("out.txt").unwrap(), b"0123456789\0\0\0\0\0\0");
+}
@@ -16,6 +16,9 @@ pub mod live_component;
 pub mod live_node_cbor;
 //pub mod live_node_cbor;
 pub mod live_node_reader;
+pub mod live_formatter;
+pub mod live_query;
+pub mod live_lsp;
 
 pub use makepad_math;
 pub use makepad_derive_live;
@@ -69,6 +72,15 @@ pub use {
         live_node_reader::{
             LiveNodeReader,
         },
+        live_formatter::{
+            format_live_design,
+            format_live_body,
+        },
+        live_query::{
+            LiveCompletion,
+            LiveCompletionKind,
+            LiveHover,
+        },
         live_node::{
             LiveImport,
             LiveProp,
@@ -98,6 +110,7 @@ pub use {
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         live_error::{
             LiveError,
+            LiveErrorSpan,
             LiveFileError
         },
         live_document::{LiveOriginal, LiveExpanded}
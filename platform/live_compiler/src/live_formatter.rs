@@ -0,0 +1,271 @@
+use {
+    std::any::TypeId,
+    crate::{
+        makepad_live_id::*,
+        makepad_live_tokenizer::{TokenWithLen, Delim, FullToken, State, Cursor, live_error_origin, LiveErrorOrigin},
+        live_error::LiveError,
+        live_parser::LiveParser,
+        live_node::{LiveNode, LiveTypeInfo},
+        live_ptr::{LiveFileId, LiveModuleId},
+        live_registry::LiveRegistry,
+        live_token::LiveToken,
+        span::{TextSpan, TextPos},
+    }
+};
+
+// The canonical formatting of live_design!{} bodies. Only the whitespace between tokens is ever
+// touched, comments and literals are copied verbatim. Line breaks are kept where the author put
+// them (collapsing runs of blank lines), lines are indented 4 spaces per open delimiter, and the
+// spacing inside a line is normalised (`a: b`, `a = <B> {`, `a, b`, `{a}`, `f(a)`).
+// Every formatted body is parsed again and compared against the original, so the formatter can
+// never change what a live_design!{} means.
+
+const INDENT: &str = "    ";
+
+/// Formats every `live_design!{}` block in a Rust source file, leaving the rest of the file alone.
+pub fn format_live_design(source: &str) -> Result<String, LiveError> {
+    let chars: Vec<char> = source.chars().collect();
+    let tokens = tokenize_full(&chars);
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        if let Some((open, close)) = find_live_design(&tokens, index) {
+            let body_start = tokens[open].0 + 1;
+            let body_end = tokens[close].0;
+            let line_start = chars[..tokens[index].0].iter().rposition( | c | *c == '\n').map( | i | i + 1).unwrap_or(0);
+            let base_indent: String = chars[line_start..].iter().take_while( | c | **c == ' ' || **c == '\t').collect();
+            let start_pos = TextPos {
+                line: chars[..body_start].iter().filter( | c | **c == '\n').count() as u32,
+                column: 0
+            };
+            let body: String = chars[body_start..body_end].iter().collect();
+            out.extend(&chars[copied..body_start]);
+            out.push_str(&format_body(&body, &base_indent, start_pos) ?);
+            copied = body_end;
+            index = close + 1;
+        }
+        else {
+            index += 1;
+        }
+    }
+    out.extend(&chars[copied..]);
+    Ok(out)
+}
+
+/// Formats the body of a single `live_design!{}` block (the text between its braces).
+///
+/// The result starts with a newline and ends with a newline followed by `base_indent`, so that it
+/// can be put back between the braces directly. The contents are indented one level deeper than
+/// `base_indent`, which should be the indentation of the line that contains `live_design!`.
+pub fn format_live_body(body: &str, base_indent: &str) -> Result<String, LiveError> {
+    format_body(body, base_indent, TextPos::default())
+}
+
+fn format_body(body: &str, base_indent: &str, start_pos: TextPos) -> Result<String, LiveError> {
+    let (old_tokens, old_nodes) = parse_body(body, start_pos) ?;
+
+    let chars: Vec<char> = body.chars().collect();
+    let tokens = tokenize_full(&chars);
+    let formatted = format_lines(&chars, &tokens, &split_lines(&chars, &tokens), base_indent);
+
+    let (new_tokens, new_nodes) = parse_body(&formatted, start_pos) ?;
+    if old_tokens != new_tokens
+        || old_nodes.len() != new_nodes.len()
+        || old_nodes.iter().zip(new_nodes.iter()).any( | (a, b) | a.id != b.id || a.value != b.value) {
+        return Err(LiveError {
+            origin: live_error_origin!(),
+            span: TextSpan {file_id: LiveFileId::default(), start: start_pos, end: start_pos}.into(),
+            message: "Formatting live_design changed its meaning, leaving it untouched".to_string()
+        })
+    }
+    Ok(formatted)
+}
+
+fn tokenize_full(chars: &[char]) -> Vec<(usize, TokenWithLen)> {
+    let mut state = State::default();
+    let mut scratch = String::new();
+    let mut cursor = Cursor::new(chars, &mut scratch);
+    let mut tokens = Vec::new();
+    let mut last_index = 0;
+    loop {
+        let (next_state, full_token) = state.next(&mut cursor);
+        if let Some(full_token) = full_token {
+            tokens.push((last_index, full_token));
+        }
+        else {
+            break;
+        }
+        state = next_state;
+        last_index = cursor.index();
+    }
+    tokens
+}
+
+// returns the token indices of the braces of a live_design!{} starting at `index`
+fn find_live_design(tokens: &[(usize, TokenWithLen)], index: usize) -> Option<(usize, usize)> {
+    let next_non_ws = | index: usize | (index..tokens.len()).find( | i | !tokens[*i].1.is_whitespace());
+    if tokens[index].1.token != FullToken::Ident(live_id!(live_design)) {
+        return None
+    }
+    let bang = next_non_ws(index + 1) ?;
+    if tokens[bang].1.token != FullToken::Punct(live_id!(!)) {
+        return None
+    }
+    let open = next_non_ws(bang + 1) ?;
+    if !tokens[open].1.is_open_delim(Delim::Brace) {
+        return None
+    }
+    let mut depth = 0;
+    for (close, (_, token)) in tokens.iter().enumerate().skip(open + 1) {
+        if token.is_open_delim(Delim::Brace) {
+            depth += 1;
+        }
+        else if token.is_close_delim(Delim::Brace) {
+            if depth == 0 {
+                return Some((open, close))
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+fn parse_body(body: &str, start_pos: TextPos) -> Result<(Vec<LiveToken>, Vec<LiveNode>), LiveError> {
+    let tokens = LiveRegistry::tokenize_from_str(body, start_pos, LiveFileId::default()) ?;
+    // every {{RustType}} consumes a type info, we only compare what the parser produces so dummies will do
+    let rust_types = tokens.windows(2).filter( | w | {
+        w[0].token == LiveToken::Open(Delim::Brace) && w[1].token == LiveToken::Open(Delim::Brace)
+    }).count();
+    let live_type_infos = vec![LiveTypeInfo {
+        live_type: TypeId::of::<()>(),
+        type_name: LiveId(0),
+        module_id: LiveModuleId::default(),
+        live_ignore: false,
+        fields: Vec::new()
+    }; rust_types];
+    let mut parser = LiveParser::new(&tokens, &live_type_infos, LiveFileId::default());
+    let original = parser.parse_live_document() ?;
+    Ok((tokens.into_iter().map( | t | t.token).collect(), original.nodes))
+}
+
+struct FormatLine {
+    blank_before: bool,
+    // token index and whether it had whitespace in front of it
+    tokens: Vec<(usize, bool)>,
+}
+
+fn split_lines(chars: &[char], tokens: &[(usize, TokenWithLen)]) -> Vec<FormatLine> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut newlines = 0;
+    let mut had_ws = false;
+    for (index, (start, token)) in tokens.iter().enumerate() {
+        if token.is_whitespace() {
+            let count = chars[*start..*start + token.len].iter().filter( | c | **c == '\n').count();
+            if count > 0 && !line.is_empty() {
+                lines.push(FormatLine {blank_before: newlines > 1, tokens: std::mem::take(&mut line)});
+                newlines = 0;
+            }
+            newlines += count;
+            had_ws = true;
+        }
+        else {
+            line.push((index, had_ws));
+            had_ws = false;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(FormatLine {blank_before: newlines > 1, tokens: line});
+    }
+    lines
+}
+
+fn format_lines(chars: &[char], tokens: &[(usize, TokenWithLen)], lines: &[FormatLine], base_indent: &str) -> String {
+    let token = | index: usize | &tokens[index].1.token;
+    let mut out = String::new();
+    // the indentation level of the contents of each open delimiter
+    let mut stack: Vec<usize> = Vec::new();
+    let mut after_open = true;
+    for line in lines {
+        let first = token(line.tokens[0].0);
+        let level = if first.is_close() {
+            stack.last().map( | level | level - 1).unwrap_or(1)
+        }
+        else {
+            stack.last().copied().unwrap_or(1)
+        };
+        if line.blank_before && !after_open && !first.is_close() {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(base_indent);
+        for _ in 0..level {
+            out.push_str(INDENT);
+        }
+        let class_refs = class_ref_spans(tokens, line);
+        for (i, (index, had_ws)) in line.tokens.iter().enumerate() {
+            if i > 0 {
+                let in_class_ref = class_refs.iter().any( | (start, end) | i > *start && i <= *end);
+                if !in_class_ref && needs_space(token(line.tokens[i - 1].0), token(*index), *had_ws) {
+                    out.push(' ');
+                }
+            }
+            let (start, full_token) = &tokens[*index];
+            out.extend(&chars[*start..*start + full_token.len]);
+            if full_token.is_open() {
+                stack.push(level + 1);
+            }
+            else if full_token.is_close() {
+                stack.pop();
+            }
+        }
+        after_open = token(line.tokens.last().unwrap().0).is_open();
+    }
+    out.push('\n');
+    out.push_str(base_indent);
+    out
+}
+
+// the in-line token ranges of <Class> references, which are written without inner spaces
+fn class_ref_spans(tokens: &[(usize, TokenWithLen)], line: &FormatLine) -> Vec<(usize, usize)> {
+    let line_tokens: Vec<&FullToken> = line.tokens.iter().map( | (index, _) | &tokens[*index].1.token).collect();
+    let mut spans = Vec::new();
+    for i in 0..line_tokens.len() {
+        match line_tokens[i..] {
+            [FullToken::Punct(live_id!(<)), FullToken::Ident(_), FullToken::Punct(live_id!(>)), ..] => {
+                spans.push((i, i + 2))
+            }
+            _ => ()
+        }
+    }
+    spans
+}
+
+fn needs_space(prev: &FullToken, next: &FullToken, had_ws: bool) -> bool {
+    let space = match (prev, next) {
+        (_, FullToken::Comment) => true,
+        (FullToken::Comment, _) => had_ws,
+        (FullToken::Open(_), _) => false,
+        (_, FullToken::Close(_)) => false,
+        (_, FullToken::Punct(live_id!(,)) | FullToken::Punct(live_id!(;))) => false,
+        (FullToken::Punct(live_id!(,)) | FullToken::Punct(live_id!(;)), _) => true,
+        (FullToken::Punct(live_id!(.)) | FullToken::Punct(live_id!(::)), _) => false,
+        (_, FullToken::Punct(live_id!(.)) | FullToken::Punct(live_id!(::))) => false,
+        (_, FullToken::Punct(live_id!(:))) => false,
+        (FullToken::Punct(live_id!(:)), _) => true,
+        (_, FullToken::Open(Delim::Brace)) => true,
+        (FullToken::Punct(id), _) | (_, FullToken::Punct(id)) if is_spaced_operator(*id) => true,
+        _ => had_ws
+    };
+    // never glue together tokens that would tokenize differently without the space
+    space || had_ws && (prev.is_punct() && next.is_punct() || prev.is_parsed_number() && next == &FullToken::Punct(live_id!(.)))
+}
+
+fn is_spaced_operator(id: LiveId) -> bool {
+    matches!(id,
+        live_id!(=) | live_id!(==) | live_id!(!=) | live_id!(<=) | live_id!(>=)
+        | live_id!(+=) | live_id!(-=) | live_id!(*=) | live_id!(/=)
+        | live_id!(&&) | live_id!(||) | live_id!(->) | live_id!(=>)
+    )
+}
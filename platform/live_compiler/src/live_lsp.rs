@@ -0,0 +1,351 @@
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        io::{self, BufRead, Write},
+    },
+    crate::{
+        makepad_micro_serde::*,
+        live_error::LiveError,
+        live_ptr::LiveFileId,
+        live_query::LiveCompletionKind,
+        live_registry::{LiveFileChange, LiveRegistry},
+        span::{TextPos, TextSpan},
+    }
+};
+
+// A language server for the live_design!{} blocks of an app, speaking the language server
+// protocol over a pair of streams (normally stdin/stdout). Only the app itself knows the Rust
+// types behind its live_design!{} blocks, so the server runs inside an app built with
+// MAKEPAD=live_lsp: see Cx::serve_live_lsp.
+// Documents are synced in full, and every synced file goes through process_file_changes so its
+// token positions are exact 0 based file positions. Token columns count chars, LSP columns
+// count UTF-16 code units unless the client accepts utf-32, so otherwise we convert them
+// using the text of the file.
+
+#[derive(SerJson)]
+struct LspPosition {
+    line: u32,
+    character: u32,
+}
+
+#[derive(SerJson)]
+struct LspRange {
+    start: LspPosition,
+    end: LspPosition,
+}
+
+#[derive(SerJson)]
+struct LspLocation {
+    uri: String,
+    range: LspRange,
+}
+
+#[derive(SerJson)]
+struct LspCompletionItem {
+    label: String,
+    kind: u32,
+    detail: String,
+}
+
+#[derive(SerJson)]
+struct LspMarkupContent {
+    kind: String,
+    value: String,
+}
+
+#[derive(SerJson)]
+struct LspHover {
+    contents: LspMarkupContent,
+}
+
+#[derive(SerJson)]
+struct LspDiagnostic {
+    range: LspRange,
+    severity: u32,
+    source: String,
+    message: String,
+}
+
+#[derive(SerJson)]
+struct LspPublishDiagnostics {
+    uri: String,
+    diagnostics: Vec<LspDiagnostic>,
+}
+
+const CAPABILITIES: &str = r#""textDocumentSync":1,"completionProvider":{"triggerCharacters":["<"]},"hoverProvider":true,"definitionProvider":true"#;
+
+struct LiveLsp {
+    // the directory the file names in the registry are relative to, found on the first open
+    root: Option<String>,
+    // whether LSP columns count UTF-16 code units instead of chars
+    utf16: bool,
+    // the text of every file we gave to the registry, to convert columns with
+    texts: HashMap<LiveFileId, String>,
+}
+
+impl LiveRegistry {
+    /// Serves the language server protocol for the live_design!{} blocks in the registry,
+    /// reading messages from `input` and writing to `output` until the client exits.
+    pub fn serve_lsp(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> io::Result<()> {
+        let mut lsp = LiveLsp {root: None, utf16: true, texts: HashMap::new()};
+        while let Some(body) = read_message(input) ? {
+            let message = match JsonValue::deserialize_json(&body) {
+                Ok(message) => message,
+                Err(_) => continue
+            };
+            let method = message.key("method").and_then( | method | method.string()).map( | method | method.as_str()).unwrap_or("");
+            let params = message.key("params");
+            let result = match method {
+                "initialize" => {
+                    let encodings = params.and_then( | params | params.key("capabilities")).and_then( | caps | caps.key("general")).and_then( | general | general.key("positionEncodings"));
+                    lsp.utf16 = !matches!(encodings, Some(JsonValue::Array(encodings)) if encodings.iter().any( | encoding | encoding.string().map_or(false, | encoding | encoding == "utf-32")));
+                    Some(format!(
+                        r#"{{"capabilities":{{"positionEncoding":"{}",{}}},"serverInfo":{{"name":"makepad-live-lsp"}}}}"#,
+                        if lsp.utf16 {"utf-16"} else {"utf-32"},
+                        CAPABILITIES
+                    ))
+                }
+                "shutdown" => Some("null".to_string()),
+                "exit" => return Ok(()),
+                "textDocument/didOpen" => {
+                    let document = params.and_then( | params | params.key("textDocument"));
+                    let uri = document.and_then( | doc | doc.key("uri")).and_then( | uri | uri.string());
+                    let text = document.and_then( | doc | doc.key("text")).and_then( | text | text.string());
+                    if let (Some(uri), Some(text)) = (uri, text) {
+                        lsp.sync_document(self, output, uri, text) ?;
+                    }
+                    None
+                }
+                "textDocument/didChange" => {
+                    let uri = params.and_then( | params | params.key("textDocument")).and_then( | doc | doc.key("uri")).and_then( | uri | uri.string());
+                    let text = match params.and_then( | params | params.key("contentChanges")) {
+                        Some(JsonValue::Array(changes)) => changes.last().and_then( | change | change.key("text")).and_then( | text | text.string()),
+                        _ => None
+                    };
+                    if let (Some(uri), Some(text)) = (uri, text) {
+                        lsp.sync_document(self, output, uri, text) ?;
+                    }
+                    None
+                }
+                "textDocument/completion" => Some(match lsp.document_position(self, params) {
+                    Some((file_id, pos)) => self.completions_at(file_id, pos).into_iter().map( | completion | LspCompletionItem {
+                        label: completion.id.to_string(),
+                        kind: match completion.kind {
+                            LiveCompletionKind::Field => 5,
+                            LiveCompletionKind::Instance => 10,
+                            LiveCompletionKind::Component => 7,
+                        },
+                        detail: completion.detail
+                    }).collect::<Vec<_ >>().serialize_json(),
+                    None => "[]".to_string()
+                }),
+                "textDocument/hover" => Some(match lsp.document_position(self, params).and_then( | (file_id, pos) | self.hover_at(file_id, pos)) {
+                    Some(hover) => {
+                        let mut value = format!("```\n{}", hover.id);
+                        if let Some(type_name) = hover.type_name {
+                            value.push_str(&format!(": {}", type_name));
+                        }
+                        if !hover.value.is_empty() {
+                            value.push_str(&format!(" = {}", hover.value));
+                        }
+                        value.push_str("\n```");
+                        if let Some(default_value) = hover.default_value {
+                            value.push_str(&format!("\n\ndefault: `{}`", default_value));
+                        }
+                        LspHover {contents: LspMarkupContent {kind: "markdown".to_string(), value}}.serialize_json()
+                    }
+                    None => "null".to_string()
+                }),
+                "textDocument/definition" => Some(match lsp.document_position(self, params).and_then( | (file_id, pos) | self.definition_at(file_id, pos)) {
+                    Some(span) => LspLocation {
+                        uri: lsp.file_id_to_uri(self, span.file_id),
+                        range: lsp.span_to_range(span)
+                    }.serialize_json(),
+                    None => "null".to_string()
+                }),
+                _ => {
+                    if let Some(id) = message.key("id") {
+                        write_message(output, &format!(
+                            r#"{{"jsonrpc":"2.0","id":{},"error":{{"code":-32601,"message":"Method not found"}}}}"#,
+                            json_id(id)
+                        )) ?;
+                    }
+                    None
+                }
+            };
+            if let (Some(result), Some(id)) = (result, message.key("id")) {
+                write_message(output, &format!(r#"{{"jsonrpc":"2.0","id":{},"result":{}}}"#, json_id(id), result)) ?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LiveLsp {
+    fn uri_to_file_id(&mut self, registry: &LiveRegistry, uri: &str) -> Option<LiveFileId> {
+        let path = uri_to_path(uri);
+        let index = registry.live_files.iter().position( | file | {
+            path == file.file_name || path.ends_with(&format!("/{}", file.file_name))
+        }) ?;
+        if self.root.is_none() {
+            let file_name = &registry.live_files[index].file_name;
+            self.root = Some(path[..path.len() - file_name.len()].to_string());
+        }
+        Some(LiveFileId::new(index))
+    }
+
+    fn file_id_to_uri(&self, registry: &LiveRegistry, file_id: LiveFileId) -> String {
+        let file_name = registry.file_id_to_file_name(file_id);
+        match &self.root {
+            Some(root) if !file_name.starts_with('/') => path_to_uri(&format!("{}{}", root, file_name)),
+            _ => path_to_uri(file_name)
+        }
+    }
+
+    fn document_position(&mut self, registry: &LiveRegistry, params: Option<&JsonValue>) -> Option<(LiveFileId, TextPos)> {
+        let params = params ?;
+        let uri = params.key("textDocument")?.key("uri")?.string() ?;
+        let position = params.key("position") ?;
+        let file_id = self.uri_to_file_id(registry, uri) ?;
+        let line = json_u32(position.key("line") ?) ?;
+        let character = json_u32(position.key("character") ?) ?;
+        let column = match self.utf16_line(file_id, line) {
+            Some(text) => {
+                let mut units = 0;
+                text.chars().take_while( | c | {
+                    units += c.len_utf16() as u32;
+                    units <= character
+                }).count() as u32
+            }
+            None => character
+        };
+        Some((file_id, TextPos {line, column}))
+    }
+
+    // the line to convert columns with, if they need converting and we have the text of the file
+    fn utf16_line(&self, file_id: LiveFileId, line: u32) -> Option<&str> {
+        if !self.utf16 {
+            return None
+        }
+        let text = self.texts.get(&file_id) ?;
+        Some(text.lines().nth(line as usize).unwrap_or(""))
+    }
+
+    fn pos_to_lsp(&self, file_id: LiveFileId, pos: TextPos) -> LspPosition {
+        let character = match self.utf16_line(file_id, pos.line) {
+            Some(text) => text.chars().take(pos.column as usize).map( | c | c.len_utf16() as u32).sum(),
+            None => pos.column
+        };
+        LspPosition {line: pos.line, character}
+    }
+
+    fn span_to_range(&self, span: TextSpan) -> LspRange {
+        LspRange {
+            start: self.pos_to_lsp(span.file_id, span.start),
+            end: self.pos_to_lsp(span.file_id, span.end),
+        }
+    }
+
+    fn sync_document(&mut self, registry: &mut LiveRegistry, output: &mut dyn Write, uri: &str, text: &str) -> io::Result<()> {
+        let first_sync = self.root.is_none();
+        let file_id = if let Some(file_id) = self.uri_to_file_id(registry, uri) {file_id} else {return Ok(())};
+        let mut changes = Vec::new();
+        // the first time we know where the files are on disk, load them all so that the
+        // positions of every file are exact, not just of the open ones
+        if first_sync {
+            for (index, file) in registry.live_files.iter().enumerate() {
+                if index != file_id.to_index() {
+                    if let Ok(content) = fs::read_to_string(uri_to_path(&self.file_id_to_uri(registry, LiveFileId::new(index)))) {
+                        self.texts.insert(LiveFileId::new(index), content.clone());
+                        changes.push(LiveFileChange {file_name: file.file_name.clone(), content});
+                    }
+                }
+            }
+        }
+        let file_name = registry.file_id_to_file_name(file_id).to_string();
+        self.texts.insert(file_id, text.to_string());
+        changes.push(LiveFileChange {file_name: file_name.clone(), content: text.to_string()});
+        let mut errors = Vec::new();
+        registry.process_file_changes(changes, &mut errors);
+        let diagnostics = errors.into_iter().map( | error: LiveError | registry.live_error_to_live_file_error(error)).filter( | error | error.file == file_name).map( | error | LspDiagnostic {
+            range: self.span_to_range(error.span),
+            severity: 1,
+            source: "live_design".to_string(),
+            message: error.message
+        }).collect();
+        let notification = LspPublishDiagnostics {uri: uri.to_string(), diagnostics};
+        write_message(output, &format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{}}}"#,
+            notification.serialize_json()
+        ))
+    }
+}
+
+fn read_message(input: &mut dyn BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line) ? == 0 {
+            return Ok(None)
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break
+            }
+            continue
+        }
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = len.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; content_length.unwrap()];
+    input.read_exact(&mut body) ?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn write_message(output: &mut dyn Write, body: &str) -> io::Result<()> {
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body) ?;
+    output.flush()
+}
+
+fn json_id(id: &JsonValue) -> String {
+    match id {
+        JsonValue::U64(id) => id.to_string(),
+        JsonValue::I64(id) => id.to_string(),
+        JsonValue::String(id) => id.serialize_json(),
+        _ => "null".to_string()
+    }
+}
+
+fn json_u32(value: &JsonValue) -> Option<u32> {
+    match value {
+        JsonValue::U64(v) => Some(*v as u32),
+        JsonValue::I64(v) => Some(*v as u32),
+        _ => None
+    }
+}
+
+fn uri_to_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = path.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then( | hex | u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn path_to_uri(path: &str) -> String {
+    format!("file://{}", path.replace('%', "%25").replace(' ', "%20"))
+}
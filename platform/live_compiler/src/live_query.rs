@@ -0,0 +1,362 @@
+use {
+    crate::{
+        makepad_live_id::*,
+        live_eval::{live_eval, LiveEval},
+        live_node::{LiveNode, LiveValue, LiveType, LiveTypeInfo, LiveTypeField, LiveFieldKind, LiveProp, LivePropType, LiveIdAsProp},
+        live_node_vec::LiveNodeSliceApi,
+        live_ptr::LiveFileId,
+        live_registry::{LiveRegistry, LiveScopeTarget},
+        live_token::{LiveToken, LiveTokenId, TokenWithSpan},
+        span::{TextPos, TextSpan},
+    }
+};
+
+// Editor queries on the live_design!{} documents in the registry: completion, hover and
+// go-to-definition. Positions use the coordinates of the token spans of a file, which are exact
+// file positions (0 based) once a file went through process_file_changes.
+// The cursor is mapped onto the original nodes of a file to find the path of properties around
+// it, and that path is looked up in the expanded document, where all inherited properties and the
+// class parents are known.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LiveCompletionKind {
+    /// a property set with `:`, or a field of the Rust type of the component
+    Field,
+    /// a property set with `=`, usually a child component
+    Instance,
+    /// a component that can be cloned with `<Name>`
+    Component,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveCompletion {
+    pub id: LiveId,
+    pub kind: LiveCompletionKind,
+    /// the Rust type of a field, or the current value of a property
+    pub detail: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveHover {
+    pub id: LiveId,
+    pub type_name: Option<LiveId>,
+    pub value: String,
+    /// the value in the component this one was cloned from
+    pub default_value: Option<String>,
+}
+
+impl LiveRegistry {
+    /// The property names that can be written at `pos`: the fields of the Rust type of the
+    /// enclosing component and the properties it already has. Right after a `<` these are the
+    /// components in scope instead.
+    pub fn completions_at(&self, file_id: LiveFileId, pos: TextPos) -> Vec<LiveCompletion> {
+        let file = self.file_id_to_file(file_id);
+        let tokens = &file.original.tokens;
+        let nodes = &file.expanded.nodes;
+        let mut completions = Vec::new();
+        if nodes.is_empty() {
+            return completions
+        }
+        let next_token = tokens_before(tokens, pos);
+        let after_lt = | index: usize | index > 0 && tokens[index - 1].token == LiveToken::Punct(live_id!(<));
+        if after_lt(next_token) || next_token > 0 && tokens[next_token - 1].token.is_ident() && after_lt(next_token - 1) {
+            if let LiveValue::Root {id_resolve} = &nodes[0].value {
+                for (id, target) in id_resolve.iter() {
+                    if let Some((target_nodes, index)) = self.scope_target_nodes(file_id, *target) {
+                        if let LiveValue::Class {live_type, ..} = &target_nodes[index].value {
+                            completions.push(LiveCompletion {
+                                id: *id,
+                                kind: LiveCompletionKind::Component,
+                                detail: self.live_type_name(live_type).map(| name | name.to_string()).unwrap_or_default()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        else {
+            let (path, _) = node_path_at(&file.original.nodes, next_token);
+            let index = if let Some(index) = nodes.child_by_path(0, &path) {index} else {return completions};
+            if let Some(info) = self.path_type_info(nodes, &path) {
+                let mut fields = Vec::new();
+                live_fields(info, &mut fields);
+                for field in fields {
+                    completions.push(LiveCompletion {
+                        id: field.id,
+                        kind: LiveCompletionKind::Field,
+                        detail: field.live_type_info.type_name.to_string()
+                    });
+                }
+            }
+            if nodes[index].is_open() {
+                let mut child = nodes.first_child(index);
+                while let Some(index) = child {
+                    let node = &nodes[index];
+                    if !node.id.is_empty() && !completions.iter().any( | c | c.id == node.id) {
+                        completions.push(LiveCompletion {
+                            id: node.id,
+                            kind: if node.origin.has_prop_type(LivePropType::Instance) {
+                                LiveCompletionKind::Instance
+                            }
+                            else {
+                                LiveCompletionKind::Field
+                            },
+                            detail: self.live_value_to_string(nodes, index)
+                        });
+                    }
+                    child = nodes.next_child(index);
+                }
+            }
+        }
+        completions.sort_by_cached_key( | c | c.id.to_string());
+        completions.dedup_by_key( | c | c.id);
+        completions
+    }
+
+    /// Describes the identifier at `pos`: the type and value of a property, or of the component
+    /// or constant an identifier refers to.
+    pub fn hover_at(&self, file_id: LiveFileId, pos: TextPos) -> Option<LiveHover> {
+        let file = self.file_id_to_file(file_id);
+        let token_index = token_at(&file.original.tokens, pos) ?;
+        let id = if let LiveToken::Ident(id) = file.original.tokens[token_index].token {id} else {return None};
+        let nodes = &file.expanded.nodes;
+        if nodes.is_empty() {
+            return None
+        }
+        match node_path_at(&file.original.nodes, token_index) {
+            (mut path, Some(prop)) => {
+                path.push(prop);
+                let index = nodes.child_by_path(0, &path) ?;
+                Some(LiveHover {
+                    id,
+                    type_name: self.path_type_info(nodes, &path).map( | info | info.type_name),
+                    value: self.live_value_to_string(nodes, index),
+                    default_value: self.class_parent_value(file_id, nodes, &path),
+                })
+            }
+            (path, None) => {
+                let (nodes, index) = self.resolve_reference(file_id, &path, token_index, id) ?;
+                Some(LiveHover {
+                    id,
+                    type_name: match &nodes[index].value {
+                        LiveValue::Class {live_type, ..} => self.live_type_name(live_type),
+                        _ => None
+                    },
+                    value: self.live_value_to_string(nodes, index),
+                    default_value: None
+                })
+            }
+        }
+    }
+
+    /// Where the identifier at `pos` was defined. For a property that is the place it was first
+    /// defined in the chain of components it inherits from, for a component the one it was cloned
+    /// from, and for a reference the component or constant it refers to.
+    pub fn definition_at(&self, file_id: LiveFileId, pos: TextPos) -> Option<TextSpan> {
+        let file = self.file_id_to_file(file_id);
+        let token_index = token_at(&file.original.tokens, pos) ?;
+        let id = if let LiveToken::Ident(id) = file.original.tokens[token_index].token {id} else {return None};
+        let nodes = &file.expanded.nodes;
+        if nodes.is_empty() {
+            return None
+        }
+        let (target_nodes, target_index) = match node_path_at(&file.original.nodes, token_index) {
+            (mut path, Some(prop)) => {
+                path.push(prop);
+                let index = nodes.child_by_path(0, &path) ?;
+                match nodes[index].origin.first_def() {
+                    Some(first_def) if first_def != LiveTokenId::new(file_id, token_index) => {
+                        return Some(self.token_id_to_span(first_def))
+                    }
+                    _ => self.class_parent_nodes(file_id, nodes, index) ?
+                }
+            }
+            (path, None) => self.resolve_reference(file_id, &path, token_index, id) ?
+        };
+        Some(self.token_id_to_span(target_nodes[target_index].origin.token_id() ?))
+    }
+
+    // the component or constant an identifier that is not a property name refers to
+    fn resolve_reference(&self, file_id: LiveFileId, path: &[LiveProp], token_index: usize, id: LiveId) -> Option<(&[LiveNode], usize)> {
+        let file = self.file_id_to_file(file_id);
+        let nodes = &file.expanded.nodes;
+        let scope = nodes.child_by_path(0, path).unwrap_or(0);
+        // <Name> refers to the class parent of the component it is cloned into
+        if token_index > 0 && file.original.tokens[token_index - 1].token == LiveToken::Punct(live_id!(<)) {
+            if let Some(target) = self.class_parent_nodes(file_id, nodes, scope) {
+                return Some(target)
+            }
+        }
+        if let Some(index) = nodes.scope_up_down_by_name(scope, id.as_instance(), 0) {
+            return Some((nodes, index))
+        }
+        self.scope_target_nodes(file_id, self.find_scope_target(id, nodes) ?)
+    }
+
+    // the component a class was cloned from, a {{RustType}} is its own class parent
+    fn class_parent_nodes<'a>(&'a self, file_id: LiveFileId, nodes: &'a [LiveNode], index: usize) -> Option<(&'a [LiveNode], usize)> {
+        if let LiveValue::Class {class_parent, ..} = &nodes[index].value {
+            if (class_parent.file_id != file_id || class_parent.index as usize != index) && self.generation_valid(*class_parent) {
+                return Some(self.ptr_to_nodes_index(*class_parent))
+            }
+        }
+        None
+    }
+
+    // the value of a property in the class parent of the innermost component around it
+    fn class_parent_value(&self, file_id: LiveFileId, nodes: &[LiveNode], path: &[LiveProp]) -> Option<String> {
+        let mut index = 0;
+        let mut class = None;
+        for (depth, prop) in path.iter().enumerate() {
+            if depth + 1 < path.len() {
+                index = nodes.child_by_name(index, *prop) ?;
+                if nodes[index].is_class() {
+                    class = Some((index, depth + 1));
+                }
+            }
+        }
+        let (class_index, depth) = class ?;
+        let (parent_nodes, parent_index) = self.class_parent_nodes(file_id, nodes, class_index) ?;
+        let index = parent_nodes.child_by_path(parent_index, &path[depth..]) ?;
+        Some(self.live_value_to_string(parent_nodes, index))
+    }
+
+    fn scope_target_nodes(&self, file_id: LiveFileId, target: LiveScopeTarget) -> Option<(&[LiveNode], usize)> {
+        match target {
+            LiveScopeTarget::LocalPtr(index) => Some((&self.file_id_to_file(file_id).expanded.nodes, index)),
+            LiveScopeTarget::LivePtr(live_ptr) => if self.generation_valid(live_ptr) {
+                Some(self.ptr_to_nodes_index(live_ptr))
+            }
+            else {
+                None
+            }
+        }
+    }
+
+    // the Rust type of the node at path, following the {{RustType}} of classes and the field
+    // types of the objects inside them
+    fn path_type_info(&self, nodes: &[LiveNode], path: &[LiveProp]) -> Option<&LiveTypeInfo> {
+        let mut index = 0;
+        let mut info = None;
+        for prop in path {
+            index = nodes.child_by_name(index, *prop) ?;
+            info = match &nodes[index].value {
+                LiveValue::Class {live_type, ..} => self.live_type_infos.get(live_type),
+                _ => info.and_then( | info | live_field(info, prop.0)).map( | field | &field.live_type_info)
+            };
+        }
+        info
+    }
+
+    fn live_type_name(&self, live_type: &LiveType) -> Option<LiveId> {
+        self.live_type_infos.get(live_type).map( | info | info.type_name)
+    }
+
+    /// A short description of the value of a node, as used in hovers and completions.
+    pub fn live_value_to_string(&self, nodes: &[LiveNode], index: usize) -> String {
+        let node = &nodes[index];
+        match &node.value {
+            LiveValue::Bool(v) => format!("{}", v),
+            LiveValue::Int64(v) => format!("{}", v),
+            LiveValue::Uint64(v) => format!("{}", v),
+            LiveValue::Float64(v) => format!("{:?}", v),
+            LiveValue::Float32(v) => format!("{:?}", v),
+            LiveValue::Color(v) => format!("#{:08x}", v),
+            LiveValue::Vec2(v) => format!("vec2({:?}, {:?})", v.x, v.y),
+            LiveValue::Vec3(v) => format!("vec3({:?}, {:?}, {:?})", v.x, v.y, v.z),
+            LiveValue::Vec4(v) => format!("vec4({:?}, {:?}, {:?}, {:?})", v.x, v.y, v.z, v.w),
+            LiveValue::Id(id) | LiveValue::BareEnum(id) => format!("{}", id),
+            LiveValue::Str(_) | LiveValue::String(_) | LiveValue::InlineString(_) | LiveValue::Dependency(_) => {
+                format!("{:?}", self.live_node_as_string(node).unwrap())
+            }
+            LiveValue::Expr {..} => match live_eval(self, index, &mut (index + 1), nodes) {
+                Ok(LiveEval::Float64(v)) => format!("{:?}", v),
+                Ok(LiveEval::Int64(v)) => format!("{}", v),
+                Ok(LiveEval::Bool(v)) => format!("{}", v),
+                Ok(LiveEval::Vec2(v)) => format!("vec2({:?}, {:?})", v.x, v.y),
+                Ok(LiveEval::Vec3(v)) => format!("vec3({:?}, {:?}, {:?})", v.x, v.y, v.z),
+                Ok(LiveEval::Vec4(v)) => format!("vec4({:?}, {:?}, {:?}, {:?})", v.x, v.y, v.z, v.w),
+                Ok(LiveEval::String(v)) => format!("{:?}", v.as_str()),
+                Err(_) => "(..)".to_string()
+            },
+            LiveValue::Class {live_type, ..} => match self.live_type_name(live_type) {
+                Some(name) => format!("{{{{{}}}}} {{..}}", name),
+                None => "{..}".to_string()
+            },
+            LiveValue::TupleEnum(variant) => format!("{}(..)", variant),
+            LiveValue::NamedEnum(variant) => format!("{} {{..}}", variant),
+            LiveValue::Object | LiveValue::Clone {..} | LiveValue::Deref {..} => "{..}".to_string(),
+            LiveValue::Array => "[..]".to_string(),
+            LiveValue::DSL {..} => "fn".to_string(),
+            _ => String::new()
+        }
+    }
+}
+
+// the number of tokens that start before pos, which is the index of the token after the cursor
+fn tokens_before(tokens: &[TokenWithSpan], pos: TextPos) -> usize {
+    // the last token is Eof and has no span
+    tokens[..tokens.len().saturating_sub(1)].iter().take_while( | token | token.span.start < pos).count()
+}
+
+// the token under pos, a position right behind an identifier still counts as on it
+fn token_at(tokens: &[TokenWithSpan], pos: TextPos) -> Option<usize> {
+    let tokens = &tokens[..tokens.len().saturating_sub(1)];
+    let mut index = tokens.iter().rposition( | token | token.span.start <= pos) ?;
+    if !tokens[index].token.is_ident() && index > 0 && tokens[index - 1].span.end == pos && tokens[index - 1].token.is_ident() {
+        index -= 1;
+    }
+    if pos <= tokens[index].span.end {
+        Some(index)
+    }
+    else {
+        None
+    }
+}
+
+// walks the original nodes up to a token, returns the path of the nodes that are open around it
+// and the property it is the name of, if any
+fn node_path_at(nodes: &[LiveNode], token_index: usize) -> (Vec<LiveProp>, Option<LiveProp>) {
+    let mut path = Vec::new();
+    for node in nodes.iter().skip(1) {
+        let node_token = if let Some(token_id) = node.origin.token_id() {token_id.token_index()} else {continue};
+        if node.is_close() {
+            // the origin of a close node is the token after its closing delimiter
+            if node_token > token_index {
+                break
+            }
+            path.pop();
+        }
+        else if node_token == token_index {
+            if !node.id.is_empty() {
+                return (path, Some(node.prop()))
+            }
+            break
+        }
+        else if node_token > token_index {
+            break
+        }
+        // a node only opens after its name and the : or = behind it
+        else if node.is_open() && node_token + 1 < token_index {
+            path.push(node.prop());
+        }
+    }
+    (path, None)
+}
+
+// finds a field by name, looking through #[deref] fields like the derive does
+fn live_field(info: &LiveTypeInfo, id: LiveId) -> Option<&LiveTypeField> {
+    info.fields.iter().find( | field | field.id == id && !matches!(field.live_field_kind, LiveFieldKind::Calc | LiveFieldKind::Deref)).or_else( || {
+        info.fields.iter().filter( | field | field.live_field_kind == LiveFieldKind::Deref).find_map( | field | live_field(&field.live_type_info, id))
+    })
+}
+
+fn live_fields<'a>(info: &'a LiveTypeInfo, out: &mut Vec<&'a LiveTypeField>) {
+    for field in &info.fields {
+        match field.live_field_kind {
+            LiveFieldKind::Calc => (),
+            LiveFieldKind::Deref => live_fields(&field.live_type_info, out),
+            _ => out.push(field)
+        }
+    }
+}
@@ -36,6 +36,10 @@ pub struct LiveFile {
     pub expanded: LiveExpanded,
     
     pub live_type_infos: Vec<LiveTypeInfo>,
+    
+    // overrides for top level values set with set_live_tokens, and the nodes as parsed
+    pub (crate) live_tokens: Vec<(LiveId, LiveValue)>,
+    pub (crate) token_base: Option<Vec<LiveNode>>,
 }
 
 pub struct LiveRegistry {
@@ -49,6 +53,42 @@ pub struct LiveRegistry {
     pub package_root: Option<String>
 }
 
+impl LiveFile {
+    fn apply_live_tokens(&mut self, file_id: LiveFileId, errors: &mut Vec<LiveError>) {
+        if self.token_base.is_none() {
+            self.token_base = Some(self.original.nodes.clone());
+        }
+        let mut nodes = self.token_base.clone().unwrap();
+        for (id, value) in &self.live_tokens {
+            if !value.is_single_node() {
+                errors.push(LiveError {
+                    origin: live_error_origin!(),
+                    span: TextSpan {file_id, ..Default::default()}.into(),
+                    message: format!("Live token {} can only be set to a single value", id)
+                });
+                continue
+            }
+            let index = nodes.child_by_name(0, id.as_instance()).or_else( || nodes.child_by_name(0, id.as_field()));
+            if let Some(index) = index {
+                // keep the origin, so the token still points at its definition
+                let end = nodes.skip_node(index);
+                let node = LiveNode {origin: nodes[index].origin, id: *id, value: value.clone()};
+                nodes.splice(index..end, Some(node));
+            }
+            else {
+                errors.push(LiveError {
+                    origin: live_error_origin!(),
+                    span: TextSpan {file_id, ..Default::default()}.into(),
+                    message: format!("Live token {} not found in {}", id, self.module_id)
+                });
+            }
+        }
+        self.original.nodes = nodes;
+        self.reexpand = true;
+        self.generation.next_gen();
+    }
+}
+
 impl Default for LiveRegistry {
     fn default() -> Self {
         Self {
@@ -709,6 +749,10 @@ impl LiveRegistry {
                                 live_file.original = ld;
                                 live_file.reexpand = true;
                                 live_file.generation.next_gen();
+                                live_file.token_base = None;
+                                if !live_file.live_tokens.is_empty() {
+                                    live_file.apply_live_tokens(file_id, errors);
+                                }
                             }
                         };
                     }
@@ -721,6 +765,26 @@ impl LiveRegistry {
         }
     }
 
+    /// Overrides the values of top level definitions in the live_design!{} of `module_id`, like the
+    /// `THEME_*` design tokens of a theme, and re-expands every document that depends on them.
+    /// Each call replaces the overrides of the previous one, so an empty list restores the source.
+    pub fn set_live_tokens(&mut self, module_id: LiveModuleId, tokens: Vec<(LiveId, LiveValue)>, errors: &mut Vec<LiveError>) {
+        let file_id = if let Some(file_id) = self.module_id_to_file_id.get(&module_id) {
+            *file_id
+        }
+        else {
+            return errors.push(LiveError {
+                origin: live_error_origin!(),
+                span: TextSpan::default().into(),
+                message: format!("Cannot set live tokens, module {} not found", module_id)
+            })
+        };
+        let live_file = self.file_id_to_file_mut(file_id);
+        live_file.live_tokens = tokens;
+        live_file.apply_live_tokens(file_id, errors);
+        self.expand_all_documents(errors);
+    }
+    
     pub fn register_live_file(
         &mut self,
         file_name: &str,
@@ -811,7 +875,9 @@ impl LiveRegistry {
             live_type_infos,
             original,
             next_original: None,
-            expanded: LiveExpanded::new()
+            expanded: LiveExpanded::new(),
+            live_tokens: Vec::new(),
+            token_base: None,
         };
         self.module_id_to_file_id.insert(own_module_id, file_id);
         
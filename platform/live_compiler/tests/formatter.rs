@@ -0,0 +1,23 @@
+use makepad_live_compiler::{format_live_design, format_live_body};
+
+#[test]
+fn formats_spacing_and_indentation() {
+    let source = "use crate::*;\nlive_design!{\nimport crate::base::*;\n\n\n  Button = <ButtonBase>{\n width:Fit,height :Fit // size\n      margin: { top : 1.0 ,left:(THEME_SPACE)}\n draw_bg: {\n fn pixel(self)->vec4{\n return mix(#f00,#0f0,self.hover);\n }\n }\n}\n    App = {{App}}{ }\n}\nfn main() {}\n";
+    let expected = "use crate::*;\nlive_design!{\n    import crate::base::*;\n\n    Button = <ButtonBase> {\n        width: Fit, height: Fit // size\n        margin: {top: 1.0, left: (THEME_SPACE)}\n        draw_bg: {\n            fn pixel(self) -> vec4 {\n                return mix(#f00, #0f0, self.hover);\n            }\n        }\n    }\n    App = {{App}} {}\n}\nfn main() {}\n";
+    let formatted = format_live_design(source).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format_live_design(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn keeps_comments_and_literals() {
+    let body = "\n/* block\n   comment */ Label = <LabelBase>{text: \"a  ,b\", // trailing\ncolor: #FFFFFF80, x: -1.5}\n";
+    let formatted = format_live_body(body, "    ").unwrap();
+    assert_eq!(formatted, "\n        /* block\n   comment */ Label = <LabelBase> {text: \"a  ,b\", // trailing\n            color: #FFFFFF80, x: -1.5}\n    ");
+}
+
+#[test]
+fn rejects_invalid_design() {
+    assert!(format_live_body("Foo = <Bar> {", "").is_err());
+    assert!(format_live_design("live_design!{ Foo: = }").is_err());
+}
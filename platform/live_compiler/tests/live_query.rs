@@ -0,0 +1,202 @@
+use {
+    std::any::TypeId,
+    std::io::Cursor,
+    makepad_live_compiler::*,
+};
+
+struct Button;
+struct DrawBg;
+struct Area;
+
+const BASE: &str = "
+ButtonBase = {{Button}} {
+    width: 10.0
+    draw_bg: {color: #f00}
+}
+";
+
+const APP: &str = "
+import crate::base::*;
+SPACE = 4.0
+MyButton = <ButtonBase> {
+    width: (SPACE)
+    draw_bg: {color: #0f0}
+}
+";
+
+fn type_info<T: 'static>(name: &str, fields: Vec<LiveTypeField>) -> LiveTypeInfo {
+    LiveTypeInfo {
+        live_type: TypeId::of::<T>(),
+        type_name: LiveId::from_str_with_lut(name).unwrap(),
+        module_id: LiveModuleId::from_str("test::types").unwrap(),
+        live_ignore: false,
+        fields
+    }
+}
+
+// the field types have no live_design!{} of their own
+fn field(name: &str, live_type_info: LiveTypeInfo, live_field_kind: LiveFieldKind) -> LiveTypeField {
+    LiveTypeField {
+        id: LiveId::from_str_with_lut(name).unwrap(),
+        live_type_info: LiveTypeInfo {live_ignore: true, ..live_type_info},
+        live_field_kind
+    }
+}
+
+fn registry() -> (LiveRegistry, LiveFileId, LiveFileId) {
+    let button = type_info::<Button>("Button", vec![
+        field("width", type_info::<f64>("f64", vec![]), LiveFieldKind::Live),
+        field("height", type_info::<f64>("f64", vec![]), LiveFieldKind::Live),
+        field("draw_bg", type_info::<DrawBg>("DrawBg", vec![
+            field("color", type_info::<[f32; 4]>("Vec4", vec![]), LiveFieldKind::Live),
+        ]), LiveFieldKind::Live),
+        field("area", type_info::<Area>("Area", vec![]), LiveFieldKind::Calc),
+    ]);
+    let mut registry = LiveRegistry::default();
+    let base = registry.register_live_file(
+        "src/base.rs", "", LiveModuleId::from_str("test::base").unwrap(), BASE.to_string(), vec![button], TextPos::default()
+    ).ok().unwrap();
+    let app = registry.register_live_file(
+        "src/app.rs", "", LiveModuleId::from_str("test::app").unwrap(), APP.to_string(), vec![], TextPos::default()
+    ).ok().unwrap();
+    let mut errors = Vec::new();
+    registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+    (registry, base, app)
+}
+
+// the position of `needle` in `source`, plus `offset` columns
+fn pos(source: &str, needle: &str, offset: u32) -> TextPos {
+    let index = source.find(needle).unwrap();
+    let line_start = source[..index].rfind('\n').map( | i | i + 1).unwrap_or(0);
+    TextPos {
+        line: source[..index].matches('\n').count() as u32,
+        column: (index - line_start) as u32 + offset
+    }
+}
+
+#[test]
+fn completes_fields_and_components() {
+    let (registry, _, app) = registry();
+    let completions = registry.completions_at(app, pos(APP, "    width", 4));
+    let names: Vec<(String, LiveCompletionKind, String)> = completions.iter().map( | c | (c.id.to_string(), c.kind, c.detail.clone())).collect();
+    assert_eq!(names, vec![
+        ("draw_bg".to_string(), LiveCompletionKind::Field, "DrawBg".to_string()),
+        ("height".to_string(), LiveCompletionKind::Field, "f64".to_string()),
+        ("width".to_string(), LiveCompletionKind::Field, "f64".to_string()),
+    ]);
+
+    let completions = registry.completions_at(app, pos(APP, "color: #0f0", 0));
+    assert!(completions.iter().any( | c | c.id == LiveId::from_str("color") && c.detail == "Vec4"));
+    assert!(!completions.iter().any( | c | c.id == LiveId::from_str("width")));
+
+    let completions = registry.completions_at(app, pos(APP, "<ButtonBase>", 1));
+    let names: Vec<String> = completions.iter().map( | c | c.id.to_string()).collect();
+    assert_eq!(names, vec!["ButtonBase", "MyButton"]);
+    assert!(completions.iter().all( | c | c.kind == LiveCompletionKind::Component && c.detail == "Button"));
+}
+
+#[test]
+fn hovers_properties_and_references() {
+    let (registry, _, app) = registry();
+    let hover = registry.hover_at(app, pos(APP, "width", 2)).unwrap();
+    assert_eq!(hover.type_name, Some(LiveId::from_str("f64")));
+    assert_eq!(hover.value, "4.0");
+    assert_eq!(hover.default_value.as_deref(), Some("10.0"));
+
+    let hover = registry.hover_at(app, pos(APP, "color", 5)).unwrap();
+    assert_eq!(hover.type_name, Some(LiveId::from_str("Vec4")));
+    assert_eq!(hover.value, "#00ff00ff");
+    assert_eq!(hover.default_value.as_deref(), Some("#ff0000ff"));
+
+    let hover = registry.hover_at(app, pos(APP, "ButtonBase", 0)).unwrap();
+    assert_eq!(hover.type_name, Some(LiveId::from_str("Button")));
+    assert_eq!(hover.value, "{{Button}} {..}");
+
+    assert!(registry.hover_at(app, pos(APP, "#0f0", 0)).is_none());
+}
+
+#[test]
+fn finds_definitions() {
+    let (registry, base, app) = registry();
+    let span = registry.definition_at(app, pos(APP, "<ButtonBase>", 3)).unwrap();
+    assert_eq!((span.file_id, span.start), (base, pos(BASE, "ButtonBase", 0)));
+
+    let span = registry.definition_at(app, pos(APP, "MyButton", 0)).unwrap();
+    assert_eq!((span.file_id, span.start), (base, pos(BASE, "ButtonBase", 0)));
+
+    let span = registry.definition_at(app, pos(APP, "width", 0)).unwrap();
+    assert_eq!((span.file_id, span.start), (base, pos(BASE, "width", 0)));
+
+    let span = registry.definition_at(app, pos(APP, "(SPACE)", 1)).unwrap();
+    assert_eq!((span.file_id, span.start), (app, pos(APP, "SPACE", 0)));
+
+    assert!(registry.definition_at(app, pos(APP, "SPACE", 0)).is_none());
+}
+
+fn serve_lsp(registry: &mut LiveRegistry, messages: &[String]) -> String {
+    let mut input = String::new();
+    for message in messages {
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", message.len(), message));
+    }
+    let mut output = Vec::new();
+    registry.serve_lsp(&mut Cursor::new(input), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn serves_lsp() {
+    let (mut registry, _, _) = registry();
+    let text = format!("live_design!{{{}}}", APP);
+    let messages = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#.to_string(),
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///work/src/app.rs","languageId":"rust","version":1,"text":{:?}}}}}}}"#,
+            text
+        ),
+        r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///work/src/app.rs"},"position":{"line":4,"character":6}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":3,"method":"textDocument/definition","params":{"textDocument":{"uri":"file:///work/src/app.rs"},"position":{"line":3,"character":16}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ];
+    let output = serve_lsp(&mut registry, &messages);
+
+    assert!(output.contains(r#""id":1,"result":{"capabilities":{"positionEncoding":"utf-16","#));
+    assert!(output.contains(r#""method":"textDocument/publishDiagnostics","params":{"uri":"file:///work/src/app.rs","diagnostics":[]}"#));
+    assert!(output.contains(r#""id":2,"result":{"contents":{"kind":"markdown","value":"```\nwidth: f64 = 4.0\n```\n\ndefault: `10.0`"}}"#));
+    assert!(output.contains(r#""id":3,"result":{"uri":"file:///work/src/base.rs","range":{"start":{"line":1,"character":0},"end":{"line":1,"character":10}}}"#));
+    assert!(output.contains(r#""id":4,"result":null"#));
+}
+
+#[test]
+fn lsp_columns_are_utf16_unless_the_client_takes_utf32() {
+    // the emoji is one char but two UTF-16 code units, the definition of SPACE is at char 5
+    // and the use of it at char 18
+    let text = "live_design!{\nimport crate::base::*;\n/*\u{1f600}*/SPACE = 4.0\nMyButton = <ButtonBase> {\n    width: /*\u{1f600}*/ (SPACE)\n}\n}";
+    let messages = | initialize_params: &str, character: u32 | [
+        format!(r#"{{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}}"#, initialize_params),
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///work/src/app.rs","languageId":"rust","version":1,"text":{:?}}}}}}}"#,
+            text
+        ),
+        format!(
+            r#"{{"jsonrpc":"2.0","id":2,"method":"textDocument/definition","params":{{"textDocument":{{"uri":"file:///work/src/app.rs"}},"position":{{"line":4,"character":{}}}}}}}"#,
+            character
+        ),
+        r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ];
+
+    let (mut utf16_registry, _, _) = registry();
+    let output = serve_lsp(&mut utf16_registry, &messages("{}", 19));
+    assert!(output.contains(r#""diagnostics":[]"#));
+    assert!(output.contains(r#""id":2,"result":{"uri":"file:///work/src/app.rs","range":{"start":{"line":2,"character":6},"end":{"line":2,"character":11}}}"#));
+    let output = serve_lsp(&mut utf16_registry, &messages("{}", 18));
+    assert!(output.contains(r#""id":2,"result":null"#));
+
+    let (mut utf32_registry, _, _) = registry();
+    let utf32 = r#"{"capabilities":{"general":{"positionEncodings":["utf-8","utf-32","utf-16"]}}}"#;
+    let output = serve_lsp(&mut utf32_registry, &messages(utf32, 18));
+    assert!(output.contains(r#""id":1,"result":{"capabilities":{"positionEncoding":"utf-32","#));
+    assert!(output.contains(r#""id":2,"result":{"uri":"file:///work/src/app.rs","range":{"start":{"line":2,"character":5},"end":{"line":2,"character":10}}}"#));
+}
@@ -328,12 +328,12 @@ impl<'a> DrawShaderAnalyser<'a> {
             DrawShaderFieldKind::Texture {..} => {
                 let ty = self.ty_checker().ty_check_ty_expr(&decl.ty_expr) ?;
                 match ty {
-                    Ty::Texture2D | Ty::TextureOES => {}
+                    Ty::Texture2D | Ty::TextureOES | Ty::TextureCube | Ty::Texture3D | Ty::Texture2DArray => {}
                     _ => {
                         return Err(LiveError {
                             origin: live_error_origin!(),
                             span: decl.span.into(),
-                            message: String::from("texture must be either a texture2D or a textureOES (android)"),
+                            message: String::from("texture must be a texture2d, textureCube, texture3d, texture2dArray or a textureOES (android)"),
                         })
                     }
                 }
@@ -785,11 +785,12 @@ impl<'a> FnDefAnalyser<'a> {
             Stmt::For {
                 span,
                 ident,
+                ref shadow,
                 ref from_expr,
                 ref to_expr,
                 ref step_expr,
                 ref block,
-            } => self.analyse_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.analyse_for_stmt(span, ident, shadow, from_expr, to_expr, step_expr, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -841,6 +842,7 @@ impl<'a> FnDefAnalyser<'a> {
         &mut self,
         span: TokenSpan,
         ident: Ident,
+        shadow: &Cell<Option<ScopeSymShadow >>,
         from_expr: &Expr,
         to_expr: &Expr,
         step_expr: &Option<Expr>,
@@ -899,12 +901,13 @@ impl<'a> FnDefAnalyser<'a> {
             self.dep_analyser().dep_analyse_expr(step_expr);
         }
         self.scopes.push_scope();
-        self.scopes.insert_sym(
+        let new_shadow = self.scopes.insert_sym(
             span,
             ident,
             Ty::Int,
             ScopeSymKind::Local,
         );
+        shadow.set(Some(new_shadow));
         let was_inside_loop = self.is_inside_loop;
         self.is_inside_loop = true;
         self.analyse_block(block) ?;
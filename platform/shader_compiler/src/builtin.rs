@@ -159,6 +159,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -197,6 +200,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -205,6 +211,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -228,6 +237,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -236,6 +248,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -311,6 +326,9 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
             (Ty::Ivec3, Ty::Ivec3) -> Ty::Bvec3,
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
             (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
@@ -348,6 +366,15 @@ pub fn generate_builtins()-> HashMap<Ident, Builtin>{
         builtin!(sample2dOES, [
             (Ty::TextureOES, Ty::Vec2) -> Ty::Vec4
         ]),
+        builtin!(sampleCube, [
+            (Ty::TextureCube, Ty::Vec3) -> Ty::Vec4
+        ]),
+        builtin!(sample3d, [
+            (Ty::Texture3D, Ty::Vec3) -> Ty::Vec4
+        ]),
+        builtin!(sample2dArray, [
+            (Ty::Texture2DArray, Ty::Vec2, Ty::Float) -> Ty::Vec4
+        ]),
         builtin!(sign, [
             (Ty::Float) -> Ty::Float,
             (Ty::Vec2) -> Ty::Vec2,
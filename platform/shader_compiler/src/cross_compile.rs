@@ -0,0 +1,85 @@
+use {
+    crate::{
+        makepad_live_compiler::*,
+        shader_ast::*,
+        shader_registry::{ShaderRegistry, DrawShaderQuery},
+        generate_glsl,
+        generate_metal,
+        generate_hlsl,
+        generate_wgsl,
+    }
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShaderBackend {
+    Glsl,
+    Metal,
+    Hlsl,
+    Wgsl,
+}
+
+impl ShaderBackend {
+    pub const ALL: [ShaderBackend; 4] = [Self::Glsl, Self::Metal, Self::Hlsl, Self::Wgsl];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Glsl => "glsl",
+            Self::Metal => "metal",
+            Self::Hlsl => "hlsl",
+            Self::Wgsl => "wgsl",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find( | backend | backend.name() == name).cloned()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ShaderBackendError {
+    pub backend: ShaderBackend,
+    pub error: LiveError,
+}
+
+impl ShaderRegistry {
+    pub fn generate_draw_shader(&self, draw_shader_ptr: DrawShaderPtr, const_table: &DrawShaderConstTable, backend: ShaderBackend) -> Result<String, LiveError> {
+        let draw_shader_def = self.draw_shader_defs.get(&draw_shader_ptr).ok_or_else( || LiveError {
+            origin: live_error_origin!(),
+            span: TokenSpan::default().into(),
+            message: "draw shader is not analysed".to_string()
+        }) ?;
+        match backend {
+            ShaderBackend::Glsl => {
+                let mut glsl = generate_glsl::generate_vertex_shader(draw_shader_def, const_table, self) ?;
+                glsl.push_str(&generate_glsl::generate_pixel_shader(draw_shader_def, const_table, self) ?);
+                Ok(glsl)
+            }
+            ShaderBackend::Metal => Ok(generate_metal::generate_shader(draw_shader_def, const_table, self) ?.mtlsl),
+            ShaderBackend::Hlsl => generate_hlsl::generate_shader(draw_shader_def, const_table, self),
+            ShaderBackend::Wgsl => Ok(generate_wgsl::generate_shader(draw_shader_def, const_table, self) ?.wgsl),
+        }
+    }
+
+    pub fn check_draw_shader(&self, draw_shader_ptr: DrawShaderPtr, backends: &[ShaderBackend]) -> Vec<ShaderBackendError> {
+        if backends.is_empty() {
+            return Vec::new()
+        }
+        let const_table = self.compute_const_table(draw_shader_ptr);
+        backends.iter().filter_map( | backend | {
+            self.generate_draw_shader(draw_shader_ptr, &const_table, *backend).err().map( | error | ShaderBackendError {
+                backend: *backend,
+                error
+            })
+        }).collect()
+    }
+
+    // analyses like analyse_draw_shader and then generates the shader for all the given backends,
+    // an analyse error is shared by all backends so it fails the whole check
+    pub fn analyse_and_check_draw_shader<F>(&mut self, live_registry: &LiveRegistry, draw_shader_ptr: DrawShaderPtr, backends: &[ShaderBackend], ext_self: F) -> Result<Vec<ShaderBackendError>,
+        LiveError>
+    where F: FnMut(&LiveRegistry, &ShaderRegistry, TokenSpan, DrawShaderQuery, LiveType, &mut DrawShaderDef)
+    {
+        self.analyse_draw_shader(live_registry, draw_shader_ptr, ext_self) ?;
+        Ok(self.check_draw_shader(draw_shader_ptr, backends))
+    }
+}
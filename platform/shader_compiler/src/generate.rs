@@ -9,6 +9,9 @@ use{
         makepad_live_id::{LiveId, live_id},
         makepad_live_compiler::{
             makepad_math::PrettyPrintedF32,
+            LiveError,
+            LiveErrorOrigin,
+            live_error_origin,
             TokenSpan
         },
         shader_ast::*,
@@ -42,6 +45,31 @@ pub struct ClosureSiteInfo<'a> {
     pub call_ptr: FnPtr
 }
 
+/// Holds the first thing a backend could not express. Generating goes on with
+/// whatever was written so far, and the generate functions return this error.
+#[derive(Default)]
+pub struct GenerateError(RefCell<Option<LiveError >>);
+
+impl GenerateError {
+    pub fn report(&self, span: TokenSpan, message: String) {
+        let mut error = self.0.borrow_mut();
+        if error.is_none() {
+            *error = Some(LiveError {
+                origin: live_error_origin!(),
+                span: span.into(),
+                message
+            });
+        }
+    }
+    
+    pub fn into_result<T>(self, value: T) -> Result<T, LiveError> {
+        match self.0.into_inner() {
+            Some(error) => Err(error),
+            None => Ok(value)
+        }
+    }
+}
+
 pub enum StructConsType{
     Paren,
     Brace,
@@ -77,6 +105,52 @@ pub trait BackendWriter {
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
     
+    fn error(&self) -> &GenerateError;
+    
+    fn report_error(&self, span: TokenSpan, message: String) {
+        self.error().report(span, message);
+    }
+    
+    // the hooks below default to C-style syntax, backends with a different
+    // declaration syntax or without a ?: operator override them
+    
+    fn write_fn_def_name(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+    }
+    
+    fn write_fn_def_return_ty(&self, _string: &mut String, _return_ty: &Ty) {
+    }
+    
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+    
+    fn write_for_var_decl(&self, string: &mut String, ident: &dyn fmt::Display) {
+        write!(string, "int {}", ident).unwrap();
+    }
+    
+    fn cond_expr_is_select(&self) -> bool {
+        false
+    }
+    
+    fn assign_is_stmt(&self) -> bool {
+        false
+    }
+    
+    fn inout_is_pointer(&self) -> bool {
+        false
+    }
+    
+    // backends without unsigned types store them in signed ones, and call a helper
+    // for the operators and conversions where the two give different results
+    
+    fn unsigned_bin_op_fn(&self, _op: BinOp, _left_ty: &Ty) -> Option<&'static str> {
+        None
+    }
+    
+    fn unsigned_conversion_fn(&self, _ty_lit: TyLit, _arg_ty: &Ty) -> Option<&'static str> {
+        None
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -122,13 +196,20 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
-            Ty::Bool | Ty::Int | Ty::Float => {
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
                 let mut sep = "";
                 for _ in 0..ty.slots() {
                     write!(string, "{}x", sep).unwrap();
                     sep = ", ";
                 }
             }
+            param_ty if param_ty.is_vector() => {
+                let mut sep = "";
+                for index in 0..ty.slots() {
+                    write!(string, "{}x[{}]", sep, index).unwrap();
+                    sep = ", ";
+                }
+            }
             Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
                 let dst_size = match ty {
                     Ty::Mat2 => 2,
@@ -161,7 +242,7 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
                     }
                 }
             }
-            _ => panic!(),
+            param_ty => backend_writer.report_error(TokenSpan::default(), format!("cannot construct {} from {}", ty_lit, param_ty)),
         }
     } else {
         let mut sep = "";
@@ -205,11 +286,12 @@ impl<'a> BlockGenerator<'a> {
             Stmt::For {
                 span,
                 ident,
+                ref shadow,
                 ref from_expr,
                 ref to_expr,
                 ref step_expr,
                 ref block,
-            } => self.generate_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.generate_for_stmt(span, ident, shadow, from_expr, to_expr, step_expr, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -247,6 +329,7 @@ impl<'a> BlockGenerator<'a> {
         &mut self,
         _span: TokenSpan,
         ident: Ident,
+        shadow: &Cell<Option<ScopeSymShadow >>,
         from_expr: &Expr,
         to_expr: &Expr,
         step_expr: &Option<Expr>,
@@ -285,9 +368,12 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
+        let ident = DisplayVarName(ident, shadow.get().unwrap());
+        write!(self.string, "for (").unwrap();
+        self.backend_writer.write_for_var_decl(self.string, &ident);
         write!(
             self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            " = {1}; {0} {2} {3}; {0} {4} {5}) ",
             ident,
             if from <= to {from} else {from - 1},
             if from <= to {"<"} else {">="},
@@ -307,9 +393,9 @@ impl<'a> BlockGenerator<'a> {
         block_if_true: &Block,
         block_if_false: &Option<Box<Block >>,
     ) {
-        write!(self.string, "if").unwrap();
+        write!(self.string, "if (").unwrap();
         self.generate_expr(expr);
-        write!(self.string, " ").unwrap();
+        write!(self.string, ") ").unwrap();
         self.generate_block(block_if_true);
         if let Some(block_if_false) = block_if_false {
             write!(self.string, "else").unwrap();
@@ -358,11 +444,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -387,7 +470,25 @@ impl<'a> BlockGenerator<'a> {
         writeln!(self.string).unwrap();
     }
     
-    fn generate_expr_stmt(&mut self, _span: TokenSpan, expr: &Expr) {
+    fn generate_expr_stmt(&mut self, span: TokenSpan, expr: &Expr) {
+        // a = b = c; becomes b = c; a = b; when assignments aren't expressions
+        if self.backend_writer.assign_is_stmt() {
+            if let ExprKind::Bin {op, left_expr, right_expr, ..} = &expr.kind {
+                if op.is_assign() {
+                    if let ExprKind::Bin {op: right_op, left_expr: right_left_expr, ..} = &right_expr.kind {
+                        if right_op.is_assign() {
+                            self.generate_expr_stmt(span, right_expr);
+                            self.write_indent();
+                            self.generate_expr(left_expr);
+                            write!(self.string, " {} ", op).unwrap();
+                            self.generate_expr(right_left_expr);
+                            writeln!(self.string, ";").unwrap();
+                            return
+                        }
+                    }
+                }
+            }
+        }
         self.generate_expr(expr);
         writeln!(self.string, ";").unwrap();
     }
@@ -566,6 +667,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.cond_expr_is_select() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -650,6 +761,29 @@ impl<'a> ExprGenerator<'a> {
             }
         }
         
+        if let Some(op_fn) = self.backend_writer.unsigned_bin_op_fn(op, left_expr.ty.borrow().as_ref().unwrap()) {
+            write!(self.string, "(").unwrap();
+            if op.is_assign() {
+                self.generate_expr(left_expr);
+                write!(self.string, " = ").unwrap();
+            }
+            write!(self.string, "{}(", op_fn).unwrap();
+            self.generate_expr(left_expr);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(right_expr);
+            write!(self.string, "))").unwrap();
+            return
+        }
+        
+        if self.backend_writer.assign_is_stmt() {
+            if op.is_assign() {
+                self.generate_expr(left_expr);
+                write!(self.string, " {} ", op).unwrap();
+                self.generate_expr(right_expr);
+                return
+            }
+        }
+        
         write!(self.string, "(").unwrap();
         self.generate_expr(left_expr);
         write!(self.string, " {} ", op).unwrap();
@@ -688,7 +822,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 self.generate_call_body(_span, fn_def, &arg_exprs[1..], closure_site_index);
             }
-            _ => panic!(),
+            ty => self.backend_writer.report_error(_span, format!("cannot call method {} on {}", ident, ty)),
         }
     }
     
@@ -711,7 +845,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +855,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, index, arg_expr);
                 sep = ", ";
             }
             // and now the closed over values
@@ -730,7 +864,10 @@ impl<'a> ExprGenerator<'a> {
                     Ty::DrawShader(_)=>{
                         continue;
                     }
-                    Ty::ClosureDef(_) | Ty::ClosureDecl=>panic!(),
+                    Ty::ClosureDef(_) | Ty::ClosureDecl=>{
+                        self.backend_writer.report_error(_span, "closures cannot close over closures".to_string());
+                        continue;
+                    }
                     _=>()
                 }
                 write!(self.string, "{}", sep).unwrap();
@@ -748,9 +885,9 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, index, arg_expr);
                 sep = ", ";
             }
 
@@ -760,6 +897,34 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_call_arg(&mut self, fn_def: &FnDef, index: usize, arg_expr: &Expr) {
+        if self.backend_writer.inout_is_pointer() {
+            // the draw shader self param is never passed along
+            let is_inout = fn_def.params.iter()
+                .filter( | param | !matches!(param.ty_expr.ty.borrow().as_ref(), Some(Ty::DrawShader(_))))
+                .nth(index)
+                .map_or(false, | param | param.is_inout);
+            if is_inout {
+                if let ExprKind::Var {kind, ..} = &arg_expr.kind {
+                    if let Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) = kind.get() {
+                        if self.is_inout_param(ident, shadow) {
+                            write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                            return
+                        }
+                    }
+                }
+                write!(self.string, "&").unwrap();
+            }
+        }
+        self.generate_expr(arg_expr);
+    }
+    
+    fn is_inout_param(&self, ident: Ident, shadow: ScopeSymShadow) -> bool {
+        self.fn_def.map_or(false, | fn_def | fn_def.params.iter().any( | param | {
+            param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow)
+        }))
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -913,7 +1078,14 @@ impl<'a> ExprGenerator<'a> {
         let mut sep = "";
         for arg_expr in arg_exprs {
             write!(self.string, "{}", sep).unwrap();
-            self.generate_expr(arg_expr);
+            if let Some(conversion_fn) = self.backend_writer.unsigned_conversion_fn(ty_lit, arg_expr.ty.borrow().as_ref().unwrap()) {
+                write!(self.string, "{}(", conversion_fn).unwrap();
+                self.generate_expr(arg_expr);
+                write!(self.string, ")").unwrap();
+            }
+            else {
+                self.generate_expr(arg_expr);
+            }
             sep = ", ";
         }
         write!(self.string, ")").unwrap();
@@ -922,6 +1094,10 @@ impl<'a> ExprGenerator<'a> {
     fn generate_var_expr(&mut self, _span: TokenSpan, kind: &Cell<Option<VarKind >>, _ty: &Option<Ty>) {
         // ok so we have a few varkinds
         match kind.get().unwrap() {
+            VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow}
+            if self.backend_writer.inout_is_pointer() && self.is_inout_param(ident, shadow) => {
+                write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+            }
             VarKind::Local {ident, shadow} => {
                 write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
             }
@@ -960,11 +1136,8 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_name(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
@@ -985,7 +1158,9 @@ impl<'a> FnDefGenerator<'a> {
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
         self.generate_block(&self.fn_def.block);
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
@@ -1077,11 +1252,8 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_name(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
@@ -1125,7 +1297,9 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
         self.generate_block(&self.fn_def.block);
@@ -1168,13 +1342,10 @@ impl<'a> ClosureDefGenerator<'a> {
         
         let mut sep = "";
         
-        if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+        let return_ty = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_def_name(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
@@ -1196,10 +1367,12 @@ impl<'a> ClosureDefGenerator<'a> {
                     sep = ", ";
                 }
             }
+            return_ty
         }
         else {
-            panic!()
-        }
+            self.backend_writer.report_error(self.closure_def.span, "closure is passed for a parameter that is not a closure".to_string());
+            return
+        };
         
         for sym in self.closure_def.closed_over_syms.borrow().as_ref().unwrap() {
             if self.backend_writer.write_var_decl(
@@ -1219,7 +1392,9 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, return_ty.borrow().as_ref().unwrap());
+        writeln!(self.string, " {{").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
@@ -2,6 +2,7 @@ use {
     std::{
         fmt,
         fmt::Write,
+        cell::Cell,
         collections::BTreeSet,
     },
     crate::{
@@ -9,6 +10,10 @@ use {
             live_id,
             LiveId,
         },
+        makepad_live_compiler::{
+            LiveError,
+            TokenSpan
+        },
         generate::*,
         swizzle::Swizzle,
         shader_ast::*,
@@ -16,30 +21,32 @@ use {
     }
 };
 
-pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<String, LiveError> {
     let mut string = String::new();
+    let backend_writer = GlslBackendWriter {shader_registry, const_table, uses_unsigned: Cell::new(false), error: GenerateError::default()};
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        backend_writer: &backend_writer
     }
     .generate_vertex_shader();
-    string
+    backend_writer.error.into_result(string)
 }
 
-pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<String, LiveError> {
     let mut string = String::new();
+    let backend_writer = GlslBackendWriter {shader_registry, const_table, uses_unsigned: Cell::new(false), error: GenerateError::default()};
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        backend_writer: &backend_writer
     }
     .generate_pixel_shader();
-    string
+    backend_writer.error.into_result(string)
 }
 
 struct DrawShaderGenerator<'a> {
@@ -47,7 +54,7 @@ struct DrawShaderGenerator<'a> {
     shader_registry: &'a ShaderRegistry,
     string: &'a mut String,
     const_table: &'a DrawShaderConstTable,
-    backend_writer: &'a dyn BackendWriter
+    backend_writer: &'a GlslBackendWriter<'a>
 }
 
 impl<'a> DrawShaderGenerator<'a> {
@@ -57,14 +64,14 @@ impl<'a> DrawShaderGenerator<'a> {
             "{}",
             match ty {
                 Ty::Bool => "false",
-                Ty::Int => "0",
+                Ty::Int | Ty::Uint => "0",
                 Ty::Float => "0.0",
                 Ty::Bvec2 => "bvec2(0)",
                 Ty::Bvec3 => "bvec3(0)",
                 Ty::Bvec4 => "bvec4(0)",
-                Ty::Ivec2 => "ivec2(0)",
-                Ty::Ivec3 => "ivec3(0)",
-                Ty::Ivec4 => "ivec4(0)",
+                Ty::Ivec2 | Ty::Uvec2 => "ivec2(0)",
+                Ty::Ivec3 | Ty::Uvec3 => "ivec3(0)",
+                Ty::Ivec4 | Ty::Uvec4 => "ivec4(0)",
                 Ty::Vec2 => "vec2(0.0)",
                 Ty::Vec3 => "vec3(0.0)",
                 Ty::Vec4 => "vec4(0.0)",
@@ -72,7 +79,10 @@ impl<'a> DrawShaderGenerator<'a> {
                 Ty::Mat3 => "mat3(0.0)",
                 Ty::Mat4 => "mat4(0.0)",
                 Ty::Enum {..} => "0.0",
-                _ => panic!("unexpected as initializeable type {:?}", ty),
+                _ => {
+                    self.backend_writer.report_error(TokenSpan::default(), format!("cannot initialize a value of type {}", ty));
+                    return
+                }
             }
         )
             .unwrap()
@@ -213,6 +223,8 @@ impl<'a> DrawShaderGenerator<'a> {
             generate_cons_fn(self.backend_writer, self.string, ty_lit, &param_tys);
         }
         write!(self.string, "\n").unwrap();
+        // the unsigned helpers go here, once we know whether the fns below use them
+        let unsigned_helpers_pos = self.string.len();
         for fn_iter in fn_deps.iter().rev() {
             let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
             let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
@@ -243,6 +255,11 @@ impl<'a> DrawShaderGenerator<'a> {
             .generate_fn_def();
             write!(self.string, "\n").unwrap();
         }
+        if self.backend_writer.uses_unsigned.get() {
+            let mut helpers = String::new();
+            generate_unsigned_helpers(&mut helpers);
+            self.string.insert_str(unsigned_helpers_pos, &helpers);
+        }
     }
     
     pub fn generate_pixel_shader(&mut self) {
@@ -361,6 +378,7 @@ impl<'a> DrawShaderGenerator<'a> {
         packed_instances_size: Option<usize>,
         packed_varyings_size: usize,
     ) {
+        self.generate_texture_helpers();
         
         if self.const_table.table.len()>0 {
             writeln!(self.string, "uniform float const_table[{}];", self.const_table.table.len()).unwrap();
@@ -395,7 +413,7 @@ impl<'a> DrawShaderGenerator<'a> {
                     self.generate_uniform_decl(field);
                 }
                 else {
-                    panic!()
+                    self.backend_writer.report_error(field.span, "only uniforms can be in a uniform block".to_string());
                 }
             }
             write!(self.string, "\n").unwrap();
@@ -465,6 +483,36 @@ impl<'a> DrawShaderGenerator<'a> {
         slots
     }
     
+    // GLSL ES 1.00 has no 3D or array textures, so those are uploaded as a 2D atlas
+    // with the layers stacked vertically and sampled through a struct carrying the layer count
+    fn generate_texture_helpers(&mut self) {
+        let has_texture_ty = | ty: Ty | self.draw_shader_def.fields.iter().any( | field | {
+            matches!(field.kind, DrawShaderFieldKind::Texture {..}) && field.ty_expr.ty.borrow().as_ref() == Some(&ty)
+        });
+        if has_texture_ty(Ty::TextureCube) {
+            writeln!(self.string, "vec4 sampleCube(samplerCube tex, vec3 dir){{return textureCube(tex, dir);}}").unwrap();
+        }
+        if has_texture_ty(Ty::Texture3D) {
+            writeln!(self.string, "struct mpsc_texture3D {{sampler2D atlas; float layers;}};").unwrap();
+            writeln!(self.string, "vec4 sample3d(mpsc_texture3D tex, vec3 pos){{").unwrap();
+            writeln!(self.string, "    float z = clamp(pos.z, 0.0, 1.0) * tex.layers - 0.5;").unwrap();
+            writeln!(self.string, "    float z0 = clamp(floor(z), 0.0, tex.layers - 1.0);").unwrap();
+            writeln!(self.string, "    float z1 = min(z0 + 1.0, tex.layers - 1.0);").unwrap();
+            writeln!(self.string, "    float y = clamp(pos.y, 0.0, 1.0);").unwrap();
+            writeln!(self.string, "    vec4 c0 = texture2D(tex.atlas, vec2(pos.x, (z0 + y) / tex.layers));").unwrap();
+            writeln!(self.string, "    vec4 c1 = texture2D(tex.atlas, vec2(pos.x, (z1 + y) / tex.layers));").unwrap();
+            writeln!(self.string, "    return mix(c0, c1, clamp(z - z0, 0.0, 1.0));").unwrap();
+            writeln!(self.string, "}}").unwrap();
+        }
+        if has_texture_ty(Ty::Texture2DArray) {
+            writeln!(self.string, "struct mpsc_texture2DArray {{sampler2D atlas; float layers;}};").unwrap();
+            writeln!(self.string, "vec4 sample2dArray(mpsc_texture2DArray tex, vec2 pos, float layer){{").unwrap();
+            writeln!(self.string, "    float l = clamp(floor(layer + 0.5), 0.0, tex.layers - 1.0);").unwrap();
+            writeln!(self.string, "    return texture2D(tex.atlas, vec2(pos.x, (l + clamp(pos.y, 0.0, 1.0)) / tex.layers));").unwrap();
+            writeln!(self.string, "}}").unwrap();
+        }
+    }
+    
     fn generate_texture_decl(&mut self, decl: &DrawShaderFieldDef) {
         write!(self.string, "uniform ").unwrap();
         self.write_var_decl(
@@ -546,14 +594,14 @@ impl<'a> DrawShaderGenerator<'a> {
     fn write_uniform_ty_unpack(&mut self, ty: &Ty, prefix: &str, s: usize) {
         match ty {
             Ty::Bool => write!(self.string, "{}[{}]>0.5?true:false", prefix, s),
-            Ty::Int => write!(self.string, "int({}[{}])", prefix, s),
+            Ty::Int | Ty::Uint => write!(self.string, "int({}[{}])", prefix, s),
             Ty::Float => write!(self.string, "{}[{}]", prefix, s),
             Ty::Bvec2 => write!(self.string, "bvec2({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false)", prefix, s, s + 1),
             Ty::Bvec3 => write!(self.string, "bvec3({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false, {0}[{3}]>0.5?true:false)", prefix, s, s + 1, s + 2),
             Ty::Bvec4 => write!(self.string, "bvec4({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false, {0}[{3}]>0.5?true:false, {0}[{4}]>0.5?true:false)", prefix, s, s + 1, s + 2, s + 3),
-            Ty::Ivec2 => write!(self.string, "ivec2(int({0}[{1}]), int({0}[{2}]))", prefix, s, s + 1),
-            Ty::Ivec3 => write!(self.string, "ivec3(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]))", prefix, s, s + 1, s + 2),
-            Ty::Ivec4 => write!(self.string, "ivec4(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]), int({0}[{4}]))", prefix, s, s + 1, s + 2, s + 3),
+            Ty::Ivec2 | Ty::Uvec2 => write!(self.string, "ivec2(int({0}[{1}]), int({0}[{2}]))", prefix, s, s + 1),
+            Ty::Ivec3 | Ty::Uvec3 => write!(self.string, "ivec3(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]))", prefix, s, s + 1, s + 2),
+            Ty::Ivec4 | Ty::Uvec4 => write!(self.string, "ivec4(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]), int({0}[{4}]))", prefix, s, s + 1, s + 2, s + 3),
             Ty::Vec2 => write!(self.string, "vec2({0}[{1}], {0}[{2}])", prefix, s, s + 1),
             Ty::Vec3 => write!(self.string, "vec3({0}[{1}], {0}[{2}], {0}[{3}])", prefix, s, s + 1, s + 2),
             Ty::Vec4 => write!(self.string, "vec4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}])", prefix, s, s + 1, s + 2, s + 3),
@@ -562,7 +610,10 @@ impl<'a> DrawShaderGenerator<'a> {
             Ty::Mat4 => write!(self.string, "mat4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}], {0}[{5}], {0}[{6}], {0}[{7}], {0}[{8}], {0}[{9}], {0}[{10}], {0}[{11}], {0}[{12}], {0}[{13}], {0}[{14}], {0}[{15}], {0}[{16}])", prefix, s, s + 1, s + 2, s + 3, s + 4, s + 5, s + 6, s + 7, s + 8, s + 9, s + 10, s + 11, s + 12, s + 13, s + 14, s + 15,),
             //Ty::Mat4 => write!(self.string, "mat4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}], {0}[{5}], {0}[{6}], {0}[{7}], {0}[{8}], {0}[{9}], {0}[{10}], {0}[{11}], {0}[{12}], {0}[{13}], {0}[{14}], {0}[{15}], {0}[{16}])", prefix, s, s + 4, s + 8, s + 12, s + 1, s + 5, s + 9, s + 13, s + 2, s + 6, s + 10, s + 14, s + 3, s + 7, s + 11, s + 15,),
             Ty::Enum {..} => write!(self.string, "{}[{}]", prefix, s),
-            _ => panic!("unexpected as initializeable type {:?}", ty),
+            _ => {
+                self.backend_writer.report_error(TokenSpan::default(), format!("cannot unpack a uniform of type {}", ty));
+                Ok(())
+            }
         }.unwrap()
     }
     
@@ -767,12 +818,59 @@ impl<'a> VarUnpacker<'a> {
     }
 }
 
+// GLSL ES 1.00 has no unsigned types, so they are stored in 32 bit ints. Addition,
+// subtraction, multiplication and equality give the same bits either way, division,
+// ordering and conversions to and from float go through these helpers instead
+fn generate_unsigned_helpers(string: &mut String) {
+    writeln!(string, "bool mpsc_ult(int a, int b){{return (a < 0) == (b < 0) ? a < b : b < 0;}}").unwrap();
+    writeln!(string, "bool mpsc_ule(int a, int b){{return !mpsc_ult(b, a);}}").unwrap();
+    writeln!(string, "bool mpsc_ugt(int a, int b){{return mpsc_ult(b, a);}}").unwrap();
+    writeln!(string, "bool mpsc_uge(int a, int b){{return !mpsc_ult(a, b);}}").unwrap();
+    writeln!(string, "int mpsc_udiv(int a, int b){{").unwrap();
+    writeln!(string, "    if (b < 0) {{return mpsc_ult(a, b) ? 0 : 1;}}").unwrap();
+    writeln!(string, "    if (a >= 0) {{return a / b;}}").unwrap();
+    // a is 2^31 + h here, and 2^31 is q * b + s with 0 < s <= b
+    writeln!(string, "    int h = a - (-2147483647 - 1);").unwrap();
+    writeln!(string, "    int q = 2147483647 / b;").unwrap();
+    writeln!(string, "    int s = 2147483647 - q * b + 1;").unwrap();
+    writeln!(string, "    int hq = h / b;").unwrap();
+    writeln!(string, "    return q + hq + (h - hq * b >= b - s ? 1 : 0);").unwrap();
+    writeln!(string, "}}").unwrap();
+    writeln!(string, "float mpsc_utof(int x){{return x < 0 ? float(x) + 4294967296.0 : float(x);}}").unwrap();
+    writeln!(string, "int mpsc_ftou(float x){{return x >= 2147483648.0 ? int(x - 4294967296.0) : int(x);}}").unwrap();
+    for n in 2..=4 {
+        let each = | f: &str, a: &str, b: &str | {
+            (0..n).map( | i | {
+                let c = &"xyzw"[i..i + 1];
+                if b.is_empty() {format!("{}({}.{})", f, a, c)} else {format!("{}({}.{}, {}.{})", f, a, c, b, c)}
+            }).collect::<Vec<_ >> ().join(", ")
+        };
+        writeln!(string, "ivec{0} mpsc_udiv(ivec{0} a, ivec{0} b){{return ivec{0}({1});}}", n, each("mpsc_udiv", "a", "b")).unwrap();
+        writeln!(string, "ivec{0} mpsc_udiv(ivec{0} a, int b){{return mpsc_udiv(a, ivec{0}(b));}}", n).unwrap();
+        writeln!(string, "vec{0} mpsc_utof(ivec{0} x){{return vec{0}({1});}}", n, each("mpsc_utof", "x", "")).unwrap();
+        writeln!(string, "ivec{0} mpsc_ftou(vec{0} x){{return ivec{0}({1});}}", n, each("mpsc_ftou", "x", "")).unwrap();
+        for (builtin, cmp) in [("lessThan", "mpsc_ult"), ("lessThanEqual", "mpsc_ule"), ("greaterThan", "mpsc_ugt"), ("greaterThanEqual", "mpsc_uge")] {
+            writeln!(string, "bvec{0} mpsc_u{1}(ivec{0} a, ivec{0} b){{return bvec{0}({2});}}", n, builtin, each(cmp, "a", "b")).unwrap();
+        }
+    }
+}
+
+fn is_unsigned(ty: &Ty) -> bool {
+    matches!(ty, Ty::Uint | Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4)
+}
+
 struct GlslBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
-    const_table: &'a DrawShaderConstTable
+    const_table: &'a DrawShaderConstTable,
+    uses_unsigned: Cell<bool>,
+    error: GenerateError
 }
 
 impl<'a> BackendWriter for GlslBackendWriter<'a> {
+    fn error(&self) -> &GenerateError {
+        &self.error
+    }
+    
     fn get_struct_cons_type(&self) -> StructConsType {
         StructConsType::Paren
     }
@@ -797,6 +895,38 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
         false
     }
     
+    fn unsigned_bin_op_fn(&self, op: BinOp, left_ty: &Ty) -> Option<&'static str> {
+        if !is_unsigned(left_ty) {
+            return None
+        }
+        let op_fn = match op {
+            BinOp::Div | BinOp::DivAssign => "mpsc_udiv",
+            BinOp::Lt => "mpsc_ult",
+            BinOp::Le => "mpsc_ule",
+            BinOp::Gt => "mpsc_ugt",
+            BinOp::Ge => "mpsc_uge",
+            _ => return None
+        };
+        self.uses_unsigned.set(true);
+        Some(op_fn)
+    }
+    
+    fn unsigned_conversion_fn(&self, ty_lit: TyLit, arg_ty: &Ty) -> Option<&'static str> {
+        let is_float = | ty: &Ty | matches!(ty, Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4);
+        let ty = ty_lit.to_ty();
+        let conversion_fn = if is_unsigned(arg_ty) && (is_float(&ty) || ty.is_matrix()) {
+            "mpsc_utof"
+        }
+        else if is_float(arg_ty) && is_unsigned(&ty) {
+            "mpsc_ftou"
+        }
+        else {
+            return None
+        };
+        self.uses_unsigned.set(true);
+        Some(conversion_fn)
+    }
+    
     fn write_var_decl(
         &self,
         string: &mut String,
@@ -826,6 +956,11 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -861,6 +996,21 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Ivec4);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uvec2 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Uvec3 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Uvec4 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Vec2 => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Vec2);
@@ -901,6 +1051,21 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::TextureOES);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::TextureCube => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::TextureCube);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Texture3D => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::Texture3D);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Texture2DArray => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::Texture2DArray);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -947,6 +1112,9 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                // GLSL ES 1.00 has no unsigned types, they are stored in ints
+                // and the helpers from generate_unsigned_helpers do the rest
+                TyLit::Uint => "int",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -954,6 +1122,9 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 TyLit::Ivec2 => "ivec2",
                 TyLit::Ivec3 => "ivec3",
                 TyLit::Ivec4 => "ivec4",
+                TyLit::Uvec2 => "ivec2",
+                TyLit::Uvec3 => "ivec3",
+                TyLit::Uvec4 => "ivec4",
                 TyLit::Vec2 => "vec2",
                 TyLit::Vec3 => "vec3",
                 TyLit::Vec4 => "vec4",
@@ -962,13 +1133,21 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "sampler2D",
                 TyLit::TextureOES => "samplerExternalOES",
+                TyLit::TextureCube => "samplerCube",
+                TyLit::Texture3D => "mpsc_texture3D",
+                TyLit::Texture2DArray => "mpsc_texture2DArray",
             }
         )
             .unwrap();
     }
     
-    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, _arg_exprs: &[Expr]) {
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
          match ident {
+            Ident(live_id!(lessThan)) | Ident(live_id!(lessThanEqual)) | Ident(live_id!(greaterThan)) | Ident(live_id!(greaterThanEqual))
+            if is_unsigned(arg_exprs[0].ty.borrow().as_ref().unwrap()) => {
+                self.uses_unsigned.set(true);
+                write!(string, "mpsc_u{}", ident).unwrap()
+            }
             _ => {
                 write!(string, "{}", ident).unwrap()
             }
@@ -9,6 +9,10 @@ use{
             live_id,
             LiveId,
         },
+        makepad_live_compiler::{
+            LiveError,
+            TokenSpan
+        },
         generate::*,
         shader_ast::*,
         shader_registry::ShaderRegistry
@@ -19,17 +23,18 @@ pub fn index_to_char(index: usize) -> char {
     std::char::from_u32(index as u32 + 65).unwrap()
 }
 
-pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<String, LiveError> {
     let mut string = String::new();
+    let backend_writer = HlslBackendWriter {shader_registry, draw_shader_def, const_table, error: GenerateError::default()};
     DrawShaderGenerator {
         draw_shader_def,
         shader_registry,
         string: &mut string,
         const_table,
-        backend_writer: &HlslBackendWriter {shader_registry, draw_shader_def, const_table}
+        backend_writer: &backend_writer
     }
     .generate_shader();
-    string
+    backend_writer.error.into_result(string)
 }
 
 struct DrawShaderGenerator<'a> {
@@ -50,20 +55,24 @@ impl<'a> DrawShaderGenerator<'a> {
             all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
         }
 
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
-            }
-
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d_rt(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
+        let uses_builtin = | id: LiveId | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(id))
+        });
+        let samplers = [
+            (live_id!(sample2d), "float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(default_texture_sampler,pos);}"),
+            (live_id!(sample2d_rt), "float4 sample2d_rt(Texture2D tex, float2 pos){return tex.Sample(default_texture_sampler,pos);}"),
+            (live_id!(sampleCube), "float4 sampleCube(TextureCube tex, float3 dir){return tex.Sample(default_texture_sampler,dir);}"),
+            (live_id!(sample3d), "float4 sample3d(Texture3D tex, float3 pos){return tex.Sample(default_texture_sampler,pos);}"),
+            (live_id!(sample2dArray), "float4 sample2dArray(Texture2DArray tex, float2 pos, float layer){return tex.Sample(default_texture_sampler,float3(pos,layer));}"),
+        ];
+        if samplers.iter().any( | (id, _) | uses_builtin(*id)) {
+            writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
+        }
+        for (id, sampler_fn) in samplers {
+            if uses_builtin(id) {
+                writeln!(self.string, "{}", sampler_fn).unwrap();
             }
-        };
+        }
         
         self.generate_struct_decls();
         let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -174,7 +183,10 @@ impl<'a> DrawShaderGenerator<'a> {
                 live_id!(pass)=>3,
                 live_id!(user)=>5,
                 live_id!(view)=>4,
-                _=>panic!()
+                _=>{
+                    self.backend_writer.report_error(self.draw_shader_def.fields[vec[0].0].span, format!("unknown uniform block {}", ident));
+                    continue
+                }
             };
             writeln!(self.string, "cbuffer Uniforms_{} : register(b{}) {{", ident, index).unwrap();
             for (index, _item) in vec {
@@ -193,9 +205,21 @@ impl<'a> DrawShaderGenerator<'a> {
         for field in &self.draw_shader_def.fields {
             match field.kind {
                 DrawShaderFieldKind::Texture {..} => {
-                    assert_ne!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::TextureOES, "TextureOES is only available on Android");
-                    assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
-                    write!(self.string, "Texture2D {}: register(t{});", DisplayDsIdent(field.ident), index).unwrap();
+                    let texture_ty = match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Texture2D => "Texture2D",
+                        Ty::TextureCube => "TextureCube",
+                        Ty::Texture3D => "Texture3D",
+                        Ty::Texture2DArray => "Texture2DArray",
+                        Ty::TextureOES => {
+                            self.backend_writer.report_error(field.span, "textureOES is only available on Android".to_string());
+                            continue
+                        }
+                        ty => {
+                            self.backend_writer.report_error(field.span, format!("unexpected texture type {}", ty));
+                            continue
+                        }
+                    };
+                    write!(self.string, "{} {}: register(t{});", texture_ty, DisplayDsIdent(field.ident), index).unwrap();
                     index += 1;
                 }
                 _ => {}
@@ -266,7 +290,7 @@ impl<'a> DrawShaderGenerator<'a> {
                             writeln!(self.string, ": INST{};", index_to_char(index)).unwrap();
                             index += 1;
                         },
-                        x => panic!("unsupported type in generate_instance_struct {:?}", x)
+                        x => self.backend_writer.report_error(field.span, format!("instances of type {} are not supported", x))
                     }
                 }
                 _ => {}
@@ -327,7 +351,7 @@ impl<'a> DrawShaderGenerator<'a> {
                             writeln!(self.string, ": VARY{};", index_to_char(index)).unwrap();
                             index += 1;
                         }
-                        x => panic!("unsupported type in generate_varying_struct {:?}", x)
+                        x => self.backend_writer.report_error(field.span, format!("varyings of type {} are not supported", x))
                     }
                 }
                 DrawShaderFieldKind::Varying {..} => {
@@ -468,6 +492,7 @@ impl<'a> DrawShaderGenerator<'a> {
         match ty {
             Ty::Bool => write!(self.string, "false").unwrap(),
             Ty::Int => write!(self.string, "0").unwrap(),
+            Ty::Uint => write!(self.string, "0").unwrap(),
             Ty::Float => write!(self.string, "0.0").unwrap(),
             Ty::Bvec2 => write!(self.string, "bool2(0,0)").unwrap(),
             Ty::Bvec3 => write!(self.string, "bool3(0,0,0)").unwrap(),
@@ -475,6 +500,9 @@ impl<'a> DrawShaderGenerator<'a> {
             Ty::Ivec2 => write!(self.string, "int2(0,0)").unwrap(),
             Ty::Ivec3 => write!(self.string, "int3(0,0,0)").unwrap(),
             Ty::Ivec4 => write!(self.string, "int4(0,0,0,0)").unwrap(),
+            Ty::Uvec2 => write!(self.string, "uint2(0,0)").unwrap(),
+            Ty::Uvec3 => write!(self.string, "uint3(0,0,0)").unwrap(),
+            Ty::Uvec4 => write!(self.string, "uint4(0,0,0,0)").unwrap(),
             Ty::Vec2 => write!(self.string, "float2(0.0,0.0)").unwrap(),
             Ty::Vec3 => write!(self.string, "float3(0.0,0.0,0.0)").unwrap(),
             Ty::Vec4 => write!(self.string, "float4(0.0,0.0,0.0,0.0)").unwrap(),
@@ -482,7 +510,7 @@ impl<'a> DrawShaderGenerator<'a> {
             Ty::Mat3 => write!(self.string, "float3x3(0.0,0.0,0.0, 0.0,0.0,0.0, 0.0,0.0,0.0)").unwrap(),
             Ty::Mat4 => write!(self.string, "float4x4(0.0,0.0,0.0,0.0, 0.0,0.0,0.0,0.0, 0.0,0.0,0.0,0.0, 0.0,0.0,0.0,0.0)").unwrap(),
             Ty::Enum(_) => write!(self.string, "0").unwrap(),
-            _ => panic!("Implement init for type")
+            _ => self.backend_writer.report_error(TokenSpan::default(), format!("cannot initialize a value of type {}", ty))
         }
     }
     
@@ -498,10 +526,15 @@ impl<'a> DrawShaderGenerator<'a> {
 struct HlslBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
     pub draw_shader_def: &'a DrawShaderDef,
-    pub const_table: &'a DrawShaderConstTable
+    pub const_table: &'a DrawShaderConstTable,
+    pub error: GenerateError
 }
 
 impl<'a> BackendWriter for HlslBackendWriter<'a> {
+    fn error(&self) -> &GenerateError {
+        &self.error
+    }
+    
     
     fn get_struct_cons_type(&self) -> StructConsType {
         StructConsType::ConsFn
@@ -561,6 +594,11 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -596,6 +634,21 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Ivec4);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uvec2 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Uvec3 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::Uvec4 => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Vec2 => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Vec2);
@@ -626,7 +679,9 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Mat4);
                 write!(string, " {}", ident).unwrap();
             }
-            Ty::Texture2D | Ty::TextureOES => panic!(), // TODO
+            Ty::Texture2D | Ty::TextureOES | Ty::TextureCube | Ty::Texture3D | Ty::Texture2DArray => {
+                self.report_error(TokenSpan::default(), format!("cannot declare {} of type {}", ident, ty)); // TODO
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -788,6 +843,7 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -795,13 +851,19 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 TyLit::Ivec2 => "int2",
                 TyLit::Ivec3 => "int3",
                 TyLit::Ivec4 => "int4",
+                TyLit::Uvec2 => "uint2",
+                TyLit::Uvec3 => "uint3",
+                TyLit::Uvec4 => "uint4",
                 TyLit::Vec2 => "float2",
                 TyLit::Vec3 => "float3",
                 TyLit::Vec4 => "float4",
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D | TyLit::TextureOES => panic!(), // TODO
+                TyLit::Texture2D | TyLit::TextureOES | TyLit::TextureCube | TyLit::Texture3D | TyLit::Texture2DArray => {
+                    self.report_error(TokenSpan::default(), format!("{} cannot be written as a type here", ty_lit)); // TODO
+                    ""
+                }
             }
         )
             .unwrap();
@@ -18,22 +18,23 @@ pub struct MetalGeneratedShader{
     pub fields_as_uniform_blocks:BTreeMap<Ident, Vec<(usize, Ident) >>   
 }
 
-pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> MetalGeneratedShader {
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<MetalGeneratedShader, LiveError> {
     let mut string = String::new();
     let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    let backend_writer = MetalBackendWriter {shader_registry, draw_shader_def, const_table, error: GenerateError::default()};
     DrawShaderGenerator {
         draw_shader_def,
         shader_registry,
         const_table,
         string: &mut string,
         fields_as_uniform_blocks: &fields_as_uniform_blocks,
-        backend_writer: &MetalBackendWriter {shader_registry, draw_shader_def, const_table}
+        backend_writer: &backend_writer
     }
     .generate_shader();
-    MetalGeneratedShader{
+    backend_writer.error.into_result(MetalGeneratedShader{
         mtlsl:string, 
         fields_as_uniform_blocks
-    }
+    })
 }
 
 struct DrawShaderGenerator<'a> {
@@ -69,6 +70,19 @@ impl<'a> DrawShaderGenerator<'a> {
             }
         };
         
+        let uses_builtin = | id: LiveId | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(id))
+        });
+        if uses_builtin(live_id!(sampleCube)) {
+            writeln!(self.string, "float4 sampleCube(texturecube<float> tex, float3 dir){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}}").unwrap();
+        }
+        if uses_builtin(live_id!(sample3d)) {
+            writeln!(self.string, "float4 sample3d(texture3d<float> tex, float3 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
+        }
+        if uses_builtin(live_id!(sample2dArray)) {
+            writeln!(self.string, "float4 sample2dArray(texture2d_array<float> tex, float2 pos, float layer){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos,uint(max(rint(layer),0.0)));}}").unwrap();
+        }
+        
         self.generate_struct_defs();
         //let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
         self.generate_uniform_structs();
@@ -166,9 +180,21 @@ impl<'a> DrawShaderGenerator<'a> {
         for field in &self.draw_shader_def.fields {
             match field.kind {
                 DrawShaderFieldKind::Texture {..} => {
-                    assert_ne!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::TextureOES, "TextureOES is only available on Android");
-                    assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
-                    write!(self.string, "    texture2d<float> ").unwrap();
+                    let texture_ty = match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Texture2D => "texture2d<float>",
+                        Ty::TextureCube => "texturecube<float>",
+                        Ty::Texture3D => "texture3d<float>",
+                        Ty::Texture2DArray => "texture2d_array<float>",
+                        Ty::TextureOES => {
+                            self.backend_writer.report_error(field.span, "textureOES is only available on Android".to_string());
+                            continue
+                        }
+                        ty => {
+                            self.backend_writer.report_error(field.span, format!("unexpected texture type {}", ty));
+                            continue
+                        }
+                    };
+                    write!(self.string, "    {} ", texture_ty).unwrap();
                     write!(self.string, "{}", &DisplayDsIdent(field.ident)).unwrap();
                     write!(self.string, " [[texture({})]];", index).unwrap();
                     index += 1;
@@ -239,7 +265,7 @@ impl<'a> DrawShaderGenerator<'a> {
                             self.write_var_decl_packed(&DisplayDsIdent(field.ident), &Ty::Enum(*v));
                             writeln!(self.string, ";").unwrap();
                         }
-                        _ => panic!("unsupported type in generate_instance_struct")
+                        x => self.backend_writer.report_error(field.span, format!("instances of type {} are not supported", x))
                     }
                 }
                 /*                
@@ -298,7 +324,7 @@ impl<'a> DrawShaderGenerator<'a> {
                             self.write_var_decl_packed(&DisplayDsIdent(field.ident), &Ty::Enum(*v));
                             writeln!(self.string, ";").unwrap();
                         }
-                        _ => panic!("unsupported type in generate_varying_struct")
+                        x => self.backend_writer.report_error(field.span, format!("varyings of type {} are not supported", x))
                     }
                 }
                 DrawShaderFieldKind::Varying {..} => {
@@ -446,9 +472,14 @@ struct MetalBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
     pub draw_shader_def: &'a DrawShaderDef,
     pub const_table: &'a DrawShaderConstTable,
+    pub error: GenerateError,
 }
 
 impl<'a> BackendWriter for MetalBackendWriter<'a> {
+    fn error(&self) -> &GenerateError {
+        &self.error
+    }
+    
     
     fn get_struct_cons_type(&self) -> StructConsType {
         StructConsType::Brace
@@ -518,6 +549,11 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}{}", ref_prefix, ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}{}", ref_prefix, ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -557,6 +593,24 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Ivec4);
                 write!(string, " {}{}", ref_prefix, ident).unwrap();
             }
+            Ty::Uvec2 => {
+                prefix(string, sep, is_inout);
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " {}{}", ref_prefix, ident).unwrap();
+            }
+            Ty::Uvec3 => {
+                prefix(string, sep, is_inout);
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " {}{}", ref_prefix, ident).unwrap();
+            }
+            Ty::Uvec4 => {
+                prefix(string, sep, is_inout);
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " {}{}", ref_prefix, ident).unwrap();
+            }
             Ty::Vec2 => {
                 prefix(string, sep, is_inout);
                 write!(string, "{}", packed_prefix).unwrap();
@@ -592,7 +646,9 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Mat4);
                 write!(string, " {}{}", ref_prefix, ident).unwrap();
             }
-            Ty::Texture2D | Ty::TextureOES => panic!(), // TODO
+            Ty::Texture2D | Ty::TextureOES | Ty::TextureCube | Ty::Texture3D | Ty::Texture2DArray => {
+                self.report_error(TokenSpan::default(), format!("cannot declare {} of type {}", ident, ty)); // TODO
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -779,6 +835,7 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -786,13 +843,19 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 TyLit::Ivec2 => "int2",
                 TyLit::Ivec3 => "int3",
                 TyLit::Ivec4 => "int4",
+                TyLit::Uvec2 => "uint2",
+                TyLit::Uvec3 => "uint3",
+                TyLit::Uvec4 => "uint4",
                 TyLit::Vec2 => "float2",
                 TyLit::Vec3 => "float3",
                 TyLit::Vec4 => "float4",
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D | TyLit::TextureOES => panic!(), // TODO
+                TyLit::Texture2D | TyLit::TextureOES | TyLit::TextureCube | TyLit::Texture3D | TyLit::Texture2DArray => {
+                    self.report_error(TokenSpan::default(), format!("{} cannot be written as a type here", ty_lit)); // TODO
+                    ""
+                }
             }
         )
             .unwrap();
@@ -0,0 +1,969 @@
+use {
+    std::{
+        fmt::Write,
+        fmt,
+        cell::RefCell,
+        collections::{BTreeMap, BTreeSet}
+    },
+    crate::{
+        makepad_live_compiler::*,
+        makepad_live_id::*,
+        shader_ast::*,
+        generate::*,
+        shader_registry::ShaderRegistry,
+    }
+};
+
+// The binding layout follows the Metal backend:
+// vertex buffer 0 holds the geometries and vertex buffer 1 the instances, with
+// attribute locations numbered in field order starting at the geometries.
+// Bind group 0 uses the Metal buffer indices, so binding 2 is the live uniforms,
+// binding 3 the const table (an array of vec4, pad it to a multiple of 4 floats)
+// and binding 4 onwards the uniform blocks in fields_as_uniform_blocks order.
+// Bind group 1 holds the textures in field order, followed by a linear sampler.
+
+pub const WGSL_BUFFER_GEOMETRIES: usize = 0;
+pub const WGSL_BUFFER_INSTANCES: usize = 1;
+pub const WGSL_BINDING_LIVE_UNIFORMS: usize = 2;
+pub const WGSL_BINDING_CONST_TABLE: usize = 3;
+pub const WGSL_BINDING_UNIFORM_BLOCKS: usize = 4;
+
+pub struct WgslGeneratedShader{
+    pub wgsl: String,
+    pub fields_as_uniform_blocks:BTreeMap<Ident, Vec<(usize, Ident) >>
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> Result<WgslGeneratedShader, LiveError> {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    let backend_writer = WgslBackendWriter {
+        shader_registry,
+        draw_shader_def,
+        const_table,
+        helper_fns: RefCell::new(BTreeMap::new()),
+        error: GenerateError::default()
+    };
+    DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &backend_writer
+    }
+    .generate_shader();
+    // builtins that WGSL lacks are collected while generating, and module
+    // scope declarations are order independent so they can go last
+    for helper_fn in backend_writer.helper_fns.borrow().values() {
+        string.push_str(helper_fn);
+    }
+    backend_writer.error.into_result(WgslGeneratedShader{
+        wgsl:string,
+        fields_as_uniform_blocks
+    })
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a WgslBackendWriter<'a>,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        let mut all_constructor_fns = BTreeSet::new();
+
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        self.generate_struct_defs();
+        self.generate_uniform_structs();
+        self.generate_textures();
+        self.generate_geometry_struct();
+        self.generate_instance_struct();
+        self.generate_varying_struct();
+
+        for (ty_lit, ref param_tys) in all_constructor_fns{
+            self.generate_cons_fn(ty_lit, &param_tys);
+        }
+
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(&fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            &mut self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def()
+        }
+        self.generate_vertex_main();
+        self.generate_pixel_main();
+    }
+
+    fn generate_struct_defs(&mut self) {
+        // we have all the structs already from analyse
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(&DisplayStructField(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+        }
+    }
+
+    fn generate_uniform_structs(&mut self,) {
+        // WGSL has no empty structs, so the live uniforms are left out entirely without live values
+        let all_live_refs = self.draw_shader_def.all_live_refs.borrow();
+        if !all_live_refs.is_empty() {
+            writeln!(self.string, "struct LiveUniforms {{").unwrap();
+            for (value_node_ptr, ty) in all_live_refs.iter() {
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(value_node_ptr, ty);
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> live_uniforms: LiveUniforms;", WGSL_BINDING_LIVE_UNIFORMS).unwrap();
+        }
+
+        if self.const_table.table.len()>0 {
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<uniform> const_table: array<vec4<f32>, {}>;",
+                WGSL_BINDING_CONST_TABLE,
+                (self.const_table.table.len() + 3) / 4
+            ).unwrap();
+        }
+
+        let mut binding = WGSL_BINDING_UNIFORM_BLOCKS;
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            writeln!(self.string, "struct Uniforms_{} {{", ident).unwrap();
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};").unwrap();
+            writeln!(self.string, "@group(0) @binding({0}) var<uniform> uniforms_{1}: Uniforms_{1};", binding, ident).unwrap();
+            binding += 1;
+        }
+    }
+
+    fn generate_textures(&mut self) {
+        let mut index = 0;
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Texture {..} => {
+                    let ty = field.ty_expr.ty.borrow().clone().unwrap();
+                    if ty == Ty::TextureOES {
+                        self.backend_writer.report_error(field.span, "textureOES is only available on Android".to_string());
+                        continue
+                    }
+                    write!(self.string, "@group(1) @binding({}) var {}: ", index, DisplayDsIdent(field.ident)).unwrap();
+                    self.backend_writer.write_ty(self.string, &ty);
+                    writeln!(self.string, ";").unwrap();
+                    index += 1;
+                }
+                _ => {}
+            }
+        }
+        writeln!(self.string, "@group(1) @binding({}) var default_sampler: sampler;", index).unwrap();
+    }
+
+    fn has_geometries(&self) -> bool {
+        self.draw_shader_def.fields.iter().any( | field | matches!(field.kind, DrawShaderFieldKind::Geometry {..}))
+    }
+
+    fn has_instances(&self) -> bool {
+        self.draw_shader_def.fields.iter().any( | field | matches!(field.kind, DrawShaderFieldKind::Instance {..}))
+    }
+
+    fn geometry_locations(&self) -> usize {
+        self.draw_shader_def.fields.iter().filter( | field | matches!(field.kind, DrawShaderFieldKind::Geometry {..})).count()
+    }
+
+    fn generate_geometry_struct(&mut self) {
+        if !self.has_geometries() {
+            return
+        }
+        let mut location = 0;
+        writeln!(self.string, "struct Geometries {{").unwrap();
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Geometry {..} => {
+                    write!(self.string, "    @location({}) ", location).unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                    writeln!(self.string, ",").unwrap();
+                    location += 1;
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> geometries: Geometries;").unwrap();
+    }
+
+    fn generate_instance_struct(&mut self) {
+        if !self.has_instances() {
+            return
+        }
+        let mut padding = 0;
+        let mut location = self.geometry_locations();
+        writeln!(self.string, "struct Instances {{").unwrap();
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Instance {..} => {
+                    match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                            write!(self.string, "    @location({}) ", location).unwrap();
+                            if field.ident == Ident(LiveId(0)){
+                                self.write_var_decl(&DisplayPadding(padding), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                                padding += 1;
+                            }
+                            else{
+                                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                            }
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        },
+                        Ty::Mat4 => {
+                            for i in 0..4 {
+                                write!(self.string, "    @location({}) {}{}: ", location, DisplayDsIdent(field.ident), i).unwrap();
+                                self.write_ty_lit(TyLit::Vec4);
+                                writeln!(self.string, ",").unwrap();
+                                location += 1;
+                            }
+                        },
+                        Ty::Mat3 => {
+                            for i in 0..3 {
+                                write!(self.string, "    @location({}) {}{}: ", location, DisplayDsIdent(field.ident), i).unwrap();
+                                self.write_ty_lit(TyLit::Vec3);
+                                writeln!(self.string, ",").unwrap();
+                                location += 1;
+                            }
+                        },
+                        Ty::Mat2 => {
+                            write!(self.string, "    @location({}) {}: ", location, DisplayDsIdent(field.ident)).unwrap();
+                            self.write_ty_lit(TyLit::Vec4);
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        },
+                        Ty::Enum(v) =>{
+                            write!(self.string, "    @location({}) ", location).unwrap();
+                            self.write_var_decl(&DisplayDsIdent(field.ident), &Ty::Enum(*v));
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        }
+                        x => self.backend_writer.report_error(field.span, format!("instances of type {} are not supported", x))
+                    }
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> instances: Instances;").unwrap();
+    }
+
+    fn generate_varying_struct(&mut self) {
+        let mut location = 0;
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    write!(self.string, "    @location({}) ", location).unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                    writeln!(self.string, ",").unwrap();
+                    location += 1;
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                            write!(self.string, "    @location({}) ", location).unwrap();
+                            self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        },
+                        Ty::Mat4 => {
+                            for i in 0..4 {
+                                write!(self.string, "    @location({}) {}{}: ", location, DisplayDsIdent(field.ident), i).unwrap();
+                                self.write_ty_lit(TyLit::Vec4);
+                                writeln!(self.string, ",").unwrap();
+                                location += 1;
+                            }
+                        },
+                        Ty::Mat3 => {
+                            for i in 0..3 {
+                                write!(self.string, "    @location({}) {}{}: ", location, DisplayDsIdent(field.ident), i).unwrap();
+                                self.write_ty_lit(TyLit::Vec3);
+                                writeln!(self.string, ",").unwrap();
+                                location += 1;
+                            }
+                        },
+                        Ty::Mat2 => {
+                            write!(self.string, "    @location({}) {}: ", location, DisplayDsIdent(field.ident)).unwrap();
+                            self.write_ty_lit(TyLit::Vec4);
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        },
+                        Ty::Enum(v) =>{
+                            // integers can't be interpolated
+                            write!(self.string, "    @location({}) @interpolate(flat) ", location).unwrap();
+                            self.write_var_decl(&DisplayDsIdent(field.ident), &Ty::Enum(*v));
+                            writeln!(self.string, ",").unwrap();
+                            location += 1;
+                        }
+                        x => self.backend_writer.report_error(field.span, format!("varyings of type {} are not supported", x))
+                    }
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    write!(self.string, "    @location({}) ", location).unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap(),);
+                    writeln!(self.string, ",").unwrap();
+                    location += 1;
+                }
+                _ => {}
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+        writeln!(self.string, "var<private> varyings: Varyings;").unwrap();
+    }
+
+    fn generate_cons_fn(&mut self, ty_lit: TyLit, param_tys: &[Ty]) {
+        let mut cons_name = format!("consfn_{}", ty_lit);
+        for param_ty in param_tys {
+            write!(cons_name, "_{}", param_ty).unwrap();
+        }
+        if !self.backend_writer.use_cons_fn(&cons_name) {
+            return
+        }
+        // only single argument constructors are routed here, see use_cons_fn
+        write!(self.string, "fn {}(x: ", cons_name).unwrap();
+        self.backend_writer.write_ty(self.string, &param_tys[0]);
+        write!(self.string, ") -> ").unwrap();
+        self.write_ty_lit(ty_lit);
+        write!(self.string, " {{\n    return ").unwrap();
+        self.write_ty_lit(ty_lit);
+        write!(self.string, "(").unwrap();
+        let dst_size = mat_size(&ty_lit.to_ty());
+        let src_size = mat_size(&param_tys[0]);
+        let mut sep = "";
+        if dst_size > 0 {
+            for col_index in 0..dst_size {
+                for row_index in 0..dst_size {
+                    if src_size == 0 {
+                        // a scalar makes a diagonal matrix
+                        if col_index == row_index {
+                            write!(self.string, "{}x", sep).unwrap();
+                        }
+                        else {
+                            write!(self.string, "{}0.0", sep).unwrap();
+                        }
+                    }
+                    else if row_index < src_size && col_index < src_size {
+                        write!(self.string, "{}x[{}][{}]", sep, col_index, row_index).unwrap();
+                    }
+                    else {
+                        write!(self.string, "{}{}", sep, if col_index == row_index {"1.0"} else {"0.0"}).unwrap();
+                    }
+                    sep = ", ";
+                }
+            }
+        }
+        else if vec_size(&param_tys[0]) == 1 {
+            // splatting a scalar of another element type
+            let elem_ty_lit = match ty_lit {
+                TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4 => TyLit::Bool,
+                TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4 => TyLit::Int,
+                TyLit::Uvec2 | TyLit::Uvec3 | TyLit::Uvec4 => TyLit::Uint,
+                _ => TyLit::Float
+            };
+            self.write_ty_lit(elem_ty_lit);
+            write!(self.string, "(x)").unwrap();
+        }
+        else {
+            // truncating a vector
+            for index in 0..ty_lit.to_ty().slots() {
+                write!(self.string, "{}x[{}]", sep, index).unwrap();
+                sep = ", ";
+            }
+        }
+        writeln!(self.string, ");\n}}").unwrap();
+    }
+
+    fn generate_vertex_main(&mut self) {
+        write!(self.string, "@vertex fn vertex_main(").unwrap();
+        let mut sep = "";
+        if self.has_geometries() {
+            write!(self.string, "in_geometries: Geometries").unwrap();
+            sep = ", ";
+        }
+        if self.has_instances() {
+            write!(self.string, "{}in_instances: Instances", sep).unwrap();
+        }
+        writeln!(self.string, ") -> Varyings {{").unwrap();
+        if self.has_geometries() {
+            writeln!(self.string, "    geometries = in_geometries;").unwrap();
+        }
+        if self.has_instances() {
+            writeln!(self.string, "    instances = in_instances;").unwrap();
+        }
+
+        for decl in &self.draw_shader_def.fields {
+            match &decl.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    writeln!(self.string, "    varyings.{0} = geometries.{0};", DisplayDsIdent(decl.ident)).unwrap();
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    match decl.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Mat4 => {
+                            for i in 0..4 {
+                                writeln!(self.string, "    varyings.{0}{1} = instances.{0}{1};", DisplayDsIdent(decl.ident), i).unwrap();
+                            }
+                        }
+                        Ty::Mat3 => {
+                            for i in 0..3 {
+                                writeln!(self.string, "    varyings.{0}{1} = instances.{0}{1};", DisplayDsIdent(decl.ident), i).unwrap();
+                            }
+                        }
+                        _ => {
+                            writeln!(self.string, "    varyings.{0} = instances.{0};", DisplayDsIdent(decl.ident)).unwrap();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_pixel_main(&mut self) {
+        writeln!(self.string, "@fragment fn fragment_main(in_varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        writeln!(self.string, "    varyings = in_varyings;").unwrap();
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(&mut self.string, "", false, false, ident, ty);
+    }
+
+    fn write_ty_lit(&mut self, ty_lit: TyLit) {
+        self.backend_writer.write_ty_lit(&mut self.string, ty_lit);
+    }
+}
+
+fn mat_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        Ty::Mat4 => 4,
+        _ => 0
+    }
+}
+
+fn vec_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => 1,
+        Ty::Bvec2 | Ty::Ivec2 | Ty::Uvec2 | Ty::Vec2 => 2,
+        Ty::Bvec3 | Ty::Ivec3 | Ty::Uvec3 | Ty::Vec3 => 3,
+        Ty::Bvec4 | Ty::Ivec4 | Ty::Uvec4 | Ty::Vec4 => 4,
+        _ => 0
+    }
+}
+
+// WGSL can't splat a scalar of another element type, like `vec2<u32>(1.0)`
+fn is_splat_conversion(cons_name: &str) -> bool {
+    let elem = | name: &str | match name {
+        "bvec2" | "bvec3" | "bvec4" => Some("bool"),
+        "ivec2" | "ivec3" | "ivec4" => Some("int"),
+        "uvec2" | "uvec3" | "uvec4" => Some("uint"),
+        "vec2" | "vec3" | "vec4" => Some("float"),
+        _ => None
+    };
+    let mut parts = cons_name.split('_').skip(1);
+    match (parts.next().and_then(elem), parts.next(), parts.next()) {
+        (Some(dst_elem), Some(src), None) => {
+            matches!(src, "bool" | "int" | "uint" | "float") && src != dst_elem
+        }
+        _ => false
+    }
+}
+
+struct WgslBackendWriter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    pub const_table: &'a DrawShaderConstTable,
+    // helper functions for builtins WGSL doesn't have, by name
+    pub helper_fns: RefCell<BTreeMap<String, String>>,
+    pub error: GenerateError,
+}
+
+impl<'a> WgslBackendWriter<'a> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Void => (),
+            Ty::Bool => self.write_ty_lit(string, TyLit::Bool),
+            Ty::Int => self.write_ty_lit(string, TyLit::Int),
+            Ty::Uint => self.write_ty_lit(string, TyLit::Uint),
+            Ty::Float => self.write_ty_lit(string, TyLit::Float),
+            Ty::Bvec2 => self.write_ty_lit(string, TyLit::Bvec2),
+            Ty::Bvec3 => self.write_ty_lit(string, TyLit::Bvec3),
+            Ty::Bvec4 => self.write_ty_lit(string, TyLit::Bvec4),
+            Ty::Ivec2 => self.write_ty_lit(string, TyLit::Ivec2),
+            Ty::Ivec3 => self.write_ty_lit(string, TyLit::Ivec3),
+            Ty::Ivec4 => self.write_ty_lit(string, TyLit::Ivec4),
+            Ty::Uvec2 => self.write_ty_lit(string, TyLit::Uvec2),
+            Ty::Uvec3 => self.write_ty_lit(string, TyLit::Uvec3),
+            Ty::Uvec4 => self.write_ty_lit(string, TyLit::Uvec4),
+            Ty::Vec2 => self.write_ty_lit(string, TyLit::Vec2),
+            Ty::Vec3 => self.write_ty_lit(string, TyLit::Vec3),
+            Ty::Vec4 => self.write_ty_lit(string, TyLit::Vec4),
+            Ty::Mat2 => self.write_ty_lit(string, TyLit::Mat2),
+            Ty::Mat3 => self.write_ty_lit(string, TyLit::Mat3),
+            Ty::Mat4 => self.write_ty_lit(string, TyLit::Mat4),
+            Ty::Texture2D => self.write_ty_lit(string, TyLit::Texture2D),
+            Ty::TextureOES => self.write_ty_lit(string, TyLit::TextureOES),
+            Ty::TextureCube => self.write_ty_lit(string, TyLit::TextureCube),
+            Ty::Texture3D => self.write_ty_lit(string, TyLit::Texture3D),
+            Ty::Texture2DArray => self.write_ty_lit(string, TyLit::Texture2DArray),
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(struct_ptr) => write!(string, "{}", struct_ptr).unwrap(),
+            Ty::Enum(_) => write!(string, "u32").unwrap(),
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                self.report_error(TokenSpan::default(), format!("{} cannot be written as a type", ty))
+            }
+        }
+    }
+
+    fn ty_name(&self, ty: &Ty) -> String {
+        let mut string = String::new();
+        self.write_ty(&mut string, ty);
+        string
+    }
+
+    fn add_helper_fn(&self, name: &str, body: impl FnOnce() -> String) {
+        let mut helper_fns = self.helper_fns.borrow_mut();
+        if !helper_fns.contains_key(name) {
+            helper_fns.insert(name.to_string(), body());
+        }
+    }
+
+    // writes a helper that calls a WGSL builtin with its scalar arguments
+    // splatted to the vector type GLSL would have used
+    fn write_splat_helper(&self, string: &mut String, ident: Ident, wgsl_fn: &str, arg_tys: &[Ty]) {
+        let vec_ty = arg_tys.iter().find( | ty | vec_size(ty) > 1).unwrap().clone();
+        let mut name = format!("mpsc_{}", ident);
+        for ty in arg_tys {
+            write!(name, "_{}", ty).unwrap();
+        }
+        self.add_helper_fn(&name, || {
+            let mut body = format!("fn {}(", name);
+            for (index, ty) in arg_tys.iter().enumerate() {
+                write!(body, "{}x{}: {}", if index > 0 {", "} else {""}, index, self.ty_name(ty)).unwrap();
+            }
+            write!(body, ") -> {} {{\n    return {}(", self.ty_name(&vec_ty), wgsl_fn).unwrap();
+            for (index, ty) in arg_tys.iter().enumerate() {
+                let sep = if index > 0 {", "} else {""};
+                if vec_size(ty) == 1 {
+                    write!(body, "{}{}(x{})", sep, self.ty_name(&vec_ty), index).unwrap();
+                }
+                else {
+                    write!(body, "{}x{}", sep, index).unwrap();
+                }
+            }
+            writeln!(body, ");\n}}").unwrap();
+            body
+        });
+        write!(string, "{}", name).unwrap();
+    }
+
+    // writes a helper with a body in terms of its arguments x0, x1
+    fn write_body_helper(&self, string: &mut String, ident: Ident, arg_tys: &[Ty], ret_ty: &Ty, expr: &str) {
+        let mut name = format!("mpsc_{}", ident);
+        for ty in arg_tys {
+            write!(name, "_{}", ty).unwrap();
+        }
+        self.add_helper_fn(&name, || {
+            let mut body = format!("fn {}(", name);
+            for (index, ty) in arg_tys.iter().enumerate() {
+                write!(body, "{}x{}: {}", if index > 0 {", "} else {""}, index, self.ty_name(ty)).unwrap();
+            }
+            writeln!(body, ") -> {} {{\n    return {};\n}}", self.ty_name(ret_ty), expr).unwrap();
+            body
+        });
+        write!(string, "{}", name).unwrap();
+    }
+}
+
+impl<'a> BackendWriter for WgslBackendWriter<'a> {
+    fn error(&self) -> &GenerateError {
+        &self.error
+    }
+
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn enum_is_float(&self)->bool{
+        false
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        match what {
+            "consfn_mat2_float" | "consfn_mat3_float" | "consfn_mat4_float" => true,
+            "consfn_mat2_mat3" | "consfn_mat2_mat4" | "consfn_mat3_mat2" |
+            "consfn_mat3_mat4" | "consfn_mat4_mat2" | "consfn_mat4_mat3" => true,
+            "consfn_vec2_vec3" | "consfn_vec2_vec4" | "consfn_vec3_vec4" => true,
+            _ => is_splat_conversion(what)
+        }
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        match ty {
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                return false
+            }
+            Ty::Void => {
+                write!(string, "{}{}", sep, ident).unwrap();
+            }
+            _ if is_inout => {
+                write!(string, "{}{}: ptr<function, ", sep, ident).unwrap();
+                self.write_ty(string, ty);
+                write!(string, ">").unwrap();
+            }
+            _ => {
+                write!(string, "{}{}: ", sep, ident).unwrap();
+                self.write_ty(string, ty);
+            }
+        }
+        true
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+        // everything is at module scope
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, string: &mut String) {
+        write!(string, "live_uniforms.").unwrap();
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, ty: &Ty) {
+        let field_def = self.draw_shader_def.find_field(field_ident).unwrap();
+
+        match &field_def.kind {
+            DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => {
+                if is_used_in_pixel_shader.get() {
+                    write!(string, "varyings.").unwrap()
+                }
+                else {
+                    write!(string, "geometries.").unwrap()
+                }
+            }
+            DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => {
+                let prefix = if is_used_in_pixel_shader.get() {
+                    "varyings"
+                }
+                else {
+                    "instances"
+                };
+
+                match ty {
+                    Ty::Mat4 | Ty::Mat3 => {
+                        let size = mat_size(ty);
+                        self.write_ty(string, ty);
+                        write!(string, "(").unwrap();
+                        for i in 0..size {
+                            for j in 0..size {
+                                if i != 0 || j != 0 {
+                                    write!(string, ",").unwrap();
+                                }
+                                write!(string, "{}.{}{}.{}", prefix, DisplayDsIdent(field_ident), j, ["x", "y", "z", "w"][i]).unwrap();
+                            }
+                        }
+                        write!(string, ")").unwrap();
+                        return
+                    },
+                    Ty::Mat2 => {
+                        write!(string, "mat2x2<f32>({0}.{1}.x, {0}.{1}.y, {0}.{1}.z, {0}.{1}.w)", prefix, DisplayDsIdent(field_ident)).unwrap();
+                        return
+                    },
+                    _ => {
+                        write!(string, "{}.",prefix).unwrap();
+                    }
+                }
+            }
+            DrawShaderFieldKind::Varying {..} => {
+                write!(string, "varyings.").unwrap()
+            }
+            DrawShaderFieldKind::Texture {..} => {
+            }
+            DrawShaderFieldKind::Uniform {block_ident, ..} => {
+                write!(string, "uniforms_{}.", block_ident).unwrap()
+            }
+        }
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Uint => "u32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Uvec2 => "vec2<u32>",
+                TyLit::Uvec3 => "vec3<u32>",
+                TyLit::Uvec4 => "vec4<u32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+                TyLit::TextureOES => {
+                    self.report_error(TokenSpan::default(), "textureOES is only available on Android".to_string());
+                    ""
+                }
+                TyLit::TextureCube => "texture_cube<f32>",
+                TyLit::Texture3D => "texture_3d<f32>",
+                TyLit::Texture2DArray => "texture_2d_array<f32>",
+            }
+        )
+            .unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        let arg_tys: Vec<Ty> = arg_exprs.iter().map( | arg | arg.ty.borrow().clone().unwrap()).collect();
+        let is_mixed = arg_tys.iter().any( | ty | vec_size(ty) == 1) && arg_tys.iter().any( | ty | vec_size(ty) > 1);
+        match ident {
+            Ident(live_id!(atan)) => {
+                if arg_exprs.len() == 2 {
+                    write!(string, "atan2").unwrap();
+                }
+                else {
+                    write!(string, "atan").unwrap();
+                }
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap();
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap();
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap();
+            }
+            Ident(live_id!(faceforward)) => {
+                write!(string, "faceForward").unwrap();
+            }
+            Ident(live_id!(min)) | Ident(live_id!(max)) | Ident(live_id!(clamp)) |
+            Ident(live_id!(mix)) | Ident(live_id!(step)) | Ident(live_id!(smoothstep)) if is_mixed => {
+                self.write_splat_helper(string, ident, &ident.to_string(), &arg_tys);
+            }
+            Ident(live_id!(mod)) => {
+                // GLSL mod rounds towards negative infinity, WGSL % truncates
+                let ret_ty = arg_tys[0].clone();
+                if is_mixed {
+                    let expr = format!("x0 - x1 * floor(x0 / {}(x1))", self.ty_name(&ret_ty));
+                    self.write_body_helper(string, ident, &arg_tys, &ret_ty, &expr);
+                }
+                else {
+                    self.write_body_helper(string, ident, &arg_tys, &ret_ty, "x0 - x1 * floor(x0 / x1)");
+                }
+            }
+            Ident(live_id!(lessThan)) | Ident(live_id!(lessThanEqual)) |
+            Ident(live_id!(greaterThan)) | Ident(live_id!(greaterThanEqual)) |
+            Ident(live_id!(equal)) | Ident(live_id!(notEqual)) => {
+                let op = match ident {
+                    Ident(live_id!(lessThan)) => "<",
+                    Ident(live_id!(lessThanEqual)) => "<=",
+                    Ident(live_id!(greaterThan)) => ">",
+                    Ident(live_id!(greaterThanEqual)) => ">=",
+                    Ident(live_id!(equal)) => "==",
+                    _ => "!=",
+                };
+                let ret_ty = match vec_size(&arg_tys[0]) {
+                    2 => Ty::Bvec2,
+                    3 => Ty::Bvec3,
+                    _ => Ty::Bvec4
+                };
+                self.write_body_helper(string, ident, &arg_tys, &ret_ty, &format!("x0 {} x1", op));
+            }
+            Ident(live_id!(not)) => {
+                self.write_body_helper(string, ident, &arg_tys, &arg_tys[0], "!x0");
+            }
+            Ident(live_id!(matrixCompMult)) => {
+                let size = mat_size(&arg_tys[0]);
+                let expr = format!(
+                    "{}({})",
+                    self.ty_name(&arg_tys[0]),
+                    (0..size).map( | i | format!("x0[{0}] * x1[{0}]", i)).collect::<Vec<_ >>().join(", ")
+                );
+                self.write_body_helper(string, ident, &arg_tys, &arg_tys[0], &expr);
+            }
+            Ident(live_id!(inverse)) => {
+                self.add_helper_fn("mpsc_inverse_mat4", || String::from(WGSL_INVERSE_MAT4));
+                write!(string, "mpsc_inverse_mat4").unwrap();
+            }
+            Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) => {
+                // explicit lod so sampling is allowed in non-uniform control flow and the vertex stage
+                self.write_body_helper(
+                    string,
+                    ident,
+                    &arg_tys,
+                    &Ty::Vec4,
+                    "textureSampleLevel(x0, default_sampler, x1, 0.0)"
+                );
+            }
+            Ident(live_id!(sampleCube)) | Ident(live_id!(sample3d)) => {
+                self.write_body_helper(
+                    string,
+                    ident,
+                    &arg_tys,
+                    &Ty::Vec4,
+                    "textureSampleLevel(x0, default_sampler, x1, 0.0)"
+                );
+            }
+            Ident(live_id!(sample2dArray)) => {
+                self.write_body_helper(
+                    string,
+                    ident,
+                    &arg_tys,
+                    &Ty::Vec4,
+                    "textureSampleLevel(x0, default_sampler, x1, i32(max(round(x2), 0.0)), 0.0)"
+                );
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+
+    fn write_fn_def_name(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}", ident).unwrap();
+    }
+
+    fn write_fn_def_return_ty(&self, string: &mut String, return_ty: &Ty) {
+        if *return_ty != Ty::Void {
+            write!(string, " -> ").unwrap();
+            self.write_ty(string, return_ty);
+        }
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var {}: ", ident).unwrap();
+        self.write_ty(string, ty);
+    }
+
+    fn write_for_var_decl(&self, string: &mut String, ident: &dyn fmt::Display) {
+        write!(string, "var {}: i32", ident).unwrap();
+    }
+
+    fn cond_expr_is_select(&self) -> bool {
+        true
+    }
+
+    fn assign_is_stmt(&self) -> bool {
+        true
+    }
+
+    fn inout_is_pointer(&self) -> bool {
+        true
+    }
+}
+
+const WGSL_INVERSE_MAT4: &str = "fn mpsc_inverse_mat4(m: mat4x4<f32>) -> mat4x4<f32> {
+    let a00 = m[0][0]; let a01 = m[0][1]; let a02 = m[0][2]; let a03 = m[0][3];
+    let a10 = m[1][0]; let a11 = m[1][1]; let a12 = m[1][2]; let a13 = m[1][3];
+    let a20 = m[2][0]; let a21 = m[2][1]; let a22 = m[2][2]; let a23 = m[2][3];
+    let a30 = m[3][0]; let a31 = m[3][1]; let a32 = m[3][2]; let a33 = m[3][3];
+    let b00 = a00 * a11 - a01 * a10; let b01 = a00 * a12 - a02 * a10;
+    let b02 = a00 * a13 - a03 * a10; let b03 = a01 * a12 - a02 * a11;
+    let b04 = a01 * a13 - a03 * a11; let b05 = a02 * a13 - a03 * a12;
+    let b06 = a20 * a31 - a21 * a30; let b07 = a20 * a32 - a22 * a30;
+    let b08 = a20 * a33 - a23 * a30; let b09 = a21 * a32 - a22 * a31;
+    let b10 = a21 * a33 - a23 * a31; let b11 = a22 * a33 - a23 * a32;
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    return mat4x4<f32>(
+        a11 * b11 - a12 * b10 + a13 * b09, a02 * b10 - a01 * b11 - a03 * b09,
+        a31 * b05 - a32 * b04 + a33 * b03, a22 * b04 - a21 * b05 - a23 * b03,
+        a12 * b08 - a10 * b11 - a13 * b07, a00 * b11 - a02 * b08 + a03 * b07,
+        a32 * b02 - a30 * b05 - a33 * b01, a20 * b05 - a22 * b02 + a23 * b01,
+        a10 * b10 - a11 * b08 + a13 * b06, a01 * b08 - a00 * b10 - a03 * b06,
+        a30 * b04 - a31 * b02 + a33 * b00, a21 * b02 - a20 * b04 - a23 * b00,
+        a11 * b07 - a10 * b09 - a12 * b06, a00 * b09 - a01 * b07 + a02 * b06,
+        a31 * b01 - a30 * b03 - a32 * b00, a20 * b03 - a21 * b01 + a22 * b00
+    ) * (1.0 / det);
+}
+";
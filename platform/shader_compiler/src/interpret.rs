@@ -0,0 +1,1421 @@
+use {
+    std::{
+        rc::Rc,
+        collections::BTreeMap,
+    },
+    crate::{
+        makepad_live_id::{LiveId, live_id},
+        makepad_live_compiler::{
+            makepad_math::*,
+            LiveError,
+            LiveErrorOrigin,
+            live_error_origin,
+            TokenSpan
+        },
+        shader_ast::*,
+        shader_registry::ShaderRegistry,
+        swizzle::Swizzle,
+    }
+};
+
+/// A value produced by the CPU interpreter. Vectors store their component
+/// count next to the components, matrices are square and column major.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Void,
+    Bool(bool),
+    Int(i32),
+    Uint(u32),
+    Float(f32),
+    Bvec(usize, [bool; 4]),
+    Ivec(usize, [i32; 4]),
+    Uvec(usize, [u32; 4]),
+    Vec(usize, [f32; 4]),
+    Mat(usize, [f32; 16]),
+    Array(Vec<Value>),
+    Struct(StructPtr, Vec<Value>),
+    Texture(usize),
+}
+
+/// Texture inputs of a draw shader, indexed in the order the texture fields
+/// are declared. Render targets are expected to be stored top row first,
+/// so `sample2d_rt` samples the same way as `sample2d`.
+pub trait ShaderTextures {
+    fn sample_2d(&self, index: usize, uv: Vec2) -> Vec4;
+
+    fn sample_cube(&self, _index: usize, _dir: Vec3) -> Vec4 {
+        Vec4::default()
+    }
+
+    fn sample_3d(&self, _index: usize, _uvw: Vec3) -> Vec4 {
+        Vec4::default()
+    }
+
+    fn sample_2d_array(&self, _index: usize, _uv: Vec2, _layer: f32) -> Vec4 {
+        Vec4::default()
+    }
+}
+
+impl ShaderTextures for () {
+    fn sample_2d(&self, _index: usize, _uv: Vec2) -> Vec4 {
+        Vec4::default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VertexOutput {
+    pub position: Vec4,
+    pub varyings: Vec<f32>,
+}
+
+/// Runs the `vertex` and `pixel` functions of an analysed draw shader on the
+/// CPU. Uniforms and live values are set once, geometry, instance and
+/// varying inputs are passed per invocation as packed slots using the same
+/// layout the GPU backends use.
+pub struct DrawShaderInterpreter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    uniforms: Vec<Option<Value >>,
+    live_values: BTreeMap<ValuePtr, Value>,
+    uses_derivatives: bool,
+}
+
+impl<'a> DrawShaderInterpreter<'a> {
+    pub fn new(shader_registry: &'a ShaderRegistry, draw_shader_def: &'a DrawShaderDef) -> Self {
+        let mut uses_derivatives = false;
+        for fn_ptr in draw_shader_def.pixel_fns.borrow().iter() {
+            let fn_def = shader_registry.all_fns.get(fn_ptr).unwrap();
+            if let Some(deps) = fn_def.builtin_deps.borrow().as_ref() {
+                if deps.contains(&Ident(live_id!(dFdx))) || deps.contains(&Ident(live_id!(dFdy))) {
+                    uses_derivatives = true;
+                }
+            }
+        }
+        Self {
+            shader_registry,
+            draw_shader_def,
+            uniforms: vec![None; draw_shader_def.fields.len()],
+            live_values: BTreeMap::new(),
+            uses_derivatives,
+        }
+    }
+
+    pub fn field_index(&self, id: LiveId) -> Option<usize> {
+        self.draw_shader_def.fields.iter().position( | field | field.ident.0 == id)
+    }
+
+    /// Sets a uniform field, returns false if the shader has no uniform with that name.
+    pub fn set_uniform(&mut self, id: LiveId, value: Value) -> bool {
+        if let Some(index) = self.field_index(id) {
+            if let DrawShaderFieldKind::Uniform {..} = self.draw_shader_def.fields[index].kind {
+                self.uniforms[index] = Some(value);
+                return true
+            }
+        }
+        false
+    }
+
+    pub fn set_live_value(&mut self, value_ptr: ValuePtr, value: Value) {
+        self.live_values.insert(value_ptr, value);
+    }
+
+    /// Sets all live values from tightly packed slots, in the order of `all_live_refs`.
+    pub fn set_live_slots(&mut self, slots: &[f32]) {
+        let mut offset = 0;
+        for (value_ptr, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            let len = ty.slots();
+            if offset + len > slots.len() {
+                break;
+            }
+            self.live_values.insert(*value_ptr, Value::from_slots(ty, &slots[offset..offset + len]));
+            offset += len;
+        }
+    }
+
+    pub fn geometry_slots(&self) -> usize {
+        self.fields_slots( | kind | matches!(kind, DrawShaderFieldKind::Geometry {..}))
+    }
+
+    pub fn instance_slots(&self) -> usize {
+        self.fields_slots( | kind | matches!(kind, DrawShaderFieldKind::Instance {..}))
+    }
+
+    pub fn varying_slots(&self) -> usize {
+        self.fields_slots(is_packed_varying)
+    }
+
+    /// Packs geometry fields by name, fields that are not given are zero.
+    pub fn pack_geometry(&self, values: &[(LiveId, Value)]) -> Vec<f32> {
+        self.pack_fields( | kind | matches!(kind, DrawShaderFieldKind::Geometry {..}), values)
+    }
+
+    /// Packs instance fields by name, fields that are not given are zero.
+    pub fn pack_instance(&self, values: &[(LiveId, Value)]) -> Vec<f32> {
+        self.pack_fields( | kind | matches!(kind, DrawShaderFieldKind::Instance {..}), values)
+    }
+
+    /// Packs the values the pixel shader reads from the vertex shader by name.
+    pub fn pack_varyings(&self, values: &[(LiveId, Value)]) -> Vec<f32> {
+        self.pack_fields(is_packed_varying, values)
+    }
+
+    pub fn vertex(&self, geometry: &[f32], instance: &[f32], textures: &dyn ShaderTextures) -> Result<VertexOutput, LiveError> {
+        let mut inv = self.invocation(textures);
+        let mut geometry_offset = 0;
+        let mut instance_offset = 0;
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            let (slots, offset) = match field.kind {
+                DrawShaderFieldKind::Geometry {..} => (geometry, &mut geometry_offset),
+                DrawShaderFieldKind::Instance {..} => (instance, &mut instance_offset),
+                _ => continue
+            };
+            let ty = field_ty(field);
+            let len = ty.slots();
+            if *offset + len <= slots.len() {
+                inv.fields[index] = Value::from_slots(&ty, &slots[*offset..*offset + len]);
+            }
+            *offset += len;
+        }
+        let position = inv.call_entry(live_id!(vertex)) ?;
+        let position = position.to_vec4().ok_or_else( || eval_error(TokenSpan::default(), "vertex must return a vec4")) ?;
+        let mut varyings = Vec::new();
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            if is_packed_varying(&field.kind) {
+                inv.fields[index].write_slots(&mut varyings);
+            }
+        }
+        Ok(VertexOutput {position, varyings})
+    }
+
+    pub fn pixel(&self, varyings: &[f32], textures: &dyn ShaderTextures) -> Result<Vec4, LiveError> {
+        self.run_pixel(varyings, Derivatives::None, textures)
+    }
+
+    /// Runs the pixel shader with `dFdx`/`dFdy` support. `ddx` and `ddy` are the
+    /// varying deltas to the next pixel to the right and the next row down,
+    /// derivatives are the difference with a neighbouring invocation like a
+    /// GPU computes them within a pixel quad.
+    pub fn pixel_with_derivatives(&self, varyings: &[f32], ddx: &[f32], ddy: &[f32], textures: &dyn ShaderTextures) -> Result<Vec4, LiveError> {
+        if !self.uses_derivatives {
+            return self.run_pixel(varyings, Derivatives::None, textures)
+        }
+        let offset = | delta: &[f32] | varyings.iter().zip(delta.iter().chain(std::iter::repeat(&0.0))).map( | (v, d) | v + d).collect::<Vec<f32 >> ();
+        let mut probe_x = Vec::new();
+        let mut probe_y = Vec::new();
+        self.run_pixel(&offset(ddx), Derivatives::Record(&mut probe_x), textures) ?;
+        self.run_pixel(&offset(ddy), Derivatives::Record(&mut probe_y), textures) ?;
+        self.run_pixel(varyings, Derivatives::Replay {x: &probe_x, y: &probe_y, index: 0}, textures)
+    }
+
+    fn run_pixel(&self, varyings: &[f32], derivatives: Derivatives, textures: &dyn ShaderTextures) -> Result<Vec4, LiveError> {
+        let mut inv = self.invocation(textures);
+        inv.derivatives = derivatives;
+        let mut offset = 0;
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            if is_packed_varying(&field.kind) {
+                let ty = field_ty(field);
+                let len = ty.slots();
+                if offset + len <= varyings.len() {
+                    inv.fields[index] = Value::from_slots(&ty, &varyings[offset..offset + len]);
+                }
+                offset += len;
+            }
+        }
+        let color = inv.call_entry(live_id!(pixel)) ?;
+        color.to_vec4().ok_or_else( || eval_error(TokenSpan::default(), "pixel must return a vec4"))
+    }
+
+    fn invocation<'b>(&'b self, textures: &'b dyn ShaderTextures) -> Invocation<'a, 'b> {
+        let mut fields = Vec::with_capacity(self.draw_shader_def.fields.len());
+        let mut texture_index = 0;
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            fields.push(match field.kind {
+                DrawShaderFieldKind::Uniform {..} => match &self.uniforms[index] {
+                    Some(value) => value.clone(),
+                    None => Value::default_for(self.shader_registry, &field_ty(field))
+                },
+                DrawShaderFieldKind::Texture {..} => {
+                    texture_index += 1;
+                    Value::Texture(texture_index - 1)
+                }
+                _ => Value::default_for(self.shader_registry, &field_ty(field))
+            });
+        }
+        Invocation {
+            interp: self,
+            textures,
+            fields,
+            frames: Vec::new(),
+            derivatives: Derivatives::None,
+        }
+    }
+
+    fn fields_slots(&self, filter: fn(&DrawShaderFieldKind) -> bool) -> usize {
+        self.draw_shader_def.fields.iter().filter( | field | filter(&field.kind)).map( | field | field_ty(field).slots()).sum()
+    }
+
+    fn pack_fields(&self, filter: fn(&DrawShaderFieldKind) -> bool, values: &[(LiveId, Value)]) -> Vec<f32> {
+        let mut out = Vec::new();
+        for field in self.draw_shader_def.fields.iter().filter( | field | filter(&field.kind)) {
+            let ty = field_ty(field);
+            let start = out.len();
+            if let Some((_, value)) = values.iter().find( | (id, _) | *id == field.ident.0) {
+                value.write_slots(&mut out);
+            }
+            out.resize(start + ty.slots(), 0.0);
+        }
+        out
+    }
+}
+
+fn is_packed_varying(kind: &DrawShaderFieldKind) -> bool {
+    match kind {
+        DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+        DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+        DrawShaderFieldKind::Varying {..} => true,
+        _ => false
+    }
+}
+
+fn field_ty(field: &DrawShaderFieldDef) -> Ty {
+    field.ty_expr.ty.borrow().as_ref().unwrap().clone()
+}
+
+fn eval_error(span: TokenSpan, message: &str) -> LiveError {
+    LiveError {
+        origin: live_error_origin!(),
+        span: span.into(),
+        message: message.to_string()
+    }
+}
+
+enum Derivatives<'c> {
+    None,
+    Record(&'c mut Vec<Value>),
+    Replay {x: &'c [Value], y: &'c [Value], index: usize}
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Value)
+}
+
+struct ClosureBinding<'a> {
+    call_def: &'a FnDef,
+    closure_def: &'a ClosureDef,
+    locals: Vec<(Ident, ScopeSymShadow, Value)>,
+    closures: Vec<(usize, Rc<ClosureBinding<'a >>)>,
+}
+
+struct Frame<'a> {
+    fn_def: &'a FnDef,
+    locals: Vec<(Ident, ScopeSymShadow, Value)>,
+    loop_vars: Vec<(Ident, Value)>,
+    closures: Vec<(usize, Rc<ClosureBinding<'a >>)>,
+}
+
+impl<'a> Frame<'a> {
+    fn set_local(&mut self, ident: Ident, shadow: ScopeSymShadow, value: Value) {
+        if let Some(local) = self.locals.iter_mut().find( | (i, s, _) | *i == ident && *s == shadow) {
+            local.2 = value;
+        }
+        else {
+            self.locals.push((ident, shadow, value));
+        }
+    }
+
+    fn local_mut(&mut self, ident: Ident, shadow: ScopeSymShadow) -> Option<&mut Value> {
+        if let Some(local) = self.locals.iter_mut().find( | (i, s, _) | *i == ident && *s == shadow) {
+            return Some(&mut local.2)
+        }
+        // loop counters are declared without a shadow index
+        self.loop_vars.iter_mut().rev().find( | (i, _) | *i == ident).map( | (_, v) | v)
+    }
+}
+
+enum PlaceRoot {
+    Local(Ident, ScopeSymShadow),
+    Field(usize),
+}
+
+enum PlaceStep {
+    Struct(usize),
+    Swizzle(Vec<usize>),
+    Index(usize),
+}
+
+struct Invocation<'a, 'b> {
+    interp: &'b DrawShaderInterpreter<'a>,
+    textures: &'b dyn ShaderTextures,
+    fields: Vec<Value>,
+    frames: Vec<Frame<'a >>,
+    derivatives: Derivatives<'b>,
+}
+
+impl<'a, 'b> Invocation<'a, 'b> {
+    fn registry(&self) -> &'a ShaderRegistry {
+        self.interp.shader_registry
+    }
+
+    fn frame(&mut self) -> &mut Frame<'a> {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn call_entry(&mut self, id: LiveId) -> Result<Value, LiveError> {
+        let fn_def = self.registry().draw_shader_method_decl_from_ident(self.interp.draw_shader_def, Ident(id))
+            .ok_or_else( || eval_error(TokenSpan::default(), &format!("draw shader has no {} function", id))) ?;
+        self.call_fn(fn_def, &[])
+    }
+
+    // statements
+
+    fn exec_block(&mut self, block: &'a Block) -> Result<Flow, LiveError> {
+        for stmt in &block.stmts {
+            match self.exec_stmt(stmt) ? {
+                Flow::Next => (),
+                flow => return Ok(flow)
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_stmt(&mut self, stmt: &'a Stmt) -> Result<Flow, LiveError> {
+        match stmt {
+            Stmt::Break {..} => Ok(Flow::Break),
+            Stmt::Continue {..} => Ok(Flow::Continue),
+            Stmt::For {span, ident, from_expr, to_expr, step_expr, block, ..} => {
+                let const_int = | expr: &Expr | expr.const_val.borrow().as_ref().and_then( | v | v.as_ref()).and_then( | v | v.to_int());
+                let from = const_int(from_expr).ok_or_else( || eval_error(*span, "for loop bound is not const")) ?;
+                let to = const_int(to_expr).ok_or_else( || eval_error(*span, "for loop bound is not const")) ?;
+                let step = match step_expr {
+                    Some(step_expr) => const_int(step_expr).ok_or_else( || eval_error(*span, "for loop step is not const")) ?,
+                    None => if from < to {1} else {-1}
+                };
+                let mut i = if from <= to {from} else {from - 1};
+                self.frame().loop_vars.push((*ident, Value::Int(i)));
+                let mut result = Ok(Flow::Next);
+                while (from <= to && i < to) || (from > to && i >= to) {
+                    self.frame().loop_vars.last_mut().unwrap().1 = Value::Int(i);
+                    match self.exec_block(block) {
+                        Ok(Flow::Break) => break,
+                        Ok(Flow::Next) | Ok(Flow::Continue) => (),
+                        other => {
+                            result = other;
+                            break;
+                        }
+                    }
+                    i += if from <= to {step.abs()} else {-step.abs()};
+                }
+                self.frame().loop_vars.pop();
+                result
+            }
+            Stmt::If {span, expr, block_if_true, block_if_false} => {
+                let cond = self.eval_expr(expr) ?.to_bool().ok_or_else( || eval_error(*span, "if condition is not a bool")) ?;
+                if cond {
+                    self.exec_block(block_if_true)
+                }
+                else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(block_if_false)
+                }
+                else {
+                    Ok(Flow::Next)
+                }
+            }
+            Stmt::Match {span, expr, matches} => {
+                let value = self.eval_expr(expr) ?.to_f32().ok_or_else( || eval_error(*span, "match on a non enum value")) ?;
+                for match_item in matches {
+                    if let Some(enum_value) = match_item.enum_value.get() {
+                        if (value - enum_value as f32).abs() < 0.5 {
+                            return self.exec_block(&match_item.block)
+                        }
+                    }
+                }
+                Ok(Flow::Next)
+            }
+            Stmt::Let {ty, shadow, ident, expr, ..} => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(expr) ?,
+                    None => Value::default_for(self.registry(), ty.borrow().as_ref().unwrap())
+                };
+                let shadow = shadow.get().unwrap();
+                self.frame().set_local(*ident, shadow, value);
+                Ok(Flow::Next)
+            }
+            Stmt::Return {expr, ..} => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(expr) ?,
+                    None => Value::Void
+                };
+                Ok(Flow::Return(value))
+            }
+            Stmt::Block {block, ..} => self.exec_block(block),
+            Stmt::Expr {expr, ..} => {
+                self.eval_expr(expr) ?;
+                Ok(Flow::Next)
+            }
+        }
+    }
+
+    // expressions
+
+    fn eval_expr(&mut self, expr: &'a Expr) -> Result<Value, LiveError> {
+        if let Some(Some(val)) = expr.const_val.borrow().as_ref() {
+            return Ok(Value::from_val(val))
+        }
+        match &expr.kind {
+            ExprKind::Cond {span, expr, expr_if_true, expr_if_false} => {
+                let cond = self.eval_expr(expr) ?.to_bool().ok_or_else( || eval_error(*span, "condition is not a bool")) ?;
+                if cond {self.eval_expr(expr_if_true)} else {self.eval_expr(expr_if_false)}
+            }
+            ExprKind::Bin {span, op, left_expr, right_expr} => self.eval_bin_expr(*span, *op, left_expr, right_expr),
+            ExprKind::Un {span, op, expr} => {
+                let value = self.eval_expr(expr) ?;
+                match op {
+                    UnOp::Not => match value {
+                        Value::Bool(v) => Ok(Value::Bool(!v)),
+                        _ => Err(eval_error(*span, "! applied to a non bool value"))
+                    },
+                    UnOp::Neg => Lanes::from_value(&value).map( | l | l.map(l.kind, | x | -x).to_value())
+                        .ok_or_else( || eval_error(*span, "- applied to a non numeric value"))
+                }
+            }
+            ExprKind::Field {span, expr: inner, field_ident} => {
+                if let Some(Ty::DrawShader(_)) = inner.ty.borrow().as_ref() {
+                    let index = self.draw_shader_field_index(*span, *field_ident) ?;
+                    return Ok(self.fields[index].clone())
+                }
+                let value = self.eval_expr(inner) ?;
+                self.field_of(*span, value, *field_ident)
+            }
+            ExprKind::Index {span, expr, index_expr} => {
+                let value = self.eval_expr(expr) ?;
+                let index = self.eval_expr(index_expr) ?.to_f32().ok_or_else( || eval_error(*span, "index is not a number")) ? as usize;
+                value.index(index).ok_or_else( || eval_error(*span, "index out of range"))
+            }
+            ExprKind::MethodCall {span, ident, arg_exprs, ..} => {
+                let registry = self.registry();
+                let fn_def = match arg_exprs[0].ty.borrow().as_ref() {
+                    Some(Ty::Struct(struct_ptr)) => registry.struct_method_decl_from_ident(registry.structs.get(struct_ptr).unwrap(), *ident),
+                    Some(Ty::DrawShader(shader_ptr)) => registry.draw_shader_method_decl_from_ident(registry.draw_shader_defs.get(shader_ptr).unwrap(), *ident),
+                    _ => None
+                }.ok_or_else( || eval_error(*span, &format!("cannot find method {}", ident))) ?;
+                self.call_fn(fn_def, arg_exprs)
+            }
+            ExprKind::PlainCall {span, fn_ptr, param_index, arg_exprs, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    return self.call_closure(*span, param_index, arg_exprs)
+                }
+                let fn_def = fn_ptr.and_then( | fn_ptr | self.registry().all_fns.get(&fn_ptr))
+                    .ok_or_else( || eval_error(*span, "cannot find function")) ?;
+                self.call_fn(fn_def, arg_exprs)
+            }
+            ExprKind::BuiltinCall {span, ident, arg_exprs} => self.eval_builtin_call(*span, *ident, arg_exprs),
+            ExprKind::ClosureDef(_) => Ok(Value::Void),
+            ExprKind::ConsCall {span, ty_lit, arg_exprs} => {
+                let mut args = Vec::new();
+                for arg_expr in arg_exprs {
+                    args.push(self.eval_expr(arg_expr) ?);
+                }
+                Value::construct(*ty_lit, &args).ok_or_else( || eval_error(*span, &format!("cannot construct {}", ty_lit)))
+            }
+            ExprKind::StructCons {struct_ptr, span, args} => {
+                let struct_def = self.registry().structs.get(struct_ptr).unwrap();
+                let mut values = Vec::new();
+                for field in &struct_def.fields {
+                    let (_, arg) = args.iter().find( | (ident, _) | *ident == field.ident)
+                        .ok_or_else( || eval_error(*span, &format!("missing struct field {}", field.ident))) ?;
+                    values.push(self.eval_expr(arg) ?);
+                }
+                Ok(Value::Struct(*struct_ptr, values))
+            }
+            ExprKind::Var {span, kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    self.frame().local_mut(ident, shadow).map( | v | v.clone())
+                        .ok_or_else( || eval_error(*span, &format!("variable {} is not set", ident)))
+                }
+                Some(VarKind::LiveValue(value_ptr)) => {
+                    self.interp.live_values.get(&value_ptr).cloned()
+                        .ok_or_else( || eval_error(*span, "live value is not set"))
+                }
+                None => Err(eval_error(*span, "unresolved variable"))
+            },
+            ExprKind::Lit {lit, ..} => Ok(Value::from_val(&lit.to_val())),
+        }
+    }
+
+    fn draw_shader_field_index(&self, span: TokenSpan, field_ident: Ident) -> Result<usize, LiveError> {
+        self.interp.draw_shader_def.fields.iter().position( | field | field.ident == field_ident)
+            .ok_or_else( || eval_error(span, &format!("draw shader has no field {}", field_ident)))
+    }
+
+    fn field_of(&self, span: TokenSpan, value: Value, field_ident: Ident) -> Result<Value, LiveError> {
+        if let Value::Struct(struct_ptr, mut values) = value {
+            let struct_def = self.registry().structs.get(&struct_ptr).unwrap();
+            let index = struct_def.fields.iter().position( | field | field.ident == field_ident)
+                .ok_or_else( || eval_error(span, &format!("struct has no field {}", field_ident))) ?;
+            return Ok(values.swap_remove(index))
+        }
+        let swizzle = Swizzle::parse(field_ident).ok_or_else( || eval_error(span, &format!("invalid swizzle {}", field_ident))) ?;
+        let indices = swizzle.iter().cloned().collect::<Vec<_ >> ();
+        value.swizzle(&indices).ok_or_else( || eval_error(span, &format!("invalid swizzle {}", field_ident)))
+    }
+
+    fn eval_bin_expr(&mut self, span: TokenSpan, op: BinOp, left_expr: &'a Expr, right_expr: &'a Expr) -> Result<Value, LiveError> {
+        let arith = match op {
+            BinOp::Assign => {
+                let value = self.eval_expr(right_expr) ?;
+                self.assign(left_expr, value.clone()) ?;
+                return Ok(value)
+            }
+            BinOp::AddAssign => Some(BinOp::Add),
+            BinOp::SubAssign => Some(BinOp::Sub),
+            BinOp::MulAssign => Some(BinOp::Mul),
+            BinOp::DivAssign => Some(BinOp::Div),
+            BinOp::Or | BinOp::And => {
+                let left = self.eval_expr(left_expr) ?.to_bool().ok_or_else( || eval_error(span, "logic operand is not a bool")) ?;
+                if let BinOp::Or = op {
+                    if left {return Ok(Value::Bool(true))}
+                }
+                else if !left {
+                    return Ok(Value::Bool(false))
+                }
+                let right = self.eval_expr(right_expr) ?.to_bool().ok_or_else( || eval_error(span, "logic operand is not a bool")) ?;
+                return Ok(Value::Bool(right))
+            }
+            _ => None
+        };
+        if let Some(arith) = arith {
+            let left = self.eval_expr(left_expr) ?;
+            let right = self.eval_expr(right_expr) ?;
+            let value = binary_op(arith, &left, &right).ok_or_else( || eval_error(span, &format!("cannot apply {}", op))) ?;
+            self.assign(left_expr, value.clone()) ?;
+            return Ok(value)
+        }
+        let left = self.eval_expr(left_expr) ?;
+        let right = self.eval_expr(right_expr) ?;
+        binary_op(op, &left, &right).ok_or_else( || eval_error(span, &format!("cannot apply {}", op)))
+    }
+
+    // assignment
+
+    fn place(&mut self, expr: &'a Expr, steps: &mut Vec<PlaceStep>) -> Result<PlaceRoot, LiveError> {
+        match &expr.kind {
+            ExprKind::Var {span, kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => Ok(PlaceRoot::Local(ident, shadow)),
+                _ => Err(eval_error(*span, "cannot assign to this variable"))
+            },
+            ExprKind::Field {span, expr: inner, field_ident} => {
+                match inner.ty.borrow().as_ref() {
+                    Some(Ty::DrawShader(_)) => {
+                        return Ok(PlaceRoot::Field(self.draw_shader_field_index(*span, *field_ident) ?))
+                    }
+                    Some(Ty::Struct(struct_ptr)) => {
+                        let struct_def = self.registry().structs.get(struct_ptr).unwrap();
+                        let index = struct_def.fields.iter().position( | field | field.ident == *field_ident)
+                            .ok_or_else( || eval_error(*span, &format!("struct has no field {}", field_ident))) ?;
+                        let root = self.place(inner, steps) ?;
+                        steps.push(PlaceStep::Struct(index));
+                        return Ok(root)
+                    }
+                    _ => ()
+                }
+                let swizzle = Swizzle::parse(*field_ident).ok_or_else( || eval_error(*span, &format!("invalid swizzle {}", field_ident))) ?;
+                let root = self.place(inner, steps) ?;
+                steps.push(PlaceStep::Swizzle(swizzle.iter().cloned().collect()));
+                Ok(root)
+            }
+            ExprKind::Index {span, expr: inner, index_expr} => {
+                let index = self.eval_expr(index_expr) ?.to_f32().ok_or_else( || eval_error(*span, "index is not a number")) ? as usize;
+                let root = self.place(inner, steps) ?;
+                steps.push(PlaceStep::Index(index));
+                Ok(root)
+            }
+            _ => Err(eval_error(expr.span, "expression is not assignable"))
+        }
+    }
+
+    fn assign(&mut self, expr: &'a Expr, value: Value) -> Result<(), LiveError> {
+        let mut steps = Vec::new();
+        let root = self.place(expr, &mut steps) ?;
+        let target = match root {
+            PlaceRoot::Local(ident, shadow) => match self.frame().local_mut(ident, shadow) {
+                Some(target) => target,
+                None => {
+                    self.frame().set_local(ident, shadow, Value::Void);
+                    self.frame().local_mut(ident, shadow).unwrap()
+                }
+            },
+            PlaceRoot::Field(index) => &mut self.fields[index]
+        };
+        if store(target, &steps, value) {
+            Ok(())
+        }
+        else {
+            Err(eval_error(expr.span, "invalid assignment"))
+        }
+    }
+
+    // calls
+
+    fn call_fn(&mut self, fn_def: &'a FnDef, arg_exprs: &'a [Expr]) -> Result<Value, LiveError> {
+        let is_self = | ty: Option<&Ty> | matches!(ty, Some(Ty::DrawShader(_)));
+        let params = fn_def.params.iter().filter( | param | !is_self(param.ty_expr.ty.borrow().as_ref())).collect::<Vec<_ >> ();
+        let args = arg_exprs.iter().filter( | arg | !is_self(arg.ty.borrow().as_ref())).collect::<Vec<_ >> ();
+
+        let mut frame = Frame {
+            fn_def,
+            locals: Vec::new(),
+            loop_vars: Vec::new(),
+            closures: Vec::new(),
+        };
+        for (param_index, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+            if let TyExprKind::ClosureDecl {..} = param.ty_expr.kind {
+                let caller = self.frames.last().ok_or_else( || eval_error(arg.span, "closure passed without a caller")) ?;
+                let binding = match arg.kind {
+                    ExprKind::ClosureDef(index) => Rc::new(ClosureBinding {
+                        call_def: caller.fn_def,
+                        closure_def: &caller.fn_def.closure_defs[index.0],
+                        locals: caller.locals.clone(),
+                        closures: caller.closures.clone(),
+                    }),
+                    _ => return Err(eval_error(arg.span, "closure arguments must be closure literals"))
+                };
+                let index = fn_def.params.iter().position( | p | std::ptr::eq(p, *param)).unwrap_or(param_index);
+                frame.closures.push((index, binding));
+                continue;
+            }
+            let value = self.eval_expr(arg) ?;
+            if let Some(shadow) = param.shadow.get() {
+                frame.set_local(param.ident, shadow, value);
+            }
+        }
+
+        self.frames.push(frame);
+        let result = self.exec_block(&fn_def.block);
+        let frame = self.frames.pop().unwrap();
+        let value = match result ? {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        };
+
+        // write inout parameters back to the caller
+        for (param, arg) in params.iter().zip(args.iter()) {
+            if !param.is_inout {
+                continue;
+            }
+            if let Some(shadow) = param.shadow.get() {
+                if let Some((_, _, out)) = frame.locals.iter().find( | (i, s, _) | *i == param.ident && *s == shadow) {
+                    self.assign(arg, out.clone()) ?;
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    fn call_closure(&mut self, span: TokenSpan, param_index: usize, arg_exprs: &'a [Expr]) -> Result<Value, LiveError> {
+        let binding = self.frames.last().and_then( | frame | frame.closures.iter().find( | (index, _) | *index == param_index))
+            .map( | (_, binding) | binding.clone())
+            .ok_or_else( || eval_error(span, "closure is not bound")) ?;
+        let mut frame = Frame {
+            fn_def: binding.call_def,
+            locals: binding.locals.clone(),
+            loop_vars: Vec::new(),
+            closures: binding.closures.clone(),
+        };
+        for (param, arg) in binding.closure_def.params.iter().zip(arg_exprs.iter()) {
+            let value = self.eval_expr(arg) ?;
+            if let Some(shadow) = param.shadow.get() {
+                frame.set_local(param.ident, shadow, value);
+            }
+        }
+        self.frames.push(frame);
+        let result = match &binding.closure_def.kind {
+            ClosureDefKind::Expr(expr) => self.eval_expr(expr),
+            ClosureDefKind::Block(block) => self.exec_block(block).map( | flow | match flow {
+                Flow::Return(value) => value,
+                _ => Value::Void
+            })
+        };
+        self.frames.pop();
+        result
+    }
+
+    // builtins
+
+    fn eval_builtin_call(&mut self, span: TokenSpan, ident: Ident, arg_exprs: &'a [Expr]) -> Result<Value, LiveError> {
+        let mut args = Vec::new();
+        for arg_expr in arg_exprs {
+            args.push(self.eval_expr(arg_expr) ?);
+        }
+        let error = || eval_error(span, &format!("cannot evaluate builtin {}", ident));
+        match ident.0 {
+            live_id!(sample2d) | live_id!(sample2dOES) | live_id!(sample2d_rt) => {
+                let (texture, uv) = match (&args[0], args[1].to_vec2()) {
+                    (Value::Texture(texture), Some(uv)) => (*texture, uv),
+                    _ => return Err(error())
+                };
+                Ok(Value::from(self.textures.sample_2d(texture, uv)))
+            }
+            live_id!(sampleCube) | live_id!(sample3d) => {
+                let (texture, pos) = match (&args[0], args[1].to_vec3()) {
+                    (Value::Texture(texture), Some(pos)) => (*texture, pos),
+                    _ => return Err(error())
+                };
+                if ident.0 == live_id!(sampleCube) {
+                    Ok(Value::from(self.textures.sample_cube(texture, pos)))
+                }
+                else {
+                    Ok(Value::from(self.textures.sample_3d(texture, pos)))
+                }
+            }
+            live_id!(sample2dArray) => {
+                let (texture, uv, layer) = match (&args[0], args[1].to_vec2(), args[2].to_f32()) {
+                    (Value::Texture(texture), Some(uv), Some(layer)) => (*texture, uv, layer),
+                    _ => return Err(error())
+                };
+                Ok(Value::from(self.textures.sample_2d_array(texture, uv, layer)))
+            }
+            live_id!(dFdx) | live_id!(dFdy) => {
+                let arg = args.swap_remove(0);
+                match &mut self.derivatives {
+                    Derivatives::None => zero_like(&arg).ok_or_else(error),
+                    Derivatives::Record(record) => {
+                        let zero = zero_like(&arg).ok_or_else(error) ?;
+                        record.push(arg);
+                        Ok(zero)
+                    }
+                    Derivatives::Replay {x, y, index} => {
+                        let probe = if ident.0 == live_id!(dFdx) {x.get(*index)} else {y.get(*index)};
+                        *index += 1;
+                        match probe {
+                            Some(probe) => binary_op(BinOp::Sub, probe, &arg).ok_or_else(error),
+                            None => zero_like(&arg).ok_or_else(error)
+                        }
+                    }
+                }
+            }
+            _ => builtin(ident.0, &args).ok_or_else(error)
+        }
+    }
+}
+
+fn zero_like(value: &Value) -> Option<Value> {
+    Lanes::from_value(value).map( | l | l.map(l.kind, | _ | 0.0).to_value())
+}
+
+fn store(target: &mut Value, steps: &[PlaceStep], value: Value) -> bool {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => {
+            *target = value;
+            return true
+        }
+    };
+    match step {
+        PlaceStep::Struct(index) => match target {
+            Value::Struct(_, values) if *index < values.len() => store(&mut values[*index], rest, value),
+            _ => false
+        },
+        PlaceStep::Index(index) => match target {
+            Value::Array(values) if *index < values.len() => store(&mut values[*index], rest, value),
+            Value::Mat(dim, m) if *index < *dim && rest.len() == 0 => {
+                let dim = *dim;
+                match value {
+                    Value::Vec(len, v) if len == dim => {
+                        m[index * dim..index * dim + dim].copy_from_slice(&v[0..dim]);
+                        true
+                    }
+                    _ => false
+                }
+            }
+            _ if rest.len() == 0 => store_components(target, &[*index], &value),
+            _ => false
+        },
+        PlaceStep::Swizzle(indices) if rest.len() == 0 => store_components(target, indices, &value),
+        PlaceStep::Swizzle(_) => false
+    }
+}
+
+fn store_components(target: &mut Value, indices: &[usize], value: &Value) -> bool {
+    let (mut dst, src) = match (Lanes::from_value(target), Lanes::from_value(value)) {
+        (Some(dst), Some(src)) => (dst, src),
+        _ => return false
+    };
+    if dst.shape.is_matrix() || src.shape.len() != indices.len() || indices.iter().any( | i | *i >= dst.shape.len()) {
+        return false
+    }
+    for (src_index, dst_index) in indices.iter().enumerate() {
+        dst.v[*dst_index] = dst.kind.round(src.v[src_index]);
+    }
+    *target = dst.to_value();
+    true
+}
+
+// value conversions
+
+impl Value {
+    pub fn default_for(shader_registry: &ShaderRegistry, ty: &Ty) -> Value {
+        match ty {
+            Ty::Bool => Value::Bool(false),
+            Ty::Int => Value::Int(0),
+            Ty::Uint => Value::Uint(0),
+            Ty::Float | Ty::Enum(_) => Value::Float(0.0),
+            Ty::Bvec2 => Value::Bvec(2, [false; 4]),
+            Ty::Bvec3 => Value::Bvec(3, [false; 4]),
+            Ty::Bvec4 => Value::Bvec(4, [false; 4]),
+            Ty::Ivec2 => Value::Ivec(2, [0; 4]),
+            Ty::Ivec3 => Value::Ivec(3, [0; 4]),
+            Ty::Ivec4 => Value::Ivec(4, [0; 4]),
+            Ty::Uvec2 => Value::Uvec(2, [0; 4]),
+            Ty::Uvec3 => Value::Uvec(3, [0; 4]),
+            Ty::Uvec4 => Value::Uvec(4, [0; 4]),
+            Ty::Vec2 => Value::Vec(2, [0.0; 4]),
+            Ty::Vec3 => Value::Vec(3, [0.0; 4]),
+            Ty::Vec4 => Value::Vec(4, [0.0; 4]),
+            Ty::Mat2 => Value::Mat(2, [0.0; 16]),
+            Ty::Mat3 => Value::Mat(3, [0.0; 16]),
+            Ty::Mat4 => Value::Mat(4, [0.0; 16]),
+            Ty::Array {elem_ty, len} => Value::Array(vec![Value::default_for(shader_registry, elem_ty); *len]),
+            Ty::Struct(struct_ptr) => {
+                let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+                Value::Struct(*struct_ptr, struct_def.fields.iter().map( | field | {
+                    Value::default_for(shader_registry, field.ty_expr.ty.borrow().as_ref().unwrap())
+                }).collect())
+            }
+            _ => Value::Void
+        }
+    }
+
+    /// Unpacks a value from float slots, the way uniforms and instances are stored.
+    pub fn from_slots(ty: &Ty, slots: &[f32]) -> Value {
+        let get = | i: usize | slots.get(i).cloned().unwrap_or(0.0);
+        let vec = | len: usize | {let mut v = [0.0; 4]; for i in 0..len {v[i] = get(i)} v};
+        match ty {
+            Ty::Bool => Value::Bool(get(0) != 0.0),
+            Ty::Int => Value::Int(get(0) as i32),
+            Ty::Uint => Value::Uint(get(0) as u32),
+            Ty::Float | Ty::Enum(_) => Value::Float(get(0)),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+                let len = ty.slots();
+                let mut v = [false; 4];
+                for i in 0..len {v[i] = get(i) != 0.0}
+                Value::Bvec(len, v)
+            }
+            Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+                let len = ty.slots();
+                let mut v = [0; 4];
+                for i in 0..len {v[i] = get(i) as i32}
+                Value::Ivec(len, v)
+            }
+            Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => {
+                let len = ty.slots();
+                let mut v = [0; 4];
+                for i in 0..len {v[i] = get(i) as u32}
+                Value::Uvec(len, v)
+            }
+            Ty::Vec2 => Value::Vec(2, vec(2)),
+            Ty::Vec3 => Value::Vec(3, vec(3)),
+            Ty::Vec4 => Value::Vec(4, vec(4)),
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                let dim = match ty {Ty::Mat2 => 2, Ty::Mat3 => 3, _ => 4};
+                let mut m = [0.0; 16];
+                for i in 0..dim * dim {m[i] = get(i)}
+                Value::Mat(dim, m)
+            }
+            Ty::Array {elem_ty, len} => {
+                let elem_slots = elem_ty.slots();
+                Value::Array((0..*len).map( | i | {
+                    Value::from_slots(elem_ty, slots.get(i * elem_slots..).unwrap_or(&[]))
+                }).collect())
+            }
+            _ => Value::Void
+        }
+    }
+
+    pub fn write_slots(&self, out: &mut Vec<f32>) {
+        match self {
+            Value::Array(values) | Value::Struct(_, values) => {
+                for value in values {
+                    value.write_slots(out);
+                }
+            }
+            _ => if let Some(lanes) = Lanes::from_value(self) {
+                for i in 0..lanes.shape.len() {
+                    out.push(lanes.v[i] as f32);
+                }
+            }
+        }
+    }
+
+    pub fn from_val(val: &Val) -> Value {
+        match val {
+            Val::Bool(v) => Value::Bool(*v),
+            Val::Int(v) => Value::Int(*v),
+            Val::Float(v) => Value::Float(*v),
+            Val::Vec4(v) => Value::from(*v),
+        }
+    }
+
+    pub fn to_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_f32(&self) -> Option<f32> {
+        match self {
+            Value::Bool(v) => Some(if *v {1.0} else {0.0}),
+            Value::Int(v) => Some(*v as f32),
+            Value::Uint(v) => Some(*v as f32),
+            Value::Float(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_vec2(&self) -> Option<Vec2> {
+        match self {
+            Value::Vec(2, v) => Some(vec2(v[0], v[1])),
+            _ => None
+        }
+    }
+
+    pub fn to_vec3(&self) -> Option<Vec3> {
+        match self {
+            Value::Vec(3, v) => Some(vec3(v[0], v[1], v[2])),
+            _ => None
+        }
+    }
+
+    pub fn to_vec4(&self) -> Option<Vec4> {
+        match self {
+            Value::Vec(4, v) => Some(vec4(v[0], v[1], v[2], v[3])),
+            _ => None
+        }
+    }
+
+    fn swizzle(&self, indices: &[usize]) -> Option<Value> {
+        let lanes = Lanes::from_value(self)?;
+        if lanes.shape.is_matrix() || indices.len() > 4 || indices.iter().any( | i | *i >= lanes.shape.len()) {
+            return None
+        }
+        let mut v = [0.0; 16];
+        for (i, index) in indices.iter().enumerate() {
+            v[i] = lanes.v[*index];
+        }
+        let shape = if indices.len() == 1 {Shape::Scalar} else {Shape::Vector(indices.len())};
+        Some(Lanes {kind: lanes.kind, shape, v}.to_value())
+    }
+
+    fn index(&self, index: usize) -> Option<Value> {
+        match self {
+            Value::Array(values) => values.get(index).cloned(),
+            Value::Mat(dim, m) if index < *dim => {
+                let mut v = [0.0; 4];
+                v[0..*dim].copy_from_slice(&m[index * dim..index * dim + dim]);
+                Some(Value::Vec(*dim, v))
+            }
+            _ => self.swizzle(&[index])
+        }
+    }
+
+    /// Evaluates a constructor call like `vec4(v.xy, 0.0, 1.0)` or `mat3(m)`.
+    fn construct(ty_lit: TyLit, args: &[Value]) -> Option<Value> {
+        let (kind, shape) = match ty_lit {
+            TyLit::Bool => (Kind::Bool, Shape::Scalar),
+            TyLit::Int => (Kind::Int, Shape::Scalar),
+            TyLit::Uint => (Kind::Uint, Shape::Scalar),
+            TyLit::Float => (Kind::Float, Shape::Scalar),
+            TyLit::Bvec2 => (Kind::Bool, Shape::Vector(2)),
+            TyLit::Bvec3 => (Kind::Bool, Shape::Vector(3)),
+            TyLit::Bvec4 => (Kind::Bool, Shape::Vector(4)),
+            TyLit::Ivec2 => (Kind::Int, Shape::Vector(2)),
+            TyLit::Ivec3 => (Kind::Int, Shape::Vector(3)),
+            TyLit::Ivec4 => (Kind::Int, Shape::Vector(4)),
+            TyLit::Uvec2 => (Kind::Uint, Shape::Vector(2)),
+            TyLit::Uvec3 => (Kind::Uint, Shape::Vector(3)),
+            TyLit::Uvec4 => (Kind::Uint, Shape::Vector(4)),
+            TyLit::Vec2 => (Kind::Float, Shape::Vector(2)),
+            TyLit::Vec3 => (Kind::Float, Shape::Vector(3)),
+            TyLit::Vec4 => (Kind::Float, Shape::Vector(4)),
+            TyLit::Mat2 => (Kind::Float, Shape::Matrix(2)),
+            TyLit::Mat3 => (Kind::Float, Shape::Matrix(3)),
+            TyLit::Mat4 => (Kind::Float, Shape::Matrix(4)),
+            TyLit::Texture2D | TyLit::TextureOES | TyLit::TextureCube |
+            TyLit::Texture3D | TyLit::Texture2DArray => return None
+        };
+        let lanes = args.iter().map(Lanes::from_value).collect::<Option<Vec<_ >> > ()?;
+        let mut out = Lanes {kind, shape, v: [0.0; 16]};
+        if lanes.len() == 1 && lanes[0].shape == Shape::Scalar {
+            // splat a scalar, or fill the diagonal of a matrix
+            let x = kind.round(lanes[0].v[0]);
+            match shape {
+                Shape::Matrix(dim) => for i in 0..dim {out.v[i * dim + i] = x},
+                _ => for i in 0..shape.len() {out.v[i] = x}
+            }
+            return Some(out.to_value())
+        }
+        if let (1, Shape::Matrix(dim), Shape::Matrix(src_dim)) = (lanes.len(), shape, lanes[0].shape) {
+            for col in 0..dim {
+                for row in 0..dim {
+                    out.v[col * dim + row] = if col < src_dim && row < src_dim {
+                        lanes[0].v[col * src_dim + row]
+                    } else if col == row {1.0} else {0.0};
+                }
+            }
+            return Some(out.to_value())
+        }
+        let mut i = 0;
+        for arg in &lanes {
+            for j in 0..arg.shape.len() {
+                if i < shape.len() {
+                    out.v[i] = kind.round(arg.v[j]);
+                    i += 1;
+                }
+            }
+        }
+        if i < shape.len() {
+            return None
+        }
+        Some(out.to_value())
+    }
+}
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Self {Value::Float(v)}
+}
+
+impl From<Vec2> for Value {
+    fn from(v: Vec2) -> Self {Value::Vec(2, [v.x, v.y, 0.0, 0.0])}
+}
+
+impl From<Vec3> for Value {
+    fn from(v: Vec3) -> Self {Value::Vec(3, [v.x, v.y, v.z, 0.0])}
+}
+
+impl From<Vec4> for Value {
+    fn from(v: Vec4) -> Self {Value::Vec(4, [v.x, v.y, v.z, v.w])}
+}
+
+impl From<Mat4> for Value {
+    fn from(v: Mat4) -> Self {Value::Mat(4, v.v)}
+}
+
+// numeric values are computed on f64 lanes, which hold every i32 and u32
+// exactly and round back to f32 after every float operation
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Bool,
+    Int,
+    Uint,
+    Float
+}
+
+impl Kind {
+    fn round(self, x: f64) -> f64 {
+        match self {
+            Kind::Bool => if x != 0.0 {1.0} else {0.0},
+            Kind::Int => x.trunc() as i64 as i32 as f64,
+            Kind::Uint => x.trunc() as i64 as u32 as f64,
+            Kind::Float => x as f32 as f64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Shape {
+    Scalar,
+    Vector(usize),
+    Matrix(usize)
+}
+
+impl Shape {
+    fn len(self) -> usize {
+        match self {
+            Shape::Scalar => 1,
+            Shape::Vector(len) => len,
+            Shape::Matrix(dim) => dim * dim
+        }
+    }
+
+    fn is_matrix(self) -> bool {
+        matches!(self, Shape::Matrix(_))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Lanes {
+    kind: Kind,
+    shape: Shape,
+    v: [f64; 16]
+}
+
+impl Lanes {
+    fn from_value(value: &Value) -> Option<Lanes> {
+        let mut v = [0.0; 16];
+        let (kind, shape) = match value {
+            Value::Bool(x) => {v[0] = if *x {1.0} else {0.0}; (Kind::Bool, Shape::Scalar)}
+            Value::Int(x) => {v[0] = *x as f64; (Kind::Int, Shape::Scalar)}
+            Value::Uint(x) => {v[0] = *x as f64; (Kind::Uint, Shape::Scalar)}
+            Value::Float(x) => {v[0] = *x as f64; (Kind::Float, Shape::Scalar)}
+            Value::Bvec(len, x) => {for i in 0..*len {v[i] = if x[i] {1.0} else {0.0}} (Kind::Bool, Shape::Vector(*len))}
+            Value::Ivec(len, x) => {for i in 0..*len {v[i] = x[i] as f64} (Kind::Int, Shape::Vector(*len))}
+            Value::Uvec(len, x) => {for i in 0..*len {v[i] = x[i] as f64} (Kind::Uint, Shape::Vector(*len))}
+            Value::Vec(len, x) => {for i in 0..*len {v[i] = x[i] as f64} (Kind::Float, Shape::Vector(*len))}
+            Value::Mat(dim, x) => {for i in 0..dim * dim {v[i] = x[i] as f64} (Kind::Float, Shape::Matrix(*dim))}
+            _ => return None
+        };
+        Some(Lanes {kind, shape, v})
+    }
+
+    fn to_value(&self) -> Value {
+        let len = self.shape.len();
+        match (self.kind, self.shape) {
+            (Kind::Bool, Shape::Scalar) => Value::Bool(self.v[0] != 0.0),
+            (Kind::Int, Shape::Scalar) => Value::Int(self.v[0] as i32),
+            (Kind::Uint, Shape::Scalar) => Value::Uint(self.v[0] as u32),
+            (Kind::Float, Shape::Scalar) => Value::Float(self.v[0] as f32),
+            (Kind::Bool, _) => {let mut x = [false; 4]; for i in 0..len.min(4) {x[i] = self.v[i] != 0.0} Value::Bvec(len, x)}
+            (Kind::Int, _) => {let mut x = [0; 4]; for i in 0..len.min(4) {x[i] = self.v[i] as i32} Value::Ivec(len, x)}
+            (Kind::Uint, _) => {let mut x = [0; 4]; for i in 0..len.min(4) {x[i] = self.v[i] as u32} Value::Uvec(len, x)}
+            (Kind::Float, Shape::Vector(_)) => {let mut x = [0.0; 4]; for i in 0..len {x[i] = self.v[i] as f32} Value::Vec(len, x)}
+            (Kind::Float, Shape::Matrix(dim)) => {let mut x = [0.0; 16]; for i in 0..len {x[i] = self.v[i] as f32} Value::Mat(dim, x)}
+        }
+    }
+
+    fn map(&self, kind: Kind, f: impl Fn(f64) -> f64) -> Lanes {
+        let mut out = Lanes {kind, shape: self.shape, v: [0.0; 16]};
+        for i in 0..self.shape.len() {
+            out.v[i] = kind.round(f(self.v[i]));
+        }
+        out
+    }
+
+    fn get(&self, i: usize) -> f64 {
+        if self.shape == Shape::Scalar {self.v[0]} else {self.v[i]}
+    }
+
+    /// Applies `f` per component, scalars are broadcast to the shape of the other arguments.
+    fn zip(args: &[Lanes], kind: Kind, f: impl Fn(&[f64]) -> f64) -> Option<Lanes> {
+        let mut shape = Shape::Scalar;
+        for arg in args {
+            if arg.shape != Shape::Scalar {
+                if shape != Shape::Scalar && shape != arg.shape {
+                    return None
+                }
+                shape = arg.shape;
+            }
+        }
+        let mut out = Lanes {kind, shape, v: [0.0; 16]};
+        let mut xs = [0.0; 4];
+        for i in 0..shape.len() {
+            for (j, arg) in args.iter().enumerate() {
+                xs[j] = arg.get(i);
+            }
+            out.v[i] = kind.round(f(&xs[0..args.len()]));
+        }
+        Some(out)
+    }
+
+    fn dot(&self, other: &Lanes) -> f64 {
+        (0..self.shape.len()).map( | i | self.v[i] * other.v[i]).sum::<f64>()
+    }
+}
+
+fn binary_op(op: BinOp, left: &Value, right: &Value) -> Option<Value> {
+    match op {
+        BinOp::Eq => return Some(Value::Bool(left == right)),
+        BinOp::Ne => return Some(Value::Bool(left != right)),
+        _ => ()
+    }
+    let a = Lanes::from_value(left)?;
+    let b = Lanes::from_value(right)?;
+    let kind = if a.kind == Kind::Float || b.kind == Kind::Float {Kind::Float} else {a.kind};
+    let compare = | f: fn(f64, f64) -> bool | {
+        if a.shape != Shape::Scalar || b.shape != Shape::Scalar {
+            return None
+        }
+        Some(Value::Bool(f(a.v[0], b.v[0])))
+    };
+    match op {
+        BinOp::Lt => compare( | x, y | x < y),
+        BinOp::Le => compare( | x, y | x <= y),
+        BinOp::Gt => compare( | x, y | x > y),
+        BinOp::Ge => compare( | x, y | x >= y),
+        BinOp::Add => Lanes::zip(&[a, b], kind, | x | x[0] + x[1]).map( | l | l.to_value()),
+        BinOp::Sub => Lanes::zip(&[a, b], kind, | x | x[0] - x[1]).map( | l | l.to_value()),
+        BinOp::Div => Lanes::zip(&[a, b], kind, | x | {
+            if kind != Kind::Float && x[1] == 0.0 {0.0} else {x[0] / x[1]}
+        }).map( | l | l.to_value()),
+        BinOp::Mul => match (a.shape, b.shape) {
+            (Shape::Matrix(dim), Shape::Matrix(dim2)) if dim == dim2 => {
+                let mut out = Lanes {kind: Kind::Float, shape: a.shape, v: [0.0; 16]};
+                for col in 0..dim {
+                    for row in 0..dim {
+                        let sum = (0..dim).map( | k | a.v[k * dim + row] * b.v[col * dim + k]).sum::<f64>();
+                        out.v[col * dim + row] = Kind::Float.round(sum);
+                    }
+                }
+                Some(out.to_value())
+            }
+            (Shape::Matrix(dim), Shape::Vector(len)) if dim == len => {
+                let mut out = Lanes {kind: Kind::Float, shape: b.shape, v: [0.0; 16]};
+                for row in 0..dim {
+                    out.v[row] = Kind::Float.round((0..dim).map( | k | a.v[k * dim + row] * b.v[k]).sum::<f64>());
+                }
+                Some(out.to_value())
+            }
+            (Shape::Vector(len), Shape::Matrix(dim)) if dim == len => {
+                let mut out = Lanes {kind: Kind::Float, shape: a.shape, v: [0.0; 16]};
+                for col in 0..dim {
+                    out.v[col] = Kind::Float.round((0..dim).map( | k | a.v[k] * b.v[col * dim + k]).sum::<f64>());
+                }
+                Some(out.to_value())
+            }
+            _ => Lanes::zip(&[a, b], kind, | x | x[0] * x[1]).map( | l | l.to_value())
+        },
+        _ => None
+    }
+}
+
+fn builtin(id: LiveId, args: &[Value]) -> Option<Value> {
+    let lanes = args.iter().map(Lanes::from_value).collect::<Option<Vec<_ >> > ()?;
+    let kind = lanes.get(0)?.kind;
+    let map = | f: fn(f64) -> f64 | Some(lanes[0].map(kind, f).to_value());
+    let zip = | f: &dyn Fn(&[f64]) -> f64 | Lanes::zip(&lanes, kind, f).map( | l | l.to_value());
+    let compare = | f: fn(f64, f64) -> bool | Lanes::zip(&lanes, Kind::Bool, | x | if f(x[0], x[1]) {1.0} else {0.0}).map( | l | l.to_value());
+    let scalar = | x: f64 | Some(Value::Float(x as f32));
+    match id {
+        live_id!(abs) => map(f64::abs),
+        live_id!(acos) => map(f64::acos),
+        live_id!(asin) => map(f64::asin),
+        live_id!(atan) => if lanes.len() == 2 {zip(&| x | x[0].atan2(x[1]))} else {map(f64::atan)},
+        live_id!(ceil) => map(f64::ceil),
+        live_id!(floor) => map(f64::floor),
+        live_id!(fract) => map( | x | x - x.floor()),
+        live_id!(cos) => map(f64::cos),
+        live_id!(sin) => map(f64::sin),
+        live_id!(tan) => map(f64::tan),
+        live_id!(exp) => map(f64::exp),
+        live_id!(exp2) => map(f64::exp2),
+        live_id!(log) => map(f64::ln),
+        live_id!(log2) => map(f64::log2),
+        live_id!(sqrt) => map(f64::sqrt),
+        live_id!(inversesqrt) => map( | x | 1.0 / x.sqrt()),
+        live_id!(degrees) => map(f64::to_degrees),
+        live_id!(radians) => map(f64::to_radians),
+        live_id!(sign) => map( | x | if x > 0.0 {1.0} else if x < 0.0 {-1.0} else {0.0}),
+        live_id!(pow) => zip(&| x | x[0].powf(x[1])),
+        live_id!(mod) => zip(&| x | x[0] - x[1] * (x[0] / x[1]).floor()),
+        live_id!(min) => zip(&| x | x[0].min(x[1])),
+        live_id!(max) => zip(&| x | x[0].max(x[1])),
+        live_id!(clamp) => zip(&| x | x[0].max(x[1]).min(x[2])),
+        live_id!(mix) => zip(&| x | x[0] + (x[1] - x[0]) * x[2]),
+        live_id!(step) => zip(&| x | if x[1] < x[0] {0.0} else {1.0}),
+        live_id!(smoothstep) => zip(&| x | {
+            let t = ((x[2] - x[0]) / (x[1] - x[0])).max(0.0).min(1.0);
+            t * t * (3.0 - 2.0 * t)
+        }),
+        live_id!(length) => scalar(lanes[0].dot(&lanes[0]).sqrt()),
+        live_id!(distance) => {
+            let d = Lanes::zip(&lanes, Kind::Float, | x | x[0] - x[1])?;
+            scalar(d.dot(&d).sqrt())
+        }
+        live_id!(dot) => scalar(lanes[0].dot(&lanes[1])),
+        live_id!(normalize) => {
+            let len = lanes[0].dot(&lanes[0]).sqrt();
+            Some(lanes[0].map(Kind::Float, | x | x / len).to_value())
+        }
+        live_id!(cross) => {
+            let (a, b) = (&lanes[0].v, &lanes[1].v);
+            Some(Value::Vec(3, [
+                (a[1] * b[2] - a[2] * b[1]) as f32,
+                (a[2] * b[0] - a[0] * b[2]) as f32,
+                (a[0] * b[1] - a[1] * b[0]) as f32,
+                0.0
+            ]))
+        }
+        live_id!(faceforward) => {
+            let flip = lanes[2].dot(&lanes[1]) < 0.0;
+            Some(lanes[0].map(Kind::Float, | x | if flip {x} else {-x}).to_value())
+        }
+        live_id!(reflect) => {
+            let d = lanes[1].dot(&lanes[0]);
+            zip(&| x | x[0] - 2.0 * d * x[1])
+        }
+        live_id!(refract) => {
+            let d = lanes[1].dot(&lanes[0]);
+            let eta = lanes[2].v[0];
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            if k < 0.0 {
+                return Some(lanes[0].map(Kind::Float, | _ | 0.0).to_value())
+            }
+            Lanes::zip(&lanes[0..2], Kind::Float, | x | eta * x[0] - (eta * d + k.sqrt()) * x[1]).map( | l | l.to_value())
+        }
+        live_id!(all) => Some(Value::Bool((0..lanes[0].shape.len()).all( | i | lanes[0].v[i] != 0.0))),
+        live_id!(any) => Some(Value::Bool((0..lanes[0].shape.len()).any( | i | lanes[0].v[i] != 0.0))),
+        live_id!(not) => Some(lanes[0].map(Kind::Bool, | x | if x != 0.0 {0.0} else {1.0}).to_value()),
+        live_id!(equal) => compare( | x, y | x == y),
+        live_id!(notEqual) => compare( | x, y | x != y),
+        live_id!(lessThan) => compare( | x, y | x < y),
+        live_id!(lessThanEqual) => compare( | x, y | x <= y),
+        live_id!(greaterThan) => compare( | x, y | x > y),
+        live_id!(greaterThanEqual) => compare( | x, y | x >= y),
+        live_id!(matrixCompMult) => zip(&| x | x[0] * x[1]),
+        live_id!(transpose) => {
+            let dim = match lanes[0].shape {Shape::Matrix(dim) => dim, _ => return None};
+            let mut out = lanes[0];
+            for col in 0..dim {
+                for row in 0..dim {
+                    out.v[col * dim + row] = lanes[0].v[row * dim + col];
+                }
+            }
+            Some(out.to_value())
+        }
+        live_id!(inverse) => {
+            let dim = match lanes[0].shape {Shape::Matrix(dim) => dim, _ => return None};
+            Some(invert(&lanes[0], dim).to_value())
+        }
+        _ => None
+    }
+}
+
+fn invert(m: &Lanes, dim: usize) -> Lanes {
+    // gauss-jordan on column major storage, singular matrices give zeros
+    let mut a = [[0.0f64; 8]; 4];
+    for row in 0..dim {
+        for col in 0..dim {
+            a[row][col] = m.v[col * dim + row];
+        }
+        a[row][dim + row] = 1.0;
+    }
+    let mut out = Lanes {kind: Kind::Float, shape: m.shape, v: [0.0; 16]};
+    for col in 0..dim {
+        let pivot = (col..dim).max_by( | x, y | a[*x][col].abs().total_cmp(&a[*y][col].abs())).unwrap();
+        if a[pivot][col] == 0.0 {
+            return out
+        }
+        a.swap(col, pivot);
+        let p = a[col][col];
+        for x in 0..dim * 2 {
+            a[col][x] /= p;
+        }
+        for row in 0..dim {
+            if row != col {
+                let f = a[row][col];
+                for x in 0..dim * 2 {
+                    a[row][x] -= f * a[col][x];
+                }
+            }
+        }
+    }
+    for row in 0..dim {
+        for col in 0..dim {
+            out.v[col * dim + row] = Kind::Float.round(a[row][dim + col]);
+        }
+    }
+    out
+}
@@ -14,13 +14,13 @@ pub mod lhs_check;
 pub mod swizzle;
 pub mod util;
 pub mod generate;
+pub mod interpret;
+pub mod cross_compile;
 
-#[cfg(any(target_os = "android", target_os = "linux", target_arch = "wasm32"))]
 pub mod generate_glsl;
-#[cfg(any(target_os = "macos", target_os="ios", target_os="tvos"))]
 pub mod generate_metal;
-#[cfg(any(target_os = "windows"))]
 pub mod generate_hlsl;
+pub mod generate_wgsl;
 
 pub use makepad_live_compiler;
 pub use makepad_live_compiler::makepad_math;
@@ -41,6 +41,15 @@ pub use {
             DrawShaderConstTable,
             ValuePtr,
         },
+        interpret::{
+            DrawShaderInterpreter,
+            ShaderTextures,
+            VertexOutput,
+        },
+        cross_compile::{
+            ShaderBackend,
+            ShaderBackendError,
+        },
         shader_registry::{
             ShaderEnum,
             ShaderRegistry,
@@ -307,6 +307,7 @@ pub enum Stmt {
     For {
         span: TokenSpan,
         ident: Ident,
+        shadow: Cell<Option<ScopeSymShadow >>,
         from_expr: Expr,
         to_expr: Expr,
         step_expr: Option<Expr>,
@@ -522,6 +523,7 @@ pub enum ShaderTy {
     Void,
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -529,6 +531,9 @@ pub enum ShaderTy {
     Ivec2,
     Ivec3,
     Ivec4,
+    Uvec2,
+    Uvec3,
+    Uvec4,
     Vec2,
     Vec3,
     Vec4,
@@ -537,6 +542,9 @@ pub enum ShaderTy {
     Mat4,
     Texture2D,
     TextureOES,
+    TextureCube,
+    Texture3D,
+    Texture2DArray,
     Array {elem_ty: Rc<ShaderTy>, len: usize},
     Struct(StructPtr),
     Enum(LiveType),
@@ -552,6 +560,7 @@ pub type Ty = ShaderTy;
 pub enum TyLit {
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -559,6 +568,9 @@ pub enum TyLit {
     Ivec2,
     Ivec3,
     Ivec4,
+    Uvec2,
+    Uvec3,
+    Uvec4,
     Vec2,
     Vec3,
     Vec4,
@@ -567,6 +579,9 @@ pub enum TyLit {
     Mat4,
     Texture2D,
     TextureOES,
+    TextureCube,
+    Texture3D,
+    Texture2DArray,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -858,6 +873,10 @@ impl DrawShaderDef {
 }
 
 impl BinOp {
+    pub fn is_assign(self) -> bool {
+        matches!(self, BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign)
+    }
+    
     pub fn from_assign_op(token: LiveToken) -> Option<BinOp> {
         match token {
             LiveToken::Punct(live_id!( =)) => Some(BinOp::Assign),
@@ -977,6 +996,7 @@ impl Ty {
             Ty::Void => None,
             Ty::Bool => Some(TyLit::Bool),
             Ty::Int => Some(TyLit::Int),
+            Ty::Uint => Some(TyLit::Uint),
             Ty::Float => Some(TyLit::Float),
             Ty::Bvec2 => Some(TyLit::Bvec2),
             Ty::Bvec3 => Some(TyLit::Bvec3),
@@ -984,6 +1004,9 @@ impl Ty {
             Ty::Ivec2 => Some(TyLit::Ivec2),
             Ty::Ivec3 => Some(TyLit::Ivec3),
             Ty::Ivec4 => Some(TyLit::Ivec4),
+            Ty::Uvec2 => Some(TyLit::Uvec2),
+            Ty::Uvec3 => Some(TyLit::Uvec3),
+            Ty::Uvec4 => Some(TyLit::Uvec4),
             Ty::Vec2 => Some(TyLit::Vec2),
             Ty::Vec3 => Some(TyLit::Vec3),
             Ty::Vec4 => Some(TyLit::Vec4),
@@ -992,6 +1015,9 @@ impl Ty {
             Ty::Mat4 => Some(TyLit::Mat4),
             Ty::Texture2D => Some(TyLit::Bool),
             Ty::TextureOES => Some(TyLit::Bool),
+            Ty::TextureCube => Some(TyLit::Bool),
+            Ty::Texture3D => Some(TyLit::Bool),
+            Ty::Texture2DArray => Some(TyLit::Bool),
             Ty::Array {..} => None,
             Ty::Struct(_) => None,
             Ty::Enum(_) => None,
@@ -1003,7 +1029,7 @@ impl Ty {
     
     pub fn is_scalar(&self) -> bool {
         match self {
-            Ty::Bool | Ty::Int | Ty::Float => true,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => true,
             _ => false,
         }
     }
@@ -1016,6 +1042,9 @@ impl Ty {
                 | Ty::Ivec2
                 | Ty::Ivec3
                 | Ty::Ivec4
+                | Ty::Uvec2
+                | Ty::Uvec3
+                | Ty::Uvec4
                 | Ty::Vec2
                 | Ty::Vec3
                 | Ty::Vec4 => true,
@@ -1033,14 +1062,17 @@ impl Ty {
     pub fn slots(&self) -> usize {
         match self {
             Ty::Void => 0,
-            Ty::Bool | Ty::Int | Ty::Float => 1,
-            Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 => 2,
-            Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 => 3,
-            Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 | Ty::Mat2 => 4,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => 1,
+            Ty::Bvec2 | Ty::Ivec2 | Ty::Uvec2 | Ty::Vec2 => 2,
+            Ty::Bvec3 | Ty::Ivec3 | Ty::Uvec3 | Ty::Vec3 => 3,
+            Ty::Bvec4 | Ty::Ivec4 | Ty::Uvec4 | Ty::Vec4 | Ty::Mat2 => 4,
             Ty::Mat3 => 9,
             Ty::Mat4 => 16,
             Ty::Texture2D {..} => panic!(),
             Ty::TextureOES {..} => panic!(),
+            Ty::TextureCube {..} => panic!(),
+            Ty::Texture3D {..} => panic!(),
+            Ty::Texture2DArray {..} => panic!(),
             Ty::Array {elem_ty, len} => elem_ty.slots() * len,
             Ty::Enum(_) => 1,
             Ty::Struct(_) => panic!(),
@@ -1058,6 +1090,7 @@ impl Ty {
                 Ty::Void => panic!(),
                 Ty::Bool => TyExprKind::Lit {ty_lit: TyLit::Bool},
                 Ty::Int => TyExprKind::Lit {ty_lit: TyLit::Int},
+                Ty::Uint => TyExprKind::Lit {ty_lit: TyLit::Uint},
                 Ty::Float => TyExprKind::Lit {ty_lit: TyLit::Float},
                 Ty::Bvec2 => TyExprKind::Lit {ty_lit: TyLit::Bvec2},
                 Ty::Bvec3 => TyExprKind::Lit {ty_lit: TyLit::Bvec3},
@@ -1065,6 +1098,9 @@ impl Ty {
                 Ty::Ivec2 => TyExprKind::Lit {ty_lit: TyLit::Ivec2},
                 Ty::Ivec3 => TyExprKind::Lit {ty_lit: TyLit::Ivec3},
                 Ty::Ivec4 => TyExprKind::Lit {ty_lit: TyLit::Ivec4},
+                Ty::Uvec2 => TyExprKind::Lit {ty_lit: TyLit::Uvec2},
+                Ty::Uvec3 => TyExprKind::Lit {ty_lit: TyLit::Uvec3},
+                Ty::Uvec4 => TyExprKind::Lit {ty_lit: TyLit::Uvec4},
                 Ty::Vec2 => TyExprKind::Lit {ty_lit: TyLit::Vec2},
                 Ty::Vec3 => TyExprKind::Lit {ty_lit: TyLit::Vec3},
                 Ty::Vec4 => TyExprKind::Lit {ty_lit: TyLit::Vec4},
@@ -1073,6 +1109,9 @@ impl Ty {
                 Ty::Mat4 => TyExprKind::Lit {ty_lit: TyLit::Mat4},
                 Ty::Texture2D => TyExprKind::Lit {ty_lit: TyLit::Texture2D},
                 Ty::TextureOES => TyExprKind::Lit {ty_lit: TyLit::TextureOES},
+                Ty::TextureCube => TyExprKind::Lit {ty_lit: TyLit::TextureCube},
+                Ty::Texture3D => TyExprKind::Lit {ty_lit: TyLit::Texture3D},
+                Ty::Texture2DArray => TyExprKind::Lit {ty_lit: TyLit::Texture2DArray},
                 Ty::Array {elem_ty, len} => {
                     TyExprKind::Array {
                         elem_ty_expr: Box::new(elem_ty.to_ty_expr()),
@@ -1129,12 +1168,16 @@ impl Ty {
             LiveValue::Id(id) => match id {
                 live_id!(bool) => Self::Bool,
                 live_id!(int) => Self::Int,
+                live_id!(uint) => Self::Uint,
                 live_id!(float) => Self::Float,
                 live_id!(vec2) => Self::Vec2,
                 live_id!(vec3) => Self::Vec3,
                 live_id!(vec4) => Self::Vec4,
                 live_id!(texture2d) => Self::Texture2D,
                 live_id!(textureOES) => Self::TextureOES,
+                live_id!(textureCube) => Self::TextureCube,
+                live_id!(texture3d) => Self::Texture3D,
+                live_id!(texture2dArray) => Self::Texture2DArray,
                 _ => {
                     return Err(LiveError {
                         origin: live_error_origin!(),
@@ -1166,6 +1209,7 @@ impl fmt::Display for Ty {
             Ty::Void => write!(f, "void"),
             Ty::Bool => write!(f, "bool"),
             Ty::Int => write!(f, "int"),
+            Ty::Uint => write!(f, "uint"),
             Ty::Float => write!(f, "float"),
             Ty::Bvec2 => write!(f, "bvec2"),
             Ty::Bvec3 => write!(f, "bvec3"),
@@ -1173,6 +1217,9 @@ impl fmt::Display for Ty {
             Ty::Ivec2 => write!(f, "ivec2"),
             Ty::Ivec3 => write!(f, "ivec3"),
             Ty::Ivec4 => write!(f, "ivec4"),
+            Ty::Uvec2 => write!(f, "uvec2"),
+            Ty::Uvec3 => write!(f, "uvec3"),
+            Ty::Uvec4 => write!(f, "uvec4"),
             Ty::Vec2 => write!(f, "vec2"),
             Ty::Vec3 => write!(f, "vec3"),
             Ty::Vec4 => write!(f, "vec4"),
@@ -1181,6 +1228,9 @@ impl fmt::Display for Ty {
             Ty::Mat4 => write!(f, "mat4"),
             Ty::Texture2D => write!(f, "texture2D"),
             Ty::TextureOES => write!(f, "textureOES"),
+            Ty::TextureCube => write!(f, "textureCube"),
+            Ty::Texture3D => write!(f, "texture3D"),
+            Ty::Texture2DArray => write!(f, "texture2DArray"),
             Ty::Array {elem_ty, len} => write!(f, "{}[{}]", elem_ty, len),
             Ty::Struct(struct_ptr) => write!(f, "Struct:{:?}", struct_ptr),
             Ty::DrawShader(shader_ptr) => write!(f, "DrawShader:{:?}", shader_ptr),
@@ -1203,13 +1253,20 @@ impl TyLit {
             live_id!(float) => Some(TyLit::Float),
             live_id!(bool) => Some(TyLit::Bool),
             live_id!(int) => Some(TyLit::Int),
+            live_id!(uint) => Some(TyLit::Uint),
             live_id!(bvec2) => Some(TyLit::Bvec2),
             live_id!(bvec3) => Some(TyLit::Bvec3),
             live_id!(bvec4) => Some(TyLit::Bvec4),
-            live_id!(ivec2) => Some(TyLit::Ivec4),
-            live_id!(ivec3) => Some(TyLit::Ivec4),
+            live_id!(ivec2) => Some(TyLit::Ivec2),
+            live_id!(ivec3) => Some(TyLit::Ivec3),
             live_id!(ivec4) => Some(TyLit::Ivec4),
+            live_id!(uvec2) => Some(TyLit::Uvec2),
+            live_id!(uvec3) => Some(TyLit::Uvec3),
+            live_id!(uvec4) => Some(TyLit::Uvec4),
             live_id!(texture2D) => Some(TyLit::Texture2D),
+            live_id!(textureCube) => Some(TyLit::TextureCube),
+            live_id!(texture3D) => Some(TyLit::Texture3D),
+            live_id!(texture2DArray) => Some(TyLit::Texture2DArray),
             _ => None
         }
     }
@@ -1218,6 +1275,7 @@ impl TyLit {
         match self {
             TyLit::Bool => Ty::Bool,
             TyLit::Int => Ty::Int,
+            TyLit::Uint => Ty::Uint,
             TyLit::Float => Ty::Float,
             TyLit::Bvec2 => Ty::Bvec2,
             TyLit::Bvec3 => Ty::Bvec3,
@@ -1225,6 +1283,9 @@ impl TyLit {
             TyLit::Ivec2 => Ty::Ivec2,
             TyLit::Ivec3 => Ty::Ivec3,
             TyLit::Ivec4 => Ty::Ivec4,
+            TyLit::Uvec2 => Ty::Uvec2,
+            TyLit::Uvec3 => Ty::Uvec3,
+            TyLit::Uvec4 => Ty::Uvec4,
             TyLit::Vec2 => Ty::Vec2,
             TyLit::Vec3 => Ty::Vec3,
             TyLit::Vec4 => Ty::Vec4,
@@ -1233,6 +1294,9 @@ impl TyLit {
             TyLit::Mat4 => Ty::Mat4,
             TyLit::Texture2D => Ty::Texture2D,
             TyLit::TextureOES => Ty::TextureOES,
+            TyLit::TextureCube => Ty::TextureCube,
+            TyLit::Texture3D => Ty::Texture3D,
+            TyLit::Texture2DArray => Ty::Texture2DArray,
         }
     }
     
@@ -1246,6 +1310,7 @@ impl fmt::Display for TyLit {
             match self {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -1253,6 +1318,9 @@ impl fmt::Display for TyLit {
                 TyLit::Ivec2 => "ivec2",
                 TyLit::Ivec3 => "ivec3",
                 TyLit::Ivec4 => "ivec4",
+                TyLit::Uvec2 => "uvec2",
+                TyLit::Uvec3 => "uvec3",
+                TyLit::Uvec4 => "uvec4",
                 TyLit::Vec2 => "vec2",
                 TyLit::Vec3 => "vec3",
                 TyLit::Vec4 => "vec4",
@@ -1261,6 +1329,9 @@ impl fmt::Display for TyLit {
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "texture2D",
                 TyLit::TextureOES => "textureOES",
+                TyLit::TextureCube => "textureCube",
+                TyLit::Texture3D => "texture3D",
+                TyLit::Texture2DArray => "texture2DArray",
             }
         )
     }
@@ -640,6 +640,7 @@ impl<'a> ShaderParser<'a> {
         Ok(span.end(self, | span | Stmt::For {
             span,
             ident,
+            shadow: Cell::new(None),
             from_expr,
             to_expr,
             step_expr,
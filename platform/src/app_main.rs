@@ -28,6 +28,9 @@ macro_rules!app_main {
             cx.borrow_mut().init_websockets(std::option_env!("MAKEPAD_STUDIO_HTTP").unwrap_or(""));
             //cx.borrow_mut().init_websockets("");
             live_design(&mut *cx.borrow_mut());
+            if cx.borrow_mut().serve_live_lsp(){
+                return
+            }
             cx.borrow_mut().init_cx_os();
             Cx::event_loop(cx);
         }
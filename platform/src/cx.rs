@@ -13,7 +13,10 @@ use {
     crate::{
         makepad_live_compiler::{
             LiveRegistry,
-            LiveFileChange
+            LiveFileChange,
+            LiveModuleId,
+            LiveId,
+            LiveValue,
         },
         makepad_shader_compiler::ShaderRegistry,
         draw_shader::CxDrawShaders,
@@ -37,6 +40,7 @@ use {
         window::CxWindowPool,
         draw_list::CxDrawListPool,
         web_socket::WebSocket,
+        file_dialogs::FileDialogResultEvent,
         pass::CxPassPool,
         texture::{CxTexturePool,TextureFormat,Texture},
         geometry::{
@@ -80,6 +84,7 @@ pub struct Cx {
     pub keyboard: CxKeyboard,
     pub fingers: CxFingers,
     pub (crate) ime_area: Area,
+    pub (crate) rtl: bool,
     pub (crate) drag_drop: CxDragDrop,
     
     pub (crate) platform_ops: Vec<CxOsOp>,
@@ -88,6 +93,8 @@ pub struct Cx {
     
     pub (crate) new_actions: ActionsBuf,
     
+    pub (crate) file_dialog_results: Vec<FileDialogResultEvent>,
+    
     pub (crate) dependencies: HashMap<String, CxDependency>,
     
     pub (crate) triggers: HashMap<Area, Vec<Trigger >>,
@@ -96,6 +103,7 @@ pub struct Cx {
 
     pub (crate) live_file_change_receiver: std::sync::mpsc::Receiver<Vec<LiveFileChange>>,
     pub (crate) live_file_change_sender: std::sync::mpsc::Sender<Vec<LiveFileChange >>,
+    pub (crate) live_token_changes: Vec<(LiveModuleId, Vec<(LiveId, LiveValue)>)>,
 
     pub shader_registry: ShaderRegistry,
     
@@ -156,7 +164,8 @@ pub enum OsType {
     Android(AndroidParams),
     LinuxWindow (LinuxWindowParams),
     LinuxDirect,
-    Web(WebParams)
+    Web(WebParams),
+    Headless
 }
 
 #[derive(Default)]
@@ -227,6 +236,7 @@ impl Cx {
             
             new_draw_event: Default::default(),
             new_actions: Default::default(),
+            file_dialog_results: Default::default(),
             
             redraw_id: 1,
             event_id: 1,
@@ -238,6 +248,7 @@ impl Cx {
             fingers: Default::default(),
             drag_drop: Default::default(),
             ime_area: Default::default(),
+            rtl: false,
             platform_ops: Default::default(),
             studio_web_socket: None,
             studio_http: "".to_string(),
@@ -251,6 +262,7 @@ impl Cx {
             
             live_file_change_receiver: recv,
             live_file_change_sender: send,
+            live_token_changes: Vec::new(),
             
             shader_registry: ShaderRegistry::new(),
             
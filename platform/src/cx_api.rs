@@ -1,4 +1,4 @@
-use crate::file_dialogs::FileDialog;
+use crate::file_dialogs::{FileDialog, FileDialogKind};
 
 use {
     crate::{
@@ -75,6 +75,9 @@ pub enum CxOsOp {
         request_id: LiveId,
         request: HttpRequest,
     },
+    CancelHttpRequest {
+        request_id: LiveId,
+    },
 
     PrepareVideoPlayback(LiveId, VideoSource, u32, bool, bool),
     BeginVideoPlayback(LiveId),
@@ -367,6 +370,18 @@ impl Cx {
     pub fn redraw_all(&mut self) {
         self.new_draw_event.redraw_all = true;
     }
+    
+    // lays the whole app out right to left, apart from the layouts that set their own direction
+    pub fn set_rtl(&mut self, rtl: bool) {
+        if self.rtl != rtl {
+            self.rtl = rtl;
+            self.redraw_all();
+        }
+    }
+    
+    pub fn is_rtl(&self) -> bool {
+        self.rtl
+    }
 
     pub fn redraw_area(&mut self, area: Area) {
         if let Some(draw_list_id) = area.draw_list_id() {
@@ -494,6 +509,15 @@ impl Cx {
             request,
         });
     }
+
+    /// Cancels a request started with `http_request`, no response events are sent for it afterwards.
+    /// 
+    /// Only implemented on Linux, other platforms log that cancelling is not supported and let the request finish.
+    pub fn cancel_http_request(&mut self, request_id: LiveId) {
+        self.platform_ops.push(CxOsOp::CancelHttpRequest {
+            request_id,
+        });
+    }
     /*
         pub fn web_socket_open(&mut self, request_id: LiveId, request: HttpRequest) {
             self.platform_ops.push(CxOsOp::WebSocketOpen{
@@ -574,6 +598,15 @@ impl Cx {
         self.platform_ops.push(CxOsOp::SelectFolderDialog(FileDialog::new()));
 
     }
+
+    pub fn open_file_dialog(&mut self, kind: FileDialogKind, dialog: FileDialog) {
+        self.platform_ops.push(match kind {
+            FileDialogKind::SaveFile => CxOsOp::SaveFileDialog(dialog),
+            FileDialogKind::SelectFile => CxOsOp::SelectFileDialog(dialog),
+            FileDialogKind::SaveFolder => CxOsOp::SaveFolderDialog(dialog),
+            FileDialogKind::SelectFolder => CxOsOp::SelectFolderDialog(dialog),
+        });
+    }
 }
 
 #[macro_export]
@@ -69,6 +69,9 @@ pub struct CxDrawShaders {
     pub fingerprints: Vec<DrawShaderFingerprint>,
     pub error_set: HashSet<DrawShaderPtr>,
     pub error_fingerprints: Vec<Vec<LiveNode >>,
+    /// Backends every compiled draw shader is also generated for, see `Cx::shader_check_requested`
+    pub check_backends: Vec<ShaderBackend>,
+    pub backend_errors: Vec<(DrawShaderPtr, ShaderBackendError)>,
 }
 
 impl CxDrawShaders{
@@ -77,6 +80,7 @@ impl CxDrawShaders{
         self.fingerprints.clear();
         self.error_set.clear();
         self.error_fingerprints.clear();
+        self.backend_errors.clear();
     }
 }
 
@@ -89,6 +93,28 @@ impl Cx {
         self.draw_shaders.fingerprints.clear();
         self.draw_shaders.error_set.clear();
         self.draw_shaders.error_fingerprints.clear();
+        self.draw_shaders.backend_errors.clear();
+    }
+    
+    /// The backends passed with `--check-shaders=glsl,metal,hlsl,wgsl`, or all of them for
+    /// a plain `--check-shaders`. The app then runs headless, generates every draw shader
+    /// it compiles for these backends, reports the errors and exits.
+    pub fn shader_check_requested() -> Option<Vec<ShaderBackend >> {
+        for arg in std::env::args() {
+            if arg == "--check-shaders" {
+                return Some(ShaderBackend::ALL.to_vec())
+            }
+            if let Some(names) = arg.strip_prefix("--check-shaders=") {
+                return Some(names.split(',').filter_map( | name | {
+                    let backend = ShaderBackend::from_name(name.trim());
+                    if backend.is_none() {
+                        crate::error!("Unknown shader backend {} for --check-shaders", name);
+                    }
+                    backend
+                }).collect())
+            }
+        }
+        None
     }
 }
 
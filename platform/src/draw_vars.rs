@@ -10,7 +10,10 @@ use {
             LiveModuleId,
             LiveType,
             LiveNode,
-            LiveNodeSliceApi
+            LiveNodeSliceApi,
+            LiveError,
+            LiveErrorSpan,
+            LiveFileError
         },
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         makepad_shader_compiler::*,
@@ -157,6 +160,25 @@ impl DrawVars {
         }
     }
     
+    fn log_shader_error(err: LiveFileError, is_shader_check: bool) {
+        if std::env::args().find(|v| v == "--message-format=json").is_some(){
+            // a shader check exits right after, so it can't wait for the studio websocket
+            let log_with_level = if is_shader_check {crate::log::log_json_with_level} else {crate::log::log_with_level};
+            log_with_level(
+                &err.file,
+                err.span.start.line,
+                err.span.start.column,
+                err.span.end.line,
+                err.span.end.column,
+                err.message,
+                crate::log::LogLevel::Error
+            );
+        }
+        else{
+            log!("Error {}", err);
+        }
+    }
+    
     pub fn init_shader(&mut self, cx: &mut Cx, apply: &mut Apply, draw_shader_ptr: DrawShaderPtr, geometry_fields: &dyn GeometryFields) {
         self.draw_shader = None;
         
@@ -208,7 +230,8 @@ impl DrawVars {
             let live_registry_cp = cx.live_registry.clone();
             let live_registry = live_registry_cp.borrow();
             
-            let result = cx.shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | live_registry, shader_registry, span, draw_shader_query, live_type, draw_shader_def | {
+            let check_backends = cx.draw_shaders.check_backends.clone();
+            let result = cx.shader_registry.analyse_and_check_draw_shader(&live_registry, draw_shader_ptr, &check_backends, | live_registry, shader_registry, span, draw_shader_query, live_type, draw_shader_def | {
                 match draw_shader_query {
                     DrawShaderQuery::DrawShader => {
                         fn recur_expand(
@@ -280,22 +303,25 @@ impl DrawVars {
                     cx.draw_shaders.error_fingerprints.push(fingerprint);
                     // ok so. lets get the source for this file id
                     let err = live_registry.live_error_to_live_file_error(e);
-                    if std::env::args().find(|v| v == "--message-format=json").is_some(){
-                        crate::log::log_with_level(
-                            &err.file,
-                            err.span.start.line,
-                            err.span.start.column,
-                            err.span.end.line,
-                            err.span.end.column,
-                            err.message,
-                            crate::log::LogLevel::Error
-                        );
-                    }
-                    else{
-                        log!("Error {}", err);
-                    }
+                    Self::log_shader_error(err, !check_backends.is_empty());
                 }
-                Ok(()) => {
+                Ok(backend_errors) => {
+                    // the shader is fine for this platform but can't be generated for another one
+                    for backend_error in backend_errors {
+                        // errors without a place in the source point at the shader class
+                        let span = match &backend_error.error.span {
+                            LiveErrorSpan::Token(span) if span.token_id.file_id().is_some() => (*span).into(),
+                            LiveErrorSpan::Text(span) => (*span).into(),
+                            _ => live_registry.ptr_to_node(draw_shader_ptr.0).origin.token_id().unwrap().into()
+                        };
+                        let err = live_registry.live_error_to_live_file_error(LiveError {
+                            origin: backend_error.error.origin.clone(),
+                            span,
+                            message: format!("Draw shader fails on {}: {}", backend_error.backend.name(), backend_error.error.message)
+                        });
+                        Self::log_shader_error(err, true);
+                        cx.draw_shaders.backend_errors.push((draw_shader_ptr, backend_error));
+                    }
                     // OK! SO the shader parsed
                     let draw_shader_id = cx.draw_shaders.shaders.len();
                     
@@ -23,6 +23,7 @@ use {
         midi::MidiPortsEvent,
         video::VideoInputsEvent,
         draw_list::DrawListId,
+        file_dialogs::{FileDialogRequestEvent, FileDialogResultEvent},
     },
 };
 
@@ -175,6 +176,8 @@ pub enum Event {
     TextureHandleReady(TextureHandleReadyEvent),
 
     BackPressed,
+    FileDialogRequest(FileDialogRequestEvent),
+    FileDialogResult(FileDialogResultEvent),
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
 }
@@ -249,6 +252,8 @@ impl Event{
 
             #[cfg(target_arch = "wasm32")]
             51=>"ToWasmMsg",
+            52=>"FileDialogRequest",
+            53=>"FileDialogResult",
             _=>panic!()
         }
     }
@@ -318,6 +323,8 @@ impl Event{
 
             #[cfg(target_arch = "wasm32")]
             Self::ToWasmMsg(_)=>51,
+            Self::FileDialogRequest(_)=>52,
+            Self::FileDialogResult(_)=>53,
         }
     }
 }
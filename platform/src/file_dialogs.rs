@@ -1,9 +1,15 @@
 // mildly stripped down version of native_dialog_rs dialog interface.
-use std::path::{PathBuf};
+use {
+    std::{cell::Cell, path::PathBuf},
+    crate::{
+        cx::Cx,
+        event::Event,
+    },
+};
 
 
 /// Represents a set of file extensions and their description.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Filter {
     pub description: String,
     pub extensions: Vec<String>,
@@ -11,7 +17,7 @@ pub struct Filter {
 
 /// Builds and shows file dialogs.
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FileDialog {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
@@ -93,3 +99,66 @@ impl Default for FileDialog {
     }
 }
 
+/// Which of the four dialogs is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileDialogKind {
+    SaveFile,
+    SelectFile,
+    SaveFolder,
+    SelectFolder,
+}
+
+impl FileDialogKind {
+    pub fn is_save(&self) -> bool {
+        matches!(self, Self::SaveFile | Self::SaveFolder)
+    }
+
+    pub fn is_folder(&self) -> bool {
+        matches!(self, Self::SaveFolder | Self::SelectFolder)
+    }
+}
+
+/// Sent to the app when the platform has no native dialog, so a dialog drawn by the app can be
+/// shown instead. The choice is reported back with `Cx::post_file_dialog_result`. Whoever
+/// shows the dialog sets `handled`, otherwise the request is answered as cancelled.
+#[derive(Debug)]
+pub struct FileDialogRequestEvent {
+    pub kind: FileDialogKind,
+    pub dialog: FileDialog,
+    pub handled: Cell<bool>,
+}
+
+/// The outcome of a file dialog, where a path of `None` means it was cancelled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileDialogResultEvent {
+    pub kind: FileDialogKind,
+    pub path: Option<PathBuf>,
+}
+
+impl Cx {
+    /// Reports the choice of a file dialog that isn't native, which arrives as
+    /// `Event::FileDialogResult` once the current event is handled.
+    pub fn post_file_dialog_result(&mut self, result: FileDialogResultEvent) {
+        self.file_dialog_results.push(result);
+    }
+
+    /// Asks the app for a dialog of its own, and cancels the dialog when nothing showed one,
+    /// so the app always hears back about the dialog it opened.
+    pub (crate) fn request_file_dialog(&mut self, kind: FileDialogKind, dialog: FileDialog) {
+        let event = Event::FileDialogRequest(FileDialogRequestEvent {kind, dialog, handled: Cell::new(false)});
+        self.call_event_handler(&event);
+        if let Event::FileDialogRequest(e) = &event {
+            if !e.handled.get() {
+                self.post_file_dialog_result(FileDialogResultEvent {kind, path: None});
+                self.handle_file_dialog_results();
+            }
+        }
+    }
+
+    pub (crate) fn handle_file_dialog_results(&mut self) {
+        for result in std::mem::take(&mut self.file_dialog_results) {
+            self.call_event_handler(&Event::FileDialogResult(result));
+        }
+    }
+}
+
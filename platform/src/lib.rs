@@ -154,6 +154,12 @@ pub use {
         thread::*,
         video::*,
         web_socket::{WebSocket,WebSocketMessage},
+        file_dialogs::{
+            FileDialog,
+            FileDialogKind,
+            FileDialogRequestEvent,
+            FileDialogResultEvent,
+        },
         event::{
             VirtualKeyboardEvent,
             HttpRequest,
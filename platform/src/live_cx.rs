@@ -255,10 +255,14 @@ impl Cx {
         while let Ok(changes) = self.live_file_change_receiver.try_recv(){
             all_changes.extend(changes);
         }
-        if all_changes.len()>0{
+        let token_changes = std::mem::take(&mut self.live_token_changes);
+        if all_changes.len()>0 || token_changes.len()>0{
             let mut live_registry = self.live_registry.borrow_mut();
             let mut errs = Vec::new();
             live_registry.process_file_changes(all_changes, &mut errs);
+            for (module_id, tokens) in token_changes{
+                live_registry.set_live_tokens(module_id, tokens, &mut errs);
+            }
             for err in errs {
                 
                 // alright we need to output the correct error
@@ -285,6 +289,32 @@ impl Cx {
         }
     }
     
+    /// Swaps the values of the top level definitions in the live_design!{} of `module_id`, like the
+    /// `THEME_*` tokens of a theme. The change goes through the same path as a live edit of the
+    /// file, so on the next event loop cycle all widgets re-apply and redraw.
+    /// Each call replaces the previous overrides of the module, an empty list restores its source.
+    pub fn set_live_tokens(&mut self, module_id: LiveModuleId, tokens: Vec<(LiveId, LiveValue)>){
+        self.live_token_changes.retain(|(id, _)| *id != module_id);
+        self.live_token_changes.push((module_id, tokens));
+    }
+    
+    /// When the app was built with `MAKEPAD=live_lsp` and started with `--live-lsp` this serves the
+    /// language server protocol for its live_design!{} blocks on stdin/stdout instead of running
+    /// the app, and returns true. Other builds ignore the flag.
+    pub fn serve_live_lsp(&mut self) -> bool {
+        if !cfg!(live_lsp) || !std::env::args().any(|arg| arg == "--live-lsp") {
+            return false
+        }
+        let mut live_registry = self.live_registry.borrow_mut();
+        let mut errs = Vec::new();
+        live_registry.expand_all_documents(&mut errs);
+        let stdin = std::io::stdin();
+        if let Err(err) = live_registry.serve_lsp(&mut stdin.lock(), &mut std::io::stdout()) {
+            eprintln!("Live LSP stopped: {}", err);
+        }
+        true
+    }
+    
     // ok so now what. now we should run the expansion
     pub fn live_expand(&mut self) {
         let mut errs = Vec::new();
@@ -105,6 +105,41 @@ pub fn log_with_level(file_name:&str, line_start:u32, column_start:u32, line_end
 }
 
 
+/// Prints a log message as a line of cargo's `--message-format=json` output, so tools that
+/// parse cargo's output (like Studio) pick it up when the app can't reach Studio's websocket.
+pub fn log_json_with_level(file_name:&str, line_start:u32, column_start:u32, line_end:u32, column_end:u32, message:String, level:LogLevel){
+    fn escape(s: &str) -> String {
+        let mut out = String::new();
+        for c in s.chars() {
+            match c {
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                '\\' => out.push_str("\\\\"),
+                '"' => out.push_str("\\\""),
+                _ => out.push(c)
+            }
+        }
+        out
+    }
+    let level = match level {
+        LogLevel::Warning => "warning",
+        LogLevel::Error => "error",
+        LogLevel::Panic => "panic",
+        LogLevel::Log | LogLevel::Wait => "log",
+    };
+    println!(
+        "{{\"reason\":\"makepad-error-log\",\"message\":{{\"message\":\"{}\",\"level\":\"{}\",\"spans\":[{{\"file_name\":\"{}\",\"byte_start\":0,\"byte_end\":0,\"line_start\":{},\"line_end\":{},\"column_start\":{},\"column_end\":{},\"is_primary\":true,\"text\":[]}}],\"children\":[]}}}}",
+        escape(&message),
+        level,
+        escape(file_name),
+        line_start + 1,
+        line_end + 1,
+        column_start + 1,
+        column_end + 1
+    );
+}
+
 use std::time::Instant;
 
 pub fn profile_start() -> Instant {
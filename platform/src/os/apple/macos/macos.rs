@@ -138,6 +138,9 @@ const KEEP_ALIVE_COUNT: usize = 5;
 impl Cx {
     
     pub fn event_loop(cx: Rc<RefCell<Cx >>) {
+        if Cx::headless_requested() {
+            return Cx::headless_event_loop(cx);
+        }
         
         cx.borrow_mut().self_ref = Some(cx.clone());
         cx.borrow_mut().os_type = OsType::Macos;
@@ -515,6 +518,9 @@ impl Cx {
                 CxOsOp::HttpRequest {request_id, request} => {
                     make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
+                CxOsOp::CancelHttpRequest {request_id: _} => {
+                    crate::log!("Cancelling http requests not supported yet");
+                },
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 },
@@ -30,6 +30,7 @@ use {
             Texture,
             TexturePixel,
             TextureFormat,
+            TextureDim,
         },
     },
     std::time::{Instant},
@@ -518,11 +519,17 @@ impl Cx {
             if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
-                let gen = generate_metal::generate_shader(
+                let gen = match generate_metal::generate_shader(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
-                );
+                ) {
+                    Ok(gen) => gen,
+                    Err(err) => {
+                        crate::log!("Error {}", self.live_registry.borrow().live_error_to_live_file_error(err));
+                        continue
+                    }
+                };
                 
                 if cx_shader.mapping.flags.debug {
                     crate::log!("{}", gen.mtlsl);
@@ -843,8 +850,15 @@ impl CxTexture {
                 msg_send![class!(MTLTextureDescriptor), new]
             }).unwrap());
                         
-            let _: () = unsafe {msg_send![descriptor.as_id(), setTextureType: MTLTextureType::D2]};
-            let _: () = unsafe {msg_send![descriptor.as_id(), setDepth: 1u64]};
+            let (texture_type, depth, array_length) = match alloc.dim{
+                TextureDim::D2 => (MTLTextureType::D2, 1, 1),
+                TextureDim::Cube => (MTLTextureType::Cube, 1, 1),
+                TextureDim::D3{depth} => (MTLTextureType::D3, depth, 1),
+                TextureDim::D2Array{layers} => (MTLTextureType::D2Array, 1, layers),
+            };
+            let _: () = unsafe {msg_send![descriptor.as_id(), setTextureType: texture_type]};
+            let _: () = unsafe {msg_send![descriptor.as_id(), setDepth: depth as u64]};
+            let _: () = unsafe {msg_send![descriptor.as_id(), setArrayLength: array_length as u64]};
             let _: () = unsafe {msg_send![descriptor.as_id(), setStorageMode: MTLStorageMode::Shared]};
             let _: () = unsafe {msg_send![descriptor.as_id(), setUsage: MTLTextureUsage::ShaderRead]};
             let _: () = unsafe {msg_send![descriptor.as_id(), setWidth: alloc.width as u64]};
@@ -868,6 +882,24 @@ impl CxTexture {
                     bytesPerRow: (width as u64) * bpp
                 ]};
             }
+            // cube faces and array layers are separate slices of the texture
+            fn update_slices(texture:&Option<RcObjcId>, width: usize, height: usize, slices: usize, data: &[u32]){
+                let region = MTLRegion {
+                    origin: MTLOrigin {x: 0, y: 0, z: 0},
+                    size: MTLSize {width: width as u64, height: height as u64, depth: 1}
+                };
+                for slice in 0..slices{
+                    let () = unsafe {msg_send![
+                        texture.as_ref().unwrap().as_id(),
+                        replaceRegion: region
+                        mipmapLevel: 0
+                        slice: slice as u64
+                        withBytes: data[slice * width * height..].as_ptr() as *const std::ffi::c_void
+                        bytesPerRow: (width as u64) * 4
+                        bytesPerImage: (width * height) as u64 * 4
+                    ]};
+                }
+            }
             
             match &self.format{
                 TextureFormat::VecBGRAu8_32{width, height, data}=>{
@@ -885,6 +917,27 @@ impl CxTexture {
                 TextureFormat::VecRf32{width, height, data}=>{
                     update_data(&self.os.texture, *width, *height, 4,  data.as_ptr() as *const std::ffi::c_void);
                 }
+                TextureFormat::VecCubeBGRAu8_32{width, height, data}=>{
+                    update_slices(&self.os.texture, *width, *height, 6, data);
+                }
+                TextureFormat::VecArrayBGRAu8_32{width, height, layers, data}=>{
+                    update_slices(&self.os.texture, *width, *height, *layers, data);
+                }
+                TextureFormat::Vec3dBGRAu8_32{width, height, depth, data}=>{
+                    let region = MTLRegion {
+                        origin: MTLOrigin {x: 0, y: 0, z: 0},
+                        size: MTLSize {width: *width as u64, height: *height as u64, depth: *depth as u64}
+                    };
+                    let () = unsafe {msg_send![
+                        self.os.texture.as_ref().unwrap().as_id(),
+                        replaceRegion: region
+                        mipmapLevel: 0
+                        slice: 0u64
+                        withBytes: data.as_ptr() as *const std::ffi::c_void
+                        bytesPerRow: (*width as u64) * 4
+                        bytesPerImage: (*width * *height) as u64 * 4
+                    ]};
+                }
                 _=>panic!()
             }
         }
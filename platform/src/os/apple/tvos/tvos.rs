@@ -256,6 +256,9 @@ impl Cx {
                 CxOsOp::HttpRequest{request_id, request} => {
                     make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
+                CxOsOp::CancelHttpRequest {request_id: _} => {
+                    crate::log!("Cancelling http requests not supported yet");
+                },
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 }
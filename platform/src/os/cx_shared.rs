@@ -180,6 +180,7 @@ impl Cx {
         self.inner_key_focus_change();
         self.handle_triggers();
         self.handle_actions();
+        self.handle_file_dialog_results();
     }
 
     // helpers
@@ -0,0 +1,790 @@
+use {
+    std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        rc::Rc,
+        time::Duration,
+    },
+    self::super::sw_raster::{sample_bilinear, SwFramebuffer, SwVertex},
+    crate::{
+        makepad_live_id::*,
+        makepad_math::*,
+        area::Area,
+        cx::{Cx, OsType},
+        cx_api::CxOsOp,
+        file_dialogs::FileDialogKind,
+        draw_list::DrawListId,
+        makepad_shader_compiler::{DrawShaderInterpreter, DrawShaderPtr, ShaderBackend, ShaderTextures, ValuePtr, interpret::Value},
+        texture::TextureFormat,
+        draw_shader::DrawShaderInputs,
+        event::{
+            Event,
+            KeyEvent,
+            KeyModifiers,
+            MouseDownEvent,
+            MouseMoveEvent,
+            MouseUpEvent,
+            ScrollEvent,
+            TextInputEvent,
+            WindowGeom,
+            WindowGeomChangeEvent,
+        },
+        os::{
+            cx_native::EventFlow,
+            cx_stdin::{PollTimer, PollTimers},
+        },
+        pass::{CxPassParent, PassClearColor, PassClearDepth, PassId},
+        thread::SignalToUI,
+        window::WindowId,
+    }
+};
+
+/// Environment variable that routes `Cx::event_loop` to the headless backend.
+pub const HEADLESS_ENV_VAR: &str = "MAKEPAD_HEADLESS";
+
+const HEADLESS_DEFAULT_SIZE: DVec2 = DVec2 {x: 800.0, y: 600.0};
+
+/// Frames a `--check-shaders` run draws so lazily created widgets compile their shaders too.
+const SHADER_CHECK_FRAMES: usize = 10;
+
+#[derive(Clone, Debug)]
+pub enum HeadlessEvent {
+    MouseDown(MouseDownEvent),
+    MouseMove(MouseMoveEvent),
+    MouseUp(MouseUpEvent),
+    Scroll(ScrollEvent),
+    KeyDown(KeyEvent),
+    KeyUp(KeyEvent),
+    TextInput(TextInputEvent),
+    WindowGeomChange(WindowGeomChangeEvent),
+    Paint,
+}
+
+pub struct HeadlessWindow {
+    pub window_id: WindowId,
+    pub window_geom: WindowGeom,
+    pub framebuffer: SwFramebuffer,
+}
+
+/// All state owned by the headless backend, the equivalent of the list of
+/// native windows the other backends keep next to `Cx`.
+#[derive(Default)]
+pub struct CxHeadless {
+    pub windows: Vec<HeadlessWindow>,
+    /// Render targets of passes that draw to a texture, by texture index.
+    pub render_targets: HashMap<usize, SwFramebuffer>,
+    /// The definition each draw shader is interpreted from, by draw shader id.
+    pub shader_defs: HashMap<usize, DrawShaderPtr>,
+    pub timers: PollTimers,
+    pub clipboard: String,
+    pub terminated: bool,
+    /// Leaves the framebuffers alone, for tests that only drive events and layout,
+    /// as rasterizing text on the CPU is slow.
+    pub skip_painting: bool,
+}
+
+impl CxHeadless {
+    pub fn window(&self, window_id: WindowId) -> Option<&HeadlessWindow> {
+        self.windows.iter().find( | w | w.window_id == window_id)
+    }
+}
+
+impl Cx {
+    pub fn headless_requested() -> bool {
+        if cfg!(feature = "headless") || Cx::shader_check_requested().is_some() {
+            return true
+        }
+        match std::env::var(HEADLESS_ENV_VAR) {
+            Ok(v) => v != "" && v != "0",
+            Err(_) => false
+        }
+    }
+
+    pub fn headless_event_loop(cx: Rc<RefCell<Cx>>) {
+        let mut app = HeadlessApp::from_rc(cx);
+        if let Some(backends) = Cx::shader_check_requested() {
+            let failed = app.check_shaders(backends);
+            std::process::exit(if failed > 0 {1} else {0});
+        }
+        app.startup();
+        while !app.headless.terminated {
+            std::thread::sleep(Duration::from_millis(8));
+            app.frame();
+        }
+    }
+
+    pub (crate) fn headless_event_callback(&mut self, headless: &mut CxHeadless, event: HeadlessEvent) -> EventFlow {
+        if let EventFlow::Exit = self.headless_handle_platform_ops(headless) {
+            return EventFlow::Exit
+        }
+        match event {
+            HeadlessEvent::MouseDown(e) => {
+                self.fingers.process_tap_count(e.abs, e.time);
+                self.fingers.mouse_down(e.button, e.window_id);
+                self.call_event_handler(&Event::MouseDown(e))
+            }
+            HeadlessEvent::MouseMove(e) => {
+                self.call_event_handler(&Event::MouseMove(e));
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+                self.fingers.switch_captures();
+            }
+            HeadlessEvent::MouseUp(e) => {
+                let button = e.button;
+                self.call_event_handler(&Event::MouseUp(e));
+                self.fingers.mouse_up(button);
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+            }
+            HeadlessEvent::Scroll(e) => {
+                self.call_event_handler(&Event::Scroll(e))
+            }
+            HeadlessEvent::KeyDown(e) => {
+                self.keyboard.process_key_down(e.clone());
+                self.call_event_handler(&Event::KeyDown(e))
+            }
+            HeadlessEvent::KeyUp(e) => {
+                self.keyboard.process_key_up(e.clone());
+                self.call_event_handler(&Event::KeyUp(e))
+            }
+            HeadlessEvent::TextInput(e) => {
+                self.call_event_handler(&Event::TextInput(e))
+            }
+            HeadlessEvent::WindowGeomChange(e) => {
+                if let Some(window) = headless.windows.iter_mut().find( | w | w.window_id == e.window_id) {
+                    window.window_geom = e.new_geom.clone();
+                    self.windows[e.window_id].window_geom = e.new_geom.clone();
+                    if e.old_geom.inner_size != e.new_geom.inner_size {
+                        if let Some(main_pass_id) = self.windows[e.window_id].main_pass_id {
+                            self.redraw_pass_and_child_passes(main_pass_id);
+                        }
+                    }
+                }
+                self.call_event_handler(&Event::WindowGeomChange(e));
+            }
+            HeadlessEvent::Paint => {
+                if SignalToUI::check_and_clear_ui_signal() {
+                    self.handle_media_signals();
+                    self.call_event_handler(&Event::Signal);
+                }
+                for event in headless.timers.get_dispatch() {
+                    self.call_event_handler(&event);
+                }
+                if self.handle_live_edit() {
+                    self.call_event_handler(&Event::LiveEdit);
+                    self.redraw_all();
+                }
+                self.handle_networking_events();
+                if let EventFlow::Exit = self.headless_handle_platform_ops(headless) {
+                    return EventFlow::Exit
+                }
+                if self.new_next_frames.len() != 0 {
+                    self.call_next_frame_event(headless.timers.time_now());
+                }
+                if self.need_redrawing() {
+                    self.call_draw_event();
+                    self.headless_compile_shaders(headless);
+                }
+                if let EventFlow::Exit = self.headless_handle_platform_ops(headless) {
+                    return EventFlow::Exit
+                }
+                self.headless_handle_repaint(headless);
+            }
+        }
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+            EventFlow::Poll
+        } else {
+            EventFlow::Wait
+        }
+    }
+
+    fn headless_handle_platform_ops(&mut self, headless: &mut CxHeadless) -> EventFlow {
+        let mut ret = EventFlow::Poll;
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let window = &mut self.windows[window_id];
+                    let window_geom = WindowGeom {
+                        dpi_factor: window.dpi_override.unwrap_or(1.0),
+                        inner_size: window.create_inner_size.unwrap_or(HEADLESS_DEFAULT_SIZE),
+                        outer_size: window.create_inner_size.unwrap_or(HEADLESS_DEFAULT_SIZE),
+                        position: window.create_position.unwrap_or(DVec2::default()),
+                        ..Default::default()
+                    };
+                    window.window_geom = window_geom.clone();
+                    window.is_created = true;
+                    headless.windows.push(HeadlessWindow {
+                        window_id,
+                        window_geom,
+                        framebuffer: SwFramebuffer::default(),
+                    });
+                }
+                CxOsOp::CloseWindow(window_id) => {
+                    if let Some(index) = headless.windows.iter().position( | w | w.window_id == window_id) {
+                        self.windows[window_id].is_created = false;
+                        headless.windows.remove(index);
+                        if headless.windows.len() == 0 {
+                            ret = EventFlow::Exit
+                        }
+                    }
+                }
+                CxOsOp::Quit => {
+                    ret = EventFlow::Exit
+                }
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    headless.timers.timers.insert(timer_id, PollTimer::new(interval, repeats));
+                }
+                CxOsOp::StopTimer(timer_id) => {
+                    headless.timers.timers.remove(&timer_id);
+                }
+                CxOsOp::CopyToClipboard(content) => {
+                    headless.clipboard = content;
+                }
+                // there is no native dialog, so the app can draw its own
+                CxOsOp::SaveFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFile, dialog);
+                }
+                CxOsOp::SelectFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFile, dialog);
+                }
+                CxOsOp::SaveFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFolder, dialog);
+                }
+                CxOsOp::SelectFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFolder, dialog);
+                }
+                // there is no native surface to talk to, so everything else is dropped
+                _ => ()
+            }
+        }
+        if let EventFlow::Exit = ret {
+            headless.terminated = true;
+            self.call_event_handler(&Event::Shutdown);
+        }
+        ret
+    }
+
+    pub (crate) fn headless_compile_shaders(&mut self, headless: &mut CxHeadless) {
+        // the CPU rasterizer interprets shaders straight from the shader registry,
+        // so all that is left to do is to remember which definition belongs to
+        // which shader, as shaders with equal fingerprints share one id
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if self.shader_registry.draw_shader_defs.get(draw_shader_ptr).is_none() {
+                crate::error!("Headless: draw shader has no definition");
+                continue
+            }
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(draw_shader_ptr) {
+                headless.shader_defs.insert(item.draw_shader_id, *draw_shader_ptr);
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+    }
+
+    pub (crate) fn headless_handle_repaint(&mut self, headless: &mut CxHeadless) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        self.repaint_id += 1;
+        if headless.skip_painting {
+            for pass_id in &passes_todo {
+                self.passes[*pass_id].paint_dirty = false;
+            }
+            return
+        }
+        let time = headless.timers.time_now();
+        for pass_id in &passes_todo {
+            self.passes[*pass_id].set_time(time as f32);
+            self.headless_draw_pass(*pass_id, headless);
+        }
+    }
+
+    fn headless_draw_pass(&mut self, pass_id: PassId, headless: &mut CxHeadless) {
+        self.passes[pass_id].paint_dirty = false;
+        let draw_list_id = if let Some(id) = self.passes[pass_id].main_draw_list_id {id} else {return};
+        let dpi_factor = if let Some(dpi) = self.passes[pass_id].dpi_factor {dpi} else {return};
+        let pass_rect = if let Some(rect) = self.get_pass_rect(pass_id, dpi_factor) {rect} else {return};
+        if pass_rect.size.x < 0.5 || pass_rect.size.y < 0.5 {
+            return
+        }
+        self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
+        self.passes[pass_id].set_dpi_factor(dpi_factor);
+
+        let width = (pass_rect.size.x * dpi_factor) as usize;
+        let height = (pass_rect.size.y * dpi_factor) as usize;
+        let with_depth = self.passes[pass_id].depth_texture.is_some();
+
+        // resolve the target and what it needs clearing with
+        let (mut fb, target, clear_color) = match self.passes[pass_id].parent.clone() {
+            CxPassParent::Window(window_id) => {
+                let window = if let Some(window) = headless.windows.iter_mut().find( | w | w.window_id == window_id) {window} else {return};
+                let clear_color = if self.passes[pass_id].dont_clear {None}
+                else if let Some(color_texture) = self.passes[pass_id].color_textures.get(0) {
+                    match color_texture.clear_color {
+                        PassClearColor::InitWith(color) | PassClearColor::ClearWith(color) => Some(color)
+                    }
+                }
+                else {
+                    Some(self.passes[pass_id].clear_color)
+                };
+                (std::mem::take(&mut window.framebuffer), HeadlessTarget::Window(window_id), clear_color)
+            }
+            CxPassParent::Pass(_) | CxPassParent::None => {
+                let color_texture = if let Some(ct) = self.passes[pass_id].color_textures.get(0) {ct.clone()} else {return};
+                let texture_id = color_texture.texture.texture_id();
+                let cxtexture = &mut self.textures[texture_id];
+                cxtexture.alloc_render(width, height);
+                let (width, height) = if let Some(alloc) = &cxtexture.alloc {(alloc.width, alloc.height)} else {(width, height)};
+                let clear_color = match color_texture.clear_color {
+                    PassClearColor::InitWith(color) => if cxtexture.check_initial() {Some(color)} else {None},
+                    PassClearColor::ClearWith(color) => Some(color)
+                };
+                let mut fb = headless.render_targets.remove(&texture_id.0).unwrap_or_default();
+                if fb.resize(width, height, with_depth) {
+                    fb.clear_color(Vec4::default());
+                }
+                (fb, HeadlessTarget::Texture(texture_id.0), clear_color)
+            }
+        };
+        fb.resize(width, height, with_depth);
+        if let Some(clear_color) = clear_color {
+            fb.clear_color(clear_color);
+        }
+        if let Some(depth_texture) = &self.passes[pass_id].depth_texture {
+            let clear_depth = match self.passes[pass_id].clear_depth {
+                PassClearDepth::InitWith(depth) => {
+                    if self.textures[depth_texture.texture_id()].check_initial() {Some(depth)} else {None}
+                }
+                PassClearDepth::ClearWith(depth) => Some(depth)
+            };
+            if let Some(clear_depth) = clear_depth {
+                fb.clear_depth(clear_depth);
+            }
+        }
+
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.headless_render_view(pass_id, draw_list_id, &mut zbias, zbias_step, &mut fb, headless);
+
+        match target {
+            HeadlessTarget::Window(window_id) => {
+                if let Some(window) = headless.windows.iter_mut().find( | w | w.window_id == window_id) {
+                    window.framebuffer = fb;
+                }
+            }
+            HeadlessTarget::Texture(texture_index) => {
+                headless.render_targets.insert(texture_index, fb);
+            }
+        }
+    }
+
+    fn headless_render_view(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32,
+        fb: &mut SwFramebuffer,
+        headless: &CxHeadless,
+    ) {
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+
+        for draw_item_id in 0..draw_items_len {
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
+                self.headless_render_view(pass_id, sub_list_id, zbias, zbias_step, fb, headless);
+                continue
+            }
+            let draw_list = &mut self.draw_lists[draw_list_id];
+            let view_uniforms = draw_list.draw_list_uniforms.as_slice().to_vec();
+            let draw_item = &mut draw_list.draw_items[draw_item_id];
+            let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                draw_call
+            }else {
+                continue;
+            };
+            draw_call.instance_dirty = false;
+            draw_call.uniforms_dirty = false;
+            draw_call.draw_uniforms.set_zbias(*zbias);
+            *zbias += zbias_step;
+
+            let sh = &self.draw_shaders.shaders[draw_call.draw_shader.draw_shader_id];
+            let draw_shader_def = if let Some(def) = headless.shader_defs.get(&draw_call.draw_shader.draw_shader_id)
+                .and_then( | ptr | self.shader_registry.draw_shader_defs.get(ptr)) {def} else {continue};
+            let mapping = &sh.mapping;
+            let instances = draw_item.instances.as_ref().unwrap();
+            if mapping.instances.total_slots == 0 || instances.len() < mapping.instances.total_slots {
+                continue
+            }
+            let geometry_id = if let Some(geometry_id) = draw_call.geometry_id {geometry_id} else {continue};
+            let geometry = &mut self.geometries[geometry_id];
+            geometry.dirty = false;
+
+            let mut interp = DrawShaderInterpreter::new(&self.shader_registry, draw_shader_def);
+            set_uniforms(&mut interp, &mapping.pass_uniforms, self.passes[pass_id].pass_uniforms.as_slice());
+            set_uniforms(&mut interp, &mapping.view_uniforms, &view_uniforms);
+            set_uniforms(&mut interp, &mapping.draw_uniforms, draw_call.draw_uniforms.as_slice());
+            set_uniforms(&mut interp, &mapping.user_uniforms, &draw_call.user_uniforms);
+            for input in &mapping.live_uniforms.inputs {
+                if let Some(slots) = mapping.live_uniforms_buf.get(input.offset..input.offset + input.slots) {
+                    interp.set_live_value(ValuePtr(input.live_ptr.unwrap()), Value::from_slots(&input.ty, slots));
+                }
+            }
+            let textures = HeadlessTextures {
+                slots: (0..mapping.textures.len()).map( | i | {
+                    let texture_id = draw_call.texture_slots.get(i)?.as_ref()?.texture_id();
+                    match headless.render_targets.get(&texture_id.0) {
+                        Some(fb) => Some(HeadlessTexture::Target(fb)),
+                        None => Some(HeadlessTexture::Format(&self.textures[texture_id].format))
+                    }
+                }).collect()
+            };
+
+            let geom_slots = mapping.geometries.total_slots;
+            let inst_slots = mapping.instances.total_slots;
+            let num_vertices = if geom_slots > 0 {geometry.vertices.len() / geom_slots} else {0};
+            let mut vertices = vec![SwVertex::default(); num_vertices];
+
+            'instances: for inst in instances.chunks_exact(inst_slots) {
+                for (i, vertex) in vertices.iter_mut().enumerate() {
+                    let geom = &geometry.vertices[i * geom_slots..(i + 1) * geom_slots];
+                    match interp.vertex(geom, inst, &textures) {
+                        Ok(out) => {
+                            vertex.pos = out.position;
+                            vertex.varyings = out.varyings;
+                        }
+                        Err(err) => {
+                            crate::error!("Headless: vertex shader failed: {}", err.message);
+                            break 'instances;
+                        }
+                    }
+                }
+                let mut failed = None;
+                for tri in geometry.indices.chunks_exact(3) {
+                    let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+                    if a >= num_vertices || b >= num_vertices || c >= num_vertices {
+                        continue
+                    }
+                    fb.draw_triangle([&vertices[a], &vertices[b], &vertices[c]], &mut | frag | {
+                        if failed.is_some() {
+                            return None
+                        }
+                        match interp.pixel_with_derivatives(frag.varyings, frag.ddx, frag.ddy, &textures) {
+                            Ok(color) => Some(color),
+                            Err(err) => {
+                                failed = Some(err);
+                                None
+                            }
+                        }
+                    });
+                }
+                if let Some(err) = failed {
+                    crate::error!("Headless: pixel shader failed: {}", err.message);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+enum HeadlessTarget {
+    Window(WindowId),
+    Texture(usize)
+}
+
+fn set_uniforms(interp: &mut DrawShaderInterpreter, inputs: &DrawShaderInputs, buf: &[f32]) {
+    for input in &inputs.inputs {
+        if let Some(slots) = buf.get(input.offset..input.offset + input.slots) {
+            interp.set_uniform(input.id, Value::from_slots(&input.ty, slots));
+        }
+    }
+}
+
+enum HeadlessTexture<'a> {
+    Format(&'a TextureFormat),
+    Target(&'a SwFramebuffer),
+}
+
+/// The textures bound to a draw call, sampled with linear filtering like the
+/// GPU backends do by default.
+struct HeadlessTextures<'a> {
+    slots: Vec<Option<HeadlessTexture<'a >> >,
+}
+
+impl<'a> ShaderTextures for HeadlessTextures<'a> {
+    fn sample_2d(&self, index: usize, uv: Vec2) -> Vec4 {
+        let unorm = | v: u8 | v as f32 / 255.0;
+        let row = | width: usize, unpack_row_length: &Option<usize> | unpack_row_length.unwrap_or(width);
+        match self.slots.get(index) {
+            Some(Some(HeadlessTexture::Target(fb))) => fb.sample_linear(uv),
+            Some(Some(HeadlessTexture::Format(format))) => match format {
+                TextureFormat::VecBGRAu8_32 {width, height, data} |
+                TextureFormat::VecMipBGRAu8_32 {width, height, data, ..} => {
+                    sample_bgra_slice(*width, *height, data, 0, uv)
+                }
+                TextureFormat::VecRGBAf32 {width, height, data} => {
+                    sample_bilinear(*width, *height, uv, &| x, y | {
+                        let i = (y * width + x) * 4;
+                        data.get(i..i + 4).map( | c | vec4(c[0], c[1], c[2], c[3])).unwrap_or_default()
+                    })
+                }
+                TextureFormat::VecRu8 {width, height, data, unpack_row_length} => {
+                    let stride = row(*width, unpack_row_length);
+                    sample_bilinear(*width, *height, uv, &| x, y | {
+                        vec4(unorm(data.get(y * stride + x).cloned().unwrap_or(0)), 0.0, 0.0, 1.0)
+                    })
+                }
+                TextureFormat::VecRGu8 {width, height, data, unpack_row_length} => {
+                    let stride = row(*width, unpack_row_length);
+                    sample_bilinear(*width, *height, uv, &| x, y | {
+                        let i = (y * stride + x) * 2;
+                        data.get(i..i + 2).map( | c | vec4(unorm(c[0]), unorm(c[1]), 0.0, 1.0)).unwrap_or_default()
+                    })
+                }
+                TextureFormat::VecRf32 {width, height, data} => {
+                    sample_bilinear(*width, *height, uv, &| x, y | {
+                        vec4(data.get(y * width + x).cloned().unwrap_or(0.0), 0.0, 0.0, 1.0)
+                    })
+                }
+                _ => Vec4::default()
+            },
+            _ => Vec4::default()
+        }
+    }
+
+    fn sample_cube(&self, index: usize, dir: Vec3) -> Vec4 {
+        let (width, height, data) = match self.slots.get(index) {
+            Some(Some(HeadlessTexture::Format(TextureFormat::VecCubeBGRAu8_32 {width, height, data}))) => (*width, *height, data),
+            _ => return Vec4::default()
+        };
+        // face selection and orientation follow the GL cube map convention
+        let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+        let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+            if dir.x > 0.0 {(0, -dir.z, -dir.y, ax)} else {(1, dir.z, -dir.y, ax)}
+        }
+        else if ay >= az {
+            if dir.y > 0.0 {(2, dir.x, dir.z, ay)} else {(3, dir.x, -dir.z, ay)}
+        }
+        else if dir.z > 0.0 {
+            (4, dir.x, -dir.y, az)
+        }
+        else {
+            (5, -dir.x, -dir.y, az)
+        };
+        if ma == 0.0 {
+            return Vec4::default()
+        }
+        let uv = vec2((sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5);
+        sample_bgra_slice(width, height, data, face, uv)
+    }
+
+    fn sample_3d(&self, index: usize, uvw: Vec3) -> Vec4 {
+        let (width, height, depth, data) = match self.slots.get(index) {
+            Some(Some(HeadlessTexture::Format(TextureFormat::Vec3dBGRAu8_32 {width, height, depth, data}))) => (*width, *height, *depth, data),
+            _ => return Vec4::default()
+        };
+        if depth == 0 || !uvw.z.is_finite() {
+            return Vec4::default()
+        }
+        let z = uvw.z.max(0.0).min(1.0) * depth as f32 - 0.5;
+        let z0 = z.floor().max(0.0).min(depth as f32 - 1.0);
+        let z1 = (z0 + 1.0).min(depth as f32 - 1.0);
+        let uv = vec2(uvw.x, uvw.y);
+        Vec4::from_lerp(
+            sample_bgra_slice(width, height, data, z0 as usize, uv),
+            sample_bgra_slice(width, height, data, z1 as usize, uv),
+            (z - z0).max(0.0).min(1.0)
+        )
+    }
+
+    fn sample_2d_array(&self, index: usize, uv: Vec2, layer: f32) -> Vec4 {
+        let (width, height, layers, data) = match self.slots.get(index) {
+            Some(Some(HeadlessTexture::Format(TextureFormat::VecArrayBGRAu8_32 {width, height, layers, data}))) => (*width, *height, *layers, data),
+            _ => return Vec4::default()
+        };
+        if layers == 0 || !layer.is_finite() {
+            return Vec4::default()
+        }
+        let layer = layer.round().max(0.0).min(layers as f32 - 1.0) as usize;
+        sample_bgra_slice(width, height, data, layer, uv)
+    }
+}
+
+/// Samples one `width*height` image out of a BGRA buffer holding several.
+fn sample_bgra_slice(width: usize, height: usize, data: &[u32], slice: usize, uv: Vec2) -> Vec4 {
+    let unorm = | v: u8 | v as f32 / 255.0;
+    let offset = slice * width * height;
+    sample_bilinear(width, height, uv, &| x, y | {
+        let v = data.get(offset + y * width + x).cloned().unwrap_or(0);
+        vec4(unorm((v >> 16) as u8), unorm((v >> 8) as u8), unorm(v as u8), unorm((v >> 24) as u8))
+    })
+}
+
+/// Drives a `Cx` without any window system or GPU, for use from tests:
+/// events are fed in explicitly and every window paints into a CPU
+/// framebuffer that can be read back afterwards.
+pub struct HeadlessApp {
+    pub cx: Rc<RefCell<Cx>>,
+    pub headless: CxHeadless,
+}
+
+impl HeadlessApp {
+    /// Takes a `Cx` that went through `live_design` and `init_cx_os`.
+    pub fn new(cx: Cx) -> Self {
+        Self::from_rc(Rc::new(RefCell::new(cx)))
+    }
+
+    pub fn from_rc(cx: Rc<RefCell<Cx>>) -> Self {
+        cx.borrow_mut().self_ref = Some(cx.clone());
+        cx.borrow_mut().os_type = OsType::Headless;
+        Self {
+            cx,
+            headless: CxHeadless::default()
+        }
+    }
+
+    pub fn startup(&mut self) {
+        self.cx.borrow_mut().call_event_handler(&Event::Startup);
+        self.cx.borrow_mut().redraw_all();
+        self.frame();
+    }
+
+    /// Starts the app and generates every draw shader it compiles for `backends` too.
+    /// Prints a summary and returns the number of errors, which are left in `cx.draw_shaders`.
+    pub fn check_shaders(&mut self, backends: Vec<ShaderBackend>) -> usize {
+        self.cx.borrow_mut().draw_shaders.check_backends = backends;
+        self.startup();
+        self.settle(SHADER_CHECK_FRAMES);
+        let cx = self.cx.borrow();
+        let failed = cx.draw_shaders.error_set.len() + cx.draw_shaders.backend_errors.len();
+        println!(
+            "Checked {} draw shaders for {}: {} errors",
+            cx.shader_registry.draw_shader_defs.len(),
+            cx.draw_shaders.check_backends.iter().map( | backend | backend.name()).collect::<Vec<_ >>().join(","),
+            failed
+        );
+        failed
+    }
+
+    /// Runs one iteration of the event loop: timers, signals, next frames,
+    /// drawing and painting. Returns whether more frames are pending.
+    pub fn frame(&mut self) -> bool {
+        self.dispatch(HeadlessEvent::Paint)
+    }
+
+    /// Runs frames until nothing is animating or redrawing anymore, or `max_frames` is hit.
+    pub fn settle(&mut self, max_frames: usize) {
+        for _ in 0..max_frames {
+            if !self.frame() {
+                break
+            }
+        }
+    }
+
+    pub fn dispatch(&mut self, event: HeadlessEvent) -> bool {
+        if self.headless.terminated {
+            return false
+        }
+        let event_flow = self.cx.borrow_mut().headless_event_callback(&mut self.headless, event);
+        let executor = self.cx.borrow_mut().executor.take().unwrap();
+        executor.run_until_stalled();
+        self.cx.borrow_mut().executor = Some(executor);
+        event_flow == EventFlow::Poll
+    }
+
+    pub fn time_now(&self) -> f64 {
+        self.headless.timers.time_now()
+    }
+
+    pub fn window_id(&self, index: usize) -> Option<WindowId> {
+        self.headless.windows.get(index).map( | w | w.window_id)
+    }
+
+    /// The framebuffer of the `index`th created window, as of the last paint.
+    pub fn window_framebuffer(&self, index: usize) -> Option<&SwFramebuffer> {
+        self.headless.windows.get(index).map( | w | &w.framebuffer)
+    }
+
+    pub fn mouse_down(&mut self, abs: DVec2, button: usize) {
+        let (window_id, pos) = self.cx.borrow().windows.window_id_contains(abs);
+        let time = self.time_now();
+        self.dispatch(HeadlessEvent::MouseDown(MouseDownEvent {
+            abs: abs - pos,
+            button,
+            window_id,
+            modifiers: KeyModifiers::default(),
+            handled: Cell::new(Area::Empty),
+            time,
+        }));
+    }
+
+    pub fn mouse_move(&mut self, abs: DVec2) {
+        let (window_id, pos) = self.captured_window(abs);
+        let time = self.time_now();
+        self.dispatch(HeadlessEvent::MouseMove(MouseMoveEvent {
+            abs: abs - pos,
+            window_id,
+            modifiers: KeyModifiers::default(),
+            handled: Cell::new(Area::Empty),
+            time,
+        }));
+    }
+
+    pub fn mouse_up(&mut self, abs: DVec2, button: usize) {
+        let (window_id, pos) = self.captured_window(abs);
+        let time = self.time_now();
+        self.dispatch(HeadlessEvent::MouseUp(MouseUpEvent {
+            abs: abs - pos,
+            button,
+            window_id,
+            modifiers: KeyModifiers::default(),
+            time,
+        }));
+    }
+
+    pub fn click(&mut self, abs: DVec2) {
+        self.mouse_move(abs);
+        self.mouse_down(abs, 0);
+        self.mouse_up(abs, 0);
+        self.frame();
+    }
+
+    pub fn key_down(&mut self, e: KeyEvent) {
+        self.dispatch(HeadlessEvent::KeyDown(e));
+    }
+
+    pub fn key_up(&mut self, e: KeyEvent) {
+        self.dispatch(HeadlessEvent::KeyUp(e));
+    }
+
+    pub fn text_input(&mut self, input: &str) {
+        self.dispatch(HeadlessEvent::TextInput(TextInputEvent {
+            input: input.to_string(),
+            replace_last: false,
+            was_paste: false
+        }));
+    }
+
+    pub fn resize_window(&mut self, index: usize, inner_size: DVec2, dpi_factor: f64) {
+        let window = if let Some(window) = self.headless.windows.get(index) {window} else {return};
+        let old_geom = window.window_geom.clone();
+        let new_geom = WindowGeom {
+            inner_size,
+            outer_size: inner_size,
+            dpi_factor,
+            ..old_geom.clone()
+        };
+        let window_id = window.window_id;
+        self.dispatch(HeadlessEvent::WindowGeomChange(WindowGeomChangeEvent {
+            window_id,
+            old_geom,
+            new_geom
+        }));
+    }
+
+    fn captured_window(&self, abs: DVec2) -> (WindowId, DVec2) {
+        let cx = self.cx.borrow();
+        if let Some((_, window_id)) = cx.fingers.first_mouse_button {
+            (window_id, cx.windows[window_id].window_geom.position)
+        }
+        else {
+            cx.windows.window_id_contains(abs)
+        }
+    }
+}
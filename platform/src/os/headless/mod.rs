@@ -0,0 +1,5 @@
+pub mod headless;
+pub mod sw_raster;
+
+pub use self::headless::*;
+pub use self::sw_raster::*;
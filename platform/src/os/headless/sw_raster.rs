@@ -0,0 +1,277 @@
+use {
+    crate::{
+        makepad_math::*,
+    }
+};
+
+/// A CPU-side render target. Colors are stored as premultiplied linear RGBA
+/// floats so blending matches the GPU backends, the depth buffer is optional
+/// and only allocated for passes that have a depth texture attached.
+#[derive(Clone, Debug, Default)]
+pub struct SwFramebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub color: Vec<Vec4>,
+    pub depth: Vec<f32>,
+}
+
+/// A vertex after the vertex stage: its clip space position and the
+/// flattened varyings that get interpolated across the triangle.
+#[derive(Clone, Debug, Default)]
+pub struct SwVertex {
+    pub pos: Vec4,
+    pub varyings: Vec<f32>,
+}
+
+/// One covered pixel as seen by the pixel stage: the interpolated varyings
+/// and how much they change one pixel to the right and one row down, which
+/// is what shader derivatives are computed from.
+pub struct SwFragment<'a> {
+    pub varyings: &'a [f32],
+    pub ddx: &'a [f32],
+    pub ddy: &'a [f32],
+}
+
+const NEAR_W: f32 = 1e-5;
+
+impl SwFramebuffer {
+    pub fn new(width: usize, height: usize, with_depth: bool) -> Self {
+        let mut fb = Self::default();
+        fb.resize(width, height, with_depth);
+        fb
+    }
+
+    /// Returns true if the buffer was (re)allocated.
+    pub fn resize(&mut self, width: usize, height: usize, with_depth: bool) -> bool {
+        let depth_len = if with_depth {width * height} else {0};
+        if self.width == width && self.height == height && self.depth.len() == depth_len {
+            return false
+        }
+        self.width = width;
+        self.height = height;
+        self.color.clear();
+        self.color.resize(width * height, Vec4::default());
+        self.depth.clear();
+        self.depth.resize(depth_len, 1.0);
+        true
+    }
+
+    pub fn clear_color(&mut self, color: Vec4) {
+        for c in &mut self.color {
+            *c = color;
+        }
+    }
+
+    pub fn clear_depth(&mut self, depth: f32) {
+        for d in &mut self.depth {
+            *d = depth;
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Vec4> {
+        if x >= self.width || y >= self.height {
+            return None
+        }
+        Some(self.color[y * self.width + x])
+    }
+
+    /// Samples the buffer with normalized coordinates (nearest filtering, clamp to edge).
+    pub fn sample(&self, uv: Vec2) -> Vec4 {
+        if self.width == 0 || self.height == 0 {
+            return Vec4::default()
+        }
+        let x = ((uv.x * self.width as f32).floor() as isize).max(0).min(self.width as isize - 1) as usize;
+        let y = ((uv.y * self.height as f32).floor() as isize).max(0).min(self.height as isize - 1) as usize;
+        self.color[y * self.width + x]
+    }
+
+    /// Samples the buffer with normalized coordinates (linear filtering, clamp to edge).
+    pub fn sample_linear(&self, uv: Vec2) -> Vec4 {
+        sample_bilinear(self.width, self.height, uv, &| x, y | self.color[y * self.width + x])
+    }
+
+    /// Tightly packed 8 bit RGBA rows, top row first.
+    pub fn to_rgba_u8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.color.len() * 4);
+        for c in &self.color {
+            out.push(unorm8(c.x));
+            out.push(unorm8(c.y));
+            out.push(unorm8(c.z));
+            out.push(unorm8(c.w));
+        }
+        out
+    }
+
+    /// Rasterizes one triangle given in clip space. `shade` receives the
+    /// interpolated varyings of every covered pixel center and returns a
+    /// premultiplied color, or `None` to discard the fragment.
+    pub fn draw_triangle(&mut self, tri: [&SwVertex; 3], shade: &mut dyn FnMut(&SwFragment) -> Option<Vec4>) {
+        // clip against the w > 0 plane, everything else is handled by the
+        // pixel bounds and the depth range check
+        let inside = [tri[0].pos.w > NEAR_W, tri[1].pos.w > NEAR_W, tri[2].pos.w > NEAR_W];
+        if inside[0] && inside[1] && inside[2] {
+            self.raster_triangle(tri, shade);
+            return
+        }
+        let mut poly = Vec::new();
+        for i in 0..3 {
+            let a = tri[i];
+            let b = tri[(i + 1) % 3];
+            if inside[i] {
+                poly.push(a.clone());
+            }
+            if inside[i] != inside[(i + 1) % 3] {
+                let t = (NEAR_W - a.pos.w) / (b.pos.w - a.pos.w);
+                poly.push(lerp_vertex(a, b, t));
+            }
+        }
+        for i in 1..poly.len().saturating_sub(1) {
+            self.raster_triangle([&poly[0], &poly[i], &poly[i + 1]], shade);
+        }
+    }
+
+    fn raster_triangle(&mut self, tri: [&SwVertex; 3], shade: &mut dyn FnMut(&SwFragment) -> Option<Vec4>) {
+        if self.width == 0 || self.height == 0 {
+            return
+        }
+        // to window coordinates, row 0 is the top of the image
+        let mut sx = [0f32; 3];
+        let mut sy = [0f32; 3];
+        let mut sz = [0f32; 3];
+        let mut inv_w = [0f32; 3];
+        for i in 0..3 {
+            let p = tri[i].pos;
+            inv_w[i] = 1.0 / p.w;
+            sx[i] = (p.x * inv_w[i] * 0.5 + 0.5) * self.width as f32;
+            sy[i] = (0.5 - p.y * inv_w[i] * 0.5) * self.height as f32;
+            sz[i] = p.z * inv_w[i] * 0.5 + 0.5;
+        }
+        let area = edge(sx[0], sy[0], sx[1], sy[1], sx[2], sy[2]);
+        if area == 0.0 || !area.is_finite() {
+            return
+        }
+        let min_x = sx[0].min(sx[1]).min(sx[2]).floor().max(0.0) as usize;
+        let min_y = sy[0].min(sy[1]).min(sy[2]).floor().max(0.0) as usize;
+        let max_x = (sx[0].max(sx[1]).max(sx[2]).ceil().max(0.0) as usize).min(self.width);
+        let max_y = (sy[0].max(sy[1]).max(sy[2]).ceil().max(0.0) as usize).min(self.height);
+
+        // top-left fill rule, so pixels on an edge shared by two triangles
+        // are only blended once
+        let sign = area.signum();
+        let bias = [
+            top_left_bias(sx[1], sy[1], sx[2], sy[2], sign),
+            top_left_bias(sx[2], sy[2], sx[0], sy[0], sign),
+            top_left_bias(sx[0], sy[0], sx[1], sy[1], sign),
+        ];
+
+        let num_varyings = tri[0].varyings.len().min(tri[1].varyings.len()).min(tri[2].varyings.len());
+        let mut varyings = vec![0f32; num_varyings];
+        let mut ddx = vec![0f32; num_varyings];
+        let mut ddy = vec![0f32; num_varyings];
+        // perspective correct interpolation at any point of the triangle plane
+        let interpolate = | px: f32, py: f32, out: &mut [f32] | {
+            let p0 = edge(sx[1], sy[1], sx[2], sy[2], px, py) / area * inv_w[0];
+            let p1 = edge(sx[2], sy[2], sx[0], sy[0], px, py) / area * inv_w[1];
+            let p2 = edge(sx[0], sy[0], sx[1], sy[1], px, py) / area * inv_w[2];
+            let norm = 1.0 / (p0 + p1 + p2);
+            for i in 0..out.len() {
+                out[i] = (p0 * tri[0].varyings[i] + p1 * tri[1].varyings[i] + p2 * tri[2].varyings[i]) * norm;
+            }
+        };
+
+        for y in min_y..max_y {
+            let py = y as f32 + 0.5;
+            for x in min_x..max_x {
+                let px = x as f32 + 0.5;
+                let w0 = edge(sx[1], sy[1], sx[2], sy[2], px, py) * sign;
+                let w1 = edge(sx[2], sy[2], sx[0], sy[0], px, py) * sign;
+                let w2 = edge(sx[0], sy[0], sx[1], sy[1], px, py) * sign;
+                if w0 < bias[0] || w1 < bias[1] || w2 < bias[2] {
+                    continue
+                }
+                let b0 = w0 / (area * sign);
+                let b1 = w1 / (area * sign);
+                let b2 = w2 / (area * sign);
+                let z = b0 * sz[0] + b1 * sz[1] + b2 * sz[2];
+                if z < 0.0 || z > 1.0 {
+                    continue
+                }
+                let index = y * self.width + x;
+                if self.depth.len() > 0 && z > self.depth[index] {
+                    continue
+                }
+                interpolate(px, py, &mut varyings);
+                if num_varyings > 0 {
+                    interpolate(px + 1.0, py, &mut ddx);
+                    interpolate(px, py + 1.0, &mut ddy);
+                    for i in 0..num_varyings {
+                        ddx[i] -= varyings[i];
+                        ddy[i] -= varyings[i];
+                    }
+                }
+                let fragment = SwFragment {varyings: &varyings, ddx: &ddx, ddy: &ddy};
+                if let Some(src) = shade(&fragment) {
+                    if self.depth.len() > 0 {
+                        self.depth[index] = z;
+                    }
+                    // premultiplied alpha blending, ONE / ONE_MINUS_SRC_ALPHA
+                    let dst = self.color[index];
+                    let inv_a = 1.0 - src.w;
+                    self.color[index] = vec4(
+                        src.x + dst.x * inv_a,
+                        src.y + dst.y * inv_a,
+                        src.z + dst.z * inv_a,
+                        src.w + dst.w * inv_a,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Bilinear filtering over texel centers with clamp to edge addressing,
+/// `texel` returns the color at an integer texel coordinate.
+pub fn sample_bilinear(width: usize, height: usize, uv: Vec2, texel: &dyn Fn(usize, usize) -> Vec4) -> Vec4 {
+    if width == 0 || height == 0 || !uv.x.is_finite() || !uv.y.is_finite() {
+        return Vec4::default()
+    }
+    let fx = uv.x * width as f32 - 0.5;
+    let fy = uv.y * height as f32 - 0.5;
+    let x0 = fx.floor();
+    let y0 = fy.floor();
+    let tx = fx - x0;
+    let ty = fy - y0;
+    let clamp_x = | x: f32 | (x as isize).max(0).min(width as isize - 1) as usize;
+    let clamp_y = | y: f32 | (y as isize).max(0).min(height as isize - 1) as usize;
+    let (xa, xb, ya, yb) = (clamp_x(x0), clamp_x(x0 + 1.0), clamp_y(y0), clamp_y(y0 + 1.0));
+    let top = Vec4::from_lerp(texel(xa, ya), texel(xb, ya), tx);
+    let bottom = Vec4::from_lerp(texel(xa, yb), texel(xb, yb), tx);
+    Vec4::from_lerp(top, bottom, ty)
+}
+
+fn edge(ax: f32, ay: f32, bx: f32, by: f32, px: f32, py: f32) -> f32 {
+    (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+}
+
+fn top_left_bias(ax: f32, ay: f32, bx: f32, by: f32, sign: f32) -> f32 {
+    let dx = (bx - ax) * sign;
+    let dy = (by - ay) * sign;
+    // with y pointing down a top edge is horizontal going right, a left edge goes up
+    if (dy == 0.0 && dx > 0.0) || dy < 0.0 {
+        0.0
+    }
+    else {
+        f32::MIN_POSITIVE
+    }
+}
+
+fn lerp_vertex(a: &SwVertex, b: &SwVertex, t: f32) -> SwVertex {
+    SwVertex {
+        pos: Vec4::from_lerp(a.pos, b.pos, t),
+        varyings: a.varyings.iter().zip(b.varyings.iter()).map( | (a, b) | a + (b - a) * t).collect()
+    }
+}
+
+fn unorm8(v: f32) -> u8 {
+    (v.max(0.0).min(1.0) * 255.0 + 0.5) as u8
+}
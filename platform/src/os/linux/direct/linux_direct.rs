@@ -9,7 +9,8 @@ use {
     self::super::super::{
         gl_sys,
         select_timer::SelectTimers,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        http::CxLinuxHttp,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi},
@@ -69,6 +70,9 @@ impl DirectApp {
 
 impl Cx {
     pub fn event_loop(cx: Rc<RefCell<Cx >>) {
+        if Cx::headless_requested() {
+            return Cx::headless_event_loop(cx);
+        }
         
         let mut cx = cx.borrow_mut();
         
@@ -179,6 +183,7 @@ impl Cx {
                     if Signal::check_and_clear_ui_signal() {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
+                        self.handle_networking_events();
                     }
                 }
                 else {
@@ -297,6 +302,12 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http.make_http_request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http.cancel_http_request(request_id);
+                },
                 _ => ()
             }
         }
@@ -319,5 +330,6 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub (crate) media: CxLinuxMedia,
+    pub (crate) http: CxLinuxHttp,
 }
 
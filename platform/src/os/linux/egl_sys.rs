@@ -239,7 +239,7 @@ unsafe extern "C" fn(
 ),
 >;
 
-struct Module(::std::ptr::NonNull<::std::os::raw::c_void>);
+pub(crate) struct Module(::std::ptr::NonNull<::std::os::raw::c_void>);
 
 pub struct LibEgl {
     pub eglBindAPI: PFNEGLBINDAPIPROC,
@@ -25,6 +25,8 @@ pub const ARRAY_BUFFER: types::GLenum = 0x8892;
 pub const ELEMENT_ARRAY_BUFFER: types::GLenum = 0x8893;
 pub const TEXTURE0: types::GLenum = 0x84C0;
 pub const TEXTURE_2D: types::GLenum = 0x0DE1;
+pub const TEXTURE_CUBE_MAP: types::GLenum = 0x8513;
+pub const TEXTURE_CUBE_MAP_POSITIVE_X: types::GLenum = 0x8515;
 pub const TRIANGLES: types::GLenum = 0x0004;
 pub const UNSIGNED_INT: types::GLenum = 0x1405;
 pub const DEPTH_TEST: types::GLenum = 0x0B71;
@@ -0,0 +1,726 @@
+//! A small HTTP/1.1 client for the desktop Linux backends. Every request runs on its own thread
+//! over its own connection, https goes through the system libssl which is loaded on first use.
+
+use {
+    std::{
+        collections::HashMap,
+        ffi::{CStr, CString},
+        io::{self, BufRead, BufReader, Read, Write},
+        net::{IpAddr, Shutdown, TcpStream},
+        os::unix::io::AsRawFd,
+        sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}, mpsc::Sender},
+        time::{Duration, Instant},
+    },
+    makepad_miniz::inflate::{decompress_to_vec, decompress_to_vec_zlib},
+    self::super::ssl_sys::{self, LibSsl, SSL, SSL_CTX},
+    crate::{
+        makepad_live_id::LiveId,
+        thread::SignalToUI,
+        cx::Cx,
+        event::{Event, HttpMethod, HttpRequest, HttpResponse, NetworkResponse, NetworkResponseChannel, NetworkResponseItem, SplitUrl},
+    }
+};
+
+const MAX_REDIRECTS: usize = 10;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+impl Cx {
+    pub(crate) fn handle_networking_events(&mut self) {
+        let out = self.os.http.take_responses();
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CxLinuxHttp {
+    network_response: NetworkResponseChannel,
+    requests: HashMap<LiveId, Arc<HttpCancel>>,
+}
+
+impl CxLinuxHttp {
+    pub fn make_http_request(&mut self, request_id: LiveId, request: HttpRequest) {
+        let cancel = Arc::new(HttpCancel::default());
+        self.requests.insert(request_id, cancel.clone());
+        let worker = HttpWorker {
+            request_id,
+            cancel,
+            sender: self.network_response.sender.clone()
+        };
+        std::thread::spawn(move || {
+            let response = match worker.run(request) {
+                Ok(response) => NetworkResponse::HttpResponse(response),
+                Err(err) => NetworkResponse::HttpRequestError(err)
+            };
+            worker.send(response);
+        });
+    }
+
+    pub fn cancel_http_request(&mut self, request_id: LiveId) {
+        if let Some(cancel) = self.requests.remove(&request_id) {
+            cancel.cancel();
+        }
+    }
+
+    // anything still queued for a cancelled request is dropped here
+    pub fn take_responses(&mut self) -> Vec<NetworkResponseItem> {
+        let mut out = Vec::new();
+        while let Ok(item) = self.network_response.receiver.try_recv() {
+            if !self.requests.contains_key(&item.request_id) {
+                continue
+            }
+            if let NetworkResponse::HttpResponse(_) | NetworkResponse::HttpRequestError(_) = item.response {
+                self.requests.remove(&item.request_id);
+            }
+            out.push(item);
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct HttpCancel {
+    cancelled: AtomicBool,
+    // the connection in use, which is shut down on cancel to wake up a blocking read
+    stream: Mutex<Option<TcpStream>>,
+}
+
+impl HttpCancel {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(stream) = self.stream.lock().unwrap().take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn set_stream(&self, stream: &TcpStream) -> Result<(), String> {
+        let mut slot = self.stream.lock().unwrap();
+        if self.is_cancelled() {
+            return Err("request cancelled".into())
+        }
+        *slot = Some(stream.try_clone().map_err( | e | e.to_string()) ?);
+        Ok(())
+    }
+}
+
+struct HttpWorker {
+    request_id: LiveId,
+    cancel: Arc<HttpCancel>,
+    sender: Sender<NetworkResponseItem>,
+}
+
+struct Progress {
+    total: u64,
+    last: Instant,
+}
+
+impl HttpWorker {
+    fn send(&self, response: NetworkResponse) {
+        if !self.cancel.is_cancelled() {
+            let _ = self.sender.send(NetworkResponseItem {
+                request_id: self.request_id,
+                response
+            });
+            SignalToUI::set_ui_signal();
+        }
+    }
+
+    fn run(&self, mut request: HttpRequest) -> Result<HttpResponse, String> {
+        for _ in 0..=MAX_REDIRECTS {
+            let split = request.split_url();
+            let mut connection = self.connect(&split, request.ignore_ssl_cert) ?;
+            write_request(&mut connection, &request, &split).map_err( | e | format!("cannot send request to {}: {}", split.host, e)) ?;
+            let mut reader = BufReader::new(connection);
+            let (status_code, headers) = read_head(&mut reader) ?;
+
+            if let (301 | 302 | 303 | 307 | 308, Some(location)) = (status_code, header_value(&headers, "location")) {
+                let url = resolve_url(&split, location);
+                let same_host = url.split_once("://").map_or(false, | (_, rest) | rest.split(['/', ':', '?']).next() == Some(split.host));
+                // like browsers, a redirected post turns into a get unless it is a 307 or 308
+                if (status_code == 303 && request.method != HttpMethod::HEAD) || (request.method == HttpMethod::POST && status_code < 303) {
+                    request.method = HttpMethod::GET;
+                    request.body = None;
+                    request.headers.retain( | name, _ | !name.eq_ignore_ascii_case("content-type"));
+                }
+                if !same_host {
+                    request.headers.retain( | name, _ | !name.eq_ignore_ascii_case("authorization"));
+                }
+                request.url = url;
+                continue
+            }
+
+            let body = self.read_body(&mut reader, &request.method, status_code, &headers) ?;
+            let mut response = HttpResponse::new(request.metadata_id, status_code, String::new(), Some(body));
+            for (name, value) in headers {
+                response.set_header(name, value);
+            }
+            return Ok(response)
+        }
+        Err(format!("too many redirects for {}", request.url))
+    }
+
+    fn connect(&self, split: &SplitUrl, ignore_ssl_cert: bool) -> Result<Connection, String> {
+        if split.proto != "http" && split.proto != "https" {
+            return Err(format!("unsupported protocol {}", split.proto))
+        }
+        let stream = TcpStream::connect(format!("{}:{}", split.host, split.port))
+            .map_err( | e | format!("cannot connect to {}:{}: {}", split.host, split.port, e)) ?;
+        self.cancel.set_stream(&stream) ?;
+        if split.proto == "https" {
+            Ok(Connection::Tls(TlsStream::connect(stream, split.host, !ignore_ssl_cert) ?))
+        }
+        else {
+            Ok(Connection::Plain(stream))
+        }
+    }
+
+    fn read_body(&self, reader: &mut impl BufRead, method: &HttpMethod, status_code: u16, headers: &[(String, String)]) -> Result<Vec<u8>, String> {
+        let mut body = Vec::new();
+        if *method == HttpMethod::HEAD || status_code == 204 || status_code == 304 {
+            return Ok(body)
+        }
+        let content_length = header_value(headers, "content-length").and_then( | len | len.parse::<usize>().ok());
+        let mut progress = Progress {
+            total: content_length.unwrap_or(0) as u64,
+            last: Instant::now()
+        };
+
+        if header_value(headers, "transfer-encoding").map_or(false, | te | te.to_ascii_lowercase().contains("chunked")) {
+            loop {
+                let line = read_line(reader) ?;
+                let size = line.split(';').next().unwrap().trim();
+                let size = usize::from_str_radix(size, 16).map_err( | _ | format!("invalid chunk size {}", size)) ?;
+                if size == 0 {
+                    // skip the trailer headers
+                    while !read_line(reader) ?.is_empty() {}
+                    break
+                }
+                self.read_into(reader, &mut body, Some(size), &mut progress) ?;
+                read_line(reader) ?;
+            }
+        }
+        else {
+            // without a length the body runs up to the end of the connection
+            self.read_into(reader, &mut body, content_length, &mut progress) ?;
+        }
+        self.send(NetworkResponse::HttpProgress {loaded: body.len() as u64, total: progress.total});
+
+        match header_value(headers, "content-encoding").map( | ce | ce.trim().to_ascii_lowercase()).as_deref() {
+            Some("gzip") | Some("x-gzip") => {
+                let offset = gzip_data_offset(&body).ok_or("invalid gzip header in response body") ?;
+                decompress_to_vec(&body[offset..]).map_err( | e | format!("cannot inflate gzip response body: {:?}", e))
+            }
+            Some("deflate") => {
+                // servers disagree on whether this has a zlib header or not
+                decompress_to_vec_zlib(&body).or_else( | _ | decompress_to_vec(&body))
+                    .map_err( | e | format!("cannot inflate response body: {:?}", e))
+            }
+            _ => Ok(body)
+        }
+    }
+
+    fn read_into(&self, reader: &mut impl Read, body: &mut Vec<u8>, len: Option<usize>, progress: &mut Progress) -> Result<(), String> {
+        let mut buf = [0u8; 16384];
+        let mut left = len;
+        while left != Some(0) {
+            if self.cancel.is_cancelled() {
+                return Err("request cancelled".into())
+            }
+            let want = left.map_or(buf.len(), | left | left.min(buf.len()));
+            let n = reader.read(&mut buf[..want]).map_err( | e | format!("cannot read response body: {}", e)) ?;
+            if n == 0 {
+                if left.is_some() {
+                    return Err("connection closed before the end of the response body".into())
+                }
+                break
+            }
+            body.extend_from_slice(&buf[..n]);
+            left = left.map( | left | left - n);
+            if progress.last.elapsed() >= PROGRESS_INTERVAL {
+                progress.last = Instant::now();
+                self.send(NetworkResponse::HttpProgress {loaded: body.len() as u64, total: progress.total});
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_request(connection: &mut Connection, request: &HttpRequest, split: &SplitUrl) -> io::Result<()> {
+    let has_header = | name: &str | request.headers.keys().any( | key | key.eq_ignore_ascii_case(name));
+    let mut head = format!("{} /{} HTTP/1.1\r\n", request.method.to_string(), split.file);
+    if !has_header("host") {
+        if split.port == "80" && split.proto == "http" || split.port == "443" && split.proto == "https" {
+            head.push_str(&format!("Host: {}\r\n", split.host));
+        }
+        else {
+            head.push_str(&format!("Host: {}:{}\r\n", split.host, split.port));
+        }
+    }
+    if !has_header("user-agent") {
+        head.push_str("User-Agent: makepad\r\n");
+    }
+    if !has_header("accept-encoding") {
+        head.push_str("Accept-Encoding: gzip, deflate\r\n");
+    }
+    // every request has its own connection, and the length is that of the body we send
+    for (name, values) in &request.headers {
+        if !name.eq_ignore_ascii_case("connection") && !name.eq_ignore_ascii_case("content-length") {
+            head.push_str(&format!("{}: {}\r\n", name, values.join(",")));
+        }
+    }
+    head.push_str("Connection: close\r\n");
+    match &request.body {
+        Some(body) => head.push_str(&format!("Content-Length: {}\r\n", body.len())),
+        None if matches!(request.method, HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH) => head.push_str("Content-Length: 0\r\n"),
+        None => ()
+    }
+    head.push_str("\r\n");
+    connection.write_all(head.as_bytes()) ?;
+    if let Some(body) = &request.body {
+        connection.write_all(body) ?;
+    }
+    connection.flush()
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line).map_err( | e | format!("cannot read response: {}", e)) ?;
+    if line.is_empty() {
+        return Err("connection closed before the end of the response".into())
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn read_head(reader: &mut impl BufRead) -> Result<(u16, Vec<(String, String)>), String> {
+    loop {
+        let status_line = read_line(reader) ?;
+        let status_code = status_line.strip_prefix("HTTP/")
+            .and_then( | rest | rest.split(' ').nth(1))
+            .and_then( | code | code.parse::<u16>().ok())
+            .ok_or_else( | | format!("invalid status line {}", status_line)) ?;
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader) ?;
+            if line.is_empty() {
+                break
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        // informational responses like 100 continue come before the real one
+        if status_code >= 200 {
+            return Ok((status_code, headers))
+        }
+    }
+}
+
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find( | (key, _) | key.eq_ignore_ascii_case(name)).map( | (_, value) | value.as_str())
+}
+
+fn resolve_url(base: &SplitUrl, location: &str) -> String {
+    if location.contains("://") {
+        location.to_string()
+    }
+    else if let Some(rest) = location.strip_prefix("//") {
+        format!("{}://{}", base.proto, rest)
+    }
+    else if let Some(path) = location.strip_prefix('/') {
+        format!("{}://{}:{}/{}", base.proto, base.host, base.port, path)
+    }
+    else {
+        // relative to the directory of the current path
+        let file = base.file.split(['?', '#']).next().unwrap();
+        let dir = file.rsplit_once('/').map_or("", | (dir, _) | dir);
+        if dir.is_empty() {
+            format!("{}://{}:{}/{}", base.proto, base.host, base.port, location)
+        }
+        else {
+            format!("{}://{}:{}/{}/{}", base.proto, base.host, base.port, dir, location)
+        }
+    }
+}
+
+// the deflate data in a gzip member comes after a header with a few optional fields
+fn gzip_data_offset(data: &[u8]) -> Option<usize> {
+    if data.get(0..3) ? != [0x1f, 0x8b, 8] {
+        return None
+    }
+    let flags = *data.get(3) ?;
+    let mut pos = 10;
+    if flags & 0x04 != 0 {
+        pos += 2 + u16::from_le_bytes([*data.get(pos) ?, *data.get(pos + 1) ?]) as usize;
+    }
+    // the file name and comment are zero terminated
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            pos += data.get(pos..) ?.iter().position( | b | *b == 0) ? + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        pos += 2;
+    }
+    (pos <= data.len()).then_some(pos)
+}
+
+enum Connection {
+    Plain(TcpStream),
+    Tls(TlsStream),
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.read(buf),
+            Self::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.write(buf),
+            Self::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.flush(),
+            Self::Tls(_) => Ok(()),
+        }
+    }
+}
+
+struct TlsStream {
+    lib: &'static LibSsl,
+    ctx: *mut SSL_CTX,
+    ssl: *mut SSL,
+    // owns the socket libssl reads from and writes to
+    _stream: TcpStream,
+}
+
+impl TlsStream {
+    fn connect(stream: TcpStream, host: &str, verify: bool) -> Result<Self, String> {
+        let lib = LibSsl::get().ok_or("https needs libssl, which could not be loaded") ?;
+        let c_host = CString::new(host).map_err( | _ | format!("invalid host name {}", host)) ?;
+        unsafe {
+            let ctx = (lib.SSL_CTX_new)((lib.TLS_client_method)());
+            if ctx.is_null() {
+                return Err("cannot create a TLS context".into())
+            }
+            if verify {
+                (lib.SSL_CTX_set_default_verify_paths)(ctx);
+                (lib.SSL_CTX_set_verify)(ctx, ssl_sys::SSL_VERIFY_PEER, None);
+            }
+            else {
+                (lib.SSL_CTX_set_verify)(ctx, ssl_sys::SSL_VERIFY_NONE, None);
+            }
+            // plenty of servers close the connection without a TLS goodbye
+            if let Some(set_options) = lib.SSL_CTX_set_options {
+                set_options(ctx, ssl_sys::SSL_OP_IGNORE_UNEXPECTED_EOF);
+            }
+            let tls = TlsStream {lib, ctx, ssl: (lib.SSL_new)(ctx), _stream: stream};
+            if tls.ssl.is_null() || (lib.SSL_set_fd)(tls.ssl, tls._stream.as_raw_fd()) != 1 {
+                return Err("cannot create a TLS connection".into())
+            }
+            // the server name is only sent for names, not addresses
+            if host.parse::<IpAddr>().is_err() {
+                (lib.SSL_ctrl)(tls.ssl, ssl_sys::SSL_CTRL_SET_TLSEXT_HOSTNAME, ssl_sys::TLSEXT_NAMETYPE_host_name, c_host.as_ptr() as *mut _);
+            }
+            if verify && (lib.SSL_set1_host)(tls.ssl, c_host.as_ptr()) != 1 {
+                return Err(format!("cannot verify the host name {}", host))
+            }
+            if (lib.SSL_connect)(tls.ssl) != 1 {
+                let verify_result = (lib.SSL_get_verify_result)(tls.ssl);
+                if verify && verify_result != ssl_sys::X509_V_OK {
+                    let reason = CStr::from_ptr((lib.X509_verify_cert_error_string)(verify_result));
+                    return Err(format!("certificate of {} not accepted: {}", host, reason.to_string_lossy()))
+                }
+                return Err(format!("TLS handshake with {} failed (error {:x})", host, (lib.ERR_get_error)()))
+            }
+            Ok(tls)
+        }
+    }
+
+    fn io_error(&self, ret: i32) -> io::Error {
+        let error = unsafe {(self.lib.SSL_get_error)(self.ssl, ret)};
+        io::Error::new(io::ErrorKind::Other, format!("TLS error {}", error))
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(i32::MAX as usize) as i32;
+        let ret = unsafe {(self.lib.SSL_read)(self.ssl, buf.as_mut_ptr() as *mut _, len)};
+        if ret > 0 {
+            return Ok(ret as usize)
+        }
+        if unsafe {(self.lib.SSL_get_error)(self.ssl, ret)} == ssl_sys::SSL_ERROR_ZERO_RETURN {
+            return Ok(0)
+        }
+        Err(self.io_error(ret))
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(i32::MAX as usize) as i32;
+        let ret = unsafe {(self.lib.SSL_write)(self.ssl, buf.as_ptr() as *const _, len)};
+        if ret > 0 {
+            return Ok(ret as usize)
+        }
+        Err(self.io_error(ret))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for TlsStream {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ssl.is_null() {
+                (self.lib.SSL_shutdown)(self.ssl);
+                (self.lib.SSL_free)(self.ssl);
+            }
+            (self.lib.SSL_CTX_free)(self.ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            net::TcpListener,
+            sync::mpsc::{channel, Receiver},
+        },
+        makepad_miniz::deflate::compress_to_vec,
+    };
+
+    type Handler = Box<dyn FnOnce(&mut TcpStream) + Send>;
+
+    /// A server on a local port that answers each connection with the next handler.
+    /// Returns its address and the requests it got, head and body as one string.
+    fn serve(handlers: Vec<Handler>) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            for handler in handlers {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut len = 0;
+                loop {
+                    let line = read_line(&mut reader).unwrap();
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        len = value.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    request.push('\n');
+                    if line.is_empty() {
+                        break
+                    }
+                }
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+                sender.send(request).unwrap();
+                handler(&mut stream);
+            }
+        });
+        (addr, receiver)
+    }
+
+    fn respond(response: &'static [u8]) -> Handler {
+        Box::new(move | stream | stream.write_all(response).unwrap())
+    }
+
+    /// Everything the request sent up to and including its final response.
+    fn wait_for(http: &mut CxLinuxHttp, request_id: LiveId) -> Vec<NetworkResponse> {
+        let start = Instant::now();
+        let mut out = Vec::new();
+        while start.elapsed() < Duration::from_secs(10) {
+            for item in http.take_responses() {
+                assert_eq!(item.request_id, request_id);
+                let done = matches!(item.response, NetworkResponse::HttpResponse(_) | NetworkResponse::HttpRequestError(_));
+                out.push(item.response);
+                if done {
+                    return out
+                }
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("no response in time, got {:?}", out)
+    }
+
+    fn response_of(responses: &[NetworkResponse]) -> &HttpResponse {
+        match responses.last() {
+            Some(NetworkResponse::HttpResponse(response)) => response,
+            other => panic!("expected a response, got {:?}", other)
+        }
+    }
+
+    fn get(url: String) -> Vec<NetworkResponse> {
+        let mut http = CxLinuxHttp::default();
+        http.make_http_request(LiveId(1), HttpRequest::new(url, HttpMethod::GET));
+        wait_for(&mut http, LiveId(1))
+    }
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn reads_chunked_bodies() {
+        let (addr, requests) = serve(vec![respond(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5;name=value\r\nhello\r\n7\r\n, world\r\n0\r\nTrailer: yes\r\n\r\n")]);
+        let responses = get(format!("{}/chunks", addr));
+        let response = response_of(&responses);
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.get_string_body().as_deref(), Some("hello, world"));
+        assert!(requests.recv().unwrap().starts_with("GET /chunks HTTP/1.1\n"));
+    }
+
+    #[test]
+    fn redirected_posts_turn_into_gets() {
+        let (addr, requests) = serve(vec![
+            respond(b"HTTP/1.1 302 Found\r\nLocation: next?a=1\r\nContent-Length: 0\r\n\r\n"),
+            respond(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone"),
+        ]);
+        let mut request = HttpRequest::new(format!("{}/form/post", addr), HttpMethod::POST);
+        request.set_header("Content-Type".into(), "text/plain".into());
+        request.set_body_string("data");
+        let mut http = CxLinuxHttp::default();
+        http.make_http_request(LiveId(1), request);
+        let responses = wait_for(&mut http, LiveId(1));
+        assert_eq!(response_of(&responses).get_string_body().as_deref(), Some("done"));
+
+        let post = requests.recv().unwrap();
+        assert!(post.starts_with("POST /form/post HTTP/1.1\n"), "{}", post);
+        assert!(post.contains("Content-Type: text/plain\n") && post.ends_with("\ndata"), "{}", post);
+        let redirected = requests.recv().unwrap();
+        assert!(redirected.starts_with("GET /form/next?a=1 HTTP/1.1\n"), "{}", redirected);
+        assert!(!redirected.contains("Content-Type") && !redirected.contains("Content-Length"), "{}", redirected);
+        assert!(redirected.ends_with("\n\n"), "{}", redirected);
+    }
+
+    #[test]
+    fn inflates_gzip_bodies() {
+        let text = "gzip ".repeat(100);
+        // a header with a file name in it, then the data and its checksum and length
+        let mut body = vec![0x1f, 0x8b, 8, 0x08, 0, 0, 0, 0, 0, 255];
+        body.extend_from_slice(b"name.txt\0");
+        body.extend_from_slice(&compress_to_vec(text.as_bytes(), 6));
+        body.extend_from_slice(&crc32(text.as_bytes()).to_le_bytes());
+        body.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        let (addr, _requests) = serve(vec![Box::new(move | stream | {
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", body.len());
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        })]);
+        let responses = get(addr);
+        assert_eq!(response_of(&responses).get_string_body(), Some(text));
+    }
+
+    #[test]
+    fn reports_progress_while_reading() {
+        let (addr, _requests) = serve(vec![Box::new( | stream | {
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n").unwrap();
+            for part in [b"abc", b"def", b"ghi"] {
+                stream.write_all(part).unwrap();
+                std::thread::sleep(PROGRESS_INTERVAL * 2);
+            }
+        })]);
+        let responses = get(addr);
+        let progress: Vec<_> = responses.iter().filter_map( | response | match response {
+            NetworkResponse::HttpProgress {loaded, total} => Some((*loaded, *total)),
+            _ => None
+        }).collect();
+        // the reads in between report along the way, and the last one always comes
+        assert!(progress.len() >= 2, "{:?}", progress);
+        assert!(progress.iter().any( | (loaded, _) | *loaded < 9), "{:?}", progress);
+        assert_eq!(progress.last(), Some(&(9, 9)));
+        assert!(progress.windows(2).all( | w | w[0].0 <= w[1].0), "{:?}", progress);
+        assert_eq!(response_of(&responses).get_string_body().as_deref(), Some("abcdefghi"));
+    }
+
+    #[test]
+    fn cancelled_requests_close_the_connection_and_stay_quiet() {
+        let (sent, has_sent) = channel();
+        let (closed, was_closed) = channel();
+        let (addr, _requests) = serve(vec![Box::new(move | stream | {
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nstart").unwrap();
+            sent.send(()).unwrap();
+            // the client shuts the connection down instead of waiting for the rest
+            let mut buf = [0u8; 16];
+            closed.send(stream.read(&mut buf).map( | n | n == 0).unwrap_or(true)).unwrap();
+        })]);
+        let mut http = CxLinuxHttp::default();
+        http.make_http_request(LiveId(1), HttpRequest::new(addr, HttpMethod::GET));
+        has_sent.recv_timeout(Duration::from_secs(10)).unwrap();
+        http.cancel_http_request(LiveId(1));
+        assert!(was_closed.recv_timeout(Duration::from_secs(10)).unwrap());
+        std::thread::sleep(PROGRESS_INTERVAL * 2);
+        assert!(http.take_responses().is_empty());
+    }
+
+    #[test]
+    fn read_head_skips_informational_responses() {
+        let mut reader = &b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\nX-Empty:\r\nbroken line\r\n\r\nbody"[..];
+        let (status_code, headers) = read_head(&mut reader).unwrap();
+        assert_eq!(status_code, 404);
+        assert_eq!(headers, vec![("Content-Type".into(), "text/html".into()), ("X-Empty".into(), String::new())]);
+        assert_eq!(header_value(&headers, "content-type"), Some("text/html"));
+        assert_eq!(reader, b"body");
+
+        assert!(read_head(&mut &b"garbage\r\n\r\n"[..]).is_err());
+        assert!(read_head(&mut &b"HTTP/1.1 200 OK\r\nCut: off"[..]).is_err());
+    }
+
+    #[test]
+    fn resolve_url_handles_every_kind_of_location() {
+        let request = HttpRequest::new("https://host.org:8443/dir/file?q=a/b".into(), HttpMethod::GET);
+        let base = request.split_url();
+        assert_eq!(resolve_url(&base, "http://other.org/x"), "http://other.org/x");
+        assert_eq!(resolve_url(&base, "//other.org/x"), "https://other.org/x");
+        assert_eq!(resolve_url(&base, "/x"), "https://host.org:8443/x");
+        assert_eq!(resolve_url(&base, "x?y=1"), "https://host.org:8443/dir/x?y=1");
+
+        let request = HttpRequest::new("http://host.org/file".into(), HttpMethod::GET);
+        assert_eq!(resolve_url(&request.split_url(), "x"), "http://host.org:80/x");
+    }
+
+    #[test]
+    fn gzip_data_offset_skips_the_optional_fields() {
+        let header = | flags: u8 | vec![0x1f, 0x8b, 8, flags, 0, 0, 0, 0, 0, 255];
+        assert_eq!(gzip_data_offset(&header(0)), Some(10));
+        // extra field, name, comment and header checksum, in that order
+        let mut data = header(0x04 | 0x08 | 0x10 | 0x02);
+        data.extend_from_slice(&[3, 0, 1, 2, 3]);
+        data.extend_from_slice(b"name\0comment\0");
+        data.extend_from_slice(&[0, 0]);
+        assert_eq!(gzip_data_offset(&data), Some(data.len()));
+
+        assert_eq!(gzip_data_offset(b"PK\x03\x04"), None);
+        assert_eq!(gzip_data_offset(&header(0x08)), None);
+        assert_eq!(gzip_data_offset(&header(0x02)), None);
+    }
+}
@@ -9,7 +9,7 @@ use {
     self::super::gl_sys,
     crate::{
         makepad_live_id::*,
-        makepad_shader_compiler::generate_glsl,
+        makepad_shader_compiler::{generate_glsl, shader_ast::ShaderTy},
         cx::{Cx, OsType, OsType::Android},
         texture::{Texture, TextureFormat, TexturePixel, CxTexture},
         makepad_math::{Mat4, DVec2, Vec4},
@@ -220,16 +220,27 @@ impl Cx {
                             // Video playback with SurfaceTexture requires TEXTURE_EXTERNAL_OES, for any other format we assume regular 2D textures
                             match cxtexture.format {
                                 TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, texture),
+                                TextureFormat::VecCubeBGRAu8_32{..} => gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, texture),
                                 _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, texture)     
                             }
                         }
                         else {
                             match cxtexture.format {
                                 TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, 0),
+                                TextureFormat::VecCubeBGRAu8_32{..} => gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, 0),
                                 _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0)     
                             }
                         }
                         gl_sys::Uniform1i(shgl.textures[i].loc, i as i32);
+                        // 3D and array textures are stacked in a 2D atlas, the shader needs the slice count
+                        if shgl.texture_layers[i].loc != -1 {
+                            let layers = match cxtexture.format {
+                                TextureFormat::Vec3dBGRAu8_32{depth, ..} => depth,
+                                TextureFormat::VecArrayBGRAu8_32{layers, ..} => layers,
+                                _ => 1
+                            };
+                            gl_sys::Uniform1fv(shgl.texture_layers[i].loc, 1, [layers as f32].as_ptr());
+                        }
                     }
                     
                     gl_sys::DrawElementsInstanced(
@@ -467,6 +478,13 @@ impl Cx {
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
                 );
+                let (vertex, pixel) = match (vertex, pixel) {
+                    (Ok(vertex), Ok(pixel)) => (vertex, pixel),
+                    (Err(err), _) | (_, Err(err)) => {
+                        crate::log!("Error {}", self.live_registry.borrow().live_error_to_live_file_error(err));
+                        continue
+                    }
+                };
                 
                 if cx_shader.mapping.flags.debug {
                     crate::log!("{}\n{}", vertex, pixel);
@@ -515,6 +533,7 @@ pub struct GlShader {
     pub geometries: Vec<OpenglAttribute>,
     pub instances: Vec<OpenglAttribute>,
     pub textures: Vec<OpenglUniform>,
+    pub texture_layers: Vec<OpenglUniform>,
     pub pass_uniforms: OpenglUniform,
     pub view_uniforms: OpenglUniform,
     pub draw_uniforms: OpenglUniform,
@@ -602,6 +621,7 @@ impl GlShader{
                 geometries:Self::opengl_get_attributes(program, "packed_geometry_", mapping.geometries.total_slots),
                 instances: Self::opengl_get_attributes(program, "packed_instance_", mapping.instances.total_slots),
                 textures: Self::opengl_get_texture_slots(program, &mapping.textures),
+                texture_layers: Self::opengl_get_texture_layers(program, &mapping.textures),
                 pass_uniforms: Self::opengl_get_uniform(program, "pass_table"),
                 view_uniforms: Self::opengl_get_uniform(program, "view_table"),
                 draw_uniforms: Self::opengl_get_uniform(program, "draw_table"),
@@ -728,6 +748,10 @@ impl GlShader{
         for slot in texture_slots {
             let mut name0 = "ds_".to_string();
             name0.push_str(&slot.id.to_string());
+            // 3D and array textures are a struct wrapping an atlas sampler
+            if let ShaderTy::Texture3D | ShaderTy::Texture2DArray = slot.ty {
+                name0.push_str(".atlas");
+            }
             name0.push_str("\0");
             unsafe {
                 gl_texture_slots.push(OpenglUniform {
@@ -737,6 +761,21 @@ impl GlShader{
         }
         gl_texture_slots
     }
+    
+    pub fn opengl_get_texture_layers(program: u32, texture_slots: &Vec<DrawShaderTextureInput>) -> Vec<OpenglUniform> {
+        let mut gl_texture_layers = Vec::new();
+        for slot in texture_slots {
+            let loc = if let ShaderTy::Texture3D | ShaderTy::Texture2DArray = slot.ty {
+                let name0 = format!("ds_{}.layers\0", slot.id);
+                unsafe {gl_sys::GetUniformLocation(program, name0.as_ptr() as *const _)}
+            }
+            else {
+                -1
+            };
+            gl_texture_layers.push(OpenglUniform {loc});
+        }
+        gl_texture_layers
+    }
 
     pub fn free_resources(self){
         unsafe{
@@ -914,10 +953,16 @@ impl CxTexture {
             }
         }
         if self.check_updated(){
+            let target = if let TextureFormat::VecCubeBGRAu8_32{..} = &self.format{
+                gl_sys::TEXTURE_CUBE_MAP
+            }
+            else{
+                gl_sys::TEXTURE_2D
+            };
             unsafe{
-                gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap());
-                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
-                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
+                gl_sys::BindTexture(target, self.os.gl_texture.unwrap());
+                gl_sys::TexParameteri(target, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
+                gl_sys::TexParameteri(target, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
             }                       
             match &self.format{
                 TextureFormat::VecBGRAu8_32{width, height, data}=>unsafe{
@@ -1021,10 +1066,46 @@ impl CxTexture {
                         data.as_ptr() as *const _
                     );
                 },
+                TextureFormat::VecCubeBGRAu8_32{width, height, data}=>unsafe{
+                    gl_sys::TexParameteri(target, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
+                    gl_sys::TexParameteri(target, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
+                    let face_size = width * height;
+                    for face in 0..6{
+                        gl_sys::TexImage2D(
+                            gl_sys::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                            0,
+                            gl_sys::BGRA as i32,
+                            *width as i32,
+                            *height as i32,
+                            0,
+                            gl_sys::BGRA,
+                            gl_sys::UNSIGNED_BYTE,
+                            data[face * face_size..].as_ptr() as *const _
+                        );
+                    }
+                },
+                // GLES2 has no 3D or array textures, the slices are uploaded as one
+                // tall 2D atlas which is exactly how they are laid out in memory
+                TextureFormat::Vec3dBGRAu8_32{width, height, depth: slices, data} |
+                TextureFormat::VecArrayBGRAu8_32{width, height, layers: slices, data}=>unsafe{
+                    gl_sys::TexParameteri(target, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
+                    gl_sys::TexParameteri(target, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
+                    gl_sys::TexImage2D(
+                        gl_sys::TEXTURE_2D,
+                        0,
+                        gl_sys::BGRA as i32,
+                        *width as i32,
+                        (*height * *slices) as i32,
+                        0,
+                        gl_sys::BGRA,
+                        gl_sys::UNSIGNED_BYTE,
+                        data.as_ptr() as *const _
+                    );
+                },
                 _=>{panic!()}
             }
             unsafe{
-                gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
+                gl_sys::BindTexture(target, 0);
             }
         }
     }
@@ -0,0 +1,91 @@
+#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals, dead_code)]
+
+use {
+    std::{
+        os::raw::{c_char, c_int, c_long, c_ulong, c_void},
+        sync::OnceLock,
+    },
+    self::super::egl_sys::Module,
+};
+
+pub enum SSL_METHOD {}
+pub enum SSL_CTX {}
+pub enum SSL {}
+
+pub type SSL_verify_cb = Option<unsafe extern "C" fn(c_int, *mut c_void) -> c_int>;
+
+pub const SSL_VERIFY_NONE: c_int = 0;
+pub const SSL_VERIFY_PEER: c_int = 1;
+
+pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
+pub const TLSEXT_NAMETYPE_host_name: c_long = 0;
+
+pub const SSL_OP_IGNORE_UNEXPECTED_EOF: u64 = 1 << 7;
+
+pub const SSL_ERROR_ZERO_RETURN: c_int = 6;
+pub const X509_V_OK: c_long = 0;
+
+pub struct LibSsl {
+    pub TLS_client_method: unsafe extern "C" fn() -> *const SSL_METHOD,
+    pub SSL_CTX_new: unsafe extern "C" fn(method: *const SSL_METHOD) -> *mut SSL_CTX,
+    pub SSL_CTX_free: unsafe extern "C" fn(ctx: *mut SSL_CTX),
+    pub SSL_CTX_set_default_verify_paths: unsafe extern "C" fn(ctx: *mut SSL_CTX) -> c_int,
+    pub SSL_CTX_set_verify: unsafe extern "C" fn(ctx: *mut SSL_CTX, mode: c_int, callback: SSL_verify_cb),
+    // a macro before openssl 3
+    pub SSL_CTX_set_options: Option<unsafe extern "C" fn(ctx: *mut SSL_CTX, options: u64) -> u64>,
+    pub SSL_new: unsafe extern "C" fn(ctx: *mut SSL_CTX) -> *mut SSL,
+    pub SSL_free: unsafe extern "C" fn(ssl: *mut SSL),
+    pub SSL_set_fd: unsafe extern "C" fn(ssl: *mut SSL, fd: c_int) -> c_int,
+    pub SSL_ctrl: unsafe extern "C" fn(ssl: *mut SSL, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long,
+    pub SSL_set1_host: unsafe extern "C" fn(ssl: *mut SSL, hostname: *const c_char) -> c_int,
+    pub SSL_connect: unsafe extern "C" fn(ssl: *mut SSL) -> c_int,
+    pub SSL_read: unsafe extern "C" fn(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int,
+    pub SSL_write: unsafe extern "C" fn(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int,
+    pub SSL_shutdown: unsafe extern "C" fn(ssl: *mut SSL) -> c_int,
+    pub SSL_get_error: unsafe extern "C" fn(ssl: *const SSL, ret: c_int) -> c_int,
+    pub SSL_get_verify_result: unsafe extern "C" fn(ssl: *const SSL) -> c_long,
+    // these two live in libcrypto, which libssl pulls in
+    pub ERR_get_error: unsafe extern "C" fn() -> c_ulong,
+    pub X509_verify_cert_error_string: unsafe extern "C" fn(n: c_long) -> *const c_char,
+
+    _keep_module_alive: Module,
+}
+
+// the function pointers are only ever read after loading
+unsafe impl Send for LibSsl {}
+unsafe impl Sync for LibSsl {}
+
+static LIB_SSL: OnceLock<Option<LibSsl>> = OnceLock::new();
+
+impl LibSsl {
+    // libssl is loaded the first time a https request needs it, and stays loaded
+    pub fn get() -> Option<&'static LibSsl> {
+        LIB_SSL.get_or_init(Self::try_load).as_ref()
+    }
+
+    fn try_load() -> Option<LibSsl> {
+        let module = Module::load("libssl.so.3").or_else( | _ | Module::load("libssl.so.1.1")).or_else( | _ | Module::load("libssl.so")).ok() ?;
+        Some(LibSsl {
+            TLS_client_method: module.get_symbol("TLS_client_method").ok() ?,
+            SSL_CTX_new: module.get_symbol("SSL_CTX_new").ok() ?,
+            SSL_CTX_free: module.get_symbol("SSL_CTX_free").ok() ?,
+            SSL_CTX_set_default_verify_paths: module.get_symbol("SSL_CTX_set_default_verify_paths").ok() ?,
+            SSL_CTX_set_verify: module.get_symbol("SSL_CTX_set_verify").ok() ?,
+            SSL_CTX_set_options: module.get_symbol("SSL_CTX_set_options").ok(),
+            SSL_new: module.get_symbol("SSL_new").ok() ?,
+            SSL_free: module.get_symbol("SSL_free").ok() ?,
+            SSL_set_fd: module.get_symbol("SSL_set_fd").ok() ?,
+            SSL_ctrl: module.get_symbol("SSL_ctrl").ok() ?,
+            SSL_set1_host: module.get_symbol("SSL_set1_host").ok() ?,
+            SSL_connect: module.get_symbol("SSL_connect").ok() ?,
+            SSL_read: module.get_symbol("SSL_read").ok() ?,
+            SSL_write: module.get_symbol("SSL_write").ok() ?,
+            SSL_shutdown: module.get_symbol("SSL_shutdown").ok() ?,
+            SSL_get_error: module.get_symbol("SSL_get_error").ok() ?,
+            SSL_get_verify_result: module.get_symbol("SSL_get_verify_result").ok() ?,
+            ERR_get_error: module.get_symbol("ERR_get_error").ok() ?,
+            X509_verify_cert_error_string: module.get_symbol("X509_verify_cert_error_string").ok() ?,
+            _keep_module_alive: module,
+        })
+    }
+}
@@ -11,7 +11,8 @@ use {
         x11::xlib_event::*,
         x11::xlib_app::*,
         x11::x11_sys,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        http::CxLinuxHttp,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
@@ -19,6 +20,7 @@ use {
         makepad_live_id::*,
         thread::SignalToUI,
         event::Event,
+        file_dialogs::FileDialogKind,
         pass::CxPassParent,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
@@ -29,6 +31,9 @@ use {
 
 impl Cx {
     pub fn event_loop(cx:Rc<RefCell<Cx>>) {
+        if Cx::headless_requested() {
+            return Cx::headless_event_loop(cx);
+        }
         cx.borrow_mut().self_ref = Some(cx.clone());
         cx.borrow_mut().os_type = OsType::LinuxWindow(LinuxWindowParams{
             custom_window_chrome: false
@@ -203,6 +208,11 @@ impl Cx {
                     if SignalToUI::check_and_clear_ui_signal(){
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
+                        self.handle_networking_events();
+                    }
+                    if self.handle_live_edit(){
+                        self.call_event_handler(&Event::LiveEdit);
+                        self.redraw_all();
                     }
                 }
                 else{
@@ -219,9 +229,6 @@ impl Cx {
         
     }
 
-    pub(crate) fn handle_networking_events(&mut self) {
-    }
-    
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
         self.os.opengl_cx.as_ref().unwrap().make_current();
         let mut passes_todo = Vec::new();
@@ -336,8 +343,11 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    self.os.http.make_http_request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest{request_id} => {
+                    self.os.http.cancel_http_request(request_id);
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
@@ -348,10 +358,19 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),
+                // there is no native dialog, so the app can draw its own
+                CxOsOp::SaveFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFile, dialog);
+                }
+                CxOsOp::SelectFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFile, dialog);
+                }
+                CxOsOp::SaveFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFolder, dialog);
+                }
+                CxOsOp::SelectFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFolder, dialog);
+                }
             }
         }
         ret
@@ -378,6 +397,7 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
+    pub(crate) http: CxLinuxHttp,
     pub (crate) stdin_timers: PollTimers,
     pub (crate) start_time: Option<Instant>,
     // HACK(eddyb) generalize this to EGL, properly.
@@ -282,6 +282,12 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.http.make_http_request(request_id, request);
+                },
+                CxOsOp::CancelHttpRequest {request_id} => {
+                    self.os.http.cancel_http_request(request_id);
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
@@ -221,6 +221,27 @@ pub struct FromWasmAllocTextureImage2D_BGRAu8_32 {
     pub data: WasmPtrU32
 }
 
+/// Six faces of `width*height` texels in +x, -x, +y, -y, +z, -z order
+#[allow(non_camel_case_types)]
+#[derive(FromWasm)]
+pub struct FromWasmAllocTextureCube_BGRAu8_32 {
+    pub texture_id: usize,
+    pub width: usize,
+    pub height: usize,
+    pub data: WasmPtrU32
+}
+
+/// 3D and array textures, uploaded as a 2D atlas with the layers stacked vertically
+#[allow(non_camel_case_types)]
+#[derive(FromWasm)]
+pub struct FromWasmAllocTextureAtlas_BGRAu8_32 {
+    pub texture_id: usize,
+    pub width: usize,
+    pub height: usize,
+    pub layers: usize,
+    pub data: WasmPtrU32
+}
+
 #[allow(non_camel_case_types)]
 #[derive(FromWasm)]
 pub struct FromWasmAllocTextureImage2D_Ru8 {
@@ -472,6 +472,9 @@ impl Cx {
                         body: WasmDataU8::from_vec_u8(request.body.unwrap_or(Vec::new())),
                     });
                 },
+                CxOsOp::CancelHttpRequest{request_id:_} => {
+                    crate::log!("Cancelling http requests not supported yet");
+                },
                 /*
                 CxOsOp::WebSocketOpen{request_id, request}=>{
                     let headers = request.get_headers_string();
@@ -585,6 +588,8 @@ impl CxOsApi for Cx {
             FromWasmAllocVao::to_js_code(),
             FromWasmAllocTextureImage2D_BGRAu8_32::to_js_code(),
             FromWasmAllocTextureImage2D_Ru8::to_js_code(),
+            FromWasmAllocTextureCube_BGRAu8_32::to_js_code(),
+            FromWasmAllocTextureAtlas_BGRAu8_32::to_js_code(),
             FromWasmBeginRenderTexture::to_js_code(),
             FromWasmBeginRenderCanvas::to_js_code(),
             FromWasmSetDefaultDepthAndBlendMode::to_js_code(),
@@ -233,10 +233,15 @@ export class WasmWebGL extends WasmWebBrowser {
        //console.log(args.pixel)
         let texture_locs = [];
         for (let i = 0; i < args.textures.length; i ++) {
+            let ty = args.textures[i].ty;
+            // 3D and array textures are a struct wrapping a 2D atlas and its layer count
+            let is_atlas = ty == "texture3D" || ty == "texture2DArray";
+            let name = "ds_" + args.textures[i].name;
             texture_locs.push({
                 name: args.textures[i].name,
-                ty: args.textures[i].ty,
-                loc: gl.getUniformLocation(program, "ds_" + args.textures[i].name),
+                ty: ty,
+                loc: gl.getUniformLocation(program, is_atlas? name + ".atlas": name),
+                layers_loc: is_atlas? gl.getUniformLocation(program, name + ".layers"): null,
             });
         }
         
@@ -373,8 +378,11 @@ export class WasmWebGL extends WasmWebBrowser {
             if (texture_id !== undefined) {
                 let tex_obj = this.textures[texture_id];
                 gl.activeTexture(gl.TEXTURE0 + i);
-                gl.bindTexture(gl.TEXTURE_2D, tex_obj);
+                gl.bindTexture(tex_loc.ty == "textureCube"? gl.TEXTURE_CUBE_MAP: gl.TEXTURE_2D, tex_obj);
                 gl.uniform1i(tex_loc.loc, i);
+                if (tex_loc.layers_loc) {
+                    gl.uniform1f(tex_loc.layers_loc, (tex_obj && tex_obj.mp_layers) || 1);
+                }
             }
         }
         
@@ -431,6 +439,38 @@ export class WasmWebGL extends WasmWebBrowser {
         this.textures[args.texture_id] = gl_tex;
     }
     
+    FromWasmAllocTextureCube_BGRAu8_32(args) {
+        var gl = this.gl;
+        var gl_tex = this.textures[args.texture_id] || gl.createTexture()
+        
+        gl.bindTexture(gl.TEXTURE_CUBE_MAP, gl_tex)
+        gl.texParameteri(gl.TEXTURE_CUBE_MAP, gl.TEXTURE_MAG_FILTER, gl.LINEAR)
+        gl.texParameteri(gl.TEXTURE_CUBE_MAP, gl.TEXTURE_MIN_FILTER, gl.LINEAR)
+        gl.texParameteri(gl.TEXTURE_CUBE_MAP, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE)
+        gl.texParameteri(gl.TEXTURE_CUBE_MAP, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE)
+        let face_size = args.width * args.height * 4;
+        for (let face = 0; face < 6; face ++) {
+            let data_array = new Uint8Array(this.memory.buffer, args.data.ptr + face * face_size, face_size);
+            gl.texImage2D(gl.TEXTURE_CUBE_MAP_POSITIVE_X + face, 0, gl.RGBA, args.width, args.height, 0, gl.RGBA, gl.UNSIGNED_BYTE, data_array);
+        }
+        this.textures[args.texture_id] = gl_tex;
+    }
+    
+    FromWasmAllocTextureAtlas_BGRAu8_32(args) {
+        var gl = this.gl;
+        var gl_tex = this.textures[args.texture_id] || gl.createTexture()
+        
+        gl.bindTexture(gl.TEXTURE_2D, gl_tex)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.LINEAR)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.LINEAR)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE)
+        let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height * args.layers * 4);
+        gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, args.width, args.height * args.layers, 0, gl.RGBA, gl.UNSIGNED_BYTE, data_array);
+        gl_tex.mp_layers = args.layers;
+        this.textures[args.texture_id] = gl_tex;
+    }
+    
     FromWasmAllocTextureImage2D_Ru8(args) {
         var gl = this.gl;
         var gl_tex = this.textures[args.texture_id] || gl.createTexture()
@@ -100,6 +100,24 @@ impl Cx {
                                         data: WasmPtrU8::new(&data)
                                     });
                                 }
+                                TextureFormat::VecCubeBGRAu8_32{width, height, data}=>{
+                                    self.os.from_wasm(FromWasmAllocTextureCube_BGRAu8_32 {
+                                        texture_id: texture_id.0,
+                                        width: *width,
+                                        height: *height,
+                                        data: WasmPtrU32::new(&data)
+                                    });
+                                }
+                                TextureFormat::Vec3dBGRAu8_32{width, height, depth: layers, data} |
+                                TextureFormat::VecArrayBGRAu8_32{width, height, layers, data}=>{
+                                    self.os.from_wasm(FromWasmAllocTextureAtlas_BGRAu8_32 {
+                                        texture_id: texture_id.0,
+                                        width: *width,
+                                        height: *height,
+                                        layers: *layers,
+                                        data: WasmPtrU32::new(&data)
+                                    });
+                                }
                                 x=>panic!("Texture format not implemented for webGL {:?}", x)
                             }
                         }
@@ -342,6 +360,13 @@ impl Cx {
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
                 );
+                let (vertex, pixel) = match (vertex, pixel) {
+                    (Ok(vertex), Ok(pixel)) => (vertex, pixel),
+                    (Err(err), _) | (_, Err(err)) => {
+                        crate::log!("Error {}", self.live_registry.borrow().live_error_to_live_file_error(err));
+                        continue
+                    }
+                };
                  
                 if cx_shader.mapping.flags.debug {
                    crate::log!("{}\n{}", vertex,pixel);
@@ -39,12 +39,18 @@ use crate::{
                     D3D11_USAGE_DEFAULT,
                     D3D11_BIND_CONSTANT_BUFFER,
                     D3D11_RESOURCE_MISC_FLAG,
+                    D3D11_RESOURCE_MISC_TEXTURECUBE,
                     D3D11_SUBRESOURCE_DATA,
                     D3D11_CREATE_DEVICE_FLAG,
                     D3D11_SDK_VERSION,
                     D3D11_BIND_FLAG,
                     D3D11_BIND_SHADER_RESOURCE,
                     D3D11_TEXTURE2D_DESC,
+                    D3D11_TEXTURE3D_DESC,
+                    D3D11_SHADER_RESOURCE_VIEW_DESC,
+                    D3D11_SHADER_RESOURCE_VIEW_DESC_0,
+                    D3D11_TEXCUBE_SRV,
+                    D3D11_TEX2D_ARRAY_SRV,
                     D3D11_BIND_RENDER_TARGET,
                     D3D11_BIND_DEPTH_STENCIL,
                     D3D11_DEPTH_STENCIL_DESC,
@@ -98,6 +104,8 @@ use crate::{
                     D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
                     D3D_DRIVER_TYPE_UNKNOWN,
                     D3D_FEATURE_LEVEL_11_0,
+                    D3D_SRV_DIMENSION_TEXTURECUBE,
+                    D3D_SRV_DIMENSION_TEXTURE2DARRAY,
                 },
                 Dxgi::{
                     IDXGIFactory2,
@@ -472,11 +480,17 @@ impl Cx {
             if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
-                let hlsl = generate_hlsl::generate_shader(
+                let hlsl = match generate_hlsl::generate_shader(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
-                );
+                ) {
+                    Ok(hlsl) => hlsl,
+                    Err(err) => {
+                        crate::log!("Error {}", self.live_registry.borrow().live_error_to_live_file_error(err));
+                        continue
+                    }
+                };
                 
                 if cx_shader.mapping.flags.debug {
                     crate::log!("{}", hlsl);
@@ -806,6 +820,82 @@ impl CxTexture {
         // TODO maybe we can update the data instead of making a new texture?
         if self.alloc_vec(){}
         if self.check_updated(){
+            match &self.format{
+                TextureFormat::Vec3dBGRAu8_32{width, height, depth, data}=>{
+                    let sub_data = D3D11_SUBRESOURCE_DATA {
+                        pSysMem: data.as_ptr() as *const _,
+                        SysMemPitch: (width * 4) as u32,
+                        SysMemSlicePitch: (width * height * 4) as u32
+                    };
+                    let texture_desc = D3D11_TEXTURE3D_DESC {
+                        Width: *width as u32,
+                        Height: *height as u32,
+                        Depth: *depth as u32,
+                        MipLevels: 1,
+                        Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        Usage: D3D11_USAGE_DEFAULT,
+                        BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                        CPUAccessFlags: 0,
+                        MiscFlags: 0,
+                    };
+                    let mut texture = None;
+                    unsafe {d3d11_cx.device.CreateTexture3D(&texture_desc, Some(&sub_data), Some(&mut texture)).unwrap()};
+                    let resource: ID3D11Resource = texture.unwrap().cast().unwrap();
+                    let mut shader_resource_view = None;
+                    unsafe {d3d11_cx.device.CreateShaderResourceView(&resource, None, Some(&mut shader_resource_view)).unwrap()};
+                    self.os.texture = None;
+                    self.os.shader_resource_view = shader_resource_view;
+                    return
+                }
+                TextureFormat::VecCubeBGRAu8_32{width, height, data} |
+                TextureFormat::VecArrayBGRAu8_32{width, height, data, ..}=>{
+                    let is_cube = matches!(self.format, TextureFormat::VecCubeBGRAu8_32{..});
+                    let slices = data.len() / (width * height).max(1);
+                    let sub_data: Vec<D3D11_SUBRESOURCE_DATA> = (0..slices).map(|slice| D3D11_SUBRESOURCE_DATA {
+                        pSysMem: data[slice * width * height..].as_ptr() as *const _,
+                        SysMemPitch: (width * 4) as u32,
+                        SysMemSlicePitch: 0
+                    }).collect();
+                    let texture_desc = D3D11_TEXTURE2D_DESC {
+                        Width: *width as u32,
+                        Height: *height as u32,
+                        MipLevels: 1,
+                        ArraySize: slices as u32,
+                        Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        SampleDesc: DXGI_SAMPLE_DESC {
+                            Count: 1,
+                            Quality: 0
+                        },
+                        Usage: D3D11_USAGE_DEFAULT,
+                        BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                        CPUAccessFlags: 0,
+                        MiscFlags: if is_cube {D3D11_RESOURCE_MISC_TEXTURECUBE.0 as u32} else {0},
+                    };
+                    let view_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
+                        Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                        ViewDimension: if is_cube {D3D_SRV_DIMENSION_TEXTURECUBE} else {D3D_SRV_DIMENSION_TEXTURE2DARRAY},
+                        Anonymous: if is_cube {
+                            D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
+                                TextureCube: D3D11_TEXCUBE_SRV {MostDetailedMip: 0, MipLevels: 1}
+                            }
+                        }
+                        else {
+                            D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
+                                Texture2DArray: D3D11_TEX2D_ARRAY_SRV {MostDetailedMip: 0, MipLevels: 1, FirstArraySlice: 0, ArraySize: slices as u32}
+                            }
+                        }
+                    };
+                    let mut texture = None;
+                    unsafe {d3d11_cx.device.CreateTexture2D(&texture_desc, Some(sub_data.as_ptr()), Some(&mut texture)).unwrap()};
+                    let resource: ID3D11Resource = texture.clone().unwrap().cast().unwrap();
+                    let mut shader_resource_view = None;
+                    unsafe {d3d11_cx.device.CreateShaderResourceView(&resource, Some(&view_desc), Some(&mut shader_resource_view)).unwrap()};
+                    self.os.texture = texture;
+                    self.os.shader_resource_view = shader_resource_view;
+                    return
+                }
+                _=>()
+            }
             fn get_descs(format: DXGI_FORMAT, width: usize, height: usize, bpp: usize, data: *const std::ffi::c_void)->(D3D11_SUBRESOURCE_DATA,D3D11_TEXTURE2D_DESC) {
                 let sub_data = D3D11_SUBRESOURCE_DATA {
                     pSysMem: data,
@@ -8,6 +8,7 @@ use {
         makepad_live_id::*,
         cx::*,
         event::*,
+        file_dialogs::FileDialogKind,
         thread::SignalToUI,
         os::{
             windows::{
@@ -30,6 +31,9 @@ use {
 impl Cx {
     
     pub fn event_loop(cx: Rc<RefCell<Cx >>) {
+        if Cx::headless_requested() {
+            return Cx::headless_event_loop(cx);
+        }
         
         cx.borrow_mut().self_ref = Some(cx.clone());
         cx.borrow_mut().os_type = OsType::Windows;
@@ -383,6 +387,9 @@ impl Cx {
                 CxOsOp::HttpRequest {request_id: _, request: _} => {
                     todo!("HttpRequest not implemented yet on windows, we'll get there");
                 },
+                CxOsOp::CancelHttpRequest {request_id: _} => {
+                    crate::log!("Cancelling http requests not supported yet");
+                },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
@@ -391,10 +398,19 @@ impl Cx {
                 CxOsOp::UnmuteVideoPlayback(_) => todo!(),
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
-                CxOsOp::SaveFileDialog(_) =>  todo!(),
-                CxOsOp::SelectFileDialog(_) =>  todo!(),
-                CxOsOp::SaveFolderDialog(_) =>  todo!(),
-                CxOsOp::SelectFolderDialog(_) =>  todo!(),
+                // there is no native dialog, so the app can draw its own
+                CxOsOp::SaveFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFile, dialog);
+                }
+                CxOsOp::SelectFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFile, dialog);
+                }
+                CxOsOp::SaveFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFolder, dialog);
+                }
+                CxOsOp::SelectFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFolder, dialog);
+                }
             }
         }
         ret
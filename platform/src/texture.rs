@@ -86,6 +86,13 @@ pub enum TextureFormat {
     VecRu8{width:usize, height:usize, data:Vec<u8>, unpack_row_length:Option<usize>},
     VecRGu8{width:usize, height:usize, data:Vec<u8>, unpack_row_length:Option<usize>},
     VecRf32{width:usize, height:usize, data:Vec<f32>},
+    /// Six square faces of `width*height` texels each, stored one after
+    /// the other in +x, -x, +y, -y, +z, -z order.
+    VecCubeBGRAu8_32{width:usize, height:usize, data:Vec<u32>},
+    /// `depth` slices of `width*height` texels, stored slice after slice.
+    Vec3dBGRAu8_32{width:usize, height:usize, depth:usize, data:Vec<u32>},
+    /// `layers` images of `width*height` texels, stored layer after layer.
+    VecArrayBGRAu8_32{width:usize, height:usize, layers:usize, data:Vec<u32>},
     DepthD32{size:TextureSize},
     RenderBGRAu8{size:TextureSize},
     RenderRGBAf16{size:TextureSize},
@@ -101,6 +108,27 @@ pub(crate) struct TextureAlloc{
     pub pixel: TexturePixel,
     pub width: usize,
     pub height: usize,
+    pub dim: TextureDim,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TextureDim{
+    D2,
+    Cube,
+    D3{depth:usize},
+    D2Array{layers:usize},
+}
+
+impl TextureDim{
+    /// The number of `width*height` images the texture is made of
+    pub fn slices(&self)->usize{
+        match self{
+            Self::D2=>1,
+            Self::Cube=>6,
+            Self::D3{depth}=>*depth,
+            Self::D2Array{layers}=>*layers,
+        }
+    }
 }
 
 #[allow(unused)]    
@@ -161,6 +189,15 @@ impl CxTexture{
                                 return false
                             }
                         }
+                        TextureFormat::VecCubeBGRAu8_32{width, height, data} |
+                        TextureFormat::Vec3dBGRAu8_32{width, height, data, ..} |
+                        TextureFormat::VecArrayBGRAu8_32{width, height, data, ..}=>{
+                            let slices = alloc.dim.slices();
+                            if width * height * slices != data.len(){
+                                error!("Texture buffer size incorrect {}*{}*{} != {}", width, height, slices, data.len());
+                                return false
+                            }
+                        }
                         _=>()
                     }
                 }
@@ -270,6 +307,9 @@ impl TextureFormat{
             Self::VecRu8{..}=>true,
             Self::VecRGu8{..}=>true,
             Self::VecRf32{..}=>true,
+            Self::VecCubeBGRAu8_32{..}=>true,
+            Self::Vec3dBGRAu8_32{..}=>true,
+            Self::VecArrayBGRAu8_32{..}=>true,
             _=>false
         }
     }
@@ -306,6 +346,9 @@ impl TextureFormat{
             Self::VecRu8{width, height, ..}=>Some((*width,*height)),
             Self::VecRGu8{width, height, ..}=>Some((*width,*height)),
             Self::VecRf32{width, height,..}=>Some((*width,*height)),
+            Self::VecCubeBGRAu8_32{width, height,..}=>Some((*width,*height)),
+            Self::Vec3dBGRAu8_32{width, height,..}=>Some((*width,*height)),
+            Self::VecArrayBGRAu8_32{width, height,..}=>Some((*width,*height)),
             _=>None
         }
     }
@@ -316,36 +359,63 @@ impl TextureFormat{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
+                dim: TextureDim::D2,
                 category: TextureCategory::Vec{updated:true}
             }),
             Self::VecMipBGRAu8_32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
+                dim: TextureDim::D2,
                 category: TextureCategory::Vec{updated:true}
             }),
             Self::VecRGBAf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGBAf32,
+                dim: TextureDim::D2,
                 category: TextureCategory::Vec{updated:true}
             }),
             Self::VecRu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Ru8,
+                dim: TextureDim::D2,
                 category: TextureCategory::Vec{updated:true}
             }),
             Self::VecRGu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGu8,
+                dim: TextureDim::D2,
                 category: TextureCategory::Vec{updated:true}
             }),
             Self::VecRf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Rf32,
+                dim: TextureDim::D2,
+                category: TextureCategory::Vec{updated:true}
+            }),
+            Self::VecCubeBGRAu8_32{width,height,..}=>Some(TextureAlloc{
+                width:*width,
+                height:*height,
+                pixel:TexturePixel::BGRAu8,
+                dim: TextureDim::Cube,
+                category: TextureCategory::Vec{updated:true}
+            }),
+            Self::Vec3dBGRAu8_32{width,height,depth,..}=>Some(TextureAlloc{
+                width:*width,
+                height:*height,
+                pixel:TexturePixel::BGRAu8,
+                dim: TextureDim::D3{depth:*depth},
+                category: TextureCategory::Vec{updated:true}
+            }),
+            Self::VecArrayBGRAu8_32{width,height,layers,..}=>Some(TextureAlloc{
+                width:*width,
+                height:*height,
+                pixel:TexturePixel::BGRAu8,
+                dim: TextureDim::D2Array{layers:*layers},
                 category: TextureCategory::Vec{updated:true}
             }),
             _=>None
@@ -360,6 +430,7 @@ impl TextureFormat{
                     width,
                     height,
                     pixel:TexturePixel::BGRAu8,
+                    dim: TextureDim::D2,
                     category: TextureCategory::Render{initial:true}
                 })
             }
@@ -369,6 +440,7 @@ impl TextureFormat{
                     width,
                     height,
                     pixel:TexturePixel::RGBAf16,
+                    dim: TextureDim::D2,
                     category: TextureCategory::Render{initial:true}
                 })
             }
@@ -378,6 +450,7 @@ impl TextureFormat{
                     width,
                     height,
                     pixel:TexturePixel::RGBAf32,
+                    dim: TextureDim::D2,
                     category: TextureCategory::Render{initial:true}
                 })
             }
@@ -393,6 +466,7 @@ impl TextureFormat{
                     width,
                     height,
                     pixel:TexturePixel::D32,
+                    dim: TextureDim::D2,
                     category: TextureCategory::DepthBuffer{initial:true}
                 })
             },
@@ -409,6 +483,7 @@ impl TextureFormat{
                     width: 0,
                     height: 0,
                     pixel:TexturePixel::VideoRGB,
+                    dim: TextureDim::D2,
                     category: TextureCategory::Video{initial:true}
                 })
             },
@@ -424,6 +499,7 @@ impl TextureFormat{
                     width:*width,
                     height:*height,
                     pixel:TexturePixel::BGRAu8,
+                    dim: TextureDim::D2,
                     category: TextureCategory::Shared{initial:true},
                 })
             }
@@ -464,7 +540,10 @@ impl Texture {
     pub fn swap_vec_u32(&self, cx: &mut Cx, image: &mut Vec<u32>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         match &mut cxtexture.format{
-            TextureFormat::VecBGRAu8_32{data,..} => {
+            TextureFormat::VecBGRAu8_32{data,..} |
+            TextureFormat::VecCubeBGRAu8_32{data,..} |
+            TextureFormat::Vec3dBGRAu8_32{data,..} |
+            TextureFormat::VecArrayBGRAu8_32{data,..} => {
                 std::mem::swap(data, image);
                 cxtexture.set_updated(true);
             }
@@ -356,6 +356,13 @@ impl MatchEvent for App{
                     // lets write the file
                     self.data.file_system.request_save_file_for_tab_id(action.path.from_end(1), false)
                 }
+                CodeEditorAction::SaveRequested => {
+                    // format the live_design on an explicit save
+                    let tab_id = action.path.from_end(1);
+                    self.data.file_system.format_live_design_for_tab_id(tab_id);
+                    self.data.file_system.request_save_file_for_tab_id(tab_id, false);
+                    dock.redraw_tab(cx, tab_id);
+                }
                 CodeEditorAction::None=>{}
             }
             
@@ -208,16 +208,22 @@ impl BuildConnection {
                 "--message-format=json".into(),
             ],
             BuildTarget::CheckMacos => vec![
+                "makepad".into(),
                 "check".into(),
+                "target".into(),
                 "--target=aarch64-apple-darwin".into(),
+                "--shaders=metal".into(),
                 "-p".into(),
                 what.binary.clone(),
                 "--release".into(),
                 "--message-format=json".into(),
             ],
             BuildTarget::CheckWindows => vec![
+                "makepad".into(),
                 "check".into(),
+                "target".into(),
                 "--target=x86_64-pc-windows-msvc".into(),
+                "--shaders=hlsl".into(),
                 "-p".into(),
                 what.binary.clone(),
                 "--release".into(),
@@ -3,7 +3,8 @@ use {
     std::path::Path,
     crate::{
         makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
-        makepad_platform::makepad_live_compiler::LiveFileChange,
+        makepad_code_editor::{selection::Affinity, session::SelectionMode, history::NewGroup, text::Position},
+        makepad_platform::makepad_live_compiler::{LiveFileChange, format_live_design},
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
         file_system::FileClient,
@@ -228,6 +229,50 @@ impl FileSystem {
         }
     }
     
+    pub fn format_live_design_for_tab_id(&mut self, tab_id: LiveId) {
+        let Some(session) = self.get_session_mut(tab_id) else {
+            return
+        };
+        let old = session.document().as_text().to_string();
+        let new = match format_live_design(&old) {
+            Ok(new) => new,
+            Err(e) => {
+                log!("Cannot format live_design {}", e);
+                return
+            }
+        };
+        if new == old {
+            return
+        }
+        // only replace the lines that changed, so the cursor and undo history stay sensible
+        let old_lines: Vec<&str> = old.split('\n').collect();
+        let new_lines: Vec<&str> = new.split('\n').collect();
+        let prefix = old_lines.iter().zip(&new_lines).take_while( | (a, b) | a == b).count()
+            .min(old_lines.len() - 1).min(new_lines.len() - 1);
+        let suffix = old_lines[prefix..].iter().rev().zip(new_lines[prefix..].iter().rev()).take_while( | (a, b) | a == b).count();
+        let old_end = old_lines.len() - suffix;
+        let new_end = new_lines.len() - suffix;
+        let (end, replace) = if suffix > 0 {
+            (Position {line_index: old_end, byte_index: 0}, new_lines[prefix..new_end].iter().map( | line | format!("{}\n", line)).collect())
+        }
+        else {
+            (Position {line_index: old_end - 1, byte_index: old_lines[old_end - 1].len()}, new_lines[prefix..new_end].join("\n"))
+        };
+        let cursor = session.selections()[0].cursor.position;
+        session.set_selection(Position {line_index: prefix, byte_index: 0}, Affinity::After, SelectionMode::Simple, NewGroup::Yes);
+        session.move_to(end, Affinity::Before, NewGroup::No);
+        session.paste(replace.into());
+        // put the cursor back where it was, as far as the new text allows
+        let line_index = cursor.line_index.min(new_lines.len() - 1);
+        let line = new_lines[line_index];
+        let mut byte_index = cursor.byte_index.min(line.len());
+        while !line.is_char_boundary(byte_index) {
+            byte_index -= 1;
+        }
+        session.set_selection(Position {line_index, byte_index}, Affinity::After, SelectionMode::Simple, NewGroup::Yes);
+        self.handle_sessions();
+    }
+    
     pub fn clear_decorations(&mut self, file_node_id: &LiveId) {
         // ok lets see if we have a document
         // ifnot, we create a new one
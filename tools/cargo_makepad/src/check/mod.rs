@@ -173,12 +173,54 @@ pub fn handle_check(args: &[String]) -> Result<(), String> {
                 return Err("No crate to check".to_string())
             }
         }
+        "target" =>{
+            check_target(&args[1..])
+        }
         _=>{
             return Err("Unknown command".to_string())
         }
     }
 }
 
+// cargo check for one target, and because the shader generators are built on every host
+// the app itself can then generate all its draw shaders for that target's backends
+fn check_target(args: &[String]) -> Result<(), String> {
+    let mut target = None;
+    let mut shaders = None;
+    let mut rest = Vec::new();
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("--target=") {
+            target = Some(value.to_string());
+        }
+        else if let Some(value) = arg.strip_prefix("--shaders=") {
+            shaders = Some(value.to_string());
+        }
+        else {
+            rest.push(arg.as_str());
+        }
+    }
+    let target = target.ok_or_else( || "check target needs --target=<triple>".to_string()) ?;
+    let cwd = std::env::current_dir().unwrap();
+    
+    let target_arg = format!("--target={}", target);
+    let mut args_out = vec!["check", target_arg.as_str()];
+    args_out.extend_from_slice(&rest);
+    shell_env(&[], &cwd, "cargo", &args_out) ?;
+    
+    if let Some(shaders) = shaders {
+        let check_shaders = format!("--check-shaders={}", shaders);
+        let mut args_out = vec!["run"];
+        args_out.extend_from_slice(&rest);
+        args_out.push("--");
+        args_out.push(&check_shaders);
+        if rest.contains(&"--message-format=json") {
+            args_out.push("--message-format=json");
+        }
+        shell_env(&[], &cwd, "cargo", &args_out) ?;
+    }
+    Ok(())
+}
+
 fn check(toolchain:&str, branch:&str, ty:BuildTy, args: &[String], par:usize) -> (String, String, bool) {
     
     let toolchain = format!("--target={}", toolchain);
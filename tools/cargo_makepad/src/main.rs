@@ -73,6 +73,14 @@ println!("    wasm [options] run <cargo args>              Build and run a wasm
     println!("    android [options] expand-sdk");
     println!("    android [options] remove-sdk-sources");
     println!();
+    println!("Check commands:");
+    println!();
+    println!("    check install-toolchain                      Install all toolchains used by check all");
+    println!("    check all <cargo args>                       Check a project for all supported targets");
+    println!("    check target --target=x [--shaders=x] <cargo args>");
+    println!("                                                 Check a project for one target and then run it with --check-shaders");
+    println!("                                                 to generate every draw shader for the given backends (glsl,metal,hlsl,wgsl)");
+    println!();
     println!("Linux commands:");
     println!();
     println!("    linux apt-get-install-makepad-deps           Call apt-get install with all dependencies needed for makepad.");
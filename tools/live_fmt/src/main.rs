@@ -0,0 +1,87 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use makepad_live_compiler::format_live_design;
+
+// formats the live_design!{} blocks of the given .rs files, or of all .rs files in the given directories
+
+fn main() {
+    let mut check = false;
+    let mut files = Vec::new();
+    for arg in env::args().skip(1) {
+        if arg == "--check" {
+            check = true;
+        }
+        else if arg.starts_with('-') {
+            println!("Usage: makepad-live-fmt [--check] <file or directory>...");
+            println!("  --check  don't write anything, exit with 1 if a file is not formatted");
+            exit(1);
+        }
+        else {
+            collect_rs_files(Path::new(&arg), &mut files);
+        }
+    }
+
+    let mut unformatted = 0;
+    let mut errors = 0;
+    for file in files {
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{}: {}", file.display(), err);
+                errors += 1;
+                continue
+            }
+        };
+        if !source.contains("live_design!") {
+            continue
+        }
+        match format_live_design(&source) {
+            Ok(formatted) if formatted != source => {
+                unformatted += 1;
+                if check {
+                    println!("{} is not formatted", file.display());
+                }
+                else if let Err(err) = fs::write(&file, formatted) {
+                    eprintln!("{}: {}", file.display(), err);
+                    errors += 1;
+                }
+                else {
+                    println!("Formatted {}", file.display());
+                }
+            }
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}: {}", file.display(), err);
+                errors += 1;
+            }
+        }
+    }
+    if errors > 0 || check && unformatted > 0 {
+        exit(1);
+    }
+}
+
+fn collect_rs_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        if path.file_name().map_or(false, | name | name == "target" || name == ".git") {
+            return
+        }
+        let mut entries: Vec<PathBuf> = match fs::read_dir(path) {
+            Ok(iter) => iter.filter_map( | entry | entry.ok().map( | entry | entry.path())).collect(),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                return
+            }
+        };
+        entries.sort();
+        for entry in entries {
+            collect_rs_files(&entry, files);
+        }
+    }
+    else if path.extension().map_or(false, | ext | ext == "rs") {
+        files.push(path.into());
+    }
+}
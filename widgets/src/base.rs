@@ -11,6 +11,7 @@ live_design!{
     import crate::drop_down::DropDownBase;
     import crate::file_tree::FileTreeBase;
     import crate::file_tree::FileTreeNodeBase;
+    import crate::file_dialog::FileDialogViewBase;
     import crate::fold_button::FoldButtonBase;
     import crate::fold_header::FoldHeaderBase;
     import crate::image::ImageBase;
@@ -373,7 +374,6 @@ live_design!{
         }
     }
     
-    MultiWindow = <MultiWindowBase> {}
     View = <ViewBase> {}
 
     SolidView = <ViewBase> {show_bg: true, draw_bg: {
@@ -890,7 +890,6 @@ live_design!{
         }
     }
     
-    MultiWindow = <MultiWindowBase>{}
     PageFlip = <PageFlipBase>{}
     KeyboardView = <KeyboardViewBase>{}
     // todo fix this by allowing reexporting imports
@@ -911,6 +910,7 @@ live_design!{
     DropDownBase = <DropDownBase> {}
     FileTreeBase = <FileTreeBase> {}
     FileTreeNodeBase = <FileTreeNodeBase> {}
+    FileDialogViewBase = <FileDialogViewBase> {}
     FoldButtonBase = <FoldButtonBase> {}
     FoldHeaderBase = <FoldHeaderBase> {}
     ImageBase = <ImageBase> {}
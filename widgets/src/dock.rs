@@ -63,6 +63,7 @@ pub struct Dock {
     #[rust] items: ComponentMap<LiveId, (LiveId, WidgetRef)>,
     #[rust] drop_state: Option<DropPosition>,
     #[rust] dock_item_iter_stack: Vec<(LiveId, usize)>,
+    #[rust] rtl: bool,
 }
 
 impl WidgetNode for Dock{
@@ -349,6 +350,7 @@ impl Dock {
     
     fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
         cx.begin_turtle(walk, self.layout);
+        self.rtl = cx.turtle().is_rtl();
         //self.drop_zones.clear();
     }
     
@@ -719,7 +721,13 @@ impl Dock {
                     let new_split = LiveId::unique();
                     let new_tabs = LiveId::unique();
                     self.set_parent_split(pos.id, new_split);
-                    self.dock_items.insert(new_split, match pos.part {
+                    // right to left a splitter puts its a side on the right
+                    let part = match pos.part {
+                        DropPart::Left if self.rtl => DropPart::Right,
+                        DropPart::Right if self.rtl => DropPart::Left,
+                        part => part
+                    };
+                    self.dock_items.insert(new_split, match part {
                         DropPart::Left => DockItem::Splitter {
                             axis: SplitterAxis::Horizontal,
                             align: SplitterAlign::Weighted(0.5),
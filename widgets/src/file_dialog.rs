@@ -0,0 +1,491 @@
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        makepad_platform::file_dialogs::Filter,
+        button::*,
+        drop_down::*,
+        file_tree::*,
+        portal_list::*,
+        text_input::*,
+        turtle_step::*,
+        view::*,
+        widget::*,
+    },
+};
+
+live_design!{
+    FileDialogViewBase = {{FileDialogView}} {}
+}
+
+struct DirEntry {
+    name: String,
+    is_dir: bool,
+}
+
+/// A file dialog drawn by makepad itself, for the platforms without a native one.
+/// It reports its choice with `Cx::post_file_dialog_result`, like a native dialog would.
+#[derive(Live, LiveHook, Widget)]
+pub struct FileDialogView {
+    #[deref] view: View,
+    #[live] crumb: Option<LivePtr>,
+    #[rust] crumbs: ComponentMap<usize, WidgetRef>,
+    // only set while the dialog is showing
+    #[rust] kind: Option<FileDialogKind>,
+    #[rust] filters: Vec<Filter>,
+    #[rust] filter_index: usize,
+    #[rust] current_dir: PathBuf,
+    #[rust] entries: Vec<DirEntry>,
+    #[rust] selected: Option<usize>,
+    // the areas to focus only exist once the dialog is drawn
+    #[rust] focus_on_draw: bool,
+    #[rust] visible_items: usize,
+    #[rust] tree_roots: Vec<PathBuf>,
+    #[rust] tree_folders: HashMap<PathBuf, Vec<String>>,
+    #[rust] tree_paths: HashMap<LiveId, PathBuf>,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum FileDialogViewAction {
+    Finished,
+    None
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else( || std::env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+fn tree_node_id(root_index: usize, path: &Path) -> LiveId {
+    // the home folder is also somewhere below the root, so the ids are per tree root
+    LiveId::from_str_num(&path.to_string_lossy(), root_index as u64)
+}
+
+fn list_folders(path: &Path) -> Vec<String> {
+    let mut folders = Vec::new();
+    if let Ok(dir) = fs::read_dir(path) {
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with('.') && entry.path().is_dir() {
+                folders.push(name);
+            }
+        }
+    }
+    folders.sort_by_key( | name | name.to_lowercase());
+    folders
+}
+
+fn path_label(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => path.to_string_lossy().to_string()
+    }
+}
+
+pub fn file_dialog_title(kind: FileDialogKind, dialog: &FileDialog) -> String {
+    if let Some(title) = &dialog.title {
+        return title.clone()
+    }
+    match kind {
+        FileDialogKind::SaveFile => "Save File",
+        FileDialogKind::SelectFile => "Open File",
+        FileDialogKind::SaveFolder => "Save Folder",
+        FileDialogKind::SelectFolder => "Select Folder",
+    }.to_string()
+}
+
+impl FileDialogView {
+    pub fn open(&mut self, cx: &mut Cx, kind: FileDialogKind, dialog: &FileDialog) {
+        self.kind = Some(kind);
+        self.filters = if kind.is_folder() {Vec::new()} else {dialog.filters.clone()};
+        self.filter_index = 0;
+        self.tree_folders.clear();
+        self.tree_paths.clear();
+
+        let start_dir = dialog.location.clone().map( | location | {
+            if location.is_dir() {location} else {location.parent().map( | p | p.to_path_buf()).unwrap_or(location)}
+        }).filter( | dir | dir.is_dir())
+            .or_else(home_dir)
+            .or_else( || std::env::current_dir().ok())
+            .unwrap_or_else( || PathBuf::from("/"));
+
+        self.tree_roots.clear();
+        if let Some(home) = home_dir() {
+            self.tree_roots.push(home);
+        }
+        if let Some(root) = start_dir.ancestors().last() {
+            self.tree_roots.push(root.to_path_buf());
+        }
+
+        let mut labels: Vec<String> = self.filters.iter().map( | filter | {
+            let extensions: Vec<String> = filter.extensions.iter().map( | ext | format!("*.{}", ext)).collect();
+            format!("{} ({})", filter.description, extensions.join(", "))
+        }).collect();
+        labels.push("All files".to_string());
+        let filter = self.drop_down(id!(filter));
+        filter.set_labels(labels);
+        filter.set_selected_item(0);
+        self.view(id!(filter_box)).set_visible(!self.filters.is_empty());
+
+        self.view(id!(name_box)).set_visible(kind != FileDialogKind::SelectFolder);
+        self.text_input(id!(name_input)).set_text(if kind.is_save() {dialog.filename.as_deref().unwrap_or("")} else {""});
+        self.button(id!(confirm_button)).set_text(match kind {
+            FileDialogKind::SaveFile | FileDialogKind::SaveFolder => "Save",
+            FileDialogKind::SelectFile => "Open",
+            FileDialogKind::SelectFolder => "Select",
+        });
+
+        self.navigate(cx, start_dir);
+        self.focus_on_draw = true;
+    }
+
+    fn is_filtered_in(&self, name: &str) -> bool {
+        let Some(filter) = self.filters.get(self.filter_index) else {
+            return true
+        };
+        let name = name.to_lowercase();
+        filter.extensions.iter().any( | ext | name.ends_with(&format!(".{}", ext.to_lowercase())))
+    }
+
+    fn read_entries(&mut self) {
+        let is_folder = self.kind.map_or(false, | kind | kind.is_folder());
+        self.entries.clear();
+        if let Ok(dir) = fs::read_dir(&self.current_dir) {
+            for entry in dir.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') {
+                    continue
+                }
+                let is_dir = entry.path().is_dir();
+                if !is_dir && (is_folder || !self.is_filtered_in(&name)) {
+                    continue
+                }
+                self.entries.push(DirEntry {name, is_dir});
+            }
+        }
+        self.entries.sort_by( | a, b | b.is_dir.cmp(&a.is_dir).then_with( || a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+        self.selected = None;
+    }
+
+    fn navigate(&mut self, cx: &mut Cx, dir: PathBuf) {
+        self.current_dir = dir;
+        self.read_entries();
+        self.portal_list(id!(entries)).set_first_id_and_scroll(0, 0.0);
+
+        // unfold the tree down to the new folder
+        if let Some(mut tree) = self.file_tree(id!(folders)).borrow_mut() {
+            for (root_index, root) in self.tree_roots.iter().enumerate() {
+                for ancestor in self.current_dir.ancestors() {
+                    if ancestor.starts_with(root) {
+                        tree.set_folder_is_open(cx, tree_node_id(root_index, ancestor), true, Animate::No);
+                    }
+                }
+            }
+        }
+        self.view.redraw(cx);
+    }
+
+    fn navigate_up(&mut self, cx: &mut Cx) {
+        let Some(parent) = self.current_dir.parent().map( | p | p.to_path_buf()) else {
+            return
+        };
+        let from = path_label(&self.current_dir);
+        self.navigate(cx, parent);
+        if let Some(index) = self.entries.iter().position( | entry | entry.is_dir && entry.name == from) {
+            self.select(cx, index);
+        }
+    }
+
+    fn select(&mut self, cx: &mut Cx, index: usize) {
+        let Some(entry) = self.entries.get(index) else {
+            return
+        };
+        self.selected = Some(index);
+        if !entry.is_dir && self.kind.map_or(false, | kind | !kind.is_folder()) {
+            let name = entry.name.clone();
+            self.text_input(id!(name_input)).set_text(&name);
+        }
+        // keep the selection in view, the last visible item may only be partly showing
+        let list = self.portal_list(id!(entries));
+        let first_id = list.first_id();
+        if index < first_id {
+            list.set_first_id_and_scroll(index, 0.0);
+        }
+        else if self.visible_items > 1 && index + 2 > first_id + self.visible_items {
+            list.set_first_id_and_scroll(index + 2 - self.visible_items, 0.0);
+        }
+        self.view.redraw(cx);
+    }
+
+    // a double click or enter on an entry goes into a folder, or picks a file
+    fn activate(&mut self, cx: &mut Cx, scope: &mut Scope, index: usize) {
+        let Some(entry) = self.entries.get(index) else {
+            return
+        };
+        let path = self.current_dir.join(&entry.name);
+        if entry.is_dir {
+            self.navigate(cx, path);
+        }
+        else if self.kind == Some(FileDialogKind::SelectFile) {
+            self.finish(cx, scope, Some(path));
+        }
+        else {
+            self.confirm(cx, scope);
+        }
+    }
+
+    fn confirm(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(kind) = self.kind else {
+            return
+        };
+        let name = self.text_input(id!(name_input)).text();
+        let name = name.trim();
+        let selected_dir = self.selected.and_then( | index | self.entries.get(index)).filter( | entry | entry.is_dir).map( | entry | self.current_dir.join(&entry.name));
+        match kind {
+            FileDialogKind::SelectFile | FileDialogKind::SaveFile => {
+                if name.is_empty() {
+                    if let Some(dir) = selected_dir {
+                        self.navigate(cx, dir);
+                    }
+                    return
+                }
+                let mut path = self.current_dir.join(name);
+                if path.is_dir() {
+                    self.text_input(id!(name_input)).set_text("");
+                    self.navigate(cx, path);
+                }
+                else if kind == FileDialogKind::SelectFile {
+                    if path.is_file() {
+                        self.finish(cx, scope, Some(path));
+                    }
+                }
+                else {
+                    if path.extension().is_none() {
+                        if let Some(ext) = self.filters.get(self.filter_index).and_then( | filter | filter.extensions.first()) {
+                            path.set_extension(ext);
+                        }
+                    }
+                    self.finish(cx, scope, Some(path));
+                }
+            }
+            FileDialogKind::SelectFolder => {
+                let path = selected_dir.unwrap_or_else( || self.current_dir.clone());
+                self.finish(cx, scope, Some(path));
+            }
+            FileDialogKind::SaveFolder => {
+                let path = if name.is_empty() {self.current_dir.clone()} else {self.current_dir.join(name)};
+                self.finish(cx, scope, Some(path));
+            }
+        }
+    }
+
+    fn finish(&mut self, cx: &mut Cx, scope: &mut Scope, path: Option<PathBuf>) {
+        if let Some(kind) = self.kind.take() {
+            cx.post_file_dialog_result(FileDialogResultEvent {kind, path});
+            cx.widget_action(self.widget_uid(), &scope.path, FileDialogViewAction::Finished);
+        }
+    }
+
+    /// Closes the dialog as if cancel was pressed, for instance when its window went away.
+    pub fn cancel(&mut self, cx: &mut Cx) {
+        if let Some(kind) = self.kind.take() {
+            cx.post_file_dialog_result(FileDialogResultEvent {kind, path: None});
+        }
+    }
+
+    fn handle_key(&mut self, cx: &mut Cx, scope: &mut Scope, ke: &KeyEvent) {
+        let last = self.entries.len().checked_sub(1);
+        match ke.key_code {
+            KeyCode::ArrowUp if ke.modifiers.alt => self.navigate_up(cx),
+            KeyCode::Backspace => self.navigate_up(cx),
+            KeyCode::ArrowDown => if let Some(last) = last {
+                self.select(cx, self.selected.map_or(0, | index | (index + 1).min(last)));
+            }
+            KeyCode::ArrowUp => if let Some(last) = last {
+                self.select(cx, self.selected.map_or(last, | index | index.saturating_sub(1)));
+            }
+            KeyCode::Home => if last.is_some() {
+                self.select(cx, 0);
+            }
+            KeyCode::End => if let Some(last) = last {
+                self.select(cx, last);
+            }
+            KeyCode::ReturnKey => match self.selected {
+                Some(index) => self.activate(cx, scope, index),
+                None => self.confirm(cx, scope)
+            }
+            KeyCode::Escape => self.finish(cx, scope, None),
+            _ => ()
+        }
+    }
+
+    fn draw_crumbs(&mut self, cx: &mut Cx2d, scope: &mut Scope) {
+        let mut ancestors: Vec<PathBuf> = self.current_dir.ancestors().map( | p | p.to_path_buf()).collect();
+        ancestors.reverse();
+        let crumb = self.crumb;
+        for (index, path) in ancestors.iter().enumerate() {
+            let button = self.crumbs.get_or_insert(cx, index, | cx | WidgetRef::new_from_ptr(cx, crumb));
+            button.set_text(&path_label(path));
+            button.draw_all(cx, scope);
+        }
+        self.crumbs.retain_visible();
+    }
+
+    fn draw_tree_folder(&mut self, cx: &mut Cx2d, tree: &mut FileTree, root_index: usize, path: &Path, name: &str) {
+        let node_id = tree_node_id(root_index, path);
+        self.tree_paths.insert(node_id, path.to_path_buf());
+        if tree.begin_folder(cx, node_id, name).is_ok() {
+            // folders are only read once they are opened
+            let folders = self.tree_folders.entry(path.to_path_buf()).or_insert_with( || list_folders(path)).clone();
+            for folder in folders {
+                self.draw_tree_folder(cx, tree, root_index, &path.join(&folder), &folder);
+            }
+            tree.end_folder();
+        }
+    }
+
+    fn draw_entries(&mut self, cx: &mut Cx2d, list: &mut PortalList) {
+        list.set_item_range(cx, 0, self.entries.len());
+        self.visible_items = 0;
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let template = match self.entries.get(item_id) {
+                Some(entry) if entry.is_dir => live_id!(Folder),
+                Some(_) => live_id!(File),
+                None => live_id!(Empty)
+            };
+            let item = list.item(cx, item_id, template).unwrap();
+            let name = self.entries.get(item_id).map_or("", | entry | entry.name.as_str());
+            item.apply_over(cx, live!{
+                name = {text: (name)}
+                draw_bg: {
+                    is_even: (if item_id & 1 == 0 {1.0} else {0.0})
+                    selected: (if self.selected == Some(item_id) {1.0} else {0.0})
+                }
+            });
+            item.draw_all(cx, &mut Scope::empty());
+            self.visible_items += 1;
+        }
+    }
+}
+
+impl Widget for FileDialogView {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step() {
+            if let Some(mut list) = step.as_portal_list().borrow_mut() {
+                self.draw_entries(cx, &mut *list);
+            }
+            else if let Some(mut tree) = step.as_file_tree().borrow_mut() {
+                for (root_index, root) in self.tree_roots.clone().iter().enumerate() {
+                    self.draw_tree_folder(cx, &mut *tree, root_index, root, &path_label(root));
+                }
+            }
+            else if step.as_turtle_step().borrow().is_some() {
+                self.draw_crumbs(cx, scope);
+            }
+        }
+        if self.focus_on_draw {
+            self.focus_on_draw = false;
+            match self.text_input(id!(name_input)).borrow() {
+                Some(name_input) if self.kind.map_or(false, | kind | kind.is_save()) => name_input.set_key_focus(cx),
+                _ => cx.set_key_focus(self.view.area())
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        for crumb in self.crumbs.values_mut() {
+            crumb.handle_event(cx, event, scope);
+        }
+        if self.kind.is_none() {
+            return
+        }
+
+        if let Hit::KeyDown(ke) = event.hits(cx, self.view.area()) {
+            self.handle_key(cx, scope, &ke);
+        }
+
+        if let Event::Actions(actions) = event {
+            let mut crumb_clicked = None;
+            for (index, crumb) in self.crumbs.iter() {
+                if crumb.as_button().clicked(actions) {
+                    crumb_clicked = Some(*index);
+                }
+            }
+            if let Some(index) = crumb_clicked {
+                let depth = self.current_dir.ancestors().count();
+                if let Some(dir) = self.current_dir.ancestors().nth(depth - 1 - index).map( | p | p.to_path_buf()) {
+                    self.navigate(cx, dir);
+                }
+            }
+
+            if let Some(node_id) = self.file_tree(id!(folders)).folder_clicked(actions) {
+                if let Some(dir) = self.tree_paths.get(&node_id).cloned() {
+                    self.navigate(cx, dir);
+                }
+            }
+
+            let list = self.portal_list(id!(entries));
+            for (item_id, item) in list.items_with_actions(actions) {
+                if let Some(fd) = item.as_view().finger_down(actions) {
+                    cx.set_key_focus(self.view.area());
+                    self.select(cx, item_id);
+                    if fd.tap_count == 2 {
+                        self.activate(cx, scope, item_id);
+                    }
+                }
+            }
+
+            if let Some(index) = self.drop_down(id!(filter)).selected(actions) {
+                self.filter_index = index;
+                self.read_entries();
+                self.view.redraw(cx);
+            }
+
+            let name_input = self.text_input(id!(name_input));
+            if name_input.returned(actions).is_some() {
+                self.selected = None;
+                self.confirm(cx, scope);
+            }
+            else if let TextInputAction::Escape = actions.find_widget_action_cast(name_input.widget_uid()) {
+                self.finish(cx, scope, None);
+            }
+
+            if self.button(id!(confirm_button)).clicked(actions) {
+                self.confirm(cx, scope);
+            }
+            if self.button(id!(cancel_button)).clicked(actions) {
+                self.finish(cx, scope, None);
+            }
+        }
+    }
+}
+
+impl FileDialogViewRef {
+    pub fn open(&self, cx: &mut Cx, kind: FileDialogKind, dialog: &FileDialog) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.open(cx, kind, dialog);
+        }
+    }
+
+    pub fn cancel(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.cancel(cx);
+        }
+    }
+
+    pub fn finished(&self, actions: &Actions) -> bool {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FileDialogViewAction::Finished = item.cast() {
+                return true
+            }
+        }
+        false
+    }
+}
+
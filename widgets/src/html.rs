@@ -63,15 +63,20 @@ pub struct Html {
 
     /// The stack of list levels encountered so far, used to track nested lists.
     #[rust] list_stack: Vec<ListLevel>,
+    /// The CSS styles of the elements in `doc`, from `<style>` blocks and `style` attributes.
+    #[rust] styles: Vec<Option<CssStyle>>,
+    /// The style changes applied for each currently open element, undone at its close tag.
+    #[rust] style_stack: Vec<AppliedStyle>,
 }
 
 // alright lets parse the HTML
 impl LiveHook for Html {
     fn after_apply_from(&mut self, _cx: &mut Cx, _apply:&mut Apply) {
         let mut errors = Some(Vec::new());
-        let new_doc = parse_html(&*self.body, &mut errors);
+        let new_doc = parse_html_fragment(&*self.body, live_id!(body), &mut errors);
         if new_doc != self.doc{
             self.doc = new_doc;
+            self.styles = self.doc.style_sheet().compute_styles(&self.doc);
             self.text_flow.clear_items();
         }
         if errors.as_ref().unwrap().len()>0{
@@ -311,6 +316,88 @@ impl Html {
         None
     }
     
+    fn open_style(cx: &mut Cx2d, tf: &mut TextFlow, style: Option<&CssStyle>) -> AppliedStyle {
+        let mut applied = AppliedStyle::default();
+        let Some(style) = style else { return applied };
+        if let Some(color) = style.color {
+            tf.font_colors.push(vec4(color.r, color.g, color.b, color.a));
+            applied.color = true;
+        }
+        if let Some(font_size) = style.font_size {
+            match font_size {
+                CssFontSize::Scale(scale) => tf.push_size_rel_scale(scale),
+                CssFontSize::AbsScale(scale) => tf.push_size_abs_scale(scale),
+            }
+            applied.font_size = true;
+        }
+        match style.bold {
+            Some(true) => {
+                tf.bold.push();
+                applied.bold = true;
+            }
+            Some(false) => {
+                // font-weight: normal inside a bold element
+                applied.saved_bold = Some(tf.bold.value());
+                tf.bold.clear();
+            }
+            None => ()
+        }
+        let has_margin = style.margin_top.is_some() || style.margin_right.is_some()
+            || style.margin_bottom.is_some() || style.margin_left.is_some();
+        if has_margin || style.text_align.is_some() {
+            let em = tf.current_font_size();
+            let length = |length: Option<CssLength>| match length {
+                Some(CssLength::Px(px)) => px,
+                Some(CssLength::Em(scale)) => scale * em,
+                None => 0.0,
+            };
+            let margin = Margin {
+                left: length(style.margin_left),
+                top: length(style.margin_top),
+                right: length(style.margin_right),
+                bottom: length(style.margin_bottom),
+            };
+            let align = match style.text_align {
+                Some(CssTextAlign::Center) => Align { x: 0.5, y: 0.0 },
+                Some(CssTextAlign::Right) => Align { x: 1.0, y: 0.0 },
+                _ => Align { x: 0.0, y: 0.0 },
+            };
+            cx.turtle_new_line();
+            tf.begin_block(cx, margin, align);
+            applied.block = true;
+        }
+        else if style.display == Some(CssDisplay::Block) {
+            cx.turtle_new_line();
+            applied.new_line = true;
+        }
+        applied
+    }
+
+    fn close_style(cx: &mut Cx2d, tf: &mut TextFlow, applied: AppliedStyle) {
+        if applied.block {
+            tf.end_block(cx);
+            cx.turtle_new_line();
+        }
+        if applied.new_line {
+            cx.turtle_new_line();
+        }
+        if let Some(saved_bold) = applied.saved_bold {
+            tf.bold.clear();
+            for _ in 0..saved_bold {
+                tf.bold.push();
+            }
+        }
+        if applied.bold {
+            tf.bold.pop();
+        }
+        if applied.font_size {
+            tf.font_sizes.pop();
+        }
+        if applied.color {
+            tf.font_colors.pop();
+        }
+    }
+    
     pub fn handle_text_node(
         cx: &mut Cx2d,
         tf: &mut TextFlow,
@@ -344,8 +431,15 @@ impl Widget for Html {
         // alright lets iterate the html doc and draw it
         let mut node = self.doc.new_walker();
         let mut auto_id = 0;
+        self.style_stack.clear();
         while !node.done() {
             let mut trim = TrimWhitespaceInText::default();
+            let open_style = node.open_tag_lc().map(|_| self.styles.get(node.index).and_then(|s| s.as_ref()));
+            if let Some(Some(CssStyle{display: Some(CssDisplay::None), ..})) = open_style {
+                node.jump_to_close();
+                node.walk();
+                continue;
+            }
             match Self::handle_open_tag(cx, tf, &mut node, &mut self.list_stack, &self.ul_markers, &self.ol_markers, &self.ol_separator) {
                 (Some(_), _tws) => {
                     handle_custom_widget(cx, scope, tf, &self.doc, &mut node, &mut auto_id); 
@@ -354,6 +448,15 @@ impl Widget for Html {
                     trim = tws;
                 }
             }
+            // css is applied inside the element's own styling so it can override it
+            if let Some(style) = open_style {
+                self.style_stack.push(Self::open_style(cx, tf, style));
+            }
+            if node.close_tag_lc().is_some() {
+                if let Some(applied) = self.style_stack.pop() {
+                    Self::close_style(cx, tf, applied);
+                }
+            }
             match Self::handle_close_tag(cx, tf, &mut node, &mut self.list_stack) {
                 _ => ()
             }
@@ -371,7 +474,8 @@ impl Widget for Html {
     fn set_text(&mut self, v:&str){
         self.body = Rc::new(v.to_string());
         let mut errors = Some(Vec::new());
-        self.doc = parse_html(&*self.body, &mut errors);
+        self.doc = parse_html_fragment(&*self.body, live_id!(body), &mut errors);
+        self.styles = self.doc.style_sheet().compute_styles(&self.doc);
         if errors.as_ref().unwrap().len()>0{
             log!("HTML parser returned errors {:?}", errors)
         }
@@ -477,6 +581,21 @@ impl Widget for HtmlLink {
 }
 
 
+/// The changes made to the `TextFlow` for an element's CSS style.
+/// `display: inline` is accepted but doesn't remove the line breaks of block elements like `p`.
+#[derive(Debug, Default)]
+struct AppliedStyle {
+    color: bool,
+    font_size: bool,
+    bold: bool,
+    /// The bold count to restore after a `font-weight: normal` element.
+    saved_bold: Option<usize>,
+    /// Whether a block was started for margins or text alignment.
+    block: bool,
+    /// Whether an inline element was made `display: block`.
+    new_line: bool,
+}
+
 /// The format and metadata of a list at a given nesting level.
 #[derive(Debug)]
 struct ListLevel {
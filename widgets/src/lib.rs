@@ -48,6 +48,7 @@ pub mod page_flip;
 pub mod keyboard_view;
 pub mod flat_list;
 pub mod file_tree;
+pub mod file_dialog;
 pub mod slides_view;
 pub mod color_picker;
 pub mod root;
@@ -67,6 +68,7 @@ pub mod data_binding;
 
 pub mod base;
 pub mod theme_desktop_dark;
+pub mod theme;
 pub mod image_cache;
 pub mod bare_step;
 pub mod turtle_step;
@@ -106,10 +108,12 @@ pub use crate::{
     expandable_panel::*,
     window::*,
     multi_window::*,
+    file_dialog::*,
     scroll_bars::{ScrollBars},
     scroll_shadow::{DrawScrollShadow},
     scroll_bar::{ScrollBar},
     slides_view::{SlidesView},
+    theme::{ThemeSet, CxThemeExt},
     widget_match_event::WidgetMatchEvent,
     widget::{
         WidgetSet,
@@ -176,6 +180,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::dock::live_design(cx);
     crate::color_picker::live_design(cx);
     crate::file_tree::live_design(cx);
+    crate::file_dialog::live_design(cx);
     crate::slides_view::live_design(cx);
     crate::tab_close_button::live_design(cx);
     crate::keyboard_view::live_design(cx);
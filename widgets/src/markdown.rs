@@ -40,6 +40,8 @@ impl Widget for Markdown {
         let tf = &mut self.text_flow;
         tf.begin(cx, walk); 
         // alright lets walk the markdown
+        let mut in_header_row = false;
+        let mut in_footnotes = false;
         for node in &self.doc.nodes{
             match node{
                 MarkdownNode::BeginHead{level}=>{
@@ -108,6 +110,12 @@ impl Widget for Markdown {
                 MarkdownNode::EndUnderline=>{
                     tf.underline.pop();
                 },
+                MarkdownNode::BeginStrikethrough=>{
+                    tf.strikethrough.push();
+                },
+                MarkdownNode::EndStrikethrough=>{
+                    tf.strikethrough.pop();
+                },
                 MarkdownNode::BeginInlineCode=>{
                     tf.fixed.push();
                     tf.inline_code.push();     
@@ -148,6 +156,60 @@ impl Widget for Markdown {
                 MarkdownNode::Text{start, end}=>{
                     tf.draw_text(cx, &self.doc.decoded[*start..*end]);
                 }
+                MarkdownNode::BeginTable{columns}=>{
+                    cx.turtle_new_line_with_spacing(self.paragraph_spacing);
+                    tf.begin_table(cx, *columns);
+                },
+                MarkdownNode::EndTable=>{
+                    tf.end_table(cx);
+                },
+                MarkdownNode::BeginTableRow{header}=>{
+                    in_header_row = *header;
+                    if in_header_row{
+                        tf.bold.push();
+                    }
+                    tf.begin_table_row(cx, *header);
+                },
+                MarkdownNode::EndTableRow=>{
+                    tf.end_table_row(cx);
+                    if in_header_row{
+                        tf.bold.pop();
+                    }
+                },
+                MarkdownNode::BeginTableCell{align}=>{
+                    let x = match align{
+                        MarkdownTableAlign::None | MarkdownTableAlign::Left => 0.0,
+                        MarkdownTableAlign::Center => 0.5,
+                        MarkdownTableAlign::Right => 1.0,
+                    };
+                    tf.begin_table_cell(cx, Align{x, y:0.0});
+                },
+                MarkdownNode::EndTableCell=>{
+                    tf.end_table_cell(cx);
+                },
+                MarkdownNode::TaskCheckbox{checked}=>{
+                    tf.fixed.push();
+                    tf.draw_text(cx, if *checked{"[x] "}else{"[ ] "});
+                    tf.fixed.pop();
+                },
+                MarkdownNode::FootnoteRef{number, ..}=>{
+                    tf.push_size_rel_scale(0.7);
+                    tf.draw_text(cx, &format!("[{}]", number));
+                    tf.font_sizes.pop();
+                },
+                MarkdownNode::BeginFootnote{number, ..}=>{
+                    cx.turtle_new_line_with_spacing(self.paragraph_spacing);
+                    if !in_footnotes{
+                        in_footnotes = true;
+                        tf.sep(cx);
+                        cx.turtle_new_line();
+                    }
+                    tf.push_size_rel_scale(0.85);
+                    tf.draw_text(cx, &format!("{}.", number));
+                },
+                MarkdownNode::EndFootnote=>{
+                    tf.font_sizes.pop();
+                },
             }
         }
         
@@ -4,6 +4,7 @@ use {
         widget::*,
         makepad_derive_widget::*,
         window::*,
+        file_dialog::*,
         makepad_draw::*,
     }
 };
@@ -16,6 +17,7 @@ live_design!{
 pub struct MultiWindow {
     #[rust] draw_state: DrawStateWrap<DrawState>,
     #[rust] windows: ComponentMap<LiveId, Window>,
+    #[live] file_dialog: Option<LivePtr>,
 }
 
 impl LiveHook for MultiWindow {
@@ -61,12 +63,52 @@ impl WidgetNode for MultiWindow{
         
 }
 
+impl MultiWindow {
+    fn open_file_dialog(&mut self, cx: &mut Cx, e: &FileDialogRequestEvent) {
+        e.handled.set(true);
+        // there is one dialog at a time, like a native one would be modal
+        if self.windows.get(&live_id!(file_dialog)).is_some() {
+            cx.post_file_dialog_result(FileDialogResultEvent {kind: e.kind, path: None});
+            return
+        }
+        let file_dialog = self.file_dialog;
+        let window = self.windows.get_or_insert(cx, live_id!(file_dialog), | cx | Window::new_from_ptr(cx, file_dialog));
+        let title = file_dialog_title(e.kind, &e.dialog);
+        window.apply_over(cx, live!{window: {title: (title)}});
+        window.file_dialog_view(id!(dialog)).open(cx, e.kind, &e.dialog);
+        window.redraw(cx);
+    }
+    
+    fn handle_file_dialog_actions(&mut self, cx: &mut Cx, actions: &Actions) {
+        let Some(window) = self.windows.get_mut(&live_id!(file_dialog)) else {
+            return
+        };
+        let dialog = window.file_dialog_view(id!(dialog));
+        let closed = matches!(actions.find_widget_action_cast(window.widget_uid()), WindowAction::WindowClosed);
+        if closed {
+            dialog.cancel(cx);
+        }
+        if closed || dialog.finished(actions) {
+            window.close(cx);
+            self.windows.remove(&live_id!(file_dialog));
+        }
+    }
+}
+
 impl Widget for MultiWindow {
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if let Event::FileDialogRequest(e) = event {
+            if self.file_dialog.is_some() && !cx.os_type().is_single_window() {
+                self.open_file_dialog(cx, e);
+            }
+        }
         for window in self.windows.values_mut() {
             window.handle_event(cx, event, scope);
         }
+        if let Event::Actions(actions) = event {
+            self.handle_file_dialog_actions(cx, actions);
+        }
     }
     
      fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, _walk: Walk) -> DrawStep {
@@ -63,6 +63,7 @@ pub struct Splitter {
     #[live(SplitterAlign::Weighted(0.5))] pub align: SplitterAlign,
     #[rust] rect: Rect,
     #[rust] position: f64,
+    #[rust] rtl: bool,
     #[rust] drag_start_align: Option<SplitterAlign>,
     #[rust] area_a: Area,
     #[rust] area_b: Area,
@@ -126,6 +127,8 @@ impl Widget for Splitter {
             Hit::FingerMove(f) => {
                 if let Some(drag_start_align) = self.drag_start_align {
                     let delta = match self.axis {
+                        // right to left the a side is on the right
+                        SplitterAxis::Horizontal if self.rtl => f.abs_start.x - f.abs.x,
                         SplitterAxis::Horizontal => f.abs.x - f.abs_start.x,
                         SplitterAxis::Vertical => f.abs.y - f.abs_start.y,
                     };
@@ -200,6 +203,7 @@ impl Splitter {
         }
         
         self.rect = cx.turtle().padded_rect();
+        self.rtl = cx.turtle().is_rtl();
         self.position = self.align.to_position(self.axis, self.rect);
         
         let walk = match self.axis {
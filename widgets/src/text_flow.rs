@@ -23,7 +23,9 @@ pub enum FlowBlockType {
     Code = shader_enum(3),
     InlineCode = shader_enum(4),
     Underline = shader_enum(5),
-    Strikethrough = shader_enum(6)
+    Strikethrough = shader_enum(6),
+    TableRow = shader_enum(7),
+    TableHeader = shader_enum(8)
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -83,6 +85,7 @@ pub struct TextFlow {
     #[rust] pub underline: StackCounter,
     #[rust] pub strikethrough: StackCounter,
     #[rust] pub inline_code: StackCounter,
+    #[rust] pub font_colors: SmallVec<[Vec4;4]>,
 
     #[layout] layout: Layout,
     
@@ -95,6 +98,10 @@ pub struct TextFlow {
     #[live] list_item_walk: Walk,
     #[live] inline_code_padding: Padding,
     #[live] inline_code_margin: Margin,
+    #[live] table_walk: Walk,
+    #[live] table_layout: Layout,
+    #[live] table_cell_layout: Layout,
+    #[rust] table_column_widths: SmallVec<[f64;2]>,
         
     #[redraw] #[rust] area:Area,
     #[rust] draw_state: DrawStateWrap<DrawState>,
@@ -190,6 +197,8 @@ impl TextFlow{
         self.top_drop.clear();
         self.combine_spaces.clear();
         self.ignore_newlines.clear();
+        self.table_column_widths.clear();
+        self.font_colors.clear();
     }
     
     pub fn push_size_rel_scale(&mut self, scale: f64){
@@ -204,6 +213,10 @@ impl TextFlow{
         );
     }
     
+    pub fn current_font_size(&self)->f64{
+        *self.font_sizes.last().unwrap_or(&self.font_size)
+    }
+    
     pub fn end(&mut self, cx: &mut Cx2d){
         // lets end the turtle with how far we walked
         cx.end_turtle_with_area(&mut self.area);
@@ -265,6 +278,58 @@ impl TextFlow{
         self.draw_block.end(cx);
     }
     
+    pub fn begin_table(&mut self, cx:&mut Cx2d, columns:usize){
+        cx.begin_turtle(self.table_walk, self.table_layout);
+        // columns share the available width equally
+        let mut width = cx.turtle().width_left();
+        if width.is_nan(){
+            let fs = self.font_sizes.last().unwrap_or(&self.font_size);
+            self.draw_normal.text_style.font_size = *fs;
+            width = self.draw_normal.get_font_size() * 10.0 * columns as f64;
+        }
+        self.table_column_widths.push(width / columns.max(1) as f64);
+    }
+    
+    pub fn end_table(&mut self, cx:&mut Cx2d){
+        self.table_column_widths.pop();
+        cx.end_turtle();
+    }
+    
+    pub fn begin_table_row(&mut self, cx:&mut Cx2d, header:bool){
+        self.draw_block.block_type = if header{FlowBlockType::TableHeader}else{FlowBlockType::TableRow};
+        self.draw_block.begin(cx, Walk::fill_fit(), Layout::flow_right());
+        self.area_stack.push(self.draw_block.draw_vars.area);
+    }
+    
+    pub fn end_table_row(&mut self, cx:&mut Cx2d){
+        self.draw_block.draw_vars.area = self.area_stack.pop().unwrap();
+        self.draw_block.end(cx);
+    }
+    
+    pub fn begin_table_cell(&mut self, cx:&mut Cx2d, align:Align){
+        let width = *self.table_column_widths.last().unwrap_or(&0.0);
+        cx.begin_turtle(Walk::size(Size::Fixed(width), Size::Fit), Layout{
+            align,
+            ..self.table_cell_layout
+        });
+    }
+    
+    pub fn end_table_cell(&mut self, cx:&mut Cx2d){
+        cx.end_turtle();
+    }
+    
+    /// A full width block with a margin and the text aligned per line
+    pub fn begin_block(&mut self, cx:&mut Cx2d, margin:Margin, align:Align){
+        cx.begin_turtle(Walk{margin, ..Walk::fill_fit()}, Layout{
+            align,
+            ..self.layout
+        });
+    }
+    
+    pub fn end_block(&mut self, cx:&mut Cx2d){
+        cx.end_turtle();
+    }
+    
     pub fn item(&mut self, cx: &mut Cx, entry_id: LiveId, template: LiveId) -> Option<WidgetRef> {
         if let Some(ptr) = self.templates.get(&template) {
             let entry = self.items.get_or_insert(cx, (entry_id, template), | cx | {
@@ -316,6 +381,10 @@ impl TextFlow{
             dt.text_style.font_size = *font_size;
             dt.ignore_newlines = *self.ignore_newlines.last().unwrap_or(&true);
             dt.combine_spaces = *self.combine_spaces.last().unwrap_or(&true);
+            let color = dt.color;
+            if let Some(font_color) = self.font_colors.last(){
+                dt.color = *font_color;
+            }
             //if let Some(font) = self.font
             // the turtle is at pos X so we walk it.
             if self.inline_code.value() > 0{
@@ -354,6 +423,7 @@ impl TextFlow{
             else{
                 dt.draw_walk_word(cx, text);
             }
+            dt.color = color;
         }
     }
 }
@@ -70,7 +70,8 @@ pub struct TextInput {
     #[rust] undo_stack: Vec<UndoItem>,
     #[rust] redo_stack: Vec<UndoItem>,
     #[rust] cursor_tail: usize,
-    #[rust] cursor_head: usize
+    #[rust] cursor_head: usize,
+    #[rust] rtl: bool
 }
 
 impl Widget for TextInput {
@@ -168,24 +169,22 @@ impl Widget for TextInput {
                     self.cursor_head = self.text.chars().count();
                     self.draw_bg.redraw(cx);
                 }
+                // the arrows move visually, so right to left they swap
                 KeyCode::ArrowLeft => if !ke.modifiers.logo {
-                                        
-                    self.undo_id += 1;
-                    if self.cursor_head>0 {
-                        self.cursor_head -= 1;
+                    if self.rtl {
+                        self.cursor_forward(ke.modifiers.shift);
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    else {
+                        self.cursor_back(ke.modifiers.shift);
                     }
                     self.draw_bg.redraw(cx);
                 },
                 KeyCode::ArrowRight => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    if self.cursor_head < self.text.chars().count() {
-                        self.cursor_head += 1;
+                    if self.rtl {
+                        self.cursor_back(ke.modifiers.shift);
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    else {
+                        self.cursor_forward(ke.modifiers.shift);
                     }
                     self.draw_bg.redraw(cx);
                 }
@@ -417,6 +416,26 @@ impl TextInput {
         }
     }
     
+    fn cursor_back(&mut self, select: bool) {
+        self.undo_id += 1;
+        if self.cursor_head>0 {
+            self.cursor_head -= 1;
+        }
+        if !select {
+            self.cursor_tail = self.cursor_head;
+        }
+    }
+    
+    fn cursor_forward(&mut self, select: bool) {
+        self.undo_id += 1;
+        if self.cursor_head < self.text.chars().count() {
+            self.cursor_head += 1;
+        }
+        if !select {
+            self.cursor_tail = self.cursor_head;
+        }
+    }
+    
     pub fn select_all(&mut self) {
         self.cursor_tail = 0;
         self.cursor_head = self.text.chars().count();
@@ -574,6 +593,7 @@ impl TextInput {
         
         self.draw_bg.begin(cx, walk, self.layout);
         let turtle_rect = cx.turtle().rect();
+        self.rtl = cx.turtle().is_rtl();
         
         // this makes sure selection goes behind the text
         self.draw_select.append_to_draw_call(cx);
@@ -0,0 +1,56 @@
+use crate::makepad_platform::*;
+
+/// The named sets of design tokens the desktop theme can switch between at runtime.
+/// Dark is the theme as written in `theme_desktop_dark`, the others override some of its
+/// `THEME_*` tokens, and everything derived from those follows along.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThemeSet {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemeSet {
+    pub fn tokens(&self) -> Vec<(LiveId, LiveValue)> {
+        match self {
+            Self::Dark => vec![],
+            // the palette is built from mixes of W(hite) and B(lack), swapping them flips it
+            Self::Light => vec![
+                (live_id!(THEME_COLOR_W), LiveValue::Color(0x000000FF)),
+                (live_id!(THEME_COLOR_W_H), LiveValue::Color(0x00000000)),
+                (live_id!(THEME_COLOR_B), LiveValue::Color(0xFFFFFFFF)),
+                (live_id!(THEME_COLOR_B_H), LiveValue::Color(0xFFFFFF00)),
+                (live_id!(THEME_COLOR_FLOATING_BG), LiveValue::Color(0xDCDCDCFF)),
+                (live_id!(THEME_COLOR_AMOUNT_DEFAULT_BIG), LiveValue::Color(0x555555FF)),
+                (live_id!(THEME_COLOR_SLIDER_BIG_NUB_TOP), LiveValue::Color(0xFFFFFFFF)),
+                (live_id!(THEME_COLOR_SLIDER_BIG_NUB_TOP_HOVER), LiveValue::Color(0xFFFFFFFF)),
+                (live_id!(THEME_COLOR_SLIDER_BIG_NUB_BOTTOM), LiveValue::Color(0xC8C8C8FF)),
+                (live_id!(THEME_COLOR_SLIDER_BIG_NUB_BOTTOM_HOVER), LiveValue::Color(0xD8D8D8FF)),
+            ],
+            Self::HighContrast => vec![
+                (live_id!(THEME_COLOR_CONTRAST), LiveValue::Float64(2.5)),
+                (live_id!(THEME_FONT_SIZE_BASE), LiveValue::Float64(8.5)),
+                (live_id!(THEME_COLOR_FLOATING_BG), LiveValue::Color(0x202020FF)),
+            ]
+        }
+    }
+}
+
+pub trait CxThemeExt {
+    /// Switches the desktop theme to a named set of tokens.
+    fn set_theme(&mut self, theme: ThemeSet);
+    /// Sets the `THEME_*` tokens of the desktop theme, replacing the ones set before.
+    /// Start from `ThemeSet::tokens` to change a few tokens of a named set.
+    fn set_theme_tokens(&mut self, tokens: Vec<(LiveId, LiveValue)>);
+}
+
+impl CxThemeExt for Cx {
+    fn set_theme(&mut self, theme: ThemeSet) {
+        self.set_theme_tokens(theme.tokens())
+    }
+
+    fn set_theme_tokens(&mut self, tokens: Vec<(LiveId, LiveValue)>) {
+        self.set_live_tokens(LiveModuleId::from_str("makepad_widgets::theme_desktop_dark").unwrap(), tokens)
+    }
+}
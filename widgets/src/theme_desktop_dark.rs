@@ -724,6 +724,13 @@ live_design! {
             margin: <THEME_MSPACE_V_3> {}
         }
 
+        table_walk: { width: Fill, height: Fit }
+        table_layout: { flow: Down }
+        table_cell_layout: {
+            flow: RightWrap,
+            padding: <THEME_MSPACE_1> {}
+        }
+
         draw_block: {
             line_color: (THEME_COLOR_TEXT_DEFAULT)
             sep_color: (THEME_COLOR_DIVIDER)
@@ -808,6 +815,36 @@ live_design! {
                         sdf.fill(self.line_color);
                         return sdf.result;
                     }
+                    FlowBlockType::TableRow => {
+                        sdf.box(
+                            0.,
+                            self.rect_size.y - 1.,
+                            self.rect_size.x,
+                            1.,
+                            0.
+                        );
+                        sdf.fill(self.sep_color);
+                        return sdf.result;
+                    }
+                    FlowBlockType::TableHeader => {
+                        sdf.box(
+                            0.,
+                            0.,
+                            self.rect_size.x,
+                            self.rect_size.y,
+                            0.
+                        );
+                        sdf.fill(self.code_color);
+                        sdf.box(
+                            0.,
+                            self.rect_size.y - 2.,
+                            self.rect_size.x,
+                            2.,
+                            0.
+                        );
+                        sdf.fill(self.sep_color);
+                        return sdf.result;
+                    }
                 }
                 return #f00
             }
@@ -4584,6 +4621,116 @@ live_design! {
         }
     }
 
+    FileDialogEntry = <View> {
+        width: Fill, height: (THEME_DATA_ITEM_HEIGHT)
+        flow: Right
+        align: { y: 0.5 }
+        padding: <THEME_MSPACE_H_2> {}
+        spacing: (THEME_SPACE_2)
+        cursor: Default
+        grab_key_focus: false
+        show_bg: true
+        draw_bg: {
+            instance is_even: 0.0
+            instance selected: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        THEME_COLOR_BG_EVEN,
+                        THEME_COLOR_BG_ODD,
+                        self.is_even
+                    ),
+                    THEME_COLOR_CTRL_SELECTED,
+                    self.selected
+                );
+            }
+        }
+        icon = <View> {
+            width: 12, height: 12
+            show_bg: true
+        }
+        name = <Label> { width: Fill, text: "" }
+    }
+
+    FileDialogView = <FileDialogViewBase> {
+        width: Fill, height: Fill
+        flow: Down
+        padding: <THEME_MSPACE_2> {}
+        spacing: (THEME_SPACE_2)
+        show_bg: true
+        draw_bg: { color: (THEME_COLOR_BG_CONTAINER) }
+
+        crumb: <Button> { padding: <THEME_MSPACE_1> {} }
+
+        breadcrumbs = <TurtleStep> {
+            width: Fill, height: Fit
+            flow: Right
+            spacing: (THEME_SPACE_1)
+        }
+        body = <View> {
+            width: Fill, height: Fill
+            flow: Right
+            spacing: (THEME_SPACE_2)
+            folders = <FileTree> { width: 220, height: Fill }
+            entries = <PortalList> {
+                width: Fill, height: Fill
+                grab_key_focus: false
+                drag_scrolling: false
+                Folder = <FileDialogEntry> {
+                    icon = {
+                        draw_bg: {
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                                sdf.box(0., 2., 12., 9., 1.)
+                                sdf.fill(THEME_COLOR_TEXT_META)
+                                return sdf.result
+                            }
+                        }
+                    }
+                }
+                File = <FileDialogEntry> {
+                    icon = {
+                        draw_bg: {
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                                sdf.box(2., 0.5, 8., 11., 1.)
+                                sdf.stroke(THEME_COLOR_TEXT_META, 1.)
+                                return sdf.result
+                            }
+                        }
+                    }
+                }
+                Empty = <FileDialogEntry> {}
+            }
+        }
+        footer = <View> {
+            width: Fill, height: Fit
+            flow: Right
+            align: { x: 1.0, y: 0.5 }
+            spacing: (THEME_SPACE_2)
+            name_box = <View> {
+                width: Fill, height: Fit
+                name_input = <TextInput> { width: Fill, empty_message: "File name" }
+            }
+            filter_box = <View> {
+                width: Fit, height: Fit
+                filter = <DropDown> {}
+            }
+            cancel_button = <Button> { text: "Cancel" }
+            confirm_button = <Button> { text: "Open" }
+        }
+    }
+
+    FileDialogWindow = <Window> {
+        window: { inner_size: vec2(760, 480) }
+        body = {
+            dialog = <FileDialogView> {}
+        }
+    }
+
+    // backends without a native file dialog show this one in a window of its own
+    MultiWindow = <MultiWindowBase> { file_dialog: <FileDialogWindow> {} }
+
     Root = <RootBase> { design_window = <Designer> {} }
 
 }
\ No newline at end of file
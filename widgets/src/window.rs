@@ -28,12 +28,13 @@ pub struct Window {
     #[live] window: WindowHandle,
     #[live] stdin_size: DrawColor,
     #[rust(Overlay::new(cx))] overlay: Overlay,
-    #[rust(DrawList2d::new(cx))] main_draw_list: DrawList2d,
+    // redrawn as well, so windows opened after startup get their first draw
+    #[redraw] #[rust(DrawList2d::new(cx))] main_draw_list: DrawList2d,
     #[live] pass: Pass,
     #[rust(Texture::new(cx))] depth_texture: Texture,
     #[live] hide_caption_on_fullscreen: bool, 
     #[live] show_performance_view: bool,
-    #[deref] view: View,
+    #[redraw] #[deref] view: View,
     // #[rust(WindowMenu::new(cx))] _window_menu: WindowMenu,
     /*#[rust(Menu::main(vec![
         Menu::sub("App", vec![
@@ -179,6 +180,10 @@ impl Window {
         self.main_draw_list.end(cx);
         cx.end_pass(&self.pass);
     }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        self.window.close(cx);
+    }
 }
 
 impl Widget for Window {
@@ -0,0 +1,146 @@
+use {
+    std::{cell::RefCell, fs, path::PathBuf, rc::Rc},
+    makepad_widgets::*,
+};
+
+live_design!{
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    WithDialog = <MultiWindow> {
+        main = <Window> { window: {inner_size: vec2(200, 100)} }
+    }
+
+    WithoutDialog = <MultiWindowBase> {
+        main = <Window> { window: {inner_size: vec2(200, 100)} }
+    }
+
+    WithoutMultiWindow = <Window> { window: {inner_size: vec2(200, 100)} }
+}
+
+/// A headless app with the given root widget, and the file dialog results it received.
+fn headless_app(name: LiveId) -> (HeadlessApp, Rc<RefCell<Vec<FileDialogResultEvent >>>) {
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let mut cx = Cx::new(Box::new({
+        let results = results.clone();
+        let mut ui = WidgetRef::empty();
+        move | cx, event | {
+            if let Event::Startup = event {
+                let module_id = LiveModuleId::from_str(module_path!()).unwrap();
+                ui = WidgetRef::new_from_module(cx, module_id, name).unwrap();
+            }
+            if let Event::FileDialogResult(e) = event {
+                results.borrow_mut().push(e.clone());
+            }
+            ui.handle_event(cx, event, &mut Scope::empty());
+            if let Event::Draw(e) = event {
+                let cx = &mut Cx2d::new(cx, e);
+                while ui.draw(cx, &mut Scope::empty()).is_step() {}
+            }
+        }
+    }));
+    makepad_widgets::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.init_cx_os();
+    let mut app = HeadlessApp::new(cx);
+    // nothing here looks at pixels, and painting the dialog's text is slow
+    app.headless.skip_painting = true;
+    app.startup();
+    app.settle(10);
+    (app, results)
+}
+
+fn open_file_dialog(app: &mut HeadlessApp, kind: FileDialogKind, dialog: FileDialog) {
+    app.cx.borrow_mut().open_file_dialog(kind, dialog);
+    app.settle(10);
+}
+
+fn press(app: &mut HeadlessApp, key_code: KeyCode) {
+    let e = KeyEvent {key_code, is_repeat: false, modifiers: KeyModifiers::default(), time: app.time_now()};
+    app.key_down(e);
+    app.key_up(e);
+    app.frame();
+}
+
+fn cancelled(kind: FileDialogKind) -> FileDialogResultEvent {
+    FileDialogResultEvent {kind, path: None}
+}
+
+/// A folder holding `a.txt`, `b.txt` and `sub/c.txt`, removed again on drop.
+struct TestDir(PathBuf);
+
+impl TestDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("makepad_file_dialog_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for file in ["a.txt", "b.txt", "sub/c.txt"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        Self(dir)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn unhandled_requests_are_cancelled() {
+    for name in [live_id!(WithoutDialog), live_id!(WithoutMultiWindow)] {
+        let (mut app, results) = headless_app(name);
+        open_file_dialog(&mut app, FileDialogKind::SaveFile, FileDialog::new());
+        assert_eq!(*results.borrow(), vec![cancelled(FileDialogKind::SaveFile)], "{:?}", name);
+        assert!(app.window_id(1).is_none());
+    }
+}
+
+#[test]
+fn requests_open_one_dialog_window_at_a_time() {
+    let (mut app, results) = headless_app(live_id!(WithDialog));
+    open_file_dialog(&mut app, FileDialogKind::SelectFile, FileDialog::new());
+    assert!(results.borrow().is_empty());
+    assert!(app.window_id(1).is_some());
+    // a second request while the dialog shows is cancelled right away
+    open_file_dialog(&mut app, FileDialogKind::SaveFolder, FileDialog::new());
+    assert_eq!(*results.borrow(), vec![cancelled(FileDialogKind::SaveFolder)]);
+    assert!(app.window_id(2).is_none());
+
+    press(&mut app, KeyCode::Escape);
+    assert_eq!(*results.borrow(), vec![cancelled(FileDialogKind::SaveFolder), cancelled(FileDialogKind::SelectFile)]);
+    assert!(app.window_id(1).is_none());
+}
+
+#[test]
+fn keyboard_selects_and_opens_entries() {
+    let dir = TestDir::new("select");
+    let (mut app, results) = headless_app(live_id!(WithDialog));
+    open_file_dialog(&mut app, FileDialogKind::SelectFile, FileDialog::new().set_location(dir.0.clone()));
+
+    // folders come first, so this goes into sub and picks the only file there
+    press(&mut app, KeyCode::ArrowDown);
+    press(&mut app, KeyCode::ReturnKey);
+    press(&mut app, KeyCode::ArrowDown);
+    press(&mut app, KeyCode::ReturnKey);
+    assert_eq!(*results.borrow(), vec![FileDialogResultEvent {kind: FileDialogKind::SelectFile, path: Some(dir.0.join("sub").join("c.txt"))}]);
+    assert!(app.window_id(1).is_none());
+}
+
+#[test]
+fn keyboard_moves_up_and_around_the_list() {
+    let dir = TestDir::new("move");
+    let (mut app, results) = headless_app(live_id!(WithDialog));
+    open_file_dialog(&mut app, FileDialogKind::SelectFile, FileDialog::new().set_location(dir.0.join("sub")));
+
+    // going up selects the folder we came from, then End and Home jump to the ends
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::End);
+    press(&mut app, KeyCode::Home);
+    press(&mut app, KeyCode::ArrowDown);
+    press(&mut app, KeyCode::ArrowDown);
+    press(&mut app, KeyCode::ArrowUp);
+    press(&mut app, KeyCode::ReturnKey);
+    assert_eq!(*results.borrow(), vec![FileDialogResultEvent {kind: FileDialogKind::SelectFile, path: Some(dir.0.join("a.txt"))}]);
+}
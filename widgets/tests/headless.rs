@@ -0,0 +1,116 @@
+use makepad_widgets::*;
+
+live_design!{
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    Toggle = {{Toggle}} {
+        width: 40, height: 40
+        draw_bg: {
+            instance on: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(#f00, #0f0, self.on);
+            }
+        }
+    }
+
+    App = {{App}} {
+        ui: <Window> {
+            window: {inner_size: vec2(200, 100)}
+            show_bg: true
+            draw_bg: {color: #00f}
+            body = <View> {
+                padding: 20
+                toggle = <Toggle> {}
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct Toggle {
+    #[redraw] #[live] draw_bg: DrawQuad,
+    #[walk] walk: Walk,
+    #[rust] on: bool,
+}
+
+impl Widget for Toggle {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
+        if let Hit::FingerUp(fe) = event.hits(cx, self.draw_bg.area()) {
+            if fe.is_over {
+                self.on = !self.on;
+                self.draw_bg.redraw(cx);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_bg.apply_over(cx, live!{on: (if self.on {1.0} else {0.0})});
+        self.draw_bg.draw_walk(cx, walk);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook)]
+pub struct App {
+    #[live] ui: WidgetRef,
+}
+
+impl LiveRegister for App {
+    fn live_register(cx: &mut Cx) {
+        makepad_widgets::live_design(cx);
+    }
+}
+
+impl AppMain for App {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        self.ui.handle_event(cx, event, &mut Scope::empty());
+        if let Event::Draw(e) = event {
+            let cx = &mut Cx2d::new(cx, e);
+            while self.ui.draw(cx, &mut Scope::empty()).is_step() {}
+        }
+    }
+}
+
+fn headless_app() -> HeadlessApp {
+    let app = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let mut cx = Cx::new(Box::new(move | cx, event | {
+        if let Event::Startup = event {
+            *app.borrow_mut() = Some(App::new_main(cx));
+        }
+        <dyn AppMain>::handle_event(app.borrow_mut().as_mut().unwrap(), cx, event);
+    }));
+    live_design(&mut cx);
+    cx.init_cx_os();
+    HeadlessApp::new(cx)
+}
+
+fn assert_pixel(app: &HeadlessApp, x: usize, y: usize, color: Vec4) {
+    let pixel = app.window_framebuffer(0).unwrap().pixel(x, y).unwrap();
+    let close = | a: f32, b: f32 | (a - b).abs() < 0.01;
+    assert!(close(pixel.x, color.x) && close(pixel.y, color.y) && close(pixel.z, color.z) && close(pixel.w, color.w), "pixel ({}, {}) is {:?}, not {:?}", x, y, pixel, color);
+}
+
+#[test]
+fn click_changes_pixels() {
+    let mut app = headless_app();
+    app.startup();
+    app.settle(10);
+    let framebuffer = app.window_framebuffer(0).unwrap();
+    assert_eq!((framebuffer.width, framebuffer.height), (200, 100));
+    assert_pixel(&app, 5, 5, vec4(0.0, 0.0, 1.0, 1.0));
+    assert_pixel(&app, 40, 40, vec4(1.0, 0.0, 0.0, 1.0));
+
+    app.click(dvec2(40.0, 40.0));
+    app.settle(10);
+    assert_pixel(&app, 40, 40, vec4(0.0, 1.0, 0.0, 1.0));
+    assert_pixel(&app, 5, 5, vec4(0.0, 0.0, 1.0, 1.0));
+    // a click outside of the toggle leaves it alone
+    app.click(dvec2(150.0, 80.0));
+    app.settle(10);
+    assert_pixel(&app, 40, 40, vec4(0.0, 1.0, 0.0, 1.0));
+
+    app.click(dvec2(25.0, 55.0));
+    app.settle(10);
+    assert_pixel(&app, 40, 40, vec4(1.0, 0.0, 0.0, 1.0));
+}
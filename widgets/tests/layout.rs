@@ -0,0 +1,110 @@
+use makepad_widgets::*;
+
+live_design!{
+    DashLayout = {
+        flow: Grid{columns: [100, Fill, FillWeight(2.0)], rows: 2},
+        spacing: 10,
+        line_spacing: 5
+    }
+    WideWalk = {width: Fill, height: Fit, column_span: 2, max_height: 40}
+}
+
+fn cx() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_widgets::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+fn module_id() -> LiveModuleId {
+    LiveModuleId::from_str(&module_path!()).unwrap()
+}
+
+fn assert_rect(rect: Rect, x: f64, y: f64, w: f64, h: f64) {
+    let close = | a: f64, b: f64 | (a - b).abs() < 0.001;
+    assert!(
+        close(rect.pos.x, x) && close(rect.pos.y, y) && close(rect.size.x, w) && close(rect.size.y, h),
+        "{:?} is not ({}, {}, {}, {})", rect, x, y, w, h
+    );
+}
+
+#[test]
+fn places_walks_in_grid_cells() {
+    let mut cx = cx();
+    let layout = Layout::new_from_module(&mut cx, module_id(), live_id!(DashLayout)).unwrap();
+    let wide = Walk::new_from_module(&mut cx, module_id(), live_id!(WideWalk)).unwrap();
+    assert_eq!(wide.column_span, 2);
+
+    let draw_event = DrawEvent::default();
+    let cx = &mut Cx2d::new(&mut cx, &draw_event);
+    cx.begin_turtle(Walk::fixed(430.0, 300.0), layout);
+    // the fill columns share 430 - 100 - 2 * 10 in a 1:2 ratio, the two rows (300 - 5) / 2
+    assert_rect(cx.walk_turtle(Walk::fixed(50.0, 20.0)), 0.0, 0.0, 50.0, 20.0);
+    assert_rect(cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(30.0))), 110.0, 0.0, 310.0 / 3.0, 30.0);
+    assert_rect(cx.walk_turtle(Walk::fill()), 120.0 + 310.0 / 3.0, 0.0, 620.0 / 3.0, 147.5);
+    assert_rect(cx.walk_turtle(Walk {height: Size::Fixed(60.0), ..wide}), 0.0, 152.5, 110.0 + 310.0 / 3.0, 40.0);
+    assert_rect(cx.end_turtle(), 0.0, 0.0, 430.0, 300.0);
+
+    // without a row template the rows fit their content, a fill height waits for the row
+    let layout = Layout {flow: Flow::Grid{columns: GridTracks::fill(2), rows: GridTracks::default()}, ..Layout::default()};
+    cx.begin_turtle(Walk::size(Size::Fixed(200.0), Size::Fit), layout);
+    let mut deferred = cx.defer_walk(Walk::fill()).unwrap();
+    assert_rect(cx.walk_turtle(Walk::fixed(50.0, 30.0)), 100.0, 0.0, 50.0, 30.0);
+    let walk = deferred.resolve(cx);
+    assert_rect(cx.walk_turtle(walk), 0.0, 0.0, 100.0, 30.0);
+    assert_rect(cx.walk_turtle(Walk::fixed(20.0, 10.0)), 0.0, 30.0, 20.0, 10.0);
+    assert_rect(cx.end_turtle(), 0.0, 0.0, 200.0, 40.0);
+}
+
+#[test]
+fn shares_fill_by_weight_within_constraints() {
+    let mut cx = cx();
+    let draw_event = DrawEvent::default();
+    let cx = &mut Cx2d::new(&mut cx, &draw_event);
+    cx.begin_turtle(Walk::fixed(300.0, 100.0), Layout::flow_right());
+    let mut first = cx.defer_walk(Walk::size(Size::FillWeight(1.0), Size::Fill)).unwrap();
+    cx.walk_turtle(Walk::fixed(100.0, 10.0));
+    let mut second = cx.defer_walk(Walk::size(Size::FillWeight(3.0), Size::Fill).with_max_width(120.0)).unwrap();
+    // 150 for the second is over its max, so the first gets what is left of the 200
+    let first = first.resolve(cx);
+    let second = second.resolve(cx);
+    assert_rect(cx.walk_turtle(first), 0.0, 0.0, 80.0, 100.0);
+    assert_rect(cx.walk_turtle(second), 180.0, 0.0, 120.0, 100.0);
+    cx.end_turtle();
+
+    cx.begin_turtle(Walk::fixed(100.0, 200.0), Layout::flow_down());
+    let walk = Walk::size(Size::Fill, Size::Fit).with_min_width(150.0).with_max_height(20.0);
+    assert_rect(cx.walk_turtle(Walk {height: Size::Fixed(50.0), ..walk}), 0.0, 0.0, 150.0, 20.0);
+    cx.end_turtle();
+}
+
+#[test]
+fn mirrors_right_to_left() {
+    let mut cx = cx();
+    let draw_list = DrawList2d::new(&mut cx);
+    let draw_event = DrawEvent::default();
+    let cx = &mut Cx2d::new(&mut cx, &draw_event);
+    let redraw_id = cx.redraw_id;
+    cx.draw_lists[draw_list.draw_list_id()].redraw_id = redraw_id;
+    cx.draw_list_stack.push(draw_list.draw_list_id());
+
+    // padding and margin on the left are on the start side, which is now the right
+    let layout = Layout {direction: LayoutDirection::Rtl, padding: Padding {left: 10.0, ..Padding::default()}, spacing: 5.0, ..Layout::flow_right()};
+    let (mut first, mut second, mut down) = (Area::Empty, Area::Empty, Area::Empty);
+    cx.begin_turtle(Walk::fixed(200.0, 100.0), layout);
+    cx.walk_turtle_with_area(&mut first, Walk::fixed(50.0, 20.0).with_margin_left(4.0));
+    cx.walk_turtle_with_area(&mut second, Walk::fixed(40.0, 20.0));
+    cx.end_turtle();
+    assert_rect(first.rect(cx), 136.0, 0.0, 50.0, 20.0);
+    assert_rect(second.rect(cx), 91.0, 0.0, 40.0, 20.0);
+
+    // align.x aligns to the end, now the left, and a nested ltr layout stays as it is
+    cx.set_rtl(true);
+    cx.begin_turtle(Walk::fixed(200.0, 100.0), Layout {align: Align {x: 1.0, y: 0.0}, ..Layout::flow_down()});
+    cx.begin_turtle(Walk::fixed(100.0, 20.0), Layout {direction: LayoutDirection::Ltr, ..Layout::flow_right()});
+    cx.walk_turtle_with_area(&mut down, Walk::fixed(30.0, 20.0));
+    cx.end_turtle();
+    cx.end_turtle();
+    assert_rect(down.rect(cx), 0.0, 0.0, 30.0, 20.0);
+}
@@ -0,0 +1,74 @@
+use {
+    std::rc::Rc,
+    makepad_widgets::*,
+    makepad_draw::font_atlas::{CxFont, CxFontsAtlasRc, ShapeCache, ShapedGlyph, Direction, UnicodeBuffer},
+    makepad_draw::shader::draw_text::TextStyle,
+};
+
+live_design!{
+    ChatTextStyle = {
+        font: {path: dep("crate://self/resources/IBMPlexSans-Text.ttf")}
+        font_fallbacks: [
+            {path: dep("crate://self/resources/LiberationMono-Regular.ttf")},
+            {path: dep("crate://self/resources/NotoSans-Italic.ttf")}
+        ]
+    }
+}
+
+fn load_font(name: &str) -> CxFont {
+    let path = format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name);
+    CxFont::load_from_ttf_bytes(Rc::new(std::fs::read(path).unwrap())).unwrap()
+}
+
+#[test]
+fn reads_font_fallbacks_in_order() {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_widgets::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.live_expand();
+    let module_id = LiveModuleId::from_str(&module_path!()).unwrap();
+    let style = TextStyle::new_from_module(&mut cx, module_id, live_id!(ChatTextStyle)).unwrap();
+    let font_ids = style.font_ids();
+    assert_eq!(font_ids.len(), 3);
+    assert_eq!(font_ids[0], style.font.font_id.unwrap());
+    assert_eq!(font_ids[1], style.font_fallbacks[0].font_id.unwrap());
+    assert_eq!(font_ids[2], style.font_fallbacks[1].font_id.unwrap());
+}
+
+#[test]
+fn shapes_missing_glyphs_with_fallback_fonts() {
+    let fonts = vec![Some(load_font("IBMPlexSans-Text.ttf")), Some(load_font("LiberationMono-Regular.ttf"))];
+    let glyph = | font_id: usize, c: char | fonts[font_id].as_ref().unwrap().owned_font_face.with_ref( | face | face.glyph_index(c).unwrap().0 as usize);
+    let expected = [
+        ShapedGlyph {font_id: 0, glyph_id: glyph(0, 'a')},
+        ShapedGlyph {font_id: 1, glyph_id: glyph(1, '─')},
+        ShapedGlyph {font_id: 1, glyph_id: glyph(1, '♪')},
+        ShapedGlyph {font_id: 0, glyph_id: glyph(0, 'b')},
+        // neither font has it, so it stays the tofu of the first
+        ShapedGlyph {font_id: 0, glyph_id: 0},
+    ];
+
+    let mut cache = ShapeCache::new();
+    let (glyphs, buffer) = cache.get_or_compute_glyph_ids((Direction::LeftToRight, &[0, 1], "a─♪bق"), UnicodeBuffer::new(), &fonts);
+    assert_eq!(glyphs, &expected);
+    // a chain without the fallback is shaped on its own
+    let (glyphs, _) = cache.get_or_compute_glyph_ids((Direction::LeftToRight, &[0], "a─♪bق"), buffer, &fonts);
+    assert!(glyphs.iter().all( | g | g.font_id == 0));
+    assert_eq!(glyphs[1].glyph_id, 0);
+}
+
+#[test]
+fn measures_fallback_glyphs_in_units_of_the_first_font() {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    Cx2d::lazy_construct_font_atlas(&mut cx);
+    let atlas = cx.get_global::<CxFontsAtlasRc>().clone();
+    let mut atlas = atlas.0.borrow_mut();
+    atlas.fonts.push(Some(load_font("IBMPlexSans-Text.ttf")));
+    atlas.fonts.push(Some(load_font("LiberationMono-Regular.ttf")));
+
+    // liberation mono has 2048 units per em and plex 1000
+    let mono = atlas.get_advance(&[1], '─').unwrap();
+    assert_eq!(atlas.get_advance(&[0, 1], '─').unwrap(), mono * 1000.0 / 2048.0);
+    assert_eq!(atlas.get_advance(&[0, 1], 'a'), atlas.get_advance(&[0], 'a'));
+    assert_eq!(atlas.get_advance(&[0], '─'), None);
+}
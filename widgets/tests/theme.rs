@@ -0,0 +1,46 @@
+use makepad_widgets::*;
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    Swatch = <DrawColor> {color: (THEME_COLOR_W)}
+    AppBg = <DrawColor> {color: (THEME_COLOR_BG_APP)}
+}
+
+fn cx() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_widgets::live_design(&mut cx);
+    crate::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+fn color(cx: &mut Cx, id: LiveId) -> Vec4 {
+    DrawColor::new_from_module(cx, LiveModuleId::from_str(&module_path!()).unwrap(), id).unwrap().color
+}
+
+#[test]
+fn swaps_theme_tokens() {
+    let mut cx = cx();
+    let dark_bg = color(&mut cx, live_id!(AppBg));
+    assert_eq!(color(&mut cx, live_id!(Swatch)), vec4(1.0, 1.0, 1.0, 1.0));
+
+    cx.set_theme(ThemeSet::Light);
+    assert!(cx.handle_live_edit());
+    assert_eq!(color(&mut cx, live_id!(Swatch)), vec4(0.0, 0.0, 0.0, 1.0));
+    let light_bg = color(&mut cx, live_id!(AppBg));
+    assert!(light_bg.x > 0.5 && dark_bg.x < 0.5);
+
+    let mut tokens = ThemeSet::Light.tokens();
+    tokens.push((live_id!(THEME_COLOR_BG_APP), LiveValue::Color(0x336699FF)));
+    cx.set_theme_tokens(tokens);
+    assert!(cx.handle_live_edit());
+    assert_eq!(color(&mut cx, live_id!(AppBg)), Vec4::from_u32(0x336699FF));
+
+    cx.set_theme(ThemeSet::Dark);
+    assert!(cx.handle_live_edit());
+    assert_eq!(color(&mut cx, live_id!(Swatch)), vec4(1.0, 1.0, 1.0, 1.0));
+    assert_eq!(color(&mut cx, live_id!(AppBg)), dark_bg);
+    assert!(!cx.handle_live_edit());
+}